@@ -9,10 +9,11 @@ use client_util::rate_limiter::RateLimiter;
 use common::angle::Angle;
 use common::contact::{Contact, ContactTrait};
 use common::entity::{Armament, EntityData, EntityId, EntityKind, EntitySubKind, EntityType};
+use common::transform::Transform;
 use common_util::range::gen_radius;
-use glam::Vec2;
+use glam::{Vec2, Vec3, Vec4};
 use rand::{thread_rng, Rng};
-use renderer2d::Particle;
+use renderer2d::{GraphicLayer, Particle};
 use std::collections::HashMap;
 
 impl Mk48Game {
@@ -73,10 +74,11 @@ impl Mk48Game {
                     || armament_entity_data.sub_kind == EntitySubKind::Depositor
                     || armament_entity_data.sub_kind == EntitySubKind::DepthCharge
                     || armament_entity_data.sub_kind == EntitySubKind::Mine
+                    || armament_entity_data.sub_kind == EntitySubKind::Net
                 {
                     // Vertically-launched armaments can fire in any horizontal direction.
                     // Aircraft can quickly assume any direction.
-                    // Depositors, depth charges, and mines are not constrained by direction.
+                    // Depositors, depth charges, mines, and nets are not constrained by direction.
                     angle_diff = Angle::ZERO;
                 }
 
@@ -166,7 +168,7 @@ impl Mk48Game {
             let normalized = vector / distance;
             let offset = 5.0 + data.width * 0.4 + rng.gen::<f32>() * 10.0;
             for i in 0..3 {
-                airborne_particles.add(Mk48Particle {
+                airborne_particles.add_budgeted(Mk48Particle {
                     position: aa_gun + normalized * (offset + i as f32),
                     velocity: normalized * (distance.max(30.0) * (1.0 / time_of_flight))
                         + gen_radius(&mut rng, 1.0),
@@ -181,6 +183,28 @@ impl Mk48Game {
 
         volume
     }
+
+    /// Draws a torpedo net as a line of buoys (nets don't have sprites).
+    pub fn draw_net(
+        transform: &Transform,
+        data: &EntityData,
+        color: Vec3,
+        alpha: f32,
+        graphics: &mut GraphicLayer,
+    ) {
+        const BUOY_SPACING: f32 = 5.0;
+
+        let half_length = transform.direction.to_vec() * (data.length * 0.5);
+        let start = transform.position - half_length;
+        let end = transform.position + half_length;
+        graphics.draw_line(start, end, 0.3, Vec4::new(0.2, 0.2, 0.2, 0.8 * alpha));
+
+        let buoys = (data.length / BUOY_SPACING) as usize;
+        for i in 0..=buoys {
+            let position = start.lerp(end, i as f32 / buoys.max(1) as f32);
+            graphics.draw_filled_circle(position, 0.7, color.extend(alpha));
+        }
+    }
 }
 
 /// This is useful for avoiding firing the same weapon twice, which reduces fire rate in a high
@@ -232,9 +256,17 @@ pub struct Group {
     pub ready: u8,
 }
 
-pub fn group_armaments(armaments: &[Armament], armament_consumption: &[bool]) -> Vec<Group> {
+/// Groups armaments of the same type, omitting those outside the `loadout`.
+pub fn group_armaments(
+    armaments: &[Armament],
+    armament_consumption: &[bool],
+    loadout: u8,
+) -> Vec<Group> {
     let mut groups = Vec::<Group>::with_capacity(armaments.len().min(5));
     for (i, armament) in armaments.iter().enumerate() {
+        if !armament.in_loadout(loadout) {
+            continue;
+        }
         let ready = armament_consumption.get(i).cloned().unwrap_or(true) as u8;
         if let Some(group) = groups
             .iter_mut()
@@ -253,18 +285,23 @@ pub fn group_armaments(armaments: &[Armament], armament_consumption: &[bool]) ->
     groups
 }
 
-pub fn update(entity_type: Option<EntityType>, armament: &mut Option<EntityType>) {
+pub fn update(entity_type: Option<EntityType>, loadout: u8, armament: &mut Option<EntityType>) {
     if let Some(entity_type) = entity_type {
-        let armaments = &entity_type.data().armaments;
-        if !armaments.iter().any(|a| Some(a.entity_type) == *armament) {
+        let armaments = || {
+            entity_type
+                .data()
+                .armaments
+                .iter()
+                .filter(|a| a.in_loadout(loadout))
+        };
+        if !armaments().any(|a| Some(a.entity_type) == *armament) {
             let best = (*armament)
                 .and_then(|selection| {
-                    armaments
-                        .iter()
+                    armaments()
                         .find(|&a| a.entity_type.data().sub_kind == selection.data().sub_kind)
                 })
                 .map(|a| a.entity_type)
-                .or_else(|| armaments.get(0).map(|a| a.entity_type));
+                .or_else(|| armaments().next().map(|a| a.entity_type));
             *armament = best;
         }
     } else {
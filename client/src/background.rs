@@ -10,6 +10,7 @@ use common::entity::{EntityId, EntityType};
 use common::terrain::{Coord, RelativeCoord, Terrain};
 use common::transform::Transform;
 use common::velocity::Velocity;
+use common::world::{Biome, Season};
 use common::{terrain, world};
 use common_util::angle::{Angle, AngleRepr};
 use glam::{uvec2, vec2, vec3, Mat3, Mat4, Quat, UVec2, Vec2, Vec3};
@@ -89,6 +90,8 @@ pub struct Mk48BackgroundLayer {
     last_terrain: Vec<u8>,
     last_vegetation: Vec<SortableSprite>,
     invalidation: Option<Invalidation>,
+    season: Season,
+    biome: Biome,
     shadow_setting: ShadowSetting,
 }
 
@@ -101,13 +104,17 @@ impl Mk48BackgroundLayer {
     ) -> Self {
         let inner = BackgroundLayer::new(renderer);
 
-        let mut defines = format!("#define ARCTIC {:.1}\n", world::ARCTIC);
+        let mut defines = format!(
+            "#define ARCTIC {:.1}\n#define TROPICS {:.1}\n",
+            world::ARCTIC,
+            world::TROPICS
+        );
         if animations {
             defines += "#define ANIMATIONS\n";
         }
         if dynamic_waves {
             // renderer.enable_oes_standard_derivatives();
-            defines += "#define WAVES 6\n";
+            defines += &format!("#define WAVES {}\n", Weather::GERSTNER_WAVES);
         }
         defines += shadow_setting.shader_define();
         let frag = include_str!("./shaders/background.frag").replace("#defines", &defines);
@@ -141,6 +148,8 @@ impl Mk48BackgroundLayer {
             last_terrain: vec![],
             last_vegetation: vec![],
             last_view: TerrainView::default(),
+            season: Season::default(),
+            biome: Biome::default(),
             shader,
             shadow_setting,
             shadow_shader,
@@ -156,6 +165,8 @@ impl Mk48BackgroundLayer {
         zoom: f32,
         terrain: &mut Terrain,
         terrain_reset: bool,
+        season: Season,
+        biome: Biome,
         has_shadows: bool,
         renderer: &Renderer,
     ) -> impl Iterator<Item = SortableSprite> + '_ {
@@ -195,10 +206,15 @@ impl Mk48BackgroundLayer {
         let detail_just_loaded = self.detail_load != detail_dim;
         self.detail_load = detail_dim;
 
+        let season_changed = season != self.season || biome != self.biome;
+        self.season = season;
+        self.biome = biome;
+
         // Only create invalidations if frame cache is enabled and they'll be used.
         if self.cache_frame {
-            // Invalidate bg when terrain is reset (aka switch servers) or when detail texture loads.
-            if terrain_reset || detail_just_loaded {
+            // Invalidate bg when terrain is reset (aka switch servers), when detail texture loads, or
+            // when the season or biome changes.
+            if terrain_reset || detail_just_loaded || season_changed {
                 self.invalidation = Some(Invalidation::All);
             } else if !terrain.updated.is_empty() {
                 let updated = terrain.updated.clone();
@@ -333,6 +349,13 @@ impl RenderLayer<&ShadowResult<&Mk48Params>> for Mk48BackgroundLayer {
             }
             shader.uniform("uSun", weather.sun);
             shader.uniform("uWaterSun", weather.water_sun());
+            shader.uniform("uWaves", &weather.gerstner_waves());
+
+            let (tint, snow) = season_tint(self.season);
+            let (sand, biome_snow) = biome_tint(self.biome);
+            shader.uniform("uTint", tint);
+            shader.uniform("uSand", sand);
+            shader.uniform("uSnow", snow.max(biome_snow));
 
             shader.uniform("uHeight", &self.height_texture);
             shader.uniform("uDetail", &self.detail_texture);
@@ -537,6 +560,25 @@ fn hash_coord(x: isize, y: isize) -> u32 {
     hash(x as u16 as u32 + ((y as u16 as u32) << 16))
 }
 
+/// Returns the color tint and temperate snow cover (0 to 1) of a season.
+fn season_tint(season: Season) -> (Vec3, f32) {
+    match season {
+        Season::Normal => (Vec3::ONE, 0.0),
+        Season::Winter => (vec3(0.92, 0.97, 1.08), 0.6),
+        Season::Night => (vec3(0.35, 0.4, 0.6), 0.0),
+    }
+}
+
+/// Returns the sand color tint and temperate snow cover (0 to 1) of a biome.
+fn biome_tint(biome: Biome) -> (Vec3, f32) {
+    match biome {
+        Biome::Temperate | Biome::OpenOcean => (Vec3::ONE, 0.0),
+        Biome::Archipelago => (vec3(1.08, 1.05, 0.95), 0.0),
+        Biome::Fjord => (vec3(0.75, 0.75, 0.78), 0.25),
+        Biome::IceShelf => (vec3(0.9, 0.95, 1.0), 0.7),
+    }
+}
+
 // Hashes a u32 to another u32.
 // Based on wyhash: https://docs.rs/wyhash/latest/wyhash/
 fn hash(mut s: u32) -> u32 {
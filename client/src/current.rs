@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::altitude::Altitude;
+use common::current::{Currents, MAX_SPEED};
+use common::terrain::Terrain;
+use glam::{vec2, vec4, Vec2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use renderer2d::GraphicLayer;
+use std::f32::consts::PI;
+
+/// Draws faint streaks on the water that flow with the ocean currents, so players can see (and
+/// take advantage of) them.
+pub fn draw_currents(
+    currents: &Currents,
+    terrain: &Terrain,
+    camera: Vec2,
+    zoom: f32,
+    time: f32,
+    graphics: &mut GraphicLayer,
+) {
+    // Roughly constant number of streaks on screen. Power of two so streaks stay put while zooming.
+    let spacing = (zoom * 0.08).log2().ceil().exp2();
+    let thickness = 0.002 * zoom;
+
+    let min = ((camera - zoom) / spacing).floor().as_ivec2();
+    let max = ((camera + zoom) / spacing).ceil().as_ivec2();
+
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            // Consistently jitter each streak so they don't form a visible grid.
+            let mut rng = StdRng::seed_from_u64(((x as u32 as u64) << 32) | y as u32 as u64);
+            let origin = vec2(x as f32 + rng.gen::<f32>(), y as f32 + rng.gen::<f32>()) * spacing;
+
+            let current = currents.at(origin);
+            let strength = current.length() * (1.0 / MAX_SPEED);
+            if strength < 0.05 {
+                continue;
+            }
+
+            // Streaks travel downstream, fading in and out, then start over.
+            let phase = (time * 0.2 + rng.gen::<f32>()).fract();
+            let direction = current * (1.0 / (strength * MAX_SPEED));
+            let start = origin + direction * (phase * spacing * strength);
+            let end = start + direction * (spacing * 0.5 * strength);
+
+            if terrain
+                .sample(start)
+                .map_or(true, |altitude| altitude >= Altitude::ZERO)
+            {
+                continue;
+            }
+
+            let alpha = (phase * PI).sin() * strength.min(1.0) * 0.3;
+            graphics.draw_line(start, end, thickness, vec4(1.0, 1.0, 1.0, alpha));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use client_util::keyboard::{Key, KeyboardEvent};
+use client_util::mouse::{MouseButton, MouseState};
+use client_util::rate_limiter::RateLimiter;
+use common::altitude::Altitude;
+use common::angle::Angle;
+use common::entity::{EntityKind, EntityType};
+use common::region::{Region, RegionPixel, RegionStructure};
+use common::terrain::{Coord, Terrain, TerrainMutation, SCALE, SIZE};
+use glam::{vec4, Vec2};
+use renderer2d::GraphicLayer;
+use std::collections::HashSet;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAnchorElement;
+
+/// What dragging or clicking the mouse does in the map editor.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Tool {
+    Raise,
+    Lower,
+    /// Flattens to the altitude where the stroke started.
+    Flatten,
+    Structure,
+}
+
+/// Developer tool for painting terrain and placing structures locally, to be exported as a
+/// [`Region`] overlay file that the server stamps onto the procedural world.
+///
+/// Page Up toggles the editor. While enabled, 1-4 select a tool, -/+ resize the brush, Tab cycles
+/// structures, Left/Right rotate them, Backspace removes the one under the mouse, and Enter
+/// downloads the overlay file.
+pub struct MapEditor {
+    pub enabled: bool,
+    tool: Tool,
+    brush_radius: f32,
+    /// Target of the current [`Tool::Flatten`] stroke, if any.
+    flatten: Option<Altitude>,
+    /// Next structure to be placed.
+    structure: RegionStructure,
+    structures: Vec<RegionStructure>,
+    /// Terrain pixels that were painted, which will be exported.
+    painted: HashSet<Coord>,
+    /// World position of the mouse, as of the last tick.
+    mouse_position: Option<Vec2>,
+    paint_rate_limiter: RateLimiter,
+}
+
+impl Default for MapEditor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tool: Tool::Raise,
+            brush_radius: 100.0,
+            flatten: None,
+            structure: RegionStructure {
+                entity_type: EntityType::OilPlatform,
+                position: Vec2::ZERO,
+                direction: Angle::ZERO,
+            },
+            structures: Vec::new(),
+            painted: HashSet::new(),
+            mouse_position: None,
+            paint_rate_limiter: RateLimiter::new(0.1),
+        }
+    }
+}
+
+impl MapEditor {
+    const BRUSH_RADII: std::ops::RangeInclusive<f32> = SCALE..=1000.0;
+
+    /// Call with every keyboard event.
+    pub fn peek_keyboard(&mut self, event: &KeyboardEvent, terrain: &Terrain) {
+        if !event.down {
+            return;
+        }
+        if event.key == Key::PageUp {
+            self.enabled = !self.enabled;
+            return;
+        }
+        if !self.enabled {
+            return;
+        }
+
+        match event.key {
+            Key::One => self.tool = Tool::Raise,
+            Key::Two => self.tool = Tool::Lower,
+            Key::Three => self.tool = Tool::Flatten,
+            Key::Four => self.tool = Tool::Structure,
+            Key::MinusUnderscore => {
+                self.brush_radius = (self.brush_radius * 0.8)
+                    .clamp(*Self::BRUSH_RADII.start(), *Self::BRUSH_RADII.end())
+            }
+            Key::EqualsPlus => {
+                self.brush_radius = (self.brush_radius * 1.25)
+                    .clamp(*Self::BRUSH_RADII.start(), *Self::BRUSH_RADII.end())
+            }
+            Key::Tab => {
+                let structures: Vec<_> = EntityType::iter()
+                    .filter(|t| t.data().kind == EntityKind::Obstacle)
+                    .collect();
+                let index = structures
+                    .iter()
+                    .position(|&t| t == self.structure.entity_type)
+                    .map_or(0, |i| (i + 1) % structures.len());
+                self.structure.entity_type = structures[index];
+            }
+            Key::Left => self.structure.direction += Angle::from_degrees(15.0),
+            Key::Right => self.structure.direction -= Angle::from_degrees(15.0),
+            Key::Backspace => {
+                if let Some(position) = self.mouse_position {
+                    let brush_radius = self.brush_radius;
+                    self.structures.retain(|structure| {
+                        structure.position.distance_squared(position) > brush_radius.powi(2)
+                    });
+                }
+            }
+            Key::Enter => self.export(terrain),
+            _ => {}
+        }
+    }
+
+    /// Paints terrain or places structures with the mouse, and draws the brush and structures.
+    pub fn tick(
+        &mut self,
+        elapsed_seconds: f32,
+        mouse: &mut MouseState,
+        mouse_position: Option<Vec2>,
+        terrain: &mut Terrain,
+        zoom: f32,
+        graphics: &mut GraphicLayer,
+    ) {
+        self.mouse_position = mouse_position;
+        if !self.enabled {
+            return;
+        }
+
+        let thickness = 0.004 * zoom;
+        for structure in &self.structures {
+            Self::draw_structure(structure, thickness, 0.8, graphics);
+        }
+
+        let position = if let Some(position) = mouse_position {
+            position
+        } else {
+            return;
+        };
+
+        if self.tool == Tool::Structure {
+            self.structure.position = position;
+            Self::draw_structure(&self.structure, thickness, 0.4, graphics);
+            if mouse.take_click(MouseButton::Left) {
+                self.structures.push(self.structure);
+            }
+            return;
+        }
+
+        graphics.draw_circle(
+            position,
+            self.brush_radius,
+            thickness,
+            vec4(1.0, 1.0, 1.0, 0.6),
+        );
+
+        if !mouse.is_down(MouseButton::Left) {
+            self.flatten = None;
+            return;
+        }
+        if !self.paint_rate_limiter.update_ready(elapsed_seconds) {
+            return;
+        }
+
+        let flatten = *self
+            .flatten
+            .get_or_insert_with(|| terrain.sample(position).unwrap_or(Altitude::ZERO));
+
+        let center = if let Some(center) = Coord::from_position(position) {
+            center
+        } else {
+            return;
+        };
+        let r = (self.brush_radius / SCALE).ceil() as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (x, y) = (center.0 as isize + dx, center.1 as isize + dy);
+                if !(0..SIZE as isize).contains(&x) || !(0..SIZE as isize).contains(&y) {
+                    continue;
+                }
+                let coord = Coord(x as usize, y as usize);
+                let pixel_position = coord.corner();
+                if pixel_position.distance_squared(position) > self.brush_radius.powi(2) {
+                    continue;
+                }
+
+                // Roughly one altitude level per application.
+                let mutation = match self.tool {
+                    Tool::Raise => TerrainMutation::simple(pixel_position, 8.0),
+                    Tool::Lower => TerrainMutation::simple(pixel_position, -8.0),
+                    Tool::Flatten => {
+                        TerrainMutation::clamped(pixel_position, 0.0, flatten..=flatten)
+                    }
+                    Tool::Structure => unreachable!(),
+                };
+                if terrain.modify(mutation).is_some() {
+                    self.painted.insert(coord);
+                }
+            }
+        }
+    }
+
+    fn draw_structure(
+        structure: &RegionStructure,
+        thickness: f32,
+        alpha: f32,
+        graphics: &mut GraphicLayer,
+    ) {
+        let radius = structure.entity_type.data().radius;
+        let color = vec4(1.0, 0.85, 0.2, alpha);
+        graphics.draw_circle(structure.position, radius, thickness, color);
+        graphics.draw_line(
+            structure.position,
+            structure.position + structure.direction.to_vec() * radius,
+            thickness,
+            color,
+        );
+    }
+
+    /// Downloads everything painted and placed so far as a region overlay file.
+    fn export(&self, terrain: &Terrain) {
+        let mut pixels: Vec<_> = self
+            .painted
+            .iter()
+            .map(|&coord| RegionPixel {
+                x: coord.0 as u16,
+                y: coord.1 as u16,
+                value: terrain.at(coord),
+            })
+            .collect();
+        pixels.sort_unstable_by_key(|pixel| (pixel.y, pixel.x));
+
+        let region = Region {
+            terrain: pixels,
+            structures: self.structures.clone(),
+        };
+        let href = format!(
+            "data:application/json;charset=utf-8,{}",
+            String::from(js_sys::encode_uri_component(&region.to_json()))
+        );
+
+        match js_hooks::document().create_element("a") {
+            Ok(element) => {
+                let anchor: HtmlAnchorElement = element.unchecked_into();
+                anchor.set_href(&href);
+                anchor.set_download("region.json");
+                anchor.click();
+            }
+            Err(e) => js_hooks::console_log!("could not export region: {:?}", e),
+        }
+    }
+}
@@ -5,18 +5,31 @@ use crate::armament::{group_armaments, FireRateLimiter, Group};
 use crate::audio::Audio;
 use crate::background::{Mk48BackgroundLayer, Mk48OverlayLayer};
 use crate::camera::Mk48Camera;
+use crate::current::draw_currents;
+#[cfg(feature = "editor")]
+use crate::editor::MapEditor;
 use crate::interpolated::Interpolated;
 use crate::interpolated_contact::InterpolatedContact;
-use crate::particle::{Mk48Particle, Mk48ParticleLayer};
+use crate::key_bindings::Action;
+use crate::network_stats::NetworkStats;
+use crate::particle::{Mk48Particle, Mk48ParticleLayer, ParticleBudget};
+use crate::ping::{draw_pings, SonarPing};
 use crate::settings::{Mk48Settings, ShadowSetting};
+use crate::ship::ShipLayer;
+use crate::shipwreck::draw_shipwrecks;
+use crate::smuggling::draw_smuggling;
 use crate::sortable_sprite::SortableSprite;
 use crate::sprite::SpriteLayer;
 use crate::state::Mk48State;
 use crate::trail::TrailLayer;
 use crate::ui::{
-    InstructionStatus, UiEvent, UiProps, UiState, UiStatus, UiStatusPlaying, UiStatusRespawning,
+    InstructionStatus, KillFeedItem, UiEvent, UiProps, UiState, UiStatus, UiStatusPlaying,
+    UiStatusRespawning,
 };
 use crate::weather::Weather;
+use crate::wildlife::{generate_wildlife, WildlifeLayer};
+use crate::wreckage::draw_wreckage;
+use crate::zone::draw_zones;
 use client_util::context::Context;
 use client_util::fps_monitor::FpsMonitor;
 use client_util::game_client::GameClient;
@@ -29,21 +42,26 @@ use common::angle::Angle;
 use common::contact::{Contact, ContactTrait};
 use common::entity::{EntityData, EntityId, EntityKind, EntitySubKind, EntityType};
 use common::guidance::Guidance;
-use common::protocol::{Command, Control, Fire, Hint, Pay, Spawn, Update, Upgrade};
+use common::interest::Interest;
+use common::protocol::{
+    Ack, Command, Control, ControlAircraft, ControlDelta, Doctrine, Fire, Hint, Pay, Purchase,
+    Spawn, Update, Upgrade, ZoneHolder,
+};
 use common::ticks::Ticks;
 use common::transform::Transform;
 use common::velocity::Velocity;
 use common::world::strict_area_border;
 use common_util::range::{gen_radius, lerp, map_ranges};
 use core_protocol::id::{GameId, TeamId};
-use glam::{Mat2, UVec2, Vec2, Vec3, Vec4Swizzles};
+use core_protocol::rpc::ClientUpdate;
+use glam::{vec2, vec4, Mat2, UVec2, Vec2, Vec3, Vec4Swizzles};
 use rand::{thread_rng, Rng};
 use renderer::{gray_a, rgb_array, rgba, DefaultRender, Layer, RenderChain};
 use renderer2d::{Camera2d, GraphicLayer, TextLayer};
 use renderer3d::ShadowLayer;
 use renderer3d::{ShadowParams, ShadowResult};
-use std::collections::HashMap;
-use std::f32::consts::PI;
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::{PI, TAU};
 
 pub struct Mk48Game {
     /// Mk48 specific camera.
@@ -66,8 +84,16 @@ pub struct Mk48Game {
     pub interpolated_altitude: Interpolated,
     /// Last control, for diffing.
     pub last_control: Option<Control>,
+    /// Controls sent as deltas since the last full control.
+    controls_since_full: u8,
     /// Rate limit control websocket messages.
     pub control_rate_limiter: RateLimiter,
+    /// Which updates were received, so the server can resend contacts from any that were lost.
+    ack: Ack,
+    /// Last ack sent, to avoid sending the same one again.
+    last_ack: Ack,
+    /// Rate limit ack websocket messages.
+    ack_rate_limiter: RateLimiter,
     /// Rate limit ui props messages.
     pub ui_props_rate_limiter: RateLimiter,
     /// Playing the alarm fast sound too often is annoying.
@@ -81,6 +107,20 @@ pub struct Mk48Game {
     pub fire_rate_limiter: FireRateLimiter,
     /// FPS counter
     pub fps_counter: FpsMonitor,
+    /// Reduces particles when FPS is low.
+    particle_budget: ParticleBudget,
+    /// Timing of game updates, for the network statistics overlay and automatic interpolation delay.
+    pub network_stats: NetworkStats,
+    /// Recent sinkings, oldest first.
+    kill_feed: VecDeque<KillFeedItem>,
+    /// For generating unique [`KillFeedItem::key`]s.
+    kill_feed_counter: u32,
+    /// Whether our most recent sinking was mutual, for the respawn screen.
+    mutual_death: bool,
+    /// Active sonar pings heard recently, oldest first.
+    pings: Vec<SonarPing>,
+    #[cfg(feature = "editor")]
+    editor: MapEditor,
     ui_state: UiState,
 }
 
@@ -93,9 +133,19 @@ type FullLayer = ShadowLayer<Mk48Layer>;
 pub struct Mk48Layer {
     #[render(&ShadowParams)]
     background: Mk48BackgroundLayer,
+    /// Streaks that flow with ocean currents.
+    currents: GraphicLayer,
+    sea_level_wildlife: WildlifeLayer<false>,
     pub sea_level_particles: Mk48ParticleLayer<false>,
+    /// Wrecks and debris left by sunken boats.
+    wreckage: GraphicLayer,
+    /// Torpedo nets, which float below boats.
+    nets: GraphicLayer,
+    #[render(&ShadowParams)]
+    ships: ShipLayer,
     // TODO sprite shadows. #[render(&ShadowParams)]
     sprites: SpriteLayer,
+    airborne_wildlife: WildlifeLayer<true>,
     pub airborne_particles: Mk48ParticleLayer<true>,
     trails: TrailLayer,
     overlay: Mk48OverlayLayer,
@@ -117,10 +167,11 @@ impl std::ops::Deref for Mk48Params {
 
 /// Back 75 degrees is reverse angle.
 const REVERSE_ANGLE: f32 = PI * 3.0 / 8.0;
-pub const SURFACE_KEY: Key = Key::R;
-pub const ACTIVE_KEY: Key = Key::Z;
 
 impl Mk48Game {
+    /// Send a full control this often, in case the server lost track of the previous one.
+    const CONTROLS_PER_FULL: u8 = 50;
+
     // Don't reverse early on, when the player doesn't have a great idea of their orientation.
     fn can_reverse(first_control: bool, player_contact: &Contact) -> bool {
         Self::has_reverse(player_contact)
@@ -129,7 +180,8 @@ impl Mk48Game {
 
     // Level 1 ships can't reverse with mouse controls.
     fn has_reverse(player_contact: &Contact) -> bool {
-        player_contact.entity_type().unwrap().data().level > 1
+        let data = player_contact.data();
+        data.kind == EntityKind::Boat && data.level > 1
     }
 
     // Right button down or left button down and time has passed.
@@ -140,8 +192,12 @@ impl Mk48Game {
     fn create_render_chain(context: &Context<Self>) -> Result<RenderChain<FullLayer>, String> {
         let shadows = context.settings.shadows;
 
-        RenderChain::new([0, 53, 116, 255], context.common_settings.antialias, |r| {
+        let antialiasing = context.settings.antialias.antialiasing();
+
+        RenderChain::new([0, 53, 116, 255], antialiasing, |r| {
             r.enable_cull_face(); // Required for shadows.
+            r.enable_angle_instanced_arrays(); // Required for wildlife.
+            let sprites = SpriteLayer::new(r, shadows);
             ShadowLayer::with_viewport(
                 r,
                 Mk48Layer {
@@ -152,8 +208,14 @@ impl Mk48Game {
                         context.settings.dynamic_waves,
                         shadows,
                     ),
+                    currents: GraphicLayer::new(r),
+                    sea_level_wildlife: WildlifeLayer::new(r),
                     sea_level_particles: Mk48ParticleLayer::new(r, shadows),
-                    sprites: SpriteLayer::new(r, shadows),
+                    wreckage: GraphicLayer::new(r),
+                    nets: GraphicLayer::new(r),
+                    ships: ShipLayer::new(r, shadows, &sprites),
+                    sprites,
+                    airborne_wildlife: WildlifeLayer::new(r),
                     airborne_particles: Mk48ParticleLayer::new(r, shadows),
                     trails: TrailLayer::new(r),
                     overlay: Mk48OverlayLayer::new(r),
@@ -173,6 +235,12 @@ impl Mk48Game {
 impl GameClient for Mk48Game {
     const GAME_ID: GameId = GameId::Mk48;
     const LICENSES: &'static [(&'static str, &'static [&'static str])] = crate::licenses::LICENSES;
+    const PRIORITY_AUDIO: &'static [Audio] = &[
+        Audio::AlarmFast,
+        Audio::AlarmSlow,
+        Audio::Collect,
+        Audio::Upgrade,
+    ];
 
     type Audio = Audio;
     type GameRequest = Command;
@@ -200,25 +268,89 @@ impl GameClient for Mk48Game {
             interpolated_altitude: Interpolated::new(0.2),
             respawn_overridden: false,
             last_control: None,
+            controls_since_full: 0,
             control_rate_limiter: RateLimiter::new(0.1),
+            ack: Ack::default(),
+            last_ack: Ack::default(),
+            ack_rate_limiter: RateLimiter::new(0.2),
             ui_props_rate_limiter,
             alarm_fast_rate_limiter: RateLimiter::new(10.0),
             peek_update_sound_counter: 0,
             fire_rate_limiter: FireRateLimiter::new(),
             fps_counter: FpsMonitor::new(1.0),
+            particle_budget: ParticleBudget::default(),
+            network_stats: NetworkStats::default(),
+            kill_feed: VecDeque::new(),
+            kill_feed_counter: 0,
+            mutual_death: false,
+            pings: Vec::new(),
+            #[cfg(feature = "editor")]
+            editor: MapEditor::default(),
             ui_state: UiState::default(),
         })
     }
 
+    fn peek_core(&mut self, inbound: &ClientUpdate, _context: &mut Context<Self>) {
+        if let ClientUpdate::SessionCreated { .. } = inbound {
+            // Don't count time spent (re)connecting against the network statistics.
+            self.network_stats.reset();
+            // The server may not know our previous control.
+            self.last_control = None;
+            // Nor which updates it sent us.
+            self.ack = Ack::default();
+            self.last_ack = Ack::default();
+        }
+    }
+
     /// This violates the normal "peek" contract by doing the work of apply, when it comes to contacts.
     fn peek_game(&mut self, update: &Update, context: &mut Context<Self>) {
         self.peek_update_sound_counter = self.peek_update_sound_counter.saturating_add(1);
         // Only play sounds for 10 peeked updates between frames.
         let play_sounds = self.peek_update_sound_counter < 10;
 
+        self.network_stats
+            .record_update(context.client.time_seconds);
+        self.ack.receive(update.sequence);
+
+        for sinking in update.sinkings.iter() {
+            if Some(sinking.player_id) == context.state.core.player_id {
+                self.mutual_death = sinking.mutual;
+            }
+            if self.kill_feed.len() >= KillFeedItem::MAX {
+                self.kill_feed.pop_front();
+            }
+            self.kill_feed_counter = self.kill_feed_counter.wrapping_add(1);
+            self.kill_feed.push_back(KillFeedItem {
+                key: self.kill_feed_counter,
+                time: context.client.time_seconds,
+                sinking: sinking.clone(),
+            });
+        }
+
+        for &position in update.pings.iter() {
+            if play_sounds {
+                let distance = position.distance(self.camera.center);
+                context
+                    .audio
+                    .play_with_volume(Audio::Sonar3, 0.5 * Self::volume_at(distance));
+            }
+            self.pings.push(SonarPing {
+                position,
+                time: context.client.time_seconds,
+            });
+        }
+
         let updated: HashMap<EntityId, &Contact> =
             update.contacts.iter().map(|c| (c.id(), c)).collect();
 
+        // Updates are about half a round trip old (fall back to a rough estimate).
+        let latency = context
+            .state
+            .core
+            .rtt
+            .map_or(0.1, |rtt| rtt as f32 * 0.0005)
+            .min(0.5);
+
         for (id, &contact) in updated.iter() {
             if let Some(InterpolatedContact { model, .. }) = context.state.game.contacts.get(id) {
                 if Some(*id) == context.state.game.entity_id {
@@ -240,11 +372,7 @@ impl GameClient for Mk48Game {
 
                 // Mutable borrow after immutable borrows.
                 let network_contact = context.state.game.contacts.get_mut(id).unwrap();
-                network_contact.model = contact.clone();
-
-                // Compensate for the fact that the data is a little old (second parameter is rough
-                // estimate of latency)
-                network_contact.model.simulate(0.1);
+                network_contact.receive(contact, latency, context.client.time_seconds);
             } else {
                 if play_sounds {
                     self.play_new_contact_audio(
@@ -272,6 +400,15 @@ impl GameClient for Mk48Game {
         // Contacts absent in the update are currently considered lost.
         // Borrow entity_id early to avoid use of self in closure.
         let entity_id = &mut context.state.game.entity_id;
+        let player_id = context.state.core.player_id;
+        let camera_pos = self.camera.center;
+        // The server is less interested in far away contacts, so sends them less often. Err on the
+        // side of considering contacts further away (i.e. keeping them alive longer), since the
+        // server's camera may differ slightly.
+        let view_range = self
+            .mk48_camera
+            .saved_camera
+            .map_or(0.0, |(_, range)| range * 0.9);
         for contact in context
             .state
             .game
@@ -282,12 +419,15 @@ impl GameClient for Mk48Game {
                     false
                 } else {
                     *idle = idle.saturating_add(Ticks::ONE);
-                    if *idle
-                        <= view
-                            .entity_type()
-                            .map(|t| *t.data().kind.keep_alive().end())
-                            .unwrap_or(EntityKind::MAX_KEEP_ALIVE)
-                    {
+                    let interest = if player_id.is_some() && view.player_id() == player_id {
+                        Interest::Near
+                    } else {
+                        Interest::of(
+                            view.transform().position.distance_squared(camera_pos),
+                            view_range,
+                        )
+                    };
+                    if *idle <= interest.keep_alive(view.entity_type().map(|t| t.data().kind)) {
                         // Still in keep alive period.
                         return false;
                     }
@@ -400,19 +540,31 @@ impl GameClient for Mk48Game {
     }
 
     fn peek_keyboard(&mut self, event: &KeyboardEvent, context: &mut Context<Self>) {
+        #[cfg(feature = "editor")]
+        self.editor
+            .peek_keyboard(event, &context.state.game.terrain);
+
+        if event.down && event.key == Key::Home && context.settings.network_stats_shown {
+            self.report_desync(context);
+        }
+
         if event.down {
             if let Some(contact) = context.state.game.player_contact() {
                 let entity_type = contact.entity_type().unwrap();
                 let consumptions: Vec<bool> = contact.reloads().iter().map(|b| *b).collect();
-                let groups = group_armaments(&entity_type.data().armaments, &consumptions);
-                match event.key {
-                    SURFACE_KEY => {
+                let groups = group_armaments(
+                    &entity_type.data().armaments,
+                    &consumptions,
+                    context.state.game.loadout,
+                );
+                match context.settings.key_bindings.action(event.key) {
+                    Some(Action::Surface) => {
                         self.set_submerge(!self.ui_state.submerge, &*context);
                     }
-                    ACTIVE_KEY => {
+                    Some(Action::ActiveSensors) => {
                         self.set_active(!self.ui_state.active, &*context);
                     }
-                    Key::Tab => {
+                    Some(Action::CycleArmament) => {
                         self.ui_state.armament = groups
                             .get(
                                 self.ui_state
@@ -450,13 +602,35 @@ impl GameClient for Mk48Game {
     }
 
     fn tick(&mut self, elapsed_seconds: f32, context: &mut Context<Self>) {
+        self.render_chain
+            .set_color_grading(context.settings.color_grading());
         let mut frame = self.render_chain.begin(context.client.time_seconds);
         let (renderer, shadow_layer) = frame.draw();
         let layer = &mut shadow_layer.inner;
 
+        // Spawn fewer cosmetic particles if struggling to keep up.
+        let particle_budget = self.particle_budget.budget(
+            context.settings.auto_quality,
+            context.settings.low_bandwidth,
+        );
+        layer.sea_level_particles.set_budget(particle_budget);
+        layer.airborne_particles.set_budget(particle_budget);
+
+        // Names and chat may be in any script, but the preferred glyphs depend on the language.
+        let language = context.common_settings.language;
+        layer.text.set_fonts(language.text_fonts());
+        layer.text.set_right_to_left(language.is_right_to_left());
+
         // Allow more sounds to be played in peek.
         self.peek_update_sound_counter = 0;
 
+        // Expire old kill feed items.
+        while self.kill_feed.front().map_or(false, |item| {
+            context.client.time_seconds - item.time > KillFeedItem::SECONDS
+        }) {
+            self.kill_feed.pop_front();
+        }
+
         // The distance from player's boat to the closest visible member of each team, for the purpose of sorting and
         // filtering.
         let mut team_proximity: HashMap<TeamId, f32> = HashMap::new();
@@ -467,9 +641,10 @@ impl GameClient for Mk48Game {
             elapsed_seconds,
             layer.background.cache_frame,
         );
-        let (camera, _) = self
-            .mk48_camera
-            .camera(context.state.game.player_contact(), renderer.aspect_ratio());
+        let (camera, _) = self.mk48_camera.camera(
+            context.state.game.steered_contact(),
+            renderer.aspect_ratio(),
+        );
 
         // Update audio volume.
         if Self::maybe_contact_mut(
@@ -493,6 +668,9 @@ impl GameClient for Mk48Game {
         } else {
             None
         };
+        let interpolation_delay = self
+            .network_stats
+            .interpolation_delay(context.settings.interpolation);
         // A subset of game logic.
         for interp in &mut context.state.game.contacts.values_mut() {
             if interp
@@ -525,17 +703,25 @@ impl GameClient for Mk48Game {
 
             interp.update_error_bound(elapsed_seconds, debug_latency_entity_id);
             interp.generate_particles(layer);
-            interp.interpolate(elapsed_seconds, context.state.game.entity_id);
+            interp.interpolate(
+                elapsed_seconds,
+                interpolation_delay,
+                context.state.game.entity_id,
+            );
         }
 
         // May have changed due to the above.
-        let (camera, zoom) = self
-            .mk48_camera
-            .camera(context.state.game.player_contact(), renderer.aspect_ratio());
+        let (camera, zoom) = self.mk48_camera.camera(
+            context.state.game.steered_contact(),
+            renderer.aspect_ratio(),
+        );
 
         // Set camera before update layers so they don't get last frame's camera.
         // TODO decouple update and render.
-        self.camera.update(camera, zoom, renderer.canvas_size());
+        // Jitter is in view space, so convert it to world space for temporal antialiasing.
+        let jitter = renderer.taa_jitter() * vec2(zoom, zoom / renderer.aspect_ratio());
+        self.camera
+            .update(camera + jitter, zoom, renderer.canvas_size());
         let weather = Weather::new(renderer.time);
 
         let (visual_range, visual_restriction, area) =
@@ -567,6 +753,8 @@ impl GameClient for Mk48Game {
             zoom,
             &mut context.state.game.terrain,
             terrain_reset,
+            context.state.game.season,
+            context.state.game.biome,
             context.settings.shadows.is_some(),
             &*renderer,
         ));
@@ -577,6 +765,23 @@ impl GameClient for Mk48Game {
             context.settings.shadows,
         );
 
+        if context.settings.wildlife {
+            let time = js_sys::Date::now() * 0.001;
+            generate_wildlife(
+                camera,
+                zoom,
+                &context.state.game.terrain,
+                time,
+                |species, instance| {
+                    if species.is_airborne() {
+                        layer.airborne_wildlife.draw(species, instance);
+                    } else {
+                        layer.sea_level_wildlife.draw(species, instance);
+                    }
+                },
+            );
+        }
+
         layer.overlay.update(
             visual_range,
             visual_restriction,
@@ -610,10 +815,19 @@ impl GameClient for Mk48Game {
         for InterpolatedContact { view: contact, .. } in context.state.game.contacts.values() {
             let friendly = context.state.core.is_friendly(contact.player_id());
 
+            // Blue and orange remain distinct with the common forms of colorblindness, unlike green
+            // and red.
+            let colorblind = context.settings.colorblind;
             let color_bytes = if friendly {
-                [58, 255, 140]
+                if colorblind {
+                    [86, 180, 233]
+                } else {
+                    [58, 255, 140]
+                }
             } else if contact.is_boat() {
                 [255; 3]
+            } else if colorblind {
+                [230, 159, 0]
             } else {
                 [231, 76, 60]
             };
@@ -647,13 +861,8 @@ impl GameClient for Mk48Game {
                         && settings.dynamic_waves
                         && data.kind == EntityKind::Collectible
                     {
-                        let t = context.client.time_seconds;
-
-                        // Moves waves with the wind.
-                        let mut input = (transform.position + weather.wind * t) * 0.1;
-
-                        // Offset waves from regular grid.
-                        input.x += input.y * 0.3;
+                        let input =
+                            weather.wave_phase(transform.position, context.client.time_seconds);
 
                         // Don't apply waves when collectibles are moving (aka attracted to boat).
                         let f = map_ranges(transform.velocity.to_mps(), 0.0..4.0, 1.0..0.0, true);
@@ -665,13 +874,47 @@ impl GameClient for Mk48Game {
                         );
                     }
 
-                    sortable_sprites.push(SortableSprite::new_entity(
+                    let mut sortable_sprite = SortableSprite::new_entity(
                         entity_id,
                         entity_type,
                         transform,
                         altitude,
                         alpha,
-                    ));
+                    );
+
+                    // Pitch, roll, and heave boats with the waves. Small boats are tossed around
+                    // more than large ones, especially in rough seas.
+                    if settings.animations
+                        && settings.dynamic_waves
+                        && contact.is_boat()
+                        && !contact.altitude().is_submerged()
+                    {
+                        let time = context.client.time_seconds;
+                        let slope = weather.wave_slope(transform.position, time);
+                        let forward = transform.direction.to_vec();
+                        let smallness = (10.0 / data.length).min(1.0);
+                        sortable_sprite.tilt =
+                            (Vec2::new(slope.dot(forward), slope.dot(forward.perp()))
+                                * (smallness * 0.3))
+                                .clamp(Vec2::splat(-1.0), Vec2::splat(1.0));
+
+                        // Crests lift boats, which moves their shadows.
+                        let (heave, _) = weather.gerstner(transform.position, time);
+                        let heave = heave * smallness;
+                        sortable_sprite.altitude += heave;
+                        sortable_sprite.shadow_height = (sortable_sprite.shadow_height + heave)
+                            .max(sortable_sprite.shadow_height * 0.5);
+                    }
+
+                    if data.sub_kind == EntitySubKind::Net {
+                        // Nets are drawn as a segmented line of buoys instead of a sprite.
+                        Self::draw_net(&transform, data, color, alpha, &mut layer.nets);
+                    } else if matches!(data.sub_kind, EntitySubKind::Debris | EntitySubKind::Wreck)
+                    {
+                        draw_wreckage(entity_id, &transform, data, alpha, &mut layer.wreckage);
+                    } else {
+                        sortable_sprites.push(sortable_sprite);
+                    }
                 }
 
                 if contact.is_boat()
@@ -692,14 +935,14 @@ impl GameClient for Mk48Game {
                         let armament = &data.armaments[i];
                         if armament.hidden
                             || armament.vertical
-                            || !(armament.external || (friendly && !context.settings.cinematic))
+                            || !(armament.external || (friendly && !context.settings.hud_hidden()))
                         {
                             continue;
                         }
                         let armament_type = armament.entity_type;
 
                         let reloaded = contact.reloads().get(i).map(|r| *r).unwrap_or(false);
-                        if !reloaded && context.settings.cinematic {
+                        if !reloaded && context.settings.hud_hidden() {
                             continue;
                         }
 
@@ -740,7 +983,7 @@ impl GameClient for Mk48Game {
                 // GUI overlays.
                 let overlay_vertical_position = data.radius * 1.2;
 
-                if !context.settings.cinematic {
+                if !context.settings.hud_hidden() {
                     match data.kind {
                         EntityKind::Boat => {
                             // Is this player's own boat?
@@ -1023,7 +1266,9 @@ impl GameClient for Mk48Game {
                                             let is_vertical = armament.vertical;
                                             let is_stationary = matches!(
                                                 armament_data.sub_kind,
-                                                EntitySubKind::DepthCharge | EntitySubKind::Mine
+                                                EntitySubKind::DepthCharge
+                                                    | EntitySubKind::Mine
+                                                    | EntitySubKind::Net
                                             );
 
                                             if !is_stationary {
@@ -1105,14 +1350,31 @@ impl GameClient for Mk48Game {
                                     true,
                                 );
 
-                                // Health indicator.
-                                layer.graphics.draw_rounded_line(
-                                    offset_x(center, length * -0.5),
-                                    offset_x(center, length * (health - 0.5)),
-                                    thickness,
-                                    health_color,
-                                    true,
-                                );
+                                // Health indicator, dashed for foes so it doesn't rely on color.
+                                let start = length * -0.5;
+                                let end = length * (health - 0.5);
+                                if colorblind && !friendly {
+                                    let dash = length / 12.0;
+                                    let mut x = start;
+                                    while x < end {
+                                        layer.graphics.draw_rounded_line(
+                                            offset_x(center, x),
+                                            offset_x(center, (x + dash * 0.5).min(end)),
+                                            thickness,
+                                            health_color,
+                                            true,
+                                        );
+                                        x += dash;
+                                    }
+                                } else {
+                                    layer.graphics.draw_rounded_line(
+                                        offset_x(center, start),
+                                        offset_x(center, end),
+                                        thickness,
+                                        health_color,
+                                        true,
+                                    );
+                                }
                             }
 
                             // Name
@@ -1146,12 +1408,26 @@ impl GameClient for Mk48Game {
                         EntityKind::Weapon | EntityKind::Decoy | EntityKind::Aircraft => {
                             let triangle_position = contact.transform().position
                                 + Vec2::new(0.0, overlay_vertical_position);
-                            layer.graphics.draw_triangle(
-                                triangle_position + Vec2::new(0.0, 0.01 * zoom),
-                                Vec2::splat(0.02 * zoom),
-                                180f32.to_radians(),
-                                color.extend(1.0),
-                            );
+                            let center = triangle_position + Vec2::new(0.0, 0.01 * zoom);
+                            let scale = Vec2::splat(0.02 * zoom);
+                            let angle = 180f32.to_radians();
+                            if colorblind && !friendly {
+                                // Outlined, so foes don't rely on color.
+                                layer.graphics.draw_triangle_outline(
+                                    center,
+                                    scale,
+                                    angle,
+                                    0.003 * zoom,
+                                    color.extend(1.0),
+                                );
+                            } else {
+                                layer.graphics.draw_triangle(
+                                    center,
+                                    scale,
+                                    angle,
+                                    color.extend(1.0),
+                                );
+                            }
                         }
                         _ => {}
                     }
@@ -1226,9 +1502,9 @@ impl GameClient for Mk48Game {
                             };
 
                             if is_airborne {
-                                layer.airborne_particles.add(particle);
+                                layer.airborne_particles.add_budgeted(particle);
                             } else {
-                                layer.sea_level_particles.add(particle);
+                                layer.sea_level_particles.add_budgeted(particle);
                             }
                         }
 
@@ -1249,7 +1525,7 @@ impl GameClient for Mk48Game {
                                     + tangent_vector
                                         * ((spread * 1.0 + data.width * 0.01) * speed * side);
 
-                                layer.sea_level_particles.add(Mk48Particle {
+                                layer.sea_level_particles.add_budgeted(Mk48Particle {
                                     position,
                                     velocity,
                                     radius: 1.0,
@@ -1265,7 +1541,7 @@ impl GameClient for Mk48Game {
                 if !contact.altitude().is_submerged() {
                     for exhaust in data.exhausts.iter() {
                         for _ in 0..amount * 2 {
-                            layer.airborne_particles.add(Mk48Particle {
+                            layer.airborne_particles.add_budgeted(Mk48Particle {
                                 position: contact.transform().position
                                     + direction_vector * exhaust.position_forward
                                     + tangent_vector * exhaust.position_side
@@ -1282,6 +1558,39 @@ impl GameClient for Mk48Game {
                         }
                     }
                 }
+
+                // Smoke from fires, and water pumped out of flooded compartments.
+                let effects = contact.effects();
+                if effects.fire() && !contact.altitude().is_submerged() {
+                    for _ in 0..amount {
+                        layer.airborne_particles.add_budgeted(Mk48Particle {
+                            position: contact.transform().position
+                                + direction_vector * (data.length * (rng.gen::<f32>() - 0.5) * 0.6)
+                                + gen_radius(&mut rng, data.width * 0.3),
+                            // Drifts with the current.
+                            velocity: gen_radius(&mut rng, 4.0)
+                                + context.state.game.currents.at(contact.transform().position),
+                            radius: (data.width * 0.1).clamp(1.0, 3.0),
+                            // Mostly flames, some black smoke.
+                            color: rng.gen_range(-1.0..0.2),
+                            smoothness: 1.0,
+                        });
+                    }
+                }
+                if effects.flooding() && contact.altitude() == Altitude::ZERO {
+                    for _ in 0..amount {
+                        let side = if rng.gen() { -1f32 } else { 1f32 };
+                        layer.sea_level_particles.add_budgeted(Mk48Particle {
+                            position: contact.transform().position
+                                + direction_vector * (data.length * (rng.gen::<f32>() - 0.5) * 0.5)
+                                + tangent_vector * (data.width * side * 0.5),
+                            velocity: tangent_vector * (side * 5.0) + gen_radius(&mut rng, 1.0),
+                            radius: 1.0,
+                            color: 1.0,
+                            smoothness: 1.0,
+                        });
+                    }
+                }
             } else {
                 layer.sprites.draw(
                     "contact",
@@ -1292,7 +1601,24 @@ impl GameClient for Mk48Game {
                     1.0,
                     0.0,
                     0.0,
+                    Vec2::ZERO,
                 );
+
+                if contact.relayed() {
+                    // Dashed ring, to distinguish contacts detected by a fleet member's radar.
+                    const DASHES: usize = 8;
+                    let dash = TAU / (DASHES * 2) as f32;
+                    for i in 0..DASHES {
+                        let start = i as f32 * dash * 2.0;
+                        layer.graphics.draw_arc(
+                            contact.transform().position,
+                            9.0,
+                            start..start + dash,
+                            zoom * 0.002,
+                            vec4(0.5, 0.8, 1.0, 0.8),
+                        );
+                    }
+                }
             }
         }
 
@@ -1303,6 +1629,11 @@ impl GameClient for Mk48Game {
                 .play_with_volume(Audio::Aa, anti_aircraft_volume.min(0.5));
         }
 
+        // Hulls need shadow maps, and fall back to sprites on weak GPUs.
+        let hulls = context.settings.ship_models
+            && context.settings.shadows.is_some()
+            && particle_budget >= ShipLayer::MIN_BUDGET;
+
         // Sort sprites by altitude.
         sortable_sprites.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
         for SortableSprite {
@@ -1311,8 +1642,10 @@ impl GameClient for Mk48Game {
             dimensions,
             frame,
             height,
+            hull,
             shadow_height,
             sprite,
+            tilt,
             transform:
                 Transform {
                     direction,
@@ -1323,6 +1656,15 @@ impl GameClient for Mk48Game {
         } in sortable_sprites
         {
             let angle = direction.to_radians();
+            if hulls && hull && alpha == 1.0 {
+                // Casts its own shadow.
+                let (uvs, aspect) = layer.sprites.uvs(sprite);
+                layer.ships.draw(
+                    uvs, aspect, position, dimensions, angle, altitude, height, tilt,
+                );
+                continue;
+            }
+
             if alpha == 1.0 && shadow_height != 0.0 && context.settings.shadows.is_some() {
                 // Water plane covers whole world and is pointing up.
                 let water_pos = Vec3::ZERO;
@@ -1340,19 +1682,86 @@ impl GameClient for Mk48Game {
                     if normal_dot > 0.0 {
                         let scale = water_distance / normal_dot;
                         let displacement = shadow_normal * scale;
-                        let center = (shadow_pos + displacement).truncate();
+                        let mut center = (shadow_pos + displacement).truncate();
+
+                        // Leaning shifts the shadow of the superstructure.
+                        if tilt != Vec2::ZERO {
+                            center += Mat2::from_angle(angle) * tilt * shadow_height;
+                        }
 
                         // let center = position - Vec2::splat(0.2) * height;
                         layer
                             .sprites
-                            .draw_shadow(sprite, frame, center, dimensions, angle);
+                            .draw_shadow(sprite, frame, center, dimensions, angle, tilt);
                     }
                 }
             }
 
             let center = position;
             layer.sprites.draw(
-                sprite, frame, center, dimensions, angle, alpha, altitude, height,
+                sprite, frame, center, dimensions, angle, alpha, altitude, height, tilt,
+            );
+        }
+
+        draw_currents(
+            &context.state.game.currents,
+            &context.state.game.terrain,
+            camera,
+            zoom,
+            context.client.time_seconds,
+            &mut layer.currents,
+        );
+        draw_shipwrecks(&context.state.game.shipwrecks, zoom, &mut layer.graphics);
+        draw_pings(
+            &mut self.pings,
+            context.client.time_seconds,
+            zoom,
+            &mut layer.graphics,
+        );
+        draw_zones(
+            &context.state.game.zones,
+            context
+                .state
+                .core
+                .player_id
+                .map(|player_id| ZoneHolder::new(player_id, context.state.core.team_id())),
+            zoom,
+            &mut layer.graphics,
+        );
+
+        #[cfg(feature = "editor")]
+        self.editor.tick(
+            elapsed_seconds,
+            &mut context.mouse,
+            context
+                .mouse
+                .view_position
+                .map(|p| self.camera.to_world_position(p)),
+            &mut context.state.game.terrain,
+            zoom,
+            &mut layer.graphics,
+        );
+
+        if let Some(smuggling) = &context.state.game.smuggling {
+            draw_smuggling(
+                smuggling,
+                Some(smuggling.carrier) == context.state.core.player_id,
+                &self.camera,
+                zoom,
+                &mut layer.graphics,
+                &mut layer.text,
+            );
+        }
+
+        if context.settings.network_stats_shown {
+            self.network_stats.draw(
+                context.state.core.rtt,
+                context.settings.interpolation,
+                &context.input_monitor,
+                &self.camera,
+                zoom,
+                &mut layer.graphics,
+                &mut layer.text,
             );
         }
 
@@ -1372,22 +1781,28 @@ impl GameClient for Mk48Game {
         // Send command later, when lifetimes allow.
         let mut control: Option<Command> = None;
 
-        let player_contact = Self::maybe_contact_mut(
-            &mut context.state.game.contacts,
-            context.state.game.entity_id,
-        );
-
         crate::armament::update(
-            player_contact.as_ref().and_then(|c| c.model.entity_type()),
+            context
+                .state
+                .game
+                .player_contact()
+                .and_then(|c| c.entity_type()),
+            context.state.game.loadout,
             &mut self.ui_state.armament,
         );
 
-        let status = if let Some(player_contact) = player_contact {
+        // While controlling an aircraft, steer it instead of the boat.
+        let steered_contact = Self::maybe_contact_mut(
+            &mut context.state.game.contacts,
+            context.state.game.steered_id(),
+        );
+
+        let status = if let Some(steered_contact) = steered_contact {
             let mut guidance = None;
 
             {
-                let player_contact = &player_contact.view;
-                let max_speed = player_contact.data().speed.to_mps();
+                let steered_contact = &steered_contact.view;
+                let max_speed = steered_contact.data().speed.to_mps();
 
                 let joystick = Joystick::try_from_keyboard_state(
                     context.client.time_seconds,
@@ -1397,29 +1812,29 @@ impl GameClient for Mk48Game {
 
                 if let Some(joystick) = joystick {
                     guidance = Some(Guidance {
-                        direction_target: player_contact.transform().direction
+                        direction_target: steered_contact.transform().direction
                             + Angle::from_radians(0.5 * joystick.position.x),
                         velocity_target: if joystick.stop {
                             Velocity::ZERO
                         } else if joystick.position.y.abs() > 0.05 {
-                            player_contact.transform().velocity
+                            steered_contact.transform().velocity
                                 + Velocity::from_mps(0.25 * max_speed * joystick.position.y)
                         } else {
-                            player_contact.guidance().velocity_target
+                            steered_contact.guidance().velocity_target
                         },
                     });
                     self.first_control = false; // First control was joystick.
                 }
 
                 if Self::is_holding_control(&context.mouse, context.client.time_seconds) {
-                    let current_dir = player_contact.transform().direction;
+                    let current_dir = steered_contact.transform().direction;
                     let mut direction_target = Angle::from(
-                        aim_target.unwrap_or_default() - player_contact.transform().position,
+                        aim_target.unwrap_or_default() - steered_contact.transform().position,
                     );
 
                     // Only do when start holding.
                     if !self.holding {
-                        if Self::can_reverse(self.first_control, player_contact) {
+                        if Self::can_reverse(self.first_control, steered_contact) {
                             // Starting movement behind ship turns on reverse.
                             let delta = direction_target - current_dir;
                             self.reversing = delta
@@ -1439,7 +1854,7 @@ impl GameClient for Mk48Game {
                     if stop {
                         // Limit turning while "stopped"
                         direction_target = current_dir
-                            + (direction_target - player_contact.transform().direction)
+                            + (direction_target - steered_contact.transform().direction)
                                 .clamp_magnitude(Angle::from_radians(0.5));
                     }
 
@@ -1451,8 +1866,8 @@ impl GameClient for Mk48Game {
                             let mut velocity = Velocity::from_mps(map_ranges(
                                 aim_target
                                     .unwrap_or_default()
-                                    .distance(player_contact.transform().position),
-                                player_contact.data().radii(),
+                                    .distance(steered_contact.transform().position),
+                                steered_contact.data().radii(),
                                 0.0..max_speed,
                                 true,
                             ));
@@ -1469,12 +1884,12 @@ impl GameClient for Mk48Game {
             }
 
             if let Some(guidance) = guidance.as_ref() {
-                player_contact.model.predict_guidance(guidance);
-                player_contact.view.predict_guidance(guidance);
+                steered_contact.predict_guidance(guidance, context.client.time_seconds);
             }
 
             // Re-borrow as immutable.
             let player_contact = context.state.game.player_contact().unwrap();
+            let steered_guidance = *context.state.game.steered_contact().unwrap().guidance();
 
             let status = UiStatus::Playing(UiStatusPlaying {
                 entity_type: player_contact.entity_type().unwrap(),
@@ -1483,7 +1898,11 @@ impl GameClient for Mk48Game {
                 velocity: player_contact.transform().velocity,
                 altitude: player_contact.altitude(),
                 submerge: self.ui_state.submerge,
+                depth: self.ui_state.depth,
                 active: self.ui_state.active,
+                doctrine: self.ui_state.doctrine,
+                aircraft_controlled: context.state.game.steered_id()
+                    != context.state.game.entity_id,
                 instruction_status: if player_contact.data().level <= 3 {
                     InstructionStatus {
                         touch: context.mouse.touch_screen,
@@ -1493,30 +1912,61 @@ impl GameClient for Mk48Game {
                 } else {
                     InstructionStatus::default()
                 },
+                can_respec: context.state.game.can_respec,
+                loadout: context.state.game.loadout,
+                store: context.state.game.contacts.values().any(|store| {
+                    store.view.entity_type().map_or(false, |store_type| {
+                        Purchase::in_range(
+                            player_contact.entity_type().unwrap(),
+                            player_contact.transform().position,
+                            store_type,
+                            store.view.transform().position,
+                        )
+                    })
+                }),
+                damage: player_contact.damage().to_secs()
+                    / player_contact.data().max_health().to_secs(),
                 armament: self.ui_state.armament,
                 armament_consumption: player_contact.reloads().iter().map(|b| *b).collect(),
+                disabled: player_contact.disabled(),
+                effects: player_contact.effects(),
                 team_proximity,
             });
 
-            if self.control_rate_limiter.update_ready(elapsed_seconds) {
+            // The map editor takes over the mouse.
+            #[cfg(feature = "editor")]
+            let editing = self.editor.enabled;
+            #[cfg(not(feature = "editor"))]
+            let editing = false;
+
+            if self.control_rate_limiter.update_ready(elapsed_seconds) && !editing {
                 let left_click = context.mouse.take_click(MouseButton::Left);
+                let key_bindings = context.settings.key_bindings;
 
                 // Get hint before borrow of player_contact().
                 let hint = Some(Hint {
                     aspect: aspect_ratio,
+                    low_bandwidth: context.settings.low_bandwidth,
                 });
 
                 let current_control = Control {
-                    guidance: Some(*player_contact.guidance()), // TODO don't send if hasn't changed.
+                    guidance: Some(steered_guidance), // TODO don't send if hasn't changed.
                     submerge: self.ui_state.submerge,
                     aim_target,
                     active: self.ui_state.active,
-                    pay: context.keyboard.is_down(Key::C).then_some(Pay),
+                    pay: context
+                        .keyboard
+                        .is_down(key_bindings.key(Action::Pay))
+                        .then_some(Pay),
                     fire: if left_click
                         || context
                             .keyboard
-                            .state(Key::Space)
-                            .combined(context.keyboard.state(Key::E))
+                            .state(key_bindings.key(Action::Fire))
+                            .combined(
+                                context
+                                    .keyboard
+                                    .state(key_bindings.key(Action::FireAlternate)),
+                            )
                             .is_down()
                     {
                         Self::find_best_armament(
@@ -1537,6 +1987,8 @@ impl GameClient for Mk48Game {
                         None
                     },
                     hint,
+                    depth: self.ui_state.depth,
+                    doctrine: self.ui_state.doctrine,
                 };
 
                 // Some things are not idempotent.
@@ -1552,8 +2004,25 @@ impl GameClient for Mk48Game {
                         .map(is_significant)
                         .unwrap_or(false)
                 {
-                    self.last_control = Some(current_control.clone());
-                    control = Some(Command::Control(current_control));
+                    let command = match self.last_control.as_ref() {
+                        Some(last_control)
+                            if self.controls_since_full < Self::CONTROLS_PER_FULL =>
+                        {
+                            self.controls_since_full += 1;
+                            Command::ControlDelta(ControlDelta::new(last_control, &current_control))
+                        }
+                        _ => {
+                            self.controls_since_full = 0;
+                            Command::Control(current_control.clone())
+                        }
+                    };
+                    self.last_control = Some(current_control);
+                    control = Some(command);
+                    context
+                        .input_monitor
+                        .record_command(context.client.time_seconds, context.socket.is_open());
+                } else {
+                    context.input_monitor.record_unchanged();
                 }
             }
 
@@ -1569,16 +2038,31 @@ impl GameClient for Mk48Game {
             .filter(|_| !self.respawn_overridden)
             .cloned()
         {
-            UiStatus::Respawning(UiStatusRespawning { death_reason })
+            UiStatus::Respawning(UiStatusRespawning {
+                death_reason,
+                mutual: self.mutual_death,
+            })
         } else {
             UiStatus::Spawning
         };
 
+        if !matches!(status, UiStatus::Playing(_)) {
+            // Inputs can't register without a boat.
+            context.input_monitor.discard_pending();
+        }
+
         if let Some(control) = control {
             context.send_to_game(control);
         }
 
-        self.fps_counter.update(elapsed_seconds);
+        if self.ack_rate_limiter.update_ready(elapsed_seconds) && self.ack != self.last_ack {
+            self.last_ack = self.ack;
+            context.send_to_game(Command::Ack(self.ack));
+        }
+
+        if let Some(fps) = self.fps_counter.update(elapsed_seconds) {
+            self.particle_budget.update(fps);
+        }
         self.fire_rate_limiter.update(elapsed_seconds);
 
         if self.ui_props_rate_limiter.update_ready(elapsed_seconds) {
@@ -1594,31 +2078,98 @@ impl GameClient for Mk48Game {
             UiEvent::Armament(armament) => {
                 self.ui_state.armament = armament;
             }
+            UiEvent::ControlAircraft(control) => {
+                self.control_aircraft(control, context);
+            }
+            UiEvent::CreateArena {
+                bots,
+                radius,
+                max_level,
+            } => {
+                let mut options = vec![(String::from("max_level"), max_level.to_string())];
+                if let Some(radius) = radius {
+                    options.push((String::from("radius"), radius.to_string()));
+                }
+                context.send_create_arena(bots, options);
+            }
+            UiEvent::Depth(depth) => {
+                self.ui_state.depth = depth;
+            }
+            UiEvent::Doctrine(doctrine) => {
+                self.ui_state.doctrine = doctrine;
+            }
             UiEvent::GraphicsSettingsChanged => {
                 self.render_chain = Self::create_render_chain(context).unwrap();
             }
             UiEvent::OverrideRespawn => {
                 self.respawn_overridden = true;
             }
-            UiEvent::Respawn(entity_type) => {
-                context.send_to_game(Command::Spawn(Spawn { entity_type }));
+            UiEvent::Respawn(entity_type, loadout) => {
+                self.ui_state.depth = None;
+                context.send_to_game(Command::Spawn(Spawn {
+                    entity_type,
+                    loadout,
+                }));
             }
             UiEvent::Spawn { alias, entity_type } => {
+                self.ui_state.depth = None;
                 context.send_set_alias(alias);
-                context.send_to_game(Command::Spawn(Spawn { entity_type }));
+                context.send_to_game(Command::Spawn(Spawn {
+                    entity_type,
+                    loadout: 0,
+                }));
+            }
+            UiEvent::Purchase(purchase) => {
+                context.audio.play(Audio::Collect);
+                context.send_to_game(Command::Purchase(purchase));
             }
             UiEvent::Submerge(submerge) => {
                 self.set_submerge(submerge, &*context);
             }
-            UiEvent::Upgrade(entity_type) => {
+            UiEvent::Upgrade(entity_type, loadout) => {
+                // Depth targets are specific to a submarine's depth ratings.
+                self.ui_state.depth = None;
                 context.audio.play(Audio::Upgrade);
-                context.send_to_game(Command::Upgrade(Upgrade { entity_type }));
+                context.send_to_game(Command::Upgrade(Upgrade {
+                    entity_type,
+                    loadout,
+                }));
             }
         }
     }
 }
 
 impl Mk48Game {
+    /// Reports the current contacts to the server, to be compared against what the server thinks
+    /// the world looks like.
+    fn report_desync(&self, context: &mut Context<Self>) {
+        let state = &context.state.game;
+        let mut contacts: Vec<&InterpolatedContact> = state.contacts.values().collect();
+        contacts.sort_unstable_by_key(|contact| contact.model.id());
+
+        let mut lines = String::with_capacity(contacts.len() * 32);
+        for contact in &contacts {
+            lines.push_str(&contact.model.snapshot_line());
+            lines.push('\n');
+        }
+        // Contacts that are no longer being sent, but are kept alive, could be "ghosts."
+        let idle: Vec<String> = contacts
+            .iter()
+            .filter(|contact| contact.idle != Ticks::ZERO)
+            .map(|contact| format!("{} ({:?})", contact.model.id(), contact.idle))
+            .collect();
+        let message = format!(
+            "boat at {:?}, {} contacts, idle: [{}]",
+            state
+                .player_contact()
+                .map(|contact| contact.transform().position),
+            contacts.len(),
+            idle.join(", ")
+        );
+
+        context.send_desync_report(message, lines);
+    }
+
     fn set_active(&mut self, active: bool, context: &Context<Self>) {
         if let Some(contact) = context.state.game.player_contact() {
             if active && contact.data().sensors.sonar.range >= 0.0 {
@@ -1628,6 +2179,41 @@ impl Mk48Game {
         self.ui_state.active = active;
     }
 
+    /// Takes direct control of the player's aircraft closest to their boat, or returns control
+    /// to the boat.
+    fn control_aircraft(&self, control: bool, context: &mut Context<Self>) {
+        let entity_id = if control {
+            let player_contact = match context.state.game.player_contact() {
+                Some(player_contact) => player_contact,
+                None => return,
+            };
+            let position = player_contact.transform().position;
+            context
+                .state
+                .game
+                .contacts
+                .values()
+                .map(|contact| &contact.view)
+                .filter(|contact| {
+                    contact.player_id() == player_contact.player_id()
+                        && contact.entity_type().map(|t| t.data().kind)
+                            == Some(EntityKind::Aircraft)
+                })
+                .min_by(|a, b| {
+                    let a = a.transform().position.distance_squared(position);
+                    let b = b.transform().position.distance_squared(position);
+                    a.partial_cmp(&b).unwrap()
+                })
+                .map(|contact| contact.id())
+        } else {
+            None
+        };
+        if control && entity_id.is_none() {
+            return;
+        }
+        context.send_to_game(Command::ControlAircraft(ControlAircraft { entity_id }));
+    }
+
     fn set_submerge(&mut self, submerge: bool, context: &Context<Self>) {
         if let Some(contact) = context.state.game.player_contact() {
             if contact.data().sub_kind == EntitySubKind::Submarine {
@@ -10,6 +10,7 @@ use client_util::context::Context;
 use common::contact::{Contact, ContactTrait};
 use common::entity::EntityId;
 use common::entity::{EntityData, EntityKind, EntitySubKind};
+use common::guidance::Guidance;
 use common::ticks::Ticks;
 use common_util::angle::Angle;
 use common_util::range::map_ranges;
@@ -30,6 +31,9 @@ pub struct InterpolatedContact {
     /// Idle ticks, i.e. how many updates since last seen. If exceeds entity_type.data().keep_alive(),
     /// assume entity went away.
     pub idle: Ticks,
+    /// Guidance from the player's latest input, which the server may not have applied yet, and
+    /// when it was given. Only for the contact the player steers.
+    pub predicted_guidance: Option<(Guidance, f32)>,
 }
 
 impl InterpolatedContact {
@@ -41,9 +45,39 @@ impl InterpolatedContact {
             view: contact,
             error: 0.0,
             idle: Ticks::ZERO,
+            predicted_guidance: None,
         }
     }
 
+    /// Applies the player's input locally, without waiting for the server to apply it.
+    pub fn predict_guidance(&mut self, guidance: &Guidance, time_seconds: f32) {
+        self.model.predict_guidance(guidance);
+        self.view.predict_guidance(guidance);
+        self.predicted_guidance = Some((*guidance, time_seconds));
+    }
+
+    /// Snaps the model to an update from the server, which is roughly `latency` seconds old.
+    ///
+    /// The contact the player steers is predicted: its model keeps the guidance of recent input
+    /// that the server may not have applied yet, and is extrapolated with it, so it doesn't lag
+    /// behind the input. The view then smoothly reconciles with any corrections from the server.
+    pub fn receive(&mut self, contact: &Contact, latency: f32, time_seconds: f32) {
+        self.model = contact.clone();
+
+        if let Some((guidance, time)) = self.predicted_guidance {
+            // Input takes about as long to reach the server as updates take to come back, so older
+            // input is already reflected in the update.
+            if time_seconds - time <= latency * 2.0 + Ticks::PERIOD_SECS {
+                self.model.predict_guidance(&guidance);
+            } else {
+                self.predicted_guidance = None;
+            }
+        }
+
+        // Compensate for the fact that the data is a little old.
+        self.model.simulate(latency);
+    }
+
     /// Updates measure of discrepancy between model and view, known as "error."
     pub fn update_error_bound(
         &mut self,
@@ -171,13 +205,23 @@ impl InterpolatedContact {
         }
     }
 
-    /// Performs interpolation. Takes the entity id of the player's boat.
-    pub fn interpolate(&mut self, elapsed_seconds: f32, player_entity_id: Option<EntityId>) {
+    /// Performs interpolation. Takes the interpolation delay (in seconds) and the entity id of the
+    /// player's boat.
+    pub fn interpolate(
+        &mut self,
+        elapsed_seconds: f32,
+        delay: f32,
+        player_entity_id: Option<EntityId>,
+    ) {
+        // A longer delay means gentler (smoother, but less accurate) interpolation. The default delay
+        // of one tick corresponds to the original strength.
+        let strength = Ticks::PERIOD_SECS / delay.max(0.001);
+
         // Don't interpolate view's guidance if this is the player's boat, so that it doesn't jerk around.
         self.view.interpolate_towards(
             &self.model,
             Some(self.model.id()) != player_entity_id,
-            elapsed_seconds * self.error,
+            elapsed_seconds * self.error * strength,
             elapsed_seconds,
         );
         self.model.simulate(elapsed_seconds);
@@ -300,6 +344,9 @@ impl Mk48Game {
                     EntitySubKind::Sam | EntitySubKind::RocketTorpedo => {
                         audio_layer.play_with_volume(Audio::Rocket, volume);
                     }
+                    EntitySubKind::Net => {
+                        audio_layer.play_with_volume(Audio::Splash, volume);
+                    }
                     EntitySubKind::DepthCharge | EntitySubKind::Mine => {
                         audio_layer.play_with_volume(Audio::Splash, volume);
                         if !friendly && context.state.game.entity_id.is_some() {
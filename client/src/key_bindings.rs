@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use client_util::keyboard::Key;
+use std::str::FromStr;
+
+/// An action that can be bound to a key.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    ActiveSensors,
+    CycleArmament,
+    Fire,
+    FireAlternate,
+    Pay,
+    Surface,
+}
+
+impl Action {
+    /// Every action, in the order they are stored and displayed.
+    pub const ALL: [Self; 6] = [
+        Self::Fire,
+        Self::FireAlternate,
+        Self::CycleArmament,
+        Self::Surface,
+        Self::ActiveSensors,
+        Self::Pay,
+    ];
+
+    pub fn default_key(self) -> Key {
+        match self {
+            Self::ActiveSensors => Key::Z,
+            Self::CycleArmament => Key::Tab,
+            Self::Fire => Key::Space,
+            Self::FireAlternate => Key::E,
+            Self::Pay => Key::C,
+            Self::Surface => Key::R,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ActiveSensors => "Active sensors",
+            Self::CycleArmament => "Next weapon",
+            Self::Fire => "Fire",
+            Self::FireAlternate => "Fire (alternate)",
+            Self::Pay => "Pay",
+            Self::Surface => "Surface/dive",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&a| a == self).unwrap()
+    }
+}
+
+/// Which key is bound to each [`Action`]. No two actions share a key.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KeyBindings([Key; Action::ALL.len()]);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(Action::ALL.map(Action::default_key))
+    }
+}
+
+impl KeyBindings {
+    /// Gets the key bound to `action`.
+    pub fn key(&self, action: Action) -> Key {
+        self.0[action.index()]
+    }
+
+    /// Gets the action bound to `key`, if any.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.0
+            .iter()
+            .position(|&k| k == key)
+            .map(|i| Action::ALL[i])
+    }
+
+    /// Binds `key` to `action`. If `key` was bound to another action, that action gets the key
+    /// previously bound to `action`.
+    pub fn bind(&mut self, action: Action, key: Key) {
+        let previous = self.key(action);
+        if let Some(other) = self.action(key) {
+            self.0[other.index()] = previous;
+        }
+        self.0[action.index()] = key;
+    }
+}
+
+impl ToString for KeyBindings {
+    fn to_string(&self) -> String {
+        self.0
+            .iter()
+            .map(Key::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl FromStr for KeyBindings {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = Self::default();
+        let mut split = s.split(',');
+        for action in Action::ALL {
+            let key = Key::from_str(split.next().ok_or(())?)?;
+            if ret
+                .action(key)
+                .map_or(false, |other| other.index() < action.index())
+            {
+                // Duplicate key.
+                return Err(());
+            }
+            ret.0[action.index()] = key;
+        }
+        if split.next().is_some() {
+            return Err(());
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::key_bindings::{Action, KeyBindings};
+    use client_util::keyboard::Key;
+    use std::str::FromStr;
+
+    #[test]
+    fn key_bindings() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.action(Key::R), Some(Action::Surface));
+        assert_eq!(KeyBindings::from_str(&bindings.to_string()), Ok(bindings));
+
+        // Binding a key that is already in use swaps keys.
+        bindings.bind(Action::Surface, Key::Z);
+        assert_eq!(bindings.key(Action::Surface), Key::Z);
+        assert_eq!(bindings.key(Action::ActiveSensors), Key::R);
+        assert_eq!(KeyBindings::from_str(&bindings.to_string()), Ok(bindings));
+
+        assert!(KeyBindings::from_str("Space,Space,Tab,R,Z,C").is_err());
+        assert!(KeyBindings::from_str("Space,E").is_err());
+    }
+}
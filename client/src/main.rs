@@ -19,12 +19,21 @@ mod armament;
 mod audio;
 mod background;
 mod camera;
+mod current;
+#[cfg(feature = "editor")]
+mod editor;
 mod game;
 mod interpolated;
 mod interpolated_contact;
+mod key_bindings;
 mod licenses;
+mod network_stats;
 mod particle;
+mod ping;
 mod settings;
+mod ship;
+mod shipwreck;
+mod smuggling;
 mod sortable_sprite;
 mod sprite;
 mod state;
@@ -33,6 +42,9 @@ mod trail;
 mod translation;
 mod ui;
 mod weather;
+mod wildlife;
+mod wreckage;
+mod zone;
 
 fn main() {
     yew_frontend::entry_point::<Mk48Game, Mk48Ui, Mk48Route>();
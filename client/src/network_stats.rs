@@ -0,0 +1,231 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::settings::InterpolationSetting;
+use client_util::input_monitor::InputMonitor;
+use common::ticks::Ticks;
+use glam::{vec2, vec4, Vec2};
+use renderer2d::{Camera2d, GraphicLayer, TextAnchor, TextLayer};
+use std::collections::VecDeque;
+
+/// Measures the timing of game updates, to help diagnose rubber-banding.
+#[derive(Default)]
+pub struct NetworkStats {
+    /// Client time of the most recent game update.
+    last_update: Option<f32>,
+    /// Seconds between recent game updates, oldest first.
+    intervals: VecDeque<f32>,
+}
+
+impl NetworkStats {
+    /// The server sends one game update per tick.
+    const EXPECTED_INTERVAL: f32 = Ticks::PERIOD_SECS;
+    /// How many intervals to keep (5 seconds worth).
+    const HISTORY: usize = 50;
+    /// Limits of automatic interpolation delay, in seconds.
+    const AUTO_DELAY_MIN: f32 = 0.05;
+    const AUTO_DELAY_MAX: f32 = 0.5;
+
+    /// Call upon receiving a game update.
+    pub fn record_update(&mut self, time_seconds: f32) {
+        if let Some(last_update) = self.last_update {
+            if self.intervals.len() >= Self::HISTORY {
+                self.intervals.pop_front();
+            }
+            self.intervals
+                .push_back((time_seconds - last_update).max(0.0));
+        }
+        self.last_update = Some(time_seconds);
+    }
+
+    /// Call upon (re)connecting, so the time spent disconnected isn't counted.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Average seconds between updates.
+    fn mean_interval(&self) -> Option<f32> {
+        (!self.intervals.is_empty())
+            .then(|| self.intervals.iter().sum::<f32>() / self.intervals.len() as f32)
+    }
+
+    /// Updates received per second.
+    pub fn update_rate(&self) -> Option<f32> {
+        self.mean_interval().map(|mean| 1.0 / mean.max(0.001))
+    }
+
+    /// Standard deviation of seconds between updates.
+    pub fn jitter(&self) -> Option<f32> {
+        self.mean_interval().map(|mean| {
+            let variance = self
+                .intervals
+                .iter()
+                .map(|interval| (interval - mean).powi(2))
+                .sum::<f32>()
+                / self.intervals.len() as f32;
+            variance.sqrt()
+        })
+    }
+
+    /// Updates that should have been received, but weren't, during the recorded history.
+    pub fn dropped(&self) -> u32 {
+        let expected = (self.intervals.iter().sum::<f32>() / Self::EXPECTED_INTERVAL).round();
+        (expected as u32).saturating_sub(self.intervals.len() as u32)
+    }
+
+    /// Returns the interpolation delay, in seconds, based on the setting.
+    pub fn interpolation_delay(&self, setting: InterpolationSetting) -> f32 {
+        match setting {
+            InterpolationSetting::Auto => {
+                // Leave enough time for a typical late update to arrive.
+                let mean = self.mean_interval().unwrap_or(Self::EXPECTED_INTERVAL);
+                let jitter = self.jitter().unwrap_or(0.0);
+                (mean + 2.0 * jitter).clamp(Self::AUTO_DELAY_MIN, Self::AUTO_DELAY_MAX)
+            }
+            InterpolationSetting::Manual(millis) => millis as f32 / 1000.0,
+        }
+    }
+
+    /// Draws a graph of recent update intervals, along with statistics (including those of the
+    /// input pipeline), on the left side of the screen.
+    pub fn draw(
+        &self,
+        rtt: Option<u16>,
+        setting: InterpolationSetting,
+        input: &InputMonitor,
+        camera: &Camera2d,
+        zoom: f32,
+        graphics: &mut GraphicLayer,
+        text: &mut TextLayer,
+    ) {
+        let bottom_left = camera.to_world_position(vec2(-0.95, -0.2));
+        let top_right = camera.to_world_position(vec2(-0.55, 0.0));
+        let size = top_right - bottom_left;
+
+        graphics.draw_rectangle(
+            bottom_left + size * 0.5,
+            size,
+            0.0,
+            vec4(0.0, 0.0, 0.0, 0.25),
+        );
+
+        // Intervals up to this long fit within the graph.
+        let max_interval = Self::EXPECTED_INTERVAL * 4.0;
+        let bar_width = size.x / Self::HISTORY as f32;
+        for (i, &interval) in self.intervals.iter().enumerate() {
+            let height = (interval / max_interval).min(1.0) * size.y;
+            let color = if interval > Self::EXPECTED_INTERVAL * 1.5 {
+                vec4(1.0, 0.3, 0.3, 0.8)
+            } else {
+                vec4(0.3, 1.0, 0.3, 0.8)
+            };
+            graphics.draw_rectangle(
+                bottom_left + vec2((i as f32 + 0.5) * bar_width, height * 0.5),
+                vec2(bar_width * 0.8, height),
+                0.0,
+                color,
+            );
+        }
+
+        // Mark the expected interval.
+        let expected_y = bottom_left.y + Self::EXPECTED_INTERVAL / max_interval * size.y;
+        graphics.draw_line(
+            Vec2::new(bottom_left.x, expected_y),
+            Vec2::new(top_right.x, expected_y),
+            size.y * 0.01,
+            vec4(1.0, 1.0, 1.0, 0.5),
+        );
+
+        let millis = |seconds: Option<f32>| {
+            seconds
+                .map(|s| format!("{:.0}ms", s * 1000.0))
+                .unwrap_or_else(|| String::from("?"))
+        };
+        let delay = self.interpolation_delay(setting);
+        let lines = [
+            format!("RTT: {}", millis(rtt.map(|rtt| rtt as f32 * 0.001))),
+            format!("Jitter: {}", millis(self.jitter())),
+            format!(
+                "Updates: {}/s",
+                self.update_rate()
+                    .map(|rate| format!("{:.1}", rate))
+                    .unwrap_or_else(|| String::from("?"))
+            ),
+            format!("Dropped: {}", self.dropped()),
+            format!(
+                "Delay: {}{}",
+                millis(Some(delay)),
+                if setting == InterpolationSetting::Auto {
+                    " (auto)"
+                } else {
+                    ""
+                }
+            ),
+            format!(
+                "Inputs: {} ({} ignored, {} merged)",
+                input.received, input.ignored, input.merged
+            ),
+            // Client fell behind.
+            format!(
+                "Input lag: {} ({} stale)",
+                millis(input.latency),
+                input.stale
+            ),
+            // Network.
+            format!("Commands: {} ({} unsent)", input.sent, input.unsent),
+            // Server.
+            format!(
+                "Unacked: {} (max {})",
+                input.unacknowledged, input.max_unacknowledged
+            ),
+        ];
+
+        let scale = 0.025 * zoom;
+        // Align lines with the start of the graph, which is on the right for right-to-left languages.
+        let start_x = if text.is_right_to_left() {
+            top_right.x
+        } else {
+            bottom_left.x
+        };
+        for (i, line) in lines.iter().enumerate() {
+            text.draw_anchored(
+                line,
+                Vec2::new(start_x, top_right.y + scale * (lines.len() - i) as f32),
+                TextAnchor::Start,
+                scale,
+                [255, 255, 255, 255],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network_stats::NetworkStats;
+    use crate::settings::InterpolationSetting;
+
+    #[test]
+    fn network_stats() {
+        let mut stats = NetworkStats::default();
+        assert_eq!(stats.update_rate(), None);
+        assert_eq!(stats.dropped(), 0);
+
+        for i in 0..20 {
+            stats.record_update(i as f32 * 0.1);
+        }
+        assert!((stats.update_rate().unwrap() - 10.0).abs() < 0.01);
+        assert!(stats.jitter().unwrap() < 0.001);
+        assert_eq!(stats.dropped(), 0);
+        assert!((stats.interpolation_delay(InterpolationSetting::Auto) - 0.1).abs() < 0.001);
+        assert_eq!(
+            stats.interpolation_delay(InterpolationSetting::Manual(250)),
+            0.25
+        );
+
+        // Two updates went missing.
+        stats.record_update(2.2);
+        assert_eq!(stats.dropped(), 2);
+        assert!(stats.jitter().unwrap() > 0.01);
+        assert!(stats.interpolation_delay(InterpolationSetting::Auto) > 0.1);
+    }
+}
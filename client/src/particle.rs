@@ -28,6 +28,49 @@ impl Particle for Mk48Particle {
     const LIFESPAN: f32 = 1.25;
 }
 
+/// Adapts the fraction of cosmetic particles that get spawned to keep the framerate above a target.
+pub struct ParticleBudget {
+    budget: f32,
+}
+
+impl Default for ParticleBudget {
+    fn default() -> Self {
+        Self { budget: 1.0 }
+    }
+}
+
+impl ParticleBudget {
+    /// Below this framerate, the budget is reduced.
+    const TARGET_FPS: f32 = 45.0;
+    /// Above this framerate, the budget is gradually restored.
+    const RECOVERY_FPS: f32 = 55.0;
+    /// Never spawn less than this fraction of particles.
+    const MIN_BUDGET: f32 = 0.1;
+    /// Spawn at most this fraction of particles in low bandwidth mode, since updates are sparser.
+    const LOW_BANDWIDTH_BUDGET: f32 = 0.25;
+
+    /// Call with every new FPS sample.
+    pub fn update(&mut self, fps: f32) {
+        if fps < Self::TARGET_FPS {
+            // Cut back proportionally, but not too drastically in one step.
+            self.budget *= (fps / Self::TARGET_FPS).max(0.5);
+        } else if fps > Self::RECOVERY_FPS {
+            self.budget += 0.1;
+        }
+        self.budget = self.budget.clamp(Self::MIN_BUDGET, 1.0);
+    }
+
+    /// Returns the fraction of particles to spawn, in the range [`Self::MIN_BUDGET`] to 1.
+    pub fn budget(&self, auto_quality: bool, low_bandwidth: bool) -> f32 {
+        let budget = if auto_quality { self.budget } else { 1.0 };
+        if low_bandwidth {
+            budget.min(Self::LOW_BANDWIDTH_BUDGET)
+        } else {
+            budget
+        }
+    }
+}
+
 /// [`Deref`]s to its inner [`ParticleLayer`].
 #[derive(Layer)]
 #[alpha]
@@ -35,6 +78,10 @@ pub struct Mk48ParticleLayer<const AIRBORNE: bool> {
     #[layer]
     inner: ParticleLayer<Mk48Particle>,
     shader: Shader,
+    /// Fraction of particles passed to [`Self::add_budgeted`] that are actually added.
+    budget: f32,
+    /// Accumulates fractional particles so that the budget is respected without randomness.
+    budget_accumulator: f32,
 }
 
 impl<const A: bool> Deref for Mk48ParticleLayer<A> {
@@ -62,7 +109,27 @@ impl<const A: bool> Mk48ParticleLayer<A> {
         // TODO don't create 2 shaders for 2 particle layers.
         let shader = Shader::new(renderer, &vert, include_str!("shaders/particle.frag"));
 
-        Self { inner, shader }
+        Self {
+            inner,
+            shader,
+            budget: 1.0,
+            budget_accumulator: 0.0,
+        }
+    }
+
+    /// Sets the fraction of particles passed to [`Self::add_budgeted`] that are actually added.
+    pub fn set_budget(&mut self, budget: f32) {
+        self.budget = budget;
+    }
+
+    /// Like [`ParticleLayer::add`], but only adds a fraction of cosmetic particles (such as wakes)
+    /// according to the budget.
+    pub fn add_budgeted(&mut self, particle: Mk48Particle) {
+        self.budget_accumulator += self.budget;
+        if self.budget_accumulator >= 1.0 {
+            self.budget_accumulator -= 1.0;
+            self.inner.add(particle);
+        }
     }
 }
 
@@ -86,3 +153,29 @@ impl<const A: bool> RenderLayer<&ShadowResult<&Mk48Params>> for Mk48ParticleLaye
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::particle::ParticleBudget;
+
+    #[test]
+    fn particle_budget() {
+        let mut budget = ParticleBudget::default();
+        assert_eq!(budget.budget(true, false), 1.0);
+
+        for _ in 0..20 {
+            budget.update(20.0);
+        }
+        assert_eq!(budget.budget(true, false), ParticleBudget::MIN_BUDGET);
+        assert_eq!(budget.budget(false, false), 1.0);
+
+        for _ in 0..20 {
+            budget.update(60.0);
+        }
+        assert_eq!(budget.budget(true, false), 1.0);
+        assert_eq!(
+            budget.budget(false, true),
+            ParticleBudget::LOW_BANDWIDTH_BUDGET
+        );
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common_util::range::map_ranges;
+use glam::{vec4, Vec2};
+use renderer2d::GraphicLayer;
+
+/// An active sonar ping heard by the player.
+pub struct SonarPing {
+    pub position: Vec2,
+    /// When the ping was heard.
+    pub time: f32,
+}
+
+impl SonarPing {
+    /// How long pings are shown for.
+    pub const SECONDS: f32 = 1.5;
+    /// Final radius of a ping's ring.
+    const RADIUS: f32 = 150.0;
+}
+
+/// Draws each ping as a ring that expands and fades out, removing pings that are over.
+pub fn draw_pings(pings: &mut Vec<SonarPing>, time: f32, zoom: f32, graphics: &mut GraphicLayer) {
+    pings.retain(|ping| time - ping.time < SonarPing::SECONDS);
+
+    let thickness = 0.003 * zoom;
+    for ping in pings.iter() {
+        let t = (time - ping.time) * (1.0 / SonarPing::SECONDS);
+        let alpha = map_ranges(t, 0.0..1.0, 0.8..0.0, true);
+        graphics.draw_circle(
+            ping.position,
+            SonarPing::RADIUS * t.sqrt(),
+            thickness,
+            vec4(0.3, 0.8, 1.0, alpha),
+        );
+    }
+}
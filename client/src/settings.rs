@@ -1,21 +1,210 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::key_bindings::KeyBindings;
 use client_util::browser_storage::BrowserStorages;
 use client_util::js_util::is_mobile;
 use client_util::setting::Settings;
+use renderer::{Antialiasing, ColorGrading, Lut};
 use std::str::FromStr;
 
 /// Settings can be set via Javascript (see util/settings.js and page/Settings.svelte).
-#[derive(Clone, Default, PartialEq, Settings)]
+#[derive(Clone, PartialEq, Settings)]
 pub struct Mk48Settings {
     pub animations: bool,
+    pub antialias: AntialiasSetting,
+    pub auto_quality: bool,
     #[setting(no_store)]
     pub cinematic: bool,
     pub circle_hud: bool,
+    /// Colorblind-safe contact colors, with friend and foe also distinguished by shape.
+    pub colorblind: bool,
     pub dynamic_waves: bool,
+    /// Multiplies brightness.
+    #[setting(range = "0.25..4.0", finite)]
+    pub exposure: f32,
     pub fps_shown: bool,
+    pub interpolation: InterpolationSetting,
+    pub key_bindings: KeyBindings,
+    /// Requests fewer updates from the server, and spawns fewer particles.
+    pub low_bandwidth: bool,
+    pub lut: LutSetting,
+    pub network_stats_shown: bool,
+    /// Hides the GUI and adds letterboxing, for taking screenshots.
+    #[setting(no_store)]
+    pub photo_mode: bool,
+    /// Where 0 is grayscale and 1 is unchanged.
+    #[setting(range = "0.0..2.0", finite)]
+    pub saturation: f32,
     pub shadows: ShadowSetting,
+    pub ship_models: bool,
+    pub wildlife: bool,
+}
+
+impl Default for Mk48Settings {
+    fn default() -> Self {
+        Self {
+            animations: false,
+            antialias: AntialiasSetting::default(),
+            auto_quality: true,
+            cinematic: false,
+            circle_hud: false,
+            colorblind: false,
+            dynamic_waves: false,
+            exposure: 1.0,
+            fps_shown: false,
+            interpolation: InterpolationSetting::default(),
+            key_bindings: KeyBindings::default(),
+            low_bandwidth: false,
+            lut: LutSetting::default(),
+            network_stats_shown: false,
+            photo_mode: false,
+            saturation: 1.0,
+            shadows: ShadowSetting::default(),
+            // Opt in, since hull meshes cost more to render than sprites.
+            ship_models: false,
+            // Cheap enough to be on by default, but can be disabled to reduce distraction.
+            wildlife: true,
+        }
+    }
+}
+
+impl Mk48Settings {
+    /// Whether to hide the in-game GUI.
+    pub fn hud_hidden(&self) -> bool {
+        self.cinematic || self.photo_mode
+    }
+
+    pub fn color_grading(&self) -> ColorGrading {
+        ColorGrading {
+            exposure: self.exposure,
+            saturation: self.saturation,
+            lut: self.lut.lut(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AntialiasSetting {
+    None,
+    Fxaa,
+    Msaa,
+    Taa,
+}
+
+impl Default for AntialiasSetting {
+    fn default() -> Self {
+        if is_mobile() {
+            Self::None
+        } else {
+            Self::Msaa
+        }
+    }
+}
+
+impl AntialiasSetting {
+    pub fn antialiasing(self) -> Antialiasing {
+        match self {
+            Self::None => Antialiasing::None,
+            Self::Fxaa => Antialiasing::Fxaa,
+            Self::Msaa => Antialiasing::Msaa,
+            Self::Taa => Antialiasing::Taa,
+        }
+    }
+}
+
+impl ToString for AntialiasSetting {
+    fn to_string(&self) -> String {
+        match self {
+            Self::None => "none",
+            Self::Fxaa => "fxaa",
+            Self::Msaa => "msaa",
+            Self::Taa => "taa",
+        }
+        .to_string()
+    }
+}
+
+impl FromStr for AntialiasSetting {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "none" => Self::None,
+            "fxaa" => Self::Fxaa,
+            "msaa" => Self::Msaa,
+            "taa" => Self::Taa,
+            _ => return Err(()),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum LutSetting {
+    #[default]
+    None,
+    Warm,
+    Cool,
+    Noir,
+    Vintage,
+}
+
+impl LutSetting {
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::Warm,
+        Self::Cool,
+        Self::Noir,
+        Self::Vintage,
+    ];
+
+    pub fn lut(self) -> Lut {
+        match self {
+            Self::None => Lut::None,
+            Self::Warm => Lut::Warm,
+            Self::Cool => Lut::Cool,
+            Self::Noir => Lut::Noir,
+            Self::Vintage => Lut::Vintage,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "No Filter",
+            Self::Warm => "Warm",
+            Self::Cool => "Cool",
+            Self::Noir => "Noir",
+            Self::Vintage => "Vintage",
+        }
+    }
+}
+
+impl ToString for LutSetting {
+    fn to_string(&self) -> String {
+        match self {
+            Self::None => "none",
+            Self::Warm => "warm",
+            Self::Cool => "cool",
+            Self::Noir => "noir",
+            Self::Vintage => "vintage",
+        }
+        .to_string()
+    }
+}
+
+impl FromStr for LutSetting {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "none" => Self::None,
+            "warm" => Self::Warm,
+            "cool" => Self::Cool,
+            "noir" => Self::Noir,
+            "vintage" => Self::Vintage,
+            _ => return Err(()),
+        })
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -82,3 +271,43 @@ impl FromStr for ShadowSetting {
         })
     }
 }
+
+/// How quickly contacts are interpolated towards their latest server-authoritative state.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum InterpolationSetting {
+    /// Adapt to the measured update interval and jitter.
+    #[default]
+    Auto,
+    /// A fixed delay, in milliseconds. Higher values are smoother, but lag further behind.
+    Manual(u16),
+}
+
+impl InterpolationSetting {
+    /// Delays offered in the settings dialog, in milliseconds.
+    pub const MANUAL_OPTIONS: [u16; 4] = [50, 100, 200, 400];
+}
+
+impl ToString for InterpolationSetting {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Auto => "auto".to_string(),
+            Self::Manual(millis) => millis.to_string(),
+        }
+    }
+}
+
+impl FromStr for InterpolationSetting {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            Ok(Self::Auto)
+        } else {
+            u16::from_str(s)
+                .ok()
+                .filter(|&millis| millis > 0)
+                .map(Self::Manual)
+                .ok_or(())
+        }
+    }
+}
@@ -17,6 +17,9 @@ uniform float uTime;
 uniform vec3 uSun;
 uniform vec3 uWaterSun;
 uniform vec2 uWind;
+uniform vec3 uTint;
+uniform vec3 uSand;
+uniform float uSnow;
 
 #ifdef SHADOWS
     uniform mat4 uShadowMatrix;
@@ -61,21 +64,32 @@ float noise(vec3 x) {
     mix( hash(n + dot(step, vec3(0, 1, 1))), hash(n + dot(step, vec3(1, 1, 1))), u.x), u.y), u.z);
 }
 
+/* <----- End modified source from shadertoy. */
+
 #ifdef WAVES
-    float waveNoise(vec3 x) {
-        float v = 0.0;
-        float a = 0.5;
-        vec3 shift = vec3(100);
-        mat2 rot = mat2(cos(0.5), sin(0.5), -sin(0.5), cos(0.5));
+    uniform vec4 uWaves[WAVES];
+
+    // Sum of Gerstner waves, each a direction (xy), wave number (z), and amplitude (w) from
+    // Weather::gerstner_waves. Returns height as a fraction of the total amplitude (-1 to 1) and
+    // outputs the normal, which has sharper crests than a sum of sines.
+    float gerstner(vec2 p, float t, out vec3 normal) {
+        float height = 0.0;
+        float amplitude = 0.0;
+        normal = vec3(0.0, 0.0, 1.0);
         for (int i = 0; i < WAVES; ++i) {
-            v += a * noise(x);
-            x = vec3(rot * x.xy, x.z) * vec3(vec2(2.0), 2.11) + shift;
-            a *= 0.5;
+            vec4 wave = uWaves[i];
+            float ka = wave.z * wave.w;
+            float f = wave.z * dot(wave.xy, p) - sqrt(9.8 * wave.z) * t;
+            float s = sin(f);
+            height += wave.w * s;
+            amplitude += wave.w;
+            normal -= vec3(wave.xy * (ka * cos(f)), ka * s);
         }
-        return v;
+        normal.z = max(normal.z, 0.1); // Prevent loops in storms.
+        normal = normalize(normal);
+        return amplitude > 0.0 ? height / amplitude : 0.0;
     }
 #endif
-/* <----- End modified source from shadertoy. */
 
 /* Modified source from https://github.com/Erkaman/glsl-worley/blob/master/worley2D.glsl liscensed under MIT liscense. */
 vec3 mod289(vec3 x) {
@@ -170,6 +184,7 @@ void main() {
     float height = textureBicubic(uHeight, vUv);
 
     float arctic = smoothstep(ARCTIC - BORDER, ARCTIC + BORDER, vPosition.y - noise(vPosition.x * 0.005 + 139.21) * (BORDER * 0.5));
+    float tropics = smoothstep(TROPICS + BORDER, TROPICS - BORDER, vPosition.y + noise(vPosition.x * 0.005 + 71.37) * (BORDER * 0.5));
     bool ocean = vPosition.y < ARCTIC;
 
     if (ocean) {
@@ -190,9 +205,11 @@ void main() {
     // Contains sand, grass, snow, and waves.
     vec4 detail = texture(uDetail, vUv2);
 
-    vec3 sand = (vec3(0.76816154, 1.0870991, 0.82120496) * detail.x + vec3(0.30392796, -0.067789495, -0.22626717)) * 0.8;
+    vec3 sand = (vec3(0.76816154, 1.0870991, 0.82120496) * detail.x + vec3(0.30392796, -0.067789495, -0.22626717)) * 0.8 * uSand;
     vec3 snow = (vec3(1.0810544, 0.9797763, 0.95707744) * detail.z + vec3(-0.078879535, 0.018439114, 0.05167395)) * 0.8;
-    vec3 lowLand = ocean ? sand : snow;
+    // Seasonal snow doesn't reach the tropics, which have lighter sand.
+    vec3 temperate = mix(sand, snow, uSnow * (1.0 - tropics));
+    vec3 lowLand = ocean ? mix(temperate, sand * vec3(1.15, 1.1, 1.0), tropics) : snow;
 
     #ifdef SHADOWS
         vec3 position = vec3(vPosition, max(heightMeters, 0.0));
@@ -239,7 +256,8 @@ void main() {
         } else {
             vec2 wavePos = vPosition + uWind * (uTime * -0.42);
             #ifdef WAVES
-                vec2 wn = waveNoise(vec3(wavePos, uTime) * 0.07) * 0.8 * vec2(WAVE_HEIGHT, 2.5);
+                vec3 waveN;
+                vec2 wn = vec2((gerstner(vPosition, uTime, waveN) * 0.5 + 0.5) * 0.8 * WAVE_HEIGHT, 0.0);
             #else
                 #ifdef ANIMATIONS
                     float v = texture(uDetail, wavePos * 0.005).w;
@@ -253,9 +271,15 @@ void main() {
 
             vec3 deep = mix(vec3(0, 0.0331, 0.171) * 0.82, vec3(0.0, 0.0331, 0.0763), arctic) * (mix(light, waterLight, 0.6));
             vec3 shallow = mix(vec3(0.0331, 0.113, 0.242) * 0.9, vec3(0.0, 0.05, 0.115), arctic) * waterLight;
+
+            // Tropical water is more turquoise.
+            deep = mix(deep, vec3(0.0, 0.06, 0.19) * (mix(light, waterLight, 0.6)), tropics);
+            shallow = mix(shallow, vec3(0.0, 0.17, 0.24) * waterLight, tropics);
             vec3 w = mix(deep, shallow, pow(0.005, abs(sandHeight - height))); // Deep to shallow water.
 
-            vec3 waveN = normalize(cross(vec3(uDerivative, 0.0, dFdx(wn.y)), vec3(0.0, uDerivative, dFdy(wn.y))));
+            #ifndef WAVES
+                vec3 waveN = normalize(cross(vec3(uDerivative, 0.0, dFdx(wn.y)), vec3(0.0, uDerivative, dFdy(wn.y))));
+            #endif
 
             vec3 viewDir = vec3(0.0, 0.0, 1.0);
             float r = clamp(dot(reflect(-uWaterSun, waveN), viewDir), 0.0, 1.0);
@@ -280,4 +304,7 @@ void main() {
             fragColor = vec4(mix(s, w, smoothstep(-delta, delta, t)), 1.0);
         }
     }
+
+    // Seasonal tint.
+    fragColor.rgb *= uTint;
 }
@@ -0,0 +1,21 @@
+#version 300 es
+
+in vec3 position;
+in vec2 uv;
+in vec2 tangent;
+
+uniform mat3 uView;
+
+out vec4 vPosition;
+out vec2 vUv;
+out vec2 vColor;
+out vec3 vTangent;
+
+void main() {
+    // Zero height because hulls have real geometry instead of a bump map.
+    vPosition = vec4(position, 0.0);
+    gl_Position = vec4(uView * vec3(position.xy, 1.0), 1.0);
+    vUv = uv;
+    vColor = vec2(1.0);
+    vTangent = vec3(tangent, 0.0);
+}
@@ -0,0 +1,8 @@
+#version 300 es
+
+in vec3 position;
+uniform mat4 uViewProjection;
+
+void main() {
+    gl_Position = uViewProjection * vec4(position, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 300 es
+precision mediump float;
+
+in vec4 vColor;
+
+out vec4 fragColor;
+
+void main() {
+    // Premultiplied alpha.
+    fragColor = vec4(vColor.rgb * vColor.a, vColor.a);
+}
@@ -0,0 +1,31 @@
+#version 300 es
+
+// Mesh.
+in vec2 position;
+in float flap;
+in float wiggle;
+
+// Instance.
+in vec2 center;
+in float direction;
+in float scale;
+in float phase;
+in vec4 color;
+
+uniform mat3 uView;
+
+out vec4 vColor;
+
+void main() {
+    vec2 local = position;
+    // Wings fold towards the body and tails sway.
+    local.y *= 1.0 - flap * (0.5 + 0.5 * sin(phase));
+    local.y += wiggle * sin(phase);
+
+    float s = sin(direction);
+    float c = cos(direction);
+    vec2 world = center + mat2(c, s, -s, c) * local * (scale * 0.5);
+
+    gl_Position = vec4(uView * vec3(world, 1.0), 1.0);
+    vColor = color;
+}
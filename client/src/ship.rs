@@ -0,0 +1,196 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game::Mk48Params;
+use crate::settings::ShadowSetting;
+use crate::sprite::SpriteLayer;
+use glam::{vec2, vec3, Mat3, Vec2, Vec3};
+use renderer::{
+    derive_vertex, Layer, MeshBuilder, RenderLayer, Renderer, Shader, Texture, TriangleBuffer,
+};
+use renderer3d::{ShadowParams, ShadowResult};
+
+derive_vertex!(
+    struct ShipVertex {
+        pos: Vec3,
+        uv: Vec2,
+        tangent: Vec2,
+    }
+);
+
+/// Outline of a generic hull, counter-clockwise from the starboard side of the stern, in a unit
+/// square pointing towards +X. There are no per-ship models, so every hull is this outline
+/// stretched to the ship's dimensions and extruded to its deck height.
+const HULL: [Vec2; 10] = [
+    vec2(-0.5, -0.4),
+    vec2(-0.4, -0.5),
+    vec2(0.15, -0.5),
+    vec2(0.35, -0.38),
+    vec2(0.5, 0.0),
+    vec2(0.35, 0.38),
+    vec2(0.15, 0.5),
+    vec2(-0.4, 0.5),
+    vec2(-0.5, 0.4),
+    vec2(-0.5, 0.0),
+];
+
+/// Draws ships as 3D hull meshes, textured with their sprite as a deck. Unlike sprites, hulls cast
+/// real shadows via [`ShadowLayer`][`renderer3d::ShadowLayer`].
+///
+/// TODO hulls are drawn beneath all sprites, including submerged ones, until there is a depth layer.
+pub struct ShipLayer {
+    atlas_color: Texture,
+    atlas_normal: Texture,
+    buffer: TriangleBuffer<ShipVertex>,
+    /// If `mesh` was already buffered this frame (by the shadow pass).
+    buffered: bool,
+    mesh: MeshBuilder<ShipVertex>,
+    shader: Shader,
+    shadow_shader: Shader,
+}
+
+impl ShipLayer {
+    /// Below this [`ParticleBudget`][`crate::particle::ParticleBudget`] the GPU is considered
+    /// too weak for hulls, and ships are drawn as sprites instead.
+    pub const MIN_BUDGET: f32 = 0.5;
+
+    /// Shares the sprite atlas of `sprites`, since decks are textured with ship sprites.
+    pub fn new(renderer: &Renderer, shadows: ShadowSetting, sprites: &SpriteLayer) -> Self {
+        let (atlas_color, atlas_normal) = sprites.atlas();
+
+        // Same lighting as sprites, so switching between them is seamless.
+        let mut frag = "#version 300 es\n".to_owned();
+        frag += shadows.shader_define();
+        frag += include_str!("shaders/sprite.frag");
+
+        let shader = Shader::new(renderer, include_str!("shaders/ship.vert"), &frag);
+        let shadow_shader = renderer.create_shader(
+            include_str!("shaders/ship_shadow.vert"),
+            include_str!("shaders/shadow.frag"),
+        );
+
+        Self {
+            atlas_color,
+            atlas_normal,
+            buffer: TriangleBuffer::new(renderer),
+            buffered: false,
+            mesh: MeshBuilder::new(),
+            shader,
+            shadow_shader,
+        }
+    }
+
+    /// Draws a hull with its deck at `altitude` and its keel `height` below that. `uvs` and
+    /// `aspect` are those of the ship's sprite (see [`SpriteLayer::uvs`]), and the other
+    /// parameters are the same as for [`SpriteLayer::draw`].
+    pub fn draw(
+        &mut self,
+        uvs: [Vec2; 4],
+        aspect: f32,
+        center: Vec2,
+        dimensions: Vec2,
+        angle: f32,
+        altitude: f32,
+        height: f32,
+        tilt: Vec2,
+    ) {
+        let mut matrix = Mat3::from_scale_angle_translation(dimensions, angle, center);
+        if tilt != Vec2::ZERO {
+            // Same as sprites, so hulls lean the same way as their turrets.
+            matrix *= Mat3::from_cols(
+                vec3(1.0 - tilt.x.abs() * 0.5, tilt.y * 0.25, 0.0),
+                vec3(0.0, 1.0 - tilt.y.abs() * 0.5, 0.0),
+                Vec3::Z,
+            );
+        }
+
+        let tangent = Mat3::from_angle(angle).transform_vector2(Vec2::X);
+
+        // Sprites may be wider than their ship.
+        let sprite_scale = vec2(1.0, dimensions.y * aspect / dimensions.x);
+        let uv = |pos: Vec2| {
+            let s = pos * sprite_scale + 0.5;
+            uvs[0].lerp(uvs[1], s.x).lerp(uvs[3].lerp(uvs[2], s.x), s.y)
+        };
+
+        // Deck ring followed by keel ring.
+        let start = self.mesh.vertices.len();
+        for z in [altitude, altitude - height] {
+            self.mesh
+                .vertices
+                .extend(HULL.into_iter().map(|pos| ShipVertex {
+                    pos: matrix.transform_point2(pos).extend(z),
+                    uv: uv(pos),
+                    tangent,
+                }));
+        }
+
+        let n = HULL.len();
+        let index = |i: usize| (start + i) as u16;
+        for i in 1..n - 1 {
+            self.mesh.push_triangle([index(0), index(i), index(i + 1)]);
+        }
+        for i in 0..n {
+            let j = (i + 1) % n;
+            self.mesh
+                .push_quad([index(n + i), index(n + j), index(j), index(i)]);
+        }
+    }
+
+    /// Buffers `mesh` once per frame, since it is rendered twice.
+    fn buffer_once(&mut self, renderer: &Renderer) {
+        if !self.buffered {
+            self.buffer.buffer_mesh(renderer, &self.mesh);
+            self.buffered = true;
+        }
+    }
+}
+
+impl Layer for ShipLayer {
+    const ALPHA: bool = true;
+}
+
+impl RenderLayer<&ShadowParams> for ShipLayer {
+    fn render(&mut self, renderer: &Renderer, params: &ShadowParams) {
+        if self.mesh.is_empty() {
+            return;
+        }
+
+        // TODO depth layer.
+        renderer.set_depth_test(true);
+
+        self.buffer_once(renderer);
+        if let Some(shader) = self.shadow_shader.bind(renderer) {
+            params.camera.prepare_without_camera_pos(&shader);
+            self.buffer.bind(renderer).draw();
+        }
+
+        // TODO depth layer.
+        renderer.set_depth_test(false);
+    }
+}
+
+impl RenderLayer<&ShadowResult<&Mk48Params>> for ShipLayer {
+    fn render(&mut self, renderer: &Renderer, result: &ShadowResult<&Mk48Params>) {
+        if self.mesh.is_empty() {
+            return;
+        }
+
+        self.buffer_once(renderer);
+        if let Some(shader) = self.shader.bind(renderer) {
+            result.prepare_shadows(&shader);
+            let params = &result.params;
+
+            params.camera.prepare(&shader);
+            shader.uniform("uColor", &self.atlas_color);
+            shader.uniform("uNormal", &self.atlas_normal);
+            shader.uniform("uSun", params.weather.sun);
+
+            self.buffer.bind(renderer).draw();
+        }
+
+        // Always clear mesh even if shader wasn't bound.
+        self.mesh.clear();
+        self.buffered = false;
+    }
+}
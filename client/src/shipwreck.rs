@@ -0,0 +1,35 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::protocol::Shipwreck;
+use glam::vec4;
+use renderer2d::GraphicLayer;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Must match the server's salvage radius.
+const SALVAGE_RADIUS: f32 = 40.0;
+
+/// Draws shipwrecks detected by the player's submarine, and progress salvaging them.
+pub fn draw_shipwrecks(shipwrecks: &[Shipwreck], zoom: f32, graphics: &mut GraphicLayer) {
+    let thickness = 0.003 * zoom;
+    for shipwreck in shipwrecks {
+        graphics.draw_filled_circle(shipwreck.position, 6.0, vec4(0.45, 0.35, 0.25, 0.8));
+        graphics.draw_circle(
+            shipwreck.position,
+            SALVAGE_RADIUS,
+            thickness,
+            vec4(0.45, 0.35, 0.25, 0.4),
+        );
+
+        if let Some(salvage) = shipwreck.salvage {
+            // Clockwise from the top, like a clock.
+            graphics.draw_arc(
+                shipwreck.position,
+                SALVAGE_RADIUS,
+                FRAC_PI_2 - salvage.clamp(0.0, 1.0) * TAU..FRAC_PI_2,
+                thickness * 2.0,
+                vec4(1.0, 0.85, 0.3, 0.9),
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::protocol::Smuggling;
+use glam::{vec2, vec4, Vec2, Vec4};
+use renderer2d::{Camera2d, GraphicLayer, TextLayer};
+use std::f32::consts::FRAC_PI_2;
+
+/// Draws markers at the smuggled cargo and the port it must reach. Markers for positions outside
+/// the screen are drawn as arrows at the edge of the screen, pointing toward them.
+pub fn draw_smuggling(
+    smuggling: &Smuggling,
+    carrying: bool,
+    camera: &Camera2d,
+    zoom: f32,
+    graphics: &mut GraphicLayer,
+    text: &mut TextLayer,
+) {
+    let cargo_color = vec4(1.0, 0.8, 0.2, 0.8);
+    let port_color = vec4(0.3, 1.0, 0.5, 0.8);
+
+    // The carrier is headed to the port, so doesn't need to be told where they are.
+    if !carrying {
+        draw_marker(smuggling.cargo, cargo_color, camera, zoom, graphics, text);
+    }
+    draw_marker(
+        smuggling.port,
+        if carrying {
+            port_color
+        } else {
+            port_color * vec4(1.0, 1.0, 1.0, 0.5)
+        },
+        camera,
+        zoom,
+        graphics,
+        text,
+    );
+}
+
+fn draw_marker(
+    position: Vec2,
+    color: Vec4,
+    camera: &Camera2d,
+    zoom: f32,
+    graphics: &mut GraphicLayer,
+    text: &mut TextLayer,
+) {
+    // Keep arrows this far inside the edge of the screen (in view space).
+    const EDGE: f32 = 0.9;
+
+    let view = camera.to_view_position(position);
+    let radius = 0.04 * zoom;
+    let text_color = color.to_array().map(|c| (c * 255.0) as u8);
+
+    if view.abs().max_element() <= EDGE {
+        graphics.draw_circle(position, radius, radius * 0.2, color);
+        return;
+    }
+
+    let clamped = view / view.abs().max_element() * EDGE;
+    let center = camera.to_world_position(clamped);
+    let direction = view - clamped;
+    graphics.draw_triangle(
+        center,
+        Vec2::splat(radius),
+        direction.y.atan2(direction.x) - FRAC_PI_2,
+        color,
+    );
+
+    let distance = position.distance(camera.to_world_position(Vec2::ZERO));
+    text.draw(
+        &format!("{:.1}km", distance * 0.001),
+        center - direction.normalize() * radius * 1.5 - vec2(0.0, radius * 0.25),
+        radius * 0.75,
+        text_color,
+    );
+}
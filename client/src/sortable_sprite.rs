@@ -17,8 +17,12 @@ pub struct SortableSprite {
     pub entity_id: Option<EntityId>,
     pub frame: Option<usize>,
     pub height: f32,
+    /// Can be drawn as a [`ShipLayer`][`crate::ship::ShipLayer`] hull instead of a sprite.
+    pub hull: bool,
     pub shadow_height: f32,
     pub sprite: &'static str,
+    /// Pitch (x) and roll (y) due to waves.
+    pub tilt: Vec2,
     pub transform: Transform,
 }
 
@@ -31,18 +35,26 @@ impl SortableSprite {
         altitude: f32,
         alpha: f32,
     ) -> Self {
+        let data = entity_type.data();
         let height = Self::deck_height(entity_type);
         let altitude = height + altitude;
 
         Self {
             alpha,
             altitude,
-            dimensions: entity_type.data().dimensions(),
+            dimensions: data.dimensions(),
             entity_id: Some(entity_id),
             frame: None,
             height,
+            // Submarines have no deck height to extrude, and krakens aren't hull shaped.
+            hull: data.kind == EntityKind::Boat
+                && !matches!(
+                    data.sub_kind,
+                    EntitySubKind::Kraken | EntitySubKind::Submarine
+                ),
             shadow_height: altitude,
             sprite: entity_type.as_str(),
+            tilt: Vec2::ZERO,
             transform,
         }
     }
@@ -68,8 +80,10 @@ impl SortableSprite {
             entity_id: Some(entity_id),
             frame: None,
             height,
+            hull: false,
             shadow_height,
             sprite: entity_type.as_str(),
+            tilt: Vec2::ZERO,
             transform,
         }
     }
@@ -83,8 +97,10 @@ impl SortableSprite {
             entity_id: None,
             frame: Some(animation.frame(time_seconds)),
             height: 0.0,
+            hull: false,
             shadow_height: 0.0, // Animations don't have height so they don't have shadows.
             sprite: animation.name,
+            tilt: Vec2::ZERO,
             transform: Transform::from_position(animation.position),
         }
     }
@@ -3,13 +3,13 @@
 
 use crate::game::Mk48Params;
 use crate::settings::ShadowSetting;
-use glam::{Mat3, Vec2, Vec4};
+use glam::{vec3, Mat3, Vec2, Vec3, Vec4};
 use renderer::{
     derive_vertex, Layer, MeshBuilder, RenderLayer, Renderer, Shader, Texture, TextureFormat,
     TriangleBuffer,
 };
 use renderer3d::ShadowResult;
-use sprite_sheet::UvSpriteSheet;
+use sprite_sheet::{UvSprite, UvSpriteSheet};
 
 derive_vertex!(
     struct SpriteVertex {
@@ -78,7 +78,28 @@ impl SpriteLayer {
             .len()
     }
 
-    /// Draws a sprite. `angle` is in radians.
+    /// Gets the color and normal atlases, for sharing with other layers.
+    pub fn atlas(&self) -> (Texture, Texture) {
+        (self.atlas_color.clone(), self.atlas_normal.clone())
+    }
+
+    /// Gets the texture coordinates and aspect ratio of a (non-animated) sprite.
+    pub fn uvs(&self, sprite: &str) -> ([Vec2; 4], f32) {
+        let sprite = self.sprite(sprite);
+        (sprite.uvs, sprite.aspect)
+    }
+
+    /// Gets a (non-animated) sprite, falling back to the contact sprite if the atlas hasn't been
+    /// repacked since the sprite was added.
+    fn sprite(&self, sprite: &str) -> &UvSprite {
+        self.sheet
+            .sprites
+            .get(sprite)
+            .unwrap_or_else(|| &self.sheet.sprites["contact"])
+    }
+
+    /// Draws a sprite. `angle` is in radians. `tilt` is the pitch (x) and roll (y) due to waves,
+    /// as a fraction of the range `-1.0..=1.0` (zero means level).
     pub fn draw(
         &mut self,
         sprite: &str,
@@ -89,16 +110,18 @@ impl SpriteLayer {
         alpha: f32,
         altitude: f32,
         height: f32,
+        tilt: Vec2,
     ) {
         if alpha == 0.0 {
             return; // Reserved for shadows.
         }
         self.draw_inner(
-            sprite, frame, center, dimensions, angle, alpha, altitude, height,
+            sprite, frame, center, dimensions, angle, alpha, altitude, height, tilt,
         );
     }
 
-    /// Draws a sprite shadow. `angle` is in radians.
+    /// Draws a sprite shadow. `angle` is in radians. `tilt` is the same as for
+    /// [`draw`][`Self::draw`].
     pub fn draw_shadow(
         &mut self,
         sprite: &str,
@@ -106,8 +129,11 @@ impl SpriteLayer {
         center: Vec2,
         dimensions: Vec2,
         angle: f32,
+        tilt: Vec2,
     ) {
-        self.draw_inner(sprite, frame, center, dimensions, angle, 0.0, 0.0, 0.0);
+        self.draw_inner(
+            sprite, frame, center, dimensions, angle, 0.0, 0.0, 0.0, tilt,
+        );
     }
 
     /// Draws a sprite or a shadow depending on if the alpha > 0.0.
@@ -121,21 +147,33 @@ impl SpriteLayer {
         alpha: f32,
         altitude: f32,
         height: f32,
+        tilt: Vec2,
     ) {
         let sprite = if let Some(frame) = frame {
             let animation = &self.sheet.animations.get(sprite).unwrap();
             &animation[frame]
         } else {
-            self.sheet.sprites.get(sprite).expect(sprite)
+            self.sprite(sprite)
         };
 
         // TODO make sprites and entities have same aspect ratio.
-        let matrix = Mat3::from_scale_angle_translation(
+        let mut matrix = Mat3::from_scale_angle_translation(
             Vec2::new(dimensions.x, dimensions.x / sprite.aspect),
             angle,
             center,
         );
 
+        if tilt != Vec2::ZERO {
+            // Pitching (x) foreshortens the length, and rolling (y) foreshortens the width and
+            // swings the ends sideways as the deck leans.
+            let rock = Mat3::from_cols(
+                vec3(1.0 - tilt.x.abs() * 0.5, tilt.y * 0.25, 0.0),
+                vec3(0.0, 1.0 - tilt.y.abs() * 0.5, 0.0),
+                Vec3::Z,
+            );
+            matrix *= rock;
+        }
+
         let positions = [
             Vec2::new(-0.5, -0.5),
             Vec2::new(0.5, -0.5),
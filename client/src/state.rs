@@ -5,21 +5,45 @@ use crate::animation::Animation;
 use crate::interpolated_contact::InterpolatedContact;
 use client_util::apply::Apply;
 use common::contact::Contact;
+use common::current::Currents;
 use common::death_reason::DeathReason;
-use common::entity::EntityId;
-use common::protocol::Update;
+use common::entity::{EntityId, EntityOverrides};
+use common::mission::DailyMissions;
+use common::protocol::{Round, Shipwreck, Smuggling, Update, Zone};
+use common::stats::LifeStats;
 use common::terrain::Terrain;
+use common::world::{Biome, Season};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// State associated with game server connection. Reset when connection is reset.
 pub struct Mk48State {
     pub animations: Vec<Animation>,
+    pub biome: Biome,
+    /// Whether the player's boat may still use its one free respec.
+    pub can_respec: bool,
+    /// Armament loadout of the player's boat.
+    pub loadout: u8,
     pub contacts: HashMap<EntityId, InterpolatedContact>,
+    /// Aircraft under the player's direct control, if any.
+    pub controlled_aircraft: Option<EntityId>,
+    /// Ocean currents, which drift collectibles, mines, and idle boats.
+    pub currents: Arc<Currents>,
     pub death_reason: Option<DeathReason>,
     pub entity_id: Option<EntityId>,
+    /// Today's missions, and the player's progress towards them.
+    pub missions: Option<DailyMissions>,
+    /// Current round, if the arena is in blitz mode.
+    pub round: Option<Round>,
     pub score: u32,
+    pub season: Season,
+    pub shipwrecks: Box<[Shipwreck]>,
+    pub smuggling: Option<Smuggling>,
+    /// Statistics about the player's last boat, if it sank.
+    pub stats: Option<LifeStats>,
     pub terrain: Terrain,
     pub world_radius: f32,
+    pub zones: Box<[Zone]>,
     terrain_reset: bool,
 }
 
@@ -27,13 +51,25 @@ impl Default for Mk48State {
     fn default() -> Self {
         Self {
             animations: Vec::new(),
+            biome: Biome::default(),
+            can_respec: false,
+            loadout: 0,
             contacts: HashMap::new(),
+            controlled_aircraft: None,
+            currents: Arc::default(),
             death_reason: None,
             entity_id: None,
+            missions: None,
+            round: None,
             score: 0,
+            season: Season::default(),
+            shipwrecks: Box::new([]),
+            smuggling: None,
+            stats: None,
             terrain: Terrain::default(),
             // Keep border off splash screen by assuming radius.
             world_radius: 10000.0,
+            zones: Box::new([]),
             terrain_reset: false,
         }
     }
@@ -46,6 +82,21 @@ impl Mk48State {
             .map(|id| &self.contacts.get(&id).unwrap().view)
     }
 
+    /// Returns the id of the contact the player steers, which is the aircraft under their direct
+    /// control (if any), otherwise their boat.
+    pub(crate) fn steered_id(&self) -> Option<EntityId> {
+        self.controlled_aircraft
+            .filter(|id| self.entity_id.is_some() && self.contacts.contains_key(id))
+            .or(self.entity_id)
+    }
+
+    /// Returns the "view" of the contact the player steers (see [`Self::steered_id`]), which the
+    /// camera follows.
+    pub(crate) fn steered_contact(&self) -> Option<&Contact> {
+        self.steered_id()
+            .map(|id| &self.contacts.get(&id).unwrap().view)
+    }
+
     pub(crate) fn player_interpolated_contact(&self) -> Option<&InterpolatedContact> {
         self.entity_id.map(|id| self.contacts.get(&id).unwrap())
     }
@@ -66,15 +117,38 @@ impl Apply<Update> for Mk48State {
     fn apply(&mut self, update: Update) {
         self.death_reason = update.death_reason;
 
-        // Didn't consume previous update (tabbed out) and now terrain updated state is invalid.
-        self.terrain_reset = !self.terrain.updated.is_empty();
+        // Merges with any previous update that wasn't consumed yet (e.g. tabbed out).
         self.terrain.apply_update(&update.terrain);
+        if let Some(currents) = update.currents {
+            self.currents = currents;
+        }
 
         self.world_radius = update.world_radius;
         self.score = update.score;
+        self.can_respec = update.can_respec;
+        self.loadout = update.loadout;
+        self.round = update.round;
+        if let Some(missions) = update.missions {
+            self.missions = Some(missions);
+        }
+        if let Some(stats) = update.stats {
+            self.stats = Some(stats);
+        }
+        self.controlled_aircraft = update.controlled_aircraft;
+        self.season = update.season;
+        self.biome = update.biome;
+        self.shipwrecks = update.shipwrecks;
+        self.smuggling = update.smuggling;
+        self.zones = update.zones;
+
+        if let Some(entity_overrides) = update.entity_overrides {
+            entity_overrides.apply();
+        }
     }
 
     fn reset(&mut self) {
+        // The next server may not have the same balance.
+        EntityOverrides::default().apply();
         *self = Self {
             terrain_reset: true,
             ..Self::default()
@@ -1,15 +1,19 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::game::{ACTIVE_KEY, SURFACE_KEY};
+use client_util::keyboard::Key;
 use common::death_reason::DeathReason;
 use common::entity::{EntityKind, EntitySubKind, EntityType};
+use common::protocol::Doctrine;
 use core_protocol::id::LanguageId;
 use core_protocol::id::LanguageId::*;
 use core_protocol::name::PlayerAlias;
+use engine_macros::translations;
+use renderer2d::TextLayer;
 use std::fmt::Display;
 use yew_frontend::frontend::RewardedAd;
 use yew_frontend::s;
+use yew_frontend::translation::translated;
 
 pub trait Mk48Translation: Sized {
     fn death_reason(self, death_reason: &DeathReason) -> String;
@@ -18,10 +22,23 @@ pub trait Mk48Translation: Sized {
     }
     s!(death_reason_border);
     fn death_reason_collision(self, thing: impl Display) -> String;
+    s!(death_reason_crushed);
+    s!(death_reason_fire);
+    s!(death_reason_flooding);
+    /// Like [`Self::death_reason`], but mentions if the boat sank its killer too.
+    fn death_reason_maybe_mutual(self, death_reason: &DeathReason, mutual: bool) -> String {
+        match death_reason.killer().filter(|_| mutual) {
+            Some(alias) => self.death_reason_mutual(alias),
+            None => self.death_reason(death_reason),
+        }
+    }
+    fn death_reason_mutual(self, alias: PlayerAlias) -> String;
     fn death_reason_obstacle(self, entity_type: EntityType) -> String {
         self.death_reason_collision(&entity_type.data().label)
     }
     fn death_reason_ram(self, alias: PlayerAlias) -> String;
+    s!(death_reason_round_ended);
+    s!(death_reason_season_ended);
     s!(death_reason_terrain);
     fn death_reason_weapon(self, alias: PlayerAlias, entity_type: EntityType) -> String;
 
@@ -38,6 +55,7 @@ pub trait Mk48Translation: Sized {
             (EntityKind::Boat, EntitySubKind::Dredger) => self.entity_boat_dredger_name(),
             (EntityKind::Boat, EntitySubKind::Hovercraft) => self.entity_boat_hovercraft_name(),
             (EntityKind::Boat, EntitySubKind::Icebreaker) => self.entity_boat_icebreaker_name(),
+            (EntityKind::Boat, EntitySubKind::Kraken) => self.entity_boat_kraken_name(),
             (EntityKind::Boat, EntitySubKind::Lcs) => self.entity_boat_lcs_name(),
             (EntityKind::Boat, EntitySubKind::Minelayer) => self.entity_boat_minelayer_name(),
             (EntityKind::Boat, EntitySubKind::Mtb) => self.entity_boat_mtb_name(),
@@ -55,6 +73,7 @@ pub trait Mk48Translation: Sized {
             }
             (EntityKind::Weapon, EntitySubKind::Mine) => self.entity_weapon_mine_name(),
             (EntityKind::Weapon, EntitySubKind::Missile) => self.entity_weapon_missile_name(),
+            (EntityKind::Weapon, EntitySubKind::Net) => self.entity_weapon_net_name(),
             (EntityKind::Weapon, EntitySubKind::RocketTorpedo) => {
                 self.entity_weapon_rocket_torpedo_name()
             }
@@ -80,6 +99,7 @@ pub trait Mk48Translation: Sized {
             (EntityKind::Boat, EntitySubKind::Dredger) => self.entity_boat_dredger_hint(),
             (EntityKind::Boat, EntitySubKind::Hovercraft) => self.entity_boat_hovercraft_hint(),
             (EntityKind::Boat, EntitySubKind::Icebreaker) => self.entity_boat_icebreaker_hint(),
+            (EntityKind::Boat, EntitySubKind::Kraken) => self.entity_boat_kraken_hint(),
             (EntityKind::Boat, EntitySubKind::Lcs) => self.entity_boat_lcs_hint(),
             (EntityKind::Boat, EntitySubKind::Minelayer) => self.entity_boat_minelayer_hint(),
             (EntityKind::Boat, EntitySubKind::Mtb) => self.entity_boat_mtb_hint(),
@@ -113,6 +133,8 @@ pub trait Mk48Translation: Sized {
     s!(entity_boat_hovercraft_name);
     s!(entity_boat_icebreaker_hint);
     s!(entity_boat_icebreaker_name);
+    s!(entity_boat_kraken_hint);
+    s!(entity_boat_kraken_name);
     s!(entity_boat_lcs_hint);
     s!(entity_boat_lcs_name);
     s!(entity_boat_minelayer_hint);
@@ -132,6 +154,7 @@ pub trait Mk48Translation: Sized {
     s!(entity_weapon_depth_charge_name);
     s!(entity_weapon_mine_name);
     s!(entity_weapon_missile_name);
+    s!(entity_weapon_net_name);
     s!(entity_weapon_rocket_torpedo_name);
     s!(entity_weapon_rocket_name);
     s!(entity_weapon_sam_name);
@@ -143,17 +166,51 @@ pub trait Mk48Translation: Sized {
     s!(instruction_zoom_mouse);
     s!(instruction_zoom_touch);
 
+    s!(effect_fire_label);
+    s!(effect_flooding_label);
+
+    s!(module_propulsion_label);
+    s!(module_steering_label);
+    fn module_turret_label(self, turret: usize) -> String;
+
+    fn purchase_cost_label(self, coins: u32) -> String;
+    s!(purchase_label);
+    s!(purchase_reload_label);
+    s!(purchase_repair_label);
+
+    fn round_label(self, number: u32, time: &str) -> String;
+    fn round_next_label(self, time: &str) -> String;
+    fn round_results_label(self, number: u32) -> String;
+
     s!(sensor_active_label);
-    fn sensor_active_hint(self, sensors: &str) -> String;
+    fn sensor_active_hint(self, sensors: &str, key: Key) -> String;
     s!(sensor_radar_label);
     s!(sensor_sonar_label);
 
+    s!(ship_doctrine_aircraft_first_label);
+    s!(ship_doctrine_hold_fire_label);
+    fn ship_doctrine_label(self, doctrine: Doctrine) -> &'static str {
+        match doctrine {
+            Doctrine::AircraftFirst => self.ship_doctrine_aircraft_first_label(),
+            Doctrine::MissilesFirst => self.ship_doctrine_missiles_first_label(),
+            Doctrine::HoldFire => self.ship_doctrine_hold_fire_label(),
+        }
+    }
+    s!(ship_doctrine_missiles_first_label);
+    s!(ship_fly_aircraft_label);
     s!(ship_surface_label);
-    fn ship_surface_hint(self) -> String;
+    fn ship_surface_hint(self, key: Key) -> String;
 
     s!(team_fleet_label);
     s!(team_fleet_name_placeholder);
 
+    // CSS font family list for in-world text, preferring glyphs appropriate for the language.
+    s!(text_fonts);
+
+    s!(upgrade_dead_end_label);
+    s!(upgrade_respec_label);
+    s!(upgrade_tree_label);
+
     fn rewarded_ad(self, rewarded_ad: &RewardedAd) -> &'static str {
         match rewarded_ad {
             RewardedAd::Available { .. } => self.rewarded_ad_available(),
@@ -168,6 +225,7 @@ pub trait Mk48Translation: Sized {
     s!(rewarded_ad_error);
 }
 
+#[translations("../translations")]
 impl Mk48Translation for LanguageId {
     /*
     fn example(self) -> &'static str {
@@ -209,8 +267,13 @@ impl Mk48Translation for LanguageId {
         match death_reason {
             &DeathReason::Boat(alias) => self.death_reason_boat(alias),
             DeathReason::Border => self.death_reason_border().to_owned(),
+            DeathReason::Crushed => self.death_reason_crushed().to_owned(),
+            DeathReason::Fire => self.death_reason_fire().to_owned(),
+            DeathReason::Flooding => self.death_reason_flooding().to_owned(),
             &DeathReason::Obstacle(entity_type) => self.death_reason_obstacle(entity_type),
             &DeathReason::Ram(alias) => self.death_reason_ram(alias),
+            DeathReason::RoundEnded => self.death_reason_round_ended().to_owned(),
+            DeathReason::SeasonEnded => self.death_reason_season_ended().to_owned(),
             DeathReason::Terrain => self.death_reason_terrain().to_owned(),
             &DeathReason::Weapon(alias, entity_type) => {
                 self.death_reason_weapon(alias, entity_type)
@@ -256,6 +319,108 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn death_reason_round_ended(self) -> &'static str {
+        match self {
+            Arabic => "انتهت الجولة!",
+            Bork => "Round borked!",
+            English => "The round ended!",
+            French => "La manche est terminée!",
+            German => "Die Runde ist vorbei!",
+            Hindi => "राउंड समाप्त हुआ!",
+            Italian => "Il round è finito!",
+            Japanese => "ラウンド終了!",
+            Russian => "Раунд окончен!",
+            SimplifiedChinese => "本轮结束!",
+            Spanish => "¡La ronda terminó!",
+            Vietnamese => "Vòng đấu đã kết thúc!",
+        }
+    }
+
+    fn death_reason_season_ended(self) -> &'static str {
+        match self {
+            Arabic => "انتهى الموسم! عالم جديد بانتظارك.",
+            Bork => "Season borked! New world awaits.",
+            English => "The season ended! A new world awaits.",
+            French => "La saison est terminée! Un nouveau monde vous attend.",
+            German => "Die Saison ist vorbei! Eine neue Welt wartet.",
+            Hindi => "सीज़न समाप्त हुआ! एक नई दुनिया इंतज़ार कर रही है।",
+            Italian => "La stagione è finita! Un nuovo mondo ti aspetta.",
+            Japanese => "シーズン終了!新しい世界が待っています。",
+            Russian => "Сезон окончен! Вас ждёт новый мир.",
+            SimplifiedChinese => "赛季结束!新世界等着你。",
+            Spanish => "¡La temporada terminó! Un nuevo mundo te espera.",
+            Vietnamese => "Mùa giải đã kết thúc! Một thế giới mới đang chờ.",
+        }
+    }
+
+    fn death_reason_crushed(self) -> &'static str {
+        match self {
+            Arabic => "سحقها الضغط!",
+            Bork => "Borked by the deep!",
+            English => "Crushed by the pressure!",
+            French => "Écrasé par la pression!",
+            German => "Vom Druck zerquetscht!",
+            Hindi => "दबाव से कुचल गया!",
+            Italian => "Schiacciato dalla pressione!",
+            Japanese => "水圧で圧壊!",
+            Russian => "Раздавлен давлением!",
+            SimplifiedChinese => "被水压压碎!",
+            Spanish => "¡Aplastado por la presión!",
+            Vietnamese => "Bị áp suất nghiền nát!",
+        }
+    }
+
+    fn death_reason_fire(self) -> &'static str {
+        match self {
+            Arabic => "التهمتها النيران!",
+            Bork => "Borked to a crisp!",
+            English => "Burned to the waterline!",
+            French => "Consumé par les flammes!",
+            German => "Bis zur Wasserlinie ausgebrannt!",
+            Hindi => "आग में जलकर नष्ट!",
+            Italian => "Divorato dalle fiamme!",
+            Japanese => "火災で焼失!",
+            Russian => "Сгорел дотла!",
+            SimplifiedChinese => "被大火吞噬!",
+            Spanish => "¡Consumido por las llamas!",
+            Vietnamese => "Bị thiêu rụi!",
+        }
+    }
+
+    fn death_reason_flooding(self) -> &'static str {
+        match self {
+            Arabic => "غرقت بسبب تسرب المياه!",
+            Bork => "Borked by the bilge!",
+            English => "Foundered from flooding!",
+            French => "Sombré sous les voies d'eau!",
+            German => "Durch Wassereinbruch gesunken!",
+            Hindi => "पानी भरने से डूब गया!",
+            Italian => "Affondato per allagamento!",
+            Japanese => "浸水により沈没!",
+            Russian => "Затонул от пробоины!",
+            SimplifiedChinese => "因进水沉没!",
+            Spanish => "¡Hundido por inundación!",
+            Vietnamese => "Chìm do ngập nước!",
+        }
+    }
+
+    fn death_reason_mutual(self, alias: PlayerAlias) -> String {
+        match self {
+            Arabic => format!("دمار متبادل مع {alias}!"),
+            Bork => format!("Mutual borkstruction with {alias}!"),
+            English => format!("Mutual destruction with {alias}!"),
+            French => format!("Destruction mutuelle avec {alias}!"),
+            German => format!("Gegenseitige Zerstörung mit {alias}!"),
+            Hindi => format!("{alias} के साथ पारस्परिक विनाश!"),
+            Italian => format!("Distruzione reciproca con {alias}!"),
+            Japanese => format!("{alias}と相打ち!"),
+            Russian => format!("Взаимное уничтожение с {alias}!"),
+            SimplifiedChinese => format!("与{alias}同归于尽！"),
+            Spanish => format!("¡Destrucción mutua con {alias}!"),
+            Vietnamese => format!("Cùng hủy diệt với {alias}!"),
+        }
+    }
+
     fn death_reason_ram(self, alias: PlayerAlias) -> String {
         match self {
             Arabic => format!("صدم {alias}!"),
@@ -648,6 +813,40 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn entity_boat_kraken_hint(self) -> &'static str {
+        match self {
+            Arabic => "مخالبك تهاجم القوارب القريبة تلقائيًا!",
+            Bork => "Bork has many borky arms!",
+            English => "Your tentacles automatically attack nearby boats!",
+            French => "Vos tentacules attaquent automatiquement les bateaux proches!",
+            German => "Deine Tentakel greifen automatisch nahe Schiffe an!",
+            Hindi => "आपके तंबू अपने आप पास की नावों पर हमला करते हैं!",
+            Italian => "I tuoi tentacoli attaccano automaticamente le barche vicine!",
+            Japanese => "触手が近くのボートを自動的に攻撃します!",
+            Russian => "Ваши щупальца автоматически атакуют ближайшие лодки!",
+            SimplifiedChinese => "你的触手会自动攻击附近的船只!",
+            Spanish => "¡Tus tentáculos atacan automáticamente a los barcos cercanos!",
+            Vietnamese => "Xúc tu của bạn tự động tấn công thuyền gần đó!",
+        }
+    }
+
+    fn entity_boat_kraken_name(self) -> &'static str {
+        match self {
+            Arabic => "كراكن",
+            Bork => "big wiggly bork",
+            English => "kraken",
+            French => "kraken",
+            German => "Krake",
+            Hindi => "क्रैकन",
+            Italian => "kraken",
+            Japanese => "クラーケン",
+            Russian => "кракен",
+            SimplifiedChinese => "北海巨妖",
+            Spanish => "kraken",
+            Vietnamese => "thủy quái kraken",
+        }
+    }
+
     fn entity_boat_lcs_hint(self) -> &'static str {
         match self {
             Arabic => {
@@ -815,18 +1014,18 @@ impl Mk48Translation for LanguageId {
 
     fn entity_boat_submarine_hint(self) -> &'static str {
         match self {
-            Arabic => "يمكن للقارب الخاص بك تسليم الأسلحة من تحت الماء!",
-            Bork => "Bork can bork other borks from underwater!",
-            English => "Your boat can deliver weapons from underwater!",
-            French => "Votre bateau peut livrer des armes sous l'eau!",
-            German => "Dein Schiff kann Waffen unterwasser abfeuern!",
-            Hindi => "आपकी नाव पानी के नीचे से हथियार पहुंचा सकती है!",
-            Italian => "La tua barca può lanciare armi sott'acqua!",
-            Japanese => "あなたのボートは水中から武器を届けることができます!",
-            Russian => "Ваша лодка может доставлять оружие из-под воды!",
-            SimplifiedChinese => "你的船可以从水下运送武器!",
-            Spanish => "¡Tu barco puede lanzar armas desde el agua!",
-            Vietnamese => "Thuyền của bạn có thể cung cấp vũ khí từ dưới nước!",
+            Arabic => "يمكن للقارب الخاص بك تسليم الأسلحة من تحت الماء! لا تستطيع الطائرات رصدك إلا على عمق المنظار أو بعد التجويف مؤخرًا.",
+            Bork => "Bork can bork other borks from underwater! Flying borks only see you at periscope bork or after you bubble.",
+            English => "Your boat can deliver weapons from underwater! Aircraft can only spot you at periscope depth or shortly after cavitating.",
+            French => "Votre bateau peut livrer des armes sous l'eau! Les avions ne vous repèrent qu'à l'immersion périscopique ou peu après avoir cavité.",
+            German => "Dein Schiff kann Waffen unterwasser abfeuern! Flugzeuge entdecken dich nur auf Periskoptiefe oder kurz nach Kavitation.",
+            Hindi => "आपकी नाव पानी के नीचे से हथियार पहुंचा सकती है! विमान आपको केवल पेरिस्कोप गहराई पर या कैविटेशन के तुरंत बाद देख सकते हैं।",
+            Italian => "La tua barca può lanciare armi sott'acqua! Gli aerei ti individuano solo a quota periscopica o poco dopo la cavitazione.",
+            Japanese => "あなたのボートは水中から武器を届けることができます! 航空機は潜望鏡深度にいるか、キャビテーション直後にしかあなたを発見できません。",
+            Russian => "Ваша лодка может доставлять оружие из-под воды! Авиация обнаружит вас только на перископной глубине или вскоре после кавитации.",
+            SimplifiedChinese => "你的船可以从水下运送武器! 飞机只能在潜望镜深度或刚产生空化后发现你。",
+            Spanish => "¡Tu barco puede lanzar armas desde el agua! Los aviones solo te detectan a cota periscópica o poco después de cavitar.",
+            Vietnamese => "Thuyền của bạn có thể cung cấp vũ khí từ dưới nước! Máy bay chỉ phát hiện bạn ở độ sâu kính tiềm vọng hoặc ngay sau khi tạo bọt xâm thực.",
         }
     }
 
@@ -983,6 +1182,23 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn entity_weapon_net_name(self) -> &'static str {
+        match self {
+            Arabic => "شبكة طوربيد",
+            Bork => "bork net",
+            English => "torpedo net",
+            French => "filet anti-torpilles",
+            German => "Torpedonetz",
+            Hindi => "टारपीडो जाल",
+            Italian => "rete antisiluro",
+            Japanese => "防雷網",
+            Russian => "противоторпедная сеть",
+            SimplifiedChinese => "防鱼雷网",
+            Spanish => "red antitorpedos",
+            Vietnamese => "lưới chống ngư lôi",
+        }
+    }
+
     fn entity_weapon_rocket_torpedo_name(self) -> &'static str {
         match self {
             Arabic => "صاروخ طوربيد",
@@ -1136,6 +1352,210 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn effect_fire_label(self) -> &'static str {
+        match self {
+            Arabic => "حريق على متن السفينة!",
+            Bork => "Bork fire!",
+            English => "Fire on board!",
+            French => "Incendie à bord!",
+            German => "Feuer an Bord!",
+            Hindi => "जहाज़ पर आग!",
+            Italian => "Incendio a bordo!",
+            Japanese => "艦内火災!",
+            Russian => "Пожар на борту!",
+            SimplifiedChinese => "舰上起火!",
+            Spanish => "¡Fuego a bordo!",
+            Vietnamese => "Cháy trên tàu!",
+        }
+    }
+
+    fn effect_flooding_label(self) -> &'static str {
+        match self {
+            Arabic => "تسرب المياه! أبطئ لضخها.",
+            Bork => "Bilge borked! Slow down to bork it out.",
+            English => "Flooding! Slow down to pump it out.",
+            French => "Voie d'eau! Ralentissez pour pomper.",
+            German => "Wassereinbruch! Langsamer fahren zum Lenzen.",
+            Hindi => "पानी भर रहा है! बाहर निकालने के लिए धीमे चलें।",
+            Italian => "Allagamento! Rallenta per pompare fuori l'acqua.",
+            Japanese => "浸水! 減速して排水せよ。",
+            Russian => "Пробоина! Сбавьте ход, чтобы откачать воду.",
+            SimplifiedChinese => "进水! 减速以排水。",
+            Spanish => "¡Inundación! Reduce la velocidad para achicar.",
+            Vietnamese => "Ngập nước! Giảm tốc để bơm nước ra.",
+        }
+    }
+
+    fn module_propulsion_label(self) -> &'static str {
+        match self {
+            Arabic => "الدفع معطل!",
+            Bork => "Engine borked!",
+            English => "Propulsion knocked out!",
+            French => "Propulsion hors service!",
+            German => "Antrieb ausgefallen!",
+            Hindi => "प्रणोदन ठप!",
+            Italian => "Propulsione fuori uso!",
+            Japanese => "推進装置損傷!",
+            Russian => "Двигатель выведен из строя!",
+            SimplifiedChinese => "推进系统受损!",
+            Spanish => "¡Propulsión inutilizada!",
+            Vietnamese => "Động cơ bị vô hiệu hóa!",
+        }
+    }
+
+    fn module_steering_label(self) -> &'static str {
+        match self {
+            Arabic => "التوجيه معطل!",
+            Bork => "Rudder borked!",
+            English => "Steering knocked out!",
+            French => "Gouvernail hors service!",
+            German => "Ruder ausgefallen!",
+            Hindi => "पतवार ठप!",
+            Italian => "Timone fuori uso!",
+            Japanese => "操舵装置損傷!",
+            Russian => "Руль выведен из строя!",
+            SimplifiedChinese => "舵机受损!",
+            Spanish => "¡Timón inutilizado!",
+            Vietnamese => "Bánh lái bị vô hiệu hóa!",
+        }
+    }
+
+    fn module_turret_label(self, turret: usize) -> String {
+        match self {
+            Arabic => format!("البرج {turret} معطل!"),
+            Bork => format!("Turret {turret} borked!"),
+            English => format!("Turret {turret} knocked out!"),
+            French => format!("Tourelle {turret} hors service!"),
+            German => format!("Turm {turret} ausgefallen!"),
+            Hindi => format!("बुर्ज {turret} ठप!"),
+            Italian => format!("Torretta {turret} fuori uso!"),
+            Japanese => format!("砲塔{turret}損傷!"),
+            Russian => format!("Башня {turret} выведена из строя!"),
+            SimplifiedChinese => format!("炮塔{turret}受损!"),
+            Spanish => format!("¡Torreta {turret} inutilizada!"),
+            Vietnamese => format!("Tháp pháo {turret} bị vô hiệu hóa!"),
+        }
+    }
+
+    fn purchase_cost_label(self, coins: u32) -> String {
+        match self {
+            Arabic => format!("{coins} عملة"),
+            Bork => format!("{coins} borks"),
+            English => format!("{coins} coins"),
+            French => format!("{coins} pièces"),
+            German => format!("{coins} Münzen"),
+            Hindi => format!("{coins} सिक्के"),
+            Italian => format!("{coins} monete"),
+            Japanese => format!("{coins}コイン"),
+            Russian => format!("{coins} монет"),
+            SimplifiedChinese => format!("{coins}枚金币"),
+            Spanish => format!("{coins} monedas"),
+            Vietnamese => format!("{coins} xu"),
+        }
+    }
+
+    fn purchase_label(self) -> &'static str {
+        match self {
+            Arabic => "المتجر",
+            Bork => "Bork shop",
+            English => "Supplies",
+            French => "Ravitaillement",
+            German => "Versorgung",
+            Hindi => "रसद",
+            Italian => "Rifornimenti",
+            Japanese => "補給",
+            Russian => "Снабжение",
+            SimplifiedChinese => "补给",
+            Spanish => "Suministros",
+            Vietnamese => "Tiếp tế",
+        }
+    }
+
+    fn purchase_reload_label(self) -> &'static str {
+        match self {
+            Arabic => "إعادة التعبئة",
+            Bork => "Rebork",
+            English => "Reload",
+            French => "Recharger",
+            German => "Nachladen",
+            Hindi => "फिर से लोड करें",
+            Italian => "Ricarica",
+            Japanese => "再装填",
+            Russian => "Перезарядка",
+            SimplifiedChinese => "装填",
+            Spanish => "Recargar",
+            Vietnamese => "Nạp đạn",
+        }
+    }
+
+    fn purchase_repair_label(self) -> &'static str {
+        match self {
+            Arabic => "إصلاح",
+            Bork => "Bork fix",
+            English => "Repair",
+            French => "Réparer",
+            German => "Reparieren",
+            Hindi => "मरम्मत",
+            Italian => "Ripara",
+            Japanese => "修理",
+            Russian => "Ремонт",
+            SimplifiedChinese => "维修",
+            Spanish => "Reparar",
+            Vietnamese => "Sửa chữa",
+        }
+    }
+
+    fn round_label(self, number: u32, time: &str) -> String {
+        match self {
+            Arabic => format!("الجولة {number}: {time}"),
+            Bork => format!("Bork {number}: {time}"),
+            English => format!("Round {number}: {time}"),
+            French => format!("Manche {number}: {time}"),
+            German => format!("Runde {number}: {time}"),
+            Hindi => format!("राउंड {number}: {time}"),
+            Italian => format!("Round {number}: {time}"),
+            Japanese => format!("ラウンド{number}: {time}"),
+            Russian => format!("Раунд {number}: {time}"),
+            SimplifiedChinese => format!("第{number}轮: {time}"),
+            Spanish => format!("Ronda {number}: {time}"),
+            Vietnamese => format!("Vòng {number}: {time}"),
+        }
+    }
+
+    fn round_next_label(self, time: &str) -> String {
+        match self {
+            Arabic => format!("الجولة التالية خلال {time}"),
+            Bork => format!("Next bork in {time}"),
+            English => format!("Next round in {time}"),
+            French => format!("Prochaine manche dans {time}"),
+            German => format!("Nächste Runde in {time}"),
+            Hindi => format!("अगला राउंड {time} में"),
+            Italian => format!("Prossimo round tra {time}"),
+            Japanese => format!("次のラウンドまで{time}"),
+            Russian => format!("Следующий раунд через {time}"),
+            SimplifiedChinese => format!("下一轮将在{time}后开始"),
+            Spanish => format!("Próxima ronda en {time}"),
+            Vietnamese => format!("Vòng tiếp theo sau {time}"),
+        }
+    }
+
+    fn round_results_label(self, number: u32) -> String {
+        match self {
+            Arabic => format!("نتائج الجولة {number}"),
+            Bork => format!("Bork {number} borks"),
+            English => format!("Round {number} results"),
+            French => format!("Résultats de la manche {number}"),
+            German => format!("Ergebnisse der Runde {number}"),
+            Hindi => format!("राउंड {number} के परिणाम"),
+            Italian => format!("Risultati del round {number}"),
+            Japanese => format!("ラウンド{number}の結果"),
+            Russian => format!("Итоги раунда {number}"),
+            SimplifiedChinese => format!("第{number}轮结果"),
+            Spanish => format!("Resultados de la ronda {number}"),
+            Vietnamese => format!("Kết quả vòng {number}"),
+        }
+    }
+
     fn sensor_active_label(self) -> &'static str {
         match self {
             Arabic => "أجهزة استشعار نشطة",
@@ -1153,8 +1573,7 @@ impl Mk48Translation for LanguageId {
         }
     }
 
-    fn sensor_active_hint(self, sensors: &str) -> String {
-        let key = ACTIVE_KEY;
+    fn sensor_active_hint(self, sensors: &str, key: Key) -> String {
         match self {
             English => format!("({key}) Active {sensors} helps you see more, but may also give away your position"),
             Spanish => format!("({key}) Los {sensors} activos te ayudan a ver más, pero también pueden revelar tu posición"),
@@ -1205,6 +1624,74 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn ship_doctrine_aircraft_first_label(self) -> &'static str {
+        match self {
+            Arabic => "مضاد الطائرات: الطائرات",
+            Bork => "AA: bork birds",
+            English => "AA: aircraft",
+            French => "DCA : avions",
+            German => "Flak: Flugzeuge",
+            Hindi => "विमानरोधी: विमान",
+            Italian => "Contraerea: aerei",
+            Japanese => "対空: 航空機",
+            Russian => "ПВО: самолёты",
+            SimplifiedChinese => "防空：飞机",
+            Spanish => "AA: aeronaves",
+            Vietnamese => "Phòng không: máy bay",
+        }
+    }
+
+    fn ship_doctrine_hold_fire_label(self) -> &'static str {
+        match self {
+            Arabic => "مضاد الطائرات: أوقف النار",
+            Bork => "AA: no bork",
+            English => "AA: hold fire",
+            French => "DCA : cessez-le-feu",
+            German => "Flak: Feuer einstellen",
+            Hindi => "विमानरोधी: गोलीबारी रोकें",
+            Italian => "Contraerea: cessate il fuoco",
+            Japanese => "対空: 射撃中止",
+            Russian => "ПВО: не стрелять",
+            SimplifiedChinese => "防空：停火",
+            Spanish => "AA: alto el fuego",
+            Vietnamese => "Phòng không: ngừng bắn",
+        }
+    }
+
+    fn ship_doctrine_missiles_first_label(self) -> &'static str {
+        match self {
+            Arabic => "مضاد الطائرات: الصواريخ",
+            Bork => "AA: bork rockets",
+            English => "AA: missiles",
+            French => "DCA : missiles",
+            German => "Flak: Raketen",
+            Hindi => "विमानरोधी: मिसाइलें",
+            Italian => "Contraerea: missili",
+            Japanese => "対空: ミサイル",
+            Russian => "ПВО: ракеты",
+            SimplifiedChinese => "防空：导弹",
+            Spanish => "AA: misiles",
+            Vietnamese => "Phòng không: tên lửa",
+        }
+    }
+
+    fn ship_fly_aircraft_label(self) -> &'static str {
+        match self {
+            Arabic => "قيادة الطائرة",
+            Bork => "Fly bork",
+            English => "Fly aircraft",
+            French => "Piloter l'avion",
+            German => "Flugzeug fliegen",
+            Hindi => "विमान उड़ाएं",
+            Italian => "Pilota l'aereo",
+            Japanese => "航空機を操縦",
+            Russian => "Пилотировать",
+            SimplifiedChinese => "驾驶飞机",
+            Spanish => "Pilotar aeronave",
+            Vietnamese => "Lái máy bay",
+        }
+    }
+
     fn ship_surface_label(self) -> &'static str {
         match self {
             Arabic => "سطح",
@@ -1222,8 +1709,7 @@ impl Mk48Translation for LanguageId {
         }
     }
 
-    fn ship_surface_hint(self) -> String {
-        let key = SURFACE_KEY;
+    fn ship_surface_hint(self, key: Key) -> String {
         match self {
             Arabic => format!("({key}) يمكنك سطح سفينتك وقتما تشاء ، لكن الغوص أحيانًا يكون مقيدًا بعمق الماء"),
             Bork => format!("({key}) Bork can surface wherever bork wants, but diving is sometimes limited by the depth of the water"),
@@ -1240,6 +1726,22 @@ impl Mk48Translation for LanguageId {
         }
     }
 
+    fn text_fonts(self) -> &'static str {
+        match self {
+            Arabic => "Arial, 'Noto Sans Arabic', 'Noto Sans', 'Noto Sans SC', sans-serif",
+            Hindi => "Arial, 'Noto Sans Devanagari', 'Noto Sans', 'Noto Sans SC', sans-serif",
+            Japanese => {
+                "Arial, 'Noto Sans JP', 'Hiragino Sans', 'Yu Gothic', 'Noto Sans SC', sans-serif"
+            }
+            SimplifiedChinese => {
+                "Arial, 'Noto Sans SC', 'PingFang SC', 'Microsoft YaHei', 'Noto Sans JP', sans-serif"
+            }
+            Bork | English | French | German | Italian | Russian | Spanish | Vietnamese => {
+                TextLayer::DEFAULT_FONTS
+            }
+        }
+    }
+
     fn team_fleet_label(self) -> &'static str {
         match self {
             Arabic => "أسطول",
@@ -1339,4 +1841,55 @@ impl Mk48Translation for LanguageId {
             Vietnamese => "Lỗi quảng cáo",
         }
     }
+
+    fn upgrade_dead_end_label(self) -> &'static str {
+        match self {
+            Arabic => "نهاية الطريق",
+            Bork => "Bork end",
+            English => "Dead end",
+            French => "Impasse",
+            German => "Sackgasse",
+            Hindi => "आगे कोई रास्ता नहीं",
+            Italian => "Vicolo cieco",
+            Japanese => "行き止まり",
+            Russian => "Тупик",
+            SimplifiedChinese => "死路",
+            Spanish => "Callejón sin salida",
+            Vietnamese => "Ngõ cụt",
+        }
+    }
+
+    fn upgrade_respec_label(self) -> &'static str {
+        match self {
+            Arabic => "تغيير مجاني (مرة واحدة لكل حياة)",
+            Bork => "Free rebork (once per bork)",
+            English => "Free respec (once per life)",
+            French => "Respécialisation gratuite (une fois par vie)",
+            German => "Kostenloser Wechsel (einmal pro Leben)",
+            Hindi => "मुफ़्त बदलाव (प्रति जीवन एक बार)",
+            Italian => "Cambio gratuito (una volta per vita)",
+            Japanese => "無料の乗り換え（1回の出撃につき1回）",
+            Russian => "Бесплатная смена (раз за жизнь)",
+            SimplifiedChinese => "免费转换（每条命一次）",
+            Spanish => "Cambio gratuito (una vez por vida)",
+            Vietnamese => "Đổi miễn phí (một lần mỗi mạng)",
+        }
+    }
+
+    fn upgrade_tree_label(self) -> &'static str {
+        match self {
+            Arabic => "شجرة الترقية",
+            Bork => "Bork tree",
+            English => "Upgrade tree",
+            French => "Arbre d'amélioration",
+            German => "Upgrade-Baum",
+            Hindi => "अपग्रेड ट्री",
+            Italian => "Albero dei potenziamenti",
+            Japanese => "アップグレードツリー",
+            Russian => "Дерево улучшений",
+            SimplifiedChinese => "升级树",
+            Spanish => "Árbol de mejoras",
+            Vietnamese => "Cây nâng cấp",
+        }
+    }
 }
@@ -5,30 +5,44 @@ use crate::game::Mk48Game;
 use crate::translation::Mk48Translation;
 use crate::ui::about_dialog::AboutDialog;
 use crate::ui::changelog_dialog::ChangelogDialog;
+use crate::ui::dry_dock_dialog::DryDockDialog;
 use crate::ui::help_dialog::HelpDialog;
 use crate::ui::hint::Hint;
 pub use crate::ui::instructions::InstructionStatus;
+use crate::ui::key_bindings_dialog::KeyBindingsDialog;
+pub use crate::ui::kill_feed_overlay::KillFeedItem;
+use crate::ui::kill_feed_overlay::KillFeedOverlay;
 use crate::ui::levels_dialog::LevelsDialog;
 use crate::ui::logo::logo;
+use crate::ui::missions_dialog::MissionsDialog;
+use crate::ui::photo_mode_overlay::PhotoModeOverlay;
+use crate::ui::private_arena_dialog::PrivateArenaDialog;
+use crate::ui::purchase_overlay::PurchaseOverlay;
 use crate::ui::respawn_overlay::RespawnOverlay;
+use crate::ui::round_overlay::RoundOverlay;
 use crate::ui::settings_dialog::SettingsDialog;
 use crate::ui::ship_controls::ShipControls;
 use crate::ui::ships_dialog::ShipsDialog;
+use crate::ui::statistics_dialog::StatisticsDialog;
 use crate::ui::status_overlay::StatusOverlay;
 use crate::ui::upgrade_overlay::UpgradeOverlay;
 use client_util::context::Context;
 use common::altitude::Altitude;
 use common::angle::Angle;
 use common::death_reason::DeathReason;
-use common::entity::EntityType;
+use common::entity::{DisabledModules, EntityType, StatusEffects};
+use common::mission::DailyMissions;
+use common::protocol::{Doctrine, Purchase, Round};
+use common::stats::LifeStats;
 use common::velocity::Velocity;
 use core_protocol::id::{LanguageId, TeamId};
-use core_protocol::name::PlayerAlias;
+use core_protocol::name::{ClassName, PlayerAlias};
 use engine_macros::SmolRoutable;
 use glam::Vec2;
 use std::collections::HashMap;
 use stylist::yew::styled_component;
 use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
 use yew_frontend::component::discord_icon::DiscordIcon;
 use yew_frontend::component::github_icon::GithubIcon;
 use yew_frontend::component::invitation_icon::InvitationIcon;
@@ -42,28 +56,42 @@ use yew_frontend::component::terms_link::TermsLink;
 use yew_frontend::component::volume_icon::VolumeIcon;
 use yew_frontend::component::x_button::XButton;
 use yew_frontend::component::zoom_icon::ZoomIcon;
+use yew_frontend::dialog::leaderboard_dialog::LeaderboardDialog;
+use yew_frontend::dialog::server_browser_dialog::ServerBrowserDialog;
+use yew_frontend::dialog::sign_in_dialog::SignInDialog;
 use yew_frontend::frontend::{use_gctw, use_outbound_enabled};
 use yew_frontend::frontend::{use_rewarded_ad, PropertiesWrapper};
+use yew_frontend::overlay::achievement::AchievementOverlay;
 use yew_frontend::overlay::chat::ChatOverlay;
 use yew_frontend::overlay::leaderboard::LeaderboardOverlay;
 use yew_frontend::overlay::spawn::SpawnOverlay;
 use yew_frontend::overlay::team::TeamOverlay;
+use yew_frontend::theme::use_theme;
 use yew_frontend::translation::{use_translation, Translation};
 use yew_router::{Routable, Switch};
 
 mod about_dialog;
 mod changelog_dialog;
+mod dry_dock_dialog;
 mod help_dialog;
 mod hint;
 mod instructions;
+mod key_bindings_dialog;
+mod kill_feed_overlay;
 mod levels_dialog;
 mod logo;
+mod missions_dialog;
+mod photo_mode_overlay;
+mod private_arena_dialog;
+mod purchase_overlay;
 mod respawn_overlay;
+mod round_overlay;
 mod settings_dialog;
 mod ship_controls;
 mod ship_menu;
 mod ships_dialog;
 mod sprite;
+mod statistics_dialog;
 mod status_overlay;
 mod upgrade_overlay;
 
@@ -88,6 +116,9 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
 
     let margin = "0.75rem";
     let status = props.status.clone();
+    let score = props.score;
+    let missions = props.missions;
+    let stats = props.stats.clone();
     let outbound_enabled = use_outbound_enabled();
 
     /*
@@ -117,6 +148,7 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
     use yew_frontend::frontend::RewardedAd;
     use yew_icons::{Icon, IconId};
     let rewarded_ad = use_rewarded_ad();
+    let rewarded_position = Position::TopLeft { margin }.directional(use_theme().right_to_left);
     let rewarded_style = css!(
         r#"
         display: flex;
@@ -138,9 +170,18 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
 
     html! {
         <>
-            if let UiStatus::Playing(playing) = status {
+            if gctw.settings_cache.photo_mode && matches!(status, UiStatus::Playing(_)) {
+                <PhotoModeOverlay/>
+            } else if let UiStatus::Playing(playing) = status {
                 <div class={classes!(gctw.settings_cache.cinematic.then_some(cinematic_style))}>
                     <Positioner id="status" position={Position::BottomMiddle{margin}} max_width="45%">
+                        <AchievementOverlay/>
+                        if let Some(round) = props.round.clone() {
+                            <RoundOverlay {round}/>
+                        }
+                        if playing.store {
+                            <PurchaseOverlay status={playing.clone()} score={props.score}/>
+                        }
                         <StatusOverlay
                             status={playing.clone()}
                             score={props.score}
@@ -165,6 +206,10 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
                         <SettingsIcon<Mk48Route> route={Mk48Route::Settings}/>
                         <LanguageMenu/>
                     </Positioner>
+                    <KillFeedOverlay
+                        position={Position::CenterLeft{margin}}
+                        items={props.kill_feed.clone()}
+                    />
                     <TeamOverlay
                         position={Position::TopLeft{margin}}
                         style="max-width:25%;"
@@ -187,7 +232,7 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
                     <Hint entity_type={playing.entity_type}/>
                 }
             } else if let UiStatus::Respawning(respawning) = status {
-                <RespawnOverlay status={respawning} score={props.score}/>
+                <RespawnOverlay status={respawning} score={props.score} round={props.round.clone()}/>
                 <Positioner position={Position::TopRight{margin}} max_width="25%">
                     <XButton onclick={gctw.send_ui_event_callback.reform(|_| UiEvent::OverrideRespawn)}/>
                 </Positioner>
@@ -206,6 +251,8 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
                 <Positioner id="links" position={Position::BottomMiddle{margin}} flex={Flex::Row}>
                     <RouteLink<Mk48Route> route={Mk48Route::Help}>{t.help_hint()}</RouteLink<Mk48Route>>
                     <RouteLink<Mk48Route> route={Mk48Route::About}>{t.about_hint()}</RouteLink<Mk48Route>>
+                    <RouteLink<Mk48Route> route={Mk48Route::Missions}>{"Missions"}</RouteLink<Mk48Route>>
+                    <RouteLink<Mk48Route> route={Mk48Route::Leaderboards}>{"Leaderboards"}</RouteLink<Mk48Route>>
                     <PrivacyLink/>
                     <TermsLink/>
                 </Positioner>
@@ -220,7 +267,7 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
                         id="rewarded"
                         onclick={if let RewardedAd::Available{request} = &rewarded_ad { Some(request.reform(|_| {})) } else { None }}
                         disabled={!matches!(rewarded_ad, RewardedAd::Available{..})}
-                        style={Position::TopLeft{margin}.to_string()}
+                        style={rewarded_position.to_string()}
                         class={rewarded_style}
                     >
                         <Icon icon_id={IconId::OcticonsVideo16}/>
@@ -228,7 +275,7 @@ pub fn mk48_ui(props: &PropertiesWrapper<UiProps>) -> Html {
                     </button>
                 }
             }
-            <Switch<Mk48Route> render={switch}/>
+            <Switch<Mk48Route> render={move |routes| switch(routes, score, missions, stats.clone())}/>
         </>
     }
 }
@@ -239,14 +286,30 @@ pub enum Mk48Route {
     About,
     #[at("/changelog/")]
     Changelog,
+    #[at("/drydock/")]
+    DryDock,
     #[at("/help/")]
     Help,
+    #[at("/keybindings/")]
+    KeyBindings,
     #[at("/ships/")]
     Ships,
+    #[at("/leaderboards/")]
+    Leaderboards,
     #[at("/levels/")]
     Levels,
+    #[at("/missions/")]
+    Missions,
+    #[at("/private/")]
+    PrivateArena,
+    #[at("/servers/")]
+    Servers,
     #[at("/settings/")]
     Settings,
+    #[at("/sign-in/")]
+    SignIn,
+    #[at("/statistics/")]
+    Statistics,
     #[not_found]
     #[at("/")]
     Home,
@@ -256,6 +319,10 @@ pub enum Mk48Route {
 pub struct UiState {
     pub active: bool,
     pub submerge: bool,
+    /// Target depth while submerged, or [`None`] for the test depth.
+    pub depth: Option<Altitude>,
+    /// What anti-aircraft guns engage.
+    pub doctrine: Doctrine,
     pub armament: Option<EntityType>,
 }
 
@@ -264,6 +331,8 @@ impl Default for UiState {
         Self {
             active: true,
             submerge: false,
+            depth: None,
+            doctrine: Doctrine::default(),
             armament: None,
         }
     }
@@ -273,17 +342,34 @@ pub enum UiEvent {
     /// Sensors active.
     Active(bool),
     Armament(Option<EntityType>),
+    /// Take direct control of an aircraft, or return control to the boat.
+    ControlAircraft(bool),
+    /// Create a private arena, and join it.
+    CreateArena {
+        bots: bool,
+        /// World radius in meters, or [`None`] to grow with the number of boats.
+        radius: Option<u16>,
+        max_level: u8,
+    },
+    /// Target depth while submerged.
+    Depth(Option<Altitude>),
+    /// What anti-aircraft guns engage.
+    Doctrine(Doctrine),
     GraphicsSettingsChanged,
     /// Go from respawning to spawning.
     #[allow(unused)]
     OverrideRespawn,
-    Respawn(EntityType),
+    /// Spend coins at a nearby store.
+    Purchase(Purchase),
+    /// Respawn as a boat, with an armament loadout.
+    Respawn(EntityType, u8),
     Spawn {
         alias: PlayerAlias,
         entity_type: EntityType,
     },
     Submerge(bool),
-    Upgrade(EntityType),
+    /// Upgrade to a boat, with an armament loadout.
+    Upgrade(EntityType, u8),
 }
 
 #[derive(PartialEq, Clone, Default)]
@@ -291,6 +377,13 @@ pub struct UiProps {
     pub fps: f32,
     pub score: u32,
     pub status: UiStatus,
+    pub kill_feed: Box<[KillFeedItem]>,
+    /// Current round, if the arena is in blitz mode.
+    pub round: Option<Round>,
+    /// Today's missions, and the player's progress towards them.
+    pub missions: Option<DailyMissions>,
+    /// Statistics about the player's last boat, if it sank.
+    pub stats: Option<LifeStats>,
 }
 
 /// Mutually exclusive statuses.
@@ -310,17 +403,37 @@ pub struct UiStatusPlaying {
     pub position: Vec2,
     pub altitude: Altitude,
     pub submerge: bool,
+    /// Target depth while submerged, or [`None`] for the test depth.
+    pub depth: Option<Altitude>,
     /// Active sensors.
     pub active: bool,
+    /// What anti-aircraft guns engage.
+    pub doctrine: Doctrine,
+    /// Whether an aircraft is under direct control.
+    pub aircraft_controlled: bool,
     pub instruction_status: InstructionStatus,
+    /// Whether the one free respec per life is unused.
+    pub can_respec: bool,
+    /// Armament loadout of the boat, as an index into `EntityData::loadouts`.
+    pub loadout: u8,
+    /// Whether close enough to an HQ or oil platform to make purchases.
+    pub store: bool,
+    /// Fraction of health lost, from 0 to 1.
+    pub damage: f32,
     pub armament: Option<EntityType>,
     pub armament_consumption: Box<[bool]>,
+    /// Modules knocked out by critical hits.
+    pub disabled: DisabledModules,
+    /// Damage over time effects, such as fire.
+    pub effects: StatusEffects,
     pub team_proximity: HashMap<TeamId, f32>,
 }
 
 #[derive(PartialEq, Clone)]
 pub struct UiStatusRespawning {
     pub death_reason: DeathReason,
+    /// Whether the player sank their killer too.
+    pub mutual: bool,
 }
 
 impl Mk48Game {
@@ -329,13 +442,22 @@ impl Mk48Game {
             fps: self.fps_counter.last_sample().unwrap_or(0.0),
             score: context.state.game.score,
             status,
+            kill_feed: self.kill_feed.iter().cloned().collect(),
+            round: context.state.game.round.clone(),
+            missions: context.state.game.missions,
+            stats: context.state.game.stats.clone(),
         };
 
         context.set_ui_props(props);
     }
 }
 
-fn switch(routes: Mk48Route) -> Html {
+fn switch(
+    routes: Mk48Route,
+    score: u32,
+    missions: Option<DailyMissions>,
+    stats: Option<LifeStats>,
+) -> Html {
     match routes {
         Mk48Route::About => html! {
             <AboutDialog/>
@@ -343,18 +465,58 @@ fn switch(routes: Mk48Route) -> Html {
         Mk48Route::Changelog => html! {
             <ChangelogDialog/>
         },
+        Mk48Route::DryDock => html! {
+            <DryDockDialog {score}/>
+        },
         Mk48Route::Help => html! {
             <HelpDialog/>
         },
+        Mk48Route::KeyBindings => html! {
+            <KeyBindingsDialog/>
+        },
         Mk48Route::Ships => html! {
             <ShipsDialog/>
         },
+        Mk48Route::Leaderboards => html! {
+            <LeaderboardDialog classes={leaderboard_classes()}/>
+        },
         Mk48Route::Levels => html! {
             <LevelsDialog/>
         },
+        Mk48Route::Missions => html! {
+            <MissionsDialog {missions}/>
+        },
+        Mk48Route::PrivateArena => html! {
+            <PrivateArenaDialog/>
+        },
+        Mk48Route::Servers => html! {
+            <ServerBrowserDialog/>
+        },
         Mk48Route::Settings => html! {
             <SettingsDialog/>
         },
+        Mk48Route::SignIn => html! {
+            <SignInDialog/>
+        },
+        Mk48Route::Statistics => html! {
+            <StatisticsDialog {stats}/>
+        },
         Mk48Route::Home => html! {},
     }
 }
+
+/// Boats that players can sail, which have their own leaderboards, from lowest to highest level.
+fn leaderboard_classes() -> Vec<(ClassName, AttrValue)> {
+    let mut boats: Vec<_> = EntityType::iter()
+        .filter(|entity_type| entity_type.class_name().is_some())
+        .collect();
+    boats.sort_by_key(|entity_type| (entity_type.data().level, entity_type.data().label));
+    boats
+        .into_iter()
+        .map(|entity_type| {
+            let data = entity_type.data();
+            let label = format!("{} (Level {})", data.label, data.level);
+            (entity_type.class_name().unwrap(), label.into())
+        })
+        .collect()
+}
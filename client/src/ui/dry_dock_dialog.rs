@@ -0,0 +1,290 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::armament::{group_armaments, Group};
+use crate::translation::Mk48Translation;
+use common::entity::{EntityData, EntityKind, EntitySubKind, EntityType};
+use common::util::score_to_level;
+use glam::{vec3, Vec3};
+use renderer3d::{Camera3d, Model, Orthographic};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::str::FromStr;
+use stylist::yew::styled_component;
+use web_sys::{HtmlInputElement, HtmlSelectElement, InputEvent};
+use yew::{html, html_nested, use_state, Callback, Html, Properties, TargetCast};
+use yew_frontend::dialog::dialog::Dialog;
+use yew_frontend::translation::use_translation;
+
+#[derive(PartialEq, Properties)]
+pub struct DryDockDialogProps {
+    /// Hulls up to the level this score unlocks can be inspected.
+    pub score: u32,
+}
+
+/// Lets players inspect any unlocked hull as a 3D model, with its armament hardpoints annotated.
+#[styled_component(DryDockDialog)]
+pub fn dry_dock_dialog(props: &DryDockDialogProps) -> Html {
+    let t = use_translation();
+    let entity_type = use_state(|| EntityType::G5);
+    // Degrees around the vertical axis, controlled by a slider.
+    let yaw = use_state(|| 60u16);
+
+    let select_style = css!(
+        r#"
+        margin-bottom: 1em;
+        "#
+    );
+
+    let stage_style = css!(
+        r#"
+        position: relative;
+        width: 24rem;
+        height: 24rem;
+        margin: 0 auto;
+        user-select: none;
+        "#
+    );
+
+    let hardpoint_style = css!(
+        r#"
+        position: absolute;
+        width: 1rem;
+        height: 1rem;
+        margin: -0.5rem 0 0 -0.5rem;
+        border-radius: 50%;
+        background-color: #f1c40f;
+        box-shadow: 0 0 0.2rem black;
+        color: black;
+        font-size: 0.7rem;
+        line-height: 1rem;
+        text-align: center;
+        "#
+    );
+
+    let on_select = {
+        let entity_type = entity_type.clone();
+        Callback::from(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            if let Ok(selected) = EntityType::from_str(&value) {
+                entity_type.set(selected);
+            }
+        })
+    };
+
+    let on_rotate = {
+        let yaw = yaw.clone();
+        Callback::from(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlInputElement>().value();
+            if let Ok(value) = value.parse() {
+                yaw.set(value);
+            }
+        })
+    };
+
+    let max_level = score_to_level(props.score);
+    let data: &'static EntityData = entity_type.data();
+    let groups = group_armaments(&data.armaments, &[], 0);
+    let hull = HullModel::get(*entity_type);
+    let camera = hull_camera(*yaw);
+
+    // Number each group so hardpoints can be matched with the legend.
+    let group_number = |armament_type: EntityType| {
+        groups
+            .iter()
+            .position(|g| g.entity_type == armament_type)
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    };
+
+    html! {
+        <Dialog title={"Dry Dock"}>
+            <p>{"Inspect any ship you have unlocked, and where its weapons are mounted."}</p>
+
+            <select oninput={on_select} class={select_style}>
+                {EntityType::iter().filter(|t| {
+                    let data = t.data();
+                    data.kind == EntityKind::Boat && !data.npc && data.level <= max_level
+                }).map(|option| html_nested!{
+                    <option value={option.as_str()} selected={option == *entity_type}>
+                        {format!("{} (Level {})", option.data().label, option.data().level)}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+
+            <div class={stage_style}>
+                <img
+                    src={hull.model.svg_with_camera(&camera, HullModel::COLOR)}
+                    alt={data.label}
+                    style="width: 100%; height: 100%;"
+                />
+                {data.armaments.iter().filter(|a| !a.hidden).map(|armament| {
+                    let ndc = camera.world_to_ndc(hull.hardpoint(armament.position_forward, armament.position_side));
+                    let left = (ndc.x + 1.0) * 50.0;
+                    let top = (1.0 - ndc.y) * 50.0;
+                    let armament_data = armament.entity_type.data();
+                    let title = format!("{} ({})", armament_data.label, t.entity_kind_name(armament_data.kind, armament_data.sub_kind));
+                    html_nested!{
+                        <div
+                            class={hardpoint_style.clone()}
+                            style={format!("left: {left:.1}%; top: {top:.1}%;")}
+                            {title}
+                        >
+                            {group_number(armament.entity_type)}
+                        </div>
+                    }
+                }).collect::<Html>()}
+            </div>
+            <input
+                type="range"
+                min="0"
+                max="359"
+                value={yaw.to_string()}
+                oninput={on_rotate}
+                title={"Rotate"}
+                style="display: block; width: 24rem; margin: 0 auto;"
+            />
+
+            <ol>
+                {groups.iter().map(|Group{entity_type, total, ..}| {
+                    let data = entity_type.data();
+                    html_nested!{
+                        <li>
+                            {format!("{} ({}) × {}", data.label, t.entity_kind_name(data.kind, data.sub_kind), total)}
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ol>
+        </Dialog>
+    }
+}
+
+/// Orthographic camera orbiting the origin, looking down at 30 degrees, from `yaw` degrees around.
+fn hull_camera(yaw: u16) -> Camera3d {
+    let yaw = (yaw as f32).to_radians();
+    let pitch = 30f32.to_radians();
+    let position = vec3(
+        pitch.cos() * yaw.cos(),
+        pitch.sin(),
+        pitch.cos() * yaw.sin(),
+    );
+    Camera3d::looking_at(
+        position,
+        Vec3::ZERO,
+        Orthographic {
+            dimensions: Vec3::splat(2.0),
+        },
+    )
+}
+
+/// A low poly hull, generated from a boat's dimensions, since there are no hull models. It is
+/// convex, so culling back faces is enough for it to render correctly.
+struct HullModel {
+    /// Scaled to fit within the unit sphere, with +X forward, +Y up, and +Z starboard.
+    model: Model,
+    /// Meters to model space.
+    scale: f32,
+    /// Height of the deck above the waterline, in meters.
+    freeboard: f32,
+}
+
+thread_local! {
+    /// Hulls are leaked, as [`Model`] requires static data, so only generate each once.
+    static HULLS: RefCell<HashMap<EntityType, &'static HullModel>> = RefCell::default();
+}
+
+impl HullModel {
+    const COLOR: Vec3 = Vec3::new(0.55, 0.6, 0.65);
+
+    fn get(entity_type: EntityType) -> &'static Self {
+        HULLS.with(|hulls| {
+            *hulls
+                .borrow_mut()
+                .entry(entity_type)
+                .or_insert_with(|| &*Box::leak(Box::new(Self::new(entity_type.data()))))
+        })
+    }
+
+    fn new(data: &EntityData) -> Self {
+        let half_length = data.length * 0.5;
+        let half_width = data.width * 0.5;
+        let draft = data.draft.to_meters();
+        // Submarines are mostly submerged.
+        let freeboard = if data.sub_kind == EntitySubKind::Submarine {
+            half_width * 0.3
+        } else {
+            half_width * 0.5
+        };
+
+        // Deck outline, from stern to pointed bow, port (-Z) side first.
+        let outline = [
+            (-half_length, 0.8),
+            (-half_length * 0.5, 1.0),
+            (half_length * 0.3, 1.0),
+            (half_length, 0.0),
+        ];
+        let ring = |y: f32, beam: f32| -> Vec<Vec3> {
+            let port = outline.iter().map(|&(x, z)| vec3(x, y, -z * beam));
+            let starboard = outline.iter().rev().map(|&(x, z)| vec3(x, y, z * beam));
+            // Skip the duplicate bow point.
+            port.chain(starboard.skip(1)).collect()
+        };
+        let deck = ring(freeboard, half_width);
+        // Narrower at the keel.
+        let keel = ring(-draft, half_width * 0.4);
+
+        // Convex faces, lofted between the outlines.
+        let mut faces = vec![deck.clone(), keel.clone()];
+        for i in 0..deck.len() {
+            let j = (i + 1) % deck.len();
+            faces.push(vec![deck[i], keel[i], keel[j], deck[j]]);
+        }
+        let inside = vec3(0.0, (freeboard - draft) * 0.5, 0.0);
+
+        // Fit within the unit sphere, which the camera sees from any angle.
+        let radius = faces
+            .iter()
+            .flatten()
+            .map(|p| p.length())
+            .fold(0.0, f32::max);
+        let scale = 0.95 / radius;
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for face in faces {
+            let start = (vertices.len() / 3) as u16;
+            let normal = (face[1] - face[0]).cross(face[2] - face[0]);
+            let center = face.iter().fold(Vec3::ZERO, |sum, &p| sum + p) / face.len() as f32;
+            // Wind counterclockwise as seen from outside, so back faces are culled.
+            let outward = normal.dot(center - inside) >= 0.0;
+            for p in &face {
+                vertices.extend_from_slice(&(*p * scale).to_array());
+            }
+            // Faces are convex, so triangulate as a fan.
+            for i in 1..face.len() as u16 - 1 {
+                if outward {
+                    indices.extend_from_slice(&[start, start + i, start + i + 1]);
+                } else {
+                    indices.extend_from_slice(&[start, start + i + 1, start + i]);
+                }
+            }
+        }
+
+        Self {
+            model: Model {
+                vertices: Box::leak(vertices.into_boxed_slice()),
+                indices: Box::leak(indices.into_boxed_slice()),
+                normals: false,
+                uvs: false,
+                colors: false,
+            },
+            scale,
+            freeboard,
+        }
+    }
+
+    /// Position of an armament on deck, in model space (port side is positive in 2D).
+    fn hardpoint(&self, forward: f32, side: f32) -> Vec3 {
+        vec3(forward, self.freeboard, -side) * self.scale
+    }
+}
@@ -58,15 +58,18 @@ pub fn help_dialog() -> Html {
                 <li><b>{"Battleships"}</b>{" and "}<b>{"Dreadnoughts"}</b>{" are very formidable ships, having extremely
                 powerful main cannons. They may carry a minimal complement of aircraft for submarine defense."}</li>
                 <li><b>{"Submarines"}</b>{" travel underwater, making them immune to certain
-                types of weapons, but must surface to fire certain types of weapons."}</li>
+                types of weapons, but must surface to fire certain types of weapons. Their depth can be
+                set with the depth gauge, but diving past their test depth damages the hull, which is
+                crushed entirely at the maximum depth."}</li>
                 <li><b>{"Hovercraft"}</b>{" can travel on land and water."}</li>
                 <li><b>{"Rams"}</b>{" are specially designed to ram other ships."}</li>
                 <li><b>{"Dredgers"}</b>{" have the ability to modify the land. New land
                 can be created by clicking in front of them, and old land can be destroyed
                 by sailing over it."}</li>
                 <li><b>{"Icebreakers"}</b>{" can plow through ice and snow without taking damage."}</li>
-                <li><b>{"Minelayers"}</b>{" dispense magnetic mines that can help guard a small area."}</li>
-                <li><b>{"Aircraft carriers"}</b>{" command a squadron of aircraft which follow your mouse cursor to attack enemy ships!"}</li>
+                <li><b>{"Minelayers"}</b>{" dispense magnetic mines that can help guard a small area, and torpedo nets that block incoming torpedoes."}</li>
+                <li><b>{"Aircraft carriers"}</b>{" command a squadron of aircraft which follow your mouse cursor to attack enemy ships!
+                You can also fly one aircraft yourself, while your carrier holds its course, until it runs low on fuel."}</li>
             </ol>
 
             <p>
@@ -108,6 +111,8 @@ pub fn help_dialog() -> Html {
 
                 <li><b>{"Gun turrets"}</b>{" shoot very fast shells that do moderate damage."}</li>
 
+                <li><b>{"Anti-aircraft guns"}</b>{" automatically engage nearby aircraft and missiles, prioritizing whichever you choose. Their tracers give away your position, so you can order them to hold fire."}</li>
+
                 <li><b>{"Depth charges"}</b>{" are stationary weapons that can be deployed against pursuing ships or submerged submarines."}</li>
 
                 <li><b>{"Mines"}</b>{" are like depth charges but last much longer and are more damaging."}</li>
@@ -142,6 +147,12 @@ pub fn help_dialog() -> Html {
                 {"You can toggle between the modes with the 'z' key."}
             </p>
 
+            <p>
+                {"Moving fast, staying shallow, and using active sensors all make a ship easier to detect. "}
+                {"Active sonar pings can be heard from twice as far as they reach, revealing where they came from. "}
+                {"Below a depth of 60m (the thermocline), sonar has a harder time detecting targets near the surface, and vice versa."}
+            </p>
+
             <p>
                 {"If a contact is on the border of your sensor range, it will appear as an arrow. "}
                 {"In this case, you know something is there, but not what it is."}
@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::key_bindings::{Action, KeyBindings};
+use crate::settings::Mk48Settings;
+use crate::Mk48Game;
+use client_util::browser_storage::BrowserStorages;
+use client_util::keyboard::Key;
+use stylist::yew::styled_component;
+use web_sys::{KeyboardEvent, MouseEvent};
+use yew::{html, html_nested, use_state_eq, Callback, Html};
+use yew_frontend::dialog::dialog::Dialog;
+use yew_frontend::frontend::use_gctw;
+
+/// Lets players remap keys. Click an action, then press the key to bind to it.
+#[styled_component(KeyBindingsDialog)]
+pub fn key_bindings_dialog() -> Html {
+    let row_style = css!(
+        r#"
+        display: flex;
+        justify-content: space-between;
+        align-items: center;
+        margin-bottom: 0.4em;
+        user-select: none;
+        "#
+    );
+
+    let button_style = css!(
+        r#"
+        border-radius: 0.25em;
+        border: 0;
+        color: white;
+        background-color: #0075ff;
+        cursor: pointer;
+        font-weight: bold;
+        min-width: 6em;
+        padding: 0.5em;
+        "#
+    );
+
+    let gctw = use_gctw::<Mk48Game>();
+    let key_bindings = gctw.settings_cache.key_bindings;
+    // The action waiting for a key press, if any.
+    let listening = use_state_eq(|| None::<Action>);
+
+    let on_key_down = {
+        let listening = listening.clone();
+        let change_settings_callback = gctw.change_settings_callback.clone();
+        Callback::from(move |event: KeyboardEvent| {
+            // Don't let the game process the key.
+            event.prevent_default();
+            event.stop_propagation();
+
+            if let Some(action) = *listening {
+                if let Some(key) = Key::try_from_key_code(event.key_code()) {
+                    change_settings_callback.emit(Box::new(
+                        move |settings: &mut Mk48Settings,
+                              browser_storages: &mut BrowserStorages| {
+                            let mut key_bindings = settings.key_bindings;
+                            key_bindings.bind(action, key);
+                            settings.set_key_bindings(key_bindings, browser_storages);
+                        },
+                    ));
+                }
+                listening.set(None);
+            }
+        })
+    };
+
+    let on_reset = gctw.change_settings_callback.reform(|_: MouseEvent| {
+        Box::new(
+            |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_key_bindings(KeyBindings::default(), browser_storages);
+            },
+        )
+    });
+
+    html! {
+        <Dialog title={"Key Bindings"}>
+            {Action::ALL.into_iter().map(|action| {
+                let onclick = {
+                    let listening = listening.clone();
+                    Callback::from(move |_: MouseEvent| listening.set(Some(action)))
+                };
+                let label = if *listening == Some(action) {
+                    "...".to_owned()
+                } else {
+                    key_bindings.key(action).to_string()
+                };
+                html_nested! {
+                    <div class={row_style.clone()}>
+                        <span>{action.label()}</span>
+                        <button class={button_style.clone()} {onclick} onkeydown={on_key_down.clone()}>{label}</button>
+                    </div>
+                }
+            }).collect::<Html>()}
+
+            <button class={button_style.clone()} onclick={on_reset}>{"Reset to Defaults"}</button>
+        </Dialog>
+    }
+}
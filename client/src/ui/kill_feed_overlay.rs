@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translation::Mk48Translation;
+use crate::ui::sprite::Sprite;
+use common::death_reason::DeathReason;
+use common::protocol::Sinking;
+use stylist::yew::styled_component;
+use yew::{html, Html, Properties};
+use yew_frontend::component::positioner::{Flex, Position, Positioner};
+use yew_frontend::translation::use_translation;
+
+/// A recent sinking, as shown in the kill feed.
+#[derive(Clone, PartialEq)]
+pub struct KillFeedItem {
+    /// Unique, so that the fade animation isn't reset when other items come and go.
+    pub key: u32,
+    /// When the item was received (in client time seconds).
+    pub time: f32,
+    pub sinking: Sinking,
+}
+
+impl KillFeedItem {
+    /// How long items remain in the feed.
+    pub const SECONDS: f32 = 10.0;
+    /// Maximum number of items in the feed.
+    pub const MAX: usize = 5;
+}
+
+#[derive(PartialEq, Properties)]
+pub struct KillFeedProps {
+    pub position: Position,
+    pub items: Box<[KillFeedItem]>,
+}
+
+#[styled_component(KillFeedOverlay)]
+pub fn kill_feed_overlay(props: &KillFeedProps) -> Html {
+    let item_style = css!(
+        r#"
+        align-items: center;
+        background-color: #00000040;
+        color: white;
+        display: flex;
+        gap: 0.5em;
+        padding: 0.25em 0.5em;
+        pointer-events: none;
+        user-select: none;
+        white-space: nowrap;
+        animation: fade 10.0s;
+        animation-fill-mode: both;
+
+        @keyframes fade {
+            0% {
+                opacity: 0.9;
+            }
+            80% {
+                opacity: 0.9;
+            }
+            100% {
+                opacity: 0.0;
+            }
+        }
+    "#
+    );
+
+    let sprite_style = css!(
+        r#"
+        transform: scale(0.5);
+        "#
+    );
+
+    let t = use_translation();
+
+    html! {
+        <Positioner id="kill_feed" position={props.position} flex={Flex::Column}>
+            {props.items.iter().map(|KillFeedItem{key, sinking, ..}| {
+                let (killer, weapon) = match &sinking.reason {
+                    DeathReason::Weapon(alias, entity_type) => (Some(*alias), Some(*entity_type)),
+                    DeathReason::Boat(alias) | DeathReason::Ram(alias) => (Some(*alias), None),
+                    _ => (None, None),
+                };
+                let title = t.death_reason_maybe_mutual(&sinking.reason, sinking.mutual);
+
+                html! {
+                    <div key={*key} class={item_style.clone()} {title}>
+                        if let Some(killer) = killer {
+                            <b>{killer}</b>
+                        }
+                        if sinking.mutual {
+                            // They sank each other.
+                            <span>{"⚔️"}</span>
+                        }
+                        if let Some(entity_type) = weapon {
+                            <Sprite {entity_type} image_class={sprite_style.clone()}/>
+                        } else {
+                            <span>{"💥"}</span>
+                        }
+                        <span>{sinking.alias}</span>
+                        <Sprite entity_type={sinking.entity_type} image_class={sprite_style.clone()}/>
+                    </div>
+                }
+            }).collect::<Html>()}
+        </Positioner>
+    }
+}
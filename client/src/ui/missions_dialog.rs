@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::mission::{DailyMissions, Objective};
+use stylist::yew::styled_component;
+use yew::{classes, html, html_nested, Html, Properties};
+use yew_frontend::dialog::dialog::Dialog;
+
+#[derive(PartialEq, Properties)]
+pub struct MissionsDialogProps {
+    /// Today's missions, if received from the server yet.
+    pub missions: Option<DailyMissions>,
+}
+
+/// Lists today's missions, the player's progress towards them, and their rewards.
+#[styled_component(MissionsDialog)]
+pub fn missions_dialog(props: &MissionsDialogProps) -> Html {
+    let row_style = css!(
+        r#"
+        display: flex;
+        justify-content: space-between;
+        align-items: center;
+        margin-bottom: 0.4em;
+        "#
+    );
+
+    let complete_style = css!(
+        r#"
+        color: #2ecc71;
+        "#
+    );
+
+    html! {
+        <Dialog title={"Missions"}>
+            if let Some(missions) = props.missions {
+                <p>{"Complete missions for bonus starting score on your next boat. Completing all of them on consecutive days builds a streak, which increases rewards. New missions are issued at midnight (UTC)."}</p>
+                <h3>{format!("Streak: {} day{}", missions.streak, if missions.streak == 1 { "" } else { "s" })}</h3>
+                {missions.missions.iter().enumerate().map(|(i, mission)| html_nested!{
+                    <div class={classes!(row_style.clone(), missions.is_mission_complete(i).then_some(complete_style.clone()))}>
+                        <span>{format!("{} ({}/{})", objective_label(mission.objective, mission.target), missions.progress[i], mission.target)}</span>
+                        <span>{format!("+{}", mission.reward(missions.streak))}</span>
+                    </div>
+                }).collect::<Html>()}
+            } else {
+                <p>{"Missions will be available once you are connected to a server."}</p>
+            }
+        </Dialog>
+    }
+}
+
+fn objective_label(objective: Objective, target: u32) -> String {
+    let plural = if target == 1 { "" } else { "s" };
+    match objective {
+        Objective::SinkBoats => format!("Sink {target} boat{plural}"),
+        Objective::SinkSubmarines => format!("Sink {target} submarine{plural}"),
+        Objective::RamBoats => format!("Ram {target} boat{plural}"),
+        Objective::CollectCrates => format!("Collect {target} crate{plural}"),
+        Objective::CollectBarrels => format!("Collect {target} oil barrel{plural}"),
+    }
+}
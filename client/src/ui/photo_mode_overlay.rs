@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::settings::Mk48Settings;
+use crate::Mk48Game;
+use client_util::browser_storage::BrowserStorages;
+use stylist::yew::styled_component;
+use yew::{html, Html};
+use yew_frontend::component::positioner::{Position, Positioner};
+use yew_frontend::component::x_button::XButton;
+use yew_frontend::frontend::use_gctw;
+
+/// Cinematic letterboxing, replacing the GUI while in photo mode.
+#[styled_component(PhotoModeOverlay)]
+pub fn photo_mode_overlay() -> Html {
+    let bar_style = css!(
+        r#"
+        background-color: black;
+        height: 10vh;
+        left: 0;
+        pointer-events: none;
+        position: absolute;
+        width: 100%;
+    "#
+    );
+
+    let exit_style = css!(
+        r#"
+        transition: opacity 0.25s;
+
+        :not(:hover) {
+		    opacity: 0;
+	    }
+    "#
+    );
+
+    let gctw = use_gctw::<Mk48Game>();
+    let on_exit = gctw.change_settings_callback.reform(|_| {
+        Box::new(
+            |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_photo_mode(false, browser_storages);
+            },
+        )
+    });
+
+    html! {
+        <>
+            <div class={bar_style.clone()} style="top: 0;"></div>
+            <div class={bar_style} style="bottom: 0;"></div>
+            <div class={exit_style}>
+                <Positioner position={Position::TopRight{margin: "0.75rem"}}>
+                    <XButton onclick={on_exit}/>
+                </Positioner>
+            </div>
+        </>
+    }
+}
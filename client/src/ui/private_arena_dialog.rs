@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::ui::UiEvent;
+use crate::Mk48Game;
+use common::entity::EntityData;
+use std::str::FromStr;
+use stylist::yew::styled_component;
+use web_sys::{HtmlInputElement, HtmlSelectElement, InputEvent};
+use yew::{html, html_nested, use_state, Html, TargetCast};
+use yew_frontend::dialog::dialog::Dialog;
+use yew_frontend::frontend::use_gctw;
+
+/// World radii to choose from, in meters.
+const RADII: [(&str, Option<u16>); 4] = [
+    ("Automatic", None),
+    ("Small", Some(1000)),
+    ("Medium", Some(2500)),
+    ("Large", Some(5000)),
+];
+
+/// Lets a player create a private arena, which only players they invite can join.
+#[styled_component(PrivateArenaDialog)]
+pub fn private_arena_dialog() -> Html {
+    let label_style = css! {
+        r#"
+        display: block;
+		user-select: none;
+		margin-bottom: 0.4em;
+        "#
+    };
+
+    let button_style = css! {
+        r#"
+        background-color: #0075ff;
+        border: 0;
+        border-radius: 0.25em;
+        color: white;
+        cursor: pointer;
+        font-size: 1em;
+        font-weight: bold;
+        margin-top: 0.5em;
+        padding: 0.7em;
+        "#
+    };
+
+    let gctw = use_gctw::<Mk48Game>();
+    let bots = use_state(|| false);
+    let radius = use_state(|| None::<u16>);
+    let max_level = use_state(|| EntityData::MAX_BOAT_LEVEL);
+
+    let on_toggle_bots = {
+        let bots = bots.clone();
+        move |_| bots.set(!*bots)
+    };
+
+    let on_select_radius = {
+        let radius = radius.clone();
+        move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            radius.set(u16::from_str(&value).ok());
+        }
+    };
+
+    let on_select_max_level = {
+        let max_level = max_level.clone();
+        move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlInputElement>().value();
+            if let Ok(level) = u8::from_str(&value) {
+                max_level.set(level.clamp(1, EntityData::MAX_BOAT_LEVEL));
+            }
+        }
+    };
+
+    let on_create = {
+        let (bots, radius, max_level) = (*bots, *radius, *max_level);
+        gctw.send_ui_event_callback
+            .reform(move |_| UiEvent::CreateArena {
+                bots,
+                radius,
+                max_level,
+            })
+    };
+
+    html! {
+        <Dialog title={"Private Arena"}>
+            <p>{"Only players you invite can join a private arena, which closes once everyone leaves."}</p>
+
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={*bots} oninput={on_toggle_bots}/>
+                {"Bots"}
+            </label>
+
+            <label class={label_style.clone()}>
+                {"World size "}
+                <select oninput={on_select_radius}>
+                    {RADII.iter().map(|&(name, r)| html_nested!{
+                        <option value={r.map(|r| r.to_string()).unwrap_or_default()} selected={*radius == r}>{name}</option>
+                    }).collect::<Html>()}
+                </select>
+            </label>
+
+            <label class={label_style}>
+                {format!("Highest ship level: {}", *max_level)}
+                <input
+                    type="range"
+                    min="1"
+                    max={EntityData::MAX_BOAT_LEVEL.to_string()}
+                    value={max_level.to_string()}
+                    oninput={on_select_max_level}
+                />
+            </label>
+
+            <button class={button_style} onclick={on_create}>{"Create and Join"}</button>
+        </Dialog>
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::armament::{group_armaments, Group};
+use crate::translation::Mk48Translation;
+use crate::ui::sprite::Sprite;
+use crate::ui::{UiEvent, UiStatusPlaying};
+use crate::Mk48Game;
+use common::protocol::Purchase;
+use common::util::level_to_score;
+use stylist::yew::styled_component;
+use yew::{html, Html, Properties};
+use yew_frontend::frontend::use_ui_event_callback;
+use yew_frontend::translation::use_translation;
+
+#[derive(PartialEq, Properties)]
+pub struct PurchaseOverlayProps {
+    pub status: UiStatusPlaying,
+    pub score: u32,
+}
+
+/// Offers repairs and reloads, paid for in coins, while near an HQ or oil platform.
+#[styled_component(PurchaseOverlay)]
+pub fn purchase_overlay(props: &PurchaseOverlayProps) -> Html {
+    let container_style = css!(
+        r#"
+        color: white;
+        margin: 0 auto 0.25rem auto;
+        text-align: center;
+        user-select: none;
+        "#
+    );
+
+    let buttons_style = css!(
+        r#"
+        display: flex;
+        flex-wrap: wrap;
+        gap: 0.25rem;
+        justify-content: center;
+
+        button {
+            align-items: center;
+            display: flex;
+            gap: 0.25rem;
+        }
+        "#
+    );
+
+    let t = use_translation();
+    let ui_event_callback = use_ui_event_callback::<Mk48Game>();
+    let status = &props.status;
+    let level = status.entity_type.data().level;
+
+    // Can't spend below the score needed for the current level.
+    let budget = props.score.saturating_sub(level_to_score(level));
+
+    let button = |purchase: Purchase, content: Html| {
+        let coins = purchase.coins(level, status.damage);
+        let onclick = ui_event_callback.reform(move |_| UiEvent::Purchase(purchase));
+        html! {
+            <button {onclick} disabled={purchase.cost(level, status.damage) > budget}>
+                {content}
+                <small>{t.purchase_cost_label(coins)}</small>
+            </button>
+        }
+    };
+
+    let reloads = group_armaments(
+        &status.entity_type.data().armaments,
+        &status.armament_consumption,
+        status.loadout,
+    )
+    .into_iter()
+    .filter(|Group { total, ready, .. }| ready < total);
+
+    html! {
+        <div id="purchase" class={container_style}>
+            <h3 style="margin: 0.25rem 0;">{t.purchase_label()}</h3>
+            <div class={buttons_style}>
+                if status.damage > 0.0 {
+                    {button(Purchase::Repair, html!{{t.purchase_repair_label()}})}
+                }
+                {reloads.map(|Group{entity_type, ..}| button(Purchase::Reload(entity_type), html!{
+                    <>
+                        {t.purchase_reload_label()}
+                        <Sprite {entity_type}/>
+                    </>
+                })).collect::<Html>()}
+            </div>
+        </div>
+    }
+}
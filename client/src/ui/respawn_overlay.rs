@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::translation::Mk48Translation;
+use crate::ui::round_overlay::RoundOverlay;
 use crate::ui::ship_menu::ShipMenu;
-use crate::ui::{UiEvent, UiStatusRespawning};
+use crate::ui::{Mk48Route, UiEvent, UiStatusRespawning};
 use crate::Mk48Game;
+use common::protocol::Round;
 use stylist::yew::styled_component;
 use yew::{html, Html, Properties};
+use yew_frontend::component::route_link::RouteLink;
 use yew_frontend::frontend::use_ui_event_callback;
 use yew_frontend::overlay::spawn::use_splash_screen;
 use yew_frontend::translation::use_translation;
@@ -15,6 +18,8 @@ use yew_frontend::translation::use_translation;
 pub struct RespawnOverlayProps {
     pub score: u32,
     pub status: UiStatusRespawning,
+    /// Current round, if the arena is in blitz mode.
+    pub round: Option<Round>,
 }
 
 #[styled_component(RespawnOverlay)]
@@ -51,10 +56,15 @@ pub fn respawn_overlay(props: &RespawnOverlayProps) -> Html {
 
     let t = use_translation();
     let (_paused, _transitioning, onanimationend) = use_splash_screen();
-    let onclick = use_ui_event_callback::<Mk48Game>().reform(UiEvent::Respawn);
+    let onclick = use_ui_event_callback::<Mk48Game>()
+        .reform(|(entity_type, loadout)| UiEvent::Respawn(entity_type, loadout));
     html! {
         <div id="death" class={container_style} {onanimationend}>
-            <h2 class={reason_style}>{t.death_reason(&props.status.death_reason)}</h2>
+            <h2 class={reason_style}>{t.death_reason_maybe_mutual(&props.status.death_reason, props.status.mutual)}</h2>
+            <RouteLink<Mk48Route> route={Mk48Route::Statistics}>{"Statistics"}</RouteLink<Mk48Route>>
+            if let Some(round) = props.round.clone() {
+                <RoundOverlay {round}/>
+            }
             <ShipMenu
                 score={props.score}
                 {onclick}
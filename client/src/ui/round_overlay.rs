@@ -0,0 +1,65 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translation::Mk48Translation;
+use common::protocol::Round;
+use stylist::yew::styled_component;
+use yew::{html, Html, Properties};
+use yew_frontend::translation::use_translation;
+
+#[derive(PartialEq, Properties)]
+pub struct RoundOverlayProps {
+    pub round: Round,
+}
+
+/// Shows the time left in a blitz round or, once it ends, its final standings.
+#[styled_component(RoundOverlay)]
+pub fn round_overlay(props: &RoundOverlayProps) -> Html {
+    let container_style = css!(
+        r#"
+        color: white;
+        margin: 0 auto 0.25rem auto;
+        text-align: center;
+        user-select: none;
+        "#
+    );
+
+    let table_style = css!(
+        r#"
+        border-spacing: 1rem 0.1rem;
+        margin: 0 auto;
+
+        td:last-child {
+            text-align: right;
+        }
+        "#
+    );
+
+    let t = use_translation();
+    let Round {
+        number,
+        remaining,
+        results,
+    } = &props.round;
+    let time = format!("{}:{:02}", remaining / 60, remaining % 60);
+
+    html! {
+        <div id="round" class={container_style}>
+            if let Some(results) = results {
+                <h3 style="margin: 0.5rem 0 0.25rem 0;">{t.round_results_label(*number)}</h3>
+                <table class={table_style}>
+                    {results.iter().enumerate().map(|(i, dto)| html! {
+                        <tr>
+                            <td>{format!("{}.", i + 1)}</td>
+                            <td>{dto.alias}</td>
+                            <td>{dto.score}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </table>
+                <p style="margin: 0.25rem 0;">{t.round_next_label(&time)}</p>
+            } else {
+                <b>{t.round_label(*number, &time)}</b>
+            }
+        </div>
+    }
+}
@@ -1,8 +1,10 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::settings::{Mk48Settings, ShadowSetting};
-use crate::ui::UiEvent;
+use crate::settings::{
+    AntialiasSetting, InterpolationSetting, LutSetting, Mk48Settings, ShadowSetting,
+};
+use crate::ui::{Mk48Route, UiEvent};
 use crate::Mk48Game;
 use client_util::browser_storage::BrowserStorages;
 use client_util::setting::CommonSettings;
@@ -12,7 +14,9 @@ use std::str::FromStr;
 use stylist::yew::styled_component;
 use web_sys::{HtmlSelectElement, InputEvent};
 use yew::{html, html_nested, Html, TargetCast};
+use yew_frontend::component::route_link::RouteLink;
 use yew_frontend::dialog::dialog::Dialog;
+use yew_frontend::dialog::sign_in_dialog::login_type_name;
 use yew_frontend::frontend::{use_core_state, use_ctw, use_gctw};
 use yew_frontend::translation::{use_translation, Translation};
 
@@ -62,6 +66,15 @@ pub fn settings_dialog() -> Html {
         )
     });
 
+    let photo_mode = gctw.settings_cache.photo_mode;
+    let on_toggle_photo_mode = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_photo_mode(!photo_mode, browser_storages);
+            },
+        )
+    });
+
     let fps_shown = gctw.settings_cache.fps_shown;
     let on_toggle_fps = gctw.change_settings_callback.reform(move |_| {
         Box::new(
@@ -71,6 +84,80 @@ pub fn settings_dialog() -> Html {
         )
     });
 
+    let network_stats_shown = gctw.settings_cache.network_stats_shown;
+    let on_toggle_network_stats = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_network_stats_shown(!network_stats_shown, browser_storages);
+            },
+        )
+    });
+
+    let low_bandwidth = gctw.settings_cache.low_bandwidth;
+    let on_toggle_low_bandwidth = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_low_bandwidth(!low_bandwidth, browser_storages);
+            },
+        )
+    });
+
+    let interpolation = gctw.settings_cache.interpolation;
+    let on_set_interpolation = gctw
+        .change_settings_callback
+        .reform(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            Box::new(
+                move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                    if let Ok(i) = InterpolationSetting::from_str(&value) {
+                        settings.set_interpolation(i, browser_storages);
+                    }
+                },
+            )
+        });
+
+    let exposure = gctw.settings_cache.exposure;
+    let on_set_exposure = gctw
+        .change_settings_callback
+        .reform(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            Box::new(
+                move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                    if let Ok(exposure) = f32::from_str(&value) {
+                        settings.set_exposure(exposure, browser_storages);
+                    }
+                },
+            )
+        });
+
+    let saturation = gctw.settings_cache.saturation;
+    let on_set_saturation = gctw
+        .change_settings_callback
+        .reform(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            Box::new(
+                move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                    if let Ok(saturation) = f32::from_str(&value) {
+                        settings.set_saturation(saturation, browser_storages);
+                    }
+                },
+            )
+        });
+
+    let lut = gctw.settings_cache.lut;
+    let on_set_lut = gctw
+        .change_settings_callback
+        .reform(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+            Box::new(
+                move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                    if let Ok(lut) = LutSetting::from_str(&value) {
+                        settings.set_lut(lut, browser_storages);
+                    }
+                },
+            )
+        });
+
     let animations = gctw.settings_cache.animations;
     let on_toggle_animations = {
         let graphics_callback = graphics_callback.clone();
@@ -85,6 +172,15 @@ pub fn settings_dialog() -> Html {
         })
     };
 
+    let auto_quality = gctw.settings_cache.auto_quality;
+    let on_toggle_auto_quality = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_auto_quality(!auto_quality, browser_storages);
+            },
+        )
+    });
+
     let dynamic_waves = gctw.settings_cache.dynamic_waves;
     let on_toggle_dynamic_waves = {
         let graphics_callback = graphics_callback.clone();
@@ -99,6 +195,41 @@ pub fn settings_dialog() -> Html {
         })
     };
 
+    let ship_models = gctw.settings_cache.ship_models;
+    let on_toggle_ship_models = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_ship_models(!ship_models, browser_storages);
+            },
+        )
+    });
+
+    let wildlife = gctw.settings_cache.wildlife;
+    let on_toggle_wildlife = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_wildlife(!wildlife, browser_storages);
+            },
+        )
+    });
+
+    let antialias = gctw.settings_cache.antialias;
+    let on_set_antialias = {
+        let graphics_callback = graphics_callback.clone();
+        gctw.change_settings_callback
+            .reform(move |event: InputEvent| {
+                let graphics_callback = graphics_callback.clone();
+                let value = event.target_unchecked_into::<HtmlSelectElement>().value();
+                Box::new(
+                    move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                        let s = AntialiasSetting::from_str(&value).unwrap();
+                        settings.set_antialias(s, browser_storages);
+                        graphics_callback.emit(());
+                    },
+                )
+            })
+    };
+
     let shadows = gctw.settings_cache.shadows;
     let on_set_shadows = {
         let graphics_callback = graphics_callback.clone();
@@ -134,6 +265,15 @@ pub fn settings_dialog() -> Html {
         )
     });
 
+    let colorblind = gctw.settings_cache.colorblind;
+    let on_toggle_colorblind = gctw.change_settings_callback.reform(move |_| {
+        Box::new(
+            move |settings: &mut Mk48Settings, browser_storages: &mut BrowserStorages| {
+                settings.set_colorblind(!colorblind, browser_storages);
+            },
+        )
+    });
+
     let high_contrast = ctw.setting_cache.high_contrast;
     let on_toggle_high_contrast = ctw.change_common_settings_callback.reform(move |_| {
         Box::new(
@@ -143,19 +283,19 @@ pub fn settings_dialog() -> Html {
         )
     });
 
-    let antialias = ctw.setting_cache.antialias;
-    let on_toggle_antialias = {
-        let graphics_callback = graphics_callback.clone();
-        ctw.change_common_settings_callback.reform(move |_| {
-            let graphics_callback = graphics_callback.clone();
+    let ui_scale = ctw.setting_cache.ui_scale;
+    let on_set_ui_scale = ctw
+        .change_common_settings_callback
+        .reform(move |event: InputEvent| {
+            let value = event.target_unchecked_into::<HtmlSelectElement>().value();
             Box::new(
                 move |settings: &mut CommonSettings, browser_storages: &mut BrowserStorages| {
-                    settings.set_antialias(!antialias, browser_storages);
-                    graphics_callback.emit(());
+                    if let Ok(ui_scale) = f32::from_str(&value) {
+                        settings.set_ui_scale(ui_scale, browser_storages);
+                    }
                 },
             )
-        })
-    };
+        });
 
     let selected_server_id = ctw.setting_cache.server_id;
     let on_select_server_id = {
@@ -174,6 +314,11 @@ pub fn settings_dialog() -> Html {
                 {"Cinematic Mode"}
             </label>
 
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={photo_mode} oninput={on_toggle_photo_mode}/>
+                {"Photo Mode"}
+            </label>
+
             <label class={label_style.clone()}>
                 <input type="checkbox" checked={circle_hud} disabled={cinematic} oninput={on_toggle_circle_hud}/>
                 {"Circle HUD"}
@@ -184,11 +329,37 @@ pub fn settings_dialog() -> Html {
                 {"High Contrast"}
             </label>
 
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={colorblind} oninput={on_toggle_colorblind}/>
+                {"Colorblind Mode"}
+            </label>
+
+            <select
+                oninput={on_set_ui_scale}
+                class={select_style.clone()}
+            >
+                {[0.75f32, 1.0, 1.25, 1.5, 2.0].into_iter().map(|v| html_nested!{
+                    <option value={v.to_string()} selected={ui_scale == v}>{format!("{v}x UI Scale")}</option>
+                }).collect::<Html>()}
+            </select>
+
             <label class={label_style.clone()}>
                 <input type="checkbox" checked={fps_shown} oninput={on_toggle_fps}/>
                 {"FPS Counter"}
             </label>
 
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={network_stats_shown} oninput={on_toggle_network_stats}/>
+                {"Network Statistics"}
+            </label>
+
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={low_bandwidth} oninput={on_toggle_low_bandwidth}/>
+                {"Low Bandwidth"}
+            </label>
+
+            <RouteLink<Mk48Route> route={Mk48Route::KeyBindings}>{"Key Bindings"}</RouteLink<Mk48Route>>
+
             <label class={label_style.clone()}>
                 <input type="checkbox" checked={chat_dialog_shown} oninput={on_toggle_chat}/>
                 {"Radio"}
@@ -201,7 +372,7 @@ pub fn settings_dialog() -> Html {
                 if selected_server_id.is_none() || core_state.servers.is_empty() {
                     <option value="unknown" selected={true}>{"Unknown server"}</option>
                 }
-                {core_state.servers.values().map(|&ServerDto{server_id, region_id, player_count}| {
+                {core_state.servers.values().map(|&ServerDto{server_id, region_id, player_count, ..}| {
                     let region_str = region_id.as_human_readable_str();
                     html_nested!{
                         <option value={server_id.0.to_string()} selected={selected_server_id == Some(server_id)}>
@@ -211,6 +382,29 @@ pub fn settings_dialog() -> Html {
                 }).collect::<Html>()}
             </select>
 
+            <RouteLink<Mk48Route> route={Mk48Route::Servers}>{"Server Browser"}</RouteLink<Mk48Route>>
+            <RouteLink<Mk48Route> route={Mk48Route::PrivateArena}>{"Private Arena"}</RouteLink<Mk48Route>>
+            <RouteLink<Mk48Route> route={Mk48Route::SignIn}>
+                {core_state.login_type.map(|login_type| format!("Signed in with {}", login_type_name(login_type))).unwrap_or_else(|| String::from("Sign In"))}
+            </RouteLink<Mk48Route>>
+
+            if let Some(thumbnail) = selected_server_id.and_then(|id| core_state.servers.get(&id)).and_then(|server| server.thumbnail.as_deref()) {
+                <img src={thumbnail.to_owned()} alt={"Map"} style="display: block; width: 64px; height: 64px; image-rendering: pixelated;"/>
+            }
+
+            <select
+                oninput={on_set_interpolation}
+                class={select_style.clone()}
+            >
+                {std::iter::once((InterpolationSetting::Auto, String::from("Auto Interpolation")))
+                    .chain(InterpolationSetting::MANUAL_OPTIONS.into_iter().map(|millis| {
+                        (InterpolationSetting::Manual(millis), format!("{millis}ms Interpolation"))
+                    }))
+                    .map(|(v, d)| html_nested!{
+                        <option value={v.to_string()} selected={interpolation == v}>{d}</option>
+                    }).collect::<Html>()}
+            </select>
+
             <h3>{"Graphics"}</h3>
 
             <label class={label_style.clone()}>
@@ -219,8 +413,8 @@ pub fn settings_dialog() -> Html {
             </label>
 
             <label class={label_style.clone()}>
-                <input type="checkbox" checked={antialias} oninput={on_toggle_antialias}/>
-                {"Antialiasing"}
+                <input type="checkbox" checked={auto_quality} oninput={on_toggle_auto_quality}/>
+                {"Auto Quality"}
             </label>
 
             <label class={label_style.clone()}>
@@ -228,6 +422,16 @@ pub fn settings_dialog() -> Html {
                 {"Dynamic Waves"}
             </label>
 
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={ship_models} oninput={on_toggle_ship_models}/>
+                {"3D Ships"}
+            </label>
+
+            <label class={label_style.clone()}>
+                <input type="checkbox" checked={wildlife} oninput={on_toggle_wildlife}/>
+                {"Wildlife"}
+            </label>
+
             <select
                 oninput={on_set_shadows}
                 class={select_style.clone()}
@@ -236,6 +440,42 @@ pub fn settings_dialog() -> Html {
                     <option value={v.to_string()} selected={shadows == v}>{d}</option>
                 }).collect::<Html>()}
             </select>
+
+            <select
+                oninput={on_set_antialias}
+                class={select_style.clone()}
+            >
+                {[(AntialiasSetting::None, "No Antialiasing"), (AntialiasSetting::Fxaa, "FXAA"), (AntialiasSetting::Msaa, "MSAA"), (AntialiasSetting::Taa, "TAA")].into_iter().map(|(v, d)| html_nested!{
+                    <option value={v.to_string()} selected={antialias == v}>{d}</option>
+                }).collect::<Html>()}
+            </select>
+
+            <select
+                oninput={on_set_exposure}
+                class={select_style.clone()}
+            >
+                {[0.5f32, 0.75, 1.0, 1.25, 1.5, 2.0].into_iter().map(|v| html_nested!{
+                    <option value={v.to_string()} selected={exposure == v}>{format!("{v}x Exposure")}</option>
+                }).collect::<Html>()}
+            </select>
+
+            <select
+                oninput={on_set_saturation}
+                class={select_style.clone()}
+            >
+                {[0.0f32, 0.5, 1.0, 1.25, 1.5].into_iter().map(|v| html_nested!{
+                    <option value={v.to_string()} selected={saturation == v}>{format!("{v}x Saturation")}</option>
+                }).collect::<Html>()}
+            </select>
+
+            <select
+                oninput={on_set_lut}
+                class={select_style.clone()}
+            >
+                {LutSetting::ALL.into_iter().map(|v| html_nested!{
+                    <option value={v.to_string()} selected={lut == v}>{v.label()}</option>
+                }).collect::<Html>()}
+            </select>
         </Dialog>
     }
 }
@@ -2,12 +2,15 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::armament::{group_armaments, Group};
+use crate::key_bindings::Action;
 use crate::translation::Mk48Translation;
 use crate::ui::sprite::Sprite;
 use crate::ui::{UiEvent, UiStatusPlaying};
 use crate::Mk48Game;
+use client_util::keyboard::Key;
 use common::altitude::Altitude;
-use common::entity::{EntityData, EntitySubKind, EntityType};
+use common::entity::{EntityData, EntityKind, EntitySubKind, EntityType};
+use common::protocol::Doctrine;
 use core_protocol::id::LanguageId;
 use stylist::yew::styled_component;
 use stylist::{css, StyleSource};
@@ -15,7 +18,7 @@ use web_sys::MouseEvent;
 use yew::{classes, html, html_nested, AttrValue, Callback, Html, Properties};
 use yew_frontend::component::positioner::Position;
 use yew_frontend::component::section::Section;
-use yew_frontend::frontend::use_ui_event_callback;
+use yew_frontend::frontend::{use_gctw, use_ui_event_callback};
 use yew_frontend::translation::use_translation;
 
 #[derive(Properties, PartialEq)]
@@ -79,13 +82,14 @@ pub fn ship_controls(props: &ShipControlsProps) -> Html {
     };
 
     let t = use_translation();
+    let key_bindings = use_gctw::<Mk48Game>().settings_cache.key_bindings;
     let status = &props.status;
     html! {
         <Section id="controls" name={data.label.clone()} position={props.position} style={props.style.clone()} closable={false}>
             if status.entity_type.data().armaments.is_empty() {
                 <small>{t.entity_kind_hint(status.entity_type.data().kind, status.entity_type.data().sub_kind)}</small>
             } else {
-                {group_armaments(&status.entity_type.data().armaments, &*status.armament_consumption).into_iter().map(|Group{entity_type, total, ready}| {
+                {group_armaments(&status.entity_type.data().armaments, &*status.armament_consumption, status.loadout).into_iter().map(|Group{entity_type, total, ready}| {
                     let onclick = select_factory.clone()(entity_type);
                     html_nested!{
                         <div class={classes!(button_style.clone(), onclick.is_none().then(|| button_selected_style.clone()))} {onclick}>
@@ -95,8 +99,10 @@ pub fn ship_controls(props: &ShipControlsProps) -> Html {
                     }
                 }).collect::<Html>()}
             }
-            {surface_button(t, props.status.entity_type, props.status.submerge, &button_style, &button_selected_style, &ui_event_callback)}
-            {active_sensor_button(t, props.status.entity_type, props.status.active, props.status.altitude, &button_style, &button_selected_style, &ui_event_callback)}
+            {surface_button(t, props.status.entity_type, props.status.submerge, key_bindings.key(Action::Surface), &button_style, &button_selected_style, &ui_event_callback)}
+            {active_sensor_button(t, props.status.entity_type, props.status.active, props.status.altitude, key_bindings.key(Action::ActiveSensors), &button_style, &button_selected_style, &ui_event_callback)}
+            {doctrine_button(t, props.status.entity_type, props.status.doctrine, &button_style, &button_selected_style, &ui_event_callback)}
+            {fly_aircraft_button(t, props.status.entity_type, props.status.aircraft_controlled, &button_style, &button_selected_style, &ui_event_callback)}
         </Section>
     }
 }
@@ -105,6 +111,7 @@ fn surface_button(
     t: LanguageId,
     entity_type: EntityType,
     submerge: bool,
+    key: Key,
     button_style: &StyleSource,
     button_selected_style: &StyleSource,
     ui_event_callback: &Callback<UiEvent>,
@@ -115,7 +122,7 @@ fn surface_button(
         let onclick = ui_event_callback.reform(move |_: MouseEvent| UiEvent::Submerge(!submerge));
 
         html! {
-            <div class={classes!(button_style.clone(), (!submerge).then(|| button_selected_style.clone()))} {onclick} title={t.ship_surface_hint()}>
+            <div class={classes!(button_style.clone(), (!submerge).then(|| button_selected_style.clone()))} {onclick} title={t.ship_surface_hint(key)}>
                 {t.ship_surface_label()}
             </div>
         }
@@ -127,6 +134,7 @@ fn active_sensor_button(
     entity_type: EntityType,
     active: bool,
     altitude: Altitude,
+    key: Key,
     button_style: &StyleSource,
     button_selected_style: &StyleSource,
     ui_event_callback: &Callback<UiEvent>,
@@ -145,7 +153,7 @@ fn active_sensor_button(
             )
             .intersperse(" / ")
             .collect::<String>();
-        let title = t.sensor_active_hint(&sensors);
+        let title = t.sensor_active_hint(&sensors, key);
         let onclick = ui_event_callback.reform(move |_: MouseEvent| UiEvent::Active(!active));
 
         html! {
@@ -155,3 +163,58 @@ fn active_sensor_button(
         }
     }
 }
+
+/// Shows the current anti-aircraft doctrine, and cycles through doctrines when clicked.
+fn doctrine_button(
+    t: LanguageId,
+    entity_type: EntityType,
+    doctrine: Doctrine,
+    button_style: &StyleSource,
+    button_selected_style: &StyleSource,
+    ui_event_callback: &Callback<UiEvent>,
+) -> Html {
+    if entity_type.data().anti_aircraft <= 0.0 {
+        Html::default()
+    } else {
+        let next = match doctrine {
+            Doctrine::AircraftFirst => Doctrine::MissilesFirst,
+            Doctrine::MissilesFirst => Doctrine::HoldFire,
+            Doctrine::HoldFire => Doctrine::AircraftFirst,
+        };
+        let onclick = ui_event_callback.reform(move |_: MouseEvent| UiEvent::Doctrine(next));
+        let holding_fire = doctrine == Doctrine::HoldFire;
+
+        html! {
+            <div class={classes!(button_style.clone(), (!holding_fire).then(|| button_selected_style.clone()))} {onclick}>
+                {t.ship_doctrine_label(doctrine)}
+            </div>
+        }
+    }
+}
+
+fn fly_aircraft_button(
+    t: LanguageId,
+    entity_type: EntityType,
+    aircraft_controlled: bool,
+    button_style: &StyleSource,
+    button_selected_style: &StyleSource,
+    ui_event_callback: &Callback<UiEvent>,
+) -> Html {
+    let carries_aircraft = entity_type
+        .data()
+        .armaments
+        .iter()
+        .any(|armament| armament.entity_type.data().kind == EntityKind::Aircraft);
+    if !carries_aircraft {
+        Html::default()
+    } else {
+        let onclick = ui_event_callback
+            .reform(move |_: MouseEvent| UiEvent::ControlAircraft(!aircraft_controlled));
+
+        html! {
+            <div class={classes!(button_style.clone(), aircraft_controlled.then(|| button_selected_style.clone()))} {onclick}>
+                {t.ship_fly_aircraft_label()}
+            </div>
+        }
+    }
+}
@@ -28,7 +28,8 @@ pub struct ShipMenuProps {
     /// If some, upgrading. Otherwise, spawning.
     pub entity: Option<(EntityType, Vec2)>,
     pub score: u32,
-    pub onclick: Callback<EntityType>,
+    /// Called with the chosen boat and armament loadout.
+    pub onclick: Callback<(EntityType, u8)>,
     #[prop_or(true)]
     pub open: bool,
     #[prop_or(true)]
@@ -70,9 +71,29 @@ pub fn ship_menu(props: &ShipMenuProps) -> Html {
     "#
     );
 
+    let loadouts_style = css!(
+        r#"
+        display: flex;
+        gap: 0.25rem;
+        justify-content: center;
+
+        button {
+            font-size: 0.7rem;
+            padding: 0.1rem 0.3rem;
+        }
+    "#
+    );
+
     let entity_type = props.entity.map(|(entity_type, _)| entity_type);
+    // Upgrades may skip levels, or dead end (see `EntityData::upgrades_to`).
     let min_level = entity_type
-        .map(|entity_type| entity_type.data().level + 1)
+        .map(|entity_type| {
+            entity_type
+                .upgrade_options(props.score, false)
+                .map(|upgrade| upgrade.data().level)
+                .min()
+                .unwrap_or(u8::MAX)
+        })
         .unwrap_or(1);
     let max_level = score_to_level(props.score);
     let level = use_state_eq(|| max_level);
@@ -90,18 +111,23 @@ pub fn ship_menu(props: &ShipMenuProps) -> Html {
         level.set(level.clamp(min_level, max_level));
     }
 
-    let select_factory =
-        |entity_type: EntityType| -> Result<Callback<MouseEvent>, (IconId, &'static str)> {
-            if let Some(lock_icon) = locker.lock_icon(
-                entity_type,
-                props.entity.map(|(_, position)| position),
-                &rewarded_ad,
-            ) {
-                Err(lock_icon)
-            } else {
-                Ok(props.onclick.reform(move |_| entity_type))
-            }
-        };
+    let select_factory = |entity_type: EntityType,
+                          loadout: u8|
+     -> Result<Callback<MouseEvent>, (IconId, &'static str)> {
+        if let Some(lock_icon) = locker.lock_icon(
+            entity_type,
+            props.entity.map(|(_, position)| position),
+            &rewarded_ad,
+        ) {
+            Err(lock_icon)
+        } else {
+            Ok(props.onclick.reform(move |e: MouseEvent| {
+                // Loadout buttons are inside the sprite, which would otherwise pick the default.
+                e.stop_propagation();
+                (entity_type, loadout)
+            }))
+        }
+    };
 
     let increment_level_factory = |increment: i8| -> SectionArrow {
         let new = level.saturating_add_signed(increment);
@@ -153,10 +179,11 @@ pub fn ship_menu(props: &ShipMenuProps) -> Html {
                 {ships.into_iter().map(|entity_type| {
                     let mut onclick: Option<Callback<MouseEvent>> = None;
                     let mut icon_title: Option<(IconId, &'static str)> = None;
-                    match select_factory(entity_type) {
+                    match select_factory(entity_type, 0) {
                         Ok(s) => onclick = Some(s),
                         Err(it) => icon_title = Some(it),
                     };
+                    let loadouts = entity_type.data().loadouts;
 
                     html_nested!{
                         <Sprite
@@ -166,6 +193,12 @@ pub fn ship_menu(props: &ShipMenuProps) -> Html {
                             >
                             if let Some((icon_id, title)) = icon_title {
                                 <Icon {icon_id} {title} onclick={attempt_to_unlock_factory(entity_type)}/>
+                            } else if !loadouts.is_empty() {
+                                <div class={loadouts_style.clone()}>
+                                    {loadouts.iter().enumerate().map(|(i, &label)| html!{
+                                        <button onclick={select_factory(entity_type, i as u8).ok()}>{label}</button>
+                                    }).collect::<Html>()}
+                                </div>
                             }
                         </Sprite>
                     }
@@ -3,6 +3,7 @@
 
 use crate::armament::{group_armaments, Group};
 use crate::translation::Mk48Translation;
+use crate::ui::Mk48Route;
 use crate::ui::sprite::Sprite;
 use common::altitude::Altitude;
 use common::entity::{EntityData, EntityKind, EntityType};
@@ -13,6 +14,7 @@ use stylist::yew::styled_component;
 use stylist::StyleSource;
 use yew::{html, html_nested, Html};
 use yew_frontend::component::link::Link;
+use yew_frontend::component::route_link::RouteLink;
 use yew_frontend::dialog::dialog::Dialog;
 use yew_frontend::translation::use_translation;
 
@@ -30,6 +32,7 @@ pub fn ships_dialog() -> Html {
     html! {
         <Dialog title={"Ships"}>
             <p>{"The following is a list of all ships in the game, and their weapons. Note that certain values are approximate and may be affected by other factors. For example, weapon damage depends on hit location."}</p>
+            <p>{"To see where each weapon is mounted, visit the "}<RouteLink<Mk48Route> route={Mk48Route::DryDock}>{"dry dock"}</RouteLink<Mk48Route>>{"."}</p>
 
             <table>
                 {EntityType::iter().filter(|t| t.data().kind == EntityKind::Boat).map(|entity_type| html_nested!{
@@ -103,6 +106,9 @@ fn entity_card(
                         if data.torpedo_resistance != 0.0 {
                             <li>{format!("Torpedo Resistance: {}%", (data.torpedo_resistance * 100.0) as u16)}</li>
                         }
+                        if data.armor_bow != 1.0 || data.armor_belt != 1.0 || data.armor_stern != 1.0 {
+                            <li>{format!("Damage Taken (bow/belt/stern): {}%/{}%/{}%", (data.armor_bow * 100.0) as u16, (data.armor_belt * 100.0) as u16, (data.armor_stern * 100.0) as u16)}</li>
+                        }
                         if data.stealth != 0.0 {
                             <li>{format!("Stealth: {}%", (data.stealth * 100.0) as u16)}</li>
                         }
@@ -117,7 +123,7 @@ fn entity_card(
                     <Sprite {entity_type}/>
                 </td>
             </tr>
-            {group_armaments(&data.armaments, &[]).into_iter().map(|Group{entity_type, total, ..}| html_nested!{
+            {group_armaments(&data.armaments, &[], 0).into_iter().map(|Group{entity_type, total, ..}| html_nested!{
                 <tr>
                     <td colspan="2">
                         {entity_card(t, table_style, entity_type, Some(total))}
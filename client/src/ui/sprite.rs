@@ -52,12 +52,16 @@ pub fn sprite(props: &SpriteProps) -> Html {
         "#
     );
 
+    let placeholder_style = css!(
+        r#"
+        color: white;
+        font-size: 0.8em;
+        padding: 0.5em;
+        "#
+    );
+
     let t = use_translation();
     let data: &'static EntityData = props.entity_type.data();
-    let sprite = SPRITE_SHEET
-        .sprites
-        .get(props.entity_type.as_str())
-        .expect(&format!("should have sprite for {:?}", props.entity_type));
     let title = props.title.clone().unwrap_or_else(|| {
         format!(
             "{} ({})",
@@ -67,6 +71,20 @@ pub fn sprite(props: &SpriteProps) -> Html {
         .into()
     });
 
+    let Some(sprite) = SPRITE_SHEET.sprites.get(props.entity_type.as_str()) else {
+        // Some entities, like nets, are drawn procedurally in game so have no sprite.
+        return html! {
+            <div {title} onclick={props.onclick.clone()} class={classes!(container_style, placeholder_style, props.class.clone())}>
+                {data.label}
+                if let Some(children) = props.children.clone() {
+                    <div class={children_style}>
+                        {children}
+                    </div>
+                }
+            </div>
+        };
+    };
+
     html! {
         <div onclick={props.onclick.clone()} class={classes!(container_style, props.class.clone())} style={format!("width: {}px; height: {}px;", sprite.width, sprite.height)}>
             <div {title} class={classes!(image_style, props.image_class.clone())} style={format!("background-position: -{}px -{}px; width: {}px; height: {}px;", sprite.x, sprite.y, sprite.width, sprite.height)}></div>
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::stats::LifeStats;
+use stylist::yew::styled_component;
+use yew::{html, html_nested, Html, Properties};
+use yew_frontend::dialog::dialog::Dialog;
+
+#[derive(PartialEq, Properties)]
+pub struct StatisticsDialogProps {
+    /// Statistics about the player's last boat, if it sank.
+    pub stats: Option<LifeStats>,
+}
+
+/// Summarizes the life of the player's last boat.
+#[styled_component(StatisticsDialog)]
+pub fn statistics_dialog(props: &StatisticsDialogProps) -> Html {
+    let table_style = css!(
+        r#"
+        border-spacing: 1rem 0.25rem;
+        text-align: left;
+
+        td:not(:first-child), th:not(:first-child) {
+            text-align: right;
+        }
+        "#
+    );
+
+    html! {
+        <Dialog title={"Statistics"}>
+            if let Some(stats) = &props.stats {
+                <table class={table_style.clone()}>
+                    <tr>
+                        <td>{"Survived"}</td>
+                        <td>{duration(stats.survived.to_secs())}</td>
+                    </tr>
+                    <tr>
+                        <td>{"Travelled"}</td>
+                        <td>{format!("{:.1}km", stats.travelled * 0.001)}</td>
+                    </tr>
+                    <tr>
+                        <td>{"Accuracy"}</td>
+                        <td>{stats.accuracy().map(|accuracy| format!("{:.0}%", accuracy * 100.0)).unwrap_or_else(|| String::from("-"))}</td>
+                    </tr>
+                    <tr>
+                        <td>{"Boats sunk"}</td>
+                        <td>{stats.sunk.len()}</td>
+                    </tr>
+                </table>
+                if !stats.sunk.is_empty() {
+                    <h3>{"Boats sunk"}</h3>
+                    <table class={table_style.clone()}>
+                        {stats.sunk.iter().map(|entity_type| {
+                            let data = entity_type.data();
+                            html_nested!{
+                                <tr>
+                                    <td>{data.label}</td>
+                                    <td>{format!("Level {}", data.level)}</td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </table>
+                }
+                if !stats.weapons.is_empty() {
+                    <h3>{"Weapons"}</h3>
+                    <table class={table_style}>
+                        <tr>
+                            <th>{"Weapon"}</th>
+                            <th>{"Fired"}</th>
+                            <th>{"Hits"}</th>
+                            <th>{"Damage dealt"}</th>
+                            <th>{"Damage taken"}</th>
+                        </tr>
+                        {stats.weapons.iter().map(|weapon| html_nested!{
+                            <tr>
+                                <td>{weapon.weapon.data().label}</td>
+                                <td>{weapon.fired}</td>
+                                <td>{weapon.hits}</td>
+                                <td>{format!("{:.2}", weapon.damage_dealt)}</td>
+                                <td>{format!("{:.2}", weapon.damage_taken)}</td>
+                            </tr>
+                        }).collect::<Html>()}
+                    </table>
+                }
+            } else {
+                <p>{"Statistics will be available once your boat sinks."}</p>
+            }
+        </Dialog>
+    }
+}
+
+/// Formats seconds as minutes and seconds.
+fn duration(secs: f32) -> String {
+    let secs = secs as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
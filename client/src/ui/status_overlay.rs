@@ -1,12 +1,19 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::ui::UiStatusPlaying;
-use common::entity::EntityData;
+use crate::translation::Mk48Translation;
+use crate::ui::{UiEvent, UiStatusPlaying};
+use crate::Mk48Game;
+use common::altitude::Altitude;
+use common::entity::{DisabledModules, EntityData, EntitySubKind, StatusEffects};
 use common::util::level_to_score;
+use core_protocol::id::LanguageId;
 use glam::Vec2;
-use yew::{function_component, html, Html, Properties};
+use stylist::yew::styled_component;
+use web_sys::MouseEvent;
+use yew::{html, Callback, Classes, Html, Properties};
 use yew_frontend::component::meter::Meter;
+use yew_frontend::frontend::use_ui_event_callback;
 use yew_frontend::translation::{use_translation, Translation};
 
 #[derive(Properties, PartialEq)]
@@ -16,9 +23,24 @@ pub struct StatusProps {
     pub status: UiStatusPlaying,
 }
 
-#[function_component(StatusOverlay)]
+#[styled_component(StatusOverlay)]
 pub fn status_overlay(props: &StatusProps) -> Html {
+    let flash_style = css!(
+        r#"
+        animation: flash 0.5s alternate infinite;
+        cursor: default;
+        font-size: 1.5rem;
+        user-select: none;
+
+        @keyframes flash {
+            from { opacity: 1; }
+            to   { opacity: 0.3; }
+        }
+        "#
+    );
+
     let t = use_translation();
+    let ui_event_callback = use_ui_event_callback::<Mk48Game>();
     let status = &props.status;
     let level = status.entity_type.data().level;
     let next_level = level + 1;
@@ -45,6 +67,12 @@ pub fn status_overlay(props: &StatusProps) -> Html {
                     {format!("{:\u{00A0}>5.1}\u{00A0}fps", fps)}
                 }
             </h2>
+            if !status.disabled.is_empty() || !status.effects.is_empty() {
+                {damage_icons(status.disabled, status.effects, t, flash_style.into())}
+            }
+            if status.entity_type.data().sub_kind == EntitySubKind::Submarine {
+                {depth_gauge(status, &ui_event_callback)}
+            }
             if next_level <= EntityData::MAX_BOAT_LEVEL {
                 <Meter value={progress}>{t.upgrade_to_level_progress((progress * 100.0) as u8, next_level as u32)}</Meter>
             }
@@ -52,6 +80,80 @@ pub fn status_overlay(props: &StatusProps) -> Html {
     }
 }
 
+/// Shows a flashing icon for each module knocked out by critical hits, and each damage over time
+/// effect.
+fn damage_icons(
+    disabled: DisabledModules,
+    effects: StatusEffects,
+    t: LanguageId,
+    class: Classes,
+) -> Html {
+    let icon = |icon: String, title: String| {
+        html! {
+            <span class={class.clone()} {title}>{icon}</span>
+        }
+    };
+
+    html! {
+        <div style="display: flex; gap: 0.5rem; justify-content: center; margin-bottom: 0.25rem;">
+            if disabled.propulsion() {
+                {icon(String::from("⚙️"), t.module_propulsion_label().to_owned())}
+            }
+            if disabled.steering() {
+                {icon(String::from("🧭"), t.module_steering_label().to_owned())}
+            }
+            {disabled.turrets().map(|i| icon(format!("🎯{}", i + 1), t.module_turret_label(i + 1))).collect::<Html>()}
+            if effects.fire() {
+                {icon(String::from("🔥"), t.effect_fire_label().to_owned())}
+            }
+            if effects.flooding() {
+                {icon(String::from("🌊"), t.effect_flooding_label().to_owned())}
+            }
+        </div>
+    }
+}
+
+/// Shows a submarine's depth relative to its crush depth, and buttons to change its target depth.
+fn depth_gauge(status: &UiStatusPlaying, ui_event_callback: &Callback<UiEvent>) -> Html {
+    /// How much each button changes the target depth.
+    const STEP: Altitude = Altitude::from_whole_meters(10);
+
+    let data = status.entity_type.data();
+    let crush_depth = -data.depth;
+    let test_depth = -data.test_depth();
+    let depth_target = status.depth.unwrap_or(test_depth);
+
+    let change_depth = |change: Altitude| {
+        let depth = (depth_target + change).clamp(crush_depth, Altitude::ZERO);
+        ui_event_callback.reform(move |_: MouseEvent| UiEvent::Depth(Some(depth)))
+    };
+    let onclick_deeper = change_depth(-STEP);
+    let onclick_shallower = change_depth(STEP);
+
+    let depth = status.altitude.min(Altitude::ZERO);
+    let color = if depth < test_depth {
+        0xb12f00
+    } else {
+        0x0084b1
+    };
+    let value = depth.to_meters() / crush_depth.to_meters();
+
+    html! {
+        <div style="display: flex; gap: 0.25rem; margin-bottom: 0.25rem;">
+            <button onclick={onclick_shallower}>{"▲"}</button>
+            <Meter {value} {color}>
+                {format!(
+                    "{}m / {}m ({}m max)",
+                    -depth.to_meters() as i16,
+                    -depth_target.to_meters() as i16,
+                    -crush_depth.to_meters() as i16
+                )}
+            </Meter>
+            <button onclick={onclick_deeper}>{"▼"}</button>
+        </div>
+    }
+}
+
 fn fmt_position(position: Vec2) -> String {
     fn fmt_coordinate(coordinate: f32, positive: char, negative: char) -> String {
         format!(
@@ -1,13 +1,21 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::ui::instructions::Instructions;
+use crate::translation::Mk48Translation;
+use crate::ui::instructions::{InstructionStatus, Instructions};
 use crate::ui::ship_menu::ShipMenu;
+use crate::ui::sprite::Sprite;
 use crate::ui::{UiEvent, UiStatusPlaying};
 use crate::Mk48Game;
-use yew::{function_component, html, Html, Properties};
+use common::entity::{EntityData, EntityType};
+use core_protocol::id::LanguageId;
+use stylist::yew::styled_component;
+use stylist::StyleSource;
+use yew::{function_component, html, html_nested, Callback, Html, Properties};
 use yew_frontend::component::positioner::Position;
+use yew_frontend::component::section::Section;
 use yew_frontend::frontend::use_ui_event_callback;
+use yew_frontend::translation::use_translation;
 
 #[derive(Properties, PartialEq)]
 pub struct UpgradeOverlayProps {
@@ -18,15 +26,128 @@ pub struct UpgradeOverlayProps {
 
 #[function_component(UpgradeOverlay)]
 pub fn upgrade_overlay(props: &UpgradeOverlayProps) -> Html {
-    let onclick = use_ui_event_callback::<Mk48Game>().reform(UiEvent::Upgrade);
+    let onclick = use_ui_event_callback::<Mk48Game>()
+        .reform(|(entity_type, loadout)| UiEvent::Upgrade(entity_type, loadout));
+    let instructions = props.status.instruction_status != InstructionStatus::default();
     html! {
         <ShipMenu
             entity={Some((props.status.entity_type, props.status.position))}
             score={props.score}
             position={props.position.clone()}
-            {onclick}
+            onclick={onclick.clone()}
         >
-            <Instructions position={props.position} status={props.status.instruction_status}/>
+            if instructions {
+                <Instructions position={props.position} status={props.status.instruction_status}/>
+            } else {
+                <UpgradeTree
+                    position={props.position}
+                    entity_type={props.status.entity_type}
+                    score={props.score}
+                    can_respec={props.status.can_respec}
+                    {onclick}
+                />
+            }
         </ShipMenu>
     }
 }
+
+#[derive(Properties, PartialEq)]
+struct UpgradeTreeProps {
+    position: Position,
+    entity_type: EntityType,
+    score: u32,
+    can_respec: bool,
+    onclick: Callback<(EntityType, u8)>,
+}
+
+/// Shows where the player's boat can go in the upgrade tree, including branches and dead ends, and
+/// offers the free respec if it hasn't been used this life.
+#[styled_component(UpgradeTree)]
+fn upgrade_tree(props: &UpgradeTreeProps) -> Html {
+    let tree_style = css!(
+        r#"
+        color: white;
+        margin: 0;
+        padding-left: 1rem;
+        user-select: none;
+        "#
+    );
+
+    let respec_style = css!(
+        r#"
+        display: flex;
+        flex-wrap: wrap;
+        gap: 0.5rem;
+        justify-content: center;
+        "#
+    );
+
+    let t = use_translation();
+
+    // Respecs at the current level switch branches, without giving up any progress.
+    let level = props.entity_type.data().level;
+    let respecs: Vec<_> = if props.can_respec {
+        props
+            .entity_type
+            .respec_options(props.score, false)
+            .filter(|respec| respec.data().level == level)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    html! {
+        <Section
+            id="upgrade_tree"
+            name={t.upgrade_tree_label()}
+            position={props.position}
+            style="max-width: 30%;"
+            open={false}
+        >
+            <ul class={tree_style.clone()}>
+                {upgrade_branch(t, props.entity_type, 2, &tree_style)}
+            </ul>
+            if !respecs.is_empty() {
+                <h3>{t.upgrade_respec_label()}</h3>
+                <div class={respec_style}>
+                    {respecs.into_iter().map(|entity_type| html_nested!{
+                        <Sprite {entity_type} onclick={props.onclick.reform(move |_| (entity_type, 0))}/>
+                    }).collect::<Html>()}
+                </div>
+            }
+        </Section>
+    }
+}
+
+/// A boat, and the boats it upgrades to, `depth` levels deep.
+fn upgrade_branch(
+    t: LanguageId,
+    entity_type: EntityType,
+    depth: usize,
+    tree_style: &StyleSource,
+) -> Html {
+    let data = entity_type.data();
+    let upgrades = next_upgrades(entity_type);
+    html! {
+        <li>
+            {data.label}
+            if upgrades.is_empty() && data.level < EntityData::MAX_BOAT_LEVEL {
+                {format!(" ({})", t.upgrade_dead_end_label())}
+            } else if depth > 0 {
+                <ul class={tree_style.clone()}>
+                    {upgrades.into_iter().map(|upgrade| upgrade_branch(t, upgrade, depth - 1, tree_style)).collect::<Html>()}
+                </ul>
+            }
+        </li>
+    }
+}
+
+/// Boats one step up the upgrade tree, regardless of score. If the tree doesn't branch here, that's
+/// every boat of the next level.
+fn next_upgrades(entity_type: EntityType) -> Vec<EntityType> {
+    let data = entity_type.data();
+    entity_type
+        .upgrade_options(u32::MAX, false)
+        .filter(|upgrade| data.upgrades_to.is_some() || upgrade.data().level == data.level + 1)
+        .collect()
+}
@@ -1,4 +1,4 @@
-use glam::{vec2, vec3, Vec2, Vec3};
+use glam::{vec2, vec3, Mat2, Vec2, Vec3, Vec4};
 use std::f32::consts::TAU;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -6,9 +6,8 @@ pub struct Weather {
     // Points towards the sun.
     /// X/Y are aligned to world space X/Y and +Z is towards the camera.
     pub sun: Vec3,
-    /// Points in the direction of the wind.
+    /// Points in the direction of the wind. Its magnitude determines the height of the waves.
     pub wind: Vec2,
-    // TODO make wind change wave height
 }
 
 impl Default for Weather {
@@ -24,6 +23,18 @@ impl Weather {
     // Test [`Weather`] with unrealistic conditions.
     const TEST: bool = false;
 
+    /// Number of waves in [`Self::gerstner_waves`].
+    pub const GERSTNER_WAVES: usize = 4;
+
+    /// Angle relative to the wind (radians), wavelength (meters), and steepness (0 to 1 at a sea
+    /// state of 1) of each Gerstner wave.
+    const GERSTNER: [(f32, f32, f32); Self::GERSTNER_WAVES] = [
+        (0.0, 60.0, 0.22),
+        (0.6, 31.0, 0.18),
+        (-0.45, 17.0, 0.14),
+        (1.2, 9.0, 0.1),
+    ];
+
     pub fn new(time: f32) -> Self {
         if Self::TEST {
             // Make sun sin fast for testing.
@@ -38,6 +49,59 @@ impl Weather {
         }
     }
 
+    /// Roughness of the sea, relative to calm (1.0), which increases with the wind.
+    pub fn sea_state(&self) -> f32 {
+        self.wind.length() / Self::default().wind.length()
+    }
+
+    /// Phase of the (CPU approximation of) waves at a given world position and time. Waves move
+    /// with the wind.
+    pub fn wave_phase(&self, position: Vec2, time: f32) -> Vec2 {
+        let mut phase = (position + self.wind * time) * 0.1;
+
+        // Offset waves from regular grid.
+        phase.x += phase.y * 0.3;
+        phase
+    }
+
+    /// Gerstner waves that make up the water surface, each a direction (xy), wave number (z), and
+    /// amplitude in meters (w). Waves travel with the wind, and are higher the rougher the sea.
+    pub fn gerstner_waves(&self) -> [Vec4; Self::GERSTNER_WAVES] {
+        let wind = if self.wind == Vec2::ZERO {
+            Vec2::X
+        } else {
+            self.wind.normalize()
+        };
+        let sea_state = self.sea_state();
+        Self::GERSTNER.map(|(angle, wavelength, steepness)| {
+            let k = TAU / wavelength;
+            (Mat2::from_angle(angle) * wind)
+                .extend(k)
+                .extend(steepness * sea_state / k)
+        })
+    }
+
+    /// Height (meters) and slope of the water surface at a given world position and time. Matches
+    /// the background shader, for the purpose of bobbing boats.
+    pub fn gerstner(&self, position: Vec2, time: f32) -> (f32, Vec2) {
+        let mut height = 0.0;
+        let mut slope = Vec2::ZERO;
+        for wave in self.gerstner_waves() {
+            let direction = wave.truncate().truncate();
+            let f = wave.z * direction.dot(position) - (9.8 * wave.z).sqrt() * time;
+            height += wave.w * f.sin();
+            slope += direction * (wave.z * wave.w * f.cos());
+        }
+        (height, slope)
+    }
+
+    /// Slope of the waves, in world space, at a given world position and time. Each component is
+    /// in the range `-sea_state..=sea_state`.
+    pub fn wave_slope(&self, position: Vec2, time: f32) -> Vec2 {
+        let max_slope: f32 = Self::GERSTNER.iter().map(|&(_, _, s)| s).sum();
+        self.gerstner(position, time).1 * max_slope.recip()
+    }
+
     // Since the camera is orthographic, if the sun was pointing straight down the water would be
     // way too bright so we give it a different sun direction.
     pub fn water_sun(&self) -> Vec3 {
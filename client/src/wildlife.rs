@@ -0,0 +1,309 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game::Mk48Params;
+use common::altitude::Altitude;
+use common::terrain::Terrain;
+use glam::{vec2, vec4, Vec2, Vec4};
+use renderer::{
+    derive_vertex, DefaultRender, InstanceLayer, Layer, MeshBuilder, RenderLayer, Renderer, Shader,
+};
+use std::f32::consts::{PI, TAU};
+
+/// Each cell of the world may contain one group of wildlife.
+const CELL: f32 = 250.0;
+/// Don't bother generating wildlife when zoomed out this far (it would be too small to see).
+const MAX_ZOOM: f32 = 2000.0;
+
+/// Which kind of wildlife an instance represents. Also identifies its mesh, and the order they
+/// are drawn in.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Species {
+    Fish,
+    Whale,
+    Spout,
+    Seagull,
+}
+
+impl Species {
+    /// Airborne species are drawn above boats.
+    pub fn is_airborne(self) -> bool {
+        matches!(self, Self::Spout | Self::Seagull)
+    }
+
+    fn mesh(self) -> MeshBuilder<WildlifeVertex> {
+        let mut mesh = MeshBuilder::new();
+        let v = |x: f32, y: f32, flap: f32, wiggle: f32| WildlifeVertex {
+            position: vec2(x, y),
+            flap,
+            wiggle,
+        };
+        match self {
+            Self::Fish | Self::Whale => {
+                // Body (pointing towards +x) and a tail that wiggles.
+                mesh.vertices.extend([
+                    v(1.0, 0.0, 0.0, 0.0),
+                    v(0.2, 0.25, 0.0, 0.0),
+                    v(-0.5, 0.0, 0.0, 0.1),
+                    v(0.2, -0.25, 0.0, 0.0),
+                    v(-1.0, 0.3, 0.0, 0.3),
+                    v(-1.0, -0.3, 0.0, 0.3),
+                ]);
+                mesh.push_quad([0, 1, 2, 3]);
+                mesh.push_triangle([2, 4, 5]);
+            }
+            Self::Spout => {
+                // A puff of mist.
+                let n = 8u16;
+                mesh.vertices.push(v(0.0, 0.0, 0.0, 0.0));
+                for i in 0..n {
+                    let (y, x) = (i as f32 * (TAU / n as f32)).sin_cos();
+                    mesh.vertices.push(v(x, y, 0.0, 0.0));
+                }
+                for i in 0..n {
+                    mesh.push_triangle([0, i + 1, (i + 1) % n + 1]);
+                }
+            }
+            Self::Seagull => {
+                // Body and a pair of wings that flap.
+                mesh.vertices.extend([
+                    v(0.4, 0.0, 0.0, 0.0),
+                    v(0.0, 0.15, 0.0, 0.0),
+                    v(-0.4, 0.0, 0.0, 0.0),
+                    v(0.0, -0.15, 0.0, 0.0),
+                    v(-0.2, 1.0, 0.6, 0.0),
+                    v(-0.2, -1.0, 0.6, 0.0),
+                ]);
+                mesh.push_quad([0, 1, 2, 3]);
+                mesh.push_triangle([0, 4, 1]);
+                mesh.push_triangle([0, 3, 5]);
+            }
+        }
+        mesh
+    }
+}
+
+derive_vertex!(
+    pub struct WildlifeVertex {
+        pub position: Vec2,
+        /// How much the vertex moves towards the body as the animation progresses (wings).
+        pub flap: f32,
+        /// How much the vertex moves side to side as the animation progresses (tails).
+        pub wiggle: f32,
+    }
+);
+
+derive_vertex!(
+    pub struct WildlifeInstance {
+        pub position: Vec2,
+        /// In radians.
+        pub direction: f32,
+        /// Meters from nose to tail.
+        pub scale: f32,
+        /// Animation phase, in radians.
+        pub phase: f32,
+        pub color: Vec4,
+    }
+);
+
+/// Ambient birds, fish, and whales, that don't exist on the server. Seagulls,
+/// whale spouts, etc. go in the `AIRBORNE` layer.
+#[derive(Layer)]
+#[alpha]
+pub struct WildlifeLayer<const AIRBORNE: bool> {
+    #[layer]
+    instances: InstanceLayer<WildlifeVertex, u16, WildlifeInstance, Species>,
+    shader: Shader,
+}
+
+impl<const A: bool> WildlifeLayer<A> {
+    pub fn new(renderer: &Renderer) -> Self {
+        Self {
+            instances: InstanceLayer::new(renderer),
+            shader: Shader::new(
+                renderer,
+                include_str!("shaders/wildlife.vert"),
+                include_str!("shaders/wildlife.frag"),
+            ),
+        }
+    }
+
+    pub fn draw(&mut self, species: Species, instance: WildlifeInstance) {
+        debug_assert_eq!(species.is_airborne(), A);
+        self.instances
+            .draw(species, instance, move || species.mesh());
+    }
+}
+
+impl<const A: bool> RenderLayer<&Mk48Params> for WildlifeLayer<A> {
+    fn render(&mut self, renderer: &Renderer, params: &Mk48Params) {
+        if let Some(shader) = self.shader.bind(renderer) {
+            params.camera.prepare(&shader);
+            self.instances.render(renderer, &shader);
+        }
+    }
+}
+
+/// Generates wildlife near the camera. Wildlife is a pure function of terrain and (wall clock)
+/// time, so it is consistent between frames and players without any network traffic.
+pub fn generate_wildlife(
+    camera: Vec2,
+    zoom: f32,
+    terrain: &Terrain,
+    time: f64,
+    mut draw: impl FnMut(Species, WildlifeInstance),
+) {
+    if zoom > MAX_ZOOM {
+        return;
+    }
+
+    let min = ((camera - zoom) * (1.0 / CELL)).floor();
+    let max = ((camera + zoom) * (1.0 / CELL)).ceil();
+
+    for x in min.x as i32..=max.x as i32 {
+        for y in min.y as i32..=max.y as i32 {
+            let center = (vec2(x as f32, y as f32) + 0.5) * CELL;
+            let Some(altitude) = terrain.sample(center) else {
+                // Terrain isn't loaded yet.
+                continue;
+            };
+            if altitude >= Altitude::ZERO {
+                continue;
+            }
+
+            let seed = hash_cell(x, y);
+            let roll = unit(seed);
+
+            if terrain.land_in_square(center, CELL * 2.0) {
+                if roll < 0.35 {
+                    seagull_flock(center, seed, time, &mut draw);
+                }
+            } else if altitude.to_meters() < -10.0 {
+                if roll < 0.2 {
+                    fish_school(center, seed, time, &mut draw);
+                } else if roll < 0.23 {
+                    whale(center, seed, time, &mut draw);
+                }
+            }
+        }
+    }
+}
+
+/// Seagulls circle above the shore.
+fn seagull_flock(
+    center: Vec2,
+    seed: u32,
+    time: f64,
+    draw: &mut impl FnMut(Species, WildlifeInstance),
+) {
+    let count = 3 + seed % 5;
+    let radius = 20.0 + unit(seed ^ 1) * 30.0;
+    let clockwise = if seed & 2 == 0 { 1.0 } else { -1.0 };
+
+    for i in 0..count {
+        let gull = seed.wrapping_add(i.wrapping_mul(0x9E3779B9));
+        let offset = vec2(unit(gull ^ 3) - 0.5, unit(gull ^ 4) - 0.5) * 40.0;
+        let angle = cycle(time, 12.0 + unit(gull ^ 5) * 6.0, unit(gull)) * TAU * clockwise;
+        let (sin, cos) = angle.sin_cos();
+        draw(
+            Species::Seagull,
+            WildlifeInstance {
+                position: center + offset + vec2(cos, sin) * radius,
+                direction: angle + PI * 0.5 * clockwise,
+                scale: 1.5,
+                phase: cycle(time, 0.4, unit(gull ^ 6)) * TAU,
+                color: vec4(0.95, 0.95, 0.95, 1.0),
+            },
+        );
+    }
+}
+
+/// Fish swim in a loose school, that meanders around its cell.
+fn fish_school(
+    center: Vec2,
+    seed: u32,
+    time: f64,
+    draw: &mut impl FnMut(Species, WildlifeInstance),
+) {
+    let count = 8 + seed % 9;
+    let t = cycle(time, 90.0, unit(seed)) * TAU;
+    let (sin, cos) = t.sin_cos();
+    let (sin2, cos2) = (t * 2.0).sin_cos();
+    // Figure eight.
+    let school = center + vec2(sin, sin2 * 0.5) * (CELL * 0.3);
+    let direction = cos2.atan2(cos);
+
+    for i in 0..count {
+        let fish = seed.wrapping_add(i.wrapping_mul(0x9E3779B9));
+        let offset = vec2(unit(fish ^ 1) - 0.5, unit(fish ^ 2) - 0.5) * 12.0;
+        draw(
+            Species::Fish,
+            WildlifeInstance {
+                position: school + offset,
+                direction: direction + (unit(fish ^ 3) - 0.5) * 0.5,
+                scale: 0.8,
+                phase: cycle(time, 0.5, unit(fish ^ 4)) * TAU,
+                color: vec4(0.55, 0.65, 0.7, 0.35),
+            },
+        );
+    }
+}
+
+/// Whales cruise slowly across open water, surfacing to spout every so often.
+fn whale(center: Vec2, seed: u32, time: f64, draw: &mut impl FnMut(Species, WildlifeInstance)) {
+    let heading = unit(seed ^ 1) * TAU;
+    let (sin, cos) = heading.sin_cos();
+    let forward = vec2(cos, sin);
+    // Wraps around the cell, so whales don't end up concentrated anywhere.
+    let progress = cycle(time, 240.0, unit(seed)) - 0.5;
+    let position = center + forward * (progress * CELL);
+    let fade = (0.5 - progress.abs()) * 10.0;
+    let alpha = fade.clamp(0.0, 1.0);
+
+    draw(
+        Species::Whale,
+        WildlifeInstance {
+            position,
+            direction: heading,
+            scale: 15.0,
+            phase: cycle(time, 6.0, unit(seed ^ 2)) * TAU,
+            color: vec4(0.15, 0.2, 0.25, 0.5 * alpha),
+        },
+    );
+
+    // Spouts for a few seconds out of every 30.
+    let spout = cycle(time, 30.0, unit(seed ^ 3));
+    if spout < 0.1 {
+        let puff = spout * 10.0;
+        draw(
+            Species::Spout,
+            WildlifeInstance {
+                position: position + forward * 5.0,
+                direction: 0.0,
+                scale: 1.0 + puff * 4.0,
+                phase: 0.0,
+                color: vec4(1.0, 1.0, 1.0, 0.6 * (1.0 - puff) * alpha),
+            },
+        );
+    }
+}
+
+/// Returns how far through a repeating period of `period` seconds `time` is, in the range [0, 1).
+fn cycle(time: f64, period: f32, offset: f32) -> f32 {
+    (time / period as f64 + offset as f64).rem_euclid(1.0) as f32
+}
+
+fn hash_cell(x: i32, y: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6B343) ^ (y as u32).wrapping_mul(0xD8163841);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B3C6D);
+    h ^= h >> 12;
+    h
+}
+
+/// Returns a float in the range [0, 1) based on n.
+fn unit(n: u32) -> f32 {
+    let mut h = n.wrapping_mul(0x297A2D39);
+    h ^= h >> 16;
+    (h >> 8) as f32 * (1.0 / (1 << 24) as f32)
+}
@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::entity::{EntityData, EntityId, EntitySubKind};
+use common::transform::Transform;
+use glam::{vec2, vec4, Vec2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use renderer2d::GraphicLayer;
+
+/// Draws the wreck or debris left by a sunken boat, instead of a sprite. `alpha` fades it out as
+/// it sinks.
+pub fn draw_wreckage(
+    entity_id: EntityId,
+    transform: &Transform,
+    data: &EntityData,
+    alpha: f32,
+    graphics: &mut GraphicLayer,
+) {
+    let angle = transform.direction.to_radians();
+    let forward = transform.direction.to_vec();
+    let hull = vec4(0.22, 0.18, 0.16, 0.9 * alpha);
+
+    match data.sub_kind {
+        EntitySubKind::Wreck => {
+            // Broken in two, with the halves slightly askew.
+            let half = vec2(data.length * 0.48, data.width);
+            for (sign, skew) in [(1.0, 0.12), (-1.0, -0.08)] {
+                graphics.draw_rectangle(
+                    transform.position + forward * (data.length * 0.25 * sign),
+                    half,
+                    angle + skew,
+                    hull,
+                );
+            }
+        }
+        EntitySubKind::Debris => {
+            // Oil slick, littered with planks that are consistently placed for each entity.
+            graphics.draw_filled_circle(
+                transform.position,
+                data.radius,
+                vec4(0.04, 0.04, 0.06, 0.35 * alpha),
+            );
+
+            let mut rng = StdRng::seed_from_u64(entity_id.get() as u64);
+            for _ in 0..6 {
+                let offset = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5))
+                    * (data.length * 0.8);
+                graphics.draw_rectangle(
+                    transform.position + offset,
+                    vec2(rng.gen_range(2.0..6.0), 0.8),
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                    hull,
+                );
+            }
+        }
+        _ => debug_assert!(false, "{:?} isn't wreckage", data.sub_kind),
+    }
+}
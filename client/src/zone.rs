@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::protocol::{Zone, ZoneHolder};
+use glam::{vec4, Vec4};
+use renderer2d::GraphicLayer;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Draws capturable zones as rings colored by who holds them, and progress capturing them.
+pub fn draw_zones(zones: &[Zone], us: Option<ZoneHolder>, zoom: f32, graphics: &mut GraphicLayer) {
+    let thickness = 0.004 * zoom;
+    let color = |holder: Option<ZoneHolder>, alpha: f32| -> Vec4 {
+        match holder {
+            None => vec4(1.0, 1.0, 1.0, alpha),
+            Some(holder) if Some(holder) == us => vec4(0.2, 0.9, 0.3, alpha),
+            Some(_) => vec4(0.9, 0.2, 0.2, alpha),
+        }
+    };
+
+    for zone in zones {
+        let ring = if zone.contested {
+            vec4(1.0, 0.85, 0.2, 0.6)
+        } else {
+            color(zone.holder, 0.5)
+        };
+        graphics.draw_circle(zone.position, zone.radius, thickness, ring);
+
+        if let Some((capturer, progress)) = zone.capture {
+            // Clockwise from the top, like a clock.
+            graphics.draw_arc(
+                zone.position,
+                zone.radius,
+                FRAC_PI_2 - progress.clamp(0.0, 1.0) * TAU..FRAC_PI_2,
+                thickness * 2.0,
+                color(Some(capturer), 0.9),
+            );
+        }
+    }
+}
@@ -30,6 +30,9 @@ impl Altitude {
     /// creates an unbalanced experience i.e. battleships and their non-homing torpedoes vs deep subs.
     pub const SPECIAL_OVERLAP_MARGIN: Altitude = Altitude(AltitudeRepr::MAX / 2);
 
+    /// Submerged boats at or above this altitude are shallow enough to be spotted from the air.
+    pub const PERISCOPE_DEPTH: Altitude = Altitude::from_whole_meters(-10);
+
     pub fn new() -> Self {
         Self::ZERO
     }
@@ -117,6 +120,11 @@ impl Altitude {
         self < Self::ZERO
     }
 
+    /// Returns true if not below periscope depth.
+    pub fn is_periscope_depth(self) -> bool {
+        self >= Self::PERISCOPE_DEPTH
+    }
+
     /// Returns true if above zero.
     pub fn is_airborne(self) -> bool {
         self > Self::ZERO
@@ -26,6 +26,12 @@ pub trait ContactTrait {
 
     fn damage(&self) -> Ticks;
 
+    /// Modules knocked out by critical hits. Only known for boats of known type.
+    fn disabled(&self) -> DisabledModules;
+
+    /// Damage over time effects, such as fire. Only known for boats of known type.
+    fn effects(&self) -> StatusEffects;
+
     fn entity_type(&self) -> Option<EntityType>;
 
     fn guidance(&self) -> &Guidance;
@@ -34,6 +40,10 @@ pub trait ContactTrait {
 
     fn player_id(&self) -> Option<PlayerId>;
 
+    /// Whether the contact was detected by a fleet member's radar, via data link, as opposed to
+    /// one's own sensors. Relayed contacts never have a known type.
+    fn relayed(&self) -> bool;
+
     fn reloads(&self) -> &BitSlice<ReloadsStorage>;
 
     /// Whether reloads() will return real data or all zeroes.
@@ -56,6 +66,24 @@ pub trait ContactTrait {
     fn data(&self) -> &'static EntityData {
         self.entity_type().unwrap().data()
     }
+
+    /// Describes the contact as one line of a snapshot of the world, in a format that is the same
+    /// for the server and client so that their snapshots can be diffed when investigating desyncs.
+    /// Positions are rounded, since the client's are interpolated.
+    fn snapshot_line(&self) -> String {
+        let position = self.transform().position;
+        format!(
+            "{} {} {:.0} {:.0} {:.0} {}",
+            self.id(),
+            self.entity_type()
+                .map_or("unknown", |entity_type| entity_type.as_str()),
+            position.x,
+            position.y,
+            self.altitude().to_meters(),
+            self.player_id()
+                .map_or(String::from("-"), |player_id| player_id.0.to_string()),
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -64,9 +92,12 @@ pub struct Contact {
     altitude: Altitude,
     guidance: Guidance,
     damage: Ticks,
+    disabled: DisabledModules,
+    effects: StatusEffects,
     entity_type: Option<EntityType>,
     id: EntityId,
     player_id: Option<PlayerId>,
+    relayed: bool,
     reloads: Option<BitArray<ReloadsStorage>>,
     turrets: Option<Arc<[Angle]>>,
 }
@@ -76,10 +107,13 @@ impl Default for Contact {
         Self {
             altitude: Altitude::default(),
             damage: Ticks::default(),
+            disabled: DisabledModules::default(),
+            effects: StatusEffects::default(),
             entity_type: None,
             guidance: Guidance::default(),
             id: EntityId::new(u32::MAX).unwrap(),
             player_id: None,
+            relayed: false,
             reloads: None,
             transform: Transform::default(),
             turrets: None,
@@ -93,21 +127,28 @@ impl Contact {
     pub fn new(
         altitude: Altitude,
         damage: Ticks,
+        disabled: DisabledModules,
+        effects: StatusEffects,
         entity_type: Option<EntityType>,
         guidance: Guidance,
         id: EntityId,
         player_id: Option<PlayerId>,
+        relayed: bool,
         reloads: Option<BitArray<ReloadsStorage>>,
         transform: Transform,
         turrets: Option<Arc<[Angle]>>,
     ) -> Self {
+        debug_assert!(!(relayed && entity_type.is_some()));
         Self {
             altitude,
             damage,
+            disabled,
+            effects,
             entity_type,
             guidance,
             id,
             player_id,
+            relayed,
             reloads,
             transform,
             turrets,
@@ -118,28 +159,21 @@ impl Contact {
     /// of how the corresponding entity behaves on the server.
     pub fn simulate(&mut self, delta_seconds: f32) {
         if let Some(entity_type) = self.entity_type() {
+            let data = entity_type.data();
             let guidance = *self.guidance();
-            let max_speed = match entity_type.data().sub_kind {
-                // Wait until risen to surface.
-                EntitySubKind::Missile
-                | EntitySubKind::Rocket
-                | EntitySubKind::RocketTorpedo
-                | EntitySubKind::Sam
-                    if self.altitude().is_submerged() =>
-                {
-                    EntityData::SURFACING_PROJECTILE_SPEED_LIMIT
-                }
-                _ => f32::INFINITY,
-            };
+            let disabled = self.disabled();
+            let max_speed = data.max_speed(self.altitude(), disabled);
 
-            self.transform_mut().apply_guidance(
-                entity_type.data(),
+            self.transform_mut().simulate(
+                data,
                 guidance,
                 max_speed,
+                disabled.turn_scale(),
                 delta_seconds,
             );
+        } else {
+            self.transform_mut().do_kinematics(delta_seconds);
         }
-        self.transform_mut().do_kinematics(delta_seconds);
     }
 
     /// Interpolates or snaps one contact's fields to another, assuming they share the same id.
@@ -162,7 +196,10 @@ impl Contact {
 
         self.altitude = self.altitude.lerp(model.altitude, lerp);
         self.damage = model.damage;
+        self.disabled = model.disabled;
+        self.effects = model.effects;
         self.player_id = model.player_id;
+        self.relayed = model.relayed;
         self.reloads = model.reloads;
         if interpolate_guidance {
             self.guidance = model.guidance;
@@ -230,6 +267,16 @@ impl ContactTrait for Contact {
         self.damage
     }
 
+    #[inline]
+    fn disabled(&self) -> DisabledModules {
+        self.disabled
+    }
+
+    #[inline]
+    fn effects(&self) -> StatusEffects {
+        self.effects
+    }
+
     #[inline]
     fn entity_type(&self) -> Option<EntityType> {
         self.entity_type
@@ -250,6 +297,11 @@ impl ContactTrait for Contact {
         self.player_id
     }
 
+    #[inline]
+    fn relayed(&self) -> bool {
+        self.relayed
+    }
+
     #[inline]
     fn reloads(&self) -> &BitSlice<ReloadsStorage> {
         self.reloads.as_ref().map_or(&RELOADS_ARRAY_ZERO, |a| {
@@ -281,6 +333,8 @@ impl ContactTrait for Contact {
 }
 
 /// Useful for efficiently serializing contact.
+///
+/// Reloads are only known if type is, so the last bit without a type means relayed instead.
 struct ContactHeader {
     has_vel: bool,
     has_alt: bool,
@@ -290,6 +344,7 @@ struct ContactHeader {
     has_type: bool,
     has_player_id: bool,
     has_reloads: bool,
+    relayed: bool,
 }
 
 impl ContactHeader {
@@ -302,7 +357,7 @@ impl ContactHeader {
             self.has_damage,
             self.has_type,
             self.has_player_id,
-            self.has_reloads,
+            self.has_reloads || self.relayed,
         ];
 
         let mut bits: u8 = 0;
@@ -318,8 +373,10 @@ impl ContactHeader {
             *bit = bits & (1 << i) != 0
         }
 
-        let [has_vel, has_alt, has_dir_target, has_vel_target, has_damage, has_type, has_player_id, has_reloads] =
+        let [has_vel, has_alt, has_dir_target, has_vel_target, has_damage, has_type, has_player_id, has_reloads_or_relayed] =
             bools;
+        let has_reloads = has_reloads_or_relayed && has_type;
+        let relayed = has_reloads_or_relayed && !has_type;
 
         let header = Self {
             has_vel,
@@ -330,13 +387,14 @@ impl ContactHeader {
             has_type,
             has_player_id,
             has_reloads,
+            relayed,
         };
         debug_assert_eq!(bits, header.as_bits());
         header
     }
 
     fn tuple_len(&self) -> usize {
-        12 - self.as_bits().count_zeros() as usize
+        14 - self.as_bits().count_zeros() as usize - self.relayed as usize
     }
 }
 
@@ -376,6 +434,7 @@ impl<'a> ContactSerializer<'a> {
                 has_damage: c.damage != Ticks::ZERO,
                 has_player_id: c.player_id.is_some(),
                 has_reloads: c.reloads.is_some(),
+                relayed: c.relayed,
             },
         };
 
@@ -432,7 +491,7 @@ impl<'a> Serialize for ContactSerializer<'a> {
             }
         }
 
-        // 1 option or unit element.
+        // 3 option or unit elements.
         if self.c.is_boat() {
             let turrets = self.c.turrets.as_ref().unwrap();
             if turrets.is_empty() {
@@ -440,8 +499,12 @@ impl<'a> Serialize for ContactSerializer<'a> {
             } else {
                 tup.serialize_element(&KnownSizeSerializer::new(turrets))?;
             }
+            tup.serialize_element(&self.c.disabled)?;
+            tup.serialize_element(&self.c.effects)?;
         } else {
             tup.serialize_element(&())?;
+            tup.serialize_element(&())?;
+            tup.serialize_element(&())?;
         }
 
         tup.end()
@@ -588,6 +651,7 @@ impl<'de, 'a> Visitor<'de> for ContactDeserializer<'a> {
         if self.h.has_player_id {
             self.c.player_id = seq.next_element()?.unwrap();
         }
+        self.c.relayed = self.h.relayed;
         if self.h.has_reloads {
             // Must be after type is assigned.
             // Round bits up to bytes.
@@ -605,7 +669,7 @@ impl<'de, 'a> Visitor<'de> for ContactDeserializer<'a> {
             }
         }
 
-        // 1 option or unit element.
+        // 3 option or unit elements.
         if self.c.is_boat() {
             // Must be after type is assigend.
             let size = self.c.entity_type.unwrap().data().turrets.len();
@@ -618,8 +682,12 @@ impl<'de, 'a> Visitor<'de> for ContactDeserializer<'a> {
                         .unwrap(),
                 );
             }
+            self.c.disabled = seq.next_element()?.unwrap();
+            self.c.effects = seq.next_element()?.unwrap();
         } else {
             let _: () = seq.next_element()?.unwrap();
+            let _: () = seq.next_element()?.unwrap();
+            let _: () = seq.next_element()?.unwrap();
         }
 
         Ok(())
@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::terrain;
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Number of cells along each side of the [`Currents`] grid.
+pub const SIZE: usize = 64;
+/// Side length of one cell of the [`Currents`] grid (in meters).
+pub const CELL: f32 = terrain::SIZE as f32 * terrain::SCALE / SIZE as f32;
+/// Fastest representable current (in meters per second).
+pub const MAX_SPEED: f32 = 2.0;
+
+/// Large-scale ocean currents, as a coarse grid of velocities covering the whole terrain. Coarse
+/// enough to send to each client once per terrain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Currents {
+    /// Quantized velocities, row by row from the bottom left corner.
+    cells: Box<[[i8; 2]]>,
+}
+
+impl Default for Currents {
+    /// Still water everywhere.
+    fn default() -> Self {
+        Self {
+            cells: vec![[0; 2]; SIZE * SIZE].into_boxed_slice(),
+        }
+    }
+}
+
+impl Currents {
+    /// Samples `generator`, which returns a velocity (in meters per second) for a world position,
+    /// at the center of each cell.
+    pub fn with_generator(generator: impl Fn(Vec2) -> Vec2) -> Self {
+        let cells = (0..SIZE * SIZE)
+            .map(|i| {
+                let center = Self::corner(i % SIZE, i / SIZE) + Vec2::splat(CELL * 0.5);
+                let v =
+                    generator(center).clamp_length_max(MAX_SPEED) * (i8::MAX as f32 / MAX_SPEED);
+                [v.x.round() as i8, v.y.round() as i8]
+            })
+            .collect();
+        Self { cells }
+    }
+
+    /// Returns the velocity of the current at a world position (in meters per second),
+    /// interpolated between cells. Still outside the terrain.
+    pub fn at(&self, position: Vec2) -> Vec2 {
+        // Continuous cell coordinates, relative to cell centers.
+        let p = (position - Self::corner(0, 0)) * (1.0 / CELL) - Vec2::splat(0.5);
+        let floor = p.floor();
+        let frac = p - floor;
+        let (x, y) = (floor.x as isize, floor.y as isize);
+
+        let bottom = self.cell(x, y).lerp(self.cell(x + 1, y), frac.x);
+        let top = self.cell(x, y + 1).lerp(self.cell(x + 1, y + 1), frac.x);
+        bottom.lerp(top, frac.y)
+    }
+
+    fn cell(&self, x: isize, y: isize) -> Vec2 {
+        if !(0..SIZE as isize).contains(&x) || !(0..SIZE as isize).contains(&y) {
+            return Vec2::ZERO;
+        }
+        let [vx, vy] = self.cells[y as usize * SIZE + x as usize];
+        vec2(vx as f32, vy as f32) * (MAX_SPEED / i8::MAX as f32)
+    }
+
+    /// World position of the bottom left corner of a cell.
+    fn corner(x: usize, y: usize) -> Vec2 {
+        let half = SIZE as f32 * 0.5;
+        vec2(x as f32 - half, y as f32 - half) * CELL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::current::{Currents, CELL, MAX_SPEED};
+    use glam::{vec2, Vec2};
+
+    #[test]
+    fn sample() {
+        let currents = Currents::with_generator(|position| {
+            if position.x < 0.0 {
+                vec2(1.0, 0.0)
+            } else {
+                vec2(0.0, -10.0)
+            }
+        });
+
+        let west = currents.at(vec2(-5.0 * CELL, 0.0));
+        assert!(west.distance(vec2(1.0, 0.0)) < 0.02, "{:?}", west);
+        let east = currents.at(vec2(5.0 * CELL, 0.0));
+        assert!(east.distance(vec2(0.0, -MAX_SPEED)) < 0.02, "{:?}", east);
+
+        // Blends smoothly across the boundary.
+        let boundary = currents.at(Vec2::ZERO);
+        assert!(boundary.x > 0.1 && boundary.y < -0.1, "{:?}", boundary);
+
+        assert_eq!(currents.at(vec2(1e6, 0.0)), Vec2::ZERO);
+        assert_eq!(Currents::default().at(Vec2::ZERO), Vec2::ZERO);
+    }
+}
@@ -18,8 +18,13 @@ pub enum DeathReason {
     Unknown, // Used by boats only for leaving game.
     // Only for boats.
     Boat(PlayerAlias),
+    Crushed,
+    Fire,
+    Flooding,
     Obstacle(EntityType),
     Ram(PlayerAlias),
+    RoundEnded,
+    SeasonEnded,
     Weapon(PlayerAlias, EntityType),
     // Allows code to convey a reason for killing an entity that is not necessarily a player's boat.
     // In release mode, Unknown is used instead.
@@ -37,6 +42,9 @@ impl DeathReason {
             Self::Landing(_) => false,
             Self::Terrain => false,
             Self::Boat(_) => true,
+            Self::Crushed => false,
+            Self::Fire => false,
+            Self::Flooding => false,
             Self::Obstacle(entity_type) => {
                 // The assumption here is that all boats are controlled by players, and therefore
                 // should kill via Self::Boat not Self::Obstacle.
@@ -44,11 +52,21 @@ impl DeathReason {
                 false
             }
             Self::Ram(_) => true,
+            Self::RoundEnded => false,
+            Self::SeasonEnded => false,
             Self::Weapon(_, _) => true,
             #[cfg(debug_assertions)]
             Self::Debug(_) => false,
         }
     }
+
+    /// killer returns the player that caused the death, if any.
+    pub fn killer(&self) -> Option<PlayerAlias> {
+        match self {
+            Self::Boat(alias) | Self::Ram(alias) | Self::Weapon(alias, _) => Some(*alias),
+            _ => None,
+        }
+    }
 }
 
 impl PartialOrd for DeathReason {
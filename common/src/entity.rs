@@ -6,8 +6,12 @@ use std::num::NonZeroU32;
 mod _type;
 mod armament;
 mod data;
+mod effects;
 mod exhaust;
 mod kind;
+mod modules;
+mod overrides;
+mod seeker;
 mod sensor;
 mod sub_kind;
 mod turret;
@@ -16,15 +20,22 @@ pub type EntityId = NonZeroU32;
 pub use _type::EntityType;
 pub use armament::Armament;
 pub use data::EntityData;
+pub use effects::StatusEffects;
 pub use exhaust::Exhaust;
 pub use kind::EntityKind;
+pub use modules::DisabledModules;
+pub use overrides::{EntityOverride, EntityOverrides};
+pub use seeker::Seeker;
 pub use sensor::{Sensor, Sensors};
 pub use sub_kind::EntitySubKind;
 pub use turret::Turret;
 
 #[cfg(test)]
 mod tests {
-    use crate::entity::{EntityKind, EntityType};
+    use crate::entity::{EntityKind, EntitySubKind, EntityType};
+    use crate::ticks::Ticks;
+    use crate::util::level_to_score;
+    use common_util::angle::Angle;
 
     #[test]
     fn weapon_sensors() {
@@ -39,4 +50,86 @@ mod tests {
             println!("{:?} sensor range is {}", typ, range);
         }
     }
+
+    #[test]
+    fn seekers() {
+        for typ in EntityType::iter() {
+            let data = typ.data();
+            if data.kind == EntityKind::Weapon
+                && data.sensors.any()
+                && data.sub_kind != EntitySubKind::Rocket
+            {
+                assert!(data.seeker.is_some(), "{:?} has no seeker", typ);
+            }
+            if data.kind == EntityKind::Decoy {
+                assert!(data.decoy_size > 0.0, "{:?} is ineffective", typ);
+            }
+        }
+
+        let seeker = EntityType::Mark48.data().seeker.as_ref().unwrap();
+        assert!(!seeker.is_scanning(Ticks::ZERO));
+        assert!(seeker.is_scanning(seeker.delay + Ticks::ONE));
+
+        // Should never acquire a target behind it, no matter where it was aimed.
+        let heading = Angle::ZERO;
+        assert!(seeker.can_acquire(Angle::from_degrees(10.0), heading, heading));
+        assert!(!seeker.can_acquire(Angle::PI, heading, Angle::PI));
+        assert!(!seeker.can_acquire(
+            Angle::from_degrees(50.0),
+            heading,
+            Angle::from_degrees(-30.0)
+        ));
+    }
+
+    #[test]
+    fn respec() {
+        let score = level_to_score(4);
+        assert!(EntityType::Freccia.can_respec_to(EntityType::FairmileD, score, false));
+        assert!(!EntityType::Freccia.can_respec_to(EntityType::Freccia, score, false));
+        assert!(!EntityType::Freccia.can_respec_to(EntityType::Fletcher, score, false));
+        assert!(EntityType::Freccia
+            .respec_options(score, false)
+            .all(|t| t.data().level <= 3));
+
+        // Upgrading is unaffected.
+        assert!(EntityType::Freccia.can_upgrade_to(EntityType::Fletcher, score, false));
+    }
+
+    #[test]
+    fn class_names() {
+        for typ in EntityType::iter() {
+            let data = typ.data();
+            let sailable = data.kind == EntityKind::Boat && !data.npc;
+            assert_eq!(typ.class_name().is_some(), sailable, "{:?}", typ);
+        }
+        assert_eq!(
+            EntityType::Freccia.class_name().unwrap().as_str(),
+            EntityType::Freccia.as_str()
+        );
+    }
+
+    #[test]
+    fn loadouts() {
+        for typ in EntityType::iter() {
+            let data = typ.data();
+            for armament in data.armaments {
+                if let Some(loadout) = armament.loadout {
+                    assert!((loadout as usize) < data.loadouts.len(), "{:?}", typ);
+                }
+            }
+        }
+
+        let data = EntityType::ArleighBurke.data();
+        let count = |entity_type: EntityType, loadout: u8| {
+            data.armaments
+                .iter()
+                .filter(|a| a.entity_type == entity_type && a.in_loadout(loadout))
+                .count()
+        };
+        assert_eq!(data.loadouts.len(), 2);
+        assert!(count(EntityType::Harpoon, 0) > count(EntityType::Harpoon, 1));
+        assert_eq!(count(EntityType::Asroc, 0), 0);
+        assert!(count(EntityType::Asroc, 1) > 0);
+        assert_eq!(count(EntityType::Seahawk, 0), count(EntityType::Seahawk, 1));
+    }
 }
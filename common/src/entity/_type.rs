@@ -1,25 +1,55 @@
 use crate::altitude::Altitude;
 use crate::entity::{
-    Armament, EntityData, EntityKind, EntitySubKind, Exhaust, Sensor, Sensors, Turret,
+    Armament, EntityData, EntityKind, EntitySubKind, Exhaust, Seeker, Sensor, Sensors, Turret,
 };
 use crate::ticks::Ticks;
 use crate::util::{level_to_score, natural_death_coins};
 use crate::velocity::Velocity;
 use arrayvec::ArrayVec;
 use common_util::angle::Angle;
+use core_protocol::name::ClassName;
 use core_protocol::serde_util::{StrVisitor, U8Visitor};
 use macros::EntityTypeData;
 use rand::prelude::IteratorRandom;
 use rand::{thread_rng, Rng};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NOT_OVERRIDDEN: AtomicPtr<EntityData> = AtomicPtr::new(ptr::null_mut());
+
+/// Replacements for [`EntityType::DATA`], set by [`crate::entity::EntityOverrides::apply`].
+static DATA_OVERRIDES: [AtomicPtr<EntityData>; EntityType::DATA.len()] =
+    [NOT_OVERRIDDEN; EntityType::DATA.len()];
 
 impl EntityType {
     /// Data returns the data associated with the entity type.
     #[inline]
     pub fn data(self) -> &'static EntityData {
+        let overridden =
+            unsafe { DATA_OVERRIDES.get_unchecked(self as usize) }.load(Ordering::Acquire);
+        if overridden.is_null() {
+            self.base_data()
+        } else {
+            // Safety: Overridden data is static.
+            unsafe { &*overridden }
+        }
+    }
+
+    /// Like [`Self::data`] but ignores overrides.
+    #[inline]
+    pub fn base_data(self) -> &'static EntityData {
         unsafe { Self::DATA.get_unchecked(self as usize) }
     }
 
+    /// Replaces the data associated with the entity type (or restores the original, if `None`).
+    pub(crate) fn set_data_override(self, data: Option<&'static EntityData>) {
+        let overridden = data.map_or(ptr::null_mut(), |data| data as *const _ as *mut _);
+        DATA_OVERRIDES[self as usize].store(overridden, Ordering::Release);
+    }
+
     /// reduced lifespan returns a lifespan to start an entity's life at, so as to make it expire
     /// in desired_lifespan ticks
     pub fn reduced_lifespan(self, desired_lifespan: Ticks) -> Ticks {
@@ -27,23 +57,51 @@ impl EntityType {
     }
 
     /// can_spawn_as returns whether it is possible to spawn as the entity type, which may depend
-    /// on whether you are a bot.
+    /// on whether you are a bot. Krakens only appear during the kraken event.
     pub fn can_spawn_as(self, score: u32, bot: bool) -> bool {
         let data = self.data();
-        data.kind == EntityKind::Boat && level_to_score(data.level) <= score && (bot || !data.npc)
+        data.kind == EntityKind::Boat
+            && data.sub_kind != EntitySubKind::Kraken
+            && level_to_score(data.level) <= score
+            && (bot || !data.npc)
     }
 
     /// can_upgrade_to returns whether it is possible to upgrade to the entity type, which may depend
-    /// on your score and whether you are a bot.
+    /// on your score and whether you are a bot. If the upgrade tree branches here (see
+    /// [`EntityData::upgrades_to`]), only those branches are possible.
     pub fn can_upgrade_to(self, upgrade: Self, score: u32, bot: bool) -> bool {
         let data = self.data();
         let upgrade_data = upgrade.data();
         upgrade_data.level > data.level
             && upgrade_data.kind == data.kind
+            && upgrade_data.sub_kind != EntitySubKind::Kraken
+            && data
+                .upgrades_to
+                .map_or(true, |upgrades_to| upgrades_to.contains(&upgrade))
             && score >= level_to_score(upgrade_data.level)
             && (bot || !upgrade_data.npc)
     }
 
+    /// can_respec_to returns whether it is possible to use the one free respec per life to switch
+    /// to the entity type, which is any other boat at or below the current level. This allows
+    /// backing out of a branch or dead end of the upgrade tree.
+    pub fn can_respec_to(self, respec: Self, score: u32, bot: bool) -> bool {
+        let data = self.data();
+        respec != self
+            && data.kind == EntityKind::Boat
+            && respec.data().level <= data.level
+            && respec.can_spawn_as(score, bot)
+    }
+
+    /// class_name returns the name of the entity type's leaderboards, if it is a boat that players
+    /// can sail.
+    pub fn class_name(self) -> Option<ClassName> {
+        let data = self.data();
+        (data.kind == EntityKind::Boat && !data.npc)
+            .then(|| ClassName::from_str(self.as_str()).ok())
+            .flatten()
+    }
+
     /// iter returns an iterator that visits all possible entity types and allows a random choice to
     /// be made.
     pub fn iter() -> impl Iterator<Item = Self> + IteratorRandom {
@@ -75,6 +133,12 @@ impl EntityType {
         .flatten()
     }
 
+    /// respec_options returns an iterator that visits all entity types that may be switched to with
+    /// a respec (see [`Self::can_respec_to`]).
+    pub fn respec_options(self, score: u32, bot: bool) -> impl Iterator<Item = Self> {
+        Self::iter().filter(move |t| self.can_respec_to(*t, score, bot))
+    }
+
     /// iterates all loot types entity should drop. Takes score before death.
     pub fn loot(self, score: u32, score_to_coins: bool) -> impl Iterator<Item = Self> + 'static {
         let data: &EntityData = self.data();
@@ -95,7 +159,7 @@ impl EntityType {
         let mut loot_table = ArrayVec::<Self, 4>::new();
 
         match data.sub_kind {
-            EntitySubKind::Pirate => {
+            EntitySubKind::Kraken | EntitySubKind::Pirate => {
                 loot_table.push(Self::Crate);
                 loot_table.push(Self::Coin);
             }
@@ -289,15 +353,42 @@ pub enum EntityType {
     #[armament(Mark54, forward = 0.25, angle = 0, turret = 1, external)]
     #[armament(Harpoon, forward = -10.25, side = 5.5, angle = 90, symmetrical, external)]
     #[armament(Harpoon, forward = -11, side = 5.5, angle = 90, symmetrical, external)]
-    #[armament(Harpoon, forward = -10.25, side = 5.5, angle = 90, symmetrical, external)]
-    #[armament(Harpoon, forward = -11, side = 5.5, angle = 90, symmetrical, external)]
+    #[armament(
+        Harpoon,
+        forward = -10.25,
+        side = 5.5,
+        angle = 90,
+        symmetrical,
+        external,
+        loadout = "Anti-ship"
+    )]
+    #[armament(
+        Harpoon,
+        forward = -11,
+        side = 5.5,
+        angle = 90,
+        symmetrical,
+        external,
+        loadout = "Anti-ship"
+    )]
     #[armament(Essm, forward = 39.7, side = 1.5, count = 2, symmetrical, vertical)]
+    #[armament(
+        Asroc,
+        forward = 43.5,
+        side = 1.5,
+        count = 2,
+        symmetrical,
+        vertical,
+        loadout = "ASW"
+    )]
     #[armament(Seahawk, forward = -62, external)]
     #[turret(forward = -15.25, side = 9.4, medium, azimuth_br = 180)]
     #[turret(forward = -15.25, side = -9.4, medium, azimuth_bl = 180)]
     #[turret(Mark12, forward = 51, fast, azimuth_b = 20)]
     #[exhaust(forward = -2)]
     #[exhaust(forward = -18.25)]
+    #[loadout(label = "Anti-ship")]
+    #[loadout(label = "ASW")]
     ArleighBurke,
     #[info(
         label = "Bismarck",
@@ -646,6 +737,12 @@ pub enum EntityType {
     #[turret(Ak130, forward = -79.1108, angle = 180, medium, azimuth_b = 20)]
     #[exhaust(forward = -19)]
     Kirov,
+    #[info(label = "Kraken", link = "https://en.wikipedia.org/wiki/Kraken")]
+    #[entity(Boat, Kraken, level = 10)]
+    #[size(length = 100, width = 40, draft = 8)]
+    #[props(speed = 10, damage = 4)]
+    #[sensors(sonar, visual)]
+    Kraken,
     #[info(
         label = "Kolkata",
         link = "https://en.wikipedia.org/wiki/Kolkata-class_destroyer"
@@ -760,6 +857,7 @@ pub enum EntityType {
     #[armament(Wz0839, forward = -40, side = 1.75, symmetrical, external)]
     #[armament(Wz0839, forward = -41, side = 1.75, symmetrical, external)]
     #[armament(Wz0839, forward = -42, side = 1.75, symmetrical, external)]
+    #[armament(Bullivant, forward = -44)]
     Lublin,
     #[info(
         label = "Momi",
@@ -1168,6 +1266,11 @@ pub enum EntityType {
     #[entity(Obstacle, Tree)]
     #[size(length = 8, width = 8)]
     Acacia,
+    #[info(label = "Debris")]
+    #[entity(Obstacle, Debris)]
+    #[size(length = 30, width = 30)]
+    #[props(lifespan = 60)]
+    Debris,
     #[info(label = "HQ")]
     #[entity(Obstacle, Structure)]
     #[size(length = 90, width = 90)]
@@ -1180,6 +1283,11 @@ pub enum EntityType {
     #[exhaust(forward = 7, side = 21)]
     #[exhaust(forward = -23, side = 21)]
     OilPlatform,
+    #[info(label = "Wreck")]
+    #[entity(Obstacle, Wreck)]
+    #[size(length = 100, width = 16)]
+    #[props(lifespan = 60)]
+    Wreck,
     #[info(label = "100mm Gun")]
     #[entity(Turret, Gun)]
     #[size(length = 6.7, width = 4.1875)]
@@ -1470,6 +1578,14 @@ pub enum EntityType {
     #[props(speed = 993.9, range = 650000)]
     #[sensors(radar)]
     BrahMos,
+    #[info(
+        label = "Bullivant Net",
+        link = "https://en.wikipedia.org/wiki/Torpedo_net"
+    )]
+    #[entity(Weapon, Net, level = 3)]
+    #[size(length = 40, width = 1)]
+    #[props(lifespan = 300)]
+    Bullivant,
     #[info(label = "Cannon Ball")]
     #[entity(Weapon, Shell)]
     #[size(length = 0.091, width = 0.091)]
@@ -13,6 +13,10 @@ pub struct Armament {
     pub position_side: f32,
     pub angle: Angle,
     pub turret: Option<usize>,
+    /// Index of the only loadout (see
+    /// [`EntityData::loadouts`][crate::entity::EntityData::loadouts]) that includes this
+    /// armament, if any.
+    pub loadout: Option<u8>,
 }
 
 impl Armament {
@@ -29,4 +33,9 @@ impl Armament {
     pub fn is_similar_to(&self, other: &Self) -> bool {
         self.entity_type == other.entity_type && self.turret == other.turret
     }
+
+    /// Whether the armament is carried by boats with the given loadout.
+    pub fn in_loadout(&self, loadout: u8) -> bool {
+        self.loadout.map_or(true, |l| l == loadout)
+    }
 }
@@ -1,5 +1,8 @@
 use crate::altitude::Altitude;
-use crate::entity::{Armament, EntityKind, EntitySubKind, Exhaust, Sensors, Turret};
+use crate::entity::{
+    Armament, DisabledModules, EntityKind, EntitySubKind, EntityType, Exhaust, Seeker, Sensors,
+    Turret,
+};
 use crate::ticks;
 use crate::ticks::Ticks;
 use crate::transform::Transform;
@@ -31,11 +34,29 @@ pub struct EntityData {
     pub anti_aircraft: f32,
     pub ram_damage: f32,
     pub torpedo_resistance: f32,
+    /// Damage multiplier for weapons that hit the bow head-on.
+    pub armor_bow: f32,
+    /// Damage multiplier for weapons that hit the side (broadside).
+    pub armor_belt: f32,
+    /// Damage multiplier for weapons that hit the stern from behind.
+    pub armor_stern: f32,
     pub stealth: f32,
     pub sensors: Sensors,
+    /// Homing weapons have `Some`.
+    pub seeker: Option<Seeker>,
+    /// How much larger than its radius a decoy appears to seekers.
+    pub decoy_size: f32,
+    /// How much land a weapon carves when it detonates, if any.
+    pub terrain_damage: f32,
     pub armaments: &'static [Armament],
     pub turrets: &'static [Turret],
     pub exhausts: &'static [Exhaust],
+    /// Boats that this boat may upgrade to, if restricted by `#[upgrades_to(...)]`, forming
+    /// branches and dead ends in the upgrade tree. If `None`, any higher level boat will do.
+    pub upgrades_to: Option<&'static [EntityType]>,
+    /// Labels of the armament loadouts to choose from when spawning or upgrading, declared with
+    /// `#[loadout(...)]`. Empty if there is no choice.
+    pub loadouts: &'static [&'static str],
     pub label: &'static str,
     pub link: Option<&'static str>,
     pub range: f32,
@@ -51,6 +72,23 @@ impl EntityData {
     /// Constant used for checking whether a depth charge should explode.
     pub const DEPTH_CHARGE_PROXIMITY: f32 = 30.0;
 
+    /// Maximum speed in meters per second, at the given altitude and with the given modules
+    /// disabled. Shared by server physics and client prediction.
+    pub fn max_speed(&self, altitude: Altitude, disabled: DisabledModules) -> f32 {
+        match self.sub_kind {
+            // Wait until risen to surface.
+            EntitySubKind::Missile
+            | EntitySubKind::Rocket
+            | EntitySubKind::RocketTorpedo
+            | EntitySubKind::Sam
+                if altitude.is_submerged() =>
+            {
+                Self::SURFACING_PROJECTILE_SPEED_LIMIT
+            }
+            _ => self.speed.to_mps() * disabled.speed_scale(),
+        }
+    }
+
     /// radii range of throttle (0-100%) and limit of collecting things.
     pub fn radii(&self) -> Range<f32> {
         self.length * 0.55..self.length
@@ -99,6 +137,25 @@ impl EntityData {
         }
     }
 
+    /// Returns multiplier for damage due to the angle of a hit, given the cosine of the angle
+    /// between the weapon's direction and this boat's (-1 is head-on into the bow, 0 is broadside,
+    /// and 1 is into the stern). Blends smoothly between sections.
+    pub fn armor_multiplier(&self, hit_cos: f32) -> f32 {
+        let end = if hit_cos < 0.0 {
+            self.armor_bow
+        } else {
+            self.armor_stern
+        };
+        let end_weight = hit_cos.powi(2).min(1.0);
+        end * end_weight + self.armor_belt * (1.0 - end_weight)
+    }
+
+    /// Returns the depth (as a positive altitude) a submarine can safely operate at. Below it, the
+    /// hull is damaged, and gives way entirely at [`Self::depth`] (the crush depth).
+    pub fn test_depth(&self) -> Altitude {
+        self.depth * 0.8
+    }
+
     /// Returns minimum cavitation (making noisy bubbles) speed.
     pub fn cavitation_speed(&self, altitude: Altitude) -> Velocity {
         let lo = Velocity::from_knots(8.0);
@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+/// Damage over time effects on a boat, such as fires started by shells.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffects(u8);
+
+impl StatusEffects {
+    const FIRE: u8 = 1 << 0;
+    const FLOODING: u8 = 1 << 1;
+
+    /// Returns true if there are no effects.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if on fire, which burns out with time.
+    pub fn fire(self) -> bool {
+        self.0 & Self::FIRE != 0
+    }
+
+    /// Returns true if flooding, which is only pumped out while moving slowly.
+    pub fn flooding(self) -> bool {
+        self.0 & Self::FLOODING != 0
+    }
+
+    pub fn set_fire(&mut self, fire: bool) {
+        self.set(Self::FIRE, fire);
+    }
+
+    pub fn set_flooding(&mut self, flooding: bool) {
+        self.set(Self::FLOODING, flooding);
+    }
+
+    fn set(&mut self, bit: u8, value: bool) {
+        if value {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+}
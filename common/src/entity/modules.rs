@@ -0,0 +1,87 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+/// Modules of a boat that critical hits have temporarily knocked out.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DisabledModules(u32);
+
+impl DisabledModules {
+    const PROPULSION: u32 = 1 << 0;
+    const STEERING: u32 = 1 << 1;
+    /// Bits before the first turret bit.
+    const TURRET_SHIFT: usize = 2;
+    /// Turrets beyond this many can't be knocked out.
+    pub const MAX_TURRETS: usize = u32::BITS as usize - Self::TURRET_SHIFT;
+
+    /// Fraction of its top speed a boat with knocked out propulsion can make.
+    pub const PROPULSION_SPEED_SCALE: f32 = 0.3;
+    /// Fraction of its turn rate a boat with knocked out steering can make.
+    pub const STEERING_TURN_SCALE: f32 = 0.25;
+
+    /// Returns true if nothing is knocked out.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if propulsion is knocked out, capping speed.
+    pub fn propulsion(self) -> bool {
+        self.0 & Self::PROPULSION != 0
+    }
+
+    /// Returns true if steering is knocked out, capping turn rate.
+    pub fn steering(self) -> bool {
+        self.0 & Self::STEERING != 0
+    }
+
+    /// Returns true if the turret at the given index is knocked out, freezing its reloads.
+    pub fn turret(self, index: usize) -> bool {
+        index < Self::MAX_TURRETS && self.0 & (1 << (index + Self::TURRET_SHIFT)) != 0
+    }
+
+    /// Iterates the indices of knocked out turrets.
+    pub fn turrets(self) -> impl Iterator<Item = usize> {
+        (0..Self::MAX_TURRETS).filter(move |&i| self.turret(i))
+    }
+
+    pub fn set_propulsion(&mut self, disabled: bool) {
+        self.set(Self::PROPULSION, disabled);
+    }
+
+    pub fn set_steering(&mut self, disabled: bool) {
+        self.set(Self::STEERING, disabled);
+    }
+
+    pub fn set_turret(&mut self, index: usize, disabled: bool) {
+        if index < Self::MAX_TURRETS {
+            self.set(1 << (index + Self::TURRET_SHIFT), disabled);
+        }
+    }
+
+    fn set(&mut self, bit: u32, disabled: bool) {
+        if disabled {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+
+    /// Multiplier for top speed.
+    pub fn speed_scale(self) -> f32 {
+        if self.propulsion() {
+            Self::PROPULSION_SPEED_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// Multiplier for turn rate.
+    pub fn turn_scale(self) -> f32 {
+        if self.steering() {
+            Self::STEERING_TURN_SCALE
+        } else {
+            1.0
+        }
+    }
+}
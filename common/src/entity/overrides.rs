@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entity::{EntityData, EntityType};
+use crate::ticks::Ticks;
+use crate::velocity::Velocity;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Overridden data is leaked, since references to it may be held indefinitely. It is kept, so
+    /// that applying the same override again (e.g. when an experiment is restarted) reuses it.
+    static ref LEAKED: Mutex<Vec<(EntityType, EntityOverride, &'static EntityData)>> =
+        Mutex::default();
+}
+
+/// Changes to an entity type's balance, made at runtime. Fields that are `None` keep their
+/// compiled-in values.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityOverride {
+    pub damage: Option<f32>,
+    /// In meters per second.
+    pub speed: Option<f32>,
+    /// In seconds.
+    pub reload: Option<f32>,
+    pub visual_range: Option<f32>,
+    pub radar_range: Option<f32>,
+    pub sonar_range: Option<f32>,
+}
+
+impl EntityOverride {
+    fn values(&self) -> impl Iterator<Item = f32> {
+        IntoIterator::into_iter([
+            self.damage,
+            self.speed,
+            self.reload,
+            self.visual_range,
+            self.radar_range,
+            self.sonar_range,
+        ])
+        .flatten()
+    }
+
+    /// Returns a copy of `data` with this override applied.
+    pub fn applied_to(&self, data: &EntityData) -> EntityData {
+        let mut data = data.clone();
+        if let Some(damage) = self.damage {
+            data.damage = damage;
+        }
+        if let Some(speed) = self.speed {
+            data.speed = Velocity::from_mps(speed);
+        }
+        if let Some(reload) = self.reload {
+            data.reload = Ticks::from_secs(reload);
+        }
+        if let Some(range) = self.visual_range {
+            data.sensors.visual.range = range;
+        }
+        if let Some(range) = self.radar_range {
+            data.sensors.radar.range = range;
+        }
+        if let Some(range) = self.sonar_range {
+            data.sensors.sonar.range = range;
+        }
+        data
+    }
+
+    /// Like [`Self::applied_to`] the base data of `entity_type`, but static.
+    fn leak(&self, entity_type: EntityType) -> &'static EntityData {
+        let mut leaked = LEAKED.lock().unwrap();
+        if let Some(&(_, _, data)) = leaked
+            .iter()
+            .find(|(t, o, _)| *t == entity_type && o == self)
+        {
+            data
+        } else {
+            let data = Box::leak(Box::new(self.applied_to(entity_type.base_data())));
+            leaked.push((entity_type, self.clone(), data));
+            data
+        }
+    }
+}
+
+/// A set of balance changes, so balance can be experimented with without a redeploy. Serialized
+/// (e.g. as JSON) as a map from entity type to [`EntityOverride`], for example
+/// `{"Yasen": {"speed": 20.0}}`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityOverrides(pub BTreeMap<EntityType, EntityOverride>);
+
+impl EntityOverrides {
+    /// Errors if any value is negative or not finite.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self
+            .0
+            .values()
+            .flat_map(EntityOverride::values)
+            .all(|v| v.is_finite() && v >= 0.0)
+        {
+            Ok(())
+        } else {
+            Err("override values must be finite and non-negative")
+        }
+    }
+
+    /// Makes [`EntityType::data`] reflect these overrides (and only these overrides), for all
+    /// entity types.
+    pub fn apply(&self) {
+        for entity_type in EntityType::iter() {
+            entity_type.set_data_override(self.0.get(&entity_type).map(|o| o.leak(entity_type)));
+        }
+    }
+
+    /// Returns the data of `entity_type` with these overrides applied, or `None` if it isn't
+    /// overridden. Unlike [`Self::apply`], has no effect on [`EntityType::data`].
+    pub fn data(&self, entity_type: EntityType) -> Option<EntityData> {
+        self.0
+            .get(&entity_type)
+            .map(|o| o.applied_to(entity_type.base_data()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::{EntityOverrides, EntityType};
+
+    #[test]
+    fn entity_overrides() {
+        let base = EntityType::Yasen.base_data();
+        let overrides: EntityOverrides =
+            serde_json::from_str(r#"{"Yasen": {"speed": 1.0, "damage": 2.0}}"#).unwrap();
+        assert!(overrides.validate().is_ok());
+
+        let data = overrides.data(EntityType::Yasen).unwrap();
+        assert_eq!(data.damage, 2.0);
+        assert_ne!(data.speed, base.speed);
+        assert_eq!(data.reload, base.reload);
+        assert!(overrides.data(EntityType::Zubr).is_none());
+
+        let invalid: EntityOverrides =
+            serde_json::from_str(r#"{"Yasen": {"reload": -1.0}}"#).unwrap();
+        assert!(invalid.validate().is_err());
+    }
+}
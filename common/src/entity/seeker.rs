@@ -0,0 +1,36 @@
+use crate::ticks::Ticks;
+use common_util::angle::Angle;
+
+/// How a homing weapon acquires targets, and how easily it is distracted by decoys.
+#[derive(Clone, Debug)]
+pub struct Seeker {
+    /// Cannot sense targets further than this from the weapon's heading.
+    pub cone: Angle,
+    /// Ignores targets further than this from the current guidance direction, so the weapon
+    /// doesn't veer off toward (or U-turn after) something it wasn't aimed at.
+    pub deviation: Angle,
+    /// Seeker activates this long after launch.
+    pub delay: Ticks,
+    /// Once active, targets are (re)acquired this often. Zero means every tick.
+    pub reacquisition: Ticks,
+    /// Multiplier of how much larger decoys appear (zero means immune to decoys).
+    pub decoy_susceptibility: f32,
+}
+
+impl Seeker {
+    /// How much larger boats with active sensors appear to seekers.
+    pub const ACTIVE_SENSOR_SIZE: f32 = 75.0;
+
+    /// Returns true if the seeker is scanning for targets, given the weapon's lifetime.
+    pub fn is_scanning(&self, ticks: Ticks) -> bool {
+        if ticks <= self.delay {
+            return false;
+        }
+        self.reacquisition == Ticks::ZERO || (ticks - self.delay).every(self.reacquisition)
+    }
+
+    /// Returns true if a target in the given direction is within the acquisition cone.
+    pub fn can_acquire(&self, angle: Angle, heading: Angle, direction_target: Angle) -> bool {
+        (angle - heading).abs() <= self.cone && (angle - direction_target).abs() <= self.deviation
+    }
+}
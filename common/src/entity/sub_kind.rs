@@ -4,6 +4,7 @@ pub enum EntitySubKind {
     Carrier,
     Corvette,
     Cruiser,
+    Debris,
     Depositor,
     DepthCharge,
     Destroyer,
@@ -13,11 +14,13 @@ pub enum EntitySubKind {
     Hovercraft,
     Icebreaker,
     Gun,
+    Kraken,
     Lcs,
     Mine,
     Minelayer,
     Missile,
     Mtb,
+    Net,
     Pirate,
     Plane,
     Ram,
@@ -32,4 +35,5 @@ pub enum EntitySubKind {
     Tanker,
     Torpedo,
     Tree,
+    Wreck,
 }
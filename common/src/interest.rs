@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entity::EntityKind;
+use crate::ticks::Ticks;
+use glam::Vec2;
+
+/// How closely a player follows a contact, based on its distance from their camera relative to
+/// their view range. Less interesting contacts are sent less often and less precisely, saving
+/// bandwidth as well as interpolation work on the client.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Interest {
+    /// On screen. Sent as often and precisely as the contact's kind requires.
+    Near,
+    /// Around the edge of the screen. Sent at most every [`Self::FAR_KEEP_ALIVE`] ticks, with
+    /// quantized positions and without guidance, so the client extrapolates in a straight line.
+    Far,
+    /// Well off screen, only detectable with long range sensors. Sent as [`Self::Far`] but without
+    /// type (unless friendly), i.e. little more than a position.
+    Distant,
+}
+
+impl Interest {
+    /// Contacts further than this fraction of the view range are [`Self::Far`].
+    const FAR: f32 = 0.8;
+    /// Contacts further than this fraction of the view range are [`Self::Distant`].
+    const DISTANT: f32 = 1.5;
+    /// Keep alive of contacts that aren't [`Self::Near`] (see [`EntityKind::keep_alive`]).
+    pub const FAR_KEEP_ALIVE: Ticks = Ticks::from_repr(5);
+    /// Positions of contacts that aren't [`Self::Near`] are rounded to this many meters.
+    pub const FAR_PRECISION: f32 = 5.0;
+
+    /// Interest in a contact `distance_squared` away from the camera, given the view range (see
+    /// [`EntityData::camera_range`][crate::entity::EntityData::camera_range]).
+    pub fn of(distance_squared: f32, view: f32) -> Self {
+        if distance_squared <= (view * Self::FAR).powi(2) {
+            Self::Near
+        } else if distance_squared <= (view * Self::DISTANT).powi(2) {
+            Self::Far
+        } else {
+            Self::Distant
+        }
+    }
+
+    /// After how many ticks of not hearing about a contact of `kind` (if known) the client should
+    /// assume it is gone.
+    pub fn keep_alive(self, kind: Option<EntityKind>) -> Ticks {
+        match kind {
+            Some(kind) if self == Self::Near => *kind.keep_alive().end(),
+            Some(kind) => (*kind.keep_alive().end()).max(Self::FAR_KEEP_ALIVE),
+            None => EntityKind::MAX_KEEP_ALIVE,
+        }
+    }
+
+    /// Rounds a contact's position to the precision it is sent with.
+    pub fn quantize(self, position: Vec2) -> Vec2 {
+        if self == Self::Near {
+            position
+        } else {
+            (position * (1.0 / Self::FAR_PRECISION)).round() * Self::FAR_PRECISION
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::EntityKind;
+    use crate::interest::Interest;
+    use crate::ticks::Ticks;
+    use glam::vec2;
+
+    #[test]
+    fn interest() {
+        assert_eq!(Interest::of(0.0, 1000.0), Interest::Near);
+        assert_eq!(Interest::of(1000f32.powi(2), 1000.0), Interest::Far);
+        assert_eq!(Interest::of(2000f32.powi(2), 1000.0), Interest::Distant);
+        assert_eq!(Interest::of(1.0, 0.0), Interest::Distant);
+
+        let boat = Some(EntityKind::Boat);
+        assert_eq!(Interest::Near.keep_alive(boat), Ticks::ZERO);
+        assert_eq!(Interest::Far.keep_alive(boat), Interest::FAR_KEEP_ALIVE);
+        assert!(Interest::Far.keep_alive(None) >= Interest::FAR_KEEP_ALIVE);
+
+        let position = vec2(123.4, -56.7);
+        assert_eq!(Interest::Near.quantize(position), position);
+        assert_eq!(Interest::Far.quantize(position), vec2(125.0, -55.0));
+    }
+}
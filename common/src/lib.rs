@@ -17,10 +17,15 @@ pub mod altitude;
 pub mod angle;
 pub mod complete;
 pub mod contact;
+pub mod current;
 pub mod death_reason;
 pub mod entity;
 pub mod guidance;
+pub mod interest;
+pub mod mission;
 pub mod protocol;
+pub mod region;
+pub mod stats;
 pub mod terrain;
 pub mod ticks;
 pub mod transform;
@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use core_protocol::UnixTime;
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+/// Something a player can do towards a [`Mission`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Sink boats of any kind.
+    SinkBoats,
+    /// Sink submarines.
+    SinkSubmarines,
+    /// Sink boats by ramming them.
+    RamBoats,
+    /// Collect crates.
+    CollectCrates,
+    /// Collect oil barrels.
+    CollectBarrels,
+}
+
+impl Objective {
+    const ALL: [Self; 5] = [
+        Self::SinkBoats,
+        Self::SinkSubmarines,
+        Self::RamBoats,
+        Self::CollectCrates,
+        Self::CollectBarrels,
+    ];
+
+    /// Possible amounts a mission may require.
+    fn targets(self) -> &'static [u32] {
+        match self {
+            Self::SinkBoats => &[3, 4, 5],
+            Self::SinkSubmarines => &[1, 2, 3],
+            Self::RamBoats => &[1, 2],
+            Self::CollectCrates => &[25, 50, 75],
+            Self::CollectBarrels => &[10, 20, 30],
+        }
+    }
+}
+
+/// One of the daily objectives, which are the same for all players on a given (UTC) day.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Mission {
+    pub objective: Objective,
+    /// Amount required to complete the mission.
+    pub target: u32,
+}
+
+impl Mission {
+    /// Bonus starting score for completing a mission, without a streak.
+    pub const REWARD: u32 = 40;
+    /// Streaks longer than this many days don't increase rewards any further.
+    pub const MAX_STREAK: u32 = 4;
+
+    /// Bonus starting score for completing the mission, given the current streak (see
+    /// [`DailyMissions::streak`]). Each day of the streak adds a quarter to the reward.
+    pub fn reward(self, streak: u32) -> u32 {
+        Self::REWARD * (4 + streak.min(Self::MAX_STREAK)) / 4
+    }
+}
+
+/// Today's missions, and a player's progress towards them. Reset at midnight (UTC).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DailyMissions {
+    /// Days since the unix epoch.
+    pub day: u32,
+    pub missions: [Mission; Self::COUNT],
+    /// Progress towards each of `missions`, up to its target.
+    pub progress: [u32; Self::COUNT],
+    /// Days in a row, up to and including yesterday or today, that all missions were completed.
+    pub streak: u32,
+}
+
+impl DailyMissions {
+    /// Number of missions issued each day.
+    pub const COUNT: usize = 3;
+
+    /// Returns the day (since the unix epoch) at a given time (in milliseconds since the epoch).
+    pub fn day(unix_time: UnixTime) -> u32 {
+        (unix_time / (24 * 60 * 60 * 1000)) as u32
+    }
+
+    /// Issues the missions for a given day, with no progress.
+    pub fn new(day: u32, streak: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(day as u64);
+        let mut objectives = Objective::ALL.choose_multiple(&mut rng, Self::COUNT);
+        let missions = [(); Self::COUNT].map(|_| {
+            let objective = *objectives.next().unwrap();
+            Mission {
+                objective,
+                target: *objective.targets().choose(&mut rng).unwrap(),
+            }
+        });
+        Self {
+            day,
+            missions,
+            progress: [0; Self::COUNT],
+            streak,
+        }
+    }
+
+    /// Returns true iff the mission at `index` is complete.
+    pub fn is_mission_complete(&self, index: usize) -> bool {
+        self.progress[index] >= self.missions[index].target
+    }
+
+    /// Returns true iff all of the day's missions are complete.
+    pub fn is_complete(&self) -> bool {
+        (0..Self::COUNT).all(|i| self.is_mission_complete(i))
+    }
+
+    /// Records progress towards an objective, returning the bonus starting score earned by any
+    /// missions that it completed.
+    pub fn record(&mut self, objective: Objective, amount: u32) -> u32 {
+        let mut reward = 0;
+        for (mission, progress) in self.missions.iter().zip(self.progress.iter_mut()) {
+            if mission.objective != objective || *progress >= mission.target {
+                continue;
+            }
+            *progress = progress.saturating_add(amount).min(mission.target);
+            if *progress == mission.target {
+                reward += mission.reward(self.streak);
+            }
+        }
+        reward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mission::{DailyMissions, Mission};
+
+    #[test]
+    fn daily_missions() {
+        let day = DailyMissions::day(1_650_000_000_000);
+        assert_eq!(DailyMissions::new(day, 0), DailyMissions::new(day, 0));
+
+        for day in day..day + 100 {
+            let mut missions = DailyMissions::new(day, 1);
+            for (i, mission) in missions.missions.iter().enumerate() {
+                assert!(missions.missions[..i]
+                    .iter()
+                    .all(|other| other.objective != mission.objective));
+            }
+
+            for mission in missions.missions {
+                assert_eq!(missions.record(mission.objective, mission.target - 1), 0);
+            }
+            assert!(!missions.is_complete());
+
+            for mission in missions.missions {
+                assert_eq!(
+                    missions.record(mission.objective, 100),
+                    Mission::REWARD * 5 / 4
+                );
+            }
+            assert!(missions.is_complete());
+
+            // Completed missions don't pay out twice.
+            let mission = missions.missions[0];
+            assert_eq!(missions.record(mission.objective, 1), 0);
+        }
+    }
+}
@@ -1,13 +1,26 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::altitude::Altitude;
 use crate::contact::Contact;
+use crate::current::Currents;
 use crate::death_reason::DeathReason;
 use crate::entity::*;
 use crate::guidance::Guidance;
+use crate::mission::DailyMissions;
+use crate::stats::LifeStats;
 use crate::terrain::{ChunkId, SerializedChunk};
+use crate::world::{Biome, Season};
+use core_protocol::dto::LeaderboardDto;
+use core_protocol::id::{PlayerId, TeamId};
+use core_protocol::name::PlayerAlias;
 use glam::Vec2;
-use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
 
 /// Server to client update.
 #[cfg_attr(feature = "server", derive(actix::Message))]
@@ -20,9 +33,110 @@ pub struct Update {
     pub death_reason: Option<DeathReason>,
     /// Player's current score.
     pub score: u32,
+    /// Whether the player's boat may still use its one free respec (see
+    /// [`EntityType::can_respec_to`]).
+    pub can_respec: bool,
+    /// Armament loadout of the player's boat (see [`EntityData::loadouts`]).
+    pub loadout: u8,
     /// Current world border radius.
     pub world_radius: f32,
+    /// Current seasonal event.
+    pub season: Season,
+    /// Terrain style of the arena.
+    pub biome: Biome,
     pub terrain: Box<TerrainUpdate>,
+    /// Ocean currents, if the terrain (and therefore the currents) changed since they were last
+    /// sent.
+    pub currents: Option<Arc<Currents>>,
+    /// Boats sunk by other players since the last update (same for all players).
+    pub sinkings: Box<[Sinking]>,
+    /// Current smuggling objective, if any (same for all players).
+    pub smuggling: Option<Smuggling>,
+    /// Balance overrides, if they changed since the last update.
+    pub entity_overrides: Option<EntityOverrides>,
+    /// Shipwrecks within sonar range, if the player is in a submarine.
+    pub shipwrecks: Box<[Shipwreck]>,
+    /// Capturable zones (same for all players).
+    pub zones: Box<[Zone]>,
+    /// Positions of active sonar pings heard since the last update.
+    pub pings: Box<[Vec2]>,
+    /// Increases by one with each update sent to the player, to be acknowledged with an [`Ack`].
+    pub sequence: u32,
+    /// Aircraft under the player's direct control, if any (see [`ControlAircraft`]).
+    pub controlled_aircraft: Option<EntityId>,
+    /// Current round, if the arena is in blitz mode (same for all players).
+    pub round: Option<Round>,
+    /// Today's missions, and the player's progress towards them, if they changed since the last
+    /// update.
+    pub missions: Option<DailyMissions>,
+    /// Statistics about the player's last boat, sent once after it sinks.
+    pub stats: Option<LifeStats>,
+}
+
+/// A timed round of blitz mode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Round {
+    /// Starts at one.
+    pub number: u32,
+    /// Whole seconds until the round ends or, if it ended, until the next round starts.
+    pub remaining: u32,
+    /// Final standings, best first, if the round ended.
+    pub results: Option<Arc<[LeaderboardDto]>>,
+}
+
+/// A zone around a structure, which awards points over time to whoever holds it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub position: Vec2,
+    pub radius: f32,
+    pub holder: Option<ZoneHolder>,
+    /// Who is capturing the zone, and their progress (0 to 1), if anyone.
+    pub capture: Option<(ZoneHolder, f32)>,
+    /// Whether boats of more than one holder are inside, such that no progress can be made.
+    pub contested: bool,
+}
+
+/// Teams hold zones together, but players without a team can hold zones alone.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum ZoneHolder {
+    Player(PlayerId),
+    Team(TeamId),
+}
+
+impl ZoneHolder {
+    pub fn new(player_id: PlayerId, team_id: Option<TeamId>) -> Self {
+        team_id.map(Self::Team).unwrap_or(Self::Player(player_id))
+    }
+}
+
+/// A wreck on the seafloor, which submarines can salvage by loitering nearby.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shipwreck {
+    pub position: Vec2,
+    /// Progress (0 to 1) towards the next loot, if the player is salvaging it.
+    pub salvage: Option<f32>,
+}
+
+/// A player carrying valuable cargo to a far away port, which everyone else may intercept.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Smuggling {
+    pub carrier: PlayerId,
+    /// Where the carrier is.
+    pub cargo: Vec2,
+    /// Where the cargo must be delivered.
+    pub port: Vec2,
+}
+
+/// A boat sinking, as shown in the kill feed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sinking {
+    pub player_id: PlayerId,
+    pub alias: PlayerAlias,
+    pub entity_type: EntityType,
+    /// Contains the killer and weapon, if any.
+    pub reason: DeathReason,
+    /// Whether this boat had sunk its killer before sinking, e.g. in a close duel.
+    pub mutual: bool,
 }
 
 /// Updates for terrain chunks.
@@ -36,10 +150,15 @@ pub enum Command {
     Control(Control),
     Spawn(Spawn),
     Upgrade(Upgrade),
+    /// Must be after existing variants, so their bincoding stays the same.
+    ControlDelta(ControlDelta),
+    Ack(Ack),
+    ControlAircraft(ControlAircraft),
+    Purchase(Purchase),
 }
 
 /// Generic command to control one's ship.
-#[derive(Clone, Serialize, PartialEq, Deserialize, Debug)]
+#[derive(Clone, Default, Serialize, PartialEq, Deserialize, Debug)]
 pub struct Control {
     /// Steering commands.
     pub guidance: Option<Guidance>,
@@ -55,6 +174,318 @@ pub struct Control {
     pub pay: Option<Pay>,
     /// Optional hints.
     pub hint: Option<Hint>,
+    /// Target depth of submerged submarine, or [`None`] for its test depth.
+    pub depth: Option<Altitude>,
+    /// What anti-aircraft guns should engage.
+    pub doctrine: Doctrine,
+}
+
+/// Only the fields of a [`Control`] that changed since the previous one (which the server
+/// remembers), to save upstream bandwidth.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlDelta {
+    /// Bit `i` is set iff field `i` (in order of [`Control`]) is present.
+    changed: u16,
+    /// Fields that aren't present are default.
+    control: Control,
+}
+
+impl ControlDelta {
+    const GUIDANCE: u16 = 1 << 0;
+    const SUBMERGE: u16 = 1 << 1;
+    const AIM_TARGET: u16 = 1 << 2;
+    const ACTIVE: u16 = 1 << 3;
+    const FIRE: u16 = 1 << 4;
+    const PAY: u16 = 1 << 5;
+    const HINT: u16 = 1 << 6;
+    const DEPTH: u16 = 1 << 7;
+    const DOCTRINE: u16 = 1 << 8;
+
+    /// Encodes `current` relative to `previous`.
+    pub fn new(previous: &Control, current: &Control) -> Self {
+        let bits = [
+            (Self::GUIDANCE, current.guidance != previous.guidance),
+            (Self::SUBMERGE, current.submerge != previous.submerge),
+            (Self::AIM_TARGET, current.aim_target != previous.aim_target),
+            (Self::ACTIVE, current.active != previous.active),
+            // Not idempotent, so never carried over from `previous`.
+            (Self::FIRE, current.fire.is_some()),
+            (Self::PAY, current.pay.is_some()),
+            (Self::HINT, current.hint != previous.hint),
+            (Self::DEPTH, current.depth != previous.depth),
+            (Self::DOCTRINE, current.doctrine != previous.doctrine),
+        ];
+
+        Self {
+            changed: bits
+                .iter()
+                .filter(|(_, changed)| *changed)
+                .fold(0, |acc, (bit, _)| acc | bit),
+            control: current.clone(),
+        }
+    }
+
+    /// Returns true iff no fields changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed == 0
+    }
+
+    /// Decodes the full control, relative to `previous`.
+    pub fn apply(&self, previous: &Control) -> Control {
+        let has = |bit: u16| self.changed & bit != 0;
+        let delta = &self.control;
+        let mut control = previous.clone();
+        if has(Self::GUIDANCE) {
+            control.guidance = delta.guidance;
+        }
+        if has(Self::SUBMERGE) {
+            control.submerge = delta.submerge;
+        }
+        if has(Self::AIM_TARGET) {
+            control.aim_target = delta.aim_target;
+        }
+        if has(Self::ACTIVE) {
+            control.active = delta.active;
+        }
+        control.fire = delta.fire.clone().filter(|_| has(Self::FIRE));
+        control.pay = delta.pay.clone().filter(|_| has(Self::PAY));
+        if has(Self::HINT) {
+            control.hint = delta.hint.clone();
+        }
+        if has(Self::DEPTH) {
+            control.depth = delta.depth;
+        }
+        if has(Self::DOCTRINE) {
+            control.doctrine = delta.doctrine;
+        }
+        control
+    }
+
+    /// Pay is a unit struct, so its bit suffices.
+    fn tuple_len(&self) -> usize {
+        (self.changed & !Self::PAY).count_ones() as usize
+    }
+}
+
+impl Serialize for ControlDelta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Contains bits, variable tuple.
+        let mut header = serializer.serialize_tuple(2)?;
+        header.serialize_element(&self.changed)?;
+        header.serialize_element(&ControlDeltaSerializer(self))?;
+        header.end()
+    }
+}
+
+struct ControlDeltaSerializer<'a>(&'a ControlDelta);
+
+impl<'a> Serialize for ControlDeltaSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ControlDelta { changed, control } = self.0;
+        let mut tup = serializer.serialize_tuple(self.0.tuple_len())?;
+        if changed & ControlDelta::GUIDANCE != 0 {
+            tup.serialize_element(&control.guidance)?;
+        }
+        if changed & ControlDelta::SUBMERGE != 0 {
+            tup.serialize_element(&control.submerge)?;
+        }
+        if changed & ControlDelta::AIM_TARGET != 0 {
+            tup.serialize_element(&control.aim_target)?;
+        }
+        if changed & ControlDelta::ACTIVE != 0 {
+            tup.serialize_element(&control.active)?;
+        }
+        if changed & ControlDelta::FIRE != 0 {
+            tup.serialize_element(control.fire.as_ref().unwrap())?;
+        }
+        if changed & ControlDelta::HINT != 0 {
+            tup.serialize_element(&control.hint)?;
+        }
+        if changed & ControlDelta::DEPTH != 0 {
+            tup.serialize_element(&control.depth)?;
+        }
+        if changed & ControlDelta::DOCTRINE != 0 {
+            tup.serialize_element(&control.doctrine)?;
+        }
+        tup.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ControlDelta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HeaderVisitor;
+
+        impl<'de> Visitor<'de> for HeaderVisitor {
+            type Value = ControlDelta;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a control delta header tuple")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut delta = ControlDelta {
+                    changed: seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?,
+                    control: Control::default(),
+                };
+                seq.next_element_seed(ControlDeltaDeserializer(&mut delta))?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(delta)
+            }
+        }
+
+        deserializer.deserialize_tuple(2, HeaderVisitor)
+    }
+}
+
+struct ControlDeltaDeserializer<'a>(&'a mut ControlDelta);
+
+impl<'de, 'a> DeserializeSeed<'de> for ControlDeltaDeserializer<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let len = self.0.tuple_len();
+        deserializer.deserialize_tuple(len, self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for ControlDeltaDeserializer<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a control delta tuple")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let missing = || serde::de::Error::custom("missing control delta field");
+        let changed = self.0.changed;
+        let control = &mut self.0.control;
+        if changed & ControlDelta::GUIDANCE != 0 {
+            control.guidance = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::SUBMERGE != 0 {
+            control.submerge = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::AIM_TARGET != 0 {
+            control.aim_target = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::ACTIVE != 0 {
+            control.active = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::FIRE != 0 {
+            control.fire = Some(seq.next_element()?.ok_or_else(missing)?);
+        }
+        if changed & ControlDelta::PAY != 0 {
+            control.pay = Some(Pay);
+        }
+        if changed & ControlDelta::HINT != 0 {
+            control.hint = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::DEPTH != 0 {
+            control.depth = seq.next_element()?.ok_or_else(missing)?;
+        }
+        if changed & ControlDelta::DOCTRINE != 0 {
+            control.doctrine = seq.next_element()?.ok_or_else(missing)?;
+        }
+        Ok(())
+    }
+}
+
+/// Acknowledges which recent [`Update`]s were received, so that contacts sent in any that were lost
+/// can be resent right away, instead of whenever they would next be sent.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Ack {
+    /// Most recent [`Update::sequence`] received.
+    pub sequence: u32,
+    /// Bit `i` is set if the update with sequence `sequence - 1 - i` was received.
+    pub received: u32,
+}
+
+impl Ack {
+    /// Records that an update was received (possibly out of order).
+    pub fn receive(&mut self, sequence: u32) {
+        if sequence > self.sequence {
+            let shift = sequence - self.sequence;
+            // The previous most recent sequence becomes bit `shift - 1`.
+            self.received = self.received.checked_shl(shift).unwrap_or(0)
+                | 1u32.checked_shl(shift - 1).unwrap_or(0);
+            self.sequence = sequence;
+        } else if sequence < self.sequence {
+            self.received |= 1u32.checked_shl(self.sequence - sequence - 1).unwrap_or(0);
+        }
+    }
+
+    /// Returns true if the update was received. Sequences too old to be tracked are assumed not to
+    /// have been received.
+    pub fn contains(&self, sequence: u32) -> bool {
+        match sequence.cmp(&self.sequence) {
+            Ordering::Equal => true,
+            Ordering::Less => self
+                .received
+                .checked_shr(self.sequence - sequence - 1)
+                .map_or(false, |bits| bits & 1 != 0),
+            Ordering::Greater => false,
+        }
+    }
+}
+
+/// What a boat's anti-aircraft guns engage. They engage their priority at full strength, and
+/// anything else at reduced strength, but firing them gives away the boat's position.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub enum Doctrine {
+    AircraftFirst,
+    MissilesFirst,
+    HoldFire,
+}
+
+impl Doctrine {
+    /// Strength of anti-aircraft fire at targets that aren't the priority.
+    const SECONDARY_EFFICACY: f32 = 0.5;
+
+    /// Returns the strength (from 0 to 1) with which anti-aircraft guns engage a target, which is
+    /// zero for targets that aren't aircraft or missiles.
+    pub fn efficacy(self, target: &EntityData) -> f32 {
+        let aircraft = target.kind == EntityKind::Aircraft;
+        let missile = target.kind == EntityKind::Weapon
+            && matches!(
+                target.sub_kind,
+                EntitySubKind::Missile | EntitySubKind::RocketTorpedo
+            );
+
+        match self {
+            Self::AircraftFirst if aircraft => 1.0,
+            Self::MissilesFirst if missile => 1.0,
+            Self::AircraftFirst | Self::MissilesFirst if aircraft || missile => {
+                Self::SECONDARY_EFFICACY
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+impl Default for Doctrine {
+    fn default() -> Self {
+        Self::AircraftFirst
+    }
 }
 
 /// Fire/use a single weapon.
@@ -70,11 +501,17 @@ pub struct Hint {
     /// aspect ratio of screen (width / height).
     /// Allows the server to send the correct amount of terrain.
     pub aspect: f32,
+    /// Requests a reduced update profile, for slow or metered connections. Distant contacts are
+    /// updated less often, and cosmetic events (such as sonar pings) are omitted.
+    pub low_bandwidth: bool,
 }
 
 impl Default for Hint {
     fn default() -> Self {
-        Self { aspect: 1.0 }
+        Self {
+            aspect: 1.0,
+            low_bandwidth: false,
+        }
     }
 }
 
@@ -83,22 +520,82 @@ impl Default for Hint {
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Pay;
 
+/// Takes direct control of one of the player's launched aircraft, such that [`Control`] steers
+/// it instead of the player's boat (which keeps its previous course and speed).
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ControlAircraft {
+    /// Aircraft to control, or [`None`] to return control to the boat.
+    pub entity_id: Option<EntityId>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Spawn {
     /// What to spawn as. Must be an affordable boat.
     pub entity_type: EntityType,
+    /// Index into [`EntityData::loadouts`] of `entity_type`. Ignored if it has none.
+    pub loadout: u8,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Upgrade {
     /// What to upgrade to. Must be an affordable boat of higher level.
     pub entity_type: EntityType,
+    /// Index into [`EntityData::loadouts`] of `entity_type`. Ignored if it has none.
+    pub loadout: u8,
+}
+
+/// Spends coins (score) on the player's boat, while it's near an HQ or oil platform.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Purchase {
+    /// Repair all hull damage.
+    Repair,
+    /// Instantly reload all armaments of the given type.
+    Reload(EntityType),
+}
+
+impl Purchase {
+    /// Score per coin, same as a [`EntityType::Coin`] is worth.
+    pub const COIN_VALUE: u32 = 10;
+    /// How far a boat's edge may be from a store's edge to purchase (in meters).
+    pub const RANGE: f32 = 100.0;
+
+    /// Whether purchases can be made at this entity type.
+    pub fn is_store(entity_type: EntityType) -> bool {
+        matches!(entity_type, EntityType::Hq | EntityType::OilPlatform)
+    }
+
+    /// Whether a boat at `boat_position` is close enough to a store at `store_position`.
+    pub fn in_range(
+        boat_type: EntityType,
+        boat_position: Vec2,
+        store_type: EntityType,
+        store_position: Vec2,
+    ) -> bool {
+        let range = boat_type.data().radius + store_type.data().radius + Self::RANGE;
+        Self::is_store(store_type)
+            && boat_position.distance_squared(store_position) <= range.powi(2)
+    }
+
+    /// Cost in coins for a boat of `level`, that has lost `damage` (0 to 1) of its health. Bigger
+    /// boats pay more. Zero if there is nothing to buy.
+    pub fn coins(self, level: u8, damage: f32) -> u32 {
+        match self {
+            Self::Repair => (damage.clamp(0.0, 1.0) * level as f32 * 4.0).ceil() as u32,
+            Self::Reload(_) => level as u32,
+        }
+    }
+
+    /// [`Self::coins`] in score.
+    pub fn cost(self, level: u8, damage: f32) -> u32 {
+        self.coins(level, damage) * Self::COIN_VALUE
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::altitude::Altitude;
+    use crate::angle::Angle;
     use crate::contact::ReloadsStorage;
     use crate::entity::EntityId;
     use crate::guidance::Guidance;
@@ -123,9 +620,24 @@ mod tests {
                 .then(|| EntityType::iter().choose(&mut rng).unwrap());
             let is_boat = entity_type.map_or(false, |t| t.data().kind == EntityKind::Boat);
 
+            let mut disabled = DisabledModules::default();
+            if is_boat {
+                disabled.set_propulsion(rng.gen());
+                disabled.set_steering(rng.gen());
+                disabled.set_turret(rng.gen_range(0..8), rng.gen());
+            }
+
+            let mut effects = StatusEffects::default();
+            if is_boat {
+                effects.set_fire(rng.gen());
+                effects.set_flooding(rng.gen());
+            }
+
             let c = Contact::new(
                 Altitude::from_u8(rng.gen()),
                 Ticks::from_secs(rng.gen::<f32>() * 10.0),
+                disabled,
+                effects,
                 entity_type,
                 Guidance {
                     direction_target: rng.gen(),
@@ -134,6 +646,7 @@ mod tests {
                 EntityId::new(rng.gen_range(1..u32::MAX)).unwrap(),
                 rng.gen_bool(0.5)
                     .then(|| PlayerId(NonZeroU32::new(rng.gen_range(1..u32::MAX)).unwrap())),
+                entity_type.is_none() && rng.gen_bool(0.5),
                 (is_boat && rng.gen_bool(0.5)).then(|| {
                     let mut arr = BitArray::<ReloadsStorage>::ZERO;
                     for (_, mut r) in entity_type
@@ -189,4 +702,116 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn purchase() {
+        let hq = EntityType::Hq;
+        let boat = EntityType::FairmileD;
+        let radius = boat.data().radius + hq.data().radius;
+        assert!(Purchase::in_range(
+            boat,
+            Vec2::ZERO,
+            hq,
+            vec2(radius + 50.0, 0.0)
+        ));
+        assert!(!Purchase::in_range(
+            boat,
+            Vec2::ZERO,
+            hq,
+            vec2(radius + 150.0, 0.0)
+        ));
+        assert!(!Purchase::in_range(
+            boat,
+            Vec2::ZERO,
+            EntityType::Acacia,
+            Vec2::ZERO
+        ));
+
+        assert_eq!(Purchase::Repair.coins(5, 0.0), 0);
+        assert!(Purchase::Repair.coins(5, 0.5) < Purchase::Repair.coins(5, 1.0));
+        assert!(Purchase::Repair.coins(2, 1.0) < Purchase::Repair.coins(8, 1.0));
+        assert_eq!(
+            Purchase::Reload(EntityType::Mark18).cost(3, 0.0),
+            3 * Purchase::COIN_VALUE
+        );
+    }
+
+    #[test]
+    fn ack() {
+        let mut ack = Ack::default();
+        ack.receive(1);
+        ack.receive(2);
+        ack.receive(4);
+        assert!(ack.contains(1) && ack.contains(2) && ack.contains(4));
+        assert!(!ack.contains(3) && !ack.contains(5));
+
+        // Out of order.
+        ack.receive(3);
+        assert!(ack.contains(3));
+        assert_eq!(ack.sequence, 4);
+
+        // Too far behind to be tracked.
+        ack.receive(40);
+        assert!(ack.contains(40));
+        assert!(!ack.contains(4));
+        ack.receive(9);
+        assert!(ack.contains(9));
+    }
+
+    #[test]
+    fn control_delta() {
+        let previous = Control {
+            guidance: Some(Guidance {
+                direction_target: Angle::from_radians(1.0),
+                velocity_target: Velocity::from_mps(5.0),
+            }),
+            submerge: false,
+            aim_target: Some(vec2(100.0, -50.0)),
+            active: true,
+            fire: None,
+            pay: None,
+            hint: Some(Hint {
+                aspect: 1.5,
+                low_bandwidth: false,
+            }),
+            depth: None,
+            doctrine: Doctrine::AircraftFirst,
+        };
+        let current = Control {
+            aim_target: Some(vec2(110.0, -40.0)),
+            depth: Some(Altitude::from_whole_meters(-120)),
+            doctrine: Doctrine::HoldFire,
+            fire: Some(Fire { armament_index: 2 }),
+            pay: Some(Pay),
+            ..previous.clone()
+        };
+
+        let options = DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+
+        let unchanged = ControlDelta::new(&previous, &previous);
+        assert!(unchanged.is_empty());
+        assert_eq!(options.serialize(&unchanged).unwrap().len(), 2);
+
+        let delta = ControlDelta::new(&previous, &current);
+        let bytes = options.serialize(&delta).unwrap();
+        assert!(bytes.len() < options.serialize(&current).unwrap().len());
+        let decoded: ControlDelta = options.deserialize(&bytes).unwrap();
+        assert_eq!(decoded.apply(&previous), current);
+
+        let json = serde_json::to_string(&delta).unwrap();
+        let decoded: ControlDelta = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.apply(&previous), current);
+
+        // Fire and pay aren't carried over.
+        assert_eq!(
+            unchanged.apply(&current),
+            Control {
+                fire: None,
+                pay: None,
+                ..current
+            }
+        );
+    }
 }
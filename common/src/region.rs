@@ -0,0 +1,91 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::angle::Angle;
+use crate::entity::EntityType;
+use crate::terrain::Coord;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A handcrafted region, such as a canal, harbor, or event arena, made with the map editor. The
+/// server stamps it onto the procedural world.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    /// Terrain pixels that replace generated terrain.
+    pub terrain: Vec<RegionPixel>,
+    /// Structures that are spawned, and respawned if destroyed.
+    pub structures: Vec<RegionStructure>,
+}
+
+/// One terrain pixel of a [`Region`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RegionPixel {
+    pub x: u16,
+    pub y: u16,
+    /// Raw terrain value, as returned by [`Terrain::at`][`crate::terrain::Terrain::at`].
+    pub value: u8,
+}
+
+/// One structure of a [`Region`].
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionStructure {
+    pub entity_type: EntityType,
+    pub position: Vec2,
+    pub direction: Angle,
+}
+
+impl Region {
+    /// Parses a region overlay file, as exported by the map editor.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Formats a region overlay file.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Returns the raw terrain value of each pixel, for fast lookup during terrain generation.
+    pub fn terrain_values(&self) -> HashMap<Coord, u8> {
+        self.terrain
+            .iter()
+            .map(|pixel| (Coord(pixel.x as usize, pixel.y as usize), pixel.value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angle::Angle;
+    use crate::entity::EntityType;
+    use crate::region::{Region, RegionPixel, RegionStructure};
+    use crate::terrain::Coord;
+    use glam::Vec2;
+
+    #[test]
+    fn json() {
+        let region = Region {
+            terrain: vec![RegionPixel {
+                x: 500,
+                y: 510,
+                value: 0b10100000,
+            }],
+            structures: vec![RegionStructure {
+                entity_type: EntityType::OilPlatform,
+                position: Vec2::new(100.0, -200.0),
+                direction: Angle::ZERO,
+            }],
+        };
+
+        let parsed = Region::from_json(&region.to_json()).unwrap();
+        assert_eq!(parsed, region);
+        assert_eq!(
+            parsed.terrain_values().get(&Coord(500, 510)),
+            Some(&0b10100000)
+        );
+        assert!(Region::from_json("{").is_err());
+    }
+}
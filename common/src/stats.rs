@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entity::EntityType;
+use crate::ticks::Ticks;
+use serde::{Deserialize, Serialize};
+
+/// Statistics about one type of weapon, over the life of a boat.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeaponStats {
+    pub weapon: EntityType,
+    /// Times the player fired the weapon.
+    pub fired: u32,
+    /// Times the weapon hit another player's boat.
+    pub hits: u32,
+    /// Damage dealt to other players' boats, in the same units as [`EntityData::damage`].
+    ///
+    /// [`EntityData::damage`]: crate::entity::EntityData::damage
+    pub damage_dealt: f32,
+    /// Damage taken from other players' weapons of this type.
+    pub damage_taken: f32,
+}
+
+/// Statistics about the life of a player's boat, which are summarized when it sinks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LifeStats {
+    /// By type of weapon, in the order they were first used.
+    pub weapons: Vec<WeaponStats>,
+    /// Boats sunk, in order.
+    pub sunk: Vec<EntityType>,
+    /// Distance travelled, in meters.
+    pub travelled: f32,
+    /// Time spent alive.
+    pub survived: Ticks,
+}
+
+impl LifeStats {
+    fn weapon_mut(&mut self, weapon: EntityType) -> &mut WeaponStats {
+        if let Some(i) = self.weapons.iter().position(|w| w.weapon == weapon) {
+            &mut self.weapons[i]
+        } else {
+            self.weapons.push(WeaponStats {
+                weapon,
+                fired: 0,
+                hits: 0,
+                damage_dealt: 0.0,
+                damage_taken: 0.0,
+            });
+            self.weapons.last_mut().unwrap()
+        }
+    }
+
+    /// Call when the player fires a weapon.
+    pub fn fired(&mut self, weapon: EntityType) {
+        self.weapon_mut(weapon).fired += 1;
+    }
+
+    /// Call when one of the player's weapons damages another player's boat.
+    pub fn hit(&mut self, weapon: EntityType, damage: f32) {
+        let stats = self.weapon_mut(weapon);
+        stats.hits += 1;
+        stats.damage_dealt += damage;
+    }
+
+    /// Call when another player's weapon damages the player's boat.
+    pub fn hit_by(&mut self, weapon: EntityType, damage: f32) {
+        self.weapon_mut(weapon).damage_taken += damage;
+    }
+
+    /// Fraction of fired weapons that hit, or [`None`] if no weapons were fired.
+    pub fn accuracy(&self) -> Option<f32> {
+        // Weapons that weren't fired directly (e.g. by aircraft) don't count.
+        let (fired, hits) = self
+            .weapons
+            .iter()
+            .filter(|w| w.fired > 0)
+            .fold((0, 0), |(fired, hits), w| {
+                (fired + w.fired, hits + w.hits.min(w.fired))
+            });
+        (fired > 0).then(|| hits as f32 / fired as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::EntityType;
+    use crate::stats::LifeStats;
+
+    #[test]
+    fn accuracy() {
+        let mut stats = LifeStats::default();
+        assert_eq!(stats.accuracy(), None);
+
+        for _ in 0..4 {
+            stats.fired(EntityType::Mark18);
+        }
+        stats.hit(EntityType::Mark18, 0.5);
+        stats.hit_by(EntityType::Mark18, 0.25);
+        stats.hit(EntityType::Mark48, 1.0);
+        assert_eq!(stats.weapons.len(), 2);
+        assert_eq!(stats.weapons[0].damage_dealt, 0.5);
+        assert_eq!(stats.weapons[0].damage_taken, 0.25);
+        assert_eq!(stats.accuracy(), Some(0.25));
+    }
+}
@@ -104,7 +104,7 @@ fn reverse_lookup_altitude(altitude: Altitude) -> u8 {
 }
 
 // TODO make this a UVec2.
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
 pub struct Coord(pub usize, pub usize);
 
 /// Any terrain pixel can be represented as a `Coord`.
@@ -318,6 +318,21 @@ impl TerrainMutation {
         }
     }
 
+    /// Carves nearby land (and shallow seafloor) in proportion to weapon `damage`. Small amounts
+    /// are carved only some of the time, since the change would otherwise round to nothing. Returns
+    /// `None` if nothing should be carved this time.
+    pub fn carve(position: Vec2, damage: f32) -> Option<Self> {
+        const MIN_AMOUNT: f32 = 0.7;
+        let probability = (damage * (1.0 / MIN_AMOUNT)).clamp(0.0, 1.0);
+        thread_rng().gen_bool(probability as f64).then(|| {
+            Self::conditional(
+                position,
+                -20.0 * damage.max(MIN_AMOUNT),
+                Altitude(-10)..=Altitude::MAX,
+            )
+        })
+    }
+
     /// Changes the height of nearby terrain provided it is within a range, such that it remains within
     /// another range.
     pub fn conditional_clamped(
@@ -405,6 +420,29 @@ impl Terrain {
         chunk.as_ref().unwrap()
     }
 
+    /// Iterates the chunks that were generated so far (e.g. to save them).
+    pub fn iter_chunks(&self) -> impl Iterator<Item = (ChunkId, &Chunk)> + '_ {
+        self.chunks.iter().enumerate().flat_map(|(cy, chunks)| {
+            chunks.iter().enumerate().filter_map(move |(cx, chunk)| {
+                chunk
+                    .as_deref()
+                    .map(|chunk| (ChunkId(cx as u16, cy as u16), chunk))
+            })
+        })
+    }
+
+    /// Replaces a chunk with one encoded by [`Chunk::to_bytes`] (e.g. to restore a saved terrain).
+    /// Like any modified chunk, it gradually regenerates to its original height.
+    pub fn restore_chunk(&mut self, chunk_id: ChunkId, bytes: &[u8]) -> Result<(), &'static str> {
+        if chunk_id.0 as usize >= SIZE_CHUNKS || chunk_id.1 as usize >= SIZE_CHUNKS {
+            return Err("chunk out of bounds");
+        }
+        let chunk = self.mut_chunk(chunk_id);
+        *chunk = Chunk::from_bytes(bytes);
+        chunk.mark_for_regenerate();
+        Ok(())
+    }
+
     /// Applies a terrain update, overwriting relevant terrain pixels.
     pub fn apply_update(&mut self, update: &TerrainUpdate) {
         for (chunk_id, serialized) in update.iter() {
@@ -720,9 +758,9 @@ impl Terrain {
                         if now >= next_regen {
                             let chunk_id = ChunkId(cx as u16, cy as u16);
                             chunk.regenerate(chunk_id, self.generator); // TODO parallelize
-
-                            chunk.update = ChunkUpdate::Complete;
-                            self.updated.add(chunk_id);
+                            if !matches!(chunk.update, ChunkUpdate::None) {
+                                self.updated.add(chunk_id);
+                            }
                         }
                     }
                 }
@@ -840,6 +878,8 @@ impl Default for ChunkUpdate {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SerializedChunk {
     is_update: bool,
+    /// Version of the chunk after applying this.
+    version: u16,
     bytes: Arc<[u8]>, // TODO: use serde_bytes.
 }
 
@@ -848,6 +888,9 @@ pub struct Chunk {
     data: [[u8; CHUNK_SIZE / 2]; CHUNK_SIZE],
     next_regen: Option<Instant>,
     update: ChunkUpdate,
+    /// Incremented (wrapping) each tick the chunk changes, so that updates can be checked for
+    /// continuity.
+    version: u16,
 }
 
 impl Chunk {
@@ -857,6 +900,7 @@ impl Chunk {
             data: [[0; CHUNK_SIZE / 2]; CHUNK_SIZE],
             next_regen: None,
             update: ChunkUpdate::None,
+            version: 0,
         }
     }
 
@@ -878,7 +922,8 @@ impl Chunk {
         chunk
     }
 
-    /// regenerate brings each pixel of the chunk one unit closer to original height.
+    /// regenerate brings each pixel of the chunk one unit closer to original height. Changes are
+    /// captured, so they can be sent as mods instead of the whole chunk.
     pub fn regenerate(&mut self, chunk_id: ChunkId, generator: Generator) {
         let coord = chunk_id.as_coord();
         let x_offset = coord.0;
@@ -899,7 +944,7 @@ impl Chunk {
                     std::cmp::Ordering::Equal => continue,
                 };
 
-                self.set(coord, new_height);
+                self.set_capture(coord, new_height);
 
                 if new_height != original_height {
                     incomplete = true;
@@ -957,6 +1002,10 @@ impl Chunk {
     fn calculate_mods(&mut self) {
         use std::mem;
 
+        if !matches!(self.update, ChunkUpdate::None) {
+            self.version = self.version.wrapping_add(1);
+        }
+
         self.update = match mem::take(&mut self.update) {
             ChunkUpdate::None => ChunkUpdate::None,
             ChunkUpdate::Complete => ChunkUpdate::Complete,
@@ -1033,6 +1082,7 @@ impl Chunk {
                 ChunkUpdate::Mods(mods) => {
                     return SerializedChunk {
                         is_update: true,
+                        version: self.version,
                         bytes: Arc::clone(mods),
                     }
                 }
@@ -1043,31 +1093,47 @@ impl Chunk {
         // Send whole chunk.
         SerializedChunk {
             is_update: false,
+            version: self.version,
             bytes: self.to_bytes().into(), // TODO could save encoded chunk is lru cache but would require atomics.
         }
     }
 
+    /// Applies a serialized chunk. Updates that weren't yet cleared are merged, instead of being
+    /// replaced, so that nothing is missed if updates arrive faster than they are consumed.
     pub fn apply_serialized_chunk(&mut self, serialized: &SerializedChunk) {
         let bytes: &[u8] = &*serialized.bytes;
 
-        self.update = if serialized.is_update {
+        if serialized.is_update {
+            let continuous = serialized.version == self.version.wrapping_add(1);
+            debug_assert!(continuous, "missed terrain update");
+
             // Apply mods and collect coords.
-            ChunkUpdate::Coords(
-                bytes
-                    .array_chunks::<2>()
-                    .map(|b| {
-                        let m = Mod::from_bytes(*b);
-                        let (coord, value) = m.to_coord_and_value();
-                        self.set(coord.into_absolute_coord(), value);
-                        coord
-                    })
-                    .collect(),
-            )
+            let coords: Vec<_> = bytes
+                .array_chunks::<2>()
+                .map(|b| {
+                    let m = Mod::from_bytes(*b);
+                    let (coord, value) = m.to_coord_and_value();
+                    self.set(coord.into_absolute_coord(), value);
+                    coord
+                })
+                .collect();
+
+            self.update = match std::mem::take(&mut self.update) {
+                // If an update was missed, the whole chunk is suspect.
+                _ if !continuous => ChunkUpdate::Complete,
+                ChunkUpdate::None | ChunkUpdate::Mods(_) => ChunkUpdate::Coords(coords),
+                ChunkUpdate::Coords(mut existing) => {
+                    existing.extend(coords);
+                    ChunkUpdate::Coords(existing)
+                }
+                ChunkUpdate::Complete => ChunkUpdate::Complete,
+            };
         } else {
             // Overwrite chunk.
             *self = Self::from_bytes(bytes);
-            ChunkUpdate::Complete
+            self.update = ChunkUpdate::Complete;
         }
+        self.version = serialized.version;
     }
 
     /// Returns an iterator over the updated coordinates in a chunk.
@@ -1399,4 +1465,47 @@ mod tests {
         let chunk2 = Chunk::from_bytes(&bytes);
         assert_eq!(chunk.data, chunk2.data);
     }
+
+    #[test]
+    fn merge_updates() {
+        let mut server = Terrain::new();
+        let mut client = Terrain::new();
+        let chunk_id = ChunkId::from_coord(Coord::from_position(Vec2::splat(210.0)).unwrap());
+
+        let complete = server
+            .get_chunk(chunk_id)
+            .to_serialized_chunk(false, &server, chunk_id);
+        client.apply_update(&[(chunk_id, complete)]);
+        client.clear_updated();
+
+        // Client doesn't consume the first update before the second arrives.
+        for position in [Vec2::splat(210.0), Vec2::splat(310.0)] {
+            server.post_update();
+            assert_eq!(
+                server.modify(TerrainMutation::simple(position, 60.0)),
+                Some(true)
+            );
+            server.pre_update();
+            let update = server
+                .get_chunk(chunk_id)
+                .to_serialized_chunk(true, &server, chunk_id);
+            assert!(update.is_update);
+            client.apply_update(&[(chunk_id, update)]);
+        }
+
+        let client_chunk = client.get_chunk(chunk_id);
+        assert_eq!(client_chunk.data, server.get_chunk(chunk_id).data);
+        assert_eq!(client_chunk.version, 2);
+
+        // Changes from both updates are still known.
+        let updated: Vec<_> = client_chunk.updated_coords(chunk_id).collect();
+        let original = Terrain::new();
+        let Coord(x, y) = chunk_id.as_coord();
+        for coord in (y..y + CHUNK_SIZE).flat_map(|y| (x..x + CHUNK_SIZE).map(move |x| Coord(x, y)))
+        {
+            if client.at(coord) != original.at(coord) {
+                assert!(updated.contains(&coord), "{:?}", coord);
+            }
+        }
+    }
 }
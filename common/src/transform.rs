@@ -38,12 +38,14 @@ impl Transform {
         }
     }
 
-    /// apply_guidance modifies a Transform according to a Guidance.
+    /// apply_guidance modifies a Transform according to a Guidance. turn_scale multiplies the turn
+    /// rate (e.g. to account for knocked out steering).
     pub fn apply_guidance(
         &mut self,
         data: &EntityData,
         guidance: Guidance,
         mut max_speed: f32,
+        turn_scale: f32,
         delta_seconds: f32,
     ) {
         debug_assert!(max_speed >= 0.0);
@@ -52,19 +54,21 @@ impl Transform {
 
         // Collectibles don't turn with guidance.
         // Shells and rockets (at least the ones currently in the game) can't turn.
-        // Mines and depth charges have no control surfaces.
+        // Mines, nets, and depth charges have no control surfaces.
         if data.kind != EntityKind::Collectible
             && !matches!(
                 data.sub_kind,
                 EntitySubKind::Shell
                     | EntitySubKind::Rocket
                     | EntitySubKind::Mine
+                    | EntitySubKind::Net
                     | EntitySubKind::DepthCharge
             )
         {
             let delta_angle = guidance.direction_target - self.direction;
             let turn_max = Angle::from_radians(
                 (delta_seconds
+                    * turn_scale
                     * match data.kind {
                         // Longer boats turn slower.
                         EntityKind::Boat => 0.125 + 20.0 / data.length,
@@ -116,6 +120,21 @@ impl Transform {
         );
     }
 
+    /// Advances the transform by `delta_seconds`, steering according to `guidance`. This is the
+    /// common core of movement, used by both server physics and client prediction. It is
+    /// deterministic (see [`Angle::to_vec`]), so the same inputs give identical results on both.
+    pub fn simulate(
+        &mut self,
+        data: &EntityData,
+        guidance: Guidance,
+        max_speed: f32,
+        turn_scale: f32,
+        delta_seconds: f32,
+    ) {
+        self.apply_guidance(data, guidance, max_speed, turn_scale, delta_seconds);
+        self.do_kinematics(delta_seconds);
+    }
+
     /// do_kinematics updates the position field of a transform based on the direction and velocity fields.
     pub fn do_kinematics(&mut self, delta_seconds: f32) {
         self.position += self.direction.to_vec() * self.velocity.to_mps() * delta_seconds;
@@ -1,6 +1,9 @@
 use crate::entity::EntitySubKind;
 use crate::entity::EntityType;
+use core_protocol::UnixTime;
 use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// For testing larger world sizes.
 pub const SIZE: usize = 1;
@@ -11,6 +14,93 @@ pub const ARCTIC: f32 = 1250.0;
 /// Everything with a y coordinate below this is in the tropics biome.
 pub const TROPICS: f32 = -2250.0;
 
+/// Style of terrain generated outside the tropics, chosen per arena.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Biome {
+    /// Scattered islands of varying size.
+    #[default]
+    Temperate,
+    /// Many small islands.
+    Archipelago,
+    /// Few, sparse islands.
+    OpenOcean,
+    /// Large landmasses cut by narrow channels.
+    Fjord,
+    /// Ice sheets extend far south of the arctic.
+    IceShelf,
+}
+
+impl Biome {
+    pub const ALL: [Self; 5] = [
+        Self::Temperate,
+        Self::Archipelago,
+        Self::OpenOcean,
+        Self::Fjord,
+        Self::IceShelf,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Temperate => "temperate",
+            Self::Archipelago => "archipelago",
+            Self::OpenOcean => "open_ocean",
+            Self::Fjord => "fjord",
+            Self::IceShelf => "ice_shelf",
+        }
+    }
+}
+
+impl FromStr for Biome {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|biome| biome.as_str() == s)
+            .ok_or("unknown biome")
+    }
+}
+
+/// World-wide seasonal event state, which changes how the world looks (but not how it plays).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Season {
+    #[default]
+    Normal,
+    /// Around the winter holidays, everything is colder.
+    Winter,
+    /// Around Halloween, it is always night.
+    Night,
+}
+
+impl Season {
+    /// Returns the season at a given time (in milliseconds since the epoch).
+    pub fn from_unix_time(unix_time: UnixTime) -> Self {
+        match month_and_day(unix_time) {
+            (10, 24..=31) => Self::Night,
+            (12, 15..=31) | (1, 1..=7) => Self::Winter,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// Returns the (1-based) month and day of the month, in UTC.
+fn month_and_day(unix_time: UnixTime) -> (u32, u32) {
+    // Based on http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (unix_time / (24 * 60 * 60 * 1000)) as i64 + 719468;
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    (month as u32, day as u32)
+}
+
 // TODO: Would it make more sense to represent areas as [`Range<f32>`]?
 
 /// Returns if an entity is within it's spawnable area such as ocean for dredger or arctic for icebreaker.
@@ -86,3 +176,31 @@ pub fn strict_area_border_normal(entity_type: EntityType) -> Option<Vec2> {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::world::{month_and_day, Biome, Season};
+    use std::str::FromStr;
+
+    #[test]
+    fn season() {
+        const DAY: u64 = 24 * 60 * 60 * 1000;
+        assert_eq!(month_and_day(0), (1, 1));
+        // 2000-02-29
+        assert_eq!(month_and_day(11016 * DAY), (2, 29));
+        // 2022-10-31
+        assert_eq!(Season::from_unix_time(19296 * DAY + 1), Season::Night);
+        // 2022-12-25
+        assert_eq!(Season::from_unix_time(19351 * DAY), Season::Winter);
+        // 2022-07-04
+        assert_eq!(Season::from_unix_time(19177 * DAY), Season::Normal);
+    }
+
+    #[test]
+    fn biome() {
+        for biome in Biome::ALL {
+            assert_eq!(Biome::from_str(biome.as_str()), Ok(biome));
+        }
+        assert!(Biome::from_str("desert").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Silent [`AudioPlayer`], used when the `audio` feature is disabled (such as by server-side
+//! tools and tests, which have no browser to play audio in).
+
+use crate::audio::Audio;
+use crate::visibility::VisibilityEvent;
+use std::marker::PhantomData;
+
+/// Pretends to play audio, but never downloads or plays anything.
+pub struct AudioPlayer<A: Audio> {
+    spooky: PhantomData<A>,
+}
+
+impl<A: Audio> Default for AudioPlayer<A> {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl<A: Audio> AudioPlayer<A> {
+    pub fn new(_priority: &[A]) -> Self {
+        Self {
+            spooky: PhantomData,
+        }
+    }
+
+    pub fn play(&self, _audio: A) {}
+
+    pub fn play_with_volume(&self, _audio: A, _volume: f32) {}
+
+    pub fn play_with_volume_and_delay(&self, _audio: A, _volume: f32, _delay: f32) {}
+
+    pub fn play_looping(&self, _audio: A) {}
+
+    pub fn is_playing(&self, _audio: A) -> bool {
+        false
+    }
+
+    pub fn stop_playing(&self, _audio: A) {}
+
+    pub(crate) fn set_volume_setting(&self, _volume_setting: f32) {}
+
+    pub fn set_muted_by_game(&self, _muted_by_game: bool) {}
+
+    pub(crate) fn peek_visibility(&self, _event: &VisibilityEvent) {}
+
+    pub fn set_muted_by_ad(&self, _muted_by_ad: bool) {}
+}
@@ -0,0 +1,457 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! [`AudioPlayer`] backed by the Web Audio API, used when the `audio` feature is enabled.
+
+use crate::audio::Audio;
+use crate::visibility::VisibilityEvent;
+use js_sys::{Function, Promise, Reflect, Uint8Array};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{
+    AudioBuffer, AudioBufferSourceNode, AudioContext, AudioContextState, Event, GainNode, Response,
+};
+
+/// Renders (plays) audio.
+///
+/// The audio file is streamed, and each sprite is decoded on its own as soon as its part of the
+/// file arrives, so sounds can play long before the whole file is downloaded. Priority sprites
+/// (see [`AudioPlayer::new`]) are decoded first, other sprites on first use, or once the download
+/// completes.
+pub struct AudioPlayer<A: Audio> {
+    inner: Rc<RefCell<Option<Inner<A>>>>,
+}
+
+struct Inner<A: Audio> {
+    context: AudioContext,
+    sfx_gain: GainNode,
+    _music_gain: GainNode,
+    /// The audio file, as much of it has been downloaded so far.
+    download: Download,
+    /// Sprites indexed by [`Audio::index`].
+    sprites: Box<[SpriteState]>,
+    /// Audio indexed by [`Audio::index`].
+    playing: Box<[Vec<AudioBufferSourceNode>]>,
+    /// What volume is or is ramping up/down to.
+    volume_target: f32,
+    /// The game wants to mute all audio.
+    muted_by_game: bool,
+    /// Whether muted because the page is unfocused.
+    muted_by_visibility: bool,
+    /// Whether muted due to conflicting with an advertisement's audio.
+    muted_by_ad: bool,
+    /// Volume (kept up to date with the corresponding setting.
+    volume_setting: f32,
+    spooky: PhantomData<A>,
+}
+
+#[derive(Default)]
+struct Download {
+    bytes: Vec<u8>,
+    /// Used to map sprite times to byte ranges. Known once the download completes, or upfront if
+    /// the server sends an (uncompressed) content length.
+    bytes_per_second: Option<f32>,
+    complete: bool,
+}
+
+enum SpriteState {
+    /// Not needed yet.
+    Unrequested,
+    /// Will be decoded as soon as its bytes are downloaded.
+    Requested,
+    /// Being decoded (or failed to decode).
+    Decoding,
+    Decoded(SpriteBuffer),
+}
+
+/// A sprite decoded on its own from a slice of the audio file.
+struct SpriteBuffer {
+    buffer: AudioBuffer,
+    /// Seconds into `buffer` that the sprite starts.
+    offset: f32,
+}
+
+impl<A: Audio> Default for AudioPlayer<A> {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl<A: Audio> AudioPlayer<A> {
+    /// Starts downloading audio. `priority` sprites, such as UI sounds and alarms, are decoded
+    /// before any others.
+    pub fn new(priority: &[A]) -> Self {
+        if let Ok(context) = web_sys::AudioContext::new() {
+            if let Some((sfx_gain, music_gain)) = web_sys::GainNode::new(&context)
+                .ok()
+                .zip(web_sys::GainNode::new(&context).ok())
+            {
+                let _ = sfx_gain.connect_with_audio_node(&context.destination());
+                let _ = music_gain.connect_with_audio_node(&context.destination());
+
+                let mut sprites: Box<[SpriteState]> = (0..std::mem::variant_count::<A>())
+                    .map(|_| SpriteState::Unrequested)
+                    .collect();
+                for audio in priority {
+                    sprites[audio.index()] = SpriteState::Requested;
+                }
+
+                let inner = Rc::new(RefCell::new(Some(Inner {
+                    context,
+                    sfx_gain,
+                    _music_gain: music_gain,
+                    download: Download::default(),
+                    sprites,
+                    playing: vec![Vec::new(); std::mem::variant_count::<A>()].into_boxed_slice(),
+                    muted_by_game: false,
+                    muted_by_visibility: false,
+                    muted_by_ad: false,
+                    volume_target: 0.0,
+                    volume_setting: 0.0,
+                    spooky: PhantomData,
+                })));
+
+                let inner_clone = inner.clone();
+                let _ = future_to_promise(async move {
+                    if let Err(_e) = Inner::stream(&inner_clone).await {
+                        #[cfg(debug_assertions)]
+                        js_hooks::console_log!("could not load audio: {:?}", _e);
+                        *inner_clone.borrow_mut() = None;
+                    }
+                    Ok(JsValue::from_str("ok"))
+                });
+
+                return Self { inner };
+            }
+        };
+
+        Self {
+            inner: Rc::new(RefCell::new(None)),
+        }
+    }
+}
+
+impl<A: Audio> AudioPlayer<A> {
+    /// Plays a particular sound once.
+    pub fn play(&self, audio: A) {
+        self.play_with_volume(audio, 1.0);
+    }
+
+    /// Plays a particular sound once, with a specified volume.
+    pub fn play_with_volume(&self, audio: A, volume: f32) {
+        Inner::play(&self.inner, audio, volume, false);
+    }
+
+    /// Plays a particular sound once, with a specified volume and delay in seconds.
+    pub fn play_with_volume_and_delay(&self, audio: A, volume: f32, _delay: f32) {
+        Inner::play(&self.inner, audio, volume, false);
+    }
+
+    /// Plays a particular sound in a loop.
+    pub fn play_looping(&self, audio: A) {
+        Inner::play(&self.inner, audio, 1.0, true);
+    }
+
+    pub fn is_playing(&self, audio: A) -> bool {
+        self.inner
+            .borrow_mut()
+            .as_mut()
+            .map(|inner| inner.is_playing(audio))
+            .unwrap_or(false)
+    }
+
+    pub fn stop_playing(&self, audio: A) {
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.stop_playing(audio)
+        }
+    }
+
+    // Sets a multiplier for the volume of all sounds.
+    pub(crate) fn set_volume_setting(&self, volume_setting: f32) {
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.volume_setting = volume_setting;
+            inner.update_volume();
+        }
+    }
+
+    /// For the game to mute/unmute all audio.
+    pub fn set_muted_by_game(&self, muted_by_game: bool) {
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.muted_by_game = muted_by_game;
+            inner.update_volume();
+        }
+    }
+
+    pub(crate) fn peek_visibility(&self, event: &VisibilityEvent) {
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.muted_by_visibility = match event {
+                VisibilityEvent::Visible(visible) => !visible,
+            };
+            inner.update_volume();
+        }
+    }
+
+    pub fn set_muted_by_ad(&self, muted_by_ad: bool) {
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.muted_by_ad = muted_by_ad;
+            inner.update_volume();
+        }
+    }
+}
+
+impl<A: Audio> Inner<A> {
+    /// Silence padding around each sprite (the sprite sheet packer leaves 0.5 seconds between
+    /// sprites). Absorbs the imprecision of slicing a compressed file by bytes.
+    const PADDING: f32 = 0.25;
+
+    fn recalculate_volume(&self) -> f32 {
+        if self.muted_by_game || self.muted_by_visibility || self.muted_by_ad {
+            0.0
+        } else {
+            self.volume_setting
+        }
+    }
+
+    fn update_volume(&mut self) {
+        let new_volume = self.recalculate_volume();
+        if new_volume != self.volume_target {
+            self.volume_target = new_volume;
+            if let Err(_e) = self
+                .sfx_gain
+                .gain()
+                .linear_ramp_to_value_at_time(new_volume, self.context.current_time() + 1.5)
+            {
+                #[cfg(debug_assertions)]
+                js_hooks::console_log!("could not linear ramp audio: {:?}", _e);
+                self.sfx_gain.gain().set_value(new_volume);
+            }
+        }
+    }
+
+    /// Plays a particular sound, optionally in a loop. This is private, since looping is never
+    /// determined at runtime.
+    fn play(rc: &Rc<RefCell<Option<Self>>>, audio: A, volume: f32, looping: bool) {
+        let mut requested = false;
+        if let Some(inner) = rc.borrow_mut().as_mut() {
+            if inner.recalculate_volume() == 0.0 {
+                return;
+            }
+
+            if inner.context.state() == AudioContextState::Suspended {
+                let _ = inner.context.resume();
+            } else if let SpriteState::Decoded(sprite_buffer) = &inner.sprites[audio.index()] {
+                let sprite = &A::sprites()[audio.index()];
+                let source: AudioBufferSourceNode = inner
+                    .context
+                    .create_buffer_source()
+                    .unwrap()
+                    .dyn_into()
+                    .unwrap();
+
+                source.set_buffer(Some(&sprite_buffer.buffer));
+
+                let gain = web_sys::GainNode::new(&inner.context).unwrap();
+                gain.gain().set_value(volume);
+                let _ = source.connect_with_audio_node(&gain);
+
+                let _ = gain.connect_with_audio_node(&inner.sfx_gain);
+
+                // Sprite times are relative to the whole file, not the slice that was decoded.
+                let start = sprite_buffer.offset;
+                if looping {
+                    source.set_loop(true);
+                    source.set_loop_start(
+                        (start + sprite.loop_start.map_or(0.0, |ls| ls - sprite.start)) as f64,
+                    );
+                    source.set_loop_end((start + sprite.duration) as f64);
+                    let _ = source.start_with_when_and_grain_offset(0.0, start as f64);
+                } else {
+                    let _ = source.start_with_when_and_grain_offset_and_grain_duration(
+                        0.0,
+                        start as f64,
+                        sprite.duration as f64,
+                    );
+                }
+
+                let cloned_rc = Rc::clone(rc);
+                let stop = Closure::once_into_js(move |value: JsValue| {
+                    let event: Event = value.dyn_into().unwrap();
+                    if let Some(inner) = cloned_rc.borrow_mut().as_mut() {
+                        let playing = &mut inner.playing[audio.index()];
+                        for source in playing.drain_filter(|p| {
+                            *p == event
+                                .target()
+                                .unwrap()
+                                .dyn_into::<AudioBufferSourceNode>()
+                                .unwrap()
+                        }) {
+                            // Ensure no double-invocation.
+                            source.set_onended(None);
+                        }
+                    }
+                });
+
+                source.set_onended(Some(stop.as_ref().unchecked_ref()));
+
+                inner.playing[audio.index()].push(source);
+            } else if matches!(inner.sprites[audio.index()], SpriteState::Unrequested) {
+                // Too late to play this time, but should be ready next time.
+                inner.sprites[audio.index()] = SpriteState::Requested;
+                requested = true;
+            }
+        }
+        if requested {
+            Self::decode_available(rc);
+        }
+    }
+
+    /// Downloads the audio file, decoding requested sprites as their bytes arrive.
+    async fn stream(rc: &Rc<RefCell<Option<Self>>>) -> Result<(), JsValue> {
+        let response: Response = JsFuture::from(js_hooks::window().fetch_with_str(A::path()))
+            .await?
+            .dyn_into()?;
+
+        // Content length is of the compressed body, if the server compressed it.
+        let headers = response.headers();
+        let content_length = headers
+            .get("content-length")?
+            .filter(|_| matches!(headers.get("content-encoding"), Ok(None)))
+            .and_then(|s| s.parse::<usize>().ok());
+        if let Some(inner) = rc.borrow_mut().as_mut() {
+            inner.download.bytes_per_second =
+                content_length.map(|len| len as f32 / Self::total_duration());
+        }
+
+        let body = response.body().ok_or("no body")?;
+        let reader = body.get_reader();
+        let read: Function = Reflect::get(&reader, &JsValue::from_str("read"))?.dyn_into()?;
+        loop {
+            let promise: Promise = read.call0(&reader)?.dyn_into()?;
+            let result = JsFuture::from(promise).await?;
+            if Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+                break;
+            }
+            let chunk: Uint8Array =
+                Reflect::get(&result, &JsValue::from_str("value"))?.dyn_into()?;
+
+            if let Some(inner) = rc.borrow_mut().as_mut() {
+                let start = inner.download.bytes.len();
+                inner
+                    .download
+                    .bytes
+                    .resize(start + chunk.length() as usize, 0);
+                chunk.copy_to(&mut inner.download.bytes[start..]);
+            } else {
+                return Ok(());
+            }
+            Self::decode_available(rc);
+        }
+
+        if let Some(inner) = rc.borrow_mut().as_mut() {
+            let download = &mut inner.download;
+            download.complete = true;
+            download.bytes_per_second = Some(download.bytes.len() as f32 / Self::total_duration());
+
+            // Everything else is decoded in the background, in case it is needed later.
+            for sprite in inner.sprites.iter_mut() {
+                if matches!(sprite, SpriteState::Unrequested) {
+                    *sprite = SpriteState::Requested;
+                }
+            }
+        }
+        Self::decode_available(rc);
+
+        Ok(())
+    }
+
+    /// Starts decoding every requested sprite whose bytes have been downloaded.
+    fn decode_available(rc: &Rc<RefCell<Option<Self>>>) {
+        let mut borrow = rc.borrow_mut();
+        let inner = if let Some(inner) = borrow.as_mut() {
+            inner
+        } else {
+            return;
+        };
+        let bytes_per_second = if let Some(bytes_per_second) = inner.download.bytes_per_second {
+            bytes_per_second
+        } else {
+            return;
+        };
+
+        for (index, (state, sprite)) in inner.sprites.iter_mut().zip(A::sprites()).enumerate() {
+            if !matches!(state, SpriteState::Requested) {
+                continue;
+            }
+
+            let t0 = (sprite.start - Self::PADDING).max(0.0);
+            let t1 = sprite.start + sprite.duration + Self::PADDING;
+            let bytes = &inner.download.bytes;
+            let start = ((t0 * bytes_per_second) as usize).min(bytes.len());
+            let end = (t1 * bytes_per_second) as usize;
+            if end > bytes.len() && !inner.download.complete {
+                continue;
+            }
+            let slice = &bytes[start..end.min(bytes.len())];
+
+            // Decoding detaches the buffer, so it must be a copy.
+            let array_buffer = Uint8Array::from(slice).buffer();
+            let promise = match inner.context.decode_audio_data(&array_buffer) {
+                Ok(promise) => promise,
+                Err(_) => continue,
+            };
+            *state = SpriteState::Decoding;
+
+            let expected = slice.len() as f32 / bytes_per_second;
+            let offset = sprite.start - t0;
+            let rc = Rc::clone(rc);
+            let _ = future_to_promise(async move {
+                match JsFuture::from(promise)
+                    .await
+                    .map(JsCast::dyn_into::<AudioBuffer>)
+                {
+                    Ok(Ok(buffer)) => {
+                        // The decoder drops partial frames at the start of the slice, which
+                        // shifts everything earlier.
+                        let dropped = (expected - buffer.duration() as f32).max(0.0);
+                        let offset = (offset - dropped).max(0.0);
+                        if let Some(inner) = rc.borrow_mut().as_mut() {
+                            inner.sprites[index] =
+                                SpriteState::Decoded(SpriteBuffer { buffer, offset });
+                        }
+                    }
+                    _ => {
+                        #[cfg(debug_assertions)]
+                        js_hooks::console_log!("could not decode audio sprite {}", index);
+                    }
+                }
+                Ok(JsValue::from_str("ok"))
+            });
+        }
+    }
+
+    /// Duration of the whole audio file, in seconds, including silence after the last sprite.
+    fn total_duration() -> f32 {
+        A::sprites()
+            .iter()
+            .map(|sprite| sprite.start + sprite.duration)
+            .fold(0.0, f32::max)
+            + Self::PADDING * 2.0
+    }
+
+    fn is_playing(&self, audio: A) -> bool {
+        !self.playing[audio.index()].is_empty()
+    }
+
+    fn stop_playing(&mut self, audio: A) {
+        let playing = &mut self.playing[audio.index()];
+        for removed in playing.drain(..) {
+            // WebAudio bug makes unsetting loop required?
+            removed.set_loop(false);
+            let _ = removed.stop();
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+#[cfg(not(feature = "native"))]
 use js_hooks::window;
 use std::str::FromStr;
 use web_sys::Storage;
@@ -15,10 +16,19 @@ pub struct BrowserStorages {
 }
 
 impl BrowserStorages {
+    /// With the `native` feature, there is no browser, so nothing is ever stored.
     pub fn new() -> Self {
+        #[cfg(not(feature = "native"))]
+        let (local, session) = (
+            window().local_storage().ok().flatten(),
+            window().session_storage().ok().flatten(),
+        );
+        #[cfg(feature = "native")]
+        let (local, session) = (None, None);
+
         Self {
-            local: BrowserStorage::new(window().local_storage().ok().flatten()),
-            session: BrowserStorage::new(window().session_storage().ok().flatten()),
+            local: BrowserStorage::new(local),
+            session: BrowserStorage::new(session),
             no_op: BrowserStorage::new(None),
         }
     }
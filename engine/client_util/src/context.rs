@@ -5,26 +5,31 @@ use crate::apply::Apply;
 use crate::browser_storage::BrowserStorages;
 use crate::frontend::Frontend;
 use crate::game_client::GameClient;
-use crate::js_util::{domain_name_of, host, invitation_id, is_https, ws_protocol};
+use crate::input_monitor::InputMonitor;
+use crate::js_util::{domain_name_of, host, is_https, ws_protocol};
 use crate::keyboard::KeyboardState;
 use crate::mouse::MouseState;
 use crate::reconn_web_socket::ReconnWebSocket;
 use crate::setting::CommonSettings;
 use crate::visibility::VisibilityState;
-use core_protocol::dto::{LeaderboardDto, LiveboardDto, MessageDto, PlayerDto, ServerDto, TeamDto};
+use core_protocol::dto::{
+    Achievement, LeaderboardDto, LiveboardDto, MessageDto, PlayerDto, ServerDto, TeamDto,
+    TournamentDto,
+};
 use core_protocol::id::{CohortId, InvitationId, LoginType, PeriodId, PlayerId, ServerId, TeamId};
-use core_protocol::name::PlayerAlias;
+use core_protocol::name::{ClassName, PlayerAlias};
 use core_protocol::rpc::{
-    ChatUpdate, ClientRequest, ClientUpdate, InvitationUpdate, LeaderboardUpdate, LiveboardUpdate,
-    PlayerUpdate, Request, SystemUpdate, TeamUpdate, Update, WebSocketQuery,
+    AchievementUpdate, ChatUpdate, ClientRequest, ClientUpdate, InvitationUpdate,
+    LeaderboardUpdate, LiveboardUpdate, PlayerUpdate, Request, SystemUpdate, TeamUpdate, Update,
+    WebSocketQuery,
 };
+use core_protocol::web_socket::PROTOCOL_VERSION;
 use heapless::HistoryBuffer;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::rc::Rc;
 use web_sys::UrlSearchParams;
 
-#[cfg(feature = "audio")]
 use crate::audio::AudioPlayer;
 
 /// The context (except rendering) of a game.
@@ -35,11 +40,12 @@ pub struct Context<G: GameClient + ?Sized> {
     pub state: ServerState<G>,
     /// Server websocket
     pub socket: ReconnWebSocket<Update<G::GameUpdate>, Request<G::GameRequest>, ServerState<G>>,
-    /// Audio player (volume managed automatically).
-    #[cfg(feature = "audio")]
+    /// Audio player (volume managed automatically). Silent without the `audio` feature.
     pub audio: AudioPlayer<G::Audio>,
     /// Keyboard input.
     pub keyboard: KeyboardState,
+    /// Diagnoses lost input.
+    pub input_monitor: InputMonitor,
     /// Mouse input.
     pub mouse: MouseState,
     /// Whether the page is visible.
@@ -51,6 +57,9 @@ pub struct Context<G: GameClient + ?Sized> {
     /// Local storage.
     pub browser_storages: BrowserStorages,
     pub(crate) frontend: Box<dyn Frontend<G::UiProps> + 'static>,
+    /// Invitation to the private arena the player created, if any, which takes precedence over
+    /// the one in the URL.
+    pub(crate) arena_invitation_id: Option<InvitationId>,
 }
 
 /// State common to all clients.
@@ -69,7 +78,11 @@ pub struct ServerState<G: GameClient> {
 /// Server state specific to core functions
 #[derive(Default)]
 pub struct CoreState {
+    /// Achievements unlocked since the page loaded, oldest first.
+    pub achievements: Vec<Achievement>,
     pub cohort_id: Option<CohortId>,
+    /// Provider the player signed in with, if any.
+    pub login_type: Option<LoginType>,
     pub player_id: Option<PlayerId>,
     pub created_invitation_id: Option<InvitationId>,
     /// Ordered, i.e. first is captain.
@@ -78,12 +91,22 @@ pub struct CoreState {
     pub joins: Box<[TeamId]>,
     /// TODO: Deprecate `pub`
     pub leaderboards: [Box<[LeaderboardDto]>; std::mem::variant_count::<PeriodId>()],
+    /// Leaderboards of particular classes, received upon request (see
+    /// [`LeaderboardRequest::Class`][core_protocol::rpc::LeaderboardRequest::Class]).
+    pub class_leaderboards: HashMap<(ClassName, PeriodId), Box<[LeaderboardDto]>>,
     pub liveboard: Vec<LiveboardDto>,
     pub messages: HistoryBuffer<MessageDto, 9>,
     pub(crate) players: HashMap<PlayerId, PlayerDto>,
     pub real_players: u32,
+    /// Latest round trip time in milliseconds, as measured by the server.
+    pub rtt: Option<u16>,
     pub teams: HashMap<TeamId, TeamDto>,
     pub servers: HashMap<ServerId, ServerDto>,
+    /// Tournament the player opted in to, if any.
+    pub tournament: Option<TournamentDto>,
+    /// Final standings of the most recent tournament, received upon request (see
+    /// [`LeaderboardRequest::Tournament`][core_protocol::rpc::LeaderboardRequest::Tournament]).
+    pub tournament_leaderboard: Box<[LeaderboardDto]>,
 }
 
 impl<G: GameClient> Default for ServerState<G> {
@@ -134,6 +157,7 @@ impl CoreState {
             .then(|| {
                 Some(PlayerDto {
                     alias: PlayerAlias::from_bot_player_id(player_id),
+                    commendations: 0,
                     player_id,
                     team_captain: false,
                     moderator: false,
@@ -166,6 +190,17 @@ impl CoreState {
     pub fn leaderboard(&self, period_id: PeriodId) -> &[LeaderboardDto] {
         &self.leaderboards[period_id as usize]
     }
+
+    /// Gets the leaderboard of a particular class, if it was received.
+    pub fn class_leaderboard(
+        &self,
+        class: ClassName,
+        period_id: PeriodId,
+    ) -> Option<&[LeaderboardDto]> {
+        self.class_leaderboards
+            .get(&(class, period_id))
+            .map(|leaderboard| &**leaderboard)
+    }
 }
 
 impl<G: GameClient> Apply<Update<G::GameUpdate>> for ServerState<G> {
@@ -176,6 +211,11 @@ impl<G: GameClient> Apply<Update<G::GameUpdate>> for ServerState<G> {
         let mut core = Rc::borrow_mut(&mut self.core);
 
         match update {
+            Update::Achievement(update) => match update {
+                AchievementUpdate::Unlocked(unlocked) => {
+                    core.achievements.extend(unlocked.into_vec());
+                }
+            },
             Update::Chat(update) => {
                 match update {
                     ChatUpdate::Received(received) => {
@@ -189,12 +229,20 @@ impl<G: GameClient> Apply<Update<G::GameUpdate>> for ServerState<G> {
             Update::Client(update) => match update {
                 ClientUpdate::SessionCreated {
                     cohort_id,
+                    login_type,
                     player_id,
                     ..
                 } => {
                     core.cohort_id = Some(cohort_id);
+                    core.login_type = login_type;
                     core.player_id = Some(player_id);
                 }
+                ClientUpdate::RttMeasured(rtt) => {
+                    core.rtt = Some(rtt);
+                }
+                ClientUpdate::TournamentJoined(tournament) => {
+                    core.tournament = Some(tournament);
+                }
                 _ => {}
             },
             Update::Game(update) => {
@@ -209,6 +257,13 @@ impl<G: GameClient> Apply<Update<G::GameUpdate>> for ServerState<G> {
                 LeaderboardUpdate::Updated(period_id, leaderboard) => {
                     core.leaderboards[period_id as usize] = leaderboard;
                 }
+                LeaderboardUpdate::Class(class, period_id, leaderboard) => {
+                    core.class_leaderboards
+                        .insert((class, period_id), leaderboard);
+                }
+                LeaderboardUpdate::Tournament(leaderboard) => {
+                    core.tournament_leaderboard = leaderboard;
+                }
             },
             Update::Liveboard(update) => {
                 match update {
@@ -305,32 +360,36 @@ impl<G: GameClient> Context<G> {
         settings: G::GameSettings,
         frontend: Box<dyn Frontend<G::UiProps> + 'static>,
     ) -> Self {
-        let (host, server_id) = Self::compute_websocket_host(&common_settings, None, &*frontend);
+        let (host, server_id) =
+            Self::compute_websocket_host(&common_settings, None, None, None, &*frontend);
         let socket = ReconnWebSocket::new(host, common_settings.protocol, None);
         common_settings.set_server_id(server_id, &mut browser_storages);
 
         Self {
-            #[cfg(feature = "audio")]
-            audio: AudioPlayer::default(),
+            audio: AudioPlayer::new(G::PRIORITY_AUDIO),
             client: ClientState::default(),
             state: ServerState::default(),
             socket,
             keyboard: KeyboardState::default(),
+            input_monitor: InputMonitor::default(),
             mouse: MouseState::default(),
             visibility: VisibilityState::default(),
             settings,
             common_settings,
             browser_storages,
             frontend,
+            arena_invitation_id: None,
         }
     }
 
     pub(crate) fn compute_websocket_host(
         common_settings: &CommonSettings,
         override_server_id: Option<ServerId>,
+        transfer: Option<String>,
+        arena_invitation_id: Option<InvitationId>,
         frontend: &dyn Frontend<G::UiProps>,
     ) -> (String, Option<ServerId>) {
-        let scheme = ws_protocol(frontend.get_real_encryption().unwrap_or(is_https()));
+        let scheme = ws_protocol(frontend.get_real_encryption().unwrap_or_else(is_https));
         let ideal_server_id = override_server_id.or(frontend.get_ideal_server_id());
         let host = frontend.get_real_host().unwrap_or_else(host);
 
@@ -341,18 +400,34 @@ impl<G: GameClient> Context<G> {
 
         // crate::console_log!("override={:?} ideal server={:?}, host={:?}, ideal_host={:?}", override_server_id, ideal_server_id, host, ideal_host);
 
-        let query = js_hooks::window().location().search().ok();
+        let query = frontend.get_real_query();
         let params = query.and_then(|query| UrlSearchParams::new_with_str(&query).ok());
-        let oauth2_code = params.and_then(|params| params.get("code"));
+        let oauth2_code = params.as_ref().and_then(|params| params.get("code"));
+        // Providers redirect back with the code, and the state, which is the provider and a nonce.
+        let (login_type, login_state) = params
+            .as_ref()
+            .and_then(|params| params.get("state"))
+            .filter(|_| oauth2_code.is_some())
+            .and_then(|state| {
+                let (login_type, nonce) = state.split_once('.')?;
+                Some((login_type.parse::<LoginType>().ok()?, nonce.to_owned()))
+            })
+            .unzip();
+        // Tournament casters connect as observers with e.g. ?observer=ADMIN_AUTH.
+        let observer_auth = params.and_then(|params| params.get("observer"));
 
         let web_socket_query = WebSocketQuery {
             protocol: Some(common_settings.protocol),
             arena_id: common_settings.arena_id,
             session_id: common_settings.session_id,
-            invitation_id: invitation_id(),
-            login_type: oauth2_code.is_some().then_some(LoginType::Discord),
+            invitation_id: arena_invitation_id.or_else(|| frontend.get_real_invitation_id()),
+            login_type,
+            login_state,
             login_id: oauth2_code,
             referrer: frontend.get_real_referrer(),
+            observer_auth,
+            transfer,
+            version: Some(PROTOCOL_VERSION),
         };
 
         let web_socket_query_url = serde_urlencoded::to_string(&web_socket_query).unwrap();
@@ -368,6 +443,11 @@ impl<G: GameClient> Context<G> {
         self.socket.is_terminated()
     }
 
+    /// Whether the server rejected this client as outdated, meaning it must be reloaded.
+    pub fn outdated(&self) -> bool {
+        self.socket.is_outdated()
+    }
+
     /// Send a game command on the socket.
     pub fn send_to_game(&mut self, request: G::GameRequest) {
         self.send_to_server(Request::Game(request));
@@ -378,11 +458,34 @@ impl<G: GameClient> Context<G> {
         self.send_to_server(Request::Client(ClientRequest::SetAlias(alias)));
     }
 
+    /// Send a request to create a private arena, with game-specific `options`. If it is created,
+    /// the player will be connected to it.
+    pub fn send_create_arena(&mut self, bots: bool, options: Vec<(String, String)>) {
+        self.send_to_server(Request::Client(ClientRequest::CreateArena {
+            bots,
+            options,
+        }));
+    }
+
+    /// Send a request to opt in to the scheduled tournament. Once it starts, the player will be
+    /// connected to its arena.
+    pub fn send_join_tournament(&mut self) {
+        self.send_to_server(Request::Client(ClientRequest::JoinTournament));
+    }
+
     /// Send a request to log an error message.
     pub fn send_trace(&mut self, message: String) {
         self.send_to_server(Request::Client(ClientRequest::Trace { message }));
     }
 
+    /// Send a report that the client's view of the world (`state`) seems wrong.
+    pub fn send_desync_report(&mut self, message: String, state: String) {
+        self.send_to_server(Request::Client(ClientRequest::ReportDesync {
+            message,
+            state,
+        }));
+    }
+
     /// Send a request on the socket.
     pub fn send_to_server(&mut self, request: Request<G::GameRequest>) {
         self.socket.send(request);
@@ -1,8 +1,8 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::js_util::referrer;
-use core_protocol::id::ServerId;
+use crate::js_util::{invitation_id, referrer};
+use core_protocol::id::{InvitationId, ServerId};
 use core_protocol::name::Referrer;
 
 pub trait Frontend<P> {
@@ -12,6 +12,14 @@ pub trait Frontend<P> {
     fn get_real_referrer(&self) -> Option<Referrer> {
         referrer()
     }
+    /// Gets the query string of the page's url, including the leading `?`.
+    fn get_real_query(&self) -> Option<String> {
+        js_hooks::window().location().search().ok()
+    }
+    /// Gets the invitation in the page's url, if any.
+    fn get_real_invitation_id(&self) -> Option<InvitationId> {
+        invitation_id()
+    }
     /// Gets url hosting client files.
     fn get_real_host(&self) -> Option<String>;
     /// True iif should use HTTPS/WSS.
@@ -17,8 +17,9 @@ pub trait GameClient: Sized + 'static {
     const GAME_ID: GameId;
 
     /// Audio files to play.
-    #[cfg(feature = "audio")]
     type Audio: crate::audio::Audio;
+    /// Audio to load before any other, such as UI sounds and alarms.
+    const PRIORITY_AUDIO: &'static [Self::Audio] = &[];
     /// Game-specific command to server.
     type GameRequest: 'static + Serialize + Clone;
     /// Game-specific state.
@@ -16,13 +16,16 @@ use common_util::range::map_ranges;
 use core_protocol::id::{PlayerId, ServerId, TeamId};
 use core_protocol::name::TeamName;
 use core_protocol::rpc::{
-    AdType, ChatRequest, ClientRequest, ClientUpdate, InvitationRequest, PlayerRequest, Request,
-    TeamRequest, Update,
+    AdType, ChatRequest, ClientRequest, ClientUpdate, InvitationRequest, Request, TeamRequest,
+    Update,
 };
 use core_protocol::web_socket::WebSocketProtocol;
 use glam::{IVec2, Vec2};
+#[cfg(not(feature = "native"))]
 use js_sys::Function;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::JsCast;
+#[cfg(not(feature = "native"))]
+use wasm_bindgen::JsValue;
 use web_sys::{
     Event, FocusEvent, HtmlInputElement, KeyboardEvent, MouseEvent, Touch, TouchEvent, WheelEvent,
 };
@@ -46,8 +49,9 @@ impl<G: GameClient> Infrastructure<G> {
         settings: G::GameSettings,
         frontend: Box<dyn Frontend<G::UiProps> + 'static>,
     ) -> Result<Self, (String, BrowserStorages, CommonSettings, G::GameSettings)> {
-        // Don't try to catch panics if aborting (because it's useless).
-        #[cfg(panic = "unwind")]
+        // Don't try to catch panics if aborting (because it's useless), or if there is no console
+        // to log them to.
+        #[cfg(all(panic = "unwind", not(feature = "native")))]
         std::panic::set_hook(Box::new(console_error_panic_hook::hook));
 
         #[cfg(feature = "joined")]
@@ -73,7 +77,6 @@ impl<G: GameClient> Infrastructure<G> {
     }
 
     pub fn frame(&mut self, time_seconds: f32) {
-        #[cfg(feature = "audio")]
         self.context
             .audio
             .set_volume_setting(self.context.common_settings.volume);
@@ -81,6 +84,9 @@ impl<G: GameClient> Infrastructure<G> {
         let elapsed_seconds = (time_seconds - self.context.client.time_seconds).clamp(0.001, 0.5);
         self.context.client.time_seconds = time_seconds;
 
+        let mut transfer = None;
+        let mut join_arena = false;
+
         for inbound in self
             .context
             .socket
@@ -101,6 +107,8 @@ impl<G: GameClient> Infrastructure<G> {
                     let (host, server_id) = Context::<G>::compute_websocket_host(
                         &self.context.common_settings,
                         server_id,
+                        None,
+                        self.context.arena_invitation_id,
                         &*self.context.frontend,
                     );
                     self.context.socket.reset_host(host);
@@ -123,21 +131,44 @@ impl<G: GameClient> Infrastructure<G> {
                             .set_session_id(Some(session_id), &mut self.context.browser_storages);
                     }
                 }
+                #[cfg(not(feature = "native"))]
                 Update::Client(ClientUpdate::EvalSnippet(snippet)) => {
                     // Do NOT use `eval`, since it runs in the local scope and therefore
                     // prevents minification.
                     let _ = Function::new_no_args(&snippet).call0(&JsValue::NULL);
                     // TODO: send result back to server.
                 }
+                Update::Client(ClientUpdate::Transferred { server_id, token }) => {
+                    transfer = Some((*server_id, token.clone()));
+                }
+                &Update::Client(
+                    ClientUpdate::ArenaCreated(invitation_id)
+                    | ClientUpdate::TournamentStarted(invitation_id),
+                ) => {
+                    self.context.arena_invitation_id = Some(invitation_id);
+                    join_arena = true;
+                }
                 _ => {}
             }
 
-            if let Update::Game(update) = &inbound {
-                self.game.peek_game(update, &mut self.context);
+            match &inbound {
+                Update::Client(update) => self.game.peek_core(update, &mut self.context),
+                Update::Game(update) => {
+                    self.context.input_monitor.record_update();
+                    self.game.peek_game(update, &mut self.context)
+                }
+                _ => {}
             }
             self.context.state.apply(inbound);
         }
 
+        // Updates from the old server (or arena) no longer matter.
+        if let Some((server_id, token)) = transfer {
+            self.connect(Some(server_id), Some(token));
+        } else if join_arena {
+            self.connect(self.context.common_settings.server_id, None);
+        }
+
         self.game.tick(elapsed_seconds, &mut self.context);
 
         if let Some(fps) = self.statistic_fps_monitor.update(elapsed_seconds) {
@@ -149,6 +180,7 @@ impl<G: GameClient> Infrastructure<G> {
     pub fn keyboard(&mut self, event: KeyboardEvent) {
         if let Some(target) = event.target() {
             if target.is_instance_of::<HtmlInputElement>() {
+                self.context.input_monitor.record_ignored();
                 return;
             }
         }
@@ -185,8 +217,13 @@ impl<G: GameClient> Infrastructure<G> {
                         event.stop_propagation();
                     }
 
+                    self.context
+                        .input_monitor
+                        .record_event(Self::event_time(&event));
                     self.game.peek_keyboard(&e, &mut self.context);
                     self.context.keyboard.apply(e);
+                } else {
+                    self.context.input_monitor.record_ignored();
                 }
             }
             _ => {}
@@ -215,8 +252,13 @@ impl<G: GameClient> Infrastructure<G> {
                         down,
                         time: self.context.client.time_seconds,
                     };
+                    self.context
+                        .input_monitor
+                        .record_event(Self::event_time(&event));
                     self.game.peek_mouse(&e, &mut self.context);
                     self.context.mouse.apply(e);
+                } else {
+                    self.context.input_monitor.record_ignored();
                 }
             }
             "mousemove" => {
@@ -244,6 +286,12 @@ impl<G: GameClient> Infrastructure<G> {
         event.prevent_default();
         event.stop_propagation();
 
+        if event.type_() != "touchmove" {
+            self.context
+                .input_monitor
+                .record_event(Self::event_time(&event));
+        }
+
         // Raw touch event.
         let touch_event = GameClientMouseEvent::Touch;
         self.game.peek_mouse(&touch_event, &mut self.context);
@@ -375,7 +423,6 @@ impl<G: GameClient> Infrastructure<G> {
         let visible = js_hooks::document().visibility_state() != web_sys::VisibilityState::Hidden;
         let e = VisibilityEvent::Visible(visible);
         self.game.peek_visibility(&e, &mut self.context);
-        #[cfg(feature = "audio")]
         self.context.audio.peek_visibility(&e);
         self.context.visibility.apply(e)
     }
@@ -387,6 +434,11 @@ impl<G: GameClient> Infrastructure<G> {
         self.context.mouse.apply(e);
     }
 
+    /// When the browser received an event, on the same clock as [`Self::frame`]'s time.
+    fn event_time(event: &Event) -> f32 {
+        (event.time_stamp() * 0.001) as f32
+    }
+
     /// Converts page position (from event) to view position (-1..1).
     fn client_coordinate_to_view(x: i32, y: i32) -> Vec2 {
         let rect = js_hooks::canvas().get_bounding_client_rect();
@@ -430,6 +482,9 @@ impl<G: GameClient> Infrastructure<G> {
 
     pub fn wheel(&mut self, event: WheelEvent) {
         event.prevent_default();
+        self.context
+            .input_monitor
+            .record_event(Self::event_time(&event));
         // each wheel step is 53 pixels.
         // do 0.5 or 1.0 raw zoom.
         let steps: f64 = event.delta_y() * (1.0 / 53.0);
@@ -448,6 +503,11 @@ impl<G: GameClient> Infrastructure<G> {
         self.send_request(Request::Chat(ChatRequest::Send { message, whisper }));
     }
 
+    /// Sends a command to the server to send a chat message to a single player.
+    pub fn send_whisper(&mut self, player_id: PlayerId, message: String) {
+        self.send_request(Request::Chat(ChatRequest::Whisper { player_id, message }));
+    }
+
     /// Sends a command to the server to create a new team.
     pub fn create_team(&mut self, team_name: TeamName) {
         self.context
@@ -496,7 +556,7 @@ impl<G: GameClient> Infrastructure<G> {
     pub fn report_player(&mut self, player_id: PlayerId) {
         self.context
             .socket
-            .send(Request::Player(PlayerRequest::Report(player_id)))
+            .send(Request::Chat(ChatRequest::Report(player_id)))
     }
 
     /// Sends a command to the server to mute or un-mute another player.
@@ -533,12 +593,29 @@ impl<G: GameClient> Infrastructure<G> {
         if server_id == self.context.common_settings.server_id {
             return;
         }
+        self.connect(server_id, None);
+    }
+
+    /// Asks the current server to transfer the player, along with their progress, to a different
+    /// server. If it agrees, the player will be connected to that server.
+    pub fn transfer_to_server_id(&mut self, server_id: ServerId) {
+        if Some(server_id) == self.context.common_settings.server_id {
+            return;
+        }
+        self.context
+            .send_to_server(Request::Client(ClientRequest::Transfer(server_id)));
+    }
+
+    /// Connects to a server, presenting a transfer token if any.
+    fn connect(&mut self, server_id: Option<ServerId>, transfer: Option<String>) {
         // Clear state from old server.
         self.context.state = ServerState::default();
 
         let (host, server_id) = Context::<G>::compute_websocket_host(
             &self.context.common_settings,
             server_id,
+            transfer,
+            self.context.arena_invitation_id,
             &*self.context.frontend,
         );
         self.context.socket =
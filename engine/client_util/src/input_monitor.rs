@@ -0,0 +1,126 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::VecDeque;
+
+/// Follows discrete keyboard and mouse events from the browser to the outgoing game command, to
+/// tell whether inputs that "didn't register" were lost by the client, the network, or the server.
+///
+/// Mouse movement is continuous, and therefore isn't tracked.
+#[derive(Default)]
+pub struct InputMonitor {
+    /// Times (in seconds, same clock as frames) of events not yet sent, oldest first.
+    pending: VecDeque<f32>,
+    /// Events received from the browser.
+    pub received: u32,
+    /// Events that never reached the game (e.g. a text input had focus, or unknown key).
+    pub ignored: u32,
+    /// Events that shared an outgoing command with other events, or didn't change it.
+    pub merged: u32,
+    /// Events that waited too long for a command, or were discarded (client fell behind).
+    pub stale: u32,
+    /// Commands sent while the socket was open.
+    pub sent: u32,
+    /// Commands that couldn't be sent because the socket wasn't open (network).
+    pub unsent: u32,
+    /// Commands sent since the last game update (server isn't keeping up, if large).
+    pub unacknowledged: u32,
+    /// Most commands sent between any two game updates.
+    pub max_unacknowledged: u32,
+    /// Smoothed seconds from event to command.
+    pub latency: Option<f32>,
+}
+
+impl InputMonitor {
+    /// Events pending longer than this are considered stale.
+    const STALE_SECONDS: f32 = 0.5;
+    /// At most this many events are kept pending.
+    const MAX_PENDING: usize = 64;
+
+    /// Call upon receiving an event that is passed on to the game.
+    pub fn record_event(&mut self, time_seconds: f32) {
+        self.received = self.received.saturating_add(1);
+        if self.pending.len() >= Self::MAX_PENDING {
+            self.pending.pop_front();
+            self.stale = self.stale.saturating_add(1);
+        }
+        self.pending.push_back(time_seconds);
+    }
+
+    /// Call upon receiving an event that isn't passed on to the game.
+    pub fn record_ignored(&mut self) {
+        self.received = self.received.saturating_add(1);
+        self.ignored = self.ignored.saturating_add(1);
+    }
+
+    /// Call when the game considers sending a command, but it is unchanged, so pending events
+    /// had no effect.
+    pub fn record_unchanged(&mut self) {
+        self.merged = self.merged.saturating_add(self.pending.len() as u32);
+        self.pending.clear();
+    }
+
+    /// Call when the game sends a command reflecting all pending events.
+    pub fn record_command(&mut self, time_seconds: f32, socket_open: bool) {
+        if socket_open {
+            self.sent = self.sent.saturating_add(1);
+            self.unacknowledged = self.unacknowledged.saturating_add(1);
+            self.max_unacknowledged = self.max_unacknowledged.max(self.unacknowledged);
+        } else {
+            self.unsent = self.unsent.saturating_add(1);
+        }
+
+        let pending = self.pending.len() as u32;
+        self.merged = self.merged.saturating_add(pending.saturating_sub(1));
+        for time in self.pending.drain(..) {
+            let latency = (time_seconds - time).max(0.0);
+            if latency > Self::STALE_SECONDS {
+                self.stale = self.stale.saturating_add(1);
+            }
+            self.latency = Some(
+                self.latency
+                    .map_or(latency, |smoothed| smoothed * 0.9 + latency * 0.1),
+            );
+        }
+    }
+
+    /// Call when pending events can't result in a command (e.g. not playing).
+    pub fn discard_pending(&mut self) {
+        self.stale = self.stale.saturating_add(self.pending.len() as u32);
+        self.pending.clear();
+    }
+
+    /// Call upon receiving a game update.
+    pub fn record_update(&mut self) {
+        self.unacknowledged = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_monitor::InputMonitor;
+
+    #[test]
+    fn input_monitor() {
+        let mut monitor = InputMonitor::default();
+        monitor.record_event(1.0);
+        monitor.record_event(1.05);
+        monitor.record_ignored();
+        monitor.record_command(1.1, true);
+        assert_eq!(monitor.received, 3);
+        assert_eq!(monitor.ignored, 1);
+        assert_eq!(monitor.merged, 1);
+        assert_eq!(monitor.sent, 1);
+        assert_eq!(monitor.stale, 0);
+
+        monitor.record_event(2.0);
+        monitor.record_command(3.0, false);
+        assert_eq!(monitor.unsent, 1);
+        assert_eq!(monitor.stale, 1);
+
+        monitor.record_command(3.1, true);
+        assert_eq!(monitor.max_unacknowledged, 2);
+        monitor.record_update();
+        assert_eq!(monitor.unacknowledged, 0);
+    }
+}
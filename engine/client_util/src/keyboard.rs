@@ -3,10 +3,11 @@
 
 use crate::apply::Apply;
 use std::num::NonZeroU8;
+use std::str::FromStr;
 use strum_macros::Display;
 
 /// Each variant is a possible key. Not guaranteed to support all keys.
-#[derive(Copy, Clone, Eq, PartialEq, Display)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Display)]
 pub enum Key {
     A,
     B,
@@ -65,6 +66,62 @@ pub enum Key {
 }
 
 impl Key {
+    /// Every key, in order of declaration.
+    pub const ALL: [Self; 52] = [
+        Self::A,
+        Self::B,
+        Self::C,
+        Self::D,
+        Self::E,
+        Self::F,
+        Self::G,
+        Self::H,
+        Self::I,
+        Self::J,
+        Self::K,
+        Self::L,
+        Self::M,
+        Self::N,
+        Self::O,
+        Self::P,
+        Self::Q,
+        Self::R,
+        Self::S,
+        Self::T,
+        Self::U,
+        Self::V,
+        Self::W,
+        Self::X,
+        Self::Y,
+        Self::Z,
+        Self::Zero,
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::Seven,
+        Self::Eight,
+        Self::Nine,
+        Self::Alt,
+        Self::Backspace,
+        Self::Ctrl,
+        Self::Down,
+        Self::Enter,
+        Self::Home,
+        Self::Left,
+        Self::PageDown,
+        Self::PageUp,
+        Self::MinusUnderscore,
+        Self::EqualsPlus,
+        Self::Right,
+        Self::Shift,
+        Self::Space,
+        Self::Tab,
+        Self::Up,
+    ];
+
     /// Converts from a Javascript keycode.
     pub fn try_from_key_code(key_code: u32) -> Option<Self> {
         Some(match key_code {
@@ -146,6 +203,18 @@ impl Key {
     }
 }
 
+/// Parses the result of [`ToString`].
+impl FromStr for Key {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.to_string() == s)
+            .ok_or(())
+    }
+}
+
 /// The state of any key.
 #[derive(Default, Copy, Clone)]
 pub enum KeyState {
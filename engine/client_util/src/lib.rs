@@ -12,14 +12,18 @@
 extern crate core;
 
 pub mod apply;
-#[cfg(feature = "audio")]
 pub mod audio;
+#[cfg(not(feature = "audio"))]
+mod audio_stub;
+#[cfg(feature = "audio")]
+mod audio_web;
 pub mod browser_storage;
 pub mod context;
 pub mod fps_monitor;
 pub mod frontend;
 pub mod game_client;
 pub mod infrastructure;
+pub mod input_monitor;
 #[cfg(feature = "joined")]
 pub mod joined;
 pub mod joystick;
@@ -31,3 +35,7 @@ pub mod reconn_web_socket;
 pub mod setting;
 pub mod visibility;
 pub mod web_socket;
+#[cfg(feature = "native")]
+mod web_socket_native;
+#[cfg(not(feature = "native"))]
+mod web_socket_web;
@@ -67,10 +67,15 @@ where
     /// Returns whether the underlying connection is closed and reconnection attempts have been
     /// exhausted.
     pub fn is_terminated(&self) -> bool {
-        self.inner.state() == State::Closed
+        matches!(self.inner.state(), State::Closed | State::Outdated)
             || (self.inner.is_error() && self.tries >= Self::MAX_TRIES)
     }
 
+    /// Returns whether the server rejected the connection because the client is outdated.
+    pub fn is_outdated(&self) -> bool {
+        self.inner.state() == State::Outdated
+    }
+
     /// Takes the current time, and returns a collection of updates to apply to the current
     /// state. Will automatically reconnect and clear state if/when the underlying connection is new.
     ///
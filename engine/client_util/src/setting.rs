@@ -2,7 +2,6 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::browser_storage::BrowserStorages;
-use crate::js_util::is_mobile;
 use core_protocol::id::{ArenaId, CohortId, LanguageId, ServerId, SessionId};
 use core_protocol::name::PlayerAlias;
 use core_protocol::web_socket::WebSocketProtocol;
@@ -43,8 +42,6 @@ pub struct CommonSettings {
     /// pull BigUint64Array into the JS shim, breaking compatibility with old devices.
     #[setting(optional)]
     pub session_id: Option<SessionId>,
-    /// Whether to set antialias rendering option.
-    pub antialias: bool,
     /// Websocket protocol.
     #[setting(volatile)]
     pub protocol: WebSocketProtocol,
@@ -53,6 +50,9 @@ pub struct CommonSettings {
     pub chat_message: String,
     /// Whether to add a contrasting border behind UI elements.
     pub high_contrast: bool,
+    /// Multiplies the size of the UI (see `yew_frontend::theme::Theme`).
+    #[setting(range = "0.5..2.0", finite)]
+    pub ui_scale: f32,
     /// Whether team menu is open.
     #[setting(volatile)]
     pub team_dialog_shown: bool,
@@ -73,10 +73,10 @@ impl Default for CommonSettings {
             server_id: None,
             arena_id: None,
             session_id: None,
-            antialias: !is_mobile(),
             protocol: WebSocketProtocol::default(),
             chat_message: String::new(),
             high_contrast: false,
+            ui_scale: 1.0,
             team_dialog_shown: true,
             chat_dialog_shown: true,
             leaderboard_dialog_shown: true,
@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! [`ProtoWebSocket`] backed by tokio-tungstenite, for clients that run natively.
+
+use crate::web_socket::State;
+use core_protocol::web_socket::{WebSocketProtocol, OUTDATED_CLOSE_CODE};
+use futures::{SinkExt, StreamExt};
+use log::{error, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Shared with the task that runs the connection.
+struct Shared {
+    state: State,
+    /// Received, but not yet decoded, messages.
+    inbound_buffer: Vec<Message>,
+}
+
+/// Websocket that obeys a protocol consisting of an inbound and outbound message.
+pub struct ProtoWebSocket<I, O> {
+    shared: Arc<Mutex<Shared>>,
+    /// Messages wait here until the connection is open.
+    outbound: UnboundedSender<Message>,
+    protocol: WebSocketProtocol,
+    _spooky: PhantomData<(I, O)>,
+}
+
+impl<I, O> ProtoWebSocket<I, O>
+where
+    I: 'static + DeserializeOwned,
+    O: 'static + Serialize,
+{
+    /// Opens a new websocket. Must be called from within a tokio runtime.
+    pub fn new(host: &str, protocol: WebSocketProtocol) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            state: State::Opening,
+            inbound_buffer: Vec::new(),
+        }));
+        let (outbound, outbound_receiver) = unbounded_channel();
+
+        tokio::spawn(run(host.to_owned(), Arc::clone(&shared), outbound_receiver));
+
+        Self {
+            shared,
+            outbound,
+            protocol,
+            _spooky: PhantomData,
+        }
+    }
+
+    /// Gets current (cached) websocket state.
+    pub fn state(&self) -> State {
+        self.shared.lock().unwrap().state
+    }
+
+    /// Returns whether closed for any reason (error or not).
+    pub fn is_closed(&self) -> bool {
+        matches!(self.state(), State::Closed | State::Error | State::Outdated)
+    }
+
+    /// Returns whether closed in error.
+    pub fn is_error(&self) -> bool {
+        matches!(self.state(), State::Error)
+    }
+
+    /// Returns whether socket is open.
+    pub fn is_open(&self) -> bool {
+        matches!(self.state(), State::Open)
+    }
+
+    /// Returns whether `receive_updates` would return a non-empty `Vec`.
+    pub fn has_updates(&self) -> bool {
+        !self.shared.lock().unwrap().inbound_buffer.is_empty()
+    }
+
+    /// Gets buffered updates.
+    pub fn receive_updates(&mut self) -> Vec<I> {
+        let mut shared = self.shared.lock().unwrap();
+        let mut updates = Vec::with_capacity(shared.inbound_buffer.len());
+
+        for message in std::mem::take(&mut shared.inbound_buffer) {
+            let result = match message {
+                Message::Binary(bytes) => bincode::deserialize(&bytes).map_err(|e| e.to_string()),
+                #[cfg(feature = "json")]
+                Message::Text(text) => serde_json::from_str(&text).map_err(|e| e.to_string()),
+                _ => Err(String::from("unsupported message")),
+            };
+
+            match result {
+                Ok(update) => updates.push(update),
+                Err(e) => {
+                    error!("error decoding websocket data: {}", e);
+                    // Mark as closed without actually closing, like the browser implementation.
+                    shared.state = State::Closed;
+                    break;
+                }
+            }
+        }
+
+        updates
+    }
+
+    /// Send a message or buffer it if the websocket is still opening.
+    pub fn send(&mut self, msg: O) {
+        match self.state() {
+            State::Opening | State::Open => {
+                let message = match self.protocol {
+                    WebSocketProtocol::Binary => Message::Binary(bincode::serialize(&msg).unwrap()),
+                    #[cfg(feature = "json")]
+                    WebSocketProtocol::Json => Message::Text(serde_json::to_string(&msg).unwrap()),
+                };
+                // If the connection just ended, the message is dropped, as it would be otherwise.
+                let _ = self.outbound.send(message);
+            }
+            _ => warn!("cannot send on closed websocket."),
+        }
+    }
+}
+
+impl<I, O> ProtoWebSocket<I, O> {
+    pub fn protocol(&mut self) -> WebSocketProtocol {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: WebSocketProtocol) {
+        self.protocol = protocol;
+    }
+
+    pub fn close(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.state {
+            State::Opening | State::Open => {
+                shared.state = State::Closed;
+                let _ = self.outbound.send(Message::Close(None));
+            }
+            _ => warn!("cannot close closed websocket."),
+        }
+    }
+}
+
+/// Connects to `host`, and then relays messages between the connection and a [`ProtoWebSocket`]
+/// until either closes.
+async fn run(host: String, shared: Arc<Mutex<Shared>>, mut outbound: UnboundedReceiver<Message>) {
+    // Closing (or being closed) takes precedence over errors.
+    let error = || {
+        let state = &mut shared.lock().unwrap().state;
+        if matches!(*state, State::Opening | State::Open) {
+            *state = State::Error;
+        }
+    };
+
+    let (mut sink, mut stream) = match tokio_tungstenite::connect_async(&host).await {
+        Ok((web_socket, _)) => web_socket.split(),
+        Err(e) => {
+            warn!("error opening websocket: {}", e);
+            error();
+            return;
+        }
+    };
+
+    {
+        let state = &mut shared.lock().unwrap().state;
+        if *state == State::Opening {
+            *state = State::Open;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            inbound = stream.next() => match inbound {
+                Some(Ok(Message::Close(frame))) => {
+                    let state = &mut shared.lock().unwrap().state;
+                    match frame.map(|frame| u16::from(frame.code)) {
+                        // Reconnecting won't help.
+                        Some(OUTDATED_CLOSE_CODE) => *state = State::Outdated,
+                        // Normal closure.
+                        Some(1000) => {
+                            if *state != State::Error {
+                                *state = State::Closed;
+                            }
+                        }
+                        // Abnormal closure.
+                        _ => *state = State::Error,
+                    }
+                    break;
+                }
+                Some(Ok(message @ (Message::Binary(_) | Message::Text(_)))) => {
+                    shared.lock().unwrap().inbound_buffer.push(message);
+                }
+                // Pings are answered automatically.
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => {
+                    error();
+                    break;
+                }
+            },
+            message = outbound.recv() => match message {
+                Some(message) => {
+                    let close = matches!(message, Message::Close(_));
+                    if let Err(e) = sink.send(message).await {
+                        warn!("error sending on websocket: {}", e);
+                        error();
+                        break;
+                    }
+                    if close {
+                        break;
+                    }
+                }
+                // The ProtoWebSocket was dropped.
+                None => {
+                    let _ = sink.close().await;
+                    break;
+                }
+            }
+        }
+    }
+}
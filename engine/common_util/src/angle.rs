@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use core_protocol::serde_util::{F32Visitor, I16Visitor};
-use glam::{Vec2, Vec2Swizzles};
+use glam::Vec2;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::f32::consts::PI;
 use std::fmt;
@@ -28,9 +28,44 @@ impl Angle {
         Self::from_radians(y.atan2(x))
     }
 
-    #[inline]
+    /// Unit vector pointing in the direction of the angle.
+    ///
+    /// Uses only basic arithmetic (unlike [`f32::sin_cos`], which may be implemented differently
+    /// on different platforms), so that physics based on it is deterministic, e.g. identical on
+    /// native servers and WebAssembly clients.
+    #[allow(clippy::excessive_precision)]
     pub fn to_vec(self) -> Vec2 {
-        Vec2::from(self.to_radians().sin_cos()).yx()
+        use std::f64::consts::{FRAC_2_PI, FRAC_PI_2, PI};
+
+        // Minimax polynomial coefficients for [-PI/4, PI/4], from musl's sinf and cosf.
+        const S: [f64; 4] = [
+            -0.166666666416265235595,
+            0.0083333293858894631756,
+            -0.000198393348360966317347,
+            0.0000027183114939898219064,
+        ];
+        const C: [f64; 4] = [
+            -0.499999997251031003120,
+            0.0416666233237390631894,
+            -0.00138867637746099294692,
+            0.0000243904487962774090654,
+        ];
+
+        // Reduce to [-PI/4, PI/4], keeping track of the quadrant.
+        let x = self.0 as f64 * (PI / Self::PI.0 as f64);
+        let quadrant = (x * FRAC_2_PI).round();
+        let r = x - quadrant * FRAC_PI_2;
+        let r2 = r * r;
+        let sin = r + r * r2 * (S[0] + r2 * (S[1] + r2 * (S[2] + r2 * S[3])));
+        let cos = 1.0 + r2 * (C[0] + r2 * (C[1] + r2 * (C[2] + r2 * C[3])));
+
+        let (sin, cos) = match quadrant as i32 & 3 {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        };
+        Vec2::new(cos as f32, sin as f32)
     }
 
     #[deprecated]
@@ -216,7 +251,7 @@ impl<'de> Deserialize<'de> for Angle {
 
 #[cfg(test)]
 mod tests {
-    use crate::angle::Angle;
+    use crate::angle::{Angle, AngleRepr};
 
     #[test]
     fn radians() {
@@ -229,6 +264,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn to_vec() {
+        for i in AngleRepr::MIN..=AngleRepr::MAX {
+            let a = Angle(i);
+            let radians = i as f64 * (std::f64::consts::PI / Angle::PI.0 as f64);
+            let v = a.to_vec();
+            let error = (v.x as f64 - radians.cos()).abs() + (v.y as f64 - radians.sin()).abs();
+            assert!(error < 1e-7, "{:?} -> {}", a, v);
+        }
+    }
+
     #[test]
     fn serde() {
         for i in -1000..1000 {
@@ -102,17 +102,43 @@ pub struct MessageDto {
     pub date_sent: UnixTime,
     /// For muting sender. None if from server.
     pub player_id: Option<PlayerId>,
+    /// Alias of the only recipient, if the message was whispered to a single player.
+    pub recipient: Option<PlayerAlias>,
     pub team_captain: bool,
     /// Don't use team_id in case team is deleted or ID re-used.
     pub team_name: Option<TeamName>,
     pub text: String,
+    /// Whether the sender linked their Discord account.
+    pub verified: bool,
     /// Whether message is directed to team only.
     pub whisper: bool,
 }
+/// A milestone a player can reach, at most once per session.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Sink another player.
+    FirstBlood,
+    /// Sink a player at least two levels higher.
+    GiantSlayer,
+    /// Stay alive for 30 minutes.
+    Survivor,
+    /// Travel a long distance in total.
+    Voyager,
+}
+
+/// Positive feedback one player can give another after a battle.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Commendation {
+    GoodTeammate,
+    HonorableFight,
+}
+
 /// The Player Data Transfer Object (DTO) binds player ID to player data.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PlayerDto {
     pub alias: PlayerAlias,
+    /// Total commendations received, across sessions.
+    pub commendations: u32,
     pub moderator: bool,
     pub player_id: PlayerId,
     pub team_captain: bool,
@@ -127,6 +153,8 @@ pub struct ServerDto {
     pub server_id: ServerId,
     pub region_id: RegionId,
     pub player_count: u32,
+    /// Tiny picture of the arena, as a PNG data URL.
+    pub thumbnail: Option<Owned<str>>,
 }
 
 impl PartialOrd for ServerDto {
@@ -175,6 +203,19 @@ pub struct TeamDto {
     pub closed: bool,
 }
 
+/// The Tournament Data Transfer Object (DTO) describes a scheduled or running tournament.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TournamentDto {
+    pub start: UnixTime,
+    pub rounds: u8,
+    /// Length of each round, in minutes.
+    pub round_minutes: u32,
+    /// Round in progress (starting at zero), once started.
+    pub round: Option<u8>,
+    /// Players who opted in, or once started, who are yet to be eliminated.
+    pub participants: u32,
+}
+
 /// Filter daily metrics.
 // TODO: Not a DTO?
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -183,6 +224,7 @@ pub enum MetricFilter {
     Referrer(Referrer),
     RegionId(RegionId),
     UserAgentId(UserAgentId),
+    VariantId(VariantId),
 }
 
 #[cfg(feature = "admin")]
@@ -199,6 +241,8 @@ mod admin {
     pub struct AdminPlayerDto {
         pub alias: PlayerAlias,
         pub player_id: PlayerId,
+        /// Which of the server's arenas the player is in.
+        pub arena_id: ArenaId,
         pub team_id: Option<TeamId>,
         pub region_id: Option<RegionId>,
         pub discord_id: Option<NonZeroU64>,
@@ -215,6 +259,70 @@ mod admin {
         pub mute: usize,
         /// Remaining minutes restricted.
         pub restriction: usize,
+        /// Remaining minutes shadow muted.
+        pub shadow_mute: usize,
+    }
+
+    /// An entry in an arena's event log, for investigating incidents after the fact.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct ArenaEventDto {
+        /// Which of the server's arenas the event happened in.
+        pub arena_id: ArenaId,
+        pub date_created: UnixTime,
+        pub event: ArenaEvent,
+    }
+
+    /// What happened, for the purposes of an [`ArenaEventDto`].
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub enum ArenaEvent {
+        /// A real player commended another.
+        Commended {
+            commender_id: PlayerId,
+            player_id: PlayerId,
+            commendation: Commendation,
+        },
+        /// A real player joined the game.
+        Joined {
+            player_id: PlayerId,
+            alias: PlayerAlias,
+        },
+        /// A real player left the game.
+        Left {
+            player_id: PlayerId,
+            alias: PlayerAlias,
+        },
+        /// A moderator (or admin) took action.
+        Moderated {
+            /// [`None`] if the action was taken via the admin interface.
+            moderator_id: Option<PlayerId>,
+            /// [`None`] if the action affected all players (e.g. slow mode).
+            player_id: Option<PlayerId>,
+            action: String,
+        },
+        /// A ranked season ended.
+        SeasonEnded {
+            season: u32,
+            /// Top players, in order.
+            champions: Vec<PlayerAlias>,
+        },
+        /// Game specific event, such as a sinking or a capture.
+        Game {
+            player_id: Option<PlayerId>,
+            text: String,
+        },
+    }
+
+    /// A player reporting another for abusive chat.
+    #[derive(Clone, Debug, PartialEq, Serialize)]
+    pub struct ReportDto {
+        pub date_created: UnixTime,
+        pub reporter_id: PlayerId,
+        pub player_id: PlayerId,
+        pub alias: PlayerAlias,
+        /// Recent messages sent by the reported player, for context.
+        pub messages: Box<[String]>,
+        /// Whether this report caused the reported player to be automatically restricted.
+        pub automuted: bool,
     }
 
     /// Like [`ServerDto`] but more details.
@@ -246,6 +354,35 @@ mod admin {
         }
     }
 
+    /// An A/B test, comparing variants of the game's configuration (e.g. balance).
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct ExperimentDto {
+        pub name: String,
+        /// If true, each player is assigned a variant based on their [`CohortId`]. Otherwise, each
+        /// arena is assigned a variant based on its [`ServerId`], and applies its configuration.
+        pub by_cohort: bool,
+        /// Indexed by [`VariantId`].
+        pub variants: Box<[ExperimentVariantDto]>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct ExperimentVariantDto {
+        pub name: String,
+        /// Passed to the game, as if by [`crate::rpc::AdminRequest::SetEntityOverrides`]. Only
+        /// possible when assigning variants to arenas.
+        pub entity_overrides: Option<String>,
+    }
+
+    /// The current experiment, with metrics summarized by variant.
+    #[derive(Clone, Debug, Serialize)]
+    pub struct ExperimentReportDto {
+        pub experiment: ExperimentDto,
+        pub started: UnixTime,
+        /// Which variant this arena was assigned, if any.
+        pub arena_variant_id: Option<VariantId>,
+        pub variants: Box<[(VariantId, MetricsSummaryDto)]>,
+    }
+
     /// The Metrics Data Transfer Object (DTO) contains core server metrics.
     #[derive(Clone, Copy, Debug, Serialize)]
     pub struct MetricsSummaryDto {
@@ -143,7 +143,7 @@ impl_wrapper_from_str!(InvitationId, NonZeroU32);
 // TraditionalChinese,
 
 /// In order that they should be presented in a language picker.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, EnumIter, EnumString, Display)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, EnumIter, EnumString, Display)]
 pub enum LanguageId {
     #[strum(serialize = "en")]
     English,
@@ -175,6 +175,11 @@ impl LanguageId {
     pub fn iter() -> impl Iterator<Item = Self> + 'static {
         <Self as IntoEnumIterator>::iter()
     }
+
+    /// Whether the language is written right-to-left, so the UI should be mirrored.
+    pub fn is_right_to_left(self) -> bool {
+        matches!(self, Self::Arabic)
+    }
 }
 
 impl Default for LanguageId {
@@ -399,6 +404,11 @@ impl UserAgentId {
     }
 }
 
+/// Index of a variant of the current experiment.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct VariantId(pub u8);
+impl_wrapper_from_str!(VariantId, u8);
+
 // This will supersede [`PlayerId`] for persistent storage.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct UserId(pub NonZeroU64);
@@ -407,6 +417,35 @@ pub struct UserId(pub NonZeroU64);
 pub enum LoginType {
     /// Discord OAuth2.
     Discord,
+    /// Google OAuth2 (OpenID Connect).
+    Google,
+    /// Sign in with Apple.
+    Apple,
+}
+
+impl LoginType {
+    /// Round-trips through [`FromStr`], e.g. as the prefix of the OAuth2 `state` parameter, which
+    /// identifies the provider that redirected back to the game.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Discord => "discord",
+            Self::Google => "google",
+            Self::Apple => "apple",
+        }
+    }
+}
+
+impl FromStr for LoginType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "discord" => Self::Discord,
+            "google" => Self::Google,
+            "apple" => Self::Apple,
+            _ => return Err("invalid login type"),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -14,6 +14,7 @@ pub mod metrics;
 pub mod name;
 pub mod rpc;
 pub mod serde_util;
+pub mod translation;
 pub mod web_socket;
 
 pub type UnixTime = u64;
@@ -8,6 +8,10 @@ use std::convert::Infallible;
 use std::str::FromStr;
 use std::sync::LazyLock;
 
+/// A game-specific class that players can achieve scores in, such as a type of ship, with its own
+/// leaderboards.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct ClassName(ArrayString<16>);
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct PlayerAlias(ArrayString<12>);
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
@@ -84,11 +88,13 @@ macro_rules! impl_from_str {
     };
 }
 
+impl_str!(ClassName);
 impl_str!(PlayerAlias);
 impl_str!(Referrer);
 // impl_str!(SurveyDetail);
 impl_str!(TeamName);
 
+impl_from_str!(ClassName);
 impl_from_str!(PlayerAlias);
 impl_from_str!(TeamName);
 
@@ -53,6 +53,9 @@ pub struct SystemQuery {
 pub struct SystemResponse {
     /// The [`ServerId`] matching the invitation, or closest to the client.
     pub server_id: Option<ServerId>,
+    /// All available servers, without thumbnails, in ascending order of [`ServerId`].
+    #[cfg_attr(feature = "client", serde(default))]
+    pub servers: Owned<[ServerDto]>,
 }
 
 actix_response!(SystemResponse);
@@ -90,6 +93,10 @@ pub struct StatusResponse {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub player_count: Option<u32>,
+    /// Tiny picture of the arena, as a PNG data URL.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
     /// Dying servers, in need of DNS replacement, according to this server.
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -119,9 +126,26 @@ pub struct WebSocketQuery {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub login_type: Option<LoginType>,
+    /// Nonce of the OAuth2 `state` the provider redirected back with, which must match the
+    /// cookie set when redirecting to the provider.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login_state: Option<String>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub referrer: Option<Referrer>,
+    /// Connect as an observer, which sees the whole arena but can't play (requires admin auth).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observer_auth: Option<String>,
+    /// Continue playing with progress from another server (see [`ClientRequest::Transfer`]).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer: Option<String>,
+    /// See [`PROTOCOL_VERSION`][crate::web_socket::PROTOCOL_VERSION]. Absent means 0.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
 }
 
 /// Client to server request.
@@ -131,6 +155,7 @@ pub enum Request<GR> {
     Client(ClientRequest),
     Game(GR),
     Invitation(InvitationRequest),
+    Leaderboard(LeaderboardRequest),
     Player(PlayerRequest),
     Team(TeamRequest),
 }
@@ -148,6 +173,7 @@ where
 #[cfg_attr(feature = "server", derive(actix::Message))]
 #[cfg_attr(feature = "server", rtype(result = "()"))]
 pub enum Update<GU> {
+    Achievement(AchievementUpdate),
     Chat(ChatUpdate),
     Client(ClientUpdate),
     Game(GU),
@@ -196,6 +222,9 @@ pub enum TeamUpdate {
 pub enum ChatRequest {
     /// Avoid seeing this player's messages.
     Mute(PlayerId),
+    /// Report this player for abusive chat (also mutes them). Players reported by enough others
+    /// are automatically restricted.
+    Report(PlayerId),
     /// For moderators only.
     RestrictPlayer { player_id: PlayerId, minutes: u32 },
     /// Send a chat message.
@@ -210,6 +239,11 @@ pub enum ChatRequest {
     SetSlowMode(u32),
     /// Resume seeing this player's messages.
     Unmute(PlayerId),
+    /// Send a chat message that is only visible to a single player (and the sender).
+    Whisper {
+        player_id: PlayerId,
+        message: String,
+    },
 }
 
 /// Chat related update from server to client.
@@ -218,21 +252,36 @@ pub enum ChatUpdate {
     Muted(PlayerId),
     PlayerRestricted { player_id: PlayerId, minutes: u32 },
     Received(Box<[Dedup<MessageDto>]>),
+    Reported(PlayerId),
     SafeModeSet(u32),
     SlowModeSet(u32),
     Sent,
     Unmuted(PlayerId),
 }
 
+/// Achievement related update from server to client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AchievementUpdate {
+    /// Newly unlocked, in order.
+    Unlocked(Box<[Achievement]>),
+}
+
 /// Player related request from client to server.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PlayerRequest {
+    /// Commend another player, at most once per session.
+    Commend {
+        player_id: PlayerId,
+        commendation: Commendation,
+    },
+    /// Equivalent to [`ChatRequest::Report`], for older clients.
     Report(PlayerId),
 }
 
 /// Player related update from server to client.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PlayerUpdate {
+    Commended(PlayerId),
     Reported(PlayerId),
     Updated {
         added: Owned<[PlayerDto]>,
@@ -241,11 +290,27 @@ pub enum PlayerUpdate {
     },
 }
 
+/// Leaderboard related request from client to server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LeaderboardRequest {
+    /// Request the leaderboard of a particular class, which, unlike the overall leaderboards,
+    /// isn't sent unless requested.
+    Class(ClassName, PeriodId),
+    /// Request the final standings of the most recent tournament.
+    Tournament,
+}
+
 /// Leaderboard related update from server to client.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LeaderboardUpdate {
     // The leaderboard contains high score players, but not teams, for prior periods.
     Updated(PeriodId, Owned<[LeaderboardDto]>),
+    /// The leaderboard of a particular class, in response to [`LeaderboardRequest::Class`]. May be
+    /// empty if it wasn't loaded yet.
+    Class(ClassName, PeriodId, Owned<[LeaderboardDto]>),
+    /// Final standings of the most recent tournament, best first, in response to
+    /// [`LeaderboardRequest::Tournament`]. Empty if there hasn't been one.
+    Tournament(Owned<[LeaderboardDto]>),
 }
 
 /// Liveboard related update from server to client.
@@ -273,6 +338,13 @@ pub enum InvitationUpdate {
 /// General request from client to server.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ClientRequest {
+    /// The client's view of the world seems wrong (e.g. a "ghost ship"). The server stores this,
+    /// along with recent snapshots of the world, for offline diffing.
+    ReportDesync {
+        message: String,
+        /// The client's view of the world, in a game-specific format.
+        state: String,
+    },
     SetAlias(PlayerAlias),
     /// An advertisement was shown or played.
     TallyAd(AdType),
@@ -280,6 +352,19 @@ pub enum ClientRequest {
     Trace {
         message: String,
     },
+    /// Switch to another server, keeping progress (e.g. score). See [`ClientUpdate::Transferred`].
+    Transfer(ServerId),
+    // New variants go last, so the bincode of existing ones stays the same.
+    /// Create a private arena on this server, which can only be joined by invitation, and closes
+    /// once empty. See [`ClientUpdate::ArenaCreated`].
+    CreateArena {
+        /// Whether the arena has bots.
+        bots: bool,
+        /// Game-specific options (e.g. world size). Unsupported options are ignored.
+        options: Vec<(String, String)>,
+    },
+    /// Opt in to the scheduled tournament. See [`ClientUpdate::TournamentStarted`].
+    JoinTournament,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -294,16 +379,36 @@ pub enum AdType {
 pub enum ClientUpdate {
     AdTallied,
     AliasSet(PlayerAlias),
+    DesyncReported,
     EvalSnippet(Owned<str>),
     FpsTallied,
+    /// Round trip time in milliseconds, as measured by the server.
+    RttMeasured(u16),
     SessionCreated {
         arena_id: ArenaId,
         cohort_id: CohortId,
+        /// Provider the player signed in with, if any.
+        login_type: Option<LoginType>,
         server_id: Option<ServerId>,
         session_id: SessionId,
         player_id: PlayerId,
     },
     Traced,
+    /// Connect to the given server with [`WebSocketQuery::transfer`] set to the token, to continue
+    /// playing there.
+    Transferred {
+        server_id: ServerId,
+        token: String,
+    },
+    // New variants go last, so the bincode of existing ones stays the same.
+    /// Connect with [`WebSocketQuery::invitation_id`] set to this, to join the private arena
+    /// that was created. Others may join with it too.
+    ArenaCreated(InvitationId),
+    /// Opted in to the tournament, which starts at the given time.
+    TournamentJoined(TournamentDto),
+    /// The tournament the player opted in to started. Connect with
+    /// [`WebSocketQuery::invitation_id`] set to this, to play in it.
+    TournamentStarted(InvitationId),
 }
 
 /// General update from server to client.
@@ -322,10 +427,37 @@ mod admin {
     /// Admin requests are from the admin interface to the core service.
     #[derive(Clone, Debug, Deserialize, Serialize)]
     pub enum AdminRequest {
+        /// Start or stop comparing what a real player is sent against what they should be able to
+        /// see. See [`AdminRequest::RequestVisibilityAudit`].
+        AuditVisibility {
+            player_id: PlayerId,
+            enabled: bool,
+        },
+        /// Caps how many game commands a given real player may send per second, such as if they are
+        /// suspected of using automated inputs. Zero removes the cap.
+        CapPlayerCommands {
+            player_id: PlayerId,
+            per_second: u32,
+        },
+        /// Cancels the scheduled or running tournament, if any.
+        CancelTournament,
         ClearSnippet {
             cohort_id: Option<CohortId>,
             referrer: Option<Referrer>,
         },
+        /// Removes an entity from the world, by its game-specific id.
+        DeleteEntity {
+            /// [`None`] means the primary arena.
+            arena_id: Option<ArenaId>,
+            entity_id: u32,
+        },
+        /// Disconnects a given real player. If `ban_minutes` is nonzero, their session (and, if
+        /// `ban_ip`, their IP address) can't reconnect for that long.
+        KickPlayer {
+            player_id: PlayerId,
+            ban_minutes: usize,
+            ban_ip: bool,
+        },
         MutePlayer {
             player_id: PlayerId,
             minutes: usize,
@@ -345,12 +477,36 @@ mod admin {
             filter: Option<MetricFilter>,
         },
         RequestDistributeLoad,
+        /// Entities within a radius of a position, in a game-specific format.
+        RequestEntities {
+            /// [`None`] means the primary arena.
+            arena_id: Option<ArenaId>,
+            x: f32,
+            y: f32,
+            radius: f32,
+        },
+        /// See [`AdminRequest::SetExperiment`].
+        RequestExperiment,
+        /// Events that happened within a period (inclusive), oldest first.
+        RequestEvents {
+            period_start: Option<crate::UnixTime>,
+            period_stop: Option<crate::UnixTime>,
+        },
         RequestGames,
+        /// Options that may be changed at runtime. See [`AdminRequest::SetOption`].
+        RequestOptions,
+        /// A player's full state (e.g. their boat, its reloads, and their recent commands), in a
+        /// game-specific format.
+        RequestPlayerState {
+            player_id: PlayerId,
+        },
         RequestPlayers,
         RequestProfile,
         RequestRedirect,
         RequestReferrers,
         RequestRegions,
+        /// Recent player reports, oldest first.
+        RequestReports,
         RequestSeries {
             game_id: GameId,
             filter: Option<MetricFilter>,
@@ -367,10 +523,24 @@ mod admin {
             filter: Option<MetricFilter>,
         },
         RequestUserAgents,
+        /// The scheduled or running tournament, if any.
+        RequestTournament,
+        /// Discrepancies found since the last request, oldest first.
+        RequestVisibilityAudit {
+            player_id: PlayerId,
+        },
         RestrictPlayer {
             player_id: PlayerId,
             minutes: usize,
         },
+        /// Schedules an elimination tournament, replacing any scheduled (but not running) one.
+        /// Players opt in until it starts, and are then moved to a dedicated arena, where only they
+        /// can play. After each round, the lower scoring half is eliminated.
+        ScheduleTournament {
+            start: crate::UnixTime,
+            rounds: u8,
+            round_minutes: u32,
+        },
         SendChat {
             // If None, goes to all players.
             player_id: Option<PlayerId>,
@@ -379,13 +549,38 @@ mod admin {
         },
         SetAllowWebSocketJson(bool),
         SetDistributeLoad(bool),
+        /// Changes game balance at runtime. The format of the patch is up to the game, except that
+        /// an empty patch restores the default balance.
+        SetEntityOverrides(String),
+        /// Starts an experiment, replacing any existing experiment, or stops it (if [`None`]).
+        SetExperiment(Option<ExperimentDto>),
         SetGameClient(minicdn::EmbeddedMiniCdn),
+        /// Changes a server option (e.g. `min_bots`) at runtime, without a restart. Game-specific
+        /// arena options are prefixed with `arena.`.
+        SetOption {
+            key: String,
+            value: String,
+        },
         SetRedirect(Option<ServerId>),
         SetSnippet {
             cohort_id: Option<CohortId>,
             referrer: Option<Referrer>,
             snippet: Owned<str>,
         },
+        /// Like [`AdminRequest::MutePlayer`], except that the player still sees their own
+        /// messages, so they don't know they are muted.
+        ShadowMutePlayer {
+            player_id: PlayerId,
+            minutes: usize,
+        },
+        /// Moves an entity, by its game-specific id, to a position.
+        TeleportEntity {
+            /// [`None`] means the primary arena.
+            arena_id: Option<ArenaId>,
+            entity_id: u32,
+            x: f32,
+            y: f32,
+        },
     }
 
     /// Admin related responses from the server.
@@ -398,19 +593,37 @@ mod admin {
         DayRequested(Owned<[(crate::UnixTime, MetricsDataPointDto)]>),
         DistributeLoadRequested(bool),
         DistributeLoadSet(bool),
+        EntitiesRequested(Box<[String]>),
+        EntityDeleted,
+        EntityOverridesSet,
+        EntityTeleported,
+        EventsRequested(Box<[ArenaEventDto]>),
+        ExperimentRequested(Option<ExperimentReportDto>),
+        /// Contains which variant this arena was assigned, if any.
+        ExperimentSet(Option<VariantId>),
         GameClientSet(u64),
         GamesRequested(Box<[(GameId, f32)]>),
         HttpServerRestarting,
+        OptionSet {
+            key: String,
+            value: String,
+        },
+        OptionsRequested(Box<[(String, String)]>),
         PlayerAliasOverridden(PlayerAlias),
+        PlayerCommandsCapped(u32),
+        PlayerKicked,
         PlayerModeratorOverridden(bool),
         PlayerMuted(usize),
         PlayerRestricted(usize),
+        PlayerShadowMuted(usize),
+        PlayerStateRequested(String),
         PlayersRequested(Box<[AdminPlayerDto]>),
         ProfileRequested(String),
         RedirectRequested(Option<ServerId>),
         RedirectSet(Option<ServerId>),
         ReferrersRequested(Box<[(Referrer, f32)]>),
         RegionsRequested(Box<[(RegionId, f32)]>),
+        ReportsRequested(Box<[ReportDto]>),
         SeriesRequested(Owned<[(crate::UnixTime, MetricsDataPointDto)]>),
         ServerIdRequested(Option<ServerId>),
         ServersRequested(Box<[AdminServerDto]>),
@@ -418,6 +631,11 @@ mod admin {
         SnippetSet,
         SnippetsRequested(Box<[SnippetDto]>),
         SummaryRequested(MetricsSummaryDto),
+        TournamentCancelled,
+        TournamentRequested(Option<TournamentDto>),
+        TournamentScheduled(TournamentDto),
         UserAgentsRequested(Box<[(UserAgentId, f32)]>),
+        VisibilityAuditRequested(Box<[String]>),
+        VisibilityAuditSet(bool),
     }
 }
@@ -0,0 +1,121 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Translation files, which let translators contribute without touching Rust code.
+//!
+//! They use a subset of [Fluent](https://projectfluent.org/) syntax, one file per language, named
+//! after its [`LanguageId`](crate::id::LanguageId) (e.g. `es.ftl`):
+//!
+//! ```ftl
+//! # Comment.
+//! chat_label = Charlar
+//! chat_send_team_message_hint =
+//!     Presiona Enter para enviar,
+//!     o Shift + Enter para enviar solo al equipo
+//! ```
+//!
+//! Placeables (`{ $name }`) aren't supported, so translations with arguments remain in Rust.
+
+/// A translation parsed from a translation file.
+#[derive(Debug, PartialEq)]
+pub struct Message<'a> {
+    /// Name of the translation method, e.g. `chat_label`.
+    pub key: &'a str,
+    pub value: String,
+    /// One-based line number of the key, for error messages.
+    pub line: usize,
+}
+
+/// Parses a translation file, returning an error message with a line number if it is invalid.
+pub fn parse_translations(src: &str) -> Result<Vec<Message<'_>>, String> {
+    let mut messages: Vec<Message> = Vec::new();
+    // Whether the previous line belongs to the last message, so indented lines continue it.
+    let mut continuing = false;
+
+    for (i, line) in src.lines().enumerate() {
+        let line_number = i + 1;
+        let err = |msg: &str| Err(format!("line {}: {}", line_number, msg));
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continuing = false;
+            continue;
+        }
+        if trimmed.contains(['{', '}']) {
+            return err("placeables are not supported");
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let message = match messages.last_mut().filter(|_| continuing) {
+                Some(message) => message,
+                None => return err("indented line doesn't continue a translation"),
+            };
+            if !message.value.is_empty() {
+                message.value.push('\n');
+            }
+            message.value.push_str(trimmed);
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return err("expected key = value"),
+        };
+        if !key.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return err("invalid key");
+        }
+        if messages.iter().any(|m| m.key == key) {
+            return err("duplicate key");
+        }
+
+        messages.push(Message {
+            key,
+            value: value.to_owned(),
+            line: line_number,
+        });
+        continuing = true;
+    }
+
+    if let Some(message) = messages.iter().find(|m| m.value.is_empty()) {
+        return Err(format!("line {}: empty translation", message.line));
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::translation::{parse_translations, Message};
+
+    #[test]
+    fn parse() {
+        let src = "# Comment.\nchat_label = Charlar\n\nhint =\n    Line one,\n    line two\n";
+        assert_eq!(
+            parse_translations(src),
+            Ok(vec![
+                Message {
+                    key: "chat_label",
+                    value: String::from("Charlar"),
+                    line: 2,
+                },
+                Message {
+                    key: "hint",
+                    value: String::from("Line one,\nline two"),
+                    line: 4,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse_translations("chat_label").is_err());
+        assert!(parse_translations("chat label = Chat").is_err());
+        assert!(parse_translations("a = 1\na = 2").is_err());
+        assert!(parse_translations("a =\n").is_err());
+        assert!(parse_translations("  orphan").is_err());
+        assert!(parse_translations("a = { $name }").is_err());
+    }
+}
@@ -20,3 +20,41 @@ impl Default for WebSocketProtocol {
         Self::Binary
     }
 }
+
+/// Version of the websocket protocol, i.e. the encoding of [`Request`] and [`Update`], which must
+/// be incremented whenever a change would break existing clients. Clients send theirs as
+/// [`WebSocketQuery::version`].
+///
+/// [`Request`]: crate::rpc::Request
+/// [`Update`]: crate::rpc::Update
+/// [`WebSocketQuery::version`]: crate::rpc::WebSocketQuery::version
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// The only other protocol version that is still supported, see [`is_supported_version`].
+pub const PREVIOUS_PROTOCOL_VERSION: u32 = PROTOCOL_VERSION - 1;
+
+/// Close code with which servers reject clients of unsupported protocol versions, which should
+/// refresh to load a compatible client. In the range reserved for applications.
+pub const OUTDATED_CLOSE_CODE: u16 = 4000;
+
+/// Whether a server can talk to a client of the given protocol version. Besides the current
+/// version, the previous one is supported, so that clients loaded before a rolling upgrade aren't
+/// disconnected. Servers translate to and from its layout. Clients that predate versioning are
+/// version 0.
+pub fn is_supported_version(version: u32) -> bool {
+    version == PROTOCOL_VERSION || version == PREVIOUS_PROTOCOL_VERSION
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::web_socket::{is_supported_version, PREVIOUS_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+    #[test]
+    fn supported_version() {
+        assert!(is_supported_version(PROTOCOL_VERSION));
+        assert!(is_supported_version(PREVIOUS_PROTOCOL_VERSION));
+        assert!(!is_supported_version(PROTOCOL_VERSION + 1));
+        assert!(!is_supported_version(PREVIOUS_PROTOCOL_VERSION - 1));
+        assert!(!is_supported_version(0));
+    }
+}
@@ -15,6 +15,7 @@ mod ply;
 pub(crate) mod settings;
 pub(crate) mod smol_routable;
 pub(crate) mod texture;
+mod translations;
 pub(crate) mod vertex;
 
 extern crate proc_macro;
@@ -61,6 +62,14 @@ pub fn include_textures(item: TokenStream) -> TokenStream {
     crate::texture::include_textures(item)
 }
 
+/// Lets translation files (see [`core_protocol::translation`]) in the given directory, relative to
+/// the current file, override an impl's static translations, checking that they exist at compile
+/// time. Expects `translated(self, key)` to be in scope.
+#[proc_macro_attribute]
+pub fn translations(attr: TokenStream, item: TokenStream) -> TokenStream {
+    crate::translations::translations(attr, item)
+}
+
 #[proc_macro_derive(Layer, attributes(alpha, depth, layer, render, stencil))]
 pub fn derive_layer(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as crate::layer::LayerInput);
@@ -0,0 +1,107 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use core_protocol::id::LanguageId;
+use core_protocol::translation::parse_translations;
+use litrs::StringLit;
+use proc_macro::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use syn::{FnArg, ImplItem, ItemImpl, ReturnType, Type};
+
+pub fn translations(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = attr.into_iter().collect::<Vec<_>>();
+    if input.len() != 1 {
+        let msg = format!("expected exactly one input token, got {}", input.len());
+        return quote! { compile_error!(#msg) }.into();
+    }
+
+    let dir_lit = match StringLit::try_from(&input[0]) {
+        Err(e) => return e.to_compile_error(),
+        Ok(lit) => lit,
+    };
+
+    let mut dir = proc_macro::Span::call_site().source_file().path();
+    dir.pop();
+    dir.push(Path::new(dir_lit.value()));
+
+    let mut item_impl: ItemImpl = match syn::parse(item) {
+        Err(e) => return e.to_compile_error().into(),
+        Ok(item_impl) => item_impl,
+    };
+
+    // Only static translations can be overridden, as translation files don't support arguments.
+    let mut keys = HashSet::new();
+    for impl_item in &mut item_impl.items {
+        let ImplItem::Method(method) = impl_item else {
+            continue;
+        };
+        let is_static = matches!(
+            method.sig.inputs.iter().collect::<Vec<_>>()[..],
+            [FnArg::Receiver(receiver)] if receiver.reference.is_none()
+        ) && matches!(
+            &method.sig.output,
+            ReturnType::Type(_, ty) if matches!(
+                &**ty,
+                Type::Reference(r) if r.mutability.is_none()
+                    && r.lifetime.as_ref().map_or(false, |l| l.ident == "static")
+                    && matches!(&*r.elem, Type::Path(p) if p.path.is_ident("str"))
+            )
+        );
+        if !is_static {
+            continue;
+        }
+
+        let key = method.sig.ident.to_string();
+        let block = &method.block;
+        method.block = syn::parse_quote! {{
+            if let Some(translated) = translated(self, #key) {
+                return translated;
+            }
+            #block
+        }};
+        keys.insert(key);
+    }
+
+    let read_err = format!("unable to read translations {}", dir.display());
+    for entry in fs::read_dir(&dir).expect(&read_err) {
+        let path = entry.expect(&read_err).path();
+        if path.extension().map_or(true, |e| e != "ftl") {
+            continue;
+        }
+        proc_macro::tracked_path::path(path.to_string_lossy());
+
+        // Keep the impl, so the error isn't buried under missing method errors.
+        let error = |msg: String| -> TokenStream {
+            let msg = format!("{}: {}", path.display(), msg);
+            quote! {
+                compile_error!(#msg);
+                #item_impl
+            }
+            .into()
+        };
+
+        let stem = path.file_stem().unwrap().to_string_lossy();
+        if LanguageId::from_str(&stem).is_err() {
+            return error(format!("unknown language {}", stem));
+        }
+
+        let load_err = format!("unable to load translations {}", path.display());
+        let src = fs::read_to_string(&path).expect(&load_err);
+        let messages = match parse_translations(&src) {
+            Err(e) => return error(e),
+            Ok(messages) => messages,
+        };
+        if let Some(message) = messages.iter().find(|m| !keys.contains(m.key)) {
+            return error(format!(
+                "line {}: unknown translation {}",
+                message.line, message.key
+            ));
+        }
+    }
+
+    quote!(#item_impl).into()
+}
@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game_service::GameArenaService;
+use crate::player::PlayerRepo;
+use core_protocol::dto::Achievement;
+use core_protocol::id::PlayerId;
+use core_protocol::rpc::AchievementUpdate;
+use std::time::Duration;
+
+/// Component of client data encompassing progress towards achievements.
+#[derive(Debug, Default)]
+pub struct ClientAchievementData {
+    /// Bit set of unlocked achievements, persisted with the session.
+    unlocked: u32,
+    /// Total distance travelled, towards [`Achievement::Voyager`] (in meters).
+    travelled: f32,
+    /// Achievements unlocked since the last update, which need to be sent to the client.
+    outbox: Vec<Achievement>,
+}
+
+impl ClientAchievementData {
+    /// Levels above the player's own that a sunken player needs to be for
+    /// [`Achievement::GiantSlayer`].
+    pub const GIANT_SLAYER_LEVELS: u8 = 2;
+    /// How long a player must stay alive for [`Achievement::Survivor`].
+    pub const SURVIVOR_DURATION: Duration = Duration::from_secs(30 * 60);
+    /// Total distance a player must travel for [`Achievement::Voyager`] (in meters).
+    pub const VOYAGER_METERS: f32 = 50_000.0;
+
+    /// Restores achievements unlocked earlier in the session (see [`Self::to_bits`]).
+    pub(crate) fn from_bits(unlocked: u32) -> Self {
+        Self {
+            unlocked,
+            ..Self::default()
+        }
+    }
+
+    /// Unlocked achievements, in a form suitable for persistence.
+    pub(crate) fn to_bits(&self) -> u32 {
+        self.unlocked
+    }
+
+    fn bit(achievement: Achievement) -> u32 {
+        1 << achievement as u32
+    }
+
+    /// Returns true iff the achievement was unlocked at any point in the session.
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked & Self::bit(achievement) != 0
+    }
+
+    /// Unlocks an achievement, queueing a notification for the client if it wasn't already
+    /// unlocked.
+    pub fn unlock(&mut self, achievement: Achievement) {
+        if !self.is_unlocked(achievement) {
+            self.unlocked |= Self::bit(achievement);
+            self.outbox.push(achievement);
+        }
+    }
+
+    /// Call when the player sinks another player. Levels are those of the game, e.g. boat levels.
+    pub fn sank(&mut self, level: u8, other_level: u8) {
+        self.unlock(Achievement::FirstBlood);
+        if other_level >= level.saturating_add(Self::GIANT_SLAYER_LEVELS) {
+            self.unlock(Achievement::GiantSlayer);
+        }
+    }
+
+    /// Call with the distance the player travelled since the last call (in meters).
+    pub fn travelled(&mut self, meters: f32) {
+        self.travelled += meters;
+        if self.travelled >= Self::VOYAGER_METERS {
+            self.unlock(Achievement::Voyager);
+        }
+    }
+
+    /// Call with how long the player has been alive.
+    pub(crate) fn survived(&mut self, alive: Duration) {
+        if alive >= Self::SURVIVOR_DURATION {
+            self.unlock(Achievement::Survivor);
+        }
+    }
+
+    /// Gets achievement update, consisting of newly unlocked achievements, for a player. Borrows
+    /// the player mutably.
+    pub(crate) fn player_delta<G: GameArenaService>(
+        player_id: PlayerId,
+        players: &PlayerRepo<G>,
+    ) -> Option<AchievementUpdate> {
+        let mut player = players.borrow_player_mut(player_id)?;
+        let achievements = &mut player.client_mut()?.achievements;
+        if achievements.outbox.is_empty() {
+            None
+        } else {
+            let unlocked = std::mem::take(&mut achievements.outbox);
+            Some(AchievementUpdate::Unlocked(unlocked.into_boxed_slice()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::achievement::ClientAchievementData;
+    use core_protocol::dto::Achievement;
+    use std::time::Duration;
+
+    const ALL: [Achievement; 4] = [
+        Achievement::FirstBlood,
+        Achievement::GiantSlayer,
+        Achievement::Survivor,
+        Achievement::Voyager,
+    ];
+
+    #[test]
+    fn unlock() {
+        let mut achievements = ClientAchievementData::default();
+
+        achievements.sank(3, 4);
+        assert!(achievements.is_unlocked(Achievement::FirstBlood));
+        assert!(!achievements.is_unlocked(Achievement::GiantSlayer));
+        achievements.sank(3, 5);
+        assert!(achievements.is_unlocked(Achievement::GiantSlayer));
+
+        achievements.survived(Duration::from_secs(60));
+        assert!(!achievements.is_unlocked(Achievement::Survivor));
+        achievements.survived(ClientAchievementData::SURVIVOR_DURATION);
+
+        for _ in 0..100 {
+            achievements.travelled(ClientAchievementData::VOYAGER_METERS / 100.0);
+        }
+
+        // Each achievement is only announced once.
+        assert_eq!(achievements.outbox, ALL, "{:?}", achievements);
+
+        let restored = ClientAchievementData::from_bits(achievements.to_bits());
+        assert!(ALL.into_iter().all(|a| restored.is_unlocked(a)));
+        assert!(restored.outbox.is_empty());
+    }
+}
@@ -1,18 +1,24 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::chat::Channel;
 use crate::client::ClientRepo;
 use crate::context::Context;
+use crate::context_service::ContextService;
+use crate::experiment::ExperimentRepo;
 use crate::game_service::GameArenaService;
 use crate::infrastructure::Infrastructure;
+use crate::log::EventLogRepo;
 use crate::metric::{Bundle, MetricBundle, MetricRepo};
 use crate::player::PlayerRepo;
 use crate::static_files::static_size_and_hash;
 use crate::status::StatusRepo;
 use crate::system::{ServerStatus, SystemRepo};
+use crate::transfer::Transfer;
 use actix::{fut, ActorFutureExt, Handler, Message, ResponseActFuture, WrapFuture};
 use core_protocol::dto::{
-    AdminPlayerDto, AdminServerDto, MessageDto, MetricFilter, MetricsDataPointDto, SnippetDto,
+    AdminPlayerDto, AdminServerDto, ArenaEvent, MessageDto, MetricFilter, MetricsDataPointDto,
+    SnippetDto,
 };
 use core_protocol::id::{CohortId, PlayerId, RegionId, ServerId, UserAgentId};
 use core_protocol::name::{PlayerAlias, Referrer};
@@ -22,6 +28,7 @@ use log::{error, info, warn};
 use minicdn::{EmbeddedMiniCdn, MiniCdn};
 use serde::{Deserialize, Serialize};
 use server_util::database_schema::Metrics;
+use server_util::rate_limiter::{RateLimiter, RateLimiterProps};
 use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -41,6 +48,8 @@ pub struct AdminRepo<G: GameArenaService> {
     allow_web_socket_json: &'static AtomicBool,
     /// Password for admin interface.
     password: Cow<'static, str>,
+    /// See [`Self::transfer_key`].
+    transfer_key: [u8; 32],
     /// Which server id to redirect to, if available.
     pub(crate) redirect_server_id_preference: Option<ServerId>,
     /// Route players to other available servers (bias towards emptier servers).
@@ -111,6 +120,7 @@ impl<G: GameArenaService> AdminRepo<G> {
             game_client,
             config_file,
             allow_web_socket_json,
+            transfer_key: Transfer::derive_key(config.password.as_bytes()),
             password: config.password,
             redirect_server_id_preference: ServerId::new(config.redirect_server_id_preference),
             distribute_load: config.distribute_load,
@@ -125,6 +135,17 @@ impl<G: GameArenaService> AdminRepo<G> {
         constant_time_eq::constant_time_eq(self.password.as_bytes(), request.auth.as_bytes())
     }
 
+    /// Observer connections (see [`crate::client::PlayerClientData::observer`]) are admin-scoped.
+    pub(crate) fn authenticate_observer(&self, auth: &str) -> bool {
+        constant_time_eq::constant_time_eq(self.password.as_bytes(), auth.as_bytes())
+    }
+
+    /// Key for signing transfers between servers (see [`Transfer`]). Servers of the same
+    /// deployment share the admin password, so the key is derived from it.
+    pub(crate) fn transfer_key(&self) -> &[u8] {
+        &self.transfer_key
+    }
+
     fn log_save_config_file(&self) {
         if let Err(e) = self.try_save_config_file() {
             error!("error saving admin config file: {}", e)
@@ -161,16 +182,27 @@ impl<G: GameArenaService> AdminRepo<G> {
         ))
     }
 
-    /// Get admin view of real players in the game.
-    fn request_players(&self, players: &PlayerRepo<G>) -> Result<AdminUpdate, &'static str> {
+    /// Get admin view of real players in all arenas.
+    fn request_players<'a>(
+        &self,
+        arenas: impl Iterator<Item = &'a ContextService<G>>,
+    ) -> Result<AdminUpdate, &'static str> {
         Ok(AdminUpdate::PlayersRequested(
-            players
-                .iter_borrow()
-                .filter_map(|player| {
+            arenas
+                .flat_map(|arena| {
+                    let arena_id = arena.context.arena_id;
+                    arena
+                        .context
+                        .players
+                        .iter_borrow()
+                        .map(move |player| (arena_id, player))
+                })
+                .filter_map(|(arena_id, player)| {
                     if let Some(client) = player.client().filter(|_| !player.is_out_of_game()) {
                         Some(AdminPlayerDto {
                             alias: client.alias,
                             player_id: player.player_id,
+                            arena_id,
                             team_id: player.team_id(),
                             region_id: client.metrics.region_id,
                             discord_id: client.discord_id,
@@ -185,6 +217,7 @@ impl<G: GameArenaService> AdminRepo<G> {
                             abuse_reports: client.chat.context.reports(),
                             mute: seconds_ceil(client.chat.context.muted_for()),
                             restriction: seconds_ceil(client.chat.context.restricted_for()),
+                            shadow_mute: seconds_ceil(client.chat.shadow_muted_for()),
                         })
                     } else {
                         None
@@ -200,6 +233,7 @@ impl<G: GameArenaService> AdminRepo<G> {
         player_id: PlayerId,
         alias: PlayerAlias,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<AdminUpdate, &'static str> {
         let mut player = players
             .borrow_player_mut(player_id)
@@ -207,7 +241,13 @@ impl<G: GameArenaService> AdminRepo<G> {
         let client = player.client_mut().ok_or("not a real player")?;
         // We still censor, in case of unauthorized admin access.
         let censored = PlayerAlias::new_sanitized(alias.as_str());
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("rename {} to {}", client.alias, censored),
+        });
         client.alias = censored;
+        client.alias_overridden = true;
         Ok(AdminUpdate::PlayerAliasOverridden(censored))
     }
 
@@ -232,6 +272,7 @@ impl<G: GameArenaService> AdminRepo<G> {
         player_id: PlayerId,
         minutes: usize,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<AdminUpdate, &'static str> {
         let mut player = players
             .borrow_player_mut(player_id)
@@ -241,6 +282,11 @@ impl<G: GameArenaService> AdminRepo<G> {
             .chat
             .context
             .mute_for(Duration::from_secs(minutes as u64 * 60));
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("mute {}m", minutes),
+        });
         Ok(AdminUpdate::PlayerMuted(seconds_ceil(
             client.chat.context.muted_for(),
         )))
@@ -253,6 +299,7 @@ impl<G: GameArenaService> AdminRepo<G> {
         player_id: PlayerId,
         minutes: usize,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<AdminUpdate, &'static str> {
         let mut player = players
             .borrow_player_mut(player_id)
@@ -262,11 +309,139 @@ impl<G: GameArenaService> AdminRepo<G> {
             .chat
             .context
             .restrict_for(Duration::from_secs(minutes as u64 * 60));
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("restrict {}m", minutes),
+        });
         Ok(AdminUpdate::PlayerRestricted(seconds_ceil(
             client.chat.context.restricted_for(),
         )))
     }
 
+    /// Shadow mutes a given real player for a configurable amount of minutes (0 means disable
+    /// shadow mute).
+    fn shadow_mute_player(
+        &self,
+        player_id: PlayerId,
+        minutes: usize,
+        players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let mut player = players
+            .borrow_player_mut(player_id)
+            .ok_or("nonexistent player")?;
+        let client = player.client_mut().ok_or("not a real player")?;
+        client
+            .chat
+            .shadow_mute_for(Duration::from_secs(minutes as u64 * 60));
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("shadow mute {}m", minutes),
+        });
+        Ok(AdminUpdate::PlayerShadowMuted(seconds_ceil(
+            client.chat.shadow_muted_for(),
+        )))
+    }
+
+    /// Caps how many game commands a given real player may send per second (0 means no cap).
+    fn cap_player_commands(
+        &self,
+        player_id: PlayerId,
+        per_second: u32,
+        players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let mut player = players
+            .borrow_player_mut(player_id)
+            .ok_or("nonexistent player")?;
+        let client = player.client_mut().ok_or("not a real player")?;
+        if per_second == u32::MAX {
+            return Err("cap too large");
+        }
+        client.command_rate_limit = (per_second != 0)
+            .then(|| RateLimiter::new(Duration::from_secs(1) / per_second, per_second));
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("cap commands {}/s", per_second),
+        });
+        Ok(AdminUpdate::PlayerCommandsCapped(per_second))
+    }
+
+    /// Disconnects a given real player, optionally banning them for a configurable amount of
+    /// minutes.
+    fn kick_player(
+        &self,
+        player_id: PlayerId,
+        ban_minutes: usize,
+        ban_ip: bool,
+        context: &mut Context<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        context.clients.kick(
+            player_id,
+            Duration::from_secs(ban_minutes as u64 * 60),
+            ban_ip,
+            &context.players,
+        )?;
+        context.events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: if ban_minutes == 0 {
+                String::from("kick")
+            } else {
+                format!(
+                    "kick and ban {}m{}",
+                    ban_minutes,
+                    if ban_ip { " by IP" } else { "" }
+                )
+            },
+        });
+        Ok(AdminUpdate::PlayerKicked)
+    }
+
+    /// Starts or stops auditing what a given real player is sent, to investigate accusations of
+    /// seeing more than they should.
+    fn audit_visibility(
+        &self,
+        player_id: PlayerId,
+        enabled: bool,
+        players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let mut player = players
+            .borrow_player_mut(player_id)
+            .ok_or("nonexistent player")?;
+        let client = player.client_mut().ok_or("not a real player")?;
+        client.visibility_audit = enabled;
+        events.record(ArenaEvent::Moderated {
+            moderator_id: None,
+            player_id: Some(player_id),
+            action: format!("visibility audit {}", if enabled { "on" } else { "off" }),
+        });
+        Ok(AdminUpdate::VisibilityAuditSet(enabled))
+    }
+
+    /// Takes discrepancies found while auditing a given real player.
+    fn request_visibility_audit(
+        &self,
+        player_id: PlayerId,
+        players: &PlayerRepo<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let player = players
+            .borrow_player(player_id)
+            .ok_or("nonexistent player")?;
+        let client = player.client().ok_or("not a real player")?;
+        if !client.visibility_audit {
+            return Err("not being audited");
+        }
+        let discrepancies = G::take_visibility_discrepancies(&mut *client.data.borrow_mut());
+        Ok(AdminUpdate::VisibilityAuditRequested(
+            discrepancies.into_boxed_slice(),
+        ))
+    }
+
     /// Get list of all known servers for the game, including incompatible/unreachable/etc. servers.
     fn request_servers(system: &Option<SystemRepo<G>>) -> Result<AdminUpdate, &'static str> {
         let system = system.as_ref().ok_or("system not configured")?;
@@ -439,8 +614,7 @@ impl<G: GameArenaService> AdminRepo<G> {
         )))
     }
 
-    /// Send a chat to all players on the server, or a specific player (in which case, will send a
-    /// whisper message).
+    /// Send a chat to all players on the server, or whisper it to a specific player.
     fn send_chat(
         &self,
         player_id: Option<PlayerId>,
@@ -452,18 +626,30 @@ impl<G: GameArenaService> AdminRepo<G> {
             IpAddr::V4(Ipv4Addr::UNSPECIFIED),
             alias,
             &message,
-            false,
+            player_id.map_or(Channel::Global, Channel::Direct),
             "ok",
         );
 
+        let recipient = player_id
+            .map(|player_id| {
+                context
+                    .players
+                    .borrow_player(player_id)
+                    .map(|player| player.alias())
+                    .ok_or("nonexistent player")
+            })
+            .transpose()?;
+
         let message = MessageDto {
             alias,
             date_sent: get_unix_time_now(),
             player_id: None,
+            recipient,
             team_captain: false,
             team_name: None,
             text: message,
-            whisper: player_id.is_some(),
+            verified: false,
+            whisper: false,
         };
 
         if let Some(player_id) = player_id {
@@ -512,6 +698,174 @@ impl<G: GameArenaService> AdminRepo<G> {
         Ok(AdminUpdate::DistributeLoadSet(distribute_load))
     }
 
+    /// Prefix of game-specific arena options.
+    const ARENA_OPTION_PREFIX: &'static str = "arena.";
+
+    /// Lists options that may be changed at runtime, and their current values.
+    fn request_options(context_service: &ContextService<G>) -> Result<AdminUpdate, &'static str> {
+        let context = &context_service.context;
+        let (min_bots, max_bots, bot_percent) = context.bots.limits();
+        let authenticate = context.clients.authenticate_rate_limit();
+
+        let options = vec![
+            ("min_bots", min_bots.to_string()),
+            ("max_bots", max_bots.to_string()),
+            ("bot_percent", bot_percent.to_string()),
+            (
+                "client_authenticate_rate_limit",
+                authenticate.rate_limit().as_secs().to_string(),
+            ),
+            (
+                "client_authenticate_burst",
+                authenticate.burst().to_string(),
+            ),
+            ("max_observers", context.clients.max_observers().to_string()),
+            (
+                "new_player_min_score",
+                context.chat.restrictions.min_score.to_string(),
+            ),
+            (
+                "new_player_probation",
+                context.chat.restrictions.probation.as_secs().to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .chain(
+            context_service
+                .service
+                .arena_options()
+                .into_iter()
+                .map(|(key, value)| (format!("{}{}", Self::ARENA_OPTION_PREFIX, key), value)),
+        )
+        .collect();
+
+        Ok(AdminUpdate::OptionsRequested(options))
+    }
+
+    /// Changes an option at runtime, after validating it. Recorded as an event, for auditing.
+    fn set_option(
+        key: String,
+        value: String,
+        context_service: &mut ContextService<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        fn parse<T: FromStr>(value: &str) -> Result<T, &'static str> {
+            value.trim().parse().map_err(|_| "invalid value")
+        }
+
+        let context = &mut context_service.context;
+        let (min_bots, max_bots, bot_percent) = context.bots.limits();
+        let authenticate = context.clients.authenticate_rate_limit();
+        let (rate_limit, burst) = (authenticate.rate_limit(), authenticate.burst());
+
+        match key.as_str() {
+            "min_bots" => context
+                .bots
+                .set_limits(parse(&value)?, max_bots, bot_percent)?,
+            "max_bots" => context
+                .bots
+                .set_limits(min_bots, parse(&value)?, bot_percent)?,
+            "bot_percent" => context
+                .bots
+                .set_limits(min_bots, max_bots, parse(&value)?)?,
+            "client_authenticate_rate_limit" => {
+                let secs: u64 = parse(&value)?;
+                if secs == 0 {
+                    return Err("rate limit must be at least 1 second");
+                }
+                context
+                    .clients
+                    .set_authenticate_rate_limit(RateLimiterProps::new(
+                        Duration::from_secs(secs),
+                        burst,
+                    ));
+            }
+            "client_authenticate_burst" => {
+                let burst: u32 = parse(&value)?;
+                if burst == u32::MAX {
+                    return Err("burst too large");
+                }
+                context
+                    .clients
+                    .set_authenticate_rate_limit(RateLimiterProps::new(rate_limit, burst));
+            }
+            "max_observers" => context.clients.set_max_observers(parse(&value)?),
+            "new_player_min_score" => context.chat.restrictions.min_score = parse(&value)?,
+            "new_player_probation" => {
+                context.chat.restrictions.probation = Duration::from_secs(parse(&value)?)
+            }
+            _ => {
+                let arena_key = key
+                    .strip_prefix(Self::ARENA_OPTION_PREFIX)
+                    .ok_or("unknown option")?;
+                context_service
+                    .service
+                    .set_arena_option(arena_key, value.trim())?;
+            }
+        }
+
+        info!("option {} set to {}", key, value);
+        context_service
+            .context
+            .events
+            .record(ArenaEvent::Moderated {
+                moderator_id: None,
+                player_id: None,
+                action: format!("set option {}={}", key, value),
+            });
+        Ok(AdminUpdate::OptionSet { key, value })
+    }
+
+    /// Describes a given player's full state, as far as the game is concerned.
+    fn request_player_state(
+        player_id: PlayerId,
+        context_service: &ContextService<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let player_tuple = context_service
+            .context
+            .players
+            .get(player_id)
+            .ok_or("nonexistent player")?;
+        context_service
+            .service
+            .inspect_player(player_tuple)
+            .map(AdminUpdate::PlayerStateRequested)
+            .ok_or("unsupported")
+    }
+
+    /// Teleports or deletes an entity. Recorded as an event, for auditing.
+    fn edit_entity(
+        entity_id: u32,
+        position: Option<(f32, f32)>,
+        context_service: &mut ContextService<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let service = &mut context_service.service;
+        let (action, update) = if let Some((x, y)) = position {
+            service.teleport_entity(entity_id, x, y)?;
+            (
+                format!("teleported entity {} to ({:.0}, {:.0})", entity_id, x, y),
+                AdminUpdate::EntityTeleported,
+            )
+        } else {
+            service.delete_entity(entity_id)?;
+            (
+                format!("deleted entity {}", entity_id),
+                AdminUpdate::EntityDeleted,
+            )
+        };
+
+        info!("{}", action);
+        context_service
+            .context
+            .events
+            .record(ArenaEvent::Moderated {
+                moderator_id: None,
+                player_id: None,
+                action,
+            });
+        Ok(update)
+    }
+
     fn set_game_client(
         &mut self,
         game_client: EmbeddedMiniCdn,
@@ -626,6 +980,7 @@ impl<G: GameArenaService> Handler<ParameterizedAdminRequest> for Infrastructure<
 
         let request = msg.request;
         let database = self.database();
+        let primary_arena_id = self.context_service.context.arena_id;
         match request {
             AdminRequest::RequestSnippets => Box::pin(fut::ready(AdminRepo::request_snippets(
                 &self.context_service.context.clients,
@@ -690,33 +1045,121 @@ impl<G: GameArenaService> Handler<ParameterizedAdminRequest> for Infrastructure<
             AdminRequest::RequestGames => Box::pin(fut::ready(self.admin.request_games())),
             AdminRequest::RequestPlayers => Box::pin(fut::ready(
                 self.admin
-                    .request_players(&self.context_service.context.players),
+                    .request_players(self.overflow.iter(&self.context_service)),
             )),
             AdminRequest::OverridePlayerAlias { player_id, alias } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
                 Box::pin(fut::ready(self.admin.override_player_alias(
                     player_id,
                     alias,
-                    &self.context_service.context.players,
+                    &context.players,
+                    &mut context.events,
                 )))
             }
             AdminRequest::OverridePlayerModerator {
                 player_id,
                 moderator,
-            } => Box::pin(fut::ready(self.admin.override_player_moderator(
-                player_id,
-                moderator,
-                &self.context_service.context.players,
-            ))),
+            } => Box::pin(fut::ready(
+                self.admin.override_player_moderator(
+                    player_id,
+                    moderator,
+                    &self
+                        .overflow
+                        .arena_of(&self.context_service, player_id)
+                        .context
+                        .players,
+                ),
+            )),
             AdminRequest::RestrictPlayer { player_id, minutes } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
                 Box::pin(fut::ready(self.admin.restrict_player(
                     player_id,
                     minutes,
-                    &self.context_service.context.players,
+                    &context.players,
+                    &mut context.events,
                 )))
             }
-            AdminRequest::MutePlayer { player_id, minutes } => Box::pin(fut::ready(
-                self.admin
-                    .mute_player(player_id, minutes, &self.context_service.context.players),
+            AdminRequest::MutePlayer { player_id, minutes } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                Box::pin(fut::ready(self.admin.mute_player(
+                    player_id,
+                    minutes,
+                    &context.players,
+                    &mut context.events,
+                )))
+            }
+            AdminRequest::ShadowMutePlayer { player_id, minutes } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                Box::pin(fut::ready(self.admin.shadow_mute_player(
+                    player_id,
+                    minutes,
+                    &context.players,
+                    &mut context.events,
+                )))
+            }
+            AdminRequest::CapPlayerCommands {
+                player_id,
+                per_second,
+            } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                Box::pin(fut::ready(self.admin.cap_player_commands(
+                    player_id,
+                    per_second,
+                    &context.players,
+                    &mut context.events,
+                )))
+            }
+            AdminRequest::KickPlayer {
+                player_id,
+                ban_minutes,
+                ban_ip,
+            } => Box::pin(fut::ready(
+                self.admin.kick_player(
+                    player_id,
+                    ban_minutes,
+                    ban_ip,
+                    &mut self
+                        .overflow
+                        .arena_of_mut(&mut self.context_service, player_id)
+                        .context,
+                ),
+            )),
+            AdminRequest::AuditVisibility { player_id, enabled } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                Box::pin(fut::ready(self.admin.audit_visibility(
+                    player_id,
+                    enabled,
+                    &context.players,
+                    &mut context.events,
+                )))
+            }
+            AdminRequest::RequestVisibilityAudit { player_id } => Box::pin(fut::ready(
+                self.admin.request_visibility_audit(
+                    player_id,
+                    &self
+                        .overflow
+                        .arena_of(&self.context_service, player_id)
+                        .context
+                        .players,
+                ),
             )),
             AdminRequest::RequestServerId => Box::pin(fut::ready(Ok(
                 AdminUpdate::ServerIdRequested(self.server_id),
@@ -733,6 +1176,17 @@ impl<G: GameArenaService> Handler<ParameterizedAdminRequest> for Infrastructure<
             AdminRequest::RequestRegions => {
                 Box::pin(fut::ready(self.admin.request_regions(&self.metrics)))
             }
+            AdminRequest::RequestReports => {
+                let mut reports: Vec<_> = self
+                    .overflow
+                    .iter(&self.context_service)
+                    .flat_map(|arena| arena.context.chat.reports())
+                    .collect();
+                reports.sort_by_key(|report| report.date_created);
+                Box::pin(fut::ready(Ok(AdminUpdate::ReportsRequested(
+                    reports.into_boxed_slice(),
+                ))))
+            }
             AdminRequest::RequestUserAgents => {
                 Box::pin(fut::ready(self.admin.request_user_agents(&self.metrics)))
             }
@@ -740,24 +1194,129 @@ impl<G: GameArenaService> Handler<ParameterizedAdminRequest> for Infrastructure<
                 player_id,
                 alias,
                 message,
-            } => Box::pin(fut::ready(self.admin.send_chat(
-                player_id,
-                alias,
-                message,
-                &mut self.context_service.context,
-            ))),
+            } => Box::pin(fut::ready(if let Some(player_id) = player_id {
+                self.admin.send_chat(
+                    Some(player_id),
+                    alias,
+                    message,
+                    &mut self
+                        .overflow
+                        .arena_of_mut(&mut self.context_service, player_id)
+                        .context,
+                )
+            } else {
+                let admin = &self.admin;
+                self.overflow
+                    .iter_mut(&mut self.context_service)
+                    .try_for_each(|arena| {
+                        admin
+                            .send_chat(None, alias, message.clone(), &mut arena.context)
+                            .map(drop)
+                    })
+                    .map(|_| AdminUpdate::ChatSent)
+            })),
             AdminRequest::RequestAllowWebSocketJson => {
                 Box::pin(fut::ready(self.admin.request_allow_web_socket_json()))
             }
             AdminRequest::SetAllowWebSocketJson(allow_web_socket_json) => Box::pin(fut::ready(
                 self.admin.set_allow_web_socket_json(allow_web_socket_json),
             )),
+            AdminRequest::RequestExperiment => Box::pin(fut::ready(
+                ExperimentRepo::request_experiment(&self.metrics),
+            )),
+            AdminRequest::SetExperiment(experiment) => {
+                Box::pin(fut::ready(ExperimentRepo::set_experiment(self, experiment)))
+            }
             AdminRequest::RequestDistributeLoad => {
                 Box::pin(fut::ready(self.admin.request_distribute_load()))
             }
             AdminRequest::SetDistributeLoad(distribute_load) => {
                 Box::pin(fut::ready(self.admin.set_distribute_load(distribute_load)))
             }
+            AdminRequest::RequestOptions => Box::pin(fut::ready(AdminRepo::request_options(
+                &self.context_service,
+            ))),
+            AdminRequest::SetOption { key, value } => Box::pin(fut::ready(AdminRepo::set_option(
+                key,
+                value,
+                &mut self.context_service,
+            ))),
+            AdminRequest::RequestEntities {
+                arena_id,
+                x,
+                y,
+                radius,
+            } => Box::pin(fut::ready(
+                self.overflow
+                    .get_mut(
+                        &mut self.context_service,
+                        arena_id.unwrap_or(primary_arena_id),
+                    )
+                    .ok_or("nonexistent arena")
+                    .map(|arena| {
+                        AdminUpdate::EntitiesRequested(
+                            arena
+                                .service
+                                .inspect_entities(x, y, radius)
+                                .into_boxed_slice(),
+                        )
+                    }),
+            )),
+            AdminRequest::RequestPlayerState { player_id } => {
+                Box::pin(fut::ready(AdminRepo::request_player_state(
+                    player_id,
+                    self.overflow.arena_of(&self.context_service, player_id),
+                )))
+            }
+            AdminRequest::TeleportEntity {
+                arena_id,
+                entity_id,
+                x,
+                y,
+            } => Box::pin(fut::ready(
+                self.overflow
+                    .get_mut(
+                        &mut self.context_service,
+                        arena_id.unwrap_or(primary_arena_id),
+                    )
+                    .ok_or("nonexistent arena")
+                    .and_then(|arena| AdminRepo::edit_entity(entity_id, Some((x, y)), arena)),
+            )),
+            AdminRequest::DeleteEntity {
+                arena_id,
+                entity_id,
+            } => Box::pin(fut::ready(
+                self.overflow
+                    .get_mut(
+                        &mut self.context_service,
+                        arena_id.unwrap_or(primary_arena_id),
+                    )
+                    .ok_or("nonexistent arena")
+                    .and_then(|arena| AdminRepo::edit_entity(entity_id, None, arena)),
+            )),
+            AdminRequest::SetEntityOverrides(patch) => Box::pin(fut::ready(
+                self.context_service
+                    .service
+                    .set_entity_overrides(&patch)
+                    .map(|_| AdminUpdate::EntityOverridesSet),
+            )),
+            AdminRequest::RequestEvents {
+                period_start,
+                period_stop,
+            } => {
+                let queries: Vec<_> = self
+                    .overflow
+                    .iter(&self.context_service)
+                    .map(|arena| arena.context.events.query(period_start, period_stop))
+                    .collect();
+                Box::pin(futures::future::try_join_all(queries).into_actor(self).map(
+                    |result, _act, _ctx| {
+                        result.map(|arenas| {
+                            AdminUpdate::EventsRequested(EventLogRepo::<G>::merge(arenas))
+                        })
+                    },
+                ))
+            }
             AdminRequest::OverrideClientHash(server_id) => Box::pin(fut::ready(
                 self.admin
                     .override_client_hash(server_id, &self.system, &mut self.status),
@@ -771,6 +1330,19 @@ impl<G: GameArenaService> Handler<ParameterizedAdminRequest> for Infrastructure<
                 self.server_id,
                 self.system.as_mut(),
             ))),
+            AdminRequest::RequestTournament => Box::pin(fut::ready(Ok(
+                AdminUpdate::TournamentRequested(self.tournament.dto()),
+            ))),
+            AdminRequest::ScheduleTournament {
+                start,
+                rounds,
+                round_minutes,
+            } => Box::pin(fut::ready(self.tournament.schedule(
+                start,
+                rounds,
+                round_minutes,
+            ))),
+            AdminRequest::CancelTournament => Box::pin(fut::ready(self.tournament.cancel())),
             AdminRequest::RequestProfile => {
                 if let Err(e) = self.admin.start_profile() {
                     Box::pin(fut::ready(Err(e)))
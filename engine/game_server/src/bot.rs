@@ -61,6 +61,33 @@ impl<G: GameArenaService> BotRepo<G> {
         )
     }
 
+    /// Returns the minimum bots, maximum bots, and bot percent.
+    pub(crate) fn limits(&self) -> (usize, usize, usize) {
+        (self.min_bots, self.max_bots, self.bot_percent)
+    }
+
+    /// Changes the limits on bot count at runtime. Bots are spawned or despawned gradually.
+    pub(crate) fn set_limits(
+        &mut self,
+        min_bots: usize,
+        max_bots: usize,
+        bot_percent: usize,
+    ) -> Result<(), &'static str> {
+        if min_bots > max_bots {
+            return Err("min bots exceeds max bots");
+        }
+        if min_bots
+            .checked_sub(1)
+            .map_or(false, |n| PlayerId::nth_bot(n).is_none())
+        {
+            return Err("too many bots");
+        }
+        self.min_bots = min_bots;
+        self.max_bots = max_bots;
+        self.bot_percent = bot_percent;
+        Ok(())
+    }
+
     /// Updates all bots.
     pub fn update(&mut self, service: &G, players: &PlayerRepo<G>) {
         self.bots
@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::game_service::GameArenaService;
+use crate::log::EventLogRepo;
 use crate::metric::MetricRepo;
-use crate::player::PlayerRepo;
+use crate::player::{PlayerData, PlayerRepo};
 use crate::team::TeamRepo;
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
-use core_protocol::dto::MessageDto;
+use core_protocol::dto::{ArenaEvent, MessageDto, ReportDto};
 use core_protocol::get_unix_time_now;
 use core_protocol::id::PlayerId;
 use core_protocol::name::PlayerAlias;
@@ -28,16 +29,69 @@ use std::time::{Duration, Instant};
 pub struct ChatRepo<G> {
     /// For new players' chat to start full.
     recent: HistoryBuffer<Arc<MessageDto>, 16>,
+    /// Recent reports, for automute and for moderators to review.
+    reports: HistoryBuffer<(Instant, ReportDto), 64>,
     /// Safe mode (profanity filter setting) is on until this time.
     safe_mode_until: Option<Instant>,
     /// Slow mode (more aggressive rate limits for all players) is on until this time.
     slow_mode_until: Option<Instant>,
+    /// What counts as a new player, who can't post links (or change alias repeatedly).
+    pub(crate) restrictions: NewPlayerRestrictions,
     emoji_replacer: AhoCorasick<u32>,
     /// Log all chats here.
     log_path: Option<Arc<str>>,
     _spooky: PhantomData<G>,
 }
 
+/// Players below a score, or within their first minutes, are restricted to cut down on spam by
+/// bots. Moderators are never restricted.
+#[derive(Copy, Clone, Debug)]
+pub struct NewPlayerRestrictions {
+    /// Players with less score are restricted.
+    pub min_score: u32,
+    /// Players with sessions younger than this are restricted.
+    pub probation: Duration,
+}
+
+impl NewPlayerRestrictions {
+    /// Returns true if the player is restricted. Bots are never restricted.
+    pub(crate) fn applies_to<G: GameArenaService>(&self, player: &PlayerData<G>) -> bool {
+        player.client().map_or(false, |client| {
+            !client.moderator
+                && (player.score < self.min_score
+                    || client.metrics.created.elapsed() < self.probation)
+        })
+    }
+}
+
+/// Returns true if the text appears to contain a link (e.g. a URL or domain name).
+fn contains_link(text: &str) -> bool {
+    const PATTERNS: &[&str] = &["://", "www.", ".com", ".net", ".org", ".io", ".gg", ".xyz"];
+    let text = text.to_lowercase();
+    PATTERNS.iter().any(|pattern| text.contains(pattern))
+}
+
+/// Who a chat message is sent to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Channel {
+    /// All players.
+    Global,
+    /// The sender's team.
+    Team,
+    /// A single player.
+    Direct(PlayerId),
+}
+
+impl Channel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Team => "team",
+            Self::Direct(_) => "direct",
+        }
+    }
+}
+
 /// Component of client data encompassing chat information.
 #[derive(Debug, Default)]
 pub struct ClientChatData {
@@ -47,9 +101,29 @@ pub struct ClientChatData {
     muted: HashSet<PlayerId>,
     /// Messages that need to be sent to the client.
     inbox: HistoryBuffer<Arc<MessageDto>, 16>,
+    /// How many times this client has been automatically restricted, and when it last happened.
+    /// Reports from before then don't count towards the next automute.
+    automuted: Option<(u32, Instant)>,
+    /// Until when this client's messages are only shown to themselves, if shadow muted by an
+    /// admin. Unlike a regular mute, they aren't told.
+    shadow_muted_until: Option<Instant>,
 }
 
 impl ClientChatData {
+    /// Shadow mutes the client for a duration (zero means disable shadow mute).
+    pub(crate) fn shadow_mute_for(&mut self, duration: Duration) {
+        self.shadow_muted_until = Instant::now()
+            .checked_add(duration)
+            .filter(|_| !duration.is_zero());
+    }
+
+    /// Remaining duration of the shadow mute, if any.
+    pub(crate) fn shadow_muted_for(&self) -> Duration {
+        self.shadow_muted_until.map_or(Duration::ZERO, |until| {
+            until.saturating_duration_since(Instant::now())
+        })
+    }
+
     /// Call when it is reasonable to assume client has forgotten state (and will receive
     /// recent messages anyway).
     pub fn forget_state(&mut self) {
@@ -78,7 +152,7 @@ impl ClientChatData {
 engine_macros::include_emoji!();
 
 impl<G: GameArenaService> ChatRepo<G> {
-    pub fn new(log_path: Option<String>) -> Self {
+    pub fn new(log_path: Option<String>, restrictions: NewPlayerRestrictions) -> Self {
         let emoji_replacer = AhoCorasickBuilder::new()
             .dfa(true)
             .build_with_size(EMOJI_FIND)
@@ -86,8 +160,10 @@ impl<G: GameArenaService> ChatRepo<G> {
 
         Self {
             recent: HistoryBuffer::new(),
+            reports: HistoryBuffer::new(),
             safe_mode_until: None,
             slow_mode_until: None,
+            restrictions,
             emoji_replacer,
             log_path: log_path.map(Into::into),
             _spooky: PhantomData,
@@ -139,6 +215,111 @@ impl<G: GameArenaService> ChatRepo<G> {
         }
     }
 
+    /// Reports within this long of each other count towards an automute.
+    const REPORT_WINDOW: Duration = Duration::from_secs(10 * 60);
+    /// Distinct players that must report a player within [`Self::REPORT_WINDOW`] to automute them.
+    const AUTOMUTE_REPORTS: usize = 3;
+    /// Minutes of the first automute. Each subsequent automute is twice as long.
+    const AUTOMUTE_MINUTES: u32 = 5;
+
+    /// Report a player for abusive chat, which also mutes them. Escalating restrictions are
+    /// applied automatically to players reported by enough distinct players.
+    pub(crate) fn report_player(
+        &mut self,
+        req_player_id: PlayerId,
+        report_player_id: PlayerId,
+        players: &mut PlayerRepo<G>,
+        metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) -> Result<ChatUpdate, &'static str> {
+        if req_player_id == report_player_id {
+            return Err("cannot report self");
+        }
+        let mut req_player = players
+            .borrow_player_mut(req_player_id)
+            .ok_or("nonexistent player")?;
+        if req_player.score < G::MINIMUM_REPORT_SCORE {
+            return Err("report requirements unmet");
+        }
+        let req_client = req_player
+            .client_mut()
+            .ok_or("only clients can report players")?;
+        let mut report_player = players
+            .borrow_player_mut(report_player_id)
+            .ok_or("cannot report nonexistent player")?;
+        let alias = report_player.alias();
+        let report_client = report_player
+            .client_mut()
+            .ok_or("only clients can be reported")?;
+        if !req_client.reported.insert(report_player_id) {
+            return Err("already reported");
+        }
+        req_client.chat.muted.insert(report_player_id);
+        report_client.chat.context.report();
+        metrics.mutate_with(|m| m.abuse_reports.increment(), &report_client.metrics);
+
+        // Each reporter can only report a given player once, so reports are from distinct players.
+        let now = Instant::now();
+        let since = now
+            .checked_sub(Self::REPORT_WINDOW)
+            .into_iter()
+            .chain(report_client.chat.automuted.map(|(_, at)| at))
+            .max();
+        let reports = 1 + self
+            .reports
+            .oldest_ordered()
+            .filter(|(at, report)| {
+                report.player_id == report_player_id && since.map_or(true, |since| *at > since)
+            })
+            .count();
+
+        let automuted = reports >= Self::AUTOMUTE_REPORTS;
+        if automuted {
+            let offenses = report_client.chat.automuted.map_or(0, |(n, _)| n);
+            let minutes = Self::AUTOMUTE_MINUTES
+                .saturating_mul(1 << offenses.min(16))
+                .min(1440);
+            if let Some(restrict_until) =
+                Self::minutes_to_instant(minutes, report_client.chat.context.restricted_until())
+            {
+                report_client.chat.context.restrict_until(restrict_until);
+            }
+            report_client.chat.automuted = Some((offenses + 1, now));
+            events.record(ArenaEvent::Moderated {
+                moderator_id: None,
+                player_id: Some(report_player_id),
+                action: format!("automute {}m after {} reports", minutes, reports),
+            });
+        }
+
+        let messages = self
+            .recent
+            .oldest_ordered()
+            .filter(|message| message.player_id == Some(report_player_id))
+            .map(|message| message.text.clone())
+            .collect();
+        self.reports.write((
+            now,
+            ReportDto {
+                date_created: get_unix_time_now(),
+                reporter_id: req_player_id,
+                player_id: report_player_id,
+                alias,
+                messages,
+                automuted,
+            },
+        ));
+
+        Ok(ChatUpdate::Reported(report_player_id))
+    }
+
+    /// Gets admin view of recent reports, oldest first.
+    pub(crate) fn reports(&self) -> impl Iterator<Item = ReportDto> + '_ {
+        self.reports
+            .oldest_ordered()
+            .map(|(_, report)| report.clone())
+    }
+
     /// Clamps minutes to a day, and then returns an instant in the future (if overflow occurs, returns old instant).
     fn minutes_to_instant(minutes: u32, old: Option<Instant>) -> Option<Instant> {
         let new = Instant::now().checked_add(Duration::from_secs(minutes as u64 * 60));
@@ -151,6 +332,7 @@ impl<G: GameArenaService> ChatRepo<G> {
         restrict_player_id: PlayerId,
         minutes: u32,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<ChatUpdate, &'static str> {
         if req_player_id == restrict_player_id {
             return Err("cannot restrict self");
@@ -171,6 +353,11 @@ impl<G: GameArenaService> ChatRepo<G> {
             Self::minutes_to_instant(minutes, restrict_client.chat.context.restricted_until())
         {
             restrict_client.chat.context.restrict_until(restrict_until);
+            events.record(ArenaEvent::Moderated {
+                moderator_id: Some(req_player_id),
+                player_id: Some(restrict_player_id),
+                action: format!("restrict {}m", minutes),
+            });
             Ok(ChatUpdate::PlayerRestricted {
                 player_id: restrict_player_id,
                 minutes,
@@ -185,6 +372,7 @@ impl<G: GameArenaService> ChatRepo<G> {
         req_player_id: PlayerId,
         minutes: u32,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<ChatUpdate, &'static str> {
         let req_player = players
             .borrow_player(req_player_id)
@@ -195,6 +383,11 @@ impl<G: GameArenaService> ChatRepo<G> {
         }
         let clamped = minutes.min(60);
         self.safe_mode_until = Self::minutes_to_instant(clamped, None);
+        events.record(ArenaEvent::Moderated {
+            moderator_id: Some(req_player_id),
+            player_id: None,
+            action: format!("safe mode {}m", clamped),
+        });
         Ok(ChatUpdate::SafeModeSet(clamped))
     }
 
@@ -203,6 +396,7 @@ impl<G: GameArenaService> ChatRepo<G> {
         req_player_id: PlayerId,
         minutes: u32,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<ChatUpdate, &'static str> {
         let req_player = players
             .borrow_player(req_player_id)
@@ -213,32 +407,49 @@ impl<G: GameArenaService> ChatRepo<G> {
         }
         let clamped = minutes.min(120);
         self.slow_mode_until = Self::minutes_to_instant(clamped, None);
+        events.record(ArenaEvent::Moderated {
+            moderator_id: Some(req_player_id),
+            player_id: None,
+            action: format!("slow mode {}m", clamped),
+        });
         Ok(ChatUpdate::SlowModeSet(clamped))
     }
 
-    /// Send a chat to all players, or one's team (whisper).
+    /// Send a chat to all players, one's team, or a single player.
     fn send_chat(
         &mut self,
         req_player_id: PlayerId,
         message: String,
-        whisper: bool,
+        channel: Channel,
         service: &mut G,
         players: &mut PlayerRepo<G>,
         teams: &TeamRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<ChatUpdate, &'static str> {
-        if let Some(text) = self.try_execute_command(req_player_id, &message, service, players) {
+        let whisper = channel == Channel::Team;
+
+        // Commands can't be whispered.
+        let command = if matches!(channel, Channel::Direct(_)) {
+            None
+        } else {
+            self.try_execute_command(req_player_id, &message, service, players, events)
+        };
+
+        if let Some(text) = command {
             if let Some(mut req_player) = players.borrow_player_mut(req_player_id) {
                 let alias = req_player.alias();
                 if let Some(req_client) = req_player.client_mut() {
-                    self.log_chat(req_client.ip_address, alias, &message, whisper, "executed");
+                    self.log_chat(req_client.ip_address, alias, &message, channel, "executed");
                     let message = MessageDto {
                         alias: G::authority_alias(),
                         date_sent: get_unix_time_now(),
                         player_id: None,
+                        recipient: None,
                         team_captain: false,
                         team_name: None,
                         text,
+                        verified: false,
                         whisper,
                     };
                     req_client.chat.receive(&Arc::new(message));
@@ -251,6 +462,21 @@ impl<G: GameArenaService> ChatRepo<G> {
             return Ok(ChatUpdate::Sent);
         }
 
+        let recipient = if let Channel::Direct(player_id) = channel {
+            if player_id == req_player_id {
+                return Err("cannot whisper to self");
+            }
+            let recipient = players
+                .borrow_player(player_id)
+                .ok_or("cannot whisper to nonexistent player")?;
+            if recipient.client().is_none() {
+                return Err("cannot whisper to bot");
+            }
+            Some(recipient.alias())
+        } else {
+            None
+        };
+
         let mut req_player = players
             .borrow_player_mut(req_player_id)
             .ok_or("nonexistent player")?;
@@ -265,6 +491,24 @@ impl<G: GameArenaService> ChatRepo<G> {
             return Err("no one to whisper to");
         }
 
+        if contains_link(&message) && self.restrictions.applies_to(&req_player) {
+            if let Some(req_client) = req_player.client_mut() {
+                let warning = MessageDto {
+                    alias: G::authority_alias(),
+                    date_sent: get_unix_time_now(),
+                    player_id: None,
+                    recipient: None,
+                    team_captain: false,
+                    team_name: None,
+                    text: String::from("New players can't post links yet."),
+                    verified: false,
+                    whisper,
+                };
+                req_client.chat.receive(&Arc::new(warning));
+            }
+            return Ok(ChatUpdate::Sent);
+        }
+
         // If the team no longer exists, no members should exist.
         debug_assert_eq!(req_player.team_id().is_some(), team.is_some());
 
@@ -308,7 +552,9 @@ impl<G: GameArenaService> ChatRepo<G> {
             let was_toxic = req_client.chat.context.total_inappropriate() > before;
             metrics.mutate_with(|m| m.toxicity.push(was_toxic), &req_client.metrics);
 
+            let shadow_muted = !req_client.chat.shadow_muted_for().is_zero();
             let verdict = match &result {
+                Ok(_) if shadow_muted => "shadow muted",
                 Ok(_) if was_toxic => "toxic",
                 Ok(_) => "ok",
                 Err(BlockReason::Inappropriate(_)) => "inappropriate",
@@ -324,7 +570,7 @@ impl<G: GameArenaService> ChatRepo<G> {
                 req_client.ip_address,
                 req_player.alias(),
                 &message,
-                whisper,
+                channel,
                 verdict,
             );
 
@@ -339,16 +585,38 @@ impl<G: GameArenaService> ChatRepo<G> {
                     alias: req_player.alias(),
                     date_sent: get_unix_time_now(),
                     player_id: Some(req_player.player_id),
+                    recipient,
                     team_captain: team.map(|t| t.is_captain(req_player_id)).unwrap_or(false),
                     team_name: team.map(|t| t.name),
                     text,
+                    verified: req_player
+                        .client()
+                        .map_or(false, |client| client.discord_id.is_some()),
                     whisper,
                 });
 
+                // Shadow muted players see their own messages, but no one else does.
+                if let Some(req_client) = req_player
+                    .client_mut()
+                    .filter(|client| !client.chat.shadow_muted_for().is_zero())
+                {
+                    req_client.chat.receive(&message);
+                    return Ok(ChatUpdate::Sent);
+                }
+
                 // We are about to borrow the players to send to them.
                 drop(req_player);
 
-                if whisper {
+                if let Channel::Direct(player_id) = channel {
+                    // The sender sees their own message too.
+                    for id in [player_id, req_player_id] {
+                        if let Some(mut player) = players.borrow_player_mut(id) {
+                            if let Some(client) = player.client_mut() {
+                                client.chat.receive(&message)
+                            }
+                        }
+                    }
+                } else if whisper {
                     if let Some(team) = team {
                         for member in team.members.iter() {
                             if let Some(mut player) = players.borrow_player_mut(member) {
@@ -373,9 +641,11 @@ impl<G: GameArenaService> ChatRepo<G> {
                         alias: G::authority_alias(),
                         date_sent: get_unix_time_now(),
                         player_id: None,
+                        recipient: None,
                         team_captain: false,
                         team_name: None,
                         text: reason.contextual_string(),
+                        verified: false,
                         whisper,
                     };
 
@@ -398,6 +668,22 @@ impl<G: GameArenaService> ChatRepo<G> {
         self.recent.write(message);
     }
 
+    /// Broadcasts a message from the server (see [`GameArenaService::authority_alias`]).
+    pub(crate) fn announce(&mut self, text: String, players: &mut PlayerRepo<G>) {
+        let message = MessageDto {
+            alias: G::authority_alias(),
+            date_sent: get_unix_time_now(),
+            player_id: None,
+            recipient: None,
+            team_captain: false,
+            team_name: None,
+            text,
+            verified: false,
+            whisper: false,
+        };
+        self.broadcast_message(Arc::new(message), players);
+    }
+
     /// Process any [`ChatRequest`].
     pub(crate) fn handle_chat_request(
         &mut self,
@@ -407,27 +693,46 @@ impl<G: GameArenaService> ChatRepo<G> {
         players: &mut PlayerRepo<G>,
         teams: &TeamRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<ChatUpdate, &'static str> {
         match request {
             ChatRequest::Mute(player_id) => self.mute_player(req_player_id, player_id, players),
+            ChatRequest::Report(player_id) => {
+                self.report_player(req_player_id, player_id, players, metrics, events)
+            }
             ChatRequest::Unmute(player_id) => self.unmute_player(req_player_id, player_id, players),
             ChatRequest::Send { message, whisper } => self.send_chat(
                 req_player_id,
                 message,
-                whisper,
+                if whisper {
+                    Channel::Team
+                } else {
+                    Channel::Global
+                },
+                service,
+                players,
+                teams,
+                metrics,
+                events,
+            ),
+            ChatRequest::Whisper { player_id, message } => self.send_chat(
+                req_player_id,
+                message,
+                Channel::Direct(player_id),
                 service,
                 players,
                 teams,
                 metrics,
+                events,
             ),
             ChatRequest::SetSafeMode(minutes) => {
-                self.set_safe_mode(req_player_id, minutes, &*players)
+                self.set_safe_mode(req_player_id, minutes, &*players, events)
             }
             ChatRequest::SetSlowMode(minutes) => {
-                self.set_slow_mode(req_player_id, minutes, &*players)
+                self.set_slow_mode(req_player_id, minutes, &*players, events)
             }
             ChatRequest::RestrictPlayer { player_id, minutes } => {
-                self.restrict_player(req_player_id, player_id, minutes, players)
+                self.restrict_player(req_player_id, player_id, minutes, players, events)
             }
         }
     }
@@ -472,11 +777,11 @@ impl<G: GameArenaService> ChatRepo<G> {
         ip: IpAddr,
         alias: PlayerAlias,
         message: &str,
-        whisper: bool,
+        channel: Channel,
         verdict: &str,
     ) {
         if let Some(log_path) = &self.log_path {
-            let ctx = if whisper { "team" } else { "global" };
+            let ctx = channel.as_str();
             let log_path = Arc::clone(log_path);
             let mut line = Vec::with_capacity(256);
             let mut writer = csv::Writer::from_writer(&mut line);
@@ -512,6 +817,7 @@ impl<G: GameArenaService> ChatRepo<G> {
         message: &str,
         service: &mut G,
         players: &PlayerRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Option<String> {
         struct FormattedDuration(Duration);
 
@@ -568,7 +874,7 @@ impl<G: GameArenaService> ChatRepo<G> {
                     None => print_until_status($name, self.$getter),
                     Some(arg) => {
                         if let Some(minutes) = parse_minutes(arg) {
-                            self.$setter(req_player_id, minutes, players)
+                            self.$setter(req_player_id, minutes, players, events)
                                 .map(|_| print_until_status($name, self.$getter))
                                 .map_err(String::from)
                                 .into_ok_or_err()
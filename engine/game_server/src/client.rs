@@ -1,41 +1,47 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::achievement::ClientAchievementData;
 use crate::chat::{ChatRepo, ClientChatData};
+use crate::desync::DesyncRepo;
 use crate::game_service::GameArenaService;
 use crate::infrastructure::Infrastructure;
 use crate::invitation::{ClientInvitationData, InvitationRepo};
 use crate::leaderboard::LeaderboardRepo;
 use crate::liveboard::LiveboardRepo;
+use crate::log::EventLogRepo;
+use crate::login::sign_in;
 use crate::metric::{ClientMetricData, MetricRepo};
 use crate::player::{PlayerData, PlayerRepo, PlayerTuple};
+use crate::streak::DailyStreak;
 use crate::system::SystemRepo;
 use crate::team::{ClientTeamData, TeamRepo};
+use crate::transfer::{Progress, Transfer};
 use actix::WrapStream;
 use actix::{
     fut, ActorFutureExt, ActorStreamExt, Context as ActorContext, ContextFutureSpawner, Handler,
     Message, ResponseActFuture, WrapFuture,
 };
 use atomic_refcell::AtomicRefCell;
-use core_protocol::dto::{InvitationDto, ServerDto};
-use core_protocol::get_unix_time_now;
+use core_protocol::dto::{ArenaEvent, InvitationDto, ServerDto};
 use core_protocol::id::{
-    ArenaId, CohortId, InvitationId, PlayerId, ServerId, SessionId, UserAgentId,
+    ArenaId, CohortId, InvitationId, LoginType, PlayerId, ServerId, SessionId, UserAgentId, UserId,
 };
 use core_protocol::name::{PlayerAlias, Referrer};
 use core_protocol::rpc::{
     AdType, ClientRequest, ClientUpdate, LeaderboardUpdate, LiveboardUpdate, PlayerUpdate, Request,
     SystemUpdate, TeamUpdate, Update,
 };
+use core_protocol::{get_unix_time_now, UnixTime};
 use futures::stream::FuturesUnordered;
 use log::{error, info, warn};
 use maybe_parallel_iterator::IntoMaybeParallelRefIterator;
 use rust_embed::RustEmbed;
-use server_util::database_schema::SessionItem;
-use server_util::generate_id::{generate_id, generate_id_64};
+use server_util::database_schema::{LoginItem, SessionItem};
+use server_util::generate_id::generate_id_64;
 use server_util::ip_rate_limiter::IpRateLimiter;
 use server_util::observer::{ObserverMessage, ObserverUpdate};
-use server_util::rate_limiter::{RateLimiter, RateLimiterProps};
+use server_util::rate_limiter::{RateLimiter, RateLimiterProps, RateLimiterState};
 use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
@@ -60,9 +66,21 @@ pub struct ClientRepo<G: GameArenaService> {
     prune_rate_limiter: RateLimiter,
     database_rate_limiter: RateLimiter,
     pending_session_write: Vec<SessionItem>,
+    /// Logins to (re)bind to sessions.
+    pending_login_write: Vec<LoginItem>,
+    /// Maximum number of observers (see [`PlayerClientData::observer`]) at once.
+    max_observers: usize,
     pub(crate) snippets: HashMap<(Option<CohortId>, Option<Referrer>), Arc<str>>,
     /// Where to log traces to.
     trace_log: Option<Arc<str>>,
+    /// Nonces of accepted transfers, and when they expire, so each may only be accepted once.
+    redeemed_transfers: HashMap<u64, UnixTime>,
+    /// Sessions banned by an admin, and until when (see [`Self::kick`]).
+    banned_sessions: HashMap<SessionId, Instant>,
+    /// Sessions of this server's previous process, whose players may reconnect and reclaim their
+    /// progress (in the same arena) until the given time (see
+    /// [`Handoff`][crate::handoff::Handoff]).
+    pub(crate) handed_off: HashMap<SessionId, (PlayerId, ArenaId, Progress, Instant)>,
     _spooky: PhantomData<G>,
 }
 
@@ -71,14 +89,80 @@ pub struct ClientRepo<G: GameArenaService> {
 struct ReferrerSnippet;
 
 impl<G: GameArenaService> ClientRepo<G> {
-    pub fn new(trace_log: Option<String>, authenticate: RateLimiterProps) -> Self {
+    /// Gets the rate limit of authentication (websocket connection) per IP.
+    pub(crate) fn authenticate_rate_limit(&self) -> &RateLimiterProps {
+        self.authenticate_rate_limiter.props()
+    }
+
+    /// Changes the rate limit of authentication at runtime.
+    pub(crate) fn set_authenticate_rate_limit(&mut self, props: RateLimiterProps) {
+        self.authenticate_rate_limiter.set_props(props);
+    }
+
+    /// Gets the maximum number of observers at once.
+    pub(crate) fn max_observers(&self) -> usize {
+        self.max_observers
+    }
+
+    /// Changes the maximum number of observers at runtime. Doesn't disconnect existing observers.
+    pub(crate) fn set_max_observers(&mut self, max_observers: usize) {
+        self.max_observers = max_observers;
+    }
+
+    /// Disconnects a real player, who will leave the game shortly. If `ban` is nonzero, their
+    /// session (and, if `ban_ip`, their IP address) can't reconnect for that long.
+    pub(crate) fn kick(
+        &mut self,
+        player_id: PlayerId,
+        ban: Duration,
+        ban_ip: bool,
+        players: &PlayerRepo<G>,
+    ) -> Result<(), &'static str> {
+        let mut player = players
+            .borrow_player_mut(player_id)
+            .ok_or("nonexistent player")?;
+        let client = player.client_mut().ok_or("not a real player")?;
+
+        if !ban.is_zero() {
+            if let Some(until) = Instant::now().checked_add(ban) {
+                self.banned_sessions.insert(client.session_id, until);
+            }
+            if ban_ip {
+                self.authenticate_rate_limiter.ban(client.ip_address, ban);
+            }
+        }
+
+        match &client.status {
+            ClientStatus::Connected { observer } => {
+                let _ = observer.send(ObserverUpdate::Close);
+            }
+            ClientStatus::Limbo { .. } => {}
+            _ => return Ok(()),
+        }
+        // Skip the rest of limbo.
+        client.status = ClientStatus::Limbo {
+            expiry: Instant::now(),
+        };
+        Ok(())
+    }
+
+    pub fn new(
+        trace_log: Option<String>,
+        authenticate: RateLimiterProps,
+        max_observers: usize,
+    ) -> Self {
         Self {
             authenticate_rate_limiter: authenticate.into(),
             prune_rate_limiter: RateLimiter::new(Duration::from_secs(1), 0),
             database_rate_limiter: RateLimiter::new(Duration::from_secs(30), 0),
             pending_session_write: Vec::new(),
+            pending_login_write: Vec::new(),
+            max_observers,
             snippets: Self::load_default_snippets(),
             trace_log: trace_log.map(Into::into),
+            redeemed_transfers: HashMap::new(),
+            banned_sessions: HashMap::new(),
+            handed_off: HashMap::new(),
             _spooky: PhantomData,
         }
     }
@@ -149,33 +233,34 @@ impl<G: GameArenaService> ClientRepo<G> {
             .unwrap_or(ServerId::new(200).unwrap());
         #[cfg(not(debug_assertions))]
         let server_id = crate::unwrap_or_return!(infrastructure.server_id);
-        let arena_id = infrastructure.context_service.context.arena_id;
+        let database = infrastructure.database;
 
         let queue = FuturesUnordered::new();
+        let login_queue = FuturesUnordered::new();
 
-        // Backlog from leaving sessions.
-        for pending in infrastructure
-            .context_service
-            .context
-            .clients
-            .pending_session_write
-            .drain(..)
+        for context_service in infrastructure
+            .overflow
+            .iter_mut(&mut infrastructure.context_service)
         {
-            queue.push(infrastructure.database.put_session(pending));
-        }
+            let context = &mut context_service.context;
+            let arena_id = context.arena_id;
 
-        for mut player in infrastructure
-            .context_service
-            .context
-            .players
-            .iter_borrow_mut()
-        {
-            let player_id = player.player_id;
-            if let Some(client) = player.client_mut() {
-                if let Some(session_item) =
-                    Self::db_session_item(server_id, arena_id, player_id, client)
-                {
-                    queue.push(infrastructure.database.put_session(session_item))
+            // Backlog from leaving sessions.
+            for pending in context.clients.pending_session_write.drain(..) {
+                queue.push(database.put_session(pending));
+            }
+            for pending in context.clients.pending_login_write.drain(..) {
+                login_queue.push(database.put_login(pending));
+            }
+
+            for mut player in context.players.iter_borrow_mut() {
+                let player_id = player.player_id;
+                if let Some(client) = player.client_mut() {
+                    if let Some(session_item) =
+                        Self::db_session_item(server_id, arena_id, player_id, client)
+                    {
+                        queue.push(database.put_session(session_item))
+                    }
                 }
             }
         }
@@ -189,6 +274,16 @@ impl<G: GameArenaService> ClientRepo<G> {
             })
             .finish()
             .spawn(ctx);
+
+        login_queue
+            .into_actor(infrastructure)
+            .map(|result, _, _| {
+                if let Err(e) = result {
+                    error!("error putting login: {:?}", e);
+                }
+            })
+            .finish()
+            .spawn(ctx);
     }
 
     /// If the session is dirty with respect to the database, creates a session item to overwrite
@@ -199,15 +294,19 @@ impl<G: GameArenaService> ClientRepo<G> {
         player_id: PlayerId,
         client: &mut PlayerClientData<G>,
     ) -> Option<SessionItem> {
+        let (streak, streak_day) = client.streak.to_parts();
         let session_item = SessionItem {
+            achievements: client.achievements.to_bits(),
             alias: client.alias,
             arena_id,
             cohort_id: client.metrics.cohort_id,
+            commendations: client.commendations,
             date_created: client.metrics.date_created,
             date_previous: client.metrics.date_previous,
             date_renewed: client.metrics.date_renewed,
             date_terminated: None,
             game_id: G::GAME_ID,
+            login_type: client.login_type,
             player_id,
             plays: client.metrics.plays + client.metrics.previous_plays,
             moderator: client.moderator,
@@ -216,6 +315,8 @@ impl<G: GameArenaService> ClientRepo<G> {
             user_agent_id: client.metrics.user_agent_id,
             server_id,
             session_id: client.session_id,
+            streak,
+            streak_day,
         };
 
         if client.session_item.as_ref() != Some(&session_item) {
@@ -237,6 +338,7 @@ impl<G: GameArenaService> ClientRepo<G> {
         leaderboards: &LeaderboardRepo<G>,
         liveboard: &LiveboardRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
         system: Option<&SystemRepo<G>>,
         arena_id: ArenaId,
         server_id: Option<ServerId>,
@@ -265,6 +367,7 @@ impl<G: GameArenaService> ClientRepo<G> {
             message: Update::Client(ClientUpdate::SessionCreated {
                 arena_id,
                 cohort_id: client.metrics.cohort_id,
+                login_type: client.login_type,
                 server_id,
                 session_id: client.session_id,
                 player_id,
@@ -303,6 +406,8 @@ impl<G: GameArenaService> ClientRepo<G> {
             observer: register_observer.clone(),
         };
         let old_status = std::mem::replace(&mut client.status, new_status);
+        let alias = client.alias;
+        let observer = client.observer;
 
         match old_status {
             ClientStatus::Connected { observer } => {
@@ -316,17 +421,38 @@ impl<G: GameArenaService> ClientRepo<G> {
             }
             ClientStatus::Pending { .. } => {
                 metrics.start_visit(client);
+                let progress = client.progress.take();
 
                 drop(player);
 
                 // We previously left the game, so now we have to rejoin.
-                game.player_joined(player_tuple, &*players);
+                if !observer {
+                    game.player_joined(player_tuple, &*players);
+                    events.record(ArenaEvent::Joined { player_id, alias });
+
+                    // Continue where the player left off on another server (or process).
+                    if let Some(progress) = progress {
+                        let mut player = player_tuple.borrow_player_mut();
+                        player.score = progress.score;
+                        if let Some(client) = player.client_mut() {
+                            client.alias = progress.alias;
+                        }
+                        drop(player);
+                        // Players who weren't alive have no game-specific progress.
+                        if !progress.game.is_empty() {
+                            game.transfer_in(player_tuple, &progress.game, &*players);
+                        }
+                    }
+                }
             }
             ClientStatus::LeavingLimbo { .. } => {
                 drop(player);
 
                 // We previously left the game, so now we have to rejoin.
-                game.player_joined(player_tuple, &*players);
+                if !observer {
+                    game.player_joined(player_tuple, &*players);
+                    events.record(ArenaEvent::Joined { player_id, alias });
+                }
             }
         }
 
@@ -411,7 +537,7 @@ impl<G: GameArenaService> ClientRepo<G> {
         let player_update = players.delta(&*teams);
         let team_update = teams.delta(&*players);
         let immut_players = &*players;
-        let player_chat_team_updates: HashMap<PlayerId, _> = players
+        let player_chat_achievement_team_updates: HashMap<PlayerId, _> = players
             .iter_player_ids()
             .filter(|&id| {
                 !id.is_bot()
@@ -427,6 +553,7 @@ impl<G: GameArenaService> ClientRepo<G> {
                     player_id,
                     (
                         ChatRepo::<G>::player_delta(player_id, immut_players),
+                        ClientAchievementData::player_delta(player_id, immut_players),
                         teams.player_delta(player_id, immut_players).unwrap(),
                     ),
                 )
@@ -452,6 +579,13 @@ impl<G: GameArenaService> ClientRepo<G> {
                         &mut *client_data.data.borrow_mut(),
                         players,
                     ) {
+                        if client_data.visibility_audit {
+                            game.audit_visibility(
+                                player_tuple,
+                                &update,
+                                &mut *client_data.data.borrow_mut(),
+                            );
+                        }
                         let _ = observer.send(ObserverUpdate::Send {
                             message: Update::Game(update),
                         });
@@ -482,8 +616,8 @@ impl<G: GameArenaService> ClientRepo<G> {
                         }
                     }
 
-                    if let Some((chat_update, (members, joiners, joins))) =
-                        player_chat_team_updates.get(&player_id)
+                    if let Some((chat_update, achievement_update, (members, joiners, joins))) =
+                        player_chat_achievement_team_updates.get(&player_id)
                     {
                         if let Some(chat_update) = chat_update {
                             let _ = observer.send(ObserverUpdate::Send {
@@ -491,6 +625,12 @@ impl<G: GameArenaService> ClientRepo<G> {
                             });
                         }
 
+                        if let Some(achievement_update) = achievement_update {
+                            let _ = observer.send(ObserverUpdate::Send {
+                                message: Update::Achievement(achievement_update.clone()),
+                            });
+                        }
+
                         // TODO: We could get members on a per team basis.
                         if let Some(members) = members {
                             let _ = observer.send(ObserverUpdate::Send {
@@ -565,6 +705,7 @@ impl<G: GameArenaService> ClientRepo<G> {
         teams: &mut TeamRepo<G>,
         invitations: &mut InvitationRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
         server_id: Option<ServerId>,
         arena_id: ArenaId,
     ) {
@@ -574,6 +715,12 @@ impl<G: GameArenaService> ClientRepo<G> {
             return;
         }
 
+        let unix_now = get_unix_time_now();
+        self.redeemed_transfers
+            .retain(|_, &mut expiry| expiry >= unix_now);
+        self.banned_sessions.retain(|_, &mut until| until > now);
+        self.handed_off.retain(|_, (_, _, _, until)| *until > now);
+
         let immut_players = &*players;
         let to_forget: Vec<PlayerId> = immut_players
             .players
@@ -590,8 +737,13 @@ impl<G: GameArenaService> ClientRepo<G> {
                         ClientStatus::Limbo { expiry } => {
                             if &now >= expiry {
                                 client_data.status = ClientStatus::LeavingLimbo { since: now };
+                                let alias = client_data.alias;
+                                let observer = client_data.observer;
                                 drop(player);
-                                service.player_left(player_tuple, immut_players);
+                                if !observer {
+                                    service.player_left(player_tuple, immut_players);
+                                    events.record(ArenaEvent::Left { player_id, alias });
+                                }
                             }
                             false
                         }
@@ -643,6 +795,20 @@ impl<G: GameArenaService> ClientRepo<G> {
         players: &PlayerRepo<G>,
     ) -> Result<Option<G::GameUpdate>, &'static str> {
         if let Some(player_data) = players.get(player_id) {
+            let mut player = player_data.borrow_player_mut();
+            if player.is_observer() {
+                return Err("observers cannot send game commands");
+            }
+            if let Some(rate_limit) = player
+                .client_mut()
+                .and_then(|client| client.command_rate_limit.as_mut())
+            {
+                if rate_limit.should_limit_rate() {
+                    // Drop silently, to avoid logging each excess command.
+                    return Ok(None);
+                }
+            }
+            drop(player);
             // Game updates for all players are usually processed at once, but we also allow
             // one-off responses.
             Ok(service.player_command(command, player_data, players))
@@ -651,11 +817,15 @@ impl<G: GameArenaService> ClientRepo<G> {
         }
     }
 
+    /// How often restricted (new) players may change their alias.
+    const RESTRICTED_ALIAS_COOLDOWN: Duration = Duration::from_secs(2 * 60);
+
     /// Request a different alias (may not be done while alive).
     fn set_alias(
         player_id: PlayerId,
         alias: PlayerAlias,
         players: &PlayerRepo<G>,
+        chat: &ChatRepo<G>,
     ) -> Result<ClientUpdate, &'static str> {
         let mut player = players
             .borrow_player_mut(player_id)
@@ -669,8 +839,23 @@ impl<G: GameArenaService> ClientRepo<G> {
             return Err("cannot change alias while alive");
         }
 
+        let restricted = chat.restrictions.applies_to(&*player);
         let client = player.client_mut().ok_or("only clients can set alias")?;
         let censored_alias = PlayerAlias::new_sanitized(alias.as_str());
+        if censored_alias != client.alias {
+            if client.alias_overridden {
+                return Err("alias was overridden by a moderator");
+            }
+            if restricted
+                && client
+                    .alias_changed
+                    .map(|changed| changed.elapsed() < Self::RESTRICTED_ALIAS_COOLDOWN)
+                    .unwrap_or(false)
+            {
+                return Err("new players cannot change alias repeatedly");
+            }
+            client.alias_changed = Some(Instant::now());
+        }
         client.alias = censored_alias;
         Ok(ClientUpdate::AliasSet(censored_alias))
     }
@@ -792,19 +977,116 @@ impl<G: GameArenaService> ClientRepo<G> {
         }
     }
 
+    /// Stores a client's report of a desync, for investigation.
+    fn report_desync(
+        player_id: PlayerId,
+        message: String,
+        state: String,
+        players: &PlayerRepo<G>,
+        desync: &DesyncRepo<G>,
+    ) -> Result<ClientUpdate, &'static str> {
+        let mut player = players
+            .borrow_player_mut(player_id)
+            .ok_or("player doesn't exist")?;
+        let client = player
+            .client_mut()
+            .ok_or("only clients can report desyncs")?;
+
+        #[cfg(debug_assertions)]
+        let desync_report_limit = u8::MAX;
+        #[cfg(not(debug_assertions))]
+        let desync_report_limit = 3;
+
+        if client.desync_reports >= desync_report_limit {
+            return Err("too many desync reports");
+        }
+
+        desync.report(player_id, message, state)?;
+        client.desync_reports = client.desync_reports.saturating_add(1);
+        Ok(ClientUpdate::DesyncReported)
+    }
+
+    /// Issues a transfer to another server, giving up the player's progress on this one.
+    fn transfer(
+        player_id: PlayerId,
+        destination: ServerId,
+        service: &mut G,
+        server_id: Option<ServerId>,
+        players: &PlayerRepo<G>,
+        transfer_key: &[u8],
+    ) -> Result<ClientUpdate, &'static str> {
+        if server_id.is_none() {
+            return Err("cannot transfer without server ids");
+        }
+        if server_id == Some(destination) {
+            return Err("cannot transfer to same server");
+        }
+
+        let player_tuple = players.get(player_id).ok_or("player doesn't exist")?;
+        let player = player_tuple.borrow_player();
+        if player.is_observer() {
+            return Err("observers cannot transfer");
+        }
+        // The game may change the score while giving up progress.
+        let score = player.score;
+        drop(player);
+
+        let progress = service
+            .transfer_out(player_tuple)
+            .ok_or("cannot transfer right now")?;
+
+        let mut player = player_tuple.borrow_player_mut();
+        player.score = G::DEFAULT_SCORE;
+        let transfer = Transfer {
+            server_id: destination,
+            expiry: get_unix_time_now() + Transfer::VALIDITY,
+            nonce: generate_id_64().get(),
+            progress: Progress {
+                alias: player.alias(),
+                score,
+                game: progress,
+            },
+        };
+        info!("player {:?} transferring to {:?}", player_id, destination);
+        Ok(ClientUpdate::Transferred {
+            server_id: destination,
+            token: transfer.sign(transfer_key),
+        })
+    }
+
     /// Handles an arbitrary [`ClientRequest`].
     fn handle_client_request(
         &mut self,
         player_id: PlayerId,
         request: ClientRequest,
+        service: &mut G,
+        server_id: Option<ServerId>,
         players: &PlayerRepo<G>,
+        chat: &ChatRepo<G>,
         metrics: &mut MetricRepo<G>,
+        desync: &DesyncRepo<G>,
+        transfer_key: &[u8],
     ) -> Result<ClientUpdate, &'static str> {
         match request {
-            ClientRequest::SetAlias(alias) => Self::set_alias(player_id, alias, players),
+            ClientRequest::CreateArena { .. } | ClientRequest::JoinTournament => {
+                debug_assert!(false, "handled by infrastructure");
+                Err("cannot handle request here")
+            }
+            ClientRequest::ReportDesync { message, state } => {
+                Self::report_desync(player_id, message, state, players, desync)
+            }
+            ClientRequest::SetAlias(alias) => Self::set_alias(player_id, alias, players, chat),
             ClientRequest::TallyAd(ad_type) => Self::tally_ad(player_id, ad_type, players, metrics),
             ClientRequest::TallyFps(fps) => Self::tally_fps(player_id, fps, players),
             ClientRequest::Trace { message } => self.trace(player_id, message, players),
+            ClientRequest::Transfer(destination) => Self::transfer(
+                player_id,
+                destination,
+                service,
+                server_id,
+                players,
+                transfer_key,
+            ),
         }
     }
 
@@ -820,7 +1102,11 @@ impl<G: GameArenaService> ClientRepo<G> {
         teams: &mut TeamRepo<G>,
         chat: &mut ChatRepo<G>,
         invitations: &mut InvitationRepo<G>,
+        leaderboard: &mut LeaderboardRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
+        desync: &DesyncRepo<G>,
+        transfer_key: &[u8],
     ) -> Result<Option<Update<G::GameUpdate>>, &'static str> {
         match request {
             // Goes first (fast path).
@@ -829,17 +1115,33 @@ impl<G: GameArenaService> ClientRepo<G> {
                     .map(|u| u.map(Update::Game))
             }
             Request::Client(request) => self
-                .handle_client_request(player_id, request, &*players, metrics)
+                .handle_client_request(
+                    player_id,
+                    request,
+                    service,
+                    server_id,
+                    &*players,
+                    chat,
+                    metrics,
+                    desync,
+                    transfer_key,
+                )
                 .map(|u| Some(Update::Client(u))),
             Request::Chat(request) => chat
-                .handle_chat_request(player_id, request, service, players, teams, metrics)
+                .handle_chat_request(player_id, request, service, players, teams, metrics, events)
                 .map(|u| Some(Update::Chat(u))),
             Request::Invitation(request) => invitations
                 .handle_invitation_request(player_id, request, arena_id, server_id, players)
                 .map(|u| Some(Update::Invitation(u))),
+            Request::Leaderboard(request) => leaderboard
+                .handle_leaderboard_request(request)
+                .map(|u| Some(Update::Leaderboard(u))),
             Request::Player(request) => players
-                .handle_player_request(player_id, request, metrics)
+                .handle_player_request(player_id, request, chat, metrics, events)
                 .map(|u| Some(Update::Player(u))),
+            Request::Team(_) if players.is_observer(player_id) => {
+                Err("observers cannot join teams")
+            }
             Request::Team(request) => teams
                 .handle_team_request(player_id, request, players)
                 .map(|u| Some(Update::Team(u))),
@@ -862,6 +1164,13 @@ impl<G: GameArenaService> ClientRepo<G> {
         };
 
         client.metrics.rtt = Some(rtt);
+
+        // Let the client know, so it can be shown to the player.
+        if let ClientStatus::Connected { observer } = &client.status {
+            let _ = observer.send(ObserverUpdate::Send {
+                message: Update::Client(ClientUpdate::RttMeasured(rtt)),
+            });
+        }
     }
 }
 
@@ -877,14 +1186,24 @@ pub struct PlayerClientData<G: GameArenaService> {
     pub(crate) session_id: SessionId,
     /// Alias chosen by player.
     pub(crate) alias: PlayerAlias,
+    /// When the alias was last changed, if ever.
+    pub(crate) alias_changed: Option<Instant>,
+    /// Whether an admin overrode the alias, in which case it can't be changed for the rest of
+    /// the session.
+    pub(crate) alias_overridden: bool,
     /// Connection state.
     pub(crate) status: ClientStatus<G>,
     /// Discord user id.
     pub(crate) discord_id: Option<NonZeroU64>,
+    /// Provider the player signed in with, if any, persisted across sessions.
+    pub(crate) login_type: Option<LoginType>,
     /// Ip address.
     pub(crate) ip_address: IpAddr,
     /// Is moderator for in-game chat?
     pub moderator: bool,
+    /// Observes the whole arena (e.g. to cast a tournament) instead of playing. Doesn't join the
+    /// game, count as a real player, or send game commands.
+    pub observer: bool,
     /// Previous database item.
     pub(crate) session_item: Option<SessionItem>,
     /// Metrics-related information associated with each client.
@@ -897,8 +1216,27 @@ pub struct PlayerClientData<G: GameArenaService> {
     pub(crate) team: ClientTeamData,
     /// Players this client has reported.
     pub(crate) reported: HashSet<PlayerId>,
+    /// Players this client has commended.
+    pub(crate) commended: HashSet<PlayerId>,
+    /// Limits how quickly this client can commend players.
+    pub(crate) commend_rate_limit: RateLimiterState,
+    /// Commendations received from other players, persisted across sessions.
+    pub commendations: u32,
+    /// Achievement-related information associated with each client.
+    pub achievements: ClientAchievementData,
+    /// Days in a row the player met the game's daily goal, persisted across sessions.
+    pub streak: DailyStreak,
     /// Number of times sent error trace (in order to limit abuse).
     pub(crate) traces: u8,
+    /// Number of desync reports sent (in order to limit abuse).
+    pub(crate) desync_reports: u8,
+    /// Whether game updates are being audited (see [`GameArenaService::audit_visibility`]).
+    pub(crate) visibility_audit: bool,
+    /// Caps the rate of game commands, if an admin suspects automated inputs.
+    pub(crate) command_rate_limit: Option<RateLimiter>,
+    /// Progress from another server (or this server's previous process), restored upon joining
+    /// the game.
+    pub(crate) progress: Option<Progress>,
     /// Game specific client data. Manually serialized
     pub(crate) data: AtomicRefCell<G::ClientData>,
 }
@@ -927,23 +1265,37 @@ impl<G: GameArenaService> PlayerClientData<G> {
         discord_id: Option<NonZeroU64>,
         ip: IpAddr,
         moderator: bool,
+        observer: bool,
     ) -> Self {
         Self {
             session_id,
             alias: G::default_alias(),
+            alias_changed: None,
+            alias_overridden: false,
             status: ClientStatus::Pending {
                 expiry: Instant::now() + Duration::from_secs(10),
             },
             discord_id,
+            login_type: None,
             ip_address: ip,
             moderator,
+            observer,
             session_item: None,
             metrics,
             invitation: ClientInvitationData::new(invitation),
             chat: ClientChatData::default(),
             team: ClientTeamData::default(),
             reported: Default::default(),
+            commended: Default::default(),
+            commend_rate_limit: Default::default(),
+            commendations: 0,
+            achievements: ClientAchievementData::default(),
+            streak: DailyStreak::default(),
             traces: 0,
+            desync_reports: 0,
+            visibility_audit: false,
+            command_rate_limit: None,
+            progress: None,
             data: AtomicRefCell::new(G::ClientData::default()),
         }
     }
@@ -957,6 +1309,13 @@ impl<G: GameArenaService> PlayerClientData<G> {
     pub fn data_mut(&mut self) -> &mut G::ClientData {
         self.data.get_mut()
     }
+
+    /// Most recently measured network round trip time, if any.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.metrics
+            .rtt
+            .map(|rtt| Duration::from_millis(rtt as u64))
+    }
 }
 
 /// Handle client messages.
@@ -975,31 +1334,81 @@ impl<G: GameArenaService> Handler<ObserverMessage<Request<G::GameRequest>, Updat
                 player_id,
                 observer,
                 ..
-            } => self.context_service.context.clients.register(
-                player_id,
-                observer,
-                &mut self.context_service.context.players,
-                &mut self.context_service.context.teams,
-                &self.context_service.context.chat,
-                &self.leaderboard,
-                &self.context_service.context.liveboard,
-                &mut self.metrics,
-                self.system.as_ref(),
-                self.context_service.context.arena_id,
-                self.server_id,
-                &mut self.context_service.service,
-            ),
+            } => {
+                let context_service = self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id);
+                context_service.context.clients.register(
+                    player_id,
+                    observer,
+                    &mut context_service.context.players,
+                    &mut context_service.context.teams,
+                    &context_service.context.chat,
+                    &self.leaderboard,
+                    &context_service.context.liveboard,
+                    &mut self.metrics,
+                    &mut context_service.context.events,
+                    self.system.as_ref(),
+                    context_service.context.arena_id,
+                    self.server_id,
+                    &mut context_service.service,
+                )
+            }
             ObserverMessage::Unregister {
                 player_id,
                 observer,
-            } => self.context_service.context.clients.unregister(
+            } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                context
+                    .clients
+                    .unregister(player_id, observer, &context.players)
+            }
+            // Unlike other requests, these concern more than the player's current arena.
+            ObserverMessage::Request {
                 player_id,
-                observer,
-                &self.context_service.context.players,
-            ),
+                request: Request::Client(ClientRequest::CreateArena { bots, options }),
+            } => match self
+                .overflow
+                .create_private(&self.context_service, player_id, bots, options)
+            {
+                Ok(arena_id) => {
+                    let invitation_id = self.invitations.create_arena_invitation(
+                        arena_id,
+                        player_id,
+                        self.server_id,
+                    );
+                    let update = ClientUpdate::ArenaCreated(invitation_id);
+                    self.overflow
+                        .send_to_player(&self.context_service, player_id, update);
+                }
+                Err(s) => warn!("creating arena resulted in {}", s),
+            },
+            ObserverMessage::Request {
+                player_id,
+                request: Request::Client(ClientRequest::JoinTournament),
+            } => match self.tournament.join(player_id) {
+                Ok(tournament) => {
+                    let update = ClientUpdate::TournamentJoined(tournament);
+                    self.overflow
+                        .send_to_player(&self.context_service, player_id, update);
+                }
+                Err(s) => warn!("joining tournament resulted in {}", s),
+            },
             ObserverMessage::Request { player_id, request } => {
-                let context = &mut self.context_service.context;
-                let service = &mut self.context_service.service;
+                let context_service = self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id);
+                let context = &mut context_service.context;
+                let service = &mut context_service.service;
+                if matches!(request, Request::Game(_))
+                    && !self.tournament.may_play(context.arena_id, player_id)
+                {
+                    // Spectating a tournament they aren't (or are no longer) a participant of.
+                    return;
+                }
                 match context.clients.handle_observer_request(
                     player_id,
                     request,
@@ -1010,7 +1419,11 @@ impl<G: GameArenaService> Handler<ObserverMessage<Request<G::GameRequest>, Updat
                     &mut context.teams,
                     &mut context.chat,
                     &mut self.invitations,
+                    &mut self.leaderboard,
                     &mut self.metrics,
+                    &mut context.events,
+                    &context.desync,
+                    self.admin.transfer_key(),
                 ) {
                     Ok(Some(message)) => {
                         let player = match context.players.borrow_player_mut(player_id) {
@@ -1041,11 +1454,15 @@ impl<G: GameArenaService> Handler<ObserverMessage<Request<G::GameRequest>, Updat
                     }
                 }
             }
-            ObserverMessage::RoundTripTime { player_id, rtt } => self
-                .context_service
-                .context
-                .clients
-                .handle_observer_rtt(player_id, rtt, &self.context_service.context.players),
+            ObserverMessage::RoundTripTime { player_id, rtt } => {
+                let context = &mut self
+                    .overflow
+                    .arena_of_mut(&mut self.context_service, player_id)
+                    .context;
+                context
+                    .clients
+                    .handle_observer_rtt(player_id, rtt, &context.players)
+            }
         }
     }
 }
@@ -1065,21 +1482,26 @@ pub struct Authenticate {
     pub invitation_id: Option<InvitationId>,
     /// Oauth2 code.
     pub oauth2_code: Option<Oauth2Code>,
+    /// Admin auth, if connecting as an observer (see [`PlayerClientData::observer`]).
+    pub observer_auth: Option<String>,
+    /// Token from [`ClientUpdate::Transferred`], if continuing from another server.
+    pub transfer: Option<String>,
 }
 
 pub enum Oauth2Code {
     Discord(String),
+    Google(String),
+    Apple(String),
 }
 
 impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
     type Result = ResponseActFuture<Self, Result<PlayerId, &'static str>>;
 
     fn handle(&mut self, mut msg: Authenticate, _ctx: &mut ActorContext<Self>) -> Self::Result {
-        let arena_id = self.context_service.context.arena_id;
-        let clients = &mut self.context_service.context.clients;
-        let players = &self.context_service.context.players;
-
-        if clients
+        if self
+            .context_service
+            .context
+            .clients
             .authenticate_rate_limiter
             .should_limit_rate(msg.ip_address)
         {
@@ -1088,19 +1510,110 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
             return Box::pin(fut::ready(Err("rate limit exceeded")));
         }
 
+        if let Some((_, session_id)) = msg.arena_id_session_id {
+            let now = Instant::now();
+            if self.overflow.iter(&self.context_service).any(|arena| {
+                arena
+                    .context
+                    .clients
+                    .banned_sessions
+                    .get(&session_id)
+                    .map_or(false, |&until| until > now)
+            }) {
+                warn!(
+                    "IP {:?} tried to reconnect a banned session",
+                    msg.ip_address
+                );
+                return Box::pin(fut::ready(Err("banned")));
+            }
+        }
+
+        let clients = &mut self.context_service.context.clients;
+        let players = &self.context_service.context.players;
+
+        let observer = msg.observer_auth.is_some();
+        if let Some(auth) = msg.observer_auth.as_deref() {
+            if !self.admin.authenticate_observer(auth) {
+                warn!("IP {:?} failed observer auth", msg.ip_address);
+                return Box::pin(fut::ready(Err("invalid observer auth")));
+            }
+
+            // TODO: O(n) on players.
+            let observers = players.iter_borrow().filter(|p| p.is_observer()).count();
+            if observers >= clients.max_observers {
+                return Box::pin(fut::ready(Err("too many observers")));
+            }
+
+            // Observers always get a new session (in the primary arena), so they can't take over
+            // a player's.
+            msg.arena_id_session_id = None;
+        }
+
+        // An invalid transfer doesn't prevent connecting, but progress won't be restored.
+        let transfer = msg
+            .transfer
+            .as_deref()
+            .zip(self.server_id)
+            .filter(|_| !observer)
+            .and_then(|(token, server_id)| {
+                Transfer::verify(
+                    token,
+                    self.admin.transfer_key(),
+                    server_id,
+                    get_unix_time_now(),
+                )
+                .inspect_err(|e| warn!("IP {:?} sent invalid transfer: {}", msg.ip_address, e))
+                .ok()
+            })
+            .filter(|transfer| !clients.redeemed_transfers.contains_key(&transfer.nonce));
+
+        // A transferred player brings their progress with them, which can only be restored to a
+        // new player, so they don't resume any previous session on this server.
+        if transfer.is_some() {
+            msg.arena_id_session_id = None;
+        }
+
+        // Accepting an invitation to a private arena takes precedence over resuming a session in
+        // another arena.
+        let private_arena_id = msg
+            .invitation_id
+            .and_then(|id| self.invitations.get(id))
+            .map(|invitation| invitation.arena_id)
+            .filter(|&arena_id| self.overflow.is_private(arena_id));
+
         // TODO: O(n) on players.
-        let cached_session_id_player_id = msg
+        let cached_arena_id_session_id_player_id = msg
             .arena_id_session_id
-            .filter(|&(msg_arena_id, _)| arena_id == msg_arena_id)
-            .and_then(|(_, msg_session_id)| {
-                players
+            .filter(|&(msg_arena_id, _)| private_arena_id.map_or(true, |id| id == msg_arena_id))
+            .and_then(|(msg_arena_id, msg_session_id)| {
+                self.overflow
+                    .iter(&self.context_service)
+                    .find(|arena| arena.context.arena_id == msg_arena_id)?
+                    .context
+                    .players
                     .iter_borrow()
                     .find(|p| {
                         p.client()
                             .map(|c| c.session_id == msg_session_id)
                             .unwrap_or(false)
                     })
-                    .map(|p| (msg_session_id, p.player_id))
+                    .map(|p| (msg_arena_id, msg_session_id, p.player_id))
+            });
+
+        // The session may be from this server's previous process (in any of its arenas), instead
+        // of in memory.
+        let handed_off = msg
+            .arena_id_session_id
+            .filter(|_| cached_arena_id_session_id_player_id.is_none())
+            .and_then(|(_, msg_session_id)| {
+                self.context_service
+                    .context
+                    .clients
+                    .handed_off
+                    .remove(&msg_session_id)
+                    .map(|(player_id, arena_id, progress, _)| {
+                        (msg_session_id, player_id, arena_id, progress)
+                    })
             });
 
         let arena_id_session_id = msg.arena_id_session_id;
@@ -1108,21 +1621,29 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
         let database = self.database();
         let discord_bot = self.discord_bot;
         let discord_oauth2 = self.discord_oauth2;
+        let google_oauth2 = self.google_oauth2;
+        let apple_oauth2 = self.apple_oauth2;
 
         Box::pin(
             async move {
-                let discord_id = if let Some((Oauth2Code::Discord(code), discord_oauth2)) =
-                    oauth2_code.zip(discord_oauth2)
-                {
-                    match discord_oauth2.authenticate(code).await {
-                        Ok(id) => Some(id),
-                        Err(e) => {
-                            warn!("{}", e);
-                            None
-                        }
+                let (discord_id, login) = match oauth2_code {
+                    Some(Oauth2Code::Discord(code)) => {
+                        let discord_id = match discord_oauth2 {
+                            Some(discord_oauth2) => match discord_oauth2.authenticate(code).await {
+                                Ok(id) => Some(id),
+                                Err(e) => {
+                                    warn!("{}", e);
+                                    None
+                                }
+                            },
+                            None => None,
+                        };
+                        let login = discord_id.map(|id| (LoginType::Discord, id.to_string()));
+                        (discord_id, login)
                     }
-                } else {
-                    None
+                    Some(Oauth2Code::Google(code)) => (None, sign_in(google_oauth2, code).await),
+                    Some(Oauth2Code::Apple(code)) => (None, sign_in(apple_oauth2, code).await),
+                    None => (None, None),
                 };
 
                 let is_moderator =
@@ -1138,7 +1659,26 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
                         false
                     };
 
-                let session_item = if cached_session_id_player_id.is_some() {
+                let login_item = if let Some((login_type, id)) = login.clone() {
+                    match database.get_login(login_type, id).await {
+                        Ok(login_item) => login_item,
+                        Err(e) => {
+                            warn!("error getting login: {:?}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                // Signing in resumes the session the login is bound to, instead of whichever
+                // session the browser remembers (if any).
+                let session_item = if let Some((arena_id, session_id)) = login_item
+                    .as_ref()
+                    .and_then(|login_item| login_item.arena_id.zip(login_item.session_id))
+                {
+                    database.get_session(arena_id, session_id).await
+                } else if cached_arena_id_session_id_player_id.is_some() {
                     // No need to load from database because session is in memory.
                     Result::Ok(None)
                 } else if let Some((arena_id, session_id)) = arena_id_session_id {
@@ -1148,64 +1688,119 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
                     Result::Ok(None)
                 };
 
-                (discord_id, is_moderator, session_item)
+                (discord_id, is_moderator, login, login_item, session_item)
             }
             .into_actor(self)
             .map(
-                move |(discord_id, mut is_moderator, db_result), act, _ctx| {
+                move |(discord_id, mut is_moderator, login, login_item, db_result), act, _ctx| {
                     let invitation = msg
                         .invitation_id
                         .and_then(|id| act.invitations.get(id).cloned());
-                    let invitation_dto = invitation.map(|i| InvitationDto {
+                    let invitation_dto = invitation.as_ref().map(|i| InvitationDto {
                         player_id: i.player_id,
                     });
 
                     let mut client_metric_data = ClientMetricData::from(&msg);
 
-                    let restore_session_id_player_id = if let Ok(Some(session_item)) = db_result {
+                    let mut commendations = 0;
+                    let mut achievements = 0;
+                    let mut streak = DailyStreak::default();
+                    let mut login_type = login.as_ref().map(|&(login_type, _)| login_type);
+                    let restore_arena_id_session_id_player_id = if let Ok(Some(session_item)) =
+                        db_result
+                    {
                         client_metric_data.supplement(&session_item);
                         // Restore moderator status.
                         is_moderator |= session_item.moderator;
-                        (session_item.arena_id == arena_id)
-                            .then_some((session_item.session_id, session_item.player_id))
+                        commendations = session_item.commendations;
+                        achievements = session_item.achievements;
+                        streak =
+                            DailyStreak::from_parts(session_item.streak, session_item.streak_day);
+                        login_type = login_type.or(session_item.login_type);
+                        let arena_id = session_item.arena_id;
+                        let exists = act
+                            .overflow
+                            .iter(&act.context_service)
+                            .any(|arena| arena.context.arena_id == arena_id);
+                        (exists && private_arena_id.map_or(true, |id| id == arena_id)).then_some((
+                            arena_id,
+                            session_item.session_id,
+                            session_item.player_id,
+                        ))
                     } else {
                         None
                     };
 
-                    let (session_id, player_id) = if let Some(existing) =
-                        cached_session_id_player_id.or(restore_session_id_player_id)
+                    // A handed off player reclaims their id, unless a new player took it meanwhile.
+                    let (handed_off_session_id_player_id, handed_off_arena_id, handed_off_progress) =
+                        match handed_off {
+                            Some((session_id, player_id, arena_id, progress))
+                                if !act.overflow.contains(&act.context_service, player_id) =>
+                            {
+                                (Some((session_id, player_id)), Some(arena_id), Some(progress))
+                            }
+                            _ => (None, None, None),
+                        };
+
+                    let login_arena_id_session_id = login_item
+                        .as_ref()
+                        .and_then(|login_item| login_item.arena_id.zip(login_item.session_id));
+                    let (arena_id, session_id, player_id) = if let Some(existing) =
+                        cached_arena_id_session_id_player_id
+                            .filter(|_| login_arena_id_session_id.is_none())
+                            .or(restore_arena_id_session_id_player_id)
+                            .filter(|_| transfer.is_none())
                     {
                         existing
                     } else {
-                        let mut session_ids = HashSet::with_capacity(
-                            act.context_service.context.players.real_players,
-                        );
-
-                        // TODO: O(n) on players.
-                        for player in act.context_service.context.players.iter_borrow() {
-                            if let Some(client_data) = player.client() {
-                                session_ids.insert(client_data.session_id);
-                            }
-                        }
-
-                        let new_session_id = loop {
-                            let session_id = SessionId(generate_id_64());
-                            if !session_ids.contains(&session_id) {
-                                break session_id;
-                            }
+                        let arena_id = if observer {
+                            act.context_service.context.arena_id
+                        } else if let Some(arena_id) = handed_off_arena_id.filter(|&arena_id| {
+                            private_arena_id.map_or(true, |id| id == arena_id)
+                                && act
+                                    .overflow
+                                    .iter(&act.context_service)
+                                    .any(|arena| arena.context.arena_id == arena_id)
+                        }) {
+                            // Rejoin the arena whose world was handed off along with the player.
+                            arena_id
+                        } else {
+                            act.overflow
+                                .arena_for_join(&act.context_service, invitation.as_ref())
                         };
 
-                        let new_player_id = loop {
-                            let player_id = PlayerId(generate_id());
-                            if !act.context_service.context.players.contains(player_id) {
-                                break player_id;
-                            }
-                        };
+                        let (session_id, player_id) = handed_off_session_id_player_id
+                            .unwrap_or_else(|| act.overflow.new_ids(&act.context_service));
 
-                        (new_session_id, new_player_id)
+                        (arena_id, session_id, player_id)
                     };
 
-                    match act.context_service.context.players.players.entry(player_id) {
+                    // The arena may have emptied out, and been removed, in the meantime.
+                    if act
+                        .overflow
+                        .get_mut(&mut act.context_service, arena_id)
+                        .is_none()
+                    {
+                        return Err("arena no longer exists");
+                    }
+
+                    // Only redeem the transfer now that it is sure to be applied (to a new
+                    // player), in case the same one was presented again in the meantime.
+                    let transfer = transfer.filter(|transfer| {
+                        act.context_service
+                            .context
+                            .clients
+                            .redeemed_transfers
+                            .insert(transfer.nonce, transfer.expiry)
+                            .is_none()
+                    });
+
+                    let context_service = act
+                        .overflow
+                        .get_mut(&mut act.context_service, arena_id)
+                        .unwrap();
+
+                    match context_service.context.players.players.entry(player_id) {
                         Entry::Occupied(mut occupied) => {
                             if let Some(client) =
                                 occupied.get_mut().borrow_player_mut().client_mut()
@@ -1217,6 +1812,7 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
                                     client.discord_id = Some(discord_id);
                                     client.moderator = is_moderator;
                                 }
+                                client.login_type = login_type.or(client.login_type);
                             } else {
                                 debug_assert!(
                                     false,
@@ -1225,23 +1821,157 @@ impl<G: GameArenaService> Handler<Authenticate> for Infrastructure<G> {
                             }
                         }
                         Entry::Vacant(vacant) => {
-                            let client = PlayerClientData::new(
+                            let mut client = PlayerClientData::new(
                                 session_id,
                                 client_metric_data,
                                 invitation_dto,
                                 discord_id,
                                 msg.ip_address,
                                 is_moderator,
+                                observer,
                             );
+                            client.commendations = commendations;
+                            client.achievements = ClientAchievementData::from_bits(achievements);
+                            client.streak = streak;
+                            client.login_type = login_type;
+                            client.progress = handed_off_progress.or(transfer.map(|t| t.progress));
                             let pd = PlayerData::new(player_id, Some(Box::new(client)));
                             let pt = Arc::new(PlayerTuple::new(pd));
                             vacant.insert(pt);
                         }
                     }
 
+                    // Bind the login to the session, so signing in elsewhere resumes it.
+                    if let Some((login_type, id)) = login.filter(|_| !observer) {
+                        if login_arena_id_session_id != Some((arena_id, session_id)) {
+                            let user_id = login_item
+                                .map(|login_item| login_item.user_id)
+                                .unwrap_or_else(|| UserId(generate_id_64()));
+                            context_service
+                                .context
+                                .clients
+                                .pending_login_write
+                                .push(LoginItem {
+                                    login_type,
+                                    id,
+                                    user_id,
+                                    arena_id: Some(arena_id),
+                                    session_id: Some(session_id),
+                                });
+                        }
+                    }
+
+                    // Entrants of a tournament are invited to its arena once it starts.
+                    if let Some(invitation_id) = msg.invitation_id.filter(|_| !observer) {
+                        act.tournament.admit(invitation_id, player_id);
+                    }
+
                     Ok(player_id)
                 },
             ),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{Authenticate, ClientRepo, ClientStatus, PlayerClientData};
+    use crate::game_service::MockGame;
+    use crate::invitation::InvitationRepo;
+    use crate::log::EventLogRepo;
+    use crate::metric::MetricRepo;
+    use crate::player::{PlayerData, PlayerRepo, PlayerTuple};
+    use crate::team::TeamRepo;
+    use core_protocol::id::{ArenaId, PlayerId, SessionId};
+    use server_util::rate_limiter::{RateLimiter, RateLimiterProps};
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::num::{NonZeroU32, NonZeroU64};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn limbo() {
+        let arena_id = ArenaId(NonZeroU32::new(1).unwrap());
+        let player_id = PlayerId(NonZeroU32::new(1).unwrap());
+        let mut repo = ClientRepo::<MockGame>::new(None, RateLimiterProps::no_limit(), 0);
+        let mut service = MockGame;
+        let mut players = PlayerRepo::new();
+        let mut teams = TeamRepo::new();
+        let mut invitations = InvitationRepo::new();
+        let mut metrics = MetricRepo::new();
+        let mut events = EventLogRepo::new(arena_id, None);
+
+        let authenticate = Authenticate {
+            ip_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            user_agent_id: None,
+            referrer: None,
+            arena_id_session_id: None,
+            invitation_id: None,
+            oauth2_code: None,
+            observer_auth: None,
+            transfer: None,
+        };
+        let mut client = PlayerClientData::new(
+            SessionId(NonZeroU64::new(1).unwrap()),
+            (&authenticate).into(),
+            None,
+            None,
+            authenticate.ip_address,
+            false,
+            false,
+        );
+        client.metrics.visit_started = Some(Instant::now());
+        client.status = ClientStatus::Limbo {
+            expiry: Instant::now() + Duration::from_secs(60),
+        };
+        players.insert(
+            player_id,
+            Arc::new(PlayerTuple::new(PlayerData::new(
+                player_id,
+                Some(Box::new(client)),
+            ))),
+        );
+
+        let mut prune = |players: &mut PlayerRepo<MockGame>| {
+            repo.prune_rate_limiter = RateLimiter::no_limit();
+            repo.prune(
+                &mut service,
+                players,
+                &mut teams,
+                &mut invitations,
+                &mut metrics,
+                &mut events,
+                None,
+                arena_id,
+            );
+        };
+
+        // Remains in game for the duration of limbo.
+        prune(&mut players);
+        assert!(players.borrow_player(player_id).unwrap().is_in_limbo());
+
+        // Leaves the game once limbo expires.
+        players
+            .borrow_player_mut(player_id)
+            .unwrap()
+            .client_mut()
+            .unwrap()
+            .status = ClientStatus::Limbo {
+            expiry: Instant::now(),
+        };
+        prune(&mut players);
+        assert!(matches!(
+            players
+                .borrow_player(player_id)
+                .unwrap()
+                .client()
+                .unwrap()
+                .status,
+            ClientStatus::LeavingLimbo { .. }
+        ));
+
+        // Is forgotten once no longer alive.
+        prune(&mut players);
+        assert!(!players.contains(player_id));
+    }
+}
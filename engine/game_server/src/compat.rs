@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use bincode::Options as _;
+use core_protocol::rpc::{Request, Update};
+use core_protocol::web_socket::{PREVIOUS_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Layouts of [`Request`] and [`Update`] as of [`PREVIOUS_PROTOCOL_VERSION`].
+///
+/// The current version only added [`WebSocketQuery::login_state`], so they are unchanged. Clients
+/// of the previous version can't complete a sign in, since they don't send it, but can otherwise
+/// play. When a change alters either layout, replace these with frozen copies of the outgoing
+/// types, and translate between them here.
+///
+/// [`WebSocketQuery::login_state`]: core_protocol::rpc::WebSocketQuery::login_state
+mod previous {
+    pub type Request<GR> = core_protocol::rpc::Request<GR>;
+    pub type Update<GU> = core_protocol::rpc::Update<GU>;
+
+    pub fn upgrade_request<GR>(request: Request<GR>) -> super::Request<GR> {
+        request
+    }
+
+    pub fn downgrade_update<GU>(update: super::Update<GU>) -> Update<GU> {
+        update
+    }
+}
+
+/// Decodes a binary request from a client of a supported protocol `version`.
+pub(crate) fn decode_request<GR: DeserializeOwned>(
+    version: u32,
+    bytes: &[u8],
+    limit: usize,
+) -> Result<Request<GR>, bincode::Error> {
+    let options = bincode::DefaultOptions::new()
+        .with_limit(limit as u64)
+        .with_fixint_encoding()
+        .allow_trailing_bytes();
+
+    if version == PREVIOUS_PROTOCOL_VERSION {
+        options
+            .deserialize::<previous::Request<GR>>(bytes)
+            .map(previous::upgrade_request)
+    } else {
+        debug_assert_eq!(version, PROTOCOL_VERSION);
+        options.deserialize(bytes)
+    }
+}
+
+/// Encodes an update for a client of a supported protocol `version`.
+pub(crate) fn encode_update<GU: Serialize>(version: u32, update: Update<GU>) -> Vec<u8> {
+    if version == PREVIOUS_PROTOCOL_VERSION {
+        bincode::serialize(&previous::downgrade_update(update))
+    } else {
+        debug_assert_eq!(version, PROTOCOL_VERSION);
+        bincode::serialize(&update)
+    }
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compat::{decode_request, encode_update, previous};
+    use core_protocol::id::{ArenaId, CohortId, LoginType, PlayerId, SessionId};
+    use core_protocol::rpc::{ClientRequest, ClientUpdate, Request, Update};
+    use core_protocol::web_socket::PREVIOUS_PROTOCOL_VERSION;
+    use std::num::{NonZeroU32, NonZeroU64, NonZeroU8};
+
+    #[test]
+    fn previous_version_round_trip() {
+        // Encoded as a client of the previous version would.
+        let request: previous::Request<()> = Request::Client(ClientRequest::TallyFps(60.0));
+        let bytes = bincode::serialize(&request).unwrap();
+        let request = decode_request::<()>(PREVIOUS_PROTOCOL_VERSION, &bytes, 1024).unwrap();
+        assert!(matches!(request, Request::Client(ClientRequest::TallyFps(fps)) if fps == 60.0));
+
+        let session_id = SessionId(NonZeroU64::new(5).unwrap());
+        let update: Update<()> = Update::Client(ClientUpdate::SessionCreated {
+            arena_id: ArenaId(NonZeroU32::new(1).unwrap()),
+            cohort_id: CohortId(NonZeroU8::new(1).unwrap()),
+            login_type: Some(LoginType::Discord),
+            server_id: None,
+            session_id,
+            player_id: PlayerId(NonZeroU32::new(7).unwrap()),
+        });
+        let bytes = encode_update(PREVIOUS_PROTOCOL_VERSION, update);
+        // Decoded as a client of the previous version would.
+        let update: previous::Update<()> = bincode::deserialize(&bytes).unwrap();
+        assert!(matches!(
+            update,
+            Update::Client(ClientUpdate::SessionCreated { session_id: s, .. }) if s == session_id
+        ));
+    }
+}
@@ -2,14 +2,19 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::bot::BotRepo;
-use crate::chat::ChatRepo;
+use crate::chat::{ChatRepo, NewPlayerRestrictions};
 use crate::client::ClientRepo;
+use crate::desync::DesyncRepo;
 use crate::game_service::GameArenaService;
 use crate::liveboard::LiveboardRepo;
+use crate::log::EventLogRepo;
 use crate::player::PlayerRepo;
+use crate::season::SeasonRepo;
 use crate::team::TeamRepo;
-use core_protocol::id::ArenaId;
+use core_protocol::id::{ArenaId, PlayerId};
+use core_protocol::name::ClassName;
 use server_util::rate_limiter::RateLimiterProps;
+use std::time::Duration;
 
 /// Things that go along with every instance of a [`GameArenaService`].
 pub struct Context<G: GameArenaService> {
@@ -20,6 +25,15 @@ pub struct Context<G: GameArenaService> {
     pub(crate) chat: ChatRepo<G>,
     pub teams: TeamRepo<G>,
     pub(crate) liveboard: LiveboardRepo<G>,
+    pub events: EventLogRepo<G>,
+    pub(crate) desync: DesyncRepo<G>,
+    pub season: SeasonRepo<G>,
+    /// Submitted by [`Self::submit_final_score`], for the daily leaderboard.
+    pub(crate) final_scores: Vec<(PlayerId, u32)>,
+    /// Submitted by [`Self::submit_class_score`], for the class leaderboards.
+    pub(crate) class_scores: Vec<(PlayerId, ClassName, u32)>,
+    /// See [`Self::tick_period`].
+    pub(crate) tick_period: Duration,
 }
 
 impl<G: GameArenaService> Context<G> {
@@ -27,17 +41,53 @@ impl<G: GameArenaService> Context<G> {
         arena_id: ArenaId,
         bots: BotRepo<G>,
         chat_log: Option<String>,
+        event_log: Option<String>,
         trace_log: Option<String>,
+        desync_dir: Option<String>,
         client_authenticate: RateLimiterProps,
+        max_observers: usize,
+        new_player_restrictions: NewPlayerRestrictions,
+        season_period: Option<Duration>,
+        tick_period: Duration,
     ) -> Self {
         Context {
             arena_id,
-            clients: ClientRepo::new(trace_log, client_authenticate),
+            clients: ClientRepo::new(trace_log, client_authenticate, max_observers),
             bots,
             players: PlayerRepo::new(),
             teams: TeamRepo::new(),
-            chat: ChatRepo::new(chat_log),
+            chat: ChatRepo::new(chat_log, new_player_restrictions),
             liveboard: LiveboardRepo::new(),
+            events: EventLogRepo::new(arena_id, event_log),
+            desync: DesyncRepo::new(desync_dir),
+            season: SeasonRepo::new(season_period),
+            final_scores: Vec::new(),
+            class_scores: Vec::new(),
+            tick_period,
         }
     }
+
+    /// How much time each call to [`GameArenaService::tick`] should simulate, which may differ
+    /// from [`GameArenaService::TICK_PERIOD_SECS`] if the arena was configured to run at another
+    /// rate (see `--tick-rate`).
+    pub fn tick_period(&self) -> Duration {
+        self.tick_period
+    }
+
+    /// Broadcasts a chat message from the server to all players.
+    pub fn announce(&mut self, text: String) {
+        self.chat.announce(text, &mut self.players);
+    }
+
+    /// Submits a player's final score, such as at the end of a timed round, to be considered for
+    /// the daily leaderboard even if it was never high enough for the liveboard.
+    pub fn submit_final_score(&mut self, player_id: PlayerId, score: u32) {
+        self.final_scores.push((player_id, score));
+    }
+
+    /// Submits the score a player achieved in a particular class, such as a type of ship, to be
+    /// considered for that class's leaderboards.
+    pub fn submit_class_score(&mut self, player_id: PlayerId, class: ClassName, score: u32) {
+        self.class_scores.push((player_id, class, score));
+    }
 }
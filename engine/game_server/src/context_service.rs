@@ -2,37 +2,164 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::bot::BotRepo;
+use crate::chat::NewPlayerRestrictions;
+use crate::client::ClientStatus;
 use crate::context::Context;
-use crate::game_service::GameArenaService;
-use crate::invitation::InvitationRepo;
+use crate::game_service::{ArenaOptions, GameArenaService};
+use crate::invitation::{Invitation, InvitationRepo};
 use crate::leaderboard::LeaderboardRepo;
 use crate::metric::MetricRepo;
+use crate::unwrap_or_return;
 use core_protocol::dto::ServerDto;
-use core_protocol::id::{ArenaId, ServerId};
+use core_protocol::id::{ArenaId, PlayerId, ServerId, SessionId};
+use core_protocol::rpc::{ClientUpdate, Update};
+use log::info;
+use server_util::generate_id::{generate_id, generate_id_64};
+use server_util::ip_rate_limiter::IpRateLimiter;
+use server_util::observer::ObserverUpdate;
 use server_util::rate_limiter::RateLimiterProps;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Contains a [`GameArenaService`] and the corresponding [`Context`].
 pub struct ContextService<G: GameArenaService> {
     pub context: Context<G>,
     pub service: G,
+    /// When the arena should next be updated (see [`Context::tick_period`]).
+    next_update: Option<Instant>,
+}
+
+/// Everything needed to create an arena, so that more arenas can be created on demand (see
+/// [`OverflowArenas`]).
+#[derive(Clone)]
+pub struct ArenaTemplate {
+    pub min_bots: Option<usize>,
+    pub max_bots: Option<usize>,
+    pub bot_percent: Option<usize>,
+    pub arena_options: ArenaOptions,
+    pub chat_log: Option<String>,
+    pub event_log: Option<String>,
+    pub trace_log: Option<String>,
+    pub desync_dir: Option<String>,
+    pub client_authenticate: RateLimiterProps,
+    pub max_observers: usize,
+    pub new_player_restrictions: NewPlayerRestrictions,
+    pub season_period: Option<Duration>,
+    pub tick_period: Duration,
+}
+
+impl ArenaTemplate {
+    /// The template for an additional arena, which logs to its own files so that arenas don't
+    /// write (and rotate) the same ones.
+    pub fn for_arena(&self, arena_id: ArenaId) -> Self {
+        let per_arena = |path: &Option<String>| path.as_deref().map(|p| arena_path(p, arena_id));
+        Self {
+            chat_log: per_arena(&self.chat_log),
+            event_log: per_arena(&self.event_log),
+            trace_log: per_arena(&self.trace_log),
+            desync_dir: per_arena(&self.desync_dir),
+            ..self.clone()
+        }
+    }
+}
+
+/// Inserts the arena id before the extension (if any) of a path, e.g. `events.log` becomes
+/// `events_1234.log`.
+fn arena_path(path: &str, arena_id: ArenaId) -> String {
+    let path = Path::new(path);
+    let mut file_name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    write!(file_name, "_{}", arena_id).unwrap();
+    if let Some(extension) = path.extension() {
+        file_name.push('.');
+        file_name.push_str(&extension.to_string_lossy());
+    }
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
 }
 
 impl<G: GameArenaService> ContextService<G> {
+    pub fn from_template(arena_id: ArenaId, template: &ArenaTemplate) -> Self {
+        let template = template.clone();
+        Self::new(
+            arena_id,
+            template.min_bots,
+            template.max_bots,
+            template.bot_percent,
+            template.arena_options,
+            template.chat_log,
+            template.event_log,
+            template.trace_log,
+            template.desync_dir,
+            template.client_authenticate,
+            template.max_observers,
+            template.new_player_restrictions,
+            template.season_period,
+            template.tick_period,
+        )
+    }
+
     pub fn new(
         arena_id: ArenaId,
         min_bots: Option<usize>,
         max_bots: Option<usize>,
         bot_percent: Option<usize>,
+        arena_options: ArenaOptions,
         chat_log: Option<String>,
+        event_log: Option<String>,
         trace_log: Option<String>,
+        desync_dir: Option<String>,
         client_authenticate: RateLimiterProps,
+        max_observers: usize,
+        new_player_restrictions: NewPlayerRestrictions,
+        season_period: Option<Duration>,
+        tick_period: Duration,
     ) -> Self {
         let bots = BotRepo::new_from_options(min_bots, max_bots, bot_percent);
 
         Self {
-            service: G::new(bots.min_bots),
-            context: Context::new(arena_id, bots, chat_log, trace_log, client_authenticate),
+            service: G::new(bots.min_bots, &arena_options),
+            context: Context::new(
+                arena_id,
+                bots,
+                chat_log,
+                event_log,
+                trace_log,
+                desync_dir,
+                client_authenticate,
+                max_observers,
+                new_player_restrictions,
+                season_period,
+                tick_period,
+            ),
+            next_update: None,
+        }
+    }
+
+    /// Returns true iff it is time to update the arena, in which case the next update is
+    /// scheduled. Updates are attempted once per `update_period`, which may be shorter than the
+    /// arena's own tick period. They are scheduled at a fixed rate, so the arena keeps pace with
+    /// real time on average.
+    pub(crate) fn is_due(&mut self, now: Instant, update_period: Duration) -> bool {
+        let tick_period = self.context.tick_period;
+        match self.next_update {
+            Some(next_update) if now + update_period / 2 < next_update => false,
+            Some(next_update) if now < next_update + tick_period => {
+                self.next_update = Some(next_update + tick_period);
+                true
+            }
+            // First update, or fell more than a tick behind (don't try to catch up).
+            _ => {
+                self.next_update = Some(now + tick_period);
+                true
+            }
         }
     }
 
@@ -51,6 +178,7 @@ impl<G: GameArenaService> ContextService<G> {
             &mut self.context.teams,
             invitations,
             metrics,
+            &mut self.context.events,
             server_id,
             self.context.arena_id,
         );
@@ -60,6 +188,7 @@ impl<G: GameArenaService> ContextService<G> {
 
         // Update game logic.
         self.service.tick(&mut self.context);
+        self.context.desync.update(&self.service);
         self.context.players.update_is_alive_and_team_id(
             &mut self.service,
             &mut self.context.teams,
@@ -80,14 +209,433 @@ impl<G: GameArenaService> ContextService<G> {
             .update(&self.service, &self.context.players);
 
         leaderboard.process(&self.context.liveboard, &self.context.players);
+        leaderboard
+            .process_final_scores(self.context.final_scores.drain(..), &self.context.players);
+        leaderboard
+            .process_class_scores(self.context.class_scores.drain(..), &self.context.players);
 
         // Post-update game logic.
         self.service.post_update(&mut self.context);
 
+        // Rate limited internally.
+        self.context.season.update(
+            &mut self.service,
+            &mut self.context.players,
+            &mut self.context.chat,
+            &mut self.context.events,
+        );
+
         // Bot commands/joining/leaving, postponed because no commands should be issued between
         // `GameService::tick` and `GameService::post_update`.
         self.context
             .bots
             .post_update(&mut self.service, &self.context.players);
+
+        // Rate limited internally.
+        self.context.events.update();
+    }
+}
+
+/// Additional arenas in the same server process, created when every arena is at capacity during
+/// a population spike, or by players who want a private arena, and removed once their real
+/// players leave. The primary arena, which is always present, is stored separately (see
+/// [`Infrastructure::context_service`]).
+///
+/// [`Infrastructure::context_service`]: crate::infrastructure::Infrastructure
+pub struct OverflowArenas<G: GameArenaService> {
+    pub(crate) arenas: Vec<ContextService<G>>,
+    /// Real players per arena, above which new players join another arena. If [`None`], all
+    /// players join the primary arena.
+    capacity: Option<usize>,
+    template: ArenaTemplate,
+    /// Tick period of public overflow arenas, which may differ from that of the primary and
+    /// private arenas (see `--overflow-tick-rate`).
+    overflow_tick_period: Duration,
+    /// Arenas that can only be joined by invitation, and who created them.
+    private: HashMap<ArenaId, PlayerId>,
+    /// Limits how often each IP address may create private arenas.
+    create_private_rate_limiter: IpRateLimiter,
+    /// Arenas that are kept, even without real players, until their players have had a chance to
+    /// join them.
+    kept_until: HashMap<ArenaId, Instant>,
+}
+
+impl<G: GameArenaService> OverflowArenas<G> {
+    /// Maximum number of private arenas at once, since each costs as much as a public one.
+    const MAX_PRIVATE: usize = 8;
+    /// Maximum number of private arenas created by the same player at once.
+    const MAX_PRIVATE_PER_PLAYER: usize = 1;
+    /// How long a private arena is kept before its first player joins.
+    const PRIVATE_GRACE: Duration = Duration::from_secs(60);
+
+    pub fn new(
+        capacity: Option<usize>,
+        template: ArenaTemplate,
+        overflow_tick_period: Duration,
+    ) -> Self {
+        Self {
+            arenas: Vec::new(),
+            capacity,
+            template,
+            overflow_tick_period,
+            private: HashMap::new(),
+            create_private_rate_limiter: IpRateLimiter::new(Duration::from_secs(60), 2),
+            kept_until: HashMap::new(),
+        }
+    }
+
+    /// Iterates the primary arena, followed by the overflow arenas.
+    pub(crate) fn iter<'a>(
+        &'a self,
+        primary: &'a ContextService<G>,
+    ) -> impl Iterator<Item = &'a ContextService<G>> {
+        std::iter::once(primary).chain(self.arenas.iter())
+    }
+
+    /// Mutably iterates the primary arena, followed by the overflow arenas.
+    pub(crate) fn iter_mut<'a>(
+        &'a mut self,
+        primary: &'a mut ContextService<G>,
+    ) -> impl Iterator<Item = &'a mut ContextService<G>> {
+        std::iter::once(primary).chain(self.arenas.iter_mut())
+    }
+
+    /// Gets the arena with a given id, if it still exists.
+    pub(crate) fn get_mut<'a>(
+        &'a mut self,
+        primary: &'a mut ContextService<G>,
+        arena_id: ArenaId,
+    ) -> Option<&'a mut ContextService<G>> {
+        self.iter_mut(primary)
+            .find(|arena| arena.context.arena_id == arena_id)
+    }
+
+    /// Gets the arena a player is in, defaulting to the primary arena.
+    pub(crate) fn arena_of<'a>(
+        &'a self,
+        primary: &'a ContextService<G>,
+        player_id: PlayerId,
+    ) -> &'a ContextService<G> {
+        self.arenas
+            .iter()
+            .find(|arena| arena.context.players.contains(player_id))
+            .unwrap_or(primary)
+    }
+
+    /// Mutable version of [`Self::arena_of`].
+    pub(crate) fn arena_of_mut<'a>(
+        &'a mut self,
+        primary: &'a mut ContextService<G>,
+        player_id: PlayerId,
+    ) -> &'a mut ContextService<G> {
+        match self
+            .arenas
+            .iter()
+            .position(|arena| arena.context.players.contains(player_id))
+        {
+            Some(index) => &mut self.arenas[index],
+            None => primary,
+        }
+    }
+
+    /// Returns true iff a player exists in any arena.
+    pub(crate) fn contains(&self, primary: &ContextService<G>, player_id: PlayerId) -> bool {
+        self.iter(primary)
+            .any(|arena| arena.context.players.contains(player_id))
+    }
+
+    /// Generates a session id and player id, unique across all arenas, for a new player.
+    pub(crate) fn new_ids(&self, primary: &ContextService<G>) -> (SessionId, PlayerId) {
+        let mut session_ids = HashSet::with_capacity(primary.context.players.real_players);
+
+        // TODO: O(n) on players.
+        for arena in self.iter(primary) {
+            for player in arena.context.players.iter_borrow() {
+                if let Some(client_data) = player.client() {
+                    session_ids.insert(client_data.session_id);
+                }
+            }
+        }
+
+        let session_id = loop {
+            let session_id = SessionId(generate_id_64());
+            if !session_ids.contains(&session_id) {
+                break session_id;
+            }
+        };
+
+        let player_id = loop {
+            let player_id = PlayerId(generate_id());
+            if !self.contains(primary, player_id) {
+                break player_id;
+            }
+        };
+
+        (session_id, player_id)
+    }
+
+    /// Sends an update to a real player, in whichever arena they are in, if they are connected.
+    pub(crate) fn send_to_player(
+        &self,
+        primary: &ContextService<G>,
+        player_id: PlayerId,
+        update: ClientUpdate,
+    ) {
+        let player = unwrap_or_return!(self
+            .iter(primary)
+            .find_map(|arena| arena.context.players.borrow_player(player_id)));
+        if let Some(ClientStatus::Connected { observer }) =
+            player.client().map(|client| &client.status)
+        {
+            let _ = observer.send(ObserverUpdate::Send {
+                message: Update::Client(update),
+            });
+        }
+    }
+
+    /// Returns true iff the arena is private (see [`Self::create_private`]).
+    pub(crate) fn is_private(&self, arena_id: ArenaId) -> bool {
+        self.private.contains_key(&arena_id)
+    }
+
+    /// Returns who created the arena, iff it is private.
+    pub(crate) fn private_creator(&self, arena_id: ArenaId) -> Option<PlayerId> {
+        self.private.get(&arena_id).copied()
+    }
+
+    /// Chooses an arena for a new player to join. Players invited to a private arena join it, and
+    /// other invited players join their inviter's arena (even if it is at capacity), so friends
+    /// land together. Otherwise, players join the least populated public arena, or a new one if
+    /// all are at capacity.
+    pub(crate) fn arena_for_join(
+        &mut self,
+        primary: &ContextService<G>,
+        invitation: Option<&Invitation>,
+    ) -> ArenaId {
+        if let Some(invitation) = invitation.filter(|i| self.is_private(i.arena_id)) {
+            return invitation.arena_id;
+        }
+
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => return primary.context.arena_id,
+        };
+
+        if let Some(invitation) = invitation {
+            if let Some(arena) = self
+                .iter(primary)
+                .find(|arena| arena.context.players.contains(invitation.player_id))
+            {
+                return arena.context.arena_id;
+            }
+        }
+
+        let (arena_id, population) = self
+            .iter(primary)
+            .filter(|arena| !self.is_private(arena.context.arena_id))
+            .map(|arena| (arena.context.arena_id, Self::population(arena)))
+            .min_by_key(|&(_, population)| population)
+            .unwrap();
+        if population < capacity {
+            return arena_id;
+        }
+
+        let arena_id = self.new_arena_id(primary);
+        info!(
+            "all arenas reached {} players, creating {:?}",
+            capacity, arena_id
+        );
+        self.arenas.push(ContextService::from_template(
+            arena_id,
+            &self.overflow_template(arena_id),
+        ));
+        arena_id
+    }
+
+    /// The template for a public overflow arena.
+    fn overflow_template(&self, arena_id: ArenaId) -> ArenaTemplate {
+        ArenaTemplate {
+            tick_period: self.overflow_tick_period,
+            ..self.template.for_arena(arena_id)
+        }
+    }
+
+    /// Creates a private arena on behalf of a real player, which players can join with
+    /// invitations to it. Only [`GameArenaService::PRIVATE_ARENA_OPTIONS`] are applied.
+    pub(crate) fn create_private(
+        &mut self,
+        primary: &ContextService<G>,
+        creator: PlayerId,
+        bots: bool,
+        options: Vec<(String, String)>,
+    ) -> Result<ArenaId, &'static str> {
+        // Checked before generating the arena's world, which is expensive.
+        let ip_address = self
+            .iter(primary)
+            .find_map(|arena| arena.context.players.borrow_player(creator))
+            .ok_or("nonexistent player")?
+            .client()
+            .ok_or("not a real player")?
+            .ip_address;
+        if self.private.values().filter(|&&c| c == creator).count() >= Self::MAX_PRIVATE_PER_PLAYER
+        {
+            return Err("already created a private arena");
+        }
+        if self.private.len() >= Self::MAX_PRIVATE {
+            return Err("too many private arenas");
+        }
+        if self
+            .create_private_rate_limiter
+            .should_limit_rate(ip_address)
+        {
+            return Err("creating private arenas too often");
+        }
+
+        let mut template = self.template.clone();
+        if !bots {
+            template.min_bots = Some(0);
+            template.max_bots = Some(0);
+        }
+        template.arena_options.extend(
+            options
+                .into_iter()
+                .filter(|(key, _)| G::PRIVATE_ARENA_OPTIONS.contains(&key.as_str())),
+        );
+
+        let arena_id = self.new_arena_id(primary);
+        info!("creating private {:?}", arena_id);
+        self.arenas.push(ContextService::from_template(
+            arena_id,
+            &template.for_arena(arena_id),
+        ));
+        self.private.insert(arena_id, creator);
+        self.kept_until
+            .insert(arena_id, Instant::now() + Self::PRIVATE_GRACE);
+        Ok(arena_id)
+    }
+
+    /// Recreates an arena handed off by a previous server process (see
+    /// [`Handoff`][crate::handoff::Handoff]), keeping it until the given time for its players to
+    /// reconnect.
+    pub(crate) fn resume(
+        &mut self,
+        arena_id: ArenaId,
+        private: Option<PlayerId>,
+        until: Instant,
+    ) -> &mut ContextService<G> {
+        info!("resuming {:?}", arena_id);
+        if let Some(creator) = private {
+            self.private.insert(arena_id, creator);
+        }
+        self.kept_until.insert(arena_id, until);
+        let template = if private.is_some() {
+            self.template.for_arena(arena_id)
+        } else {
+            self.overflow_template(arena_id)
+        };
+        self.arenas
+            .push(ContextService::from_template(arena_id, &template));
+        self.arenas.last_mut().unwrap()
+    }
+
+    /// Generates an id that no arena has.
+    fn new_arena_id(&self, primary: &ContextService<G>) -> ArenaId {
+        loop {
+            let arena_id = ArenaId(generate_id());
+            if self
+                .iter(primary)
+                .all(|arena| arena.context.arena_id != arena_id)
+            {
+                break arena_id;
+            }
+        }
+    }
+
+    /// Removes overflow arenas without any real players (including ones that are yet to connect,
+    /// or are in limbo), except new (or resumed) arenas that their players haven't joined yet.
+    pub(crate) fn prune(&mut self, invitations: &mut InvitationRepo<G>) {
+        let now = Instant::now();
+        self.kept_until.retain(|_, &mut until| until > now);
+
+        let private = &mut self.private;
+        let kept_until = &self.kept_until;
+        self.arenas.retain(|arena| {
+            let arena_id = arena.context.arena_id;
+            let retain = Self::population(arena) > 0 || kept_until.contains_key(&arena_id);
+            if !retain {
+                info!("removing empty {:?}", arena_id);
+                private.remove(&arena_id);
+                invitations.forget_arena_invitations(arena_id);
+            }
+            retain
+        });
+    }
+
+    /// Number of real players in an arena, which unlike [`PlayerRepo::real_players`] is up to
+    /// date between ticks.
+    ///
+    /// [`PlayerRepo::real_players`]: crate::player::PlayerRepo
+    fn population(arena: &ContextService<G>) -> usize {
+        // TODO: O(n) on players.
+        arena
+            .context
+            .players
+            .iter_borrow()
+            .filter(|player| player.client().is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chat::NewPlayerRestrictions;
+    use crate::context_service::{arena_path, ContextService};
+    use crate::game_service::{ArenaOptions, MockGame};
+    use core_protocol::id::ArenaId;
+    use server_util::rate_limiter::RateLimiterProps;
+    use std::num::NonZeroU32;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn per_arena_paths() {
+        let arena_id = ArenaId(NonZeroU32::new(1234).unwrap());
+        assert_eq!(arena_path("events.log", arena_id), "events_1234.log");
+        assert_eq!(arena_path("logs/chat.log", arena_id), "logs/chat_1234.log");
+        assert_eq!(arena_path("desync", arena_id), "desync_1234");
+    }
+
+    #[test]
+    fn is_due() {
+        let update_period = Duration::from_millis(100);
+        let mut context_service = ContextService::<MockGame>::new(
+            ArenaId(NonZeroU32::new(1).unwrap()),
+            Some(0),
+            Some(0),
+            None,
+            ArenaOptions::default(),
+            None,
+            None,
+            None,
+            None,
+            RateLimiterProps::no_limit(),
+            0,
+            NewPlayerRestrictions {
+                min_score: 0,
+                probation: Duration::ZERO,
+            },
+            None,
+            update_period * 2,
+        );
+
+        // Updated every other time, without drifting.
+        let start = Instant::now();
+        let due: Vec<_> = (0..8)
+            .map(|i| context_service.is_due(start + update_period * i, update_period))
+            .collect();
+        assert_eq!(due, [true, false, true, false, true, false, true, false]);
+
+        // Doesn't try to catch up after a long pause.
+        assert!(context_service.is_due(start + update_period * 20, update_period));
+        assert!(!context_service.is_due(start + update_period * 21, update_period));
     }
 }
@@ -0,0 +1,154 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game_service::GameArenaService;
+use core_protocol::id::PlayerId;
+use core_protocol::{get_unix_time_now, UnixTime};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{error, info};
+use server_util::rate_limiter::RateLimiter;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Component of [`Context`] that periodically snapshots the world, so that desyncs reported by
+/// clients (e.g. "ghost ships") can be stored alongside what the server thought the world looked
+/// like at the time, for offline diffing.
+pub struct DesyncRepo<G> {
+    /// Store reports here, one directory per report. Snapshots aren't taken if [`None`].
+    dir: Option<Arc<str>>,
+    /// Most recent snapshots, oldest first.
+    snapshots: VecDeque<WorldSnapshot>,
+    snapshot_rate_limiter: RateLimiter,
+    _spooky: PhantomData<G>,
+}
+
+/// A compressed snapshot of the world.
+struct WorldSnapshot {
+    date_created: UnixTime,
+    /// Hash of the uncompressed snapshot, to tell whether two snapshots are identical at a glance.
+    hash: u64,
+    /// Gzip compressed, as returned by [`GameArenaService::snapshot`].
+    compressed: Arc<[u8]>,
+}
+
+impl<G: GameArenaService> DesyncRepo<G> {
+    /// Time between snapshots.
+    const SNAPSHOT_PERIOD: Duration = Duration::from_secs(2);
+    /// Number of snapshots to keep (and store with each report), since a client may take a while
+    /// to notice and report a desync.
+    const SNAPSHOTS: usize = 5;
+    /// Maximum length of a client's report message.
+    const MAX_MESSAGE: usize = 1024;
+    /// Maximum length of a client's view of the world.
+    const MAX_STATE: usize = 256 * 1024;
+
+    pub fn new(dir: Option<String>) -> Self {
+        Self {
+            dir: dir.map(Into::into),
+            snapshots: VecDeque::with_capacity(Self::SNAPSHOTS),
+            snapshot_rate_limiter: RateLimiter::new(Self::SNAPSHOT_PERIOD, 0),
+            _spooky: PhantomData,
+        }
+    }
+
+    /// Takes a snapshot of the world, if enabled. Rate limited internally.
+    pub(crate) fn update(&mut self, service: &G) {
+        if self.dir.is_none() || self.snapshot_rate_limiter.should_limit_rate() {
+            return;
+        }
+        let snapshot = crate::unwrap_or_return!(service.snapshot());
+
+        let mut hasher = DefaultHasher::new();
+        snapshot.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        let compressed = match encoder
+            .write_all(snapshot.as_bytes())
+            .and_then(|_| encoder.finish())
+        {
+            Ok(compressed) => compressed,
+            Err(e) => {
+                error!("error compressing snapshot: {:?}", e);
+                return;
+            }
+        };
+
+        if self.snapshots.len() >= Self::SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(WorldSnapshot {
+            date_created: get_unix_time_now(),
+            hash,
+            compressed: compressed.into(),
+        });
+    }
+
+    /// Stores a client's report of a desync, along with recent snapshots, in a new directory.
+    pub(crate) fn report(
+        &self,
+        player_id: PlayerId,
+        message: String,
+        state: String,
+    ) -> Result<(), &'static str> {
+        let dir = self.dir.as_ref().ok_or("desync reports disabled")?;
+        if message.len() > Self::MAX_MESSAGE {
+            return Err("message too long");
+        }
+        if state.len() > Self::MAX_STATE {
+            return Err("state too long");
+        }
+        if self.snapshots.is_empty() {
+            return Err("no snapshots yet");
+        }
+
+        let now = get_unix_time_now();
+        let report_dir = Path::new(&**dir).join(format!("{}-{}", now, player_id.0));
+
+        let mut summary = format!(
+            "player: {:?}\ntime: {}\nmessage: {}\n",
+            player_id, now, message
+        );
+        let snapshots: Vec<(String, Arc<[u8]>)> = self
+            .snapshots
+            .iter()
+            .map(|snapshot| {
+                let _ = writeln!(
+                    summary,
+                    "snapshot: {} (hash {:016x})",
+                    snapshot.date_created, snapshot.hash
+                );
+                (
+                    format!("server-{}.txt.gz", snapshot.date_created),
+                    Arc::clone(&snapshot.compressed),
+                )
+            })
+            .collect();
+
+        info!("storing desync report in {:?}", report_dir);
+        tokio::task::spawn_blocking(move || {
+            let result = fs::create_dir_all(&report_dir)
+                .and_then(|_| fs::write(report_dir.join("report.txt"), summary))
+                .and_then(|_| fs::write(report_dir.join("client.txt"), state))
+                .and_then(|_| {
+                    snapshots.into_iter().try_for_each(|(name, compressed)| {
+                        fs::write(report_dir.join(name), compressed)
+                    })
+                });
+            if let Err(e) = result {
+                error!("error storing desync report: {:?}", e);
+            }
+        });
+
+        Ok(())
+    }
+}
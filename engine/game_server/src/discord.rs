@@ -1,8 +1,10 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::login::{new_state, redirect_with_state};
 use axum::http::{HeaderMap, HeaderValue};
-use axum::response::{IntoResponse, Redirect};
+use axum::response::IntoResponse;
+use core_protocol::id::LoginType;
 use oauth2::{
     basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId,
     ClientSecret, CsrfToken, RedirectUrl, Scope, TokenResponse, TokenUrl,
@@ -10,6 +12,7 @@ use oauth2::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::num::NonZeroU64;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub struct DiscordBotRepo {
@@ -167,6 +170,79 @@ impl DiscordBotRepo {
     }
 }
 
+/// Events that may be published to Discord, each to its own webhook (see `--discord-webhook`).
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DiscordEvent {
+    /// A player beat the all-time high score.
+    HighScore,
+    /// A different player took first place on a leaderboard.
+    LeaderboardTurnover,
+    /// A tournament ended.
+    Tournament,
+}
+
+impl FromStr for DiscordEvent {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "high_score" => Self::HighScore,
+            "leaderboard_turnover" => Self::LeaderboardTurnover,
+            "tournament" => Self::Tournament,
+            _ => return Err("expected high_score, leaderboard_turnover, or tournament"),
+        })
+    }
+}
+
+/// Publishes [`DiscordEvent`]s to webhooks, which unlike [`DiscordBotRepo`], need not be in the
+/// guild.
+pub struct DiscordWebhookRepo {
+    webhooks: HashMap<DiscordEvent, String>,
+    client: reqwest::Client,
+}
+
+impl DiscordWebhookRepo {
+    /// Returns [`None`] if no events are to be published.
+    pub fn new(webhooks: Vec<(DiscordEvent, String)>) -> Option<Self> {
+        (!webhooks.is_empty()).then(|| Self {
+            webhooks: webhooks.into_iter().collect(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(3))
+                .build()
+                .unwrap(),
+        })
+    }
+
+    /// Returns true iff the event has a webhook.
+    pub fn is_enabled(&self, event: DiscordEvent) -> bool {
+        self.webhooks.contains_key(&event)
+    }
+
+    pub async fn send_event(&self, event: DiscordEvent, message: &str) -> Result<(), String> {
+        let url = self
+            .webhooks
+            .get(&event)
+            .ok_or_else(|| String::from("no webhook for event"))?;
+
+        // https://discord.com/developers/docs/resources/webhook#execute-webhook
+        #[derive(Serialize)]
+        struct ExecuteWebhook<'a> {
+            content: &'a str,
+        }
+
+        self.client
+            .post(url)
+            .json(&ExecuteWebhook { content: message })
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
 pub struct DiscordOauth2Repo {
     oauth2_client: BasicClient,
     http_client: reqwest::Client,
@@ -196,14 +272,15 @@ impl DiscordOauth2Repo {
         }
     }
 
-    pub fn redirect(&self) -> impl IntoResponse {
+    pub fn redirect(&self, cookie_domain: Option<&str>) -> impl IntoResponse {
+        let (state, nonce) = new_state(LoginType::Discord);
         let (auth_url, _csrf_token) = self
             .oauth2_client
-            .authorize_url(CsrfToken::new_random)
+            .authorize_url(|| CsrfToken::new(state))
             .add_scope(Scope::new("identify".to_string()))
             .url();
 
-        Redirect::to(&auth_url.to_string())
+        redirect_with_state(auth_url.as_str(), &nonce, cookie_domain)
     }
 
     pub async fn authenticate(&self, code: String) -> Result<NonZeroU64, String> {
@@ -5,12 +5,17 @@
 //! via web_socket.
 
 use crate::admin::ParameterizedAdminRequest;
+use crate::chat::NewPlayerRestrictions;
 use crate::client::{Authenticate, Oauth2Code};
-use crate::discord::{DiscordBotRepo, DiscordOauth2Repo};
-use crate::game_service::GameArenaService;
+use crate::compat::{decode_request, encode_update};
+use crate::discord::{DiscordBotRepo, DiscordOauth2Repo, DiscordWebhookRepo};
+use crate::game_service::{ArenaOptions, GameArenaService};
+use crate::handoff::HandoffRequest;
 use crate::infrastructure::Infrastructure;
 use crate::leaderboard::LeaderboardRequest;
+use crate::login::{check_state, Oauth2Repo, STATE_COOKIE};
 use crate::options::Options;
+use crate::prometheus::{record_web_socket_send, PrometheusRequest};
 use crate::static_files::{static_size_and_hash, StaticFilesHandler};
 use crate::status::StatusRequest;
 use crate::system::{SystemRepo, SystemRequest};
@@ -19,16 +24,15 @@ use axum::body::{boxed, Empty, Full, HttpBody};
 use axum::extract::ws::{CloseCode, CloseFrame, Message};
 use axum::extract::{ConnectInfo, Query, TypedHeader, WebSocketUpgrade};
 use axum::headers::HeaderName;
-use axum::http::header::CACHE_CONTROL;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
 use axum::http::uri::{Authority, Scheme};
-use axum::http::{HeaderValue, Method, Response, StatusCode, Uri};
+use axum::http::{HeaderMap, HeaderValue, Method, Response, StatusCode, Uri};
 use axum::response::{IntoResponse, Redirect};
 use axum::routing::get;
 use axum::{Json, Router};
-use bincode::{self, Options as _};
 use core_protocol::id::*;
 use core_protocol::rpc::{Request, SystemQuery, Update, WebSocketQuery};
-use core_protocol::web_socket::WebSocketProtocol;
+use core_protocol::web_socket::{is_supported_version, WebSocketProtocol, OUTDATED_CLOSE_CODE};
 use core_protocol::{get_unix_time_now, UnixTime};
 use futures::pin_mut;
 use futures::SinkExt;
@@ -110,16 +114,34 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
         let admin_client = Arc::new(RwLock::new(release_include_mini_cdn!("../../js/public")));
         let discord_guild_id = options.discord_guild_id;
         let discord_bot = options.discord_bot_token.and_then(|t| DiscordBotRepo::new(discord_guild_id, &t));
+        let discord_webhooks = DiscordWebhookRepo::new(options.discord_webhooks);
         let discord_client_id = options.discord_client_id;
         let domain = options.domain.map(|domain| &*Box::leak(domain.into_boxed_str()));
+        let metrics_allowlist = &*Box::leak(options.metrics_allowlist.into_boxed_slice());
+        // Providers redirect back to the game, which then connects with the code.
+        let oauth2_redirect_url = domain
+            .filter(|_| cfg!(not(debug_assertions)))
+            .map(|d| format!("https://{d}"))
+            .unwrap_or_else(|| format!("http://localhost:{http_port}"));
+        // The game may connect to a subdomain, which must see the OAuth2 state cookie.
+        let oauth2_cookie_domain = domain.filter(|_| cfg!(not(debug_assertions)));
         let discord_oauth2 = options.discord_client_secret
             .map(|client_secret| &*Box::leak(Box::new(DiscordOauth2Repo::new(
                 discord_client_id,
                 client_secret,
-                domain
-                    .filter(|_| cfg!(not(debug_assertions)))
-                    .map(|d| format!("https://{d}"))
-                    .unwrap_or_else(|| format!("http://localhost:{http_port}"))
+                oauth2_redirect_url.clone(),
+            ))));
+        let google_oauth2 = options.google_client_id.zip(options.google_client_secret)
+            .map(|(client_id, client_secret)| &*Box::leak(Box::new(Oauth2Repo::google(
+                client_id,
+                client_secret,
+                oauth2_redirect_url.clone(),
+            ))));
+        let apple_oauth2 = options.apple_client_id.zip(options.apple_client_secret)
+            .map(|(client_id, client_secret)| &*Box::leak(Box::new(Oauth2Repo::apple(
+                client_id,
+                client_secret,
+                oauth2_redirect_url.clone(),
             ))));
 
         // println!("{:?}", discord_bot.as_ref().unwrap().send_message("", "", None).await);
@@ -129,15 +151,21 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                 server_id,
                 system,
                 discord_bot,
+                discord_webhooks,
                 discord_oauth2,
+                google_oauth2,
+                apple_oauth2,
                 static_hash,
                 region_id,
                 options.database_read_only,
                 options.min_bots,
                 options.max_bots,
                 options.bot_percent,
+                ArenaOptions::new(options.arena_options),
                 options.chat_log,
+                options.event_log,
                 options.trace_log,
+                options.desync_dir,
                 Arc::clone(&game_client),
                 &ALLOW_WEB_SOCKET_JSON,
                 options.admin_config_file,
@@ -145,10 +173,39 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                     Duration::from_secs(options.client_authenticate_rate_limit),
                     options.client_authenticate_burst,
                 ),
+                options.max_observers,
+                NewPlayerRestrictions {
+                    min_score: options.new_player_min_score,
+                    probation: Duration::from_secs(options.new_player_probation),
+                },
+                options
+                    .season_days
+                    .map(|days| Duration::from_secs(days as u64 * 24 * 60 * 60)),
+                options.tick_rate,
+                options.overflow_tick_rate,
+                options.handoff_file.clone(),
+                options.arena_capacity,
             )
             .await,
         );
 
+        // Hand off to the replacement process, instead of wiping everyone's progress.
+        #[cfg(unix)]
+        if options.handoff_file.is_some() {
+            let handoff_srv = srv.to_owned();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+
+                let mut terminate = signal(SignalKind::terminate()).unwrap();
+                terminate.recv().await;
+                warn!("received SIGTERM, writing handoff...");
+                if let Err(e) = handoff_srv.send(HandoffRequest).await {
+                    error!("could not write handoff: {}", e);
+                }
+                std::process::exit(0);
+            });
+        }
+
         #[cfg(not(debug_assertions))]
         let certificate_paths = options
             .certificate_path
@@ -159,6 +216,7 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
         let admin_srv = srv.to_owned();
         let leaderboard_srv = srv.to_owned();
         let status_srv = srv.to_owned();
+        let metrics_srv = srv.to_owned();
         let system_srv = srv.to_owned();
 
         #[cfg(not(debug_assertions))]
@@ -198,16 +256,38 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
         let app = Router::new()
             .fallback_service(get(StaticFilesHandler{cdn: game_client, prefix: "", browser_router}))
             .route("/oauth2/discord", get(async move || {
-                discord_oauth2.map(|oauth2| oauth2.redirect().into_response()).unwrap_or_else(|| Response::builder()
+                discord_oauth2.map(|oauth2| oauth2.redirect(oauth2_cookie_domain).into_response()).unwrap_or_else(|| Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(boxed(Full::from("404 Not Found")))
+                    .unwrap())
+            }))
+            .route("/oauth2/google", get(async move || {
+                google_oauth2.map(|oauth2| oauth2.redirect(oauth2_cookie_domain).into_response()).unwrap_or_else(|| Response::builder()
                     .status(StatusCode::NOT_FOUND)
                     .body(boxed(Full::from("404 Not Found")))
                     .unwrap())
             }))
-            .route("/ws", axum::routing::get(async move |upgrade: WebSocketUpgrade, ConnectInfo(addr): ConnectInfo<SocketAddr>, user_agent: Option<TypedHeader<axum::headers::UserAgent>>, Query(query): Query<WebSocketQuery>| {
+            .route("/oauth2/apple", get(async move || {
+                apple_oauth2.map(|oauth2| oauth2.redirect(oauth2_cookie_domain).into_response()).unwrap_or_else(|| Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(boxed(Full::from("404 Not Found")))
+                    .unwrap())
+            }))
+            .route("/ws", axum::routing::get(async move |upgrade: WebSocketUpgrade, ConnectInfo(addr): ConnectInfo<SocketAddr>, user_agent: Option<TypedHeader<axum::headers::UserAgent>>, cookie: Option<TypedHeader<axum::headers::Cookie>>, Query(query): Query<WebSocketQuery>| {
+                let version = query.version.unwrap_or(0);
+                if !is_supported_version(version) {
+                    // Browsers don't expose why a handshake failed, so upgrade and close instead.
+                    return Ok(upgrade.on_upgrade(async move |mut web_socket| {
+                        let _ = web_socket.send(Message::Close(Some(CloseFrame{code: OUTDATED_CLOSE_CODE, reason: "outdated client, please refresh".into()}))).await;
+                    }));
+                }
+
                 let user_agent_id = user_agent
                     .map(|h| UserAgent::new(h.as_str()))
                     .and_then(UserAgent::into_id);
                 let login_type = query.login_type;
+                // Only accept codes from sign ins that this browser started.
+                let login_state_valid = check_state(query.login_state.as_deref(), cookie.as_ref().and_then(|cookie| cookie.get(STATE_COOKIE)));
 
                 let authenticate = Authenticate {
                     ip_address: addr.ip(),
@@ -215,7 +295,13 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                     user_agent_id,
                     arena_id_session_id: query.arena_id.zip(query.session_id),
                     invitation_id: query.invitation_id,
-                    oauth2_code: query.login_id.filter(|id| id.len() <= 2048 && login_type == Some(LoginType::Discord)).map(Oauth2Code::Discord),
+                    oauth2_code: query.login_id.filter(|id| id.len() <= 2048 && login_state_valid).zip(login_type).map(|(code, login_type)| match login_type {
+                        LoginType::Discord => Oauth2Code::Discord(code),
+                        LoginType::Google => Oauth2Code::Google(code),
+                        LoginType::Apple => Oauth2Code::Apple(code),
+                    }),
+                    observer_auth: query.observer_auth,
+                    transfer: query.transfer.filter(|token| token.len() <= 2048),
                 };
 
                 const MAX_MESSAGE_SIZE: usize = 32768;
@@ -227,7 +313,8 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                 match ws_srv.send(authenticate).await {
                     Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
                     Ok(result) => match result {
-                        // Currently, if authentication fails, it was due to rate limit.
+                        // Currently, if authentication fails, it was due to rate limit (or too many
+                        // observers).
                         Err(_) => Err(StatusCode::TOO_MANY_REQUESTS.into_response()),
                         Ok(player_id) => Ok(upgrade
                             .max_frame_size(MAX_MESSAGE_SIZE)
@@ -271,12 +358,7 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                                                                 continue;
                                                             }
 
-                                                            match bincode::DefaultOptions::new()
-                                                                .with_limit(MAX_MESSAGE_SIZE as u64)
-                                                                .with_fixint_encoding()
-                                                                .allow_trailing_bytes()
-                                                                .deserialize(binary.as_ref())
-                                                            {
+                                                            match decode_request(version, binary.as_ref(), MAX_MESSAGE_SIZE) {
                                                                 Ok(request) => {
                                                                     protocol = WebSocketProtocol::Binary;
                                                                     let _ = ws_srv.do_send(ObserverMessage::<Request<G::GameRequest>, Update<G::GameUpdate >>::Request {
@@ -362,12 +444,14 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                                                     protocol = WebSocketProtocol::Binary;
                                                 }
                                                 let web_socket_message = match protocol {
-                                                    WebSocketProtocol::Binary => Message::Binary(bincode::serialize(&message).unwrap()),
+                                                    WebSocketProtocol::Binary => Message::Binary(encode_update(version, message)),
                                                     WebSocketProtocol::Json => Message::Text(serde_json::to_string(&message).unwrap()),
                                                 };
+                                                let send_start = Instant::now();
                                                 if web_socket.send(web_socket_message).await.is_err() {
                                                     break NORMAL_CLOSURE;
                                                 }
+                                                record_web_socket_send(send_start.elapsed());
 
                                                 if !measure_rtt_ping_governor.should_limit_rate_with_now(&MEASURE_RTT_PING, last_activity) {
                                                     if web_socket.send(Message::Ping(get_unix_time_now().to_ne_bytes().into())).await.is_err() {
@@ -490,6 +574,23 @@ pub fn entry_point<G: GameArenaService>(game_client: MiniCdn, browser_router: bo
                     }
                 }
             }))
+            .route("/metrics", get(move |ConnectInfo(addr): ConnectInfo<SocketAddr>, headers: HeaderMap| {
+                let srv = metrics_srv.to_owned();
+                debug!("received metrics request");
+
+                async move {
+                    let authorized = metrics_allowlist.contains(&addr.ip())
+                        || headers.get("auth").map(|hv| constant_time_eq::constant_time_eq(include_str!("auth.txt").as_bytes(), hv.as_bytes())).unwrap_or(false);
+                    if !authorized {
+                        return Err(StatusCode::NOT_FOUND.into_response());
+                    }
+
+                    match srv.send(PrometheusRequest).await {
+                        Ok(text) => Ok(([(CONTENT_TYPE, "text/plain; version=0.0.4")], text)),
+                        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+                    }
+                }
+            }))
             .route("/admin/", admin_router.clone())
             .route("/admin/*path", admin_router)
             .layer(ServiceBuilder::new()
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game_service::GameArenaService;
+use crate::infrastructure::Infrastructure;
+use crate::metric::MetricRepo;
+use core_protocol::dto::{ExperimentDto, ExperimentReportDto, MetricFilter};
+use core_protocol::id::{CohortId, VariantId};
+use core_protocol::rpc::AdminUpdate;
+use core_protocol::{get_unix_time_now, UnixTime};
+use log::info;
+use server_util::database_schema::Metrics;
+use std::iter;
+
+/// Runs A/B tests by assigning variants to arenas or players, so metrics can be compared between
+/// variants.
+#[derive(Default)]
+pub(crate) struct ExperimentRepo {
+    current: Option<Experiment>,
+}
+
+struct Experiment {
+    dto: ExperimentDto,
+    started: UnixTime,
+    /// Only for experiments that aren't by cohort.
+    arena_variant_id: Option<VariantId>,
+}
+
+impl Experiment {
+    /// The configuration this arena is using, if any.
+    fn arena_entity_overrides(&self) -> Option<&str> {
+        self.arena_variant_id
+            .and_then(|id| self.dto.variants[id.0 as usize].entity_overrides.as_deref())
+    }
+}
+
+impl ExperimentRepo {
+    /// Returns the variant that a client in a particular cohort belongs to, if any.
+    pub(crate) fn variant_id(&self, cohort_id: CohortId) -> Option<VariantId> {
+        let experiment = self.current.as_ref()?;
+        if experiment.dto.by_cohort {
+            let index = (cohort_id.0.get() - 1) as usize % experiment.dto.variants.len();
+            Some(VariantId(index as u8))
+        } else {
+            experiment.arena_variant_id
+        }
+    }
+
+    /// Starts or stops an experiment, applying the configuration of this arena's variant.
+    pub(crate) fn set_experiment<G: GameArenaService>(
+        infrastructure: &mut Infrastructure<G>,
+        experiment: Option<ExperimentDto>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let experiment = if let Some(dto) = experiment {
+            if dto.variants.is_empty() {
+                return Err("no variants");
+            }
+            if dto.variants.len() > u8::MAX as usize + 1 {
+                return Err("too many variants");
+            }
+            if dto.by_cohort && dto.variants.iter().any(|v| v.entity_overrides.is_some()) {
+                return Err("cohort variants cannot override entities");
+            }
+
+            // Spread variants over servers.
+            let arena_variant_id = (!dto.by_cohort).then(|| {
+                let server_number = infrastructure.server_id.map(|id| id.0.get()).unwrap_or(0);
+                VariantId((server_number as usize % dto.variants.len()) as u8)
+            });
+
+            Some(Experiment {
+                dto,
+                started: get_unix_time_now(),
+                arena_variant_id,
+            })
+        } else {
+            None
+        };
+
+        let repo = &infrastructure.metrics.experiments;
+        let previous_overrides = repo
+            .current
+            .as_ref()
+            .and_then(Experiment::arena_entity_overrides);
+        let overrides = experiment
+            .as_ref()
+            .and_then(Experiment::arena_entity_overrides);
+        if previous_overrides.is_some() || overrides.is_some() {
+            infrastructure
+                .context_service
+                .service
+                .set_entity_overrides(overrides.unwrap_or(""))?;
+        }
+
+        info!(
+            "experiment set to {:?}",
+            experiment.as_ref().map(|e| &e.dto)
+        );
+        let arena_variant_id = experiment.as_ref().and_then(|e| e.arena_variant_id);
+
+        // Any existing variant metrics are from the previous experiment.
+        infrastructure.metrics.current.bundle.by_variant_id.clear();
+        infrastructure.metrics.experiments.current = experiment;
+
+        Ok(AdminUpdate::ExperimentSet(arena_variant_id))
+    }
+
+    /// Summarizes metrics of each variant of the current experiment, since it started (up to one
+    /// day ago).
+    pub(crate) fn request_experiment<G: GameArenaService>(
+        metrics: &MetricRepo<G>,
+    ) -> Result<AdminUpdate, &'static str> {
+        let experiment = if let Some(experiment) = &metrics.experiments.current {
+            experiment
+        } else {
+            return Ok(AdminUpdate::ExperimentRequested(None));
+        };
+
+        let variants = (0..experiment.dto.variants.len())
+            .map(|i| {
+                let variant_id = VariantId(i as u8);
+                let filter = Some(MetricFilter::VariantId(variant_id));
+                let summary = metrics
+                    .history
+                    .oldest_ordered()
+                    .filter(|bundle| {
+                        bundle.start + MetricRepo::<G>::HOUR_IN_MILLIS > experiment.started
+                    })
+                    .chain(iter::once(&metrics.current))
+                    .map(|bundle| bundle.metric(filter))
+                    .sum::<Metrics>()
+                    .summarize();
+                (variant_id, summary)
+            })
+            .collect();
+
+        Ok(AdminUpdate::ExperimentRequested(Some(
+            ExperimentReportDto {
+                experiment: experiment.dto.clone(),
+                started: experiment.started,
+                arena_variant_id: experiment.arena_variant_id,
+                variants,
+            },
+        )))
+    }
+}
@@ -5,13 +5,42 @@ use crate::context::Context;
 use crate::player::{PlayerRepo, PlayerTuple};
 use core_protocol::id::{GameId, PlayerId, TeamId};
 use core_protocol::name::PlayerAlias;
+use image::RgbImage;
+use log::warn;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fmt::Debug;
 use std::marker::Send;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Game-specific options of an arena, specified as `--arena-option key=value` server arguments.
+#[derive(Clone, Debug, Default)]
+pub struct ArenaOptions(Vec<(String, String)>);
+
+impl ArenaOptions {
+    pub fn new(options: Vec<(String, String)>) -> Self {
+        Self(options)
+    }
+
+    /// Adds options, overriding any existing options with the same keys.
+    pub fn extend(&mut self, options: impl IntoIterator<Item = (String, String)>) {
+        self.0.extend(options);
+    }
+
+    /// Parses the value of the last option named `key`, if any. Invalid values are logged and
+    /// ignored.
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        let (_, value) = self.0.iter().rev().find(|(k, _)| k == key)?;
+        let parsed = T::from_str(value).ok();
+        if parsed.is_none() {
+            warn!("invalid arena option {}={}", key, value);
+        }
+        parsed
+    }
+}
+
 /// A modular game service (representing one arena).
 pub trait GameArenaService: 'static + Unpin + Sized + Send + Sync {
     const GAME_ID: GameId;
@@ -33,6 +62,8 @@ pub trait GameArenaService: 'static + Unpin + Sized + Send + Sync {
     const TEAM_JOINERS_MAX: usize = 6;
     /// Maximum number of teams a player may try to join at once, before old requests are cancelled.
     const TEAM_JOINS_MAX: usize = 3;
+    /// Keys of the [`ArenaOptions`] that players may set when creating a private arena.
+    const PRIVATE_ARENA_OPTIONS: &'static [&'static str] = &[];
 
     type Bot: 'static + Bot<Self>;
     type ClientData: 'static + Default + Debug + Unpin + Send + Sync;
@@ -41,7 +72,7 @@ pub trait GameArenaService: 'static + Unpin + Sized + Send + Sync {
     type PlayerData: 'static + Default + Unpin + Send + Sync + Debug;
     type PlayerExtension: 'static + Default + Unpin + Send + Sync;
 
-    fn new(min_players: usize) -> Self;
+    fn new(min_players: usize, arena_options: &ArenaOptions) -> Self;
 
     /// Get alias of authority figure (that, for example, sends chat moderation warnings).
     fn authority_alias() -> PlayerAlias {
@@ -91,6 +122,40 @@ pub trait GameArenaService: 'static + Unpin + Sized + Send + Sync {
         let _ = player_tuple;
     }
 
+    /// Called when a player asks to switch to another server mid-session. Returns game-specific
+    /// progress (e.g. what they were playing as) to restore there with [`Self::transfer_in`],
+    /// giving it up on this server, or [`None`] if the player can't transfer right now. The
+    /// player's score is transferred separately.
+    fn transfer_out(&mut self, player_tuple: &Arc<PlayerTuple<Self>>) -> Option<Vec<u8>> {
+        let _ = player_tuple;
+        None
+    }
+
+    /// Called after [`Self::player_joined`] if the player transferred from another server, with
+    /// the progress returned by [`Self::transfer_out`] there. Their score was already restored.
+    fn transfer_in(
+        &mut self,
+        player_tuple: &Arc<PlayerTuple<Self>>,
+        progress: &[u8],
+        _players: &PlayerRepo<Self>,
+    ) {
+        let _ = (player_tuple, progress);
+    }
+
+    /// Called when the server process is about to be replaced (see `--handoff-file`), after each
+    /// player's progress was given up with [`Self::transfer_out`]. Returns the state of the world
+    /// (e.g. terrain) to restore in the replacement process with [`Self::restore_world`].
+    fn save_world(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Called before the first tick if the server process replaced a previous one, with the
+    /// state returned by [`Self::save_world`] there.
+    fn restore_world(&mut self, world: &[u8]) -> Result<(), &'static str> {
+        let _ = world;
+        Err("unsupported")
+    }
+
     fn chat_command(
         &mut self,
         command: &str,
@@ -112,6 +177,92 @@ pub trait GameArenaService: 'static + Unpin + Sized + Send + Sync {
         _players: &PlayerRepo<Self>,
     ) -> Option<Self::GameUpdate>;
 
+    /// Called after [`Self::get_game_update`] for real players that an admin is auditing, to
+    /// compare what was sent against what the player should be able to see. Any discrepancies
+    /// should be stored in `client_data` until [`Self::take_visibility_discrepancies`].
+    fn audit_visibility(
+        &self,
+        player_tuple: &Arc<PlayerTuple<Self>>,
+        update: &Self::GameUpdate,
+        client_data: &mut Self::ClientData,
+    ) {
+        let _ = (player_tuple, update, client_data);
+    }
+
+    /// Takes discrepancies found by [`Self::audit_visibility`], oldest first.
+    fn take_visibility_discrepancies(client_data: &mut Self::ClientData) -> Vec<String> {
+        let _ = client_data;
+        Vec::new()
+    }
+
+    /// Snapshot of the world, in a game-specific (ideally human-readable and line-diffable)
+    /// format, for investigating desyncs reported by clients. Called periodically, and only if
+    /// desync reports are enabled.
+    fn snapshot(&self) -> Option<String> {
+        None
+    }
+
+    /// Describes each entity within `radius` of (`x`, `y`), in a game-specific (ideally
+    /// human-readable) format, for admins to inspect the world.
+    fn inspect_entities(&self, x: f32, y: f32, radius: f32) -> Vec<String> {
+        let _ = (x, y, radius);
+        Vec::new()
+    }
+
+    /// Describes a player's full state, in a game-specific (ideally human-readable) format, for
+    /// admins to investigate e.g. cheating reports.
+    fn inspect_player(&self, player_tuple: &Arc<PlayerTuple<Self>>) -> Option<String> {
+        let _ = player_tuple;
+        None
+    }
+
+    /// Moves an entity, by its game-specific id, in response to an admin.
+    fn teleport_entity(&mut self, entity_id: u32, x: f32, y: f32) -> Result<(), &'static str> {
+        let _ = (entity_id, x, y);
+        Err("unsupported")
+    }
+
+    /// Removes an entity, by its game-specific id, in response to an admin.
+    fn delete_entity(&mut self, entity_id: u32) -> Result<(), &'static str> {
+        let _ = entity_id;
+        Err("unsupported")
+    }
+
+    /// Tiny picture of the arena (e.g. its terrain), for server browsers. Called periodically.
+    fn thumbnail(&self) -> Option<RgbImage> {
+        None
+    }
+
+    /// Changes game balance at runtime, in response to an admin or experiment. Clients should be
+    /// sent the changes, too. An empty patch restores the default balance.
+    fn set_entity_overrides(&mut self, patch: &str) -> Result<(), &'static str> {
+        let _ = patch;
+        Err("unsupported")
+    }
+
+    /// Current values of game-specific arena options that can be changed at runtime.
+    fn arena_options(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Changes a game-specific arena option at runtime, in response to an admin. See
+    /// [`ArenaOptions`] for how they are specified at startup.
+    fn set_arena_option(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        let _ = (key, value);
+        Err("unknown arena option")
+    }
+
+    /// A ranked season just ended (see [`SeasonRepo`][crate::season::SeasonRepo]), after its
+    /// standings were archived. The game may reset the world for the next season.
+    fn season_ended(&mut self, season: u32, players: &PlayerRepo<Self>) {
+        let _ = (season, players);
+    }
+
+    /// Counts of entities by kind, for metrics.
+    fn entity_counts(&self) -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
+
     /// Returns true iff the player is considered to be "alive" i.e. they cannot change their alias.
     fn is_alive(&self, player_tuple: &Arc<PlayerTuple<Self>>) -> bool;
     /// Before sending.
@@ -209,7 +360,7 @@ impl GameArenaService for MockGame {
     type PlayerData = ();
     type PlayerExtension = ();
 
-    fn new(_min_players: usize) -> Self {
+    fn new(_min_players: usize, _arena_options: &ArenaOptions) -> Self {
         Self
     }
 
@@ -0,0 +1,247 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Hands the game over from a server process that is shutting down (e.g. during a deploy) to its
+//! replacement, so players can reconnect and continue where they left off.
+
+use crate::client::ClientStatus;
+use crate::game_service::GameArenaService;
+use crate::infrastructure::Infrastructure;
+use crate::transfer::Progress;
+use crate::unwrap_or_return;
+use actix::{Handler, Message};
+use core_protocol::id::{ArenaId, PlayerId, SessionId};
+use core_protocol::{get_unix_time_now, UnixTime};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// State written by a server process that is shutting down, and read by its replacement.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Handoff {
+    /// When the handoff was written. Stale handoffs are discarded.
+    pub written: UnixTime,
+    /// Every arena, starting with the primary one. Overflow arenas are recreated with the same
+    /// ids, so their players can rejoin them.
+    pub arenas: Vec<ArenaHandoff>,
+    /// Progress of each real player, by the session they will reconnect with, and which arena
+    /// they were in.
+    pub sessions: Vec<(SessionId, PlayerId, ArenaId, Progress)>,
+}
+
+/// The part of a [`Handoff`] specific to one arena.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ArenaHandoff {
+    pub arena_id: ArenaId,
+    /// Who created the arena, if it is private (see
+    /// [`OverflowArenas::create_private`][crate::context_service::OverflowArenas::create_private]).
+    pub private: Option<PlayerId>,
+    /// Game-specific (see [`GameArenaService::save_world`]).
+    pub world: Option<Vec<u8>>,
+}
+
+impl Handoff {
+    /// How long after being written a handoff may be resumed from, in milliseconds.
+    pub const VALIDITY: UnixTime = 60 * 1000;
+    /// How long players have to reconnect to the replacement process to reclaim their progress.
+    pub const RECLAIM: Duration = Duration::from_secs(2 * 60);
+
+    /// Writes the handoff to `path`, replacing any existing file all at once.
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let bytes =
+            bincode::serialize(self).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let temporary = format!("{}.tmp", path);
+        fs::write(&temporary, bytes)?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Reads and removes the handoff at `path`, if any, so it is only resumed from once. Returns
+    /// [`None`] if there was no valid handoff as of `now`.
+    pub fn take(path: &str, now: UnixTime) -> Option<Self> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                error!("could not read handoff {}: {}", path, e);
+                return None;
+            }
+        };
+        if let Err(e) = fs::remove_file(path) {
+            error!("could not remove handoff {}: {}", path, e);
+        }
+
+        match bincode::deserialize::<Self>(&bytes) {
+            Ok(handoff) if now.saturating_sub(handoff.written) <= Self::VALIDITY => Some(handoff),
+            Ok(_) => {
+                warn!("discarding stale handoff {}", path);
+                None
+            }
+            Err(e) => {
+                error!("invalid handoff {}: {}", path, e);
+                None
+            }
+        }
+    }
+}
+
+/// Asks the server to write a [`Handoff`] to its `--handoff-file`, prior to shutting down.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct HandoffRequest;
+
+impl<G: GameArenaService> Handler<HandoffRequest> for Infrastructure<G> {
+    type Result = ();
+
+    fn handle(&mut self, _request: HandoffRequest, _: &mut Self::Context) -> Self::Result {
+        let path = unwrap_or_return!(self.handoff_file.as_deref());
+
+        // Overflow arenas are handed off too.
+        let mut worlds = Vec::new();
+        let mut sessions = Vec::new();
+        for context_service in self.overflow.iter_mut(&mut self.context_service) {
+            let arena_id = context_service.context.arena_id;
+            let service = &mut context_service.service;
+            let players = &context_service.context.players;
+            for player_tuple in players.players.values() {
+                let player = player_tuple.borrow_player();
+                let client = match player.client() {
+                    Some(client) if !client.observer => client,
+                    _ => continue,
+                };
+                // Players who already left the game have nothing to reclaim.
+                if !matches!(
+                    client.status,
+                    ClientStatus::Connected { .. } | ClientStatus::Limbo { .. }
+                ) {
+                    continue;
+                }
+                let session_id = client.session_id;
+                let player_id = player.player_id;
+                let alias = player.alias();
+                // The game may change the score while giving up progress.
+                let score = player.score;
+                drop(player);
+
+                let game = service.transfer_out(player_tuple).unwrap_or_default();
+                sessions.push((
+                    session_id,
+                    player_id,
+                    arena_id,
+                    Progress { alias, score, game },
+                ));
+            }
+
+            // After transferring out players, so their boats aren't saved twice.
+            worlds.push((arena_id, service.save_world()));
+        }
+
+        let handoff = Handoff {
+            written: get_unix_time_now(),
+            arenas: worlds
+                .into_iter()
+                .map(|(arena_id, world)| ArenaHandoff {
+                    arena_id,
+                    private: self.overflow.private_creator(arena_id),
+                    world,
+                })
+                .collect(),
+            sessions,
+        };
+        match handoff.write(path) {
+            Ok(()) => info!(
+                "wrote handoff of {} players to {}",
+                handoff.sessions.len(),
+                path
+            ),
+            Err(e) => error!("could not write handoff {}: {}", path, e),
+        }
+    }
+}
+
+impl<G: GameArenaService> Infrastructure<G> {
+    /// Continues from the [`Handoff`] of a previous process, if any. Call before the first update.
+    pub(crate) fn resume_handoff(&mut self) {
+        let path = unwrap_or_return!(self.handoff_file.as_deref());
+        let handoff = unwrap_or_return!(Handoff::take(path, get_unix_time_now()));
+
+        let until = Instant::now() + Handoff::RECLAIM;
+        for arena in &handoff.arenas {
+            let context_service = if arena.arena_id == self.context_service.context.arena_id {
+                &mut self.context_service
+            } else {
+                // Kept until its players have had a chance to reconnect.
+                self.overflow.resume(arena.arena_id, arena.private, until)
+            };
+            if let Some(world) = &arena.world {
+                if let Err(e) = context_service.service.restore_world(world) {
+                    error!(
+                        "could not restore world of {:?} from handoff: {}",
+                        arena.arena_id, e
+                    );
+                }
+            }
+        }
+
+        let clients = &mut self.context_service.context.clients;
+        info!(
+            "resuming handoff of {} arenas and {} players",
+            handoff.arenas.len(),
+            handoff.sessions.len()
+        );
+        for (session_id, player_id, arena_id, progress) in handoff.sessions {
+            clients
+                .handed_off
+                .insert(session_id, (player_id, arena_id, progress, until));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handoff::{ArenaHandoff, Handoff};
+    use crate::transfer::Progress;
+    use core_protocol::id::{ArenaId, PlayerId, SessionId};
+    use core_protocol::name::PlayerAlias;
+    use std::num::{NonZeroU32, NonZeroU64};
+
+    #[test]
+    fn write_take() {
+        let path = std::env::temp_dir().join(format!("handoff_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let arena_id = ArenaId(NonZeroU32::new(2001).unwrap());
+        let handoff = Handoff {
+            written: 1000,
+            arenas: vec![ArenaHandoff {
+                arena_id,
+                private: None,
+                world: Some(vec![1, 2, 3]),
+            }],
+            sessions: vec![(
+                SessionId(NonZeroU64::new(5).unwrap()),
+                PlayerId(NonZeroU32::new(7).unwrap()),
+                arena_id,
+                Progress {
+                    alias: PlayerAlias::new_unsanitized("Captain"),
+                    score: 1234,
+                    game: Vec::new(),
+                },
+            )],
+        };
+        handoff.write(path).unwrap();
+        assert_eq!(Handoff::take(path, 1500), Some(handoff));
+
+        // Each handoff may only be resumed from once.
+        assert_eq!(Handoff::take(path, 1500), None);
+
+        let stale = Handoff {
+            written: 1000,
+            arenas: Vec::new(),
+            sessions: Vec::new(),
+        };
+        stale.write(path).unwrap();
+        assert_eq!(Handoff::take(path, 1000 + Handoff::VALIDITY + 1), None);
+    }
+}
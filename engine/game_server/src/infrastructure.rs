@@ -2,19 +2,23 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::admin::AdminRepo;
+use crate::chat::NewPlayerRestrictions;
 use crate::client::ClientRepo;
-use crate::context_service::ContextService;
-use crate::discord::{DiscordBotRepo, DiscordOauth2Repo};
-use crate::game_service::GameArenaService;
+use crate::context_service::{ArenaTemplate, ContextService, OverflowArenas};
+use crate::discord::{DiscordBotRepo, DiscordEvent, DiscordOauth2Repo, DiscordWebhookRepo};
+use crate::game_service::{ArenaOptions, GameArenaService};
 use crate::invitation::InvitationRepo;
 use crate::leaderboard::LeaderboardRepo;
+use crate::login::Oauth2Repo;
 use crate::metric::MetricRepo;
 use crate::status::StatusRepo;
 use crate::system::SystemRepo;
+use crate::tournament::TournamentRepo;
+use crate::unwrap_or_return;
 use actix::AsyncContext;
-use actix::{Actor, Context as ActorContext};
+use actix::{Actor, ActorFutureExt, Context as ActorContext, ContextFutureSpawner, WrapFuture};
 use core_protocol::id::{ArenaId, RegionId, ServerId};
-use log::{error, info};
+use log::{error, info, warn};
 use minicdn::MiniCdn;
 use server_util::database::Database;
 use server_util::rate_limiter::RateLimiterProps;
@@ -34,10 +38,15 @@ pub struct Infrastructure<G: GameArenaService> {
     pub(crate) database: &'static Database,
     pub(crate) system: Option<SystemRepo<G>>,
     pub(crate) discord_bot: Option<&'static DiscordBotRepo>,
+    pub(crate) discord_webhooks: Option<&'static DiscordWebhookRepo>,
     pub(crate) discord_oauth2: Option<&'static DiscordOauth2Repo>,
+    pub(crate) google_oauth2: Option<&'static Oauth2Repo>,
+    pub(crate) apple_oauth2: Option<&'static Oauth2Repo>,
 
-    /// Game specific stuff. In the future, there could be multiple of these.
+    /// Game specific stuff, of the primary arena.
     pub(crate) context_service: ContextService<G>,
+    /// Additional arenas, during population spikes (see `--arena-capacity`).
+    pub(crate) overflow: OverflowArenas<G>,
 
     /// Shared invitations.
     pub(crate) invitations: InvitationRepo<G>,
@@ -47,10 +56,18 @@ pub struct Infrastructure<G: GameArenaService> {
     pub(crate) leaderboard: LeaderboardRepo<G>,
     /// Shared metrics.
     pub(crate) metrics: MetricRepo<G>,
+    /// Shared tournament.
+    pub(crate) tournament: TournamentRepo<G>,
 
     /// Monitoring.
     pub(crate) status: StatusRepo,
 
+    /// Where to write a handoff before shutting down, and resume from upon starting (see
+    /// `--handoff-file`).
+    pub(crate) handoff_file: Option<String>,
+    /// How often arenas are updated, which is the shortest tick period of any arena. Arenas with a
+    /// longer tick period skip some updates (see `--tick-rate` and `--overflow-tick-rate`).
+    update_period: Duration,
     /// Drop missed updates.
     last_update: Instant,
 }
@@ -64,7 +81,7 @@ impl<G: GameArenaService> Actor for Infrastructure<G> {
         // TODO: Investigate whether this only affects performance or can affect correctness.
         ctx.set_mailbox_capacity(50);
 
-        ctx.run_interval(Duration::from_secs_f32(G::TICK_PERIOD_SECS), Self::update);
+        ctx.run_interval(self.update_period, Self::update);
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
@@ -81,26 +98,60 @@ impl<G: GameArenaService> Infrastructure<G> {
         server_id: Option<ServerId>,
         system: Option<SystemRepo<G>>,
         discord_bot: Option<DiscordBotRepo>,
+        discord_webhooks: Option<DiscordWebhookRepo>,
         discord_oauth2: Option<&'static DiscordOauth2Repo>,
+        google_oauth2: Option<&'static Oauth2Repo>,
+        apple_oauth2: Option<&'static Oauth2Repo>,
         client_hash: u64,
         region_id: Option<RegionId>,
         database_read_only: bool,
         min_bots: Option<usize>,
         max_bots: Option<usize>,
         bot_percent: Option<usize>,
+        arena_options: ArenaOptions,
         chat_log: Option<String>,
+        event_log: Option<String>,
         trace_log: Option<String>,
+        desync_dir: Option<String>,
         game_client: Arc<RwLock<MiniCdn>>,
         allow_web_socket_json: &'static AtomicBool,
         admin_config_file: Option<String>,
         client_authenticate: RateLimiterProps,
+        max_observers: usize,
+        new_player_restrictions: NewPlayerRestrictions,
+        season_period: Option<Duration>,
+        tick_rate: Option<u16>,
+        overflow_tick_rate: Option<u16>,
+        handoff_file: Option<String>,
+        arena_capacity: Option<usize>,
     ) -> Self {
-        // TODO: If multiple arenas, generate randomly.
+        let tick_period = tick_period::<G>(tick_rate);
+        let overflow_tick_period = overflow_tick_rate
+            .map(|rate| tick_period::<G>(Some(rate)))
+            .unwrap_or(tick_period);
+
+        // Stable across restarts, unlike the ids of overflow arenas.
         let arena_id = ArenaId(
             NonZeroU32::new(server_id.map(|s| s.0.get()).unwrap_or(0) as u32 + 2000).unwrap(),
         );
 
-        Self {
+        let template = ArenaTemplate {
+            min_bots,
+            max_bots,
+            bot_percent,
+            arena_options,
+            chat_log,
+            event_log,
+            trace_log,
+            desync_dir,
+            client_authenticate,
+            max_observers,
+            new_player_restrictions,
+            season_period,
+            tick_period,
+        };
+
+        let mut infrastructure = Self {
             server_id,
             region_id,
             /// Leak the boxes, because static lifetime facilitates async code. This will probably
@@ -108,30 +159,30 @@ impl<G: GameArenaService> Infrastructure<G> {
             database: Box::leak(Box::new(Database::new(database_read_only).await)),
             system,
             discord_bot: discord_bot.map(|b| &*Box::leak(Box::new(b))),
+            discord_webhooks: discord_webhooks.map(|w| &*Box::leak(Box::new(w))),
             discord_oauth2,
+            google_oauth2,
+            apple_oauth2,
             admin: AdminRepo::new(game_client, admin_config_file, allow_web_socket_json),
-            context_service: ContextService::new(
-                arena_id,
-                min_bots,
-                max_bots,
-                bot_percent,
-                chat_log,
-                trace_log,
-                client_authenticate,
-            ),
+            context_service: ContextService::from_template(arena_id, &template),
+            overflow: OverflowArenas::new(arena_capacity, template, overflow_tick_period),
             invitations: InvitationRepo::new(),
             leaderboard: LeaderboardRepo::new(),
             metrics: MetricRepo::new(),
+            tournament: TournamentRepo::new(),
             status: StatusRepo::new(client_hash),
+            handoff_file,
+            update_period: tick_period.min(overflow_tick_period),
             last_update: Instant::now(),
-        }
+        };
+        infrastructure.resume_handoff();
+        infrastructure
     }
 
     /// Call once every tick.
     pub fn update(&mut self, ctx: &mut <Infrastructure<G> as Actor>::Context) {
         let now = Instant::now();
-        if now.duration_since(self.last_update) < Duration::from_secs_f32(G::TICK_PERIOD_SECS * 0.5)
-        {
+        if now.duration_since(self.last_update) < self.update_period / 2 {
             // Less than half a tick elapsed. Drop this update on the floor, to avoid jerking.
             return;
         }
@@ -139,15 +190,24 @@ impl<G: GameArenaService> Infrastructure<G> {
 
         let status = &self.status;
         let server_delta = self.system.as_mut().and_then(|system| system.delta(status));
-        self.context_service.update(
-            &mut self.leaderboard,
-            &mut self.invitations,
-            &mut self.metrics,
-            self.server_id,
-            server_delta,
-        );
+        for context_service in self.overflow.iter_mut(&mut self.context_service) {
+            if !context_service.is_due(now, self.update_period) {
+                continue;
+            }
+            context_service.update(
+                &mut self.leaderboard,
+                &mut self.invitations,
+                &mut self.metrics,
+                self.server_id,
+                server_delta.clone(),
+            );
+        }
+        self.overflow.prune(&mut self.invitations);
         self.leaderboard.clear_deltas();
-        self.status.health.record_tick(G::TICK_PERIOD_SECS);
+        self.status.tick_duration = now.elapsed();
+        self.status
+            .health
+            .record_tick(self.update_period.as_secs_f32());
 
         // These are all rate-limited internally.
         LeaderboardRepo::update_to_database(self, ctx);
@@ -155,6 +215,28 @@ impl<G: GameArenaService> Infrastructure<G> {
         MetricRepo::update_to_database(self, ctx);
         ClientRepo::update_to_database(self, ctx);
         SystemRepo::update(self, ctx);
+        TournamentRepo::update(self, ctx);
+        self.status.update_thumbnail(&self.context_service.service);
+    }
+
+    /// Publishes an event to Discord, if it has a webhook (see `--discord-webhook`).
+    pub(crate) fn publish_discord_event(
+        &mut self,
+        event: DiscordEvent,
+        message: String,
+        ctx: &mut <Infrastructure<G> as Actor>::Context,
+    ) {
+        let discord_webhooks = unwrap_or_return!(self
+            .discord_webhooks
+            .filter(|webhooks| webhooks.is_enabled(event)));
+        async move { discord_webhooks.send_event(event, &message).await }
+            .into_actor(self)
+            .map(move |res, _act, _| {
+                if let Err(e) = res {
+                    error!("error publishing {:?} to discord: {}", event, e);
+                }
+            })
+            .spawn(ctx);
     }
 
     /// Returns a static reference to the database singleton.
@@ -162,3 +244,45 @@ impl<G: GameArenaService> Infrastructure<G> {
         self.database
     }
 }
+
+/// Length of a tick, in order to run at `tick_rate` (in Hz), which defaults to the game's native
+/// rate.
+fn tick_period<G: GameArenaService>(tick_rate: Option<u16>) -> Duration {
+    /// Higher rates would spend most of the time simulating, for little benefit.
+    const MAX_TICK_RATE: u16 = 60;
+
+    let native_period = Duration::from_secs_f32(G::TICK_PERIOD_SECS);
+    match tick_rate {
+        Some(rate) if (1..=MAX_TICK_RATE).contains(&rate) => {
+            Duration::from_secs_f32(1.0 / rate as f32)
+        }
+        Some(rate) => {
+            warn!(
+                "tick rate of {}Hz is outside 1-{}Hz, using the native rate",
+                rate, MAX_TICK_RATE
+            );
+            native_period
+        }
+        None => native_period,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_service::MockGame;
+    use crate::infrastructure::tick_period;
+    use std::time::Duration;
+
+    #[test]
+    fn tick_period_mock() {
+        // MockGame runs at 2Hz.
+        assert_eq!(tick_period::<MockGame>(None), Duration::from_millis(500));
+        assert_eq!(tick_period::<MockGame>(Some(1)), Duration::from_secs(1));
+        assert_eq!(tick_period::<MockGame>(Some(20)), Duration::from_millis(50));
+        assert_eq!(tick_period::<MockGame>(Some(0)), Duration::from_millis(500));
+        assert_eq!(
+            tick_period::<MockGame>(Some(1000)),
+            Duration::from_millis(500)
+        );
+    }
+}
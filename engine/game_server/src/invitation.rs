@@ -3,10 +3,11 @@
 
 use crate::game_service::GameArenaService;
 use crate::player::{PlayerData, PlayerRepo};
+use crate::team::TeamRepo;
 use crate::unwrap_or_return;
 use atomic_refcell::AtomicRefMut;
 use core_protocol::dto::InvitationDto;
-use core_protocol::id::{ArenaId, InvitationId, PlayerId, ServerId};
+use core_protocol::id::{ArenaId, InvitationId, PlayerId, ServerId, TeamId};
 use core_protocol::rpc::{InvitationRequest, InvitationUpdate};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -74,6 +75,53 @@ impl<G: GameArenaService> InvitationRepo<G> {
         }
     }
 
+    /// Creates an invitation to a private arena, on behalf of the player who created the arena.
+    pub(crate) fn create_arena_invitation(
+        &mut self,
+        arena_id: ArenaId,
+        player_id: PlayerId,
+        server_id: Option<ServerId>,
+    ) -> InvitationId {
+        loop {
+            let invitation_id = InvitationId::generate(server_id);
+            if let Entry::Vacant(entry) = self.invitations.entry(invitation_id) {
+                entry.insert(Invitation {
+                    arena_id,
+                    player_id,
+                });
+                break invitation_id;
+            }
+        }
+    }
+
+    /// Forgets all invitations to an arena, which was removed.
+    pub(crate) fn forget_arena_invitations(&mut self, arena_id: ArenaId) {
+        self.invitations
+            .retain(|_, invitation| invitation.arena_id != arena_id);
+    }
+
+    /// Puts a player who accepted an invitation on their inviter's team, if the inviter is on a
+    /// team with room for them.
+    pub(crate) fn join_inviter_team(
+        player_id: PlayerId,
+        players: &PlayerRepo<G>,
+        teams: &mut TeamRepo<G>,
+    ) -> Result<TeamId, &'static str> {
+        let inviter_id = players
+            .borrow_player(player_id)
+            .ok_or("nonexistent player")?
+            .invitation_accepted()
+            .ok_or("no invitation accepted")?
+            .player_id;
+        let team_id = players
+            .borrow_player(inviter_id)
+            .ok_or("inviter left")?
+            .team_id()
+            .ok_or("inviter not on team")?;
+        teams.add_member(player_id, team_id, players)?;
+        Ok(team_id)
+    }
+
     /// Requests an invitation id (new or recycled).
     fn create_invitation(
         &mut self,
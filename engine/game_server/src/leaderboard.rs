@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::discord::DiscordEvent;
 use crate::game_service::GameArenaService;
 use crate::infrastructure::Infrastructure;
 use crate::liveboard::LiveboardRepo;
@@ -11,13 +12,16 @@ use actix::{
 };
 use core_protocol::dto::LeaderboardDto;
 use core_protocol::get_unix_time_now;
-use core_protocol::id::PeriodId;
-use core_protocol::name::PlayerAlias;
-use core_protocol::rpc::{LeaderboardResponse, LeaderboardUpdate};
+use core_protocol::id::{PeriodId, PlayerId};
+use core_protocol::name::{ClassName, PlayerAlias};
+use core_protocol::rpc::{
+    LeaderboardRequest as ClientLeaderboardRequest, LeaderboardResponse, LeaderboardUpdate,
+};
 use futures::stream::FuturesUnordered;
 use log::error;
 use server_util::database_schema::{GameIdScoreType, ScoreItem, ScoreType};
 use server_util::rate_limiter::RateLimiter;
+use std::collections::hash_map::Entry;
 use std::collections::{BinaryHeap, HashMap};
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -27,14 +31,24 @@ use std::time::Duration;
 pub struct LeaderboardRepo<G: GameArenaService> {
     /// Stores cached leaderboards from database and whether they were changed.
     leaderboards: [(Arc<[LeaderboardDto]>, bool); std::mem::variant_count::<PeriodId>()],
-    /// Scores that should be committed to database.
-    pending: HashMap<(PlayerAlias, PeriodId), u32>,
+    /// Cached leaderboards of particular classes (see [`Context::submit_class_score`]). Only read
+    /// from database once requested by a client or scored in, and not sent unless requested.
+    ///
+    /// [`Context::submit_class_score`]: crate::context::Context::submit_class_score
+    class_leaderboards: HashMap<(ClassName, PeriodId), Arc<[LeaderboardDto]>>,
+    /// Final standings of the most recent tournament, best first. Not sent unless requested.
+    tournament: Arc<[LeaderboardDto]>,
+    /// Scores that should be committed to database, optionally for a particular class.
+    pending: HashMap<(PlayerAlias, Option<ClassName>, PeriodId), u32>,
     take_pending_rate_limit: RateLimiter,
     read_database_rate_limit: RateLimiter,
     _spooky: PhantomData<G>,
 }
 
 impl<G: GameArenaService> LeaderboardRepo<G> {
+    /// Limits the number of class leaderboards, since clients may request arbitrary classes.
+    const MAX_CLASS_LEADERBOARDS: usize = 1024;
+
     pub fn new() -> Self {
         Self {
             leaderboards: [
@@ -42,6 +56,8 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
                 (Vec::new().into(), false),
                 (Vec::new().into(), false),
             ],
+            class_leaderboards: HashMap::new(),
+            tournament: Vec::new().into(),
             pending: HashMap::new(),
             take_pending_rate_limit: RateLimiter::new(Duration::from_secs(60), 0),
             read_database_rate_limit: RateLimiter::new(Duration::from_secs(110), 0),
@@ -54,6 +70,11 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
         &self.leaderboards[period_id as usize].0
     }
 
+    /// Replaces the standings of the most recent tournament.
+    pub(crate) fn put_tournament(&mut self, standings: Arc<[LeaderboardDto]>) {
+        self.tournament = standings;
+    }
+
     /// Leaderboard relies on an external source of data, such as a database.
     pub fn put_leaderboard(&mut self, period_id: PeriodId, leaderboard: Arc<[LeaderboardDto]>) {
         if &leaderboard != self.get(period_id) {
@@ -61,14 +82,77 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
         }
     }
 
+    /// Describes a change to the top of a leaderboard that is worth publishing to Discord, if any.
+    /// Changes from an empty leaderboard (e.g. upon starting) don't count.
+    fn discord_event(
+        period_id: PeriodId,
+        old: &[LeaderboardDto],
+        new: &[LeaderboardDto],
+    ) -> Option<(DiscordEvent, String)> {
+        let (old_top, new_top) = (old.first()?, new.first()?);
+        if period_id == PeriodId::AllTime && new_top.score > old_top.score {
+            Some((
+                DiscordEvent::HighScore,
+                format!(
+                    "{} set a new all-time high score of {}!",
+                    new_top.alias, new_top.score
+                ),
+            ))
+        } else if new_top.alias != old_top.alias {
+            let period = match period_id {
+                PeriodId::AllTime => "all-time",
+                PeriodId::Daily => "daily",
+                PeriodId::Weekly => "weekly",
+            };
+            Some((
+                DiscordEvent::LeaderboardTurnover,
+                format!(
+                    "{} overtook {} for first place on the {} leaderboard, with {} points.",
+                    new_top.alias, old_top.alias, period, new_top.score
+                ),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Like [`Self::put_leaderboard`], but for the leaderboard of a particular class.
+    pub fn put_class_leaderboard(
+        &mut self,
+        class: ClassName,
+        period_id: PeriodId,
+        leaderboard: Arc<[LeaderboardDto]>,
+    ) {
+        self.class_leaderboards
+            .insert((class, period_id), leaderboard);
+    }
+
     /// Computes minimum score to earn a place on the given leaderboard.
     fn minimum_score(&self, period_id: PeriodId) -> u32 {
-        self.get(period_id)
+        Self::minimum_score_of(self.get(period_id))
+    }
+
+    fn minimum_score_of(leaderboard: &[LeaderboardDto]) -> u32 {
+        leaderboard
             .get(G::LEADERBOARD_SIZE - 1)
             .map(|dto| dto.score)
             .unwrap_or(0)
     }
 
+    /// Starts caching a class leaderboard, so it will be read from the database. Returns false if
+    /// there are too many class leaderboards to do so.
+    fn track_class(&mut self, class: ClassName, period_id: PeriodId) -> bool {
+        let len = self.class_leaderboards.len();
+        match self.class_leaderboards.entry((class, period_id)) {
+            Entry::Occupied(_) => true,
+            Entry::Vacant(_) if len >= Self::MAX_CLASS_LEADERBOARDS => false,
+            Entry::Vacant(entry) => {
+                entry.insert(Vec::new().into());
+                true
+            }
+        }
+    }
+
     /// Process liveboard scores to potentially be added to the leaderboard.
     pub(crate) fn process(&mut self, liveboard: &LiveboardRepo<G>, players: &PlayerRepo<G>) {
         let liveboard_items = liveboard.get();
@@ -96,7 +180,7 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
                     }
 
                     let alias = player.alias();
-                    let entry = self.pending.entry((alias, period_id)).or_insert(0);
+                    let entry = self.pending.entry((alias, None, period_id)).or_insert(0);
                     *entry = dto.score.max(*entry);
                 } else {
                     // TODO: Is this legitimately possible?
@@ -106,6 +190,97 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
         }
     }
 
+    /// Process final scores submitted by the game (see
+    /// [`crate::context::Context::submit_final_score`]) to potentially be added to the daily
+    /// leaderboard.
+    pub(crate) fn process_final_scores(
+        &mut self,
+        final_scores: impl Iterator<Item = (PlayerId, u32)>,
+        players: &PlayerRepo<G>,
+    ) {
+        let enough_players = players.real_players_live >= G::LEADERBOARD_MIN_PLAYERS;
+        let minimum_score = self.minimum_score(PeriodId::Daily);
+
+        for (player_id, score) in final_scores {
+            if !enough_players || score == 0 || score < minimum_score {
+                continue;
+            }
+
+            if let Some(player) = players.borrow_player(player_id) {
+                if player.is_bot() {
+                    continue;
+                }
+
+                let entry = self
+                    .pending
+                    .entry((player.alias(), None, PeriodId::Daily))
+                    .or_insert(0);
+                *entry = score.max(*entry);
+            }
+        }
+    }
+
+    /// Process class scores submitted by the game (see
+    /// [`crate::context::Context::submit_class_score`]) to potentially be added to the class
+    /// leaderboards.
+    pub(crate) fn process_class_scores(
+        &mut self,
+        class_scores: impl Iterator<Item = (PlayerId, ClassName, u32)>,
+        players: &PlayerRepo<G>,
+    ) {
+        let enough_players = players.real_players_live >= G::LEADERBOARD_MIN_PLAYERS;
+
+        for (player_id, class, score) in class_scores {
+            if !enough_players || score == 0 {
+                continue;
+            }
+
+            let alias = match players.borrow_player(player_id) {
+                Some(player) if !player.is_bot() => player.alias(),
+                _ => continue,
+            };
+
+            for period_id in PeriodId::iter() {
+                if !self.track_class(class, period_id) {
+                    continue;
+                }
+                let minimum_score =
+                    Self::minimum_score_of(&self.class_leaderboards[&(class, period_id)]);
+                if score < minimum_score {
+                    continue;
+                }
+
+                let entry = self
+                    .pending
+                    .entry((alias, Some(class), period_id))
+                    .or_insert(0);
+                *entry = score.max(*entry);
+            }
+        }
+    }
+
+    /// Responds to a client's request for a class or tournament leaderboard.
+    pub(crate) fn handle_leaderboard_request(
+        &mut self,
+        request: ClientLeaderboardRequest,
+    ) -> Result<LeaderboardUpdate, &'static str> {
+        match request {
+            ClientLeaderboardRequest::Class(class, period_id) => {
+                if !self.track_class(class, period_id) {
+                    return Err("too many class leaderboards");
+                }
+                Ok(LeaderboardUpdate::Class(
+                    class,
+                    period_id,
+                    Arc::clone(&self.class_leaderboards[&(class, period_id)]),
+                ))
+            }
+            ClientLeaderboardRequest::Tournament => {
+                Ok(LeaderboardUpdate::Tournament(Arc::clone(&self.tournament)))
+            }
+        }
+    }
+
     /// Returns scores pending database commit, draining them in the process. Rate limited.
     pub fn take_pending(&mut self) -> Option<impl Iterator<Item = ScoreItem> + '_> {
         if self.pending.is_empty() || self.take_pending_rate_limit.should_limit_rate() {
@@ -116,7 +291,7 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
             Some(
                 self.pending
                     .drain()
-                    .map(move |((alias, period_id), score)| {
+                    .map(move |((alias, class, period_id), score)| {
                         let score_type = match period_id {
                             PeriodId::AllTime => ScoreType::PlayerAllTime,
                             PeriodId::Daily => ScoreType::PlayerDay,
@@ -127,6 +302,7 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
                             game_id_score_type: GameIdScoreType {
                                 game_id: G::GAME_ID,
                                 score_type,
+                                class,
                             },
                             alias: alias.to_string(),
                             score,
@@ -151,7 +327,18 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
             return;
         }
 
-        for period_id in PeriodId::iter() {
+        let leaderboards: Vec<(Option<ClassName>, PeriodId)> = PeriodId::iter()
+            .map(|period_id| (None, period_id))
+            .chain(
+                infrastructure
+                    .leaderboard
+                    .class_leaderboards
+                    .keys()
+                    .map(|&(class, period_id)| (Some(class), period_id)),
+            )
+            .collect();
+
+        for (class, period_id) in leaderboards {
             infrastructure
                 .database()
                 .read_scores_by_type(GameIdScoreType {
@@ -161,9 +348,10 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
                         PeriodId::Weekly => ScoreType::PlayerWeek,
                         PeriodId::AllTime => ScoreType::PlayerAllTime,
                     },
+                    class,
                 })
                 .into_actor(infrastructure)
-                .map(move |res, act, _| match res {
+                .map(move |res, act, ctx| match res {
                     Ok(scores) => {
                         let heap: BinaryHeap<LeaderboardDto> = scores
                             .into_iter()
@@ -176,7 +364,20 @@ impl<G: GameArenaService> LeaderboardRepo<G> {
                         let leaderboard =
                             heap.into_iter_sorted().take(G::LEADERBOARD_SIZE).collect();
 
-                        act.leaderboard.put_leaderboard(period_id, leaderboard)
+                        if let Some(class) = class {
+                            act.leaderboard
+                                .put_class_leaderboard(class, period_id, leaderboard)
+                        } else {
+                            let event = Self::discord_event(
+                                period_id,
+                                act.leaderboard.get(period_id),
+                                &leaderboard,
+                            );
+                            act.leaderboard.put_leaderboard(period_id, leaderboard);
+                            if let Some((event, message)) = event {
+                                act.publish_discord_event(event, message, ctx);
+                            }
+                        }
                     }
                     Err(e) => {
                         error!("error reading leaderboard scores: {:?}", e);
@@ -304,3 +505,71 @@ impl<G: GameArenaService> Handler<LeaderboardRequest> for Infrastructure<G> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::discord::DiscordEvent;
+    use crate::game_service::MockGame;
+    use crate::leaderboard::LeaderboardRepo;
+    use core_protocol::dto::LeaderboardDto;
+    use core_protocol::id::PeriodId;
+    use core_protocol::name::PlayerAlias;
+
+    fn event(
+        period_id: PeriodId,
+        old: &[(&str, u32)],
+        new: &[(&str, u32)],
+    ) -> Option<DiscordEvent> {
+        fn dtos(scores: &[(&str, u32)]) -> Vec<LeaderboardDto> {
+            scores
+                .iter()
+                .map(|&(alias, score)| LeaderboardDto {
+                    alias: PlayerAlias::new_unsanitized(alias),
+                    score,
+                })
+                .collect()
+        }
+
+        LeaderboardRepo::<MockGame>::discord_event(period_id, &dtos(old), &dtos(new))
+            .map(|(event, _)| event)
+    }
+
+    #[test]
+    fn discord_event() {
+        // Loading the leaderboard for the first time.
+        assert_eq!(event(PeriodId::AllTime, &[], &[("Bob", 10)]), None);
+
+        // Below first place.
+        assert_eq!(
+            event(PeriodId::Daily, &[("Bob", 10)], &[("Bob", 10), ("Eve", 5)]),
+            None
+        );
+
+        assert_eq!(
+            event(PeriodId::AllTime, &[("Bob", 10)], &[("Bob", 20)]),
+            Some(DiscordEvent::HighScore)
+        );
+        assert_eq!(
+            event(
+                PeriodId::AllTime,
+                &[("Bob", 10)],
+                &[("Eve", 20), ("Bob", 10)]
+            ),
+            Some(DiscordEvent::HighScore)
+        );
+
+        // Only the all-time leaderboard has high scores.
+        assert_eq!(
+            event(PeriodId::Weekly, &[("Bob", 10)], &[("Bob", 20)]),
+            None
+        );
+        assert_eq!(
+            event(
+                PeriodId::Weekly,
+                &[("Bob", 10)],
+                &[("Eve", 20), ("Bob", 10)]
+            ),
+            Some(DiscordEvent::LeaderboardTurnover)
+        );
+    }
+}
@@ -15,14 +15,19 @@
 #![feature(result_into_ok_or_err)]
 #![feature(result_option_inspect)]
 
+pub mod achievement;
 pub mod admin;
 pub mod bot;
 pub mod chat;
 pub mod client;
+pub(crate) mod compat;
 pub mod context;
 pub mod context_service;
+pub mod desync;
 pub mod entry_point;
+pub mod experiment;
 pub mod game_service;
+pub(crate) mod handoff;
 pub mod infrastructure;
 pub mod invitation;
 pub mod leaderboard;
@@ -30,12 +35,18 @@ pub mod liveboard;
 pub mod metric;
 pub mod ordered_set;
 pub mod player;
+pub mod prometheus;
+pub mod season;
 pub mod status;
+pub mod streak;
 pub mod team;
+pub mod tournament;
+pub(crate) mod transfer;
 #[macro_use]
 pub mod util;
 pub mod discord;
-pub(crate) mod log;
+pub mod log;
+pub mod login;
 pub(crate) mod options;
 pub mod static_files;
 pub mod system;
@@ -1,7 +1,24 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::game_service::GameArenaService;
 use crate::options::Options;
+use core_protocol::dto::{ArenaEvent, ArenaEventDto};
+use core_protocol::id::ArenaId;
+use core_protocol::{get_unix_time_now, UnixTime};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::channel::oneshot;
+use log::{error, warn};
+use server_util::rate_limiter::RateLimiter;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 pub(crate) fn init_logger(options: &Options) {
     let mut logger = env_logger::builder();
@@ -15,3 +32,328 @@ pub(crate) fn init_logger(options: &Options) {
     logger.filter_module("server_util::ssl", options.debug_watchdog);
     logger.init();
 }
+
+/// Component of [`Context`] that keeps a structured log of arena events (joins, sinkings,
+/// moderation actions, etc.) so that incidents can be investigated after the fact.
+pub struct EventLogRepo<G> {
+    arena_id: ArenaId,
+    /// Most recent events, for when there is no log file.
+    recent: VecDeque<ArenaEventDto>,
+    /// Events that have yet to be sent to the writer.
+    pending: Vec<ArenaEventDto>,
+    /// Writes, rotates, and reads the log file, if there is one.
+    writer: Option<UnboundedSender<LogCommand>>,
+    flush_rate_limiter: RateLimiter,
+    _spooky: PhantomData<G>,
+}
+
+impl<G: GameArenaService> EventLogRepo<G> {
+    /// Maximum number of events to remember in memory.
+    const RECENT_MAX: usize = 4096;
+
+    pub fn new(arena_id: ArenaId, log_path: Option<String>) -> Self {
+        Self {
+            arena_id,
+            recent: VecDeque::new(),
+            pending: Vec::new(),
+            writer: log_path.map(LogWriter::spawn),
+            flush_rate_limiter: RateLimiter::new(Duration::from_secs(5), 0),
+            _spooky: PhantomData,
+        }
+    }
+
+    /// Records an event that happened just now.
+    pub fn record(&mut self, event: ArenaEvent) {
+        let dto = ArenaEventDto {
+            arena_id: self.arena_id,
+            date_created: get_unix_time_now(),
+            event,
+        };
+        if self.writer.is_some() {
+            self.pending.push(dto.clone());
+        }
+        if self.recent.len() >= Self::RECENT_MAX {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(dto);
+    }
+
+    /// Writes pending events to the log file, if one is configured. Rate limited internally.
+    pub(crate) fn update(&mut self) {
+        if self.pending.is_empty() || self.flush_rate_limiter.should_limit_rate() {
+            return;
+        }
+        self.flush();
+    }
+
+    /// Sends pending events to the writer. They may not be written yet, but queries will see them.
+    fn flush(&mut self) {
+        let writer = crate::unwrap_or_return!(self.writer.as_ref());
+
+        let mut lines = Vec::with_capacity(self.pending.len() * 128);
+        for event in self.pending.drain(..) {
+            if let Err(e) = serde_json::to_writer(&mut lines, &event) {
+                error!("error composing event line: {:?}", e);
+            }
+            lines.push(b'\n');
+        }
+
+        if writer.unbounded_send(LogCommand::Write(lines)).is_err() {
+            error!("event log writer stopped");
+        }
+    }
+
+    /// Gets events that happened within a period (inclusive), oldest first.
+    pub(crate) fn query(
+        &self,
+        period_start: Option<UnixTime>,
+        period_stop: Option<UnixTime>,
+    ) -> impl Future<Output = Result<Box<[ArenaEventDto]>, &'static str>> + 'static {
+        let in_period = move |event: &ArenaEventDto| {
+            within_period(event.date_created, period_start, period_stop)
+        };
+
+        // If there is a log file, only the pending events are missing from it (events that were
+        // sent to the writer are written before it handles the query).
+        let (in_memory, from_log) = if let Some(writer) = self.writer.as_ref() {
+            let (sender, receiver) = oneshot::channel();
+            let sent = writer.unbounded_send(LogCommand::Query {
+                predicate: Box::new(in_period),
+                sender,
+            });
+            (&self.pending, sent.is_ok().then_some(receiver))
+        } else {
+            (&self.recent, None)
+        };
+        let in_memory: Vec<ArenaEventDto> = in_memory
+            .iter()
+            .filter(|&e| in_period(e))
+            .cloned()
+            .collect();
+        let has_log = self.writer.is_some();
+
+        async move {
+            let mut events = match from_log {
+                Some(receiver) => receiver
+                    .await
+                    .map_err(|_| "event log query canceled")?
+                    .map_err(|e| {
+                        error!("error reading event log: {:?}", e);
+                        "error reading event log"
+                    })?,
+                None if has_log => return Err("event log writer stopped"),
+                None => VecDeque::new(),
+            };
+
+            for event in in_memory {
+                push_limited(&mut events, event, QUERY_MAX);
+            }
+
+            Ok(Vec::from(events).into_boxed_slice())
+        }
+    }
+
+    /// Combines the results of querying several arenas, oldest first.
+    pub(crate) fn merge(arenas: Vec<Box<[ArenaEventDto]>>) -> Box<[ArenaEventDto]> {
+        let mut events: Vec<_> = arenas.into_iter().flat_map(|a| a.into_vec()).collect();
+        // Stable, so events of each arena stay in order.
+        events.sort_by_key(|event| event.date_created);
+        let excess = events.len().saturating_sub(QUERY_MAX);
+        events.drain(..excess);
+        events.into_boxed_slice()
+    }
+}
+
+/// Maximum number of events returned by a single query (most recent are kept).
+const QUERY_MAX: usize = 2048;
+
+/// Sent to a [`LogWriter`], which handles them in order.
+enum LogCommand {
+    /// Append lines to the log file.
+    Write(Vec<u8>),
+    /// Read events matching the predicate from all log files.
+    Query {
+        predicate: Box<dyn Fn(&ArenaEventDto) -> bool + Send>,
+        sender: oneshot::Sender<io::Result<VecDeque<ArenaEventDto>>>,
+    },
+}
+
+/// Owns a log file (one JSON object per line), rotating it when it gets too big. A single writer
+/// per file means writes, rotations, and reads can't interleave, and a query sees every event
+/// written before it.
+struct LogWriter {
+    log_path: String,
+}
+
+impl LogWriter {
+    /// Rotate the log file after it reaches this size.
+    const ROTATE_BYTES: u64 = 16 * 1024 * 1024;
+    /// Keep this many rotated log files (in addition to the current one).
+    const ROTATIONS: usize = 4;
+
+    /// Starts a writer thread, which stops once the returned sender is dropped.
+    fn spawn(log_path: String) -> UnboundedSender<LogCommand> {
+        let (sender, receiver) = mpsc::unbounded();
+        let writer = Self { log_path };
+        thread::Builder::new()
+            .name(String::from("event_log"))
+            .spawn(move || {
+                for command in futures::executor::block_on_stream(receiver) {
+                    writer.handle(command);
+                }
+            })
+            .expect("could not spawn event log writer");
+        sender
+    }
+
+    fn handle(&self, command: LogCommand) {
+        match command {
+            LogCommand::Write(lines) => {
+                if let Err(e) = self.rotate_if_necessary().and_then(|_| {
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&self.log_path)
+                        .and_then(move |mut file| file.write_all(&lines))
+                }) {
+                    error!("error logging events: {:?}", e);
+                }
+            }
+            LogCommand::Query { predicate, sender } => {
+                // The query may have been abandoned.
+                let _ = sender.send(self.read_log(predicate));
+            }
+        }
+    }
+
+    /// Reads all events matching a predicate from all log files, oldest first.
+    fn read_log(
+        &self,
+        predicate: impl Fn(&ArenaEventDto) -> bool,
+    ) -> io::Result<VecDeque<ArenaEventDto>> {
+        let mut events = VecDeque::new();
+        for n in (0..=Self::ROTATIONS).rev() {
+            let file = match File::open(&*rotated_path(&self.log_path, n)) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for line in BufReader::new(file).lines() {
+                let line = line?;
+                match serde_json::from_str::<ArenaEventDto>(&line) {
+                    Ok(event) => {
+                        if predicate(&event) {
+                            push_limited(&mut events, event, QUERY_MAX);
+                        }
+                    }
+                    Err(e) => warn!("skipping invalid event line: {:?}", e),
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Shifts each log file to the next rotation, if the current one is too big.
+    fn rotate_if_necessary(&self) -> io::Result<()> {
+        match fs::metadata(&self.log_path) {
+            Ok(metadata) if metadata.len() >= Self::ROTATE_BYTES => {}
+            Ok(_) => return Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        }
+
+        for n in (0..Self::ROTATIONS).rev() {
+            let from = rotated_path(&self.log_path, n);
+            if Path::new(&*from).exists() {
+                fs::rename(&*from, &*rotated_path(&self.log_path, n + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Path of the `n`th most recent log file (0 is the current log file).
+fn rotated_path(log_path: &str, n: usize) -> Cow<str> {
+    if n == 0 {
+        Cow::Borrowed(log_path)
+    } else {
+        Cow::Owned(format!("{}.{}", log_path, n))
+    }
+}
+
+/// Returns true iff the time is within the (inclusive) period. Missing bounds are unbounded.
+fn within_period(time: UnixTime, start: Option<UnixTime>, stop: Option<UnixTime>) -> bool {
+    start.map(|start| time >= start).unwrap_or(true)
+        && stop.map(|stop| time <= stop).unwrap_or(true)
+}
+
+/// Pushes to the back, discarding from the front to stay within the limit.
+fn push_limited<T>(deque: &mut VecDeque<T>, item: T, limit: usize) {
+    if deque.len() >= limit {
+        deque.pop_front();
+    }
+    deque.push_back(item);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_service::MockGame;
+    use crate::log::{push_limited, rotated_path, within_period, EventLogRepo};
+    use core_protocol::dto::ArenaEvent;
+    use core_protocol::id::{ArenaId, PlayerId};
+    use core_protocol::name::PlayerAlias;
+    use std::collections::VecDeque;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn period() {
+        assert!(within_period(5, None, None));
+        assert!(within_period(5, Some(5), Some(5)));
+        assert!(!within_period(4, Some(5), None));
+        assert!(!within_period(6, None, Some(5)));
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(rotated_path("events.log", 0), "events.log");
+        assert_eq!(rotated_path("events.log", 2), "events.log.2");
+
+        let mut deque = VecDeque::new();
+        for i in 0..5 {
+            push_limited(&mut deque, i, 3);
+        }
+        assert_eq!(deque, [2, 3, 4]);
+    }
+
+    #[test]
+    fn push_flush_query() {
+        let path = std::env::temp_dir().join(format!("events_{}.log", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let arena_id = ArenaId(NonZeroU32::new(1).unwrap());
+        let mut events = EventLogRepo::<MockGame>::new(arena_id, Some(String::from(path)));
+        let joined = |n| ArenaEvent::Joined {
+            player_id: PlayerId(NonZeroU32::new(n).unwrap()),
+            alias: PlayerAlias::new_unsanitized("Captain"),
+        };
+
+        events.record(joined(1));
+        events.flush();
+        // Sent to the writer, but perhaps not written yet.
+        events.record(joined(2));
+        let queried = futures::executor::block_on(events.query(None, None)).unwrap();
+        assert_eq!(
+            queried.iter().map(|e| e.event.clone()).collect::<Vec<_>>(),
+            [joined(1), joined(2)]
+        );
+        assert!(queried.iter().all(|e| e.arena_id == arena_id));
+
+        events.flush();
+        let queried = futures::executor::block_on(events.query(None, None)).unwrap();
+        assert_eq!(queried.len(), 2);
+
+        drop(events);
+        let _ = std::fs::remove_file(path);
+    }
+}
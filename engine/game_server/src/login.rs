@@ -0,0 +1,248 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Signing in with providers other than Discord (see [`DiscordOauth2Repo`]), so players keep their
+//! progress across devices and browser cache clears.
+//!
+//! [`DiscordOauth2Repo`]: crate::discord::DiscordOauth2Repo
+
+use axum::http::header::SET_COOKIE;
+use axum::response::{IntoResponse, Redirect, Response};
+use constant_time_eq::constant_time_eq;
+use core_protocol::id::LoginType;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use log::warn;
+use rand::{thread_rng, Rng};
+use serde::Deserialize;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Cookie holding the nonce of the OAuth2 `state` of the latest redirect to a provider.
+pub const STATE_COOKIE: &str = "oauth2_state";
+
+/// Generates a random OAuth2 `state`, returning it and its nonce. The state is `login_type.nonce`,
+/// which tells the client which provider redirected back.
+pub(crate) fn new_state(login_type: LoginType) -> (String, String) {
+    let nonce = base64::encode_config(thread_rng().gen::<[u8; 16]>(), base64::URL_SAFE_NO_PAD);
+    (format!("{}.{}", login_type.as_str(), nonce), nonce)
+}
+
+/// Redirects to a provider's authorization url, storing the nonce of its `state` in a cookie. The
+/// cookie must cover subdomains (`cookie_domain`), as the game may connect to one.
+pub(crate) fn redirect_with_state(
+    auth_url: &str,
+    nonce: &str,
+    cookie_domain: Option<&str>,
+) -> Response {
+    let mut cookie = format!(
+        "{}={}; Max-Age=600; Path=/; HttpOnly; SameSite=Lax",
+        STATE_COOKIE, nonce
+    );
+    if let Some(domain) = cookie_domain {
+        cookie.push_str(&format!("; Domain={}; Secure", domain));
+    }
+    ([(SET_COOKIE, cookie)], Redirect::to(auth_url)).into_response()
+}
+
+/// Whether the nonce the client passed on from the provider's redirect matches the cookie, meaning
+/// the sign in was started by the same browser. Prevents login CSRF, i.e. an attacker tricking a
+/// player into signing in as the attacker.
+pub(crate) fn check_state(nonce: Option<&str>, cookie: Option<&str>) -> bool {
+    matches!(
+        (nonce, cookie),
+        (Some(nonce), Some(cookie)) if constant_time_eq(nonce.as_bytes(), cookie.as_bytes())
+    )
+}
+
+/// An OpenID Connect provider, which redirects back to the game with a code that is exchanged for
+/// the player's id with the provider.
+pub struct Oauth2Repo {
+    login_type: LoginType,
+    auth_url: &'static str,
+    token_url: &'static str,
+    /// Keys that sign id tokens, as a JSON Web Key Set.
+    jwks_url: &'static str,
+    /// Accepted values of the id token `iss` claim.
+    issuers: &'static [&'static str],
+    scope: Option<&'static str>,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    http_client: reqwest::Client,
+    /// Cached keys from [`Self::jwks_url`].
+    jwks: RwLock<JwkSet>,
+}
+
+/// Claims of an OpenID Connect id token, that identify the player. The audience, issuer, and
+/// expiry are checked by [`Validation`].
+#[derive(Debug, Deserialize)]
+struct Claims {
+    /// Player's unique, stable id with the provider.
+    sub: String,
+}
+
+impl Oauth2Repo {
+    pub fn google(client_id: String, client_secret: String, redirect_url: String) -> Self {
+        Self {
+            login_type: LoginType::Google,
+            auth_url: "https://accounts.google.com/o/oauth2/v2/auth",
+            token_url: "https://oauth2.googleapis.com/token",
+            jwks_url: "https://www.googleapis.com/oauth2/v3/certs",
+            issuers: &["https://accounts.google.com", "accounts.google.com"],
+            scope: Some("openid"),
+            client_id,
+            client_secret,
+            redirect_url,
+            http_client: Self::http_client(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }
+    }
+
+    /// The client secret is a JWT signed with the developer's private key, which Apple requires to
+    /// be regenerated at least every 6 months.
+    pub fn apple(client_id: String, client_secret: String, redirect_url: String) -> Self {
+        Self {
+            login_type: LoginType::Apple,
+            auth_url: "https://appleid.apple.com/auth/authorize",
+            token_url: "https://appleid.apple.com/auth/token",
+            jwks_url: "https://appleid.apple.com/auth/keys",
+            issuers: &["https://appleid.apple.com"],
+            // Requesting the name or email would require a form post, instead of a redirect.
+            scope: None,
+            client_id,
+            client_secret,
+            redirect_url,
+            http_client: Self::http_client(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }
+    }
+
+    fn http_client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(4))
+            .build()
+            .unwrap()
+    }
+
+    pub fn redirect(&self, cookie_domain: Option<&str>) -> impl IntoResponse {
+        let (state, nonce) = new_state(self.login_type);
+        let mut params = vec![
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_url.as_str()),
+            ("response_type", "code"),
+            ("state", state.as_str()),
+        ];
+        params.extend(self.scope.map(|scope| ("scope", scope)));
+        let auth_url = reqwest::Url::parse_with_params(self.auth_url, &params).unwrap();
+
+        redirect_with_state(auth_url.as_str(), &nonce, cookie_domain)
+    }
+
+    /// Returns the player's id with the provider.
+    pub async fn authenticate(&self, code: String) -> Result<String, String> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            id_token: String,
+        }
+
+        let token: TokenResponse = self
+            .http_client
+            .post(self.token_url)
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("code", code.as_str()),
+                ("grant_type", "authorization_code"),
+                ("redirect_uri", self.redirect_url.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let header = decode_header(&token.id_token).map_err(|e| e.to_string())?;
+        let kid = header
+            .kid
+            .ok_or_else(|| String::from("id token has no key id"))?;
+        let key = self.decoding_key(&kid).await?;
+
+        // Both providers sign with RS256.
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_issuer(self.issuers);
+        let claims = decode::<Claims>(&token.id_token, &key, &validation)
+            .map_err(|e| e.to_string())?
+            .claims;
+        Ok(claims.sub)
+    }
+
+    /// Returns the provider's key with the given id, refreshing the cached keys if it is unknown
+    /// (e.g. because the provider rotated them).
+    async fn decoding_key(&self, kid: &str) -> Result<DecodingKey, String> {
+        let cached = self
+            .jwks
+            .read()
+            .unwrap()
+            .find(kid)
+            .map(DecodingKey::from_jwk);
+        if let Some(key) = cached {
+            return key.map_err(|e| e.to_string());
+        }
+
+        let jwks: JwkSet = self
+            .http_client
+            .get(self.jwks_url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| e.to_string())?;
+        let key = jwks
+            .find(kid)
+            .ok_or_else(|| String::from("id token signed with unknown key"))
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).map_err(|e| e.to_string()));
+        *self.jwks.write().unwrap() = jwks;
+        key
+    }
+}
+
+/// Signs in with a provider, if it is enabled, returning the player's id with it.
+pub(crate) async fn sign_in(
+    oauth2: Option<&'static Oauth2Repo>,
+    code: String,
+) -> Option<(LoginType, String)> {
+    let oauth2 = oauth2?;
+    match oauth2.authenticate(code).await {
+        Ok(id) => Some((oauth2.login_type, id)),
+        Err(e) => {
+            warn!("{:?} login failed: {}", oauth2.login_type, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::login::{check_state, new_state};
+    use core_protocol::id::LoginType;
+
+    #[test]
+    fn state() {
+        let (state, nonce) = new_state(LoginType::Google);
+        assert_eq!(state, format!("google.{}", nonce));
+        assert_ne!(new_state(LoginType::Google).1, nonce);
+
+        assert!(check_state(Some(&nonce), Some(&nonce)));
+        assert!(!check_state(Some("forged"), Some(&nonce)));
+        assert!(!check_state(Some(&nonce), None));
+        assert!(!check_state(None, Some(&nonce)));
+    }
+}
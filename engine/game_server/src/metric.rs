@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::client::{Authenticate, PlayerClientData};
+use crate::experiment::ExperimentRepo;
 use crate::game_service::GameArenaService;
 use crate::infrastructure::Infrastructure;
 use crate::player::PlayerData;
@@ -10,7 +11,7 @@ use crate::unwrap_or_return;
 use actix::Context as ActorContext;
 use actix::{ActorFutureExt, ContextFutureSpawner, WrapFuture};
 use core_protocol::dto::{MetricFilter, MetricsDataPointDto};
-use core_protocol::id::{CohortId, RegionId, SessionId, UserAgentId};
+use core_protocol::id::{CohortId, RegionId, SessionId, UserAgentId, VariantId};
 use core_protocol::name::Referrer;
 use core_protocol::{get_unix_time_now, UnixTime};
 use heapless::HistoryBuffer;
@@ -29,6 +30,8 @@ pub(crate) struct MetricRepo<G: GameArenaService> {
     next_swap: UnixTime,
     pub(crate) current: MetricBundle,
     pub history: HistoryBuffer<MetricBundle, 24>,
+    /// Current experiment, which metrics are tagged with.
+    pub(crate) experiments: ExperimentRepo,
     _spooky: PhantomData<G>,
 }
 
@@ -122,6 +125,7 @@ pub(crate) struct Bundle<T> {
     pub(crate) by_referrer: HashMap<Referrer, T>,
     pub(crate) by_region_id: HashMap<RegionId, T>,
     pub(crate) by_user_agent_id: HashMap<UserAgentId, T>,
+    pub(crate) by_variant_id: HashMap<VariantId, T>,
 }
 
 impl<T: Default> Bundle<T> {
@@ -133,6 +137,7 @@ impl<T: Default> Bundle<T> {
         referrer: Option<Referrer>,
         region_id: Option<RegionId>,
         user_agent_id: Option<UserAgentId>,
+        variant_id: Option<VariantId>,
     ) {
         mutation(&mut self.total);
         mutation(self.by_cohort_id.entry(cohort_id).or_default());
@@ -155,6 +160,9 @@ impl<T: Default> Bundle<T> {
         if let Some(user_agent_id) = user_agent_id {
             mutation(self.by_user_agent_id.entry(user_agent_id).or_default())
         }
+        if let Some(variant_id) = variant_id {
+            mutation(self.by_variant_id.entry(variant_id).or_default())
+        }
     }
 
     /// Applies another bundle to this one, component-wise.
@@ -172,6 +180,9 @@ impl<T: Default> Bundle<T> {
         for (user_agent_id, o) in other.by_user_agent_id {
             map(self.by_user_agent_id.entry(user_agent_id).or_default(), o);
         }
+        for (variant_id, o) in other.by_variant_id {
+            map(self.by_variant_id.entry(variant_id).or_default(), o);
+        }
     }
 }
 
@@ -198,6 +209,11 @@ impl<T: 'static> Bundle<T> {
                     .into_iter()
                     .map(|(k, v)| (Some(MetricFilter::UserAgentId(k)), v)),
             )
+            .chain(
+                self.by_variant_id
+                    .into_iter()
+                    .map(|(k, v)| (Some(MetricFilter::VariantId(k)), v)),
+            )
     }
 
     pub fn get(&self, filter: Option<MetricFilter>) -> Option<&T> {
@@ -209,6 +225,7 @@ impl<T: 'static> Bundle<T> {
             Some(MetricFilter::UserAgentId(user_agent_id)) => {
                 self.by_user_agent_id.get(&user_agent_id)
             }
+            Some(MetricFilter::VariantId(variant_id)) => self.by_variant_id.get(&variant_id),
         }
     }
 }
@@ -248,7 +265,7 @@ impl<G: GameArenaService> MetricRepo<G> {
     const MINUTE_IN_MILLIS: u64 = 1000;
     #[cfg(not(debug_assertions))]
     const MINUTE_IN_MILLIS: u64 = 60 * 1000;
-    const HOUR_IN_MILLIS: u64 = 60 * Self::MINUTE_IN_MILLIS;
+    pub(crate) const HOUR_IN_MILLIS: u64 = 60 * Self::MINUTE_IN_MILLIS;
     const DAY_IN_MILLIS: u64 = 24 * Self::HOUR_IN_MILLIS;
     const MIN_VISIT_GAP: Duration = Duration::from_secs(30 * 60);
 
@@ -260,6 +277,7 @@ impl<G: GameArenaService> MetricRepo<G> {
             next_update: Self::round_down_to_minute(now) + Self::MINUTE_IN_MILLIS,
             current,
             history: HistoryBuffer::default(),
+            experiments: ExperimentRepo::default(),
             _spooky: PhantomData,
         }
     }
@@ -269,12 +287,14 @@ impl<G: GameArenaService> MetricRepo<G> {
         mutation: impl Fn(&mut Metrics),
         client_metric_data: &ClientMetricData<G>,
     ) {
+        let variant_id = self.experiments.variant_id(client_metric_data.cohort_id);
         self.current.bundle.visit_specific_mut(
             mutation,
             client_metric_data.cohort_id,
             client_metric_data.referrer,
             client_metric_data.region_id,
             client_metric_data.user_agent_id,
+            variant_id,
         );
     }
 
@@ -442,6 +462,9 @@ impl<G: GameArenaService> MetricRepo<G> {
                     client.metrics.referrer,
                     client.metrics.region_id,
                     client.metrics.user_agent_id,
+                    metrics_repo
+                        .experiments
+                        .variant_id(client.metrics.cohort_id),
                 );
                 metrics_repo.mutate_with(
                     |m| {
@@ -527,6 +550,7 @@ impl<G: GameArenaService> MetricRepo<G> {
         copy!(infrastructure, current, by_user_agent_id, UserAgentId);
         copy!(infrastructure, current, by_referrer, Referrer);
         copy!(infrastructure, current, by_region_id, RegionId);
+        copy!(infrastructure, current, by_variant_id, VariantId);
 
         macro_rules! collect {
             ($map: ident, $variant: ident) => {
@@ -571,6 +595,8 @@ impl<G: GameArenaService> MetricRepo<G> {
             }),
             by_region_id: collect!(by_region_id, RegionId),
             by_user_agent_id: collect!(by_user_agent_id, UserAgentId),
+            // Variants are specific to an experiment, so aren't worth saving.
+            by_variant_id: HashMap::new(),
         };
 
         infrastructure.metrics.history.write(current);
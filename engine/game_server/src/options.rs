@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::discord::DiscordEvent;
 use core_protocol::id::RegionId;
 use log::{warn, LevelFilter};
 use std::net::IpAddr;
@@ -19,6 +20,25 @@ pub struct Options {
     /// This percent of real players will help determine number of bots.
     #[structopt(long)]
     pub bot_percent: Option<usize>,
+    /// Game-specific arena option, as `key=value` (may be repeated).
+    #[structopt(long = "arena-option", parse(try_from_str = parse_arena_option))]
+    pub arena_options: Vec<(String, String)>,
+    /// Simulation rate (in Hz) of the primary and private arenas. Higher rates simulate more
+    /// accurately, and lower rates are cheaper to host. Defaults to the game's native rate.
+    #[structopt(long)]
+    pub tick_rate: Option<u16>,
+    /// Simulation rate (in Hz) of overflow arenas (see `--arena-capacity`). Defaults to
+    /// `--tick-rate`.
+    #[structopt(long)]
+    pub overflow_tick_rate: Option<u16>,
+    /// Upon SIGTERM, write the game and players' progress to this file, before exiting. Upon
+    /// starting, resume from it, if it was written recently.
+    #[structopt(long)]
+    pub handoff_file: Option<String>,
+    /// Real players per arena, above which new players join an additional arena (unless invited
+    /// to an existing one). By default, there is only one arena.
+    #[structopt(long)]
+    pub arena_capacity: Option<usize>,
     /// Log incoming HTTP requests
     #[cfg_attr(debug_assertions, structopt(long, default_value = "warn"))]
     #[cfg_attr(not(debug_assertions), structopt(long, default_value = "error"))]
@@ -42,9 +62,15 @@ pub struct Options {
     /// Log chats here
     #[structopt(long)]
     pub chat_log: Option<String>,
+    /// Log arena events (joins, sinkings, moderation, etc.) here
+    #[structopt(long)]
+    pub event_log: Option<String>,
     /// Log client traces here
     #[structopt(long)]
     pub trace_log: Option<String>,
+    /// Store client desync reports, along with world snapshots, in this directory
+    #[structopt(long)]
+    pub desync_dir: Option<String>,
     /// Persist admin config here.
     #[structopt(long)]
     pub admin_config_file: Option<String>,
@@ -63,6 +89,22 @@ pub struct Options {
     /// Discord guild (server) id.
     #[structopt(long, default_value = "847143438939717663")]
     pub discord_guild_id: NonZeroU64,
+    /// Google OAuth2 client id, for signing in with Google.
+    #[structopt(long)]
+    pub google_client_id: Option<String>,
+    /// Google OAuth2 client secret.
+    #[structopt(long)]
+    pub google_client_secret: Option<String>,
+    /// Apple services id, for signing in with Apple.
+    #[structopt(long)]
+    pub apple_client_id: Option<String>,
+    /// Apple client secret (a JWT signed with the developer's private key).
+    #[structopt(long)]
+    pub apple_client_secret: Option<String>,
+    /// Discord webhook to publish an event to, as `event=url`, where event is `high_score`,
+    /// `leaderboard_turnover`, or `tournament` (may be repeated).
+    #[structopt(long = "discord-webhook", parse(try_from_str = parse_discord_webhook))]
+    pub discord_webhooks: Vec<(DiscordEvent, String)>,
     /// Don't write to the database.
     #[structopt(long)]
     pub database_read_only: bool,
@@ -102,6 +144,34 @@ pub struct Options {
     /// Client authenticate rate limiting burst.
     #[structopt(long, default_value = "16")]
     pub client_authenticate_burst: u32,
+    /// New players with less score than this can't post links in chat, or change alias repeatedly.
+    #[structopt(long, default_value = "0")]
+    pub new_player_min_score: u32,
+    /// New players within this many seconds of their first connection can't post links in chat, or
+    /// change alias repeatedly.
+    #[structopt(long, default_value = "180")]
+    pub new_player_probation: u64,
+    /// Length of ranked seasons (in days), after which leaderboards are archived, titles are
+    /// awarded, and the world is reset. Seasons are disabled if unspecified.
+    #[structopt(long)]
+    pub season_days: Option<u32>,
+    /// Maximum number of observers (e.g. tournament casters), which don't count as players.
+    #[structopt(long, default_value = "4")]
+    pub max_observers: usize,
+    /// IP addresses allowed to scrape `/metrics` without the admin auth header.
+    #[structopt(long)]
+    pub metrics_allowlist: Vec<IpAddr>,
+}
+
+fn parse_arena_option(s: &str) -> Result<(String, String), &'static str> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .ok_or("expected key=value")
+}
+
+fn parse_discord_webhook(s: &str) -> Result<(DiscordEvent, String), &'static str> {
+    let (event, url) = s.split_once('=').ok_or("expected event=url")?;
+    Ok((event.parse()?, url.to_owned()))
 }
 
 impl Options {
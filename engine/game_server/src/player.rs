@@ -1,17 +1,20 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::client::PlayerClientData;
+use crate::chat::ChatRepo;
+use crate::client::{ClientStatus, PlayerClientData};
 use crate::game_service::GameArenaService;
 use crate::invitation::InvitationRepo;
+use crate::log::EventLogRepo;
 use crate::metric::MetricRepo;
 use crate::team::{PlayerTeamData, TeamRepo};
 use crate::util::diff_large_n;
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
-use core_protocol::dto::{InvitationDto, PlayerDto};
+use core_protocol::dto::{ArenaEvent, Commendation, InvitationDto, PlayerDto};
 use core_protocol::id::{PlayerId, TeamId};
 use core_protocol::name::PlayerAlias;
 use core_protocol::rpc::{PlayerRequest, PlayerUpdate};
+use server_util::rate_limiter::RateLimiterProps;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::ops::{Deref, DerefMut};
@@ -85,6 +88,12 @@ impl<G: GameArenaService> PlayerRepo<G> {
         self.players.contains_key(&player_id)
     }
 
+    /// Tests if the player exists, and is an observer (see [`PlayerData::is_observer`]).
+    pub fn is_observer(&self, player_id: PlayerId) -> bool {
+        self.borrow_player(player_id)
+            .map_or(false, |p| p.is_observer())
+    }
+
     /// Gets the player tuple of a given player.
     pub fn get(&self, player_id: PlayerId) -> Option<&Arc<PlayerTuple<G>>> {
         self.players.get(&player_id)
@@ -118,51 +127,76 @@ impl<G: GameArenaService> PlayerRepo<G> {
         self.players.remove(&player_id);
     }
 
-    fn report_player(
+    /// Handles an arbitrary [`PlayerRequest`].
+    pub(crate) fn handle_player_request(
         &mut self,
         req_player_id: PlayerId,
-        report_player_id: PlayerId,
+        request: PlayerRequest,
+        chat: &mut ChatRepo<G>,
         metrics: &mut MetricRepo<G>,
+        events: &mut EventLogRepo<G>,
     ) -> Result<PlayerUpdate, &'static str> {
-        if req_player_id == report_player_id {
-            return Err("cannot report self");
+        match request {
+            PlayerRequest::Commend {
+                player_id,
+                commendation,
+            } => self
+                .commend_player(req_player_id, player_id, commendation, events)
+                .map(|_| PlayerUpdate::Commended(player_id)),
+            PlayerRequest::Report(player_id) => chat
+                .report_player(req_player_id, player_id, self, metrics, events)
+                .map(|_| PlayerUpdate::Reported(player_id)),
+        }
+    }
+
+    /// Commendations allowed in quick succession, then at most one per this long.
+    const COMMEND_RATE_LIMIT: RateLimiterProps =
+        RateLimiterProps::const_new(Duration::from_secs(20), 2);
+
+    /// Commend another player. Each client may commend a given player once per session, and only
+    /// after playing for a bit, so commendations stay meaningful.
+    fn commend_player(
+        &mut self,
+        req_player_id: PlayerId,
+        commend_player_id: PlayerId,
+        commendation: Commendation,
+        events: &mut EventLogRepo<G>,
+    ) -> Result<(), &'static str> {
+        if req_player_id == commend_player_id {
+            return Err("cannot commend self");
         }
         let mut req_player = self
             .borrow_player_mut(req_player_id)
             .ok_or("nonexistent player")?;
-        if req_player.score < G::MINIMUM_REPORT_SCORE {
-            return Err("report requirements unmet");
+        if !req_player.was_ever_alive {
+            return Err("must play before commending");
         }
         let req_client = req_player
             .client_mut()
-            .ok_or("only clients can report players")?;
-        let mut report_player = self
-            .borrow_player_mut(report_player_id)
-            .ok_or("cannot report nonexistent player")?;
-        let report_client = report_player
+            .ok_or("only clients can commend players")?;
+        let mut commend_player = self
+            .borrow_player_mut(commend_player_id)
+            .ok_or("cannot commend nonexistent player")?;
+        let commend_client = commend_player
             .client_mut()
-            .ok_or("only clients can be reported")?;
-        if req_client.reported.insert(report_player_id) {
-            report_client.chat.context.report();
-            metrics.mutate_with(|m| m.abuse_reports.increment(), &report_client.metrics);
-            Ok(PlayerUpdate::Reported(report_player_id))
-        } else {
-            Err("already reported")
+            .ok_or("only clients can be commended")?;
+        if req_client.commended.contains(&commend_player_id) {
+            return Err("already commended");
         }
-    }
-
-    /// Handles an arbitrary [`PlayerRequest`].
-    pub(crate) fn handle_player_request(
-        &mut self,
-        req_player_id: PlayerId,
-        request: PlayerRequest,
-        metrics: &mut MetricRepo<G>,
-    ) -> Result<PlayerUpdate, &'static str> {
-        match request {
-            PlayerRequest::Report(player_id) => {
-                self.report_player(req_player_id, player_id, metrics)
-            }
+        if req_client
+            .commend_rate_limit
+            .should_limit_rate(&Self::COMMEND_RATE_LIMIT)
+        {
+            return Err("commending too quickly");
         }
+        req_client.commended.insert(commend_player_id);
+        commend_client.commendations = commend_client.commendations.saturating_add(1);
+        events.record(ArenaEvent::Commended {
+            commender_id: req_player_id,
+            player_id: commend_player_id,
+            commendation,
+        });
+        Ok(())
     }
 
     /// Updates cache of whether players are alive, tallying metrics in the process.
@@ -176,6 +210,7 @@ impl<G: GameArenaService> PlayerRepo<G> {
             let is_alive = service.is_alive(pt);
             let mut p = pt.borrow_player_mut();
             let player_id = p.player_id;
+            let first_play = is_alive && !p.was_ever_alive;
 
             if is_alive != p.was_alive {
                 if is_alive {
@@ -190,6 +225,11 @@ impl<G: GameArenaService> PlayerRepo<G> {
                 p.was_ever_alive = true;
                 p.was_alive_timestamp = Instant::now();
             }
+            if let Some(alive_duration) = p.alive_duration() {
+                if let Some(client) = p.client_mut() {
+                    client.achievements.survived(alive_duration);
+                }
+            }
             let is_out_of_game = p.is_out_of_game();
             let was_out_of_game = p.was_out_of_game;
             p.was_out_of_game = is_out_of_game;
@@ -200,6 +240,11 @@ impl<G: GameArenaService> PlayerRepo<G> {
                 teams.cleanup_player(player_id, self);
             }
 
+            if first_play {
+                // Players that followed an invitation link join their friend's fleet.
+                let _ = InvitationRepo::join_inviter_team(player_id, self, teams);
+            }
+
             p = pt.borrow_player_mut();
 
             let current_team_id = p.team_id();
@@ -223,6 +268,11 @@ impl<G: GameArenaService> PlayerRepo<G> {
         let player_dtos = self
             .iter_borrow()
             .filter_map(|p| {
+                if p.is_observer() {
+                    // Observers don't take up a player slot.
+                    return None;
+                }
+
                 if !p.is_bot() {
                     real_players += 1;
                 }
@@ -235,6 +285,7 @@ impl<G: GameArenaService> PlayerRepo<G> {
 
                     Some(PlayerDto {
                         alias: p.alias(),
+                        commendations: p.client().map(|c| c.commendations).unwrap_or(0),
                         moderator: p.client().map(|c| c.moderator).unwrap_or(false),
                         player_id: p.player_id,
                         team_id: p.team_id(),
@@ -456,6 +507,18 @@ impl<G: GameArenaService> PlayerData<G> {
         (!self.was_alive).then(|| self.was_alive_timestamp.elapsed())
     }
 
+    /// Returns true iff player is observing instead of playing (see [`PlayerClientData::observer`]).
+    pub fn is_observer(&self) -> bool {
+        self.client().map_or(false, |c| c.observer)
+    }
+
+    /// Returns true iff player is a real player who lost their connection, but may still reconnect
+    /// and resume playing (see [`GameArenaService::LIMBO`]).
+    pub fn is_in_limbo(&self) -> bool {
+        self.client()
+            .map_or(false, |c| matches!(c.status, ClientStatus::Limbo { .. }))
+    }
+
     /// Returns true iff player is a bot (their id is a bot id).
     pub fn is_bot(&self) -> bool {
         self.player_id.is_bot()
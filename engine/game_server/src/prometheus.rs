@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Exports metrics in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/)
+//! for external dashboards.
+
+use crate::game_service::GameArenaService;
+use crate::infrastructure::Infrastructure;
+use actix::{Handler, Message};
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Total time spent waiting for websockets to accept messages, a measure of backpressure.
+static WEB_SOCKET_SEND_NANOS: AtomicU64 = AtomicU64::new(0);
+/// Total messages sent to websockets.
+static WEB_SOCKET_SENDS: AtomicU64 = AtomicU64::new(0);
+
+/// Call after each websocket send completes, with how long it took.
+pub(crate) fn record_web_socket_send(elapsed: Duration) {
+    WEB_SOCKET_SEND_NANOS.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    WEB_SOCKET_SENDS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Asks the server for its metrics, in the Prometheus text format.
+#[derive(Message)]
+#[rtype(result = "String")]
+pub struct PrometheusRequest;
+
+impl<G: GameArenaService> Handler<PrometheusRequest> for Infrastructure<G> {
+    type Result = String;
+
+    fn handle(&mut self, _request: PrometheusRequest, _: &mut Self::Context) -> Self::Result {
+        let mut out = String::new();
+
+        let health = &mut self.status.health;
+        write_metric(
+            &mut out,
+            "gauge",
+            "cpu_ratio",
+            "Fraction of CPU in use.",
+            &[],
+            health.cpu(),
+        );
+        write_metric(
+            &mut out,
+            "gauge",
+            "ram_ratio",
+            "Fraction of RAM in use.",
+            &[],
+            health.ram(),
+        );
+        write_metric(
+            &mut out,
+            "gauge",
+            "uptime_seconds",
+            "Time since the server started.",
+            &[],
+            self.status.uptime().as_secs_f32(),
+        );
+        write_metric(
+            &mut out,
+            "gauge",
+            "tick_duration_seconds",
+            "Time spent processing the last tick.",
+            &[],
+            self.status.tick_duration.as_secs_f32(),
+        );
+        write_metric(
+            &mut out,
+            "counter",
+            "web_socket_sends_total",
+            "Messages sent to websockets.",
+            &[],
+            WEB_SOCKET_SENDS.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "counter",
+            "web_socket_send_seconds_total",
+            "Time spent waiting for websockets to accept messages.",
+            &[],
+            WEB_SOCKET_SEND_NANOS.load(Ordering::Relaxed) as f64 * 1e-9,
+        );
+
+        // For now, the infrastructure is always hosting one arena.
+        let context = &self.context_service.context;
+        let game_id = format!("{:?}", G::GAME_ID);
+        let arena_id = context.arena_id.0.to_string();
+        let arena = [
+            ("game_id", game_id.as_str()),
+            ("arena_id", arena_id.as_str()),
+        ];
+
+        write_metric(
+            &mut out,
+            "gauge",
+            "players",
+            "Players, including bots.",
+            &arena,
+            context.players.len(),
+        );
+        write_metric(
+            &mut out,
+            "gauge",
+            "real_players",
+            "Players that aren't bots.",
+            &arena,
+            context.players.real_players,
+        );
+        write_metric(
+            &mut out,
+            "gauge",
+            "real_players_live",
+            "Players that aren't bots and are connected.",
+            &arena,
+            context.players.real_players_live,
+        );
+
+        let entity_counts = self.context_service.service.entity_counts();
+        if !entity_counts.is_empty() {
+            write_header(&mut out, "gauge", "entities", "Entities, by kind.");
+        }
+        for (kind, count) in entity_counts {
+            write_sample(
+                &mut out,
+                "entities",
+                &[arena[0], arena[1], ("kind", kind)],
+                count,
+            );
+        }
+
+        out
+    }
+}
+
+fn write_header(out: &mut String, typ: &str, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {typ}");
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl Display) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        out.push('{');
+        for (i, (key, value)) in labels.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            // Label values are all generated by us, so they don't need escaping.
+            let _ = write!(out, "{key}=\"{value}\"");
+        }
+        out.push('}');
+    }
+    let _ = writeln!(out, " {value}");
+}
+
+fn write_metric(
+    out: &mut String,
+    typ: &str,
+    name: &str,
+    help: &str,
+    labels: &[(&str, &str)],
+    value: impl Display,
+) {
+    write_header(out, typ, name, help);
+    write_sample(out, name, labels, value);
+}
@@ -0,0 +1,163 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::chat::ChatRepo;
+use crate::game_service::GameArenaService;
+use crate::log::EventLogRepo;
+use crate::player::PlayerRepo;
+use core_protocol::dto::{ArenaEvent, LeaderboardDto};
+use core_protocol::get_unix_time_now;
+use core_protocol::name::PlayerAlias;
+use log::info;
+use server_util::rate_limiter::RateLimiter;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Component of [`Context`] that rolls over ranked seasons on a fixed schedule, counting down in
+/// chat, archiving final standings, and awarding titles to the top players of each season.
+pub struct SeasonRepo<G: GameArenaService> {
+    /// Length of each season. Seasons start at multiples of this since the Unix epoch. [`None`]
+    /// disables seasons.
+    period: Option<Duration>,
+    /// Season in progress, once known.
+    current: Option<u32>,
+    /// Number of [`Self::COUNTDOWN_SECS`] announced so far this season.
+    countdown: usize,
+    /// Highest score of each real player this season.
+    scores: HashMap<PlayerAlias, u32>,
+    /// Final standings of recent seasons, oldest first.
+    archive: VecDeque<(u32, Arc<[LeaderboardDto]>)>,
+    update_rate_limiter: RateLimiter,
+    _spooky: PhantomData<G>,
+}
+
+impl<G: GameArenaService> SeasonRepo<G> {
+    /// Announce the end of the season when this many seconds remain.
+    const COUNTDOWN_SECS: [u64; 4] = [60 * 60, 10 * 60, 60, 10];
+    /// Titles awarded to the top players of a season, in order.
+    const TITLES: [&'static str; 3] = ["champion", "runner-up", "third place"];
+    /// Maximum number of past seasons to remember.
+    const ARCHIVE_MAX: usize = 8;
+
+    pub fn new(period: Option<Duration>) -> Self {
+        Self {
+            period: period.filter(|p| !p.is_zero()),
+            current: None,
+            countdown: 0,
+            scores: HashMap::new(),
+            archive: VecDeque::new(),
+            update_rate_limiter: RateLimiter::new(Duration::from_secs(1), 0),
+            _spooky: PhantomData,
+        }
+    }
+
+    /// Final standings of recent seasons, oldest first.
+    pub fn archive(&self) -> impl Iterator<Item = (u32, &Arc<[LeaderboardDto]>)> {
+        self.archive
+            .iter()
+            .map(|(season, standings)| (*season, standings))
+    }
+
+    /// Records scores, counts down, and rolls over to the next season when it is time. Rate
+    /// limited internally.
+    pub(crate) fn update(
+        &mut self,
+        service: &mut G,
+        players: &mut PlayerRepo<G>,
+        chat: &mut ChatRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) {
+        let period = match self.period {
+            Some(period) => period.as_secs().max(1),
+            None => return,
+        };
+        if self.update_rate_limiter.should_limit_rate() {
+            return;
+        }
+
+        for player in players.iter_borrow() {
+            if player.is_bot() || !player.is_alive() {
+                continue;
+            }
+            let score = self.scores.entry(player.alias()).or_insert(0);
+            *score = player.score.max(*score);
+        }
+
+        let now_secs = get_unix_time_now() / 1000;
+        let season = (now_secs / period) as u32;
+        let remaining = period - now_secs % period;
+
+        match self.current {
+            None => self.current = Some(season),
+            Some(current) if current != season => {
+                self.end_season(current, service, players, chat, events);
+                self.current = Some(season);
+                self.countdown = 0;
+                return;
+            }
+            Some(_) => {}
+        }
+
+        let due = Self::COUNTDOWN_SECS
+            .iter()
+            .filter(|&&secs| remaining <= secs)
+            .count();
+        if due > self.countdown {
+            self.countdown = due;
+            let when = if remaining >= 60 {
+                format!("{} minutes", (remaining + 59) / 60)
+            } else {
+                format!("{} seconds", remaining)
+            };
+            chat.announce(format!("Season {} ends in {}!", season, when), players);
+        }
+    }
+
+    /// Archives standings, awards titles, and lets the game reset the world.
+    fn end_season(
+        &mut self,
+        season: u32,
+        service: &mut G,
+        players: &mut PlayerRepo<G>,
+        chat: &mut ChatRepo<G>,
+        events: &mut EventLogRepo<G>,
+    ) {
+        let mut standings: Vec<LeaderboardDto> = self
+            .scores
+            .drain()
+            .filter(|&(_, score)| score > 0)
+            .map(|(alias, score)| LeaderboardDto { alias, score })
+            .collect();
+        standings.sort_unstable_by(|a, b| b.cmp(a));
+        standings.truncate(G::LEADERBOARD_SIZE);
+
+        for (dto, title) in standings.iter().zip(Self::TITLES) {
+            chat.announce(
+                format!("Season {} {}: {} ({})", season, title, dto.alias, dto.score),
+                players,
+            );
+        }
+        events.record(ArenaEvent::SeasonEnded {
+            season,
+            champions: standings
+                .iter()
+                .take(Self::TITLES.len())
+                .map(|dto| dto.alias)
+                .collect(),
+        });
+        info!("season {} ended: {:?}", season, standings);
+
+        if self.archive.len() >= Self::ARCHIVE_MAX {
+            self.archive.pop_front();
+        }
+        self.archive.push_back((season, standings.into()));
+
+        service.season_ended(season, players);
+        chat.announce(
+            format!("Season {} has begun!", season.wrapping_add(1)),
+            players,
+        );
+    }
+}
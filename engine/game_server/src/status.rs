@@ -5,17 +5,27 @@ use crate::game_service::GameArenaService;
 use crate::infrastructure::Infrastructure;
 use actix::{Handler, Message};
 use core_protocol::rpc::StatusResponse;
+use image::{DynamicImage, ImageOutputFormat};
+use log::warn;
 use server_util::health::Health;
+use server_util::rate_limiter::RateLimiter;
+use std::io::Cursor;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Manages updating and reporting of server status.
 pub struct StatusRepo {
     pub(crate) health: Health,
     uptime: Instant,
+    /// Time spent processing the last tick.
+    pub(crate) tick_duration: Duration,
     /// Possibly overridden.
     pub(crate) client_hash: u64,
     /// Before being overridden.
     pub(crate) original_client_hash: u64,
+    /// PNG data URL of [`GameArenaService::thumbnail`], if any.
+    pub(crate) thumbnail: Option<Arc<str>>,
+    thumbnail_rate_limiter: RateLimiter,
 }
 
 impl StatusRepo {
@@ -23,14 +33,35 @@ impl StatusRepo {
         Self {
             health: Health::default(),
             uptime: Instant::now(),
+            tick_duration: Duration::ZERO,
             client_hash,
             original_client_hash: client_hash,
+            thumbnail: None,
+            thumbnail_rate_limiter: RateLimiter::new(Duration::from_secs(60), 0),
         }
     }
 
     pub fn uptime(&self) -> Duration {
         self.uptime.elapsed()
     }
+
+    /// Re-renders the thumbnail. Rate limited internally.
+    pub(crate) fn update_thumbnail<G: GameArenaService>(&mut self, service: &G) {
+        if self.thumbnail_rate_limiter.should_limit_rate() {
+            return;
+        }
+
+        self.thumbnail = service.thumbnail().and_then(|image| {
+            let mut png = Cursor::new(Vec::new());
+            if let Err(e) =
+                DynamicImage::ImageRgb8(image).write_to(&mut png, ImageOutputFormat::Png)
+            {
+                warn!("error encoding thumbnail: {}", e);
+                return None;
+            }
+            Some(format!("data:image/png;base64,{}", base64::encode(png.into_inner())).into())
+        });
+    }
 }
 
 /// Asks the server if it and the underlying hardware and OS are healthy.
@@ -48,8 +79,13 @@ impl<G: GameArenaService> Handler<StatusRequest> for Infrastructure<G> {
             region_id: self.region_id,
             redirect_server_id: self.admin.redirect_server_id_preference,
             client_hash: Some(self.status.client_hash),
-            // TODO: In the future, this will sum players for all arenas.
-            player_count: Some(self.context_service.context.players.real_players_live as u32),
+            player_count: Some(
+                self.overflow
+                    .iter(&self.context_service)
+                    .map(|arena| arena.context.players.real_players_live as u32)
+                    .sum(),
+            ),
+            thumbnail: self.status.thumbnail.as_deref().map(String::from),
             dying_server_ids: self
                 .system
                 .as_ref()
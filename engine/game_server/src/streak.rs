@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+/// Consecutive days on which a player met a daily goal, such as completing all of the game's daily
+/// missions. Days are counted since the unix epoch (in UTC). Persisted with the session.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DailyStreak {
+    /// Days in a row the goal was met, ending on `day`.
+    length: u32,
+    /// Last day the goal was met.
+    day: u32,
+}
+
+impl DailyStreak {
+    /// Restores a streak persisted with [`Self::to_parts`].
+    pub(crate) fn from_parts(length: u32, day: u32) -> Self {
+        Self { length, day }
+    }
+
+    /// The streak, in a form suitable for persistence.
+    pub(crate) fn to_parts(self) -> (u32, u32) {
+        (self.length, self.day)
+    }
+
+    /// Length of the streak as of `today`, which is zero if the goal was met neither yesterday
+    /// nor today.
+    pub fn length(&self, today: u32) -> u32 {
+        if self.day.saturating_add(1) >= today {
+            self.length
+        } else {
+            0
+        }
+    }
+
+    /// Call when the goal is met on `today`. Only the first call each day extends the streak.
+    pub fn extend(&mut self, today: u32) {
+        if self.day == today && self.length > 0 {
+            return;
+        }
+        self.length = self.length(today) + 1;
+        self.day = today;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::streak::DailyStreak;
+
+    #[test]
+    fn extend() {
+        let mut streak = DailyStreak::default();
+        assert_eq!(streak.length(19000), 0);
+
+        streak.extend(19000);
+        streak.extend(19000);
+        assert_eq!(streak.length(19000), 1);
+
+        // Still counts until the end of the next day.
+        assert_eq!(streak.length(19001), 1);
+        streak.extend(19001);
+        assert_eq!(streak.length(19001), 2);
+
+        // Missed a day.
+        assert_eq!(streak.length(19003), 0);
+        streak.extend(19003);
+        assert_eq!(streak.length(19003), 1);
+
+        let (length, day) = streak.to_parts();
+        assert_eq!(DailyStreak::from_parts(length, day), streak);
+    }
+}
@@ -108,11 +108,12 @@ pub(crate) enum ServerStatus {
 }
 
 /// Fields that a healthy/unhealthy server may advertise about itself.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub(crate) struct ServerAdvertisement {
     pub(crate) redirect_server_id: Option<ServerId>,
     pub(crate) client_hash: Option<u64>,
     pub(crate) player_count: Option<u32>,
+    pub(crate) thumbnail: Option<Arc<str>>,
 }
 
 impl ServerStatus {
@@ -218,12 +219,13 @@ impl<G: GameArenaService> SystemRepo<G> {
         self.servers
             .iter()
             .filter_map(|(&server_id, server)| {
-                if let &ServerStatus::Healthy {
+                if let ServerStatus::Healthy {
                     advertisement:
                         ServerAdvertisement {
                             redirect_server_id,
                             client_hash,
                             player_count,
+                            thumbnail,
                         },
                     ..
                 } = &server.status
@@ -240,6 +242,7 @@ impl<G: GameArenaService> SystemRepo<G> {
                                 server_id,
                                 region_id,
                                 player_count,
+                                thumbnail: thumbnail.clone(),
                             });
                         }
                     }
@@ -401,6 +404,7 @@ impl<G: GameArenaService> SystemRepo<G> {
                                 redirect_server_id: status.redirect_server_id,
                                 client_hash: status.client_hash,
                                 player_count: status.player_count,
+                                thumbnail: status.thumbnail.map(Into::into),
                             };
                             if status.healthy {
                                 info!("watchdog {:?} is healthy", server_id);
@@ -900,7 +904,15 @@ impl<G: GameArenaService> Handler<SystemRequest> for Infrastructure<G> {
 
         SystemResponse {
             server_id: ideal_server_id.or(self.server_id),
-            //servers: self.system.as_ref().map(|system| Arc::clone(system.previous)).unwrap_or_else(|| Vec::new().into())
+            servers: self
+                .system
+                .iter()
+                .flat_map(|system| system.previous.iter())
+                .map(|server| ServerDto {
+                    thumbnail: None,
+                    ..server.clone()
+                })
+                .collect(),
         }
     }
 }
@@ -443,6 +443,31 @@ impl<G: GameArenaService> TeamRepo<G> {
         Ok(TeamUpdate::Left)
     }
 
+    /// Adds a solo player to a team, without the captain having to accept them (e.g. because a
+    /// member invited them).
+    pub(crate) fn add_member(
+        &mut self,
+        player_id: PlayerId,
+        team_id: TeamId,
+        players: &PlayerRepo<G>,
+    ) -> Result<(), &'static str> {
+        let player = players
+            .borrow_player_mut(player_id)
+            .ok_or("nonexistent player")?;
+        if player.team_id().is_some() {
+            return Err("already on team");
+        }
+        let team = self.teams.get_mut(&team_id).ok_or("nonexistent team")?;
+        if team.is_full(players.real_players_live) {
+            return Err("team full");
+        }
+        team.members.insert_back(player_id);
+        // They may have also requested to join.
+        team.joiners.remove(player_id);
+        self.assign_team_and_cancel_joins(player, team_id);
+        Ok(())
+    }
+
     pub(crate) fn request_join(
         &mut self,
         req_player_id: PlayerId,
@@ -0,0 +1,378 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::context_service::{ContextService, OverflowArenas};
+use crate::discord::DiscordEvent;
+use crate::game_service::GameArenaService;
+use crate::infrastructure::Infrastructure;
+use crate::invitation::InvitationRepo;
+use crate::unwrap_or_return;
+use actix::Context as ActorContext;
+use core_protocol::dto::{LeaderboardDto, TournamentDto};
+use core_protocol::id::{ArenaId, InvitationId, PlayerId, ServerId};
+use core_protocol::rpc::{AdminUpdate, ClientUpdate};
+use core_protocol::{get_unix_time_now, UnixTime};
+use log::info;
+use server_util::rate_limiter::RateLimiter;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+/// Component of [`Infrastructure`] that runs admin-scheduled elimination tournaments. Players opt
+/// in until the tournament starts, and are then invited to a dedicated private arena (see
+/// [`OverflowArenas`]), where only they may play. After each timed round, the lower scoring half
+/// of participants is eliminated, until the last round ends or one participant remains.
+pub struct TournamentRepo<G: GameArenaService> {
+    tournament: Option<Tournament>,
+    update_rate_limiter: RateLimiter,
+    _spooky: PhantomData<G>,
+}
+
+struct Tournament {
+    start: UnixTime,
+    rounds: u8,
+    round_duration: Duration,
+    /// Players who opted in, by their id at the time.
+    entrants: HashSet<PlayerId>,
+    /// Number of [`TournamentRepo::COUNTDOWN_SECS`] announced so far.
+    countdown: usize,
+    /// Once started.
+    bracket: Option<Bracket>,
+}
+
+/// State of a running tournament.
+struct Bracket {
+    arena_id: ArenaId,
+    round: u8,
+    round_end: Instant,
+    /// Invitations sent to entrants, which are yet to be redeemed.
+    invitations: HashSet<InvitationId>,
+    /// Participants yet to be eliminated, by their id in the tournament arena, and their scores
+    /// at the start of the round.
+    participants: HashMap<PlayerId, u32>,
+    /// Eliminated participants, and their scores in the round they were eliminated, best last.
+    eliminated: Vec<LeaderboardDto>,
+}
+
+impl<G: GameArenaService> TournamentRepo<G> {
+    /// Announce the start of the tournament when this many seconds remain.
+    const COUNTDOWN_SECS: [u64; 3] = [30 * 60, 5 * 60, 60];
+    /// Maximum number of players that may opt in.
+    const MAX_ENTRANTS: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            tournament: None,
+            update_rate_limiter: RateLimiter::new(Duration::from_secs(1), 0),
+            _spooky: PhantomData,
+        }
+    }
+
+    /// Describes the scheduled or running tournament, if any.
+    pub fn dto(&self) -> Option<TournamentDto> {
+        self.tournament.as_ref().map(|tournament| TournamentDto {
+            start: tournament.start,
+            rounds: tournament.rounds,
+            round_minutes: (tournament.round_duration.as_secs() / 60) as u32,
+            round: tournament.bracket.as_ref().map(|bracket| bracket.round),
+            participants: match &tournament.bracket {
+                Some(bracket) => bracket.participants.len(),
+                None => tournament.entrants.len(),
+            } as u32,
+        })
+    }
+
+    /// Schedules a tournament, replacing any that hasn't started yet.
+    pub(crate) fn schedule(
+        &mut self,
+        start: UnixTime,
+        rounds: u8,
+        round_minutes: u32,
+    ) -> Result<AdminUpdate, &'static str> {
+        if self.is_running() {
+            return Err("tournament already running");
+        }
+        if rounds == 0 || round_minutes == 0 {
+            return Err("tournament must have rounds");
+        }
+        info!(
+            "scheduled tournament of {} {} minute rounds at {}",
+            rounds, round_minutes, start
+        );
+        self.tournament = Some(Tournament {
+            start,
+            rounds,
+            round_duration: Duration::from_secs(round_minutes as u64 * 60),
+            entrants: HashSet::new(),
+            countdown: 0,
+            bracket: None,
+        });
+        Ok(AdminUpdate::TournamentScheduled(self.dto().unwrap()))
+    }
+
+    /// Cancels the tournament, if any. Its arena, if any, remains until its players leave.
+    pub(crate) fn cancel(&mut self) -> Result<AdminUpdate, &'static str> {
+        self.tournament.take().ok_or("no tournament")?;
+        info!("cancelled tournament");
+        Ok(AdminUpdate::TournamentCancelled)
+    }
+
+    /// Opts a player in to the scheduled tournament.
+    pub(crate) fn join(&mut self, player_id: PlayerId) -> Result<TournamentDto, &'static str> {
+        let tournament = self.tournament.as_mut().ok_or("no tournament")?;
+        if tournament.bracket.is_some() {
+            return Err("tournament already started");
+        }
+        if tournament.entrants.len() >= Self::MAX_ENTRANTS {
+            return Err("tournament full");
+        }
+        tournament.entrants.insert(player_id);
+        Ok(self.dto().unwrap())
+    }
+
+    /// Admits a player who connected to the tournament arena with an invitation that was sent to
+    /// an entrant, making them a participant.
+    pub(crate) fn admit(&mut self, invitation_id: InvitationId, player_id: PlayerId) {
+        let bracket = unwrap_or_return!(self.bracket_mut());
+        if bracket.invitations.remove(&invitation_id) {
+            bracket.participants.insert(player_id, 0);
+        }
+    }
+
+    /// Returns false iff the arena is that of a running tournament, and the player isn't a
+    /// participant (e.g. they are spectating, or were eliminated).
+    pub(crate) fn may_play(&self, arena_id: ArenaId, player_id: PlayerId) -> bool {
+        match self.bracket() {
+            Some(bracket) if bracket.arena_id == arena_id => {
+                bracket.participants.contains_key(&player_id)
+            }
+            _ => true,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.bracket().is_some()
+    }
+
+    fn bracket(&self) -> Option<&Bracket> {
+        self.tournament.as_ref()?.bracket.as_ref()
+    }
+
+    fn bracket_mut(&mut self) -> Option<&mut Bracket> {
+        self.tournament.as_mut()?.bracket.as_mut()
+    }
+
+    /// Counts down, starts the tournament, and ends rounds when it is time, posting the results
+    /// to Discord. Rate limited internally.
+    pub(crate) fn update(
+        infrastructure: &mut Infrastructure<G>,
+        ctx: &mut ActorContext<Infrastructure<G>>,
+    ) {
+        let repo = &mut infrastructure.tournament;
+        if repo.update_rate_limiter.should_limit_rate() {
+            return;
+        }
+        let tournament = unwrap_or_return!(repo.tournament.as_mut());
+
+        let primary = &mut infrastructure.context_service;
+        let overflow = &mut infrastructure.overflow;
+        if tournament.bracket.is_none() {
+            let now = get_unix_time_now();
+            if now < tournament.start {
+                tournament.count_down(now, overflow, primary);
+            } else if let Err(e) = tournament.begin(
+                overflow,
+                primary,
+                &mut infrastructure.invitations,
+                infrastructure.server_id,
+            ) {
+                info!("could not start tournament: {}", e);
+                announce(
+                    format!("The tournament was cancelled, since {}.", e),
+                    overflow,
+                    primary,
+                );
+                repo.tournament = None;
+            }
+            return;
+        }
+
+        let bracket = tournament.bracket.as_mut().unwrap();
+        if Instant::now() < bracket.round_end {
+            return;
+        }
+        let results = unwrap_or_return!(bracket.end_round(
+            tournament.rounds,
+            tournament.round_duration,
+            overflow,
+            primary
+        ));
+
+        repo.tournament = None;
+        info!("tournament ended: {:?}", results);
+        infrastructure
+            .leaderboard
+            .put_tournament(results.clone().into());
+
+        let mut message = String::from("Tournament results:");
+        for (i, dto) in results.iter().enumerate() {
+            let _ = write!(message, "\n{}. {} ({})", i + 1, dto.alias, dto.score);
+        }
+        infrastructure.publish_discord_event(DiscordEvent::Tournament, message, ctx);
+    }
+}
+
+impl Tournament {
+    /// Announces the upcoming tournament in every arena, when it is time to.
+    fn count_down<G: GameArenaService>(
+        &mut self,
+        now: UnixTime,
+        overflow: &mut OverflowArenas<G>,
+        primary: &mut ContextService<G>,
+    ) {
+        let remaining = (self.start - now) / 1000;
+        let due = TournamentRepo::<G>::COUNTDOWN_SECS
+            .iter()
+            .filter(|&&secs| remaining <= secs)
+            .count();
+        if due > self.countdown {
+            self.countdown = due;
+            announce(
+                format!(
+                    "A tournament starts in {} minutes! {} players have joined.",
+                    (remaining + 59) / 60,
+                    self.entrants.len()
+                ),
+                overflow,
+                primary,
+            );
+        }
+    }
+
+    /// Creates the tournament arena, and invites entrants to it.
+    fn begin<G: GameArenaService>(
+        &mut self,
+        overflow: &mut OverflowArenas<G>,
+        primary: &mut ContextService<G>,
+        invitations: &mut InvitationRepo<G>,
+        server_id: Option<ServerId>,
+    ) -> Result<(), &'static str> {
+        if self.entrants.len() < 2 {
+            return Err("not enough players joined");
+        }
+        let arena_id = overflow.create_private(primary, false, Vec::new())?;
+
+        let mut bracket = Bracket {
+            arena_id,
+            round: 0,
+            round_end: Instant::now() + self.round_duration,
+            invitations: HashSet::with_capacity(self.entrants.len()),
+            participants: HashMap::with_capacity(self.entrants.len()),
+            eliminated: Vec::new(),
+        };
+        for &player_id in &self.entrants {
+            let invitation_id = invitations.create_arena_invitation(arena_id, player_id, server_id);
+            bracket.invitations.insert(invitation_id);
+            let update = ClientUpdate::TournamentStarted(invitation_id);
+            overflow.send_to_player(primary, player_id, update);
+        }
+        info!(
+            "started tournament of {} players in {:?}",
+            self.entrants.len(),
+            arena_id
+        );
+        self.bracket = Some(bracket);
+        Ok(())
+    }
+}
+
+impl Bracket {
+    /// Eliminates the lower scoring half of participants (and any who left), announcing the
+    /// outcome. Returns the final standings, best first, if the tournament is over.
+    fn end_round<G: GameArenaService>(
+        &mut self,
+        rounds: u8,
+        round_duration: Duration,
+        overflow: &mut OverflowArenas<G>,
+        primary: &mut ContextService<G>,
+    ) -> Option<Vec<LeaderboardDto>> {
+        let arena = match overflow.get_mut(primary, self.arena_id) {
+            Some(arena) => arena,
+            // Everyone left.
+            None => return Some(self.eliminated.drain(..).rev().collect()),
+        };
+        let players = &mut arena.context.players;
+
+        // Lowest scoring first.
+        let mut scores: Vec<(PlayerId, u32, LeaderboardDto)> = self
+            .participants
+            .iter()
+            .filter_map(|(&player_id, &start_score)| {
+                let player = players.borrow_player(player_id)?;
+                let dto = LeaderboardDto {
+                    alias: player.alias(),
+                    score: player.score.saturating_sub(start_score),
+                };
+                Some((player_id, player.score, dto))
+            })
+            .collect();
+        scores.sort_unstable_by(|a, b| a.2.cmp(&b.2));
+
+        self.round += 1;
+        let over = self.round >= rounds || scores.len() <= 2;
+        let eliminate = if over { 0 } else { scores.len() / 2 };
+        let advancing = scores.split_off(eliminate);
+
+        for (_, _, dto) in &scores {
+            arena
+                .context
+                .chat
+                .announce(format!("{} was eliminated.", dto.alias), players);
+        }
+        self.eliminated
+            .extend(scores.into_iter().map(|(_, _, dto)| dto));
+
+        if over {
+            let mut standings: Vec<_> = advancing.into_iter().map(|(_, _, dto)| dto).collect();
+            standings.reverse();
+            if let Some(winner) = standings.first() {
+                arena
+                    .context
+                    .chat
+                    .announce(format!("{} won the tournament!", winner.alias), players);
+            }
+            standings.extend(self.eliminated.drain(..).rev());
+            return Some(standings);
+        }
+
+        self.participants = advancing
+            .iter()
+            .map(|&(player_id, score, _)| (player_id, score))
+            .collect();
+        self.round_end = Instant::now() + round_duration;
+        arena.context.chat.announce(
+            format!(
+                "Round {} has begun, with {} players remaining!",
+                self.round + 1,
+                self.participants.len()
+            ),
+            players,
+        );
+        None
+    }
+}
+
+/// Announces something in chat, in every arena.
+fn announce<G: GameArenaService>(
+    text: String,
+    overflow: &mut OverflowArenas<G>,
+    primary: &mut ContextService<G>,
+) {
+    for arena in overflow.iter_mut(primary) {
+        arena
+            .context
+            .chat
+            .announce(text.clone(), &mut arena.context.players);
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use core_protocol::id::ServerId;
+use core_protocol::name::PlayerAlias;
+use core_protocol::UnixTime;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type TransferMac = Hmac<Sha256>;
+
+/// Length of a [`TransferMac`] signature, in bytes.
+const SIGNATURE_LEN: usize = 32;
+
+/// A player's progress, carried from one server to another when they switch servers mid-session.
+///
+/// Issued by the source server as a signed token, which the client presents to the destination
+/// server when connecting. Servers of the same deployment share the signing key.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Transfer {
+    /// Only this server may accept the transfer.
+    pub server_id: ServerId,
+    /// The transfer may not be accepted after this time.
+    pub expiry: UnixTime,
+    /// Random, so that each transfer may only be accepted once.
+    pub nonce: u64,
+    pub progress: Progress,
+}
+
+/// A player's progress, restored upon joining the game, whether on another server (see
+/// [`Transfer`]) or in a replacement process (see [`Handoff`][crate::handoff::Handoff]).
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Progress {
+    pub alias: PlayerAlias,
+    pub score: u32,
+    /// Game-specific (see [`GameArenaService::transfer_out`]).
+    ///
+    /// [`GameArenaService::transfer_out`]: crate::game_service::GameArenaService::transfer_out
+    pub game: Vec<u8>,
+}
+
+impl Transfer {
+    /// How long after being issued a transfer may be accepted, in milliseconds.
+    pub const VALIDITY: UnixTime = 60 * 1000;
+
+    /// Derives the key for signing transfers from a secret shared by servers of the same
+    /// deployment, so that the secret itself (e.g. the admin password) isn't used as a key.
+    pub fn derive_key(secret: &[u8]) -> [u8; SIGNATURE_LEN] {
+        let mut mac = TransferMac::new_from_slice(secret).unwrap();
+        mac.update(b"transfer");
+        let mut key = [0; SIGNATURE_LEN];
+        key.copy_from_slice(&mac.finalize().into_bytes());
+        key
+    }
+
+    /// Encodes the transfer as a URL-safe token, signed with `key`.
+    pub fn sign(&self, key: &[u8]) -> String {
+        let mut bytes = bincode::serialize(self).unwrap();
+        let mut mac = TransferMac::new_from_slice(key).unwrap();
+        mac.update(&bytes);
+        bytes.extend_from_slice(&mac.finalize().into_bytes());
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Decodes a token from [`Self::sign`], checking that it was signed with `key`, is meant for
+    /// `server_id`, and hasn't expired as of `now`. Doesn't check whether it was already accepted.
+    pub fn verify(
+        token: &str,
+        key: &[u8],
+        server_id: ServerId,
+        now: UnixTime,
+    ) -> Result<Self, &'static str> {
+        let bytes = base64::decode_config(token, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| "invalid transfer encoding")?;
+        if bytes.len() < SIGNATURE_LEN {
+            return Err("transfer too short");
+        }
+        let (payload, signature) = bytes.split_at(bytes.len() - SIGNATURE_LEN);
+        let mut mac = TransferMac::new_from_slice(key).unwrap();
+        mac.update(payload);
+        mac.verify_slice(signature)
+            .map_err(|_| "invalid transfer signature")?;
+
+        let transfer: Self = bincode::deserialize(payload).map_err(|_| "invalid transfer")?;
+        if transfer.server_id != server_id {
+            Err("transfer meant for another server")
+        } else if now > transfer.expiry {
+            Err("transfer expired")
+        } else {
+            Ok(transfer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transfer::{Progress, Transfer};
+    use core_protocol::id::ServerId;
+    use core_protocol::name::PlayerAlias;
+
+    #[test]
+    fn sign_verify() {
+        let server_id = ServerId::new(2).unwrap();
+        let transfer = Transfer {
+            server_id,
+            expiry: 1000,
+            nonce: 42,
+            progress: Progress {
+                alias: PlayerAlias::new_unsanitized("Captain"),
+                score: 1234,
+                game: vec![1, 2, 3],
+            },
+        };
+        let token = transfer.sign(b"key");
+
+        assert_eq!(
+            Transfer::verify(&token, b"key", server_id, 500),
+            Ok(transfer)
+        );
+        assert!(Transfer::verify(&token, b"other key", server_id, 500).is_err());
+        assert!(Transfer::verify(&token, b"key", ServerId::new(3).unwrap(), 500).is_err());
+        assert!(Transfer::verify(&token, b"key", server_id, 1500).is_err());
+
+        // Tampering with any part of the transfer invalidates the signature.
+        let mut bytes = base64::decode_config(&token, base64::URL_SAFE_NO_PAD).unwrap();
+        bytes[0] ^= 1;
+        let tampered = base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        assert!(Transfer::verify(&tampered, b"key", server_id, 500).is_err());
+
+        // Derived keys are deterministic, and depend on the secret.
+        let key = Transfer::derive_key(b"password");
+        assert_eq!(key, Transfer::derive_key(b"password"));
+        assert_ne!(key, Transfer::derive_key(b"other password"));
+    }
+}
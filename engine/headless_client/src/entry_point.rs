@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::frontend::HeadlessFrontend;
+use crate::options::Options;
+use crate::stats::Stats;
+use client_util::browser_storage::BrowserStorages;
+use client_util::game_client::GameClient;
+use client_util::infrastructure::Infrastructure;
+use client_util::setting::CommonSettings;
+use log::{warn, LevelFilter};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use tokio::task::LocalSet;
+
+/// Runs [`Options::clients`] simulated clients until [`Options::duration`] elapses.
+pub fn entry_point<G: GameClient>() {
+    let options = Options::from_args();
+
+    env_logger::Builder::new()
+        .filter_level(LevelFilter::Info)
+        .init();
+
+    let runtime = tokio::runtime::Runtime::new().expect("could not start runtime");
+
+    // Clients aren't `Send`, so they all run on this thread. Their connections run on the
+    // runtime's worker threads.
+    LocalSet::new().block_on(&runtime, run::<G>(options));
+}
+
+async fn run<G: GameClient>(options: Options) {
+    let stats = Arc::new(Stats::default());
+
+    let stats_clone = Arc::clone(&stats);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Stats::PERIOD);
+        // First tick is immediate.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            stats_clone.log();
+        }
+    });
+
+    let host: Rc<str> = options.host.into();
+    let tick_period = Duration::from_millis(options.tick_millis);
+    let mut ramp_up = tokio::time::interval(Duration::from_secs_f32(1.0 / options.ramp_up));
+    let ramp = async {
+        for _ in 0..options.clients {
+            ramp_up.tick().await;
+            tokio::task::spawn_local(simulate::<G>(
+                Rc::clone(&host),
+                options.encryption,
+                tick_period,
+                Arc::clone(&stats),
+            ));
+        }
+        std::future::pending::<()>().await;
+    };
+
+    if let Some(duration) = options.duration {
+        let _ = tokio::time::timeout(Duration::from_secs(duration), ramp).await;
+    } else {
+        ramp.await;
+    }
+}
+
+/// Keeps a simulated client running, starting over if it loses its connection.
+async fn simulate<G: GameClient>(
+    host: Rc<str>,
+    encryption: bool,
+    tick_period: Duration,
+    stats: Arc<Stats>,
+) {
+    const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+    loop {
+        let frontend = HeadlessFrontend {
+            host: host.to_string(),
+            encryption,
+        };
+
+        match Infrastructure::<G>::new(
+            BrowserStorages::new(),
+            CommonSettings::default(),
+            G::GameSettings::default(),
+            Box::new(frontend),
+        ) {
+            Ok(mut infrastructure) => tick(&mut infrastructure, tick_period, &stats).await,
+            Err((e, ..)) => warn!("client error: {}", e),
+        }
+
+        stats.disconnects.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(RESTART_DELAY).await;
+    }
+}
+
+/// Runs frames of an [`Infrastructure`], which reconnects as necessary, until its connection is
+/// lost for good.
+async fn tick<G: GameClient>(
+    infrastructure: &mut Infrastructure<G>,
+    tick_period: Duration,
+    stats: &Stats,
+) {
+    let start = Instant::now();
+    let mut ticker = tokio::time::interval(tick_period);
+    let mut open = false;
+
+    while !infrastructure.context.connection_lost() {
+        ticker.tick().await;
+        infrastructure.frame(start.elapsed().as_secs_f32());
+
+        if infrastructure.context.socket.is_open() != open {
+            open = !open;
+            if open {
+                stats.connected.fetch_add(1, Ordering::Relaxed);
+            } else {
+                stats.connected.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    if open {
+        stats.connected.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,41 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use client_util::frontend::Frontend;
+use core_protocol::id::{InvitationId, ServerId};
+use core_protocol::name::Referrer;
+
+/// Stands in for the browser, telling the infrastructure where to connect.
+pub(crate) struct HeadlessFrontend {
+    pub host: String,
+    pub encryption: bool,
+}
+
+impl<P> Frontend<P> for HeadlessFrontend {
+    /// There is no UI.
+    fn set_ui_props(&self, _props: P) {}
+
+    fn get_real_referrer(&self) -> Option<Referrer> {
+        None
+    }
+
+    fn get_real_host(&self) -> Option<String> {
+        Some(self.host.clone())
+    }
+
+    fn get_real_encryption(&self) -> Option<bool> {
+        Some(self.encryption)
+    }
+
+    fn get_ideal_server_id(&self) -> Option<ServerId> {
+        None
+    }
+
+    fn get_real_query(&self) -> Option<String> {
+        None
+    }
+
+    fn get_real_invitation_id(&self) -> Option<InvitationId> {
+        None
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! # Headless Client
+//!
+//! [`headless_client`][`crate`] runs many simulated clients against a game server over real
+//! websockets, for load testing. Each is a [`GameClient`][`client_util::game_client::GameClient`]
+//! driven by `client_util`'s own [`Infrastructure`][`client_util::infrastructure::Infrastructure`],
+//! built with its `native` feature, so it handles the protocol exactly like the real client, but
+//! with no renderer or UI.
+//!
+//! Implement [`GameClient`][`client_util::game_client::GameClient`] for a simulated player, and
+//! call [`entry_point`] from `main`. See [`Options`] for command line arguments.
+
+mod entry_point;
+mod frontend;
+mod options;
+mod stats;
+
+pub use crate::entry_point::entry_point;
+pub use crate::options::Options;
@@ -0,0 +1,30 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use structopt::StructOpt;
+
+/// Load test options, to be specified as arguments.
+///
+/// All clients connect from the same IP, so the server must be run with a sufficient
+/// `--client-authenticate-burst`.
+#[derive(Debug, StructOpt)]
+pub struct Options {
+    /// Host (and port) of the server.
+    #[structopt(long, default_value = "localhost:8080")]
+    pub host: String,
+    /// Whether to connect with TLS (wss).
+    #[structopt(long)]
+    pub encryption: bool,
+    /// Number of simulated clients.
+    #[structopt(long, default_value = "100")]
+    pub clients: usize,
+    /// New connections per second, until there are enough clients.
+    #[structopt(long, default_value = "20")]
+    pub ramp_up: f32,
+    /// How often clients tick (in milliseconds).
+    #[structopt(long, default_value = "100")]
+    pub tick_millis: u64,
+    /// Stop after this long (in seconds), otherwise run forever.
+    #[structopt(long)]
+    pub duration: Option<u64>,
+}
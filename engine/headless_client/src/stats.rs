@@ -0,0 +1,30 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use log::info;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Counters shared by all simulated clients. Throughput is best measured by the server itself.
+#[derive(Default)]
+pub(crate) struct Stats {
+    /// Currently open websockets.
+    pub connected: AtomicUsize,
+    /// Clients that lost their connection for good (after any reconnection attempts), or failed
+    /// to start.
+    pub disconnects: AtomicUsize,
+}
+
+impl Stats {
+    /// How often to log stats.
+    pub const PERIOD: Duration = Duration::from_secs(5);
+
+    /// Logs the current counts, and resets disconnects.
+    pub fn log(&self) {
+        info!(
+            "connected: {}, disconnects: {}",
+            self.connected.load(Ordering::Relaxed),
+            self.disconnects.swap(0, Ordering::Relaxed),
+        );
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use glam::{uvec2, UVec2};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+#[cfg(feature = "offscreen")]
+use web_sys::OffscreenCanvas;
+
+/// A canvas that a [`Renderer`][`crate::Renderer`] can draw to.
+#[derive(Clone, Debug)]
+pub enum Canvas {
+    /// A canvas element in the document.
+    Html(HtmlCanvasElement),
+    /// An [OffscreenCanvas](https://developer.mozilla.org/en-US/docs/Web/API/OffscreenCanvas),
+    /// which can be rendered to from a web worker.
+    ///
+    /// DOM dependent functionality such as [`Texture::load`][`crate::Texture::load`] and
+    /// [`Texture::from_text`][`crate::Texture::from_text`] is unavailable in web workers.
+    #[cfg(feature = "offscreen")]
+    Offscreen(OffscreenCanvas),
+}
+
+impl Canvas {
+    /// Gets the canvas element with the id "canvas" from the document.
+    ///
+    /// # Panics
+    ///
+    /// If it doesn't exist or isn't a canvas.
+    pub fn from_document() -> Self {
+        Self::Html(js_hooks::canvas())
+    }
+
+    /// Size of the canvas in real pixels (doesn't account for device pixel ratio).
+    pub fn size(&self) -> UVec2 {
+        match self {
+            Self::Html(canvas) => uvec2(canvas.width(), canvas.height()),
+            #[cfg(feature = "offscreen")]
+            Self::Offscreen(canvas) => uvec2(canvas.width(), canvas.height()),
+        }
+    }
+
+    /// See: https://developer.mozilla.org/en-US/docs/Web/API/HTMLCanvasElement/getContext
+    pub(crate) fn get_context(
+        &self,
+        context_id: &str,
+        options: &JsValue,
+    ) -> Result<Option<js_sys::Object>, JsValue> {
+        match self {
+            Self::Html(canvas) => canvas.get_context_with_context_options(context_id, options),
+            #[cfg(feature = "offscreen")]
+            Self::Offscreen(canvas) => canvas.get_context_with_context_options(context_id, options),
+        }
+    }
+}
+
+impl From<HtmlCanvasElement> for Canvas {
+    fn from(canvas: HtmlCanvasElement) -> Self {
+        Self::Html(canvas)
+    }
+}
+
+#[cfg(feature = "offscreen")]
+impl From<OffscreenCanvas> for Canvas {
+    fn from(canvas: OffscreenCanvas) -> Self {
+        Self::Offscreen(canvas)
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use glam::{vec3, Vec3};
+
+/// Color adjustments applied to the whole frame after it's rendered. Requires the `srgb` feature
+/// (see [`RenderChain::set_color_grading`][`crate::RenderChain::set_color_grading`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorGrading {
+    /// Multiplies linear colors, where 1.0 is unchanged.
+    pub exposure: f32,
+    /// Where 0.0 is grayscale and 1.0 is unchanged.
+    pub saturation: f32,
+    /// Color look up table, applied last.
+    pub lut: Lut,
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            saturation: 1.0,
+            lut: Lut::None,
+        }
+    }
+}
+
+/// Preset color look up tables.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Lut {
+    /// Colors are unchanged.
+    #[default]
+    None,
+    /// Orange tint.
+    Warm,
+    /// Blue tint.
+    Cool,
+    /// High contrast black and white.
+    Noir,
+    /// Faded sepia.
+    Vintage,
+}
+
+impl Lut {
+    /// Number of samples per channel.
+    pub(crate) const SIZE: u32 = 16;
+
+    /// Maps an sRGB color through the preset.
+    fn map(self, color: Vec3) -> Vec3 {
+        let luminance = color.dot(vec3(0.2126, 0.7152, 0.0722));
+        match self {
+            Self::None => color,
+            Self::Warm => color * vec3(1.08, 1.02, 0.88),
+            Self::Cool => color * vec3(0.9, 0.98, 1.1),
+            Self::Noir => {
+                // Smoothstep adds contrast.
+                Vec3::splat(luminance * luminance * (3.0 - 2.0 * luminance))
+            }
+            Self::Vintage => {
+                let sepia = vec3(1.07, 0.95, 0.75) * luminance;
+                // Lift blacks and lower whites for a faded look.
+                color.lerp(sepia, 0.6) * 0.85 + 0.08
+            }
+        }
+        .clamp(Vec3::ZERO, Vec3::ONE)
+    }
+
+    /// Returns RGB bytes of a [`Self::SIZE`]² by [`Self::SIZE`] texture, where blue selects
+    /// which square along the x axis.
+    pub(crate) fn bytes(self) -> Vec<u8> {
+        let size = Self::SIZE;
+        let max = (size - 1) as f32;
+        let mut bytes = Vec::with_capacity((size * size * size * 3) as usize);
+        for g in 0..size {
+            for b in 0..size {
+                for r in 0..size {
+                    let color = self.map(vec3(r as f32, g as f32, b as f32) * (1.0 / max));
+                    bytes.extend((color * 255.0).round().to_array().map(|c| c as u8));
+                }
+            }
+        }
+        bytes
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::gl::*;
-use crate::renderer::Renderer;
+use crate::renderer::{Antialiasing, Renderer};
 use crate::rgba_array;
 use crate::texture::{Texture, TextureFormat};
 use glam::{UVec2, Vec4};
@@ -226,7 +226,7 @@ impl Framebuffer {
 
                 let d = viewport.as_ivec2();
 
-                if ms && renderer.antialias {
+                if ms && renderer.antialiasing == Antialiasing::Msaa {
                     #[cfg(not(feature = "webgl2"))]
                     unimplemented!();
                     #[cfg(feature = "webgl2")]
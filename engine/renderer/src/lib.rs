@@ -11,6 +11,14 @@
 //! [WebGL](https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.WebGlRenderingContext.html)/
 //! [WebGL2](https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.WebGl2RenderingContext.html)
 //! that can be used in 2D and 3D applications.
+//!
+//! ## Embedding
+//!
+//! Create a [`RenderChain`] with [`RenderChain::builder`], passing any [`Canvas`], and a
+//! [`Layer`] (see [`renderer2d`](../renderer2d/index.html) for some ready-made ones). Enable the
+//! `offscreen` feature to render to an
+//! [OffscreenCanvas](https://developer.mozilla.org/en-US/docs/Web/API/OffscreenCanvas) from a web
+//! worker.
 
 // Gl primitives should not escape this crate.
 #[macro_use]
@@ -21,6 +29,8 @@ mod srgb_layer;
 
 mod attribs;
 mod buffer;
+mod canvas;
+mod color_grading;
 mod deque;
 mod framebuffer;
 mod index;
@@ -38,6 +48,8 @@ pub use attribs::*;
 
 // Re-export to provide a simpler api.
 pub use buffer::*;
+pub use canvas::*;
+pub use color_grading::*;
 pub use deque::*;
 pub use framebuffer::*;
 pub use index::*;
@@ -1,19 +1,38 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::canvas::Canvas;
 use crate::gl::*;
 use crate::shader::Shader;
 use crate::OwnedFramebufferBinding;
 pub use engine_macros::Layer;
-use glam::{uvec2, UVec2, Vec4};
+use glam::{vec2, UVec2, Vec2, Vec4};
 use js_hooks::error_message;
 use linear_map::LinearMap;
 use std::cell::{Cell, RefCell};
 use wasm_bindgen::JsCast;
-use web_sys::HtmlCanvasElement;
 
 #[cfg(feature = "srgb")]
 use crate::srgb_layer::SrgbLayer;
+#[cfg(feature = "srgb")]
+use crate::ColorGrading;
+
+/// How to smooth jagged edges. Only [`Antialiasing::Msaa`] is supported without the `srgb`
+/// feature.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Antialiasing {
+    /// No antialiasing.
+    #[default]
+    None,
+    /// Fast approximate antialiasing, a cheap post processing pass.
+    Fxaa,
+    /// Multisample antialiasing, the most expensive. Falls back to [`Antialiasing::Fxaa`] if
+    /// the `srgb` feature is enabled without the `webgl2` feature.
+    Msaa,
+    /// Temporal antialiasing, which blends each frame with the previous ones to reduce shimmering.
+    /// Cameras should be offset by [`Renderer::taa_jitter`] to get the most out of it.
+    Taa,
+}
 
 /// Owns a [`Renderer`] and a [`Layer`].
 pub struct RenderChain<L> {
@@ -25,17 +44,34 @@ pub struct RenderChain<L> {
 }
 
 impl<L: Layer> RenderChain<L> {
-    /// Creates a new [`RenderChain`] from a `renderer` and a `layer`.
+    /// Creates a new [`RenderChain`] that draws to the canvas element with the id "canvas." The
+    /// [`Layer`] is created by `f`. Use [`RenderChainBuilder`] for more options.
     pub fn new(
         background_color: [u8; 4],
-        antialias: bool,
+        antialiasing: Antialiasing,
+        f: impl FnOnce(&mut Renderer) -> L,
+    ) -> Result<Self, String> {
+        RenderChainBuilder::default()
+            .background_color(background_color)
+            .antialiasing(antialiasing)
+            .build(f)
+    }
+
+    /// Creates a new [`RenderChainBuilder`].
+    pub fn builder() -> RenderChainBuilder {
+        RenderChainBuilder::default()
+    }
+
+    fn with_builder(
+        builder: RenderChainBuilder,
         f: impl FnOnce(&mut Renderer) -> L,
     ) -> Result<Self, String> {
         // Don't give backbuffer aa and depth buffer if we aren't using it.
         let backbuffer = cfg!(not(feature = "srgb"));
         let mut renderer = Renderer::new(
-            background_color,
-            antialias,
+            builder.canvas.unwrap_or_else(Canvas::from_document),
+            builder.background_color,
+            builder.antialiasing,
             backbuffer,
             backbuffer && L::ALPHA,
             backbuffer && L::DEPTH,
@@ -62,6 +98,12 @@ impl<L: Layer> RenderChain<L> {
         &mut self.layer.inner
     }
 
+    /// Sets the [`ColorGrading`] applied to every frame.
+    #[cfg(feature = "srgb")]
+    pub fn set_color_grading(&mut self, color_grading: ColorGrading) {
+        self.layer.set_color_grading(&self.renderer, color_grading);
+    }
+
     /// Gets the [`Renderer`] passed to [`new`][`Self::new`].
     pub fn renderer(&self) -> &Renderer {
         &self.renderer
@@ -84,6 +126,78 @@ impl<L: Layer> RenderChain<L> {
     }
 }
 
+/// Builds a [`RenderChain`], for embedding the renderer in applications other than games.
+///
+/// ```ignore
+/// let render_chain = RenderChain::builder()
+///     .background_color([0, 0, 0, 255])
+///     .antialias(true)
+///     .canvas(canvas)
+///     .build(|r| MyLayer::new(r))?;
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct RenderChainBuilder {
+    background_color: [u8; 4],
+    antialiasing: Antialiasing,
+    canvas: Option<Canvas>,
+}
+
+impl Default for RenderChainBuilder {
+    fn default() -> Self {
+        Self {
+            background_color: [0, 0, 0, 255],
+            antialiasing: Antialiasing::None,
+            canvas: None,
+        }
+    }
+}
+
+impl RenderChainBuilder {
+    /// Sets the color that the canvas is cleared to (defaults to black).
+    pub fn background_color(mut self, background_color: [u8; 4]) -> Self {
+        self.background_color = background_color;
+        self
+    }
+
+    /// Sets whether to antialias (defaults to false). Shorthand for [`Antialiasing::Msaa`].
+    pub fn antialias(self, antialias: bool) -> Self {
+        self.antialiasing(if antialias {
+            Antialiasing::Msaa
+        } else {
+            Antialiasing::None
+        })
+    }
+
+    /// Sets the [`Antialiasing`] mode (defaults to [`Antialiasing::None`]).
+    pub fn antialiasing(mut self, antialiasing: Antialiasing) -> Self {
+        self.antialiasing = antialiasing;
+        self
+    }
+
+    /// Sets the [`Canvas`] to draw to (defaults to [`Canvas::from_document`]).
+    pub fn canvas(mut self, canvas: impl Into<Canvas>) -> Self {
+        self.canvas = Some(canvas.into());
+        self
+    }
+
+    /// Sets an [`OffscreenCanvas`][`web_sys::OffscreenCanvas`] to draw to, e.g. one transferred
+    /// to a web worker with
+    /// [`transferControlToOffscreen`](https://developer.mozilla.org/en-US/docs/Web/API/HTMLCanvasElement/transferControlToOffscreen).
+    #[cfg(feature = "offscreen")]
+    pub fn offscreen_canvas(self, canvas: web_sys::OffscreenCanvas) -> Self {
+        self.canvas(canvas)
+    }
+
+    /// Creates the [`Renderer`] and then the [`Layer`] with `f`.
+    pub fn build<L: Layer>(
+        self,
+        f: impl FnOnce(&mut Renderer) -> L,
+    ) -> Result<RenderChain<L>, String> {
+        RenderChain::with_builder(self, f)
+    }
+}
+
 /// A single frame being rendered.
 pub struct RenderFrame<'a, L> {
     #[cfg(not(feature = "srgb"))]
@@ -205,8 +319,8 @@ pub trait RenderLayer<P>: Layer {
 /// [WebGL2](https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.WebGl2RenderingContext.html)
 /// that can be used in 2D and 3D applications.
 pub struct Renderer {
-    /// HTML Canvas.
-    canvas: HtmlCanvasElement,
+    /// HTML or offscreen canvas.
+    canvas: Canvas,
     cached_canvas_size: Cell<Option<UVec2>>,
     /// WebGL context.
     pub(crate) gl: Gl,
@@ -222,9 +336,11 @@ pub struct Renderer {
     pub time_delta: f32,
     /// Cache of static shaders.
     shader_cache: RefCell<LinearMap<(&'static str, &'static str), Shader>>,
-    /// WebGL doesn't support antialiasing with srgb.
+    /// WebGL doesn't support builtin antialiasing with srgb.
     #[allow(unused)]
-    pub(crate) antialias: bool,
+    pub(crate) antialiasing: Antialiasing,
+    /// Frames rendered so far, for [`Self::taa_jitter`].
+    frame: u32,
     /// How much anisotropy to use or None if shouldn't use.
     #[cfg(feature = "anisotropy")]
     pub(crate) anisotropy: Option<u32>,
@@ -238,17 +354,18 @@ pub struct Renderer {
 }
 
 impl Renderer {
-    /// Creates a new WebGL/WebGL2 render, attaching it to the canvas element with the id "canvas."
+    /// Creates a new WebGL/WebGL2 render, attaching it to `canvas`.
     #[doc(hidden)]
     pub(crate) fn new(
+        canvas: Canvas,
         background_color: [u8; 4],
-        antialias: bool,
+        antialiasing: Antialiasing,
         builtin_aa: bool,
         alpha: bool,
         depth: bool,
         stencil: bool,
     ) -> Result<Self, String> {
-        let builtin_aa = builtin_aa && antialias;
+        let builtin_aa = builtin_aa && antialiasing == Antialiasing::Msaa;
         let _ = alpha; // TODO
 
         let options = js_sys::JSON::parse(&format!(
@@ -263,10 +380,8 @@ impl Renderer {
         ))
         .unwrap();
 
-        let canvas = js_hooks::canvas();
-        // See: https://developer.mozilla.org/en-US/docs/Web/API/HTMLCanvasElement/getContext
         let gl = canvas
-            .get_context_with_context_options(GL_NAME, &options)
+            .get_context(GL_NAME, &options)
             .map_err(|e| {
                 error_message(&e)
                     .unwrap_or_else(|| concat!("Error initializing ", gl_title!()).into())
@@ -305,7 +420,8 @@ impl Renderer {
             time: 0.0,
             time_delta: 0.0,
             shader_cache: Default::default(),
-            antialias,
+            antialiasing,
+            frame: 0,
             #[cfg(feature = "anisotropy")]
             anisotropy: None,
             #[cfg(feature = "webgl2")]
@@ -383,18 +499,48 @@ impl Renderer {
         }
     }
 
+    /// Returns the [`Canvas`] being drawn to.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
     /// Returns the aspect ratio (width / height) of the canvas.
     pub fn aspect_ratio(&self) -> f32 {
         viewport_to_aspect(self.canvas_size())
     }
 
+    /// Sub-pixel offset, in view space (`-1.0..1.0`), that cameras should add to their position
+    /// this frame. Zero unless using [`Antialiasing::Taa`].
+    pub fn taa_jitter(&self) -> Vec2 {
+        if self.antialiasing != Antialiasing::Taa || !cfg!(feature = "srgb") {
+            return Vec2::ZERO;
+        }
+
+        // Halton sequence (bases 2 and 3) covers the pixel evenly over a few frames.
+        fn halton(mut i: u32, base: u32) -> f32 {
+            let mut f = 1.0;
+            let mut r = 0.0;
+            while i > 0 {
+                f /= base as f32;
+                r += f * (i % base) as f32;
+                i /= base;
+            }
+            r
+        }
+        let i = self.frame % 8 + 1;
+        let offset = vec2(halton(i, 2), halton(i, 3)) - 0.5;
+
+        // One pixel is 2 / canvas size in view space.
+        offset * 2.0 / self.canvas_size().as_vec2()
+    }
+
     /// Size of the canvas in real pixels (doesn't account for device pixel ratio).
     pub fn canvas_size(&self) -> UVec2 {
         let cached_size = self.cached_canvas_size.get();
         if let Some(size) = cached_size {
             size
         } else {
-            let size = uvec2(self.canvas.width(), self.canvas.height());
+            let size = self.canvas.size();
             self.cached_canvas_size.set(Some(size));
             size
         }
@@ -485,6 +631,7 @@ impl Renderer {
         self.cached_canvas_size.set(None);
         self.time_delta = time_seconds - self.time;
         self.time = time_seconds;
+        self.frame = self.frame.wrapping_add(1);
 
         layer.pre_prepare(self);
     }
@@ -2,14 +2,33 @@ precision mediump float;
 
 varying vec2 vUv;
 uniform sampler2D uSampler;
+uniform sampler2D uLut;
+uniform float uExposure;
+uniform float uSaturation;
+uniform float uLutStrength;
+
+// Samples a 16x16x16 color look up table laid out as 16 squares along the x axis.
+vec3 lookup(vec3 color) {
+    color = clamp(color, 0.0, 1.0);
+    float blue = color.b * 15.0;
+    float blue0 = floor(blue);
+    float blue1 = min(blue0 + 1.0, 15.0);
+    vec2 uv = vec2((color.r * 15.0 + 0.5) / 256.0, (color.g * 15.0 + 0.5) / 16.0);
+    vec3 sample0 = texture2D(uLut, uv + vec2(blue0 / 16.0, 0.0)).rgb;
+    vec3 sample1 = texture2D(uLut, uv + vec2(blue1 / 16.0, 0.0)).rgb;
+    return mix(sample0, sample1, blue - blue0);
+}
 
 // Based on https://github.com/gfx-rs/wgpu/blob/master/wgpu-hal/src/gles/shaders/srgb_present.frag
 void main() {
     vec4 linear = texture2D(uSampler, vUv);
-    vec3 color_linear = linear.rgb;
+    vec3 color_linear = linear.rgb * uExposure;
+    float luminance = dot(color_linear, vec3(0.2126, 0.7152, 0.0722));
+    color_linear = max(mix(vec3(luminance), color_linear, uSaturation), 0.0);
     vec3 selector = ceil(color_linear - 0.0031308);
     vec3 under = 12.92 * color_linear;
     vec3 over = 1.055 * pow(color_linear, vec3(0.41666)) - 0.055;
     vec3 result = mix(under, over, selector);
+    result = mix(result, lookup(result), uLutStrength);
     gl_FragColor = vec4(result, linear.a);
 }
@@ -0,0 +1,25 @@
+precision mediump float;
+
+varying vec2 vUv;
+uniform sampler2D uCurrent;
+uniform sampler2D uHistory;
+uniform vec2 uInverseVP;
+uniform float uHistoryWeight;
+
+void main() {
+    vec3 current = texture2D(uCurrent, vUv).rgb;
+
+    // Clamp history to the colors around the current pixel, so moving edges don't leave trails.
+    vec3 minColor = current;
+    vec3 maxColor = current;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec3 neighbor = texture2D(uCurrent, vUv + vec2(float(x), float(y)) * uInverseVP).rgb;
+            minColor = min(minColor, neighbor);
+            maxColor = max(maxColor, neighbor);
+        }
+    }
+    vec3 history = clamp(texture2D(uHistory, vUv).rgb, minColor, maxColor);
+
+    gl_FragColor = vec4(mix(current, history, uHistoryWeight), 1.0);
+}
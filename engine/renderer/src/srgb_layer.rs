@@ -2,22 +2,27 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::{
-    DefaultRender, Framebuffer, Layer, RenderLayer, Renderer, Shader, TextureFormat, TriangleBuffer,
+    Antialiasing, ColorGrading, DefaultRender, Framebuffer, Layer, Lut, RenderLayer, Renderer,
+    Shader, Texture, TextureFormat, TriangleBuffer, TriangleBufferBinding,
 };
-use glam::{vec2, Vec2};
+use glam::{uvec2, vec2, UVec2, Vec2};
 
 /// Fast approximate antialiasing.
-#[cfg(not(feature = "webgl2"))]
 struct Fxaa {
     framebuffer: Framebuffer,
     shader: Shader,
 }
 
-#[cfg(not(feature = "webgl2"))]
 impl DefaultRender for Fxaa {
     fn new(renderer: &Renderer) -> Self {
         // Requires its own framebuffer because fxaa operates on non-srgb colors.
-        let framebuffer = Framebuffer::new2(renderer, false, TextureFormat::Rgba, false);
+        let framebuffer = Framebuffer::new2(
+            renderer,
+            [0; 4],
+            true,
+            TextureFormat::Rgba { premultiply: false },
+            false,
+        );
         let shader = renderer.create_shader(
             include_str!("shaders/fxaa.vert"),
             include_str!("shaders/fxaa.frag"),
@@ -29,9 +34,8 @@ impl DefaultRender for Fxaa {
     }
 }
 
-#[cfg(not(feature = "webgl2"))]
 impl Fxaa {
-    fn render(&mut self, renderer: &Renderer, binding: &crate::TriangleBufferBinding<Vec2, u16>) {
+    fn render(&mut self, renderer: &Renderer, binding: &TriangleBufferBinding<Vec2, u16>) {
         if let Some(shader) = self.shader.bind(renderer) {
             shader.uniform("uVP", renderer.canvas_size().as_vec2());
             shader.uniform("uInverseVP", renderer.canvas_size().as_vec2().recip());
@@ -41,17 +45,100 @@ impl Fxaa {
     }
 }
 
+/// Temporal antialiasing. Blends each frame with the accumulated previous frames, clamped to the
+/// current frame's local colors to avoid ghosting.
+struct Taa {
+    /// Accumulated result, including the current frame once rendered.
+    history: Framebuffer,
+    /// Where the next result is rendered, after which it's swapped with `history`.
+    next: Framebuffer,
+    shader: Shader,
+    /// Whether `history` contains a frame of the current viewport.
+    valid: bool,
+    viewport: UVec2,
+}
+
+impl DefaultRender for Taa {
+    fn new(renderer: &Renderer) -> Self {
+        let new_framebuffer = || {
+            Framebuffer::new2(
+                renderer,
+                [0; 4],
+                false,
+                TextureFormat::Srgba { premultiply: false },
+                false,
+            )
+        };
+        let shader = renderer.create_shader(
+            include_str!("shaders/srgb.vert"),
+            include_str!("shaders/taa.frag"),
+        );
+        Self {
+            history: new_framebuffer(),
+            next: new_framebuffer(),
+            shader,
+            valid: false,
+            viewport: UVec2::ZERO,
+        }
+    }
+}
+
+impl Taa {
+    /// How much of the previous frames to keep each frame.
+    const HISTORY_WEIGHT: f32 = 0.85;
+
+    fn set_viewport(&mut self, renderer: &Renderer, viewport: UVec2) {
+        if viewport != self.viewport {
+            self.viewport = viewport;
+            self.valid = false;
+        }
+        self.history.set_viewport(renderer, viewport);
+        self.next.set_viewport(renderer, viewport);
+    }
+
+    /// Blends `current` into the history.
+    fn render(
+        &mut self,
+        renderer: &Renderer,
+        current: &Framebuffer,
+        binding: &TriangleBufferBinding<Vec2, u16>,
+    ) {
+        let fb = self.next.bind(renderer);
+        if let Some(shader) = self.shader.bind(renderer) {
+            shader.uniform("uCurrent", current.as_texture());
+            shader.uniform("uHistory", self.history.as_texture());
+            shader.uniform("uInverseVP", renderer.canvas_size().as_vec2().recip());
+            shader.uniform(
+                "uHistoryWeight",
+                if self.valid {
+                    Self::HISTORY_WEIGHT
+                } else {
+                    0.0
+                },
+            );
+            binding.draw();
+        }
+        drop(fb);
+
+        std::mem::swap(&mut self.history, &mut self.next);
+        self.valid = true;
+    }
+}
+
 /// Draws its inner [`Layer`] in the [SRGB color space](https://en.wikipedia.org/wiki/SRGB). It's
 /// automatically added as the root layer if the `srgb` feature is enabled.
 pub(crate) struct SrgbLayer<I> {
     buffer: TriangleBuffer<Vec2>,
-    #[cfg(not(feature = "webgl2"))]
+    color_grading: ColorGrading,
     fxaa: Option<Fxaa>,
     /// The inner [`Layer`] passed to [`new`][`Self::new`].
     pub inner: I,
+    /// Contains [`ColorGrading::lut`].
+    lut: Texture,
     #[cfg(feature = "webgl2")]
     msaa: Option<Framebuffer>,
     shader: Shader,
+    taa: Option<Taa>,
     texture_fb: Framebuffer,
 }
 
@@ -65,17 +152,24 @@ impl<I: Layer> SrgbLayer<I> {
     /// Creates a new [`SrgbLayer`].
     pub(crate) fn with_inner(renderer: &Renderer, inner: I) -> Self {
         let background_color = renderer.background_color;
-        let antialias = renderer.antialias;
+        let antialiasing = renderer.antialiasing;
         let depth_stencil = I::DEPTH;
 
         // Use builtin msaa if possible (WebGL2 only).
         #[cfg(feature = "webgl2")]
-        let msaa = antialias
+        let msaa = (antialiasing == Antialiasing::Msaa)
             .then(|| Framebuffer::new_antialiased(renderer, background_color, depth_stencil));
 
         // Otherwise use postprocessing fxaa (WebGL doesn't support builtin aa).
-        #[cfg(not(feature = "webgl2"))]
-        let fxaa = antialias.then(|| Fxaa::new(renderer));
+        let fxaa = (antialiasing == Antialiasing::Fxaa
+            || (antialiasing == Antialiasing::Msaa && !cfg!(feature = "webgl2")))
+        .then(|| Fxaa::new(renderer));
+
+        let taa = (antialiasing == Antialiasing::Taa).then(|| Taa::new(renderer));
+
+        let color_grading = ColorGrading::default();
+        let mut lut = Texture::new_empty(renderer, TextureFormat::Rgb, true);
+        Self::buffer_lut(renderer, &mut lut, color_grading.lut);
 
         // Create a buffer that has 1 triangle covering the whole screen.
         let mut buffer = TriangleBuffer::new(renderer);
@@ -100,15 +194,30 @@ impl<I: Layer> SrgbLayer<I> {
 
         Self {
             buffer,
-            #[cfg(not(feature = "webgl2"))]
+            color_grading,
             fxaa,
             inner,
+            lut,
             #[cfg(feature = "webgl2")]
             msaa,
             shader,
+            taa,
             texture_fb,
         }
     }
+
+    /// Sets the [`ColorGrading`] applied when drawing to the main screen.
+    pub(crate) fn set_color_grading(&mut self, renderer: &Renderer, color_grading: ColorGrading) {
+        if color_grading.lut != self.color_grading.lut {
+            Self::buffer_lut(renderer, &mut self.lut, color_grading.lut);
+        }
+        self.color_grading = color_grading;
+    }
+
+    fn buffer_lut(renderer: &Renderer, texture: &mut Texture, lut: Lut) {
+        let size = Lut::SIZE;
+        texture.realloc_with_opt_bytes(renderer, uvec2(size * size, size), Some(&lut.bytes()));
+    }
 }
 
 impl<I: Layer> Layer for SrgbLayer<I> {
@@ -124,10 +233,12 @@ impl<I: Layer> Layer for SrgbLayer<I> {
         if let Some(msaa) = &mut self.msaa {
             msaa.set_viewport(renderer, viewport);
         }
-        #[cfg(not(feature = "webgl2"))]
         if let Some(fxaa) = &mut self.fxaa {
             fxaa.framebuffer.set_viewport(renderer, viewport);
         }
+        if let Some(taa) = &mut self.taa {
+            taa.set_viewport(renderer, viewport);
+        }
         self.texture_fb.set_viewport(renderer, viewport);
     }
 }
@@ -154,27 +265,41 @@ impl<I: RenderLayer<P>, P> RenderLayer<P> for SrgbLayer<I> {
             msaa.blit_to(renderer, Some(&mut self.texture_fb));
         }
 
+        // Fxaa and taa also require this binding so bind before shader.
+        let binding = self.buffer.bind(renderer);
+
+        // Blend with previous frames.
+        if let Some(taa) = &mut self.taa {
+            taa.render(renderer, &self.texture_fb, &binding);
+        }
+        let resolved = self
+            .taa
+            .as_ref()
+            .map_or(&self.texture_fb, |taa| &taa.history);
+
         // Capture main screen draw and render to fxaa fb.
-        #[cfg(not(feature = "webgl2"))]
         let fb = self.fxaa.as_mut().map(|f| f.framebuffer.bind(renderer));
 
-        // Fxaa also requires this binding so bind before shader.
-        let binding = self.buffer.bind(renderer);
-
         // Draw to main screen. Can't do `self.write_fb.blit_to(renderer, None);` because it
         // won't keep srgb encoding.
         if let Some(shader) = self.shader.bind(renderer) {
-            shader.uniform("uSampler", self.texture_fb.as_texture());
+            let ColorGrading {
+                exposure,
+                saturation,
+                lut,
+            } = self.color_grading;
+            shader.uniform("uSampler", resolved.as_texture());
+            shader.uniform("uLut", &self.lut);
+            shader.uniform("uExposure", exposure);
+            shader.uniform("uSaturation", saturation);
+            shader.uniform("uLutStrength", if lut == Lut::None { 0.0 } else { 1.0 });
             binding.draw();
         }
 
         // Draw fxaa framebuffer to main screen with fxaa applied.
-        #[cfg(not(feature = "webgl2"))]
-        {
-            drop(fb);
-            if let Some(fxaa) = &mut self.fxaa {
-                fxaa.render(renderer, &binding);
-            }
+        drop(fb);
+        if let Some(fxaa) = &mut self.fxaa {
+            fxaa.render(renderer, &binding);
         }
     }
 }
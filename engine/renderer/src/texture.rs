@@ -6,6 +6,7 @@ use crate::renderer::Renderer;
 use crate::rgb::rgba_array_to_css;
 use glam::UVec2;
 use js_hooks::document;
+use std::borrow::Cow;
 use std::cell::Cell;
 use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
@@ -464,35 +465,66 @@ impl Texture {
         drop(binding);
     }
 
+    /// Height of [`Texture::from_text`] textures, unless they had to grow to fit tall glyphs.
+    pub const TEXT_HEIGHT: u32 = 36; // 32 -> 36 to fit "😊".
+
     /// Creates a [`Texture`] from `text`, with variable length and constant height. It's format
     /// will be `TextureFormat::COLOR_RGBA`. Pass `color` to this function instead of coloring in a
     /// [`Shader`][`crate::shader::Shader`] so emoji colors are preserved.
     pub fn from_text(renderer: &Renderer, text: &str, color: [u8; 4]) -> Self {
+        Self::from_text_with_fonts(renderer, text, color, "Arial")
+    }
+
+    /// Like [`Texture::from_text`], but with `fonts`, a CSS font family list. The browser falls
+    /// back through `fonts` glyph by glyph, so it should cover every script `text` may contain.
+    /// Text is shaped by the browser, and right-to-left text is laid out as such.
+    ///
+    /// The height is [`Texture::TEXT_HEIGHT`] unless some glyphs wouldn't fit, in which case the
+    /// texture grows equally up and down, so the text stays centered at the same size.
+    pub fn from_text_with_fonts(
+        renderer: &Renderer,
+        text: &str,
+        color: [u8; 4],
+        fonts: &str,
+    ) -> Self {
         let (canvas, context) = create_canvas();
 
-        const FONT: &str = "30px Arial";
-        const HEIGHT: u32 = 36; // 32 -> 36 to fit "😊".
+        let font = format!("30px {}", fonts);
+        let text = bidi_isolate(text);
+        let setup = |context: &CanvasRenderingContext2d| {
+            context.set_font(&font);
+            context.set_text_baseline("bottom");
+            context.set_text_align("left");
+        };
+
+        setup(&context);
+        let metrics = context.measure_text(&text).unwrap();
+        let text_width = metrics.width();
 
-        context.set_font(FONT);
-        context.set_text_baseline("bottom");
-        let text_width = context.measure_text(text).unwrap().width();
+        // Baseline is 1 pixel from the bottom of the original height.
+        let overflow_top = (metrics.actual_bounding_box_ascent().ceil() as u32)
+            .saturating_sub(Self::TEXT_HEIGHT - 1);
+        let overflow_bottom =
+            (metrics.actual_bounding_box_descent().ceil() as u32).saturating_sub(1);
+        let grow = overflow_top.max(overflow_bottom);
+        let height = Self::TEXT_HEIGHT + grow * 2;
 
         let canvas_width = text_width as u32 + 2;
         canvas.set_width(canvas_width);
-        canvas.set_height(HEIGHT);
+        canvas.set_height(height);
 
         let color_string = rgba_array_to_css(color);
 
+        // Resizing the canvas resets its state.
         context.set_fill_style(&JsValue::from_str(&color_string));
-        context.set_font(FONT);
-        context.set_text_baseline("bottom");
+        setup(&context);
 
         context
-            .fill_text(text, 1.0, (HEIGHT - 1) as f64)
+            .fill_text(&text, 1.0, (Self::TEXT_HEIGHT - 1 + grow) as f64)
             .expect("could not fill text on canvas");
 
         let format = TextureFormat::COLOR_RGBA;
-        let dimensions = UVec2::new(canvas_width, HEIGHT);
+        let dimensions = UVec2::new(canvas_width, height);
 
         let gl = &renderer.gl;
         let texture = Self::new(gl, dimensions, format, TextureType::D2);
@@ -800,6 +832,22 @@ impl Texture {
     }
 }
 
+/// Wraps `text` in a right-to-left isolate if its first strong character is right-to-left, so that
+/// neutral characters (e.g. brackets around a team name) are ordered correctly.
+fn bidi_isolate(text: &str) -> Cow<str> {
+    let rtl = text
+        .chars()
+        .find(|c| c.is_alphabetic())
+        .map_or(false, |c| {
+            matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+        });
+    if rtl {
+        Cow::Owned(format!("\u{2067}{}\u{2069}", text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
 /// Creates a temporary canvas for drawing and then converting into a texture.
 fn create_canvas() -> (HtmlCanvasElement, CanvasRenderingContext2d) {
     let canvas: HtmlCanvasElement = document()
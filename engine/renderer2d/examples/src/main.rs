@@ -1,6 +1,6 @@
 use glam::*;
 use js_hooks::console_log;
-use renderer::{gray_a, rgba, DefaultRender, Layer, RenderChain};
+use renderer::{gray_a, rgba, Canvas, DefaultRender, Layer, RenderChain};
 use renderer2d::{Camera2d, GraphicLayer, TextLayer};
 
 #[derive(Layer)]
@@ -15,20 +15,20 @@ fn main() {
     #[cfg(target_family = "wasm")]
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
 
-    // Create renderer with antialiasing.
-    let antialias = true;
-    let mut render_chain = RenderChain::new(antialias, |r| {
-        // Set background color to gray.
-        let gray = gray_a(50, 255);
-        r.set_background_color(gray);
-
-        // Create our layer.
-        MyLayer {
-            graphics: GraphicLayer::new(r),
-            text: TextLayer::new(r),
-        }
-    })
-    .expect("no webgl");
+    // Create renderer with antialiasing and a gray background, drawing to the canvas with the id
+    // "canvas" (an OffscreenCanvas could be used instead with the "offscreen" feature).
+    let mut render_chain = RenderChain::builder()
+        .antialias(true)
+        .background_color([50, 50, 50, 255])
+        .canvas(Canvas::from_document())
+        .build(|r| {
+            // Create our layer.
+            MyLayer {
+                graphics: GraphicLayer::new(r),
+                text: TextLayer::new(r),
+            }
+        })
+        .expect("no webgl");
 
     // Prepare with time set to 0.
     let time_seconds = 0.0;
@@ -53,17 +53,34 @@ impl<I: Index> GraphicLayer<I> {
             I::from_usize(index + 2),
         ]);
 
+        self.mesh.vertices.extend(
+            Self::triangle_positions(center, scale, angle).map(|pos| PosColor { pos, color }),
+        );
+    }
+
+    /// Like [`draw_triangle`][`Self::draw_triangle`] but outlined instead of filled.
+    pub fn draw_triangle_outline(
+        &mut self,
+        center: Vec2,
+        scale: Vec2,
+        angle: f32,
+        thickness: f32,
+        color: Vec4,
+    ) {
+        let [a, b, c] = Self::triangle_positions(center, scale, angle);
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            self.draw_rounded_line(start, end, thickness, color, true);
+        }
+    }
+
+    fn triangle_positions(center: Vec2, scale: Vec2, angle: f32) -> [Vec2; 3] {
         let rot = Mat2::from_angle(angle);
-        let positions = [
+        [
             Vec2::new(-0.5, -0.5),
             Vec2::new(0.5, -0.5),
             Vec2::new(0.0, 0.25 * 3f32.sqrt()),
-        ];
-
-        self.mesh.vertices.extend(positions.map(|pos| PosColor {
-            pos: center + rot * (pos * scale),
-            color,
-        }));
+        ]
+        .map(|pos| center + rot * (pos * scale))
     }
 
     /// Draws a rectangle centered on `center`, with length/width of `scale` and rotated by `angle`.
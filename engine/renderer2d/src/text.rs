@@ -15,10 +15,39 @@ struct Buffers {
 }
 
 struct Draw {
-    center: Vec2,
+    position: Vec2,
+    anchor: TextAnchor,
     scale: f32,
 }
 
+/// Which point of text is drawn at a position. [`TextAnchor::Start`] and [`TextAnchor::End`]
+/// depend on [`TextLayer::set_right_to_left`], so HUD text can be mirrored for right-to-left
+/// languages.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TextAnchor {
+    Center,
+    /// Left edge, or right edge if right-to-left.
+    Start,
+    /// Right edge, or left edge if right-to-left.
+    End,
+}
+
+impl TextAnchor {
+    /// Horizontal offset from the anchor to the center of text `width` wide.
+    fn center_offset(self, width: f32, right_to_left: bool) -> f32 {
+        let offset = match self {
+            Self::Center => 0.0,
+            Self::Start => width * 0.5,
+            Self::End => width * -0.5,
+        };
+        if right_to_left {
+            -offset
+        } else {
+            offset
+        }
+    }
+}
+
 /// Draws single lines of text.
 pub struct TextLayer {
     /// Too expensive to create text textures every frame, so cache them.
@@ -26,6 +55,10 @@ pub struct TextLayer {
     /// Uses 8 bit rbga color (compatible with JS).
     /// TODO could use additive blend mode to prevent unstable ordering if it matters.
     buffers: HashMap<(String, [u8; 4]), Buffers>,
+    /// CSS font family list, see [`TextLayer::set_fonts`].
+    fonts: &'static str,
+    /// See [`TextLayer::set_right_to_left`].
+    right_to_left: bool,
     /// Same for all text.
     geometry: TriangleBuffer<Vec2>,
     shader: Shader,
@@ -52,6 +85,8 @@ impl DefaultRender for TextLayer {
 
         Self {
             buffers: HashMap::new(),
+            fonts: Self::DEFAULT_FONTS,
+            right_to_left: false,
             geometry: text_geometry,
             shader,
         }
@@ -59,9 +94,46 @@ impl DefaultRender for TextLayer {
 }
 
 impl TextLayer {
+    /// Covers most scripts, falling back to whatever the system has.
+    pub const DEFAULT_FONTS: &'static str = "Arial, 'Noto Sans', 'Noto Sans Arabic', \
+        'Noto Sans Devanagari', 'Noto Sans SC', 'Noto Sans JP', sans-serif";
+
+    /// Sets the CSS font family list to render text with. The browser falls back through it glyph
+    /// by glyph, so order it by preference for the current language (e.g. Japanese fonts before
+    /// Chinese fonts for Japanese, since they share code points). Changing it rerenders all text.
+    pub fn set_fonts(&mut self, fonts: &'static str) {
+        if fonts != self.fonts {
+            self.fonts = fonts;
+            self.buffers.clear();
+        }
+    }
+
+    /// Sets whether [`TextAnchor::Start`] and [`TextAnchor::End`] are mirrored, for right-to-left
+    /// languages.
+    pub fn set_right_to_left(&mut self, right_to_left: bool) {
+        self.right_to_left = right_to_left;
+    }
+
+    /// See [`TextLayer::set_right_to_left`].
+    pub fn is_right_to_left(&self) -> bool {
+        self.right_to_left
+    }
+
     /// Draws `text` centered at `center` with a `scale` and a `color`. TODO `scale`'s units need
     /// to be more precisely defined.
     pub fn draw(&mut self, text: &str, center: Vec2, scale: f32, color: [u8; 4]) {
+        self.draw_anchored(text, center, TextAnchor::Center, scale, color);
+    }
+
+    /// Like [`TextLayer::draw`], but draws the `anchor` point of `text` at `position`.
+    pub fn draw_anchored(
+        &mut self,
+        text: &str,
+        position: Vec2,
+        anchor: TextAnchor,
+        scale: f32,
+        color: [u8; 4],
+    ) {
         if text.is_empty() {
             return;
         }
@@ -85,7 +157,11 @@ impl TextLayer {
             })
             .or_insert_with(|| ((text.to_owned(), color), Default::default()));
 
-        entry.draws.push(Draw { center, scale });
+        entry.draws.push(Draw {
+            position,
+            anchor,
+            scale,
+        });
     }
 }
 
@@ -96,7 +172,7 @@ impl Layer for TextLayer {
         self.buffers.retain(|id, entry| {
             entry.texture.get_or_insert_with(|| {
                 // Generate textures here to avoid pipeline stall if done during rendering.
-                Texture::from_text(renderer, &id.0, id.1)
+                Texture::from_text_with_fonts(renderer, &id.0, id.1, self.fonts)
             });
 
             // Remove textures that haven't been used in 255 (u8::MAX) frames.
@@ -124,6 +200,7 @@ impl RenderLayer<&Camera2d> for TextLayer {
 
         if let Some(shader) = self.shader.bind(renderer) {
             let binding = self.geometry.bind(renderer);
+            let right_to_left = self.right_to_left;
 
             for buffers in self.buffers.values_mut() {
                 if buffers.draws.is_empty() {
@@ -133,15 +210,21 @@ impl RenderLayer<&Camera2d> for TextLayer {
                 // Shouldn't panic because texture was initialized in pre_render.
                 let texture = buffers.texture.as_ref().unwrap();
                 let texture_aspect = texture.aspect();
+                // Textures grow to fit tall glyphs, without shrinking the rest of the text.
+                let texture_scale = texture.dimensions().y as f32 / Texture::TEXT_HEIGHT as f32;
                 shader.uniform("uSampler", texture);
 
                 // TODO could draw multiple in a single draw call.
-                for Draw { center, scale } in buffers.draws.drain(..) {
-                    let model = Mat3::from_scale_angle_translation(
-                        vec2(scale * texture_aspect, scale),
-                        0.0,
-                        center,
-                    );
+                for Draw {
+                    position,
+                    anchor,
+                    scale,
+                } in buffers.draws.drain(..)
+                {
+                    let scale = scale * texture_scale;
+                    let width = scale * texture_aspect;
+                    let center = position + vec2(anchor.center_offset(width, right_to_left), 0.0);
+                    let model = Mat3::from_scale_angle_translation(vec2(width, scale), 0.0, center);
                     // Only drawing 1 at a time so we can premultiply the model and view matrix.
                     shader.uniform("uModelView", &(camera.view_matrix * model));
                     binding.draw();
@@ -150,7 +150,7 @@ impl Camera3d {
     }
 
     /// Projects from world space to ndc space.
-    pub(crate) fn world_to_ndc(&self, pos: Vec3) -> Vec3 {
+    pub fn world_to_ndc(&self, pos: Vec3) -> Vec3 {
         self.vp_matrix.project_point3(pos)
     }
 }
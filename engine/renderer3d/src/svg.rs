@@ -116,8 +116,7 @@ impl Model {
             })
     }
 
-    /// Creates an SVG data URL.
-    /// TODO: take camera info as parameter.
+    /// Creates an SVG data URL of the [`Model`] in red, viewed diagonally from above.
     pub fn svg(&self) -> String {
         let camera = Camera3d::looking_at(
             Vec3::ONE.normalize(),
@@ -130,6 +129,12 @@ impl Model {
             .normal()
             .abs_diff_eq(-Vec3::ONE.normalize(), f32::EPSILON));
 
+        self.svg_with_camera(&camera, Vec3::X)
+    }
+
+    /// Creates an SVG data URL of the [`Model`] as seen by `camera`, which must contain it. The
+    /// `color` (RGB from 0 to 1) is shaded by lighting from above.
+    pub fn svg_with_camera(&self, camera: &Camera3d, color: Vec3) -> String {
         let mut triangles: Vec<_> = self
             .triangles()
             .map(|t| {
@@ -155,7 +160,7 @@ impl Model {
         let mut edges = HashMap::<Edge, EdgeData>::new();
 
         for triangle in &triangles {
-            let visible = triangle.visible(&camera);
+            let visible = triangle.visible(camera);
 
             for edge in triangle.iter_edges() {
                 let edge_data = edges.entry(edge).or_insert_with(|| EdgeData {
@@ -174,17 +179,18 @@ impl Model {
 
         for triangle in &triangles {
             // Cull backfacing triangles.
-            if !triangle.visible(&camera) {
+            if !triangle.visible(camera) {
                 continue;
             }
 
             // Color triangle with diffuse and ambient lighting.
             const LIGHT: Vec3 = Vec3::Y;
             let light = triangle.normal.dot(LIGHT).max(0.0) * 0.5 + 0.5;
+            let [r, g, b] = (color * light * u8::MAX as f32).to_array().map(|c| c as u8);
             write!(
                 &mut svg,
                 r#"<path fill="{0}" stroke="{0}" stroke-width="0.015" d="M "#,
-                renderer::rgba_array_to_css([(light * u8::MAX as f32) as u8, 0, 0, 255])
+                renderer::rgba_array_to_css([r, g, b, 255])
             )
             .unwrap();
 
@@ -133,6 +133,7 @@ impl Database {
                         game_id_score_type: GameIdScoreType {
                             game_id,
                             score_type,
+                            class: None,
                         },
                         alias: score.alias.clone(),
                         score: score.score,
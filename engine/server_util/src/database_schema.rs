@@ -10,13 +10,14 @@ use core_protocol::id::{
 use core_protocol::metrics::{
     ContinuousExtremaMetric, DiscreteMetric, HistogramMetric, Metric, RatioMetric,
 };
-use core_protocol::name::{PlayerAlias, Referrer};
+use core_protocol::name::{ClassName, PlayerAlias, Referrer};
 use core_protocol::serde_util::StrVisitor;
 use core_protocol::UnixTime;
 use derive_more::Add;
 use serde::de::DeserializeOwned;
 use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use std::iter::Sum;
+use std::str::FromStr;
 use variant_count::VariantCount;
 
 /// The type of leaderboard score, for a particular game.
@@ -36,11 +37,13 @@ pub enum ScoreType {
     TeamDay = 5,
 }
 
-/// The type of leaderboard score, for any game. Serialized as "GameId/ScoreType".
+/// The type of leaderboard score, for any game. Serialized as "GameId/ScoreType", or
+/// "GameId/ScoreType/ClassName" for the leaderboard of a particular class.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct GameIdScoreType {
     pub game_id: GameId,
     pub score_type: ScoreType,
+    pub class: Option<ClassName>,
 }
 
 impl Serialize for GameIdScoreType {
@@ -51,11 +54,16 @@ impl Serialize for GameIdScoreType {
         let av_game_id: AttributeValue = serde_dynamo::to_attribute_value(self.game_id).unwrap();
         let av_game_score_type: AttributeValue =
             serde_dynamo::to_attribute_value(self.score_type).unwrap();
-        serializer.serialize_str(&format!(
+        let mut s = format!(
             "{}/{}",
             av_game_id.as_s().unwrap(),
             av_game_score_type.as_s().unwrap()
-        ))
+        );
+        if let Some(class) = self.class {
+            s.push('/');
+            s.push_str(class.as_str());
+        }
+        serializer.serialize_str(&s)
     }
 }
 
@@ -66,7 +74,12 @@ impl<'de> Deserialize<'de> for GameIdScoreType {
     {
         deserializer.deserialize_str(StrVisitor).and_then(|s| {
             let mut split = s.splitn(2, '/');
-            if let Some((s_game_id, s_game_score_type)) = split.next().zip(split.next()) {
+            if let Some((s_game_id, s_rest)) = split.next().zip(split.next()) {
+                // Score types contain a slash of their own (e.g. "player/day").
+                let (s_game_score_type, s_class) = match s_rest.match_indices('/').nth(1) {
+                    Some((i, _)) => (&s_rest[..i], Some(&s_rest[i + 1..])),
+                    None => (s_rest, None),
+                };
                 let game_id_opt =
                     serde_dynamo::from_attribute_value(AttributeValue::S(String::from(s_game_id)))
                         .ok();
@@ -74,12 +87,17 @@ impl<'de> Deserialize<'de> for GameIdScoreType {
                     String::from(s_game_score_type),
                 ))
                 .ok();
+                let class = match s_class.map(ClassName::from_str).transpose() {
+                    Ok(class) => class,
+                    Err(_) => return Err(de::Error::custom("class name too long")),
+                };
                 return if let Some((game_id, game_score_type)) =
                     game_id_opt.zip(game_score_type_opt)
                 {
                     Ok(Self {
                         game_id,
                         score_type: game_score_type,
+                        class,
                     })
                 } else {
                     Err(de::Error::custom("parse error"))
@@ -123,11 +141,17 @@ pub struct ScoreItem {
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SessionItem {
+    /// Bit set of achievements unlocked during the session.
+    #[serde(default)]
+    pub achievements: u32,
     pub alias: PlayerAlias,
     /// Hash key.
     pub arena_id: ArenaId,
     #[serde(default)]
     pub cohort_id: CohortId,
+    /// Commendations received from other players.
+    #[serde(default)]
+    pub commendations: u32,
     pub date_created: UnixTime,
     pub date_previous: Option<UnixTime>,
     pub date_renewed: UnixTime,
@@ -136,6 +160,9 @@ pub struct SessionItem {
     pub game_id: GameId,
     pub player_id: PlayerId,
     pub plays: u32,
+    /// Provider the player signed in with, if any.
+    #[serde(default)]
+    pub login_type: Option<LoginType>,
     pub previous_id: Option<SessionId>,
     pub referrer: Option<Referrer>,
     pub user_agent_id: Option<UserAgentId>,
@@ -145,6 +172,12 @@ pub struct SessionItem {
     pub server_id: ServerId,
     /// Range key.
     pub session_id: SessionId,
+    /// Days in a row the player met the game's daily goal, ending on `streak_day`.
+    #[serde(default)]
+    pub streak: u32,
+    /// Days since the unix epoch (in UTC) when the streak was last extended.
+    #[serde(default)]
+    pub streak_day: u32,
 }
 
 #[derive(Clone, Debug, Default, Add, Deserialize, Serialize)]
@@ -422,6 +455,9 @@ impl Serialize for GameIdMetricFilter {
                     ser(user_agent_id)
                         .ok_or_else(|| ser::Error::custom("failed to serialize user agent id"))?,
                 ),
+                MetricFilter::VariantId(variant_id) => {
+                    format!("{}/variant_id/{}", game_id_string, variant_id)
+                }
             },
             None => game_id_string,
         };
@@ -470,6 +506,11 @@ impl<'de> Deserialize<'de> for GameIdMetricFilter {
                     "user_agent_id" => MetricFilter::UserAgentId(
                         de(filter_value).ok_or_else(|| de::Error::custom("invalid user agent"))?,
                     ),
+                    "variant_id" => MetricFilter::VariantId(
+                        filter_value
+                            .parse()
+                            .map_err(|_| de::Error::custom("invalid variant id"))?,
+                    ),
                     _ => return Err(de::Error::custom("invalid filter")),
                 });
             }
@@ -498,7 +539,37 @@ pub struct UserItem {
 
 #[derive(Serialize, Deserialize)]
 pub struct LoginItem {
+    /// Hash key.
     pub login_type: LoginType,
+    /// Range key.
     pub id: String,
     pub user_id: UserId,
+    /// Most recent session, which is resumed upon signing in (e.g. on a new device).
+    #[serde(default)]
+    pub arena_id: Option<ArenaId>,
+    #[serde(default)]
+    pub session_id: Option<SessionId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database_schema::{GameIdScoreType, ScoreType};
+    use aws_sdk_dynamodb::model::AttributeValue;
+    use core_protocol::id::GameId;
+    use core_protocol::name::ClassName;
+    use std::str::FromStr;
+
+    #[test]
+    fn game_id_score_type() {
+        for class in [None, Some(ClassName::from_str("Seawolf").unwrap())] {
+            let game_id_score_type = GameIdScoreType {
+                game_id: GameId::Mk48,
+                score_type: ScoreType::PlayerDay,
+                class,
+            };
+            let av: AttributeValue = serde_dynamo::to_attribute_value(game_id_score_type).unwrap();
+            let de: GameIdScoreType = serde_dynamo::from_attribute_value(av).unwrap();
+            assert_eq!(de, game_id_score_type);
+        }
+    }
 }
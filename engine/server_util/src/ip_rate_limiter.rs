@@ -55,6 +55,30 @@ impl IpRateLimiter {
         should_limit_rate
     }
 
+    /// Blocks the ip address for a duration (e.g. because it was banned), regardless of the
+    /// properties of the rate limit.
+    pub fn ban(&mut self, ip: IpAddr, duration: Duration) {
+        if let Some(until) = Instant::now().checked_add(duration) {
+            self.usage.insert(
+                ip,
+                RateLimiterState {
+                    until,
+                    burst_used: Units::MAX,
+                },
+            );
+        }
+    }
+
+    /// Gets the properties of the rate limit.
+    pub fn props(&self) -> &RateLimiterProps {
+        &self.props
+    }
+
+    /// Changes the properties of the rate limit, without forgetting usage.
+    pub fn set_props(&mut self, props: RateLimiterProps) {
+        self.props = props;
+    }
+
     /// Clean up old items. Called automatically, not it is not necessary to call manually.
     pub fn prune(&mut self) {
         let now = Instant::now();
@@ -131,4 +155,22 @@ pub mod test {
         assert!(!limiter.should_limit_rate(ip_one));
         assert_eq!(limiter.len(), 1);
     }
+
+    #[test]
+    pub fn ban() {
+        let ip_one = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let ip_two = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+        let mut limiter = IpRateLimiter::new(Duration::from_millis(10), 3);
+
+        limiter.ban(ip_one, Duration::from_millis(100));
+        assert!(limiter.should_limit_rate(ip_one));
+        assert!(!limiter.should_limit_rate(ip_two));
+
+        limiter.prune();
+        assert!(limiter.should_limit_rate(ip_one));
+
+        std::thread::sleep(Duration::from_millis(150));
+
+        assert!(!limiter.should_limit_rate(ip_one));
+    }
 }
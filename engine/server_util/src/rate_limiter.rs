@@ -4,6 +4,7 @@
 use std::time::{Duration, Instant};
 
 /// A rate limiter that may have unique properties.
+#[derive(Debug)]
 pub struct RateLimiter {
     props: RateLimiterProps,
     state: RateLimiterState,
@@ -15,12 +16,14 @@ pub struct RateLimiter {
 pub type Units = u32;
 
 /// The state of a rate limiter.
+#[derive(Debug)]
 pub struct RateLimiterState {
     pub(crate) until: Instant,
     pub(crate) burst_used: Units,
 }
 
 /// The (sharable) properties of a rate limiter.
+#[derive(Clone, Debug)]
 pub struct RateLimiterProps {
     rate_limit: Duration,
     burst: Units,
@@ -109,6 +112,16 @@ impl RateLimiterProps {
             burst: 0,
         }
     }
+
+    /// Minimum time between actions, after the burst is used.
+    pub fn rate_limit(&self) -> Duration {
+        self.rate_limit
+    }
+
+    /// Actions allowed in quick succession.
+    pub fn burst(&self) -> Units {
+        self.burst
+    }
 }
 
 impl RateLimiter {
@@ -43,7 +43,7 @@ pub fn context_menu(props: &ContextMenuProps) -> Html {
     );
 
     html! {
-        <div {style}>
+        <div {style} role="menu">
             {props.children.clone()}
         </div>
     }
@@ -87,7 +87,7 @@ pub fn context_menu_button(props: &ContextMenuButtonProps) -> Html {
     });
 
     html! {
-        <button {onclick} {class}>
+        <button {onclick} {class} role="menuitem">
             {props.children.clone()}
         </button>
     }
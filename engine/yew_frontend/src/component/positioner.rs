@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::theme::use_theme;
 use std::fmt::{Display, Formatter, Write};
 use stylist::yew::styled_component;
 use yew::virtual_dom::AttrValue;
@@ -39,6 +40,23 @@ impl Position {
     pub fn default_text_align(&self) -> Align {
         self.horizontal().default_text_align()
     }
+
+    /// Swaps left and right, if `right_to_left`, so that the UI is mirrored for right-to-left
+    /// languages.
+    pub fn directional(self, right_to_left: bool) -> Self {
+        if !right_to_left {
+            return self;
+        }
+        match self {
+            Self::BottomLeft { margin } => Self::BottomRight { margin },
+            Self::BottomRight { margin } => Self::BottomLeft { margin },
+            Self::CenterLeft { margin } => Self::CenterRight { margin },
+            Self::CenterRight { margin } => Self::CenterLeft { margin },
+            Self::TopLeft { margin } => Self::TopRight { margin },
+            Self::TopRight { margin } => Self::TopLeft { margin },
+            Self::BottomMiddle { .. } | Self::Center | Self::TopMiddle { .. } => self,
+        }
+    }
 }
 
 impl Display for Position {
@@ -94,6 +112,15 @@ pub enum Align {
 }
 
 impl Align {
+    /// Like [`Position::directional`].
+    pub fn directional(self, right_to_left: bool) -> Self {
+        match self {
+            Self::Left if right_to_left => Self::Right,
+            Self::Right if right_to_left => Self::Left,
+            _ => self,
+        }
+    }
+
     pub fn as_css(self) -> &'static str {
         match self {
             Align::Left => "text-align: left;",
@@ -174,7 +201,9 @@ impl Position {
 
 #[styled_component(Positioner)]
 pub fn positioner(props: &PositionerProps) -> Html {
-    let mut style = format!("{:#}", props.position);
+    let right_to_left = use_theme().right_to_left;
+    let position = props.position.directional(right_to_left);
+    let mut style = format!("{:#}", position);
 
     if let Some(min_width) = props.min_width.as_ref() {
         write!(&mut style, "min-width: {};", min_width).unwrap();
@@ -190,7 +219,8 @@ pub fn positioner(props: &PositionerProps) -> Html {
 
     style += props
         .align
-        .unwrap_or(props.position.default_text_align())
+        .map(|align| align.directional(right_to_left))
+        .unwrap_or(position.default_text_align())
         .as_css();
 
     /*
@@ -2,9 +2,9 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::component::positioner::Position;
-use crate::frontend::use_ctw;
+use crate::theme::use_theme;
 use stylist::yew::styled_component;
-use web_sys::MouseEvent;
+use web_sys::{KeyboardEvent, MouseEvent};
 use yew::virtual_dom::AttrValue;
 use yew::{classes, html, use_state, Callback, Children, Html, Properties};
 use yew_icons::{Icon, IconId};
@@ -75,7 +75,7 @@ pub fn section(props: &SectionProps) -> Html {
         *open_state
     };
 
-    let onclick = props.closable.then(|| {
+    let toggle = props.closable.then(|| {
         if let Some(on_open_changed) = props.on_open_changed.clone() {
             Callback::from(move |_| {
                 let _ = on_open_changed.emit(!open);
@@ -84,6 +84,18 @@ pub fn section(props: &SectionProps) -> Html {
             Callback::from(move |_| open_state.set(!open))
         }
     });
+    let onclick = toggle
+        .as_ref()
+        .map(|toggle| toggle.reform(|_: MouseEvent| ()));
+    // Allow toggling with the keyboard, like a button.
+    let onkeydown = toggle.map(|toggle| {
+        Callback::from(move |event: KeyboardEvent| {
+            if matches!(event.key().as_str(), "Enter" | " ") {
+                event.prevent_default();
+                toggle.emit(());
+            }
+        })
+    });
 
     let h2_css_class = css!(
         r#"
@@ -127,14 +139,16 @@ pub fn section(props: &SectionProps) -> Html {
         "#
     );
 
+    let theme = use_theme();
     let high_contrast_style = css!(
         r#"
-        background-color: #00000035;
+        background-color: ${background};
         padding: 0.5rem;
         border-radius: 0.5rem;
-        "#
+        "#,
+        background = theme.panel_background()
     );
-    let high_contrast = use_ctw().setting_cache.high_contrast;
+    let high_contrast = theme.is_high_contrast();
 
     const ICON_WIDTH: &'static str = "1.5rem";
     const ICON_HEIGHT: &'static str = "1.2rem";
@@ -145,24 +159,28 @@ pub fn section(props: &SectionProps) -> Html {
     }
     if let Some(position) = props.position {
         use std::fmt::Write;
-        write!(&mut style, "{}", position).unwrap();
+        write!(&mut style, "{}", position.directional(theme.right_to_left)).unwrap();
     }
 
     html! {
         <>
-            <div id={props.id.clone()} {style} class={high_contrast.then_some(high_contrast_style)}>
+            <div id={props.id.clone()} {style} class={high_contrast.then_some(high_contrast_style)} role="region" aria-label={props.name.clone()}>
                 <h2
                     class={classes!(h2_css_class, onclick.is_some().then_some(h2_clickable_css_class))}
+                    role={onclick.is_some().then_some("button")}
+                    tabindex={onclick.is_some().then_some("0")}
+                    aria-expanded={onclick.is_some().then(|| open.to_string())}
                     {onclick}
+                    {onkeydown}
                     >
                     if let Some(maybe_callback) = props.left_arrow.unpack(open) {
-                        <span class={classes!(span_css_class.clone(), maybe_callback.is_none().then(|| reserved_style.clone()))} onclick={maybe_callback}>
+                        <span class={classes!(span_css_class.clone(), maybe_callback.is_none().then(|| reserved_style.clone()))} onclick={maybe_callback} role="button" aria-label="Previous">
                             <Icon icon_id={IconId::FontAwesomeSolidSquareCaretLeft} width={ICON_WIDTH.to_string()} height={ICON_HEIGHT.to_string()}/>
                         </span>
                     }
                     {&props.name}
                     if let Some(maybe_callback) = props.right_arrow.unpack(open) {
-                        <span class={classes!(span_css_class, maybe_callback.is_none().then_some(reserved_style))} onclick={maybe_callback}>
+                        <span class={classes!(span_css_class, maybe_callback.is_none().then_some(reserved_style))} onclick={maybe_callback} role="button" aria-label="Next">
                             <Icon icon_id={IconId::FontAwesomeSolidSquareCaretRight} width={ICON_WIDTH.to_string()} height={ICON_HEIGHT.to_string()}/>
                         </span>
                     }
@@ -30,6 +30,6 @@ pub fn x_button(props: &XButtonProps) -> Html {
     "#
     );
     html! {
-        <button onclick={props.onclick.clone()} {class}>{"X"}</button>
+        <button onclick={props.onclick.clone()} {class} aria-label="Close">{"X"}</button>
     }
 }
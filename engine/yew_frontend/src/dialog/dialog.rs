@@ -6,8 +6,10 @@ use crate::component::positioner::Align;
 use crate::component::route_link::RouteLink;
 use crate::component::x_button::XButton;
 use crate::frontend::use_ctw;
+use crate::theme::use_theme;
 use stylist::yew::styled_component;
-use web_sys::window;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlElement};
 use yew::prelude::*;
 use yew::virtual_dom::AttrValue;
 use yew_router::hooks::use_navigator;
@@ -24,9 +26,12 @@ pub struct DialogProps {
 
 #[styled_component(Dialog)]
 pub fn dialog(props: &DialogProps) -> Html {
+    let theme = use_theme();
+
     let modal_style = css!(
         r#"
-        background-color: #174479;
+        background-color: ${background};
+        border: ${border};
         border-radius: 0.5em;
         box-shadow: 5px 5px 5px #00000020;
         color: white;
@@ -37,7 +42,9 @@ pub fn dialog(props: &DialogProps) -> Html {
         position: absolute;
         text-align: center;
         user-select: text;
-    "#
+    "#,
+        background = theme.dialog_background(),
+        border = theme.dialog_border()
     );
 
     let header_style = css!(
@@ -76,7 +83,7 @@ pub fn dialog(props: &DialogProps) -> Html {
 
     let link_style = css!(
         r#"
-        background-color: #174479;
+        background-color: ${background};
         display: flex;
         align-items: center;
         justify-content: center;
@@ -88,19 +95,21 @@ pub fn dialog(props: &DialogProps) -> Html {
         :hover {
             filter: brightness(0.9);
         }
-        "#
+        "#,
+        background = theme.dialog_background()
     );
 
     let link_selected_style = css!(
         r#"
-        background-color: #174479;
+        background-color: ${background};
         filter: none;
         cursor: default;
 
         :hover {
             filter: none;
         }
-        "#
+        "#,
+        background = theme.dialog_background()
     );
 
     let onclick = {
@@ -111,6 +120,40 @@ pub fn dialog(props: &DialogProps) -> Html {
         })
     };
 
+    // Move focus into the dialog, so screen readers announce it, and restore focus on close.
+    let modal_ref = use_node_ref();
+    {
+        let modal_ref = modal_ref.clone();
+        use_effect_with_deps(
+            move |_| {
+                let previous = window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.active_element())
+                    .and_then(|e| e.dyn_into::<HtmlElement>().ok());
+                if let Some(modal) = modal_ref.cast::<HtmlElement>() {
+                    let _ = modal.focus();
+                }
+                move || {
+                    if let Some(previous) = previous {
+                        let _ = previous.focus();
+                    }
+                }
+            },
+            (),
+        );
+    }
+
+    let onkeydown = {
+        let navigator = use_navigator().unwrap();
+
+        Callback::from(move |event: KeyboardEvent| {
+            if event.key() == "Escape" {
+                event.stop_propagation();
+                navigator.push(&AnyRoute::new("/"));
+            }
+        })
+    };
+
     let routes = use_ctw().routes;
     let pathname = window()
         .unwrap()
@@ -130,18 +173,27 @@ pub fn dialog(props: &DialogProps) -> Html {
 
     html! {
         <Curtain onclick={onclick.clone()}>
-            <div onclick={Callback::from(|e: MouseEvent| e.stop_propagation())} class={modal_style}>
+            <div
+                onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}
+                {onkeydown}
+                class={modal_style}
+                ref={modal_ref}
+                role="dialog"
+                aria-modal="true"
+                aria-labelledby="dialog_title"
+                tabindex="-1"
+            >
                 <div class={header_style}>
-                    <h2>{props.title.clone()}</h2>
+                    <h2 id="dialog_title">{props.title.clone()}</h2>
                 </div>
                 <div class={content_style} style={props.align.as_css()}>
                     {props.children.clone()}
                 </div>
-                <div class={footer_style}>
+                <nav class={footer_style}>
                     {routes.into_iter().map(|route| html_nested!{
                         <RouteLink<AnyRoute> route={AnyRoute::new(route)} class={classes!(link_style.clone(), (pathname.starts_with(route)).then(|| link_selected_style.clone()))}>{route_to_title(route)}</RouteLink<AnyRoute>>
                     }).collect::<Html>()}
-                </div>
+                </nav>
                 <div style="position: absolute; top: 0.5rem; right: 0.5em;">
                     <XButton {onclick}/>
                 </div>
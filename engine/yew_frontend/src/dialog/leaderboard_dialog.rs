@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::dialog::dialog::Dialog;
+use crate::event::event_target;
+use crate::frontend::{use_core_state, use_leaderboard_request_callback};
+use crate::translation::{use_translation, Translation};
+use core_protocol::id::PeriodId;
+use core_protocol::name::ClassName;
+use core_protocol::rpc::LeaderboardRequest;
+use gloo::timers::callback::Interval;
+use std::str::FromStr;
+use stylist::yew::styled_component;
+use web_sys::{Event, HtmlSelectElement};
+use yew::{
+    classes, html, html_nested, use_effect_with_deps, use_state_eq, AttrValue, Callback, Html,
+    Properties,
+};
+
+#[derive(PartialEq, Properties)]
+pub struct LeaderboardDialogProps {
+    /// Classes with their own leaderboards, and their labels, in the order to list them.
+    #[prop_or_default]
+    pub classes: Vec<(ClassName, AttrValue)>,
+}
+
+/// Class leaderboards are requested this often while shown, since they may not be loaded at first.
+const CLASS_REFRESH_MILLIS: u32 = 10_000;
+
+/// Lets players browse the daily, weekly, and all-time leaderboards, both overall and for each
+/// class.
+#[styled_component(LeaderboardDialog)]
+pub fn leaderboard_dialog(props: &LeaderboardDialogProps) -> Html {
+    let tabs_style = css!(
+        r#"
+        display: flex;
+        gap: 0.5rem;
+        margin-bottom: 1rem;
+        "#
+    );
+
+    let tab_style = css!(
+        r#"
+        background-color: #00000025;
+        border: 0;
+        border-radius: 0.25rem;
+        color: white;
+        cursor: pointer;
+        padding: 0.5rem 1rem;
+        "#
+    );
+
+    let selected_tab_style = css!(
+        r#"
+        background-color: #0075ff;
+        "#
+    );
+
+    let table_style = css!(
+        r#"
+        border-spacing: 1rem 0.25rem;
+
+        td:first-child, td:last-child {
+            text-align: right;
+        }
+        "#
+    );
+
+    let t = use_translation();
+    let core_state = use_core_state();
+    let leaderboard_request_callback = use_leaderboard_request_callback();
+    let period_id = use_state_eq(|| PeriodId::Daily);
+    // None for the overall leaderboard.
+    let class = use_state_eq(|| None::<ClassName>);
+
+    // Class leaderboards aren't sent unless requested.
+    use_effect_with_deps(
+        move |&(class, period_id)| {
+            let interval = class.map(|class| {
+                let request = move || {
+                    leaderboard_request_callback.emit(LeaderboardRequest::Class(class, period_id))
+                };
+                request();
+                Interval::new(CLASS_REFRESH_MILLIS, request)
+            });
+            || drop(interval)
+        },
+        (*class, *period_id),
+    );
+
+    let on_change_class = {
+        let class = class.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event_target(&event);
+            class.set(
+                ClassName::from_str(&select.value())
+                    .ok()
+                    .filter(|c| !c.is_empty()),
+            );
+        })
+    };
+
+    let leaderboard = if let Some(class) = *class {
+        core_state.class_leaderboard(class, *period_id)
+    } else {
+        Some(core_state.leaderboard(*period_id))
+    };
+
+    html! {
+        <Dialog title={t.leaderboard_label(*period_id)}>
+            <div class={tabs_style}>
+                {PeriodId::iter().map(|tab| {
+                    let onclick = {
+                        let period_id = period_id.clone();
+                        Callback::from(move |_| period_id.set(tab))
+                    };
+                    html_nested!{
+                        <button
+                            class={classes!(tab_style.clone(), (tab == *period_id).then(|| selected_tab_style.clone()))}
+                            {onclick}
+                        >{t.leaderboard_label(tab)}</button>
+                    }
+                }).collect::<Html>()}
+                if !props.classes.is_empty() {
+                    <select onchange={on_change_class} class={tab_style.clone()}>
+                        <option value="" selected={class.is_none()}>{"Overall"}</option>
+                        {props.classes.iter().map(|(option, label)| html_nested!{
+                            <option value={option.to_string()} selected={*class == Some(*option)}>{label.clone()}</option>
+                        }).collect::<Html>()}
+                    </select>
+                }
+            </div>
+            if let Some(leaderboard) = leaderboard.filter(|leaderboard| !leaderboard.is_empty()) {
+                <table class={table_style}>
+                    {leaderboard.iter().enumerate().map(|(i, dto)| html_nested!{
+                        <tr>
+                            <td>{format!("{}.", i + 1)}</td>
+                            <td>{dto.alias}</td>
+                            <td>{dto.score}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </table>
+            } else if leaderboard.is_some() {
+                <p>{"No scores yet."}</p>
+            } else {
+                <p>{"Loading..."}</p>
+            }
+        </Dialog>
+    }
+}
@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 pub mod dialog;
+pub mod leaderboard_dialog;
 pub mod licensing_dialog;
 pub mod privacy_dialog;
+pub mod server_browser_dialog;
+pub mod sign_in_dialog;
 pub mod terms_dialog;
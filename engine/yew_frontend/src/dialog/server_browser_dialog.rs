@@ -0,0 +1,160 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::dialog::dialog::Dialog;
+use crate::frontend::{measure_rtt, system_request, use_ctw};
+use client_util::js_util::domain_name_of;
+use core_protocol::dto::ServerDto;
+use core_protocol::id::ServerId;
+use core_protocol::owned::Owned;
+use core_protocol::rpc::SystemQuery;
+use gloo::timers::callback::Interval;
+use js_hooks::console_log;
+use std::collections::HashMap;
+use stylist::yew::styled_component;
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    html, html_nested, use_effect_with_deps, use_force_update, use_mut_ref, use_state, Html,
+};
+
+/// The server list is refreshed this often while shown, to keep player counts up to date.
+const REFRESH_MILLIS: u32 = 10_000;
+
+/// Lists all servers, along with their player counts and round-trip-times, and lets players join
+/// one manually instead of relying on automatic server selection, or switch to one mid-session
+/// without losing progress.
+#[styled_component(ServerBrowserDialog)]
+pub fn server_browser_dialog() -> Html {
+    let table_style = css!(
+        r#"
+        border-spacing: 1rem 0.25rem;
+        text-align: left;
+
+        td:not(:first-child), th:not(:first-child) {
+            text-align: right;
+        }
+        "#
+    );
+
+    let button_style = css!(
+        r#"
+        background-color: #0075ff;
+        border: 0;
+        border-radius: 0.25rem;
+        color: white;
+        cursor: pointer;
+        padding: 0.25rem 0.75rem;
+
+        :disabled {
+            background-color: #00000025;
+            cursor: default;
+        }
+        "#
+    );
+
+    let ctw = use_ctw();
+    let servers = use_state(|| None::<Owned<[ServerDto]>>);
+    // Measured asynchronously, so shared with the futures doing the measuring.
+    let rtts = use_mut_ref(HashMap::<ServerId, u32>::new);
+    let force_update = use_force_update();
+
+    {
+        let servers = servers.clone();
+        let rtts = rtts.clone();
+        use_effect_with_deps(
+            move |_| {
+                let refresh = move || {
+                    let servers = servers.clone();
+                    let rtts = rtts.clone();
+                    let force_update = force_update.clone();
+                    spawn_local(async move {
+                        let query = SystemQuery {
+                            server_id: None,
+                            region_id: None,
+                            invitation_id: None,
+                        };
+                        let (url, response) = match system_request(&query).await {
+                            Ok(result) => result,
+                            Err(e) => {
+                                console_log!("could not list servers: {}", e);
+                                return;
+                            }
+                        };
+                        servers.set(Some(response.servers.clone()));
+
+                        let host = url.host();
+                        for server in response.servers.iter() {
+                            let server_host = if host.starts_with("localhost") {
+                                host.clone()
+                            } else {
+                                format!("{}.{}", server.server_id.0, domain_name_of(&host))
+                            };
+                            let status_url =
+                                format!("{}//{}/status.json", url.protocol(), server_host);
+                            match measure_rtt(&status_url).await {
+                                Ok(rtt) => {
+                                    rtts.borrow_mut().insert(server.server_id, rtt as u32);
+                                }
+                                Err(_) => {
+                                    rtts.borrow_mut().remove(&server.server_id);
+                                }
+                            }
+                            force_update.force_update();
+                        }
+                    });
+                };
+                refresh();
+                let interval = Interval::new(REFRESH_MILLIS, refresh);
+                || drop(interval)
+            },
+            (),
+        );
+    }
+
+    let current_server_id = ctw.setting_cache.server_id;
+    let rtts = rtts.borrow();
+
+    html! {
+        <Dialog title={"Servers"}>
+            if let Some(servers) = (*servers).as_ref().filter(|servers| !servers.is_empty()) {
+                <table class={table_style}>
+                    <tr>
+                        <th>{"Server"}</th>
+                        <th>{"Players"}</th>
+                        <th>{"Ping"}</th>
+                        <th></th>
+                        <th></th>
+                    </tr>
+                    {servers.iter().map(|&ServerDto{server_id, region_id, player_count, ..}| {
+                        let current = current_server_id == Some(server_id);
+                        let on_join = ctw.set_server_id_callback.reform(move |_| Some(server_id));
+                        let on_transfer = ctw.transfer_server_id_callback.reform(move |_| server_id);
+                        html_nested!{
+                            <tr>
+                                <td>{format!("Server {server_id} - {}", region_id.as_human_readable_str())}</td>
+                                <td>{player_count}</td>
+                                <td>{rtts.get(&server_id).map(|rtt| format!("{rtt}ms")).unwrap_or_else(|| String::from("-"))}</td>
+                                <td>
+                                    <button class={button_style.clone()} disabled={current} onclick={on_join}>
+                                        {if current { "Joined" } else { "Join" }}
+                                    </button>
+                                </td>
+                                <td>
+                                    if !current {
+                                        <button class={button_style.clone()} onclick={on_transfer} title={"Continue playing there without losing progress"}>
+                                            {"Switch region"}
+                                        </button>
+                                    }
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()}
+                </table>
+            } else if servers.is_some() {
+                <p>{"No servers are available."}</p>
+            } else {
+                <p>{"Loading..."}</p>
+            }
+        </Dialog>
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::dialog::dialog::Dialog;
+use crate::frontend::use_core_state;
+use core_protocol::id::LoginType;
+use stylist::yew::styled_component;
+use yew::{html, html_nested, Html};
+
+/// Providers to choose from, and their names.
+const PROVIDERS: [(LoginType, &str); 3] = [
+    (LoginType::Discord, "Discord"),
+    (LoginType::Google, "Google"),
+    (LoginType::Apple, "Apple"),
+];
+
+/// Human readable name of a provider.
+pub fn login_type_name(login_type: LoginType) -> &'static str {
+    PROVIDERS
+        .iter()
+        .find(|&&(l, _)| l == login_type)
+        .map(|&(_, name)| name)
+        .unwrap()
+}
+
+/// Lets players sign in, so their progress survives clearing browser data, and follows them to
+/// other devices.
+#[styled_component(SignInDialog)]
+pub fn sign_in_dialog() -> Html {
+    let button_style = css!(
+        r#"
+        background-color: #0075ff;
+        border-radius: 0.25rem;
+        color: white;
+        display: block;
+        font-weight: bold;
+        margin: 0.5rem 0;
+        padding: 0.7rem;
+        text-align: center;
+        text-decoration: none;
+        "#
+    );
+
+    let login_type = use_core_state().login_type;
+
+    html! {
+        <Dialog title={"Sign In"}>
+            if let Some(login_type) = login_type {
+                <p>{format!("You are signed in with {}.", login_type_name(login_type))}</p>
+            } else {
+                <p>{"Sign in to keep your progress, even if you clear your browser data or switch devices."}</p>
+            }
+            <p>{"Signing in with Discord also shows a verified badge next to your name in chat."}</p>
+            {PROVIDERS.iter().map(|&(login_type, name)| html_nested!{
+                // The provider redirects back to the game, which then reconnects signed in.
+                <a class={button_style.clone()} href={format!("/oauth2/{}", login_type.as_str())}>
+                    {format!("Sign in with {}", name)}
+                </a>
+            }).collect::<Html>()}
+        </Dialog>
+    }
+}
@@ -10,7 +10,9 @@ use client_util::js_util::referrer;
 use client_util::setting::CommonSettings;
 use core_protocol::id::{GameId, ServerId};
 use core_protocol::name::Referrer;
-use core_protocol::rpc::{ChatRequest, PlayerRequest, SystemQuery, SystemResponse, TeamRequest};
+use core_protocol::rpc::{
+    ChatRequest, LeaderboardRequest, PlayerRequest, SystemQuery, SystemResponse, TeamRequest,
+};
 use js_hooks::console_log;
 use std::ops::Deref;
 use wasm_bindgen::{JsCast, JsValue};
@@ -58,6 +60,7 @@ pub struct Ctw {
     pub change_common_settings_callback:
         Callback<Box<dyn FnOnce(&mut CommonSettings, &mut BrowserStorages)>>,
     pub chat_request_callback: Callback<ChatRequest>,
+    pub leaderboard_request_callback: Callback<LeaderboardRequest>,
     pub player_request_callback: Callback<PlayerRequest>,
     pub raw_zoom_callback: Callback<f32>,
     pub recreate_renderer_callback: Callback<()>,
@@ -67,6 +70,8 @@ pub struct Ctw {
     /// A copy of the core state.
     pub state: WeakCoreState,
     pub team_request_callback: Callback<TeamRequest>,
+    /// Switches servers, keeping progress (unlike [`Self::set_server_id_callback`]).
+    pub transfer_server_id_callback: Callback<ServerId>,
     pub licenses: &'static [(&'static str, &'static [&'static str])],
 }
 
@@ -80,6 +85,11 @@ pub fn use_chat_request_callback() -> Callback<ChatRequest> {
     use_ctw().chat_request_callback
 }
 
+#[hook]
+pub fn use_leaderboard_request_callback() -> Callback<LeaderboardRequest> {
+    use_ctw().leaderboard_request_callback
+}
+
 #[hook]
 pub fn use_player_request_callback() -> Callback<PlayerRequest> {
     use_ctw().player_request_callback
@@ -220,30 +230,7 @@ impl SystemInfo {
             invitation_id,
         };
 
-        let query_string = serde_urlencoded::to_string(&query).unwrap();
-
-        let url = format!("/system.json?{}", query_string);
-
-        let mut opts = RequestInit::new();
-        opts.method("GET");
-        opts.mode(RequestMode::Cors);
-
-        let request =
-            Request::new_with_str_and_init(&url, &opts).map_err(|e| format!("{:?}", e))?;
-
-        let window = web_sys::window().unwrap();
-        let resp_value = JsFuture::from(window.fetch_with_request(&request))
-            .await
-            .map_err(|e| format!("{:?}", e))?;
-        let resp: Response = resp_value.dyn_into().map_err(|e| format!("{:?}", e))?;
-        let url = Url::new(&resp.url()).map_err(|e| format!("{:?}", e))?;
-        let json_promise = resp.text().map_err(|e| format!("{:?}", e))?;
-        let json: String = JsFuture::from(json_promise)
-            .await
-            .map_err(|e| format!("{:?}", e))?
-            .as_string()
-            .ok_or(String::from("JSON not string"))?;
-        let decoded: SystemResponse = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let (url, decoded) = system_request(&query).await?;
 
         Ok(Self {
             host: url.host(),
@@ -253,6 +240,57 @@ impl SystemInfo {
     }
 }
 
+/// Makes a system request, returning the (possibly redirected) URL that responded, along with the
+/// response.
+pub(crate) async fn system_request(query: &SystemQuery) -> Result<(Url, SystemResponse), String> {
+    let query_string = serde_urlencoded::to_string(query).unwrap();
+
+    let url = format!("/system.json?{}", query_string);
+
+    let mut opts = RequestInit::new();
+    opts.method("GET");
+    opts.mode(RequestMode::Cors);
+
+    let request = Request::new_with_str_and_init(&url, &opts).map_err(|e| format!("{:?}", e))?;
+
+    let window = web_sys::window().unwrap();
+    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let resp: Response = resp_value.dyn_into().map_err(|e| format!("{:?}", e))?;
+    let url = Url::new(&resp.url()).map_err(|e| format!("{:?}", e))?;
+    let json_promise = resp.text().map_err(|e| format!("{:?}", e))?;
+    let json: String = JsFuture::from(json_promise)
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .as_string()
+        .ok_or(String::from("JSON not string"))?;
+    let decoded: SystemResponse = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    Ok((url, decoded))
+}
+
+/// Measures the round-trip-time, in milliseconds, of a request to a server's status endpoint.
+pub(crate) async fn measure_rtt(url: &str) -> Result<f64, String> {
+    let mut opts = RequestInit::new();
+    opts.method("GET");
+    opts.mode(RequestMode::Cors);
+
+    let request = Request::new_with_str_and_init(url, &opts).map_err(|e| format!("{:?}", e))?;
+
+    let window = web_sys::window().unwrap();
+    let start = js_sys::Date::now();
+    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let end = js_sys::Date::now();
+    let resp: Response = resp_value.dyn_into().map_err(|e| format!("{:?}", e))?;
+    if resp.ok() {
+        Ok(end - start)
+    } else {
+        Err(format!("status {}", resp.status()))
+    }
+}
+
 impl<P: PartialEq> Frontend<P> for Yew<P> {
     fn set_ui_props(&self, props: P) {
         self.set_ui_props.emit(props);
@@ -16,6 +16,7 @@ pub mod event;
 pub mod frontend;
 mod keyboard;
 pub mod overlay;
+pub mod theme;
 pub mod translation;
 pub mod window;
 
@@ -27,6 +28,8 @@ use crate::error_tracer::ErrorTracer;
 use crate::frontend::{post_message, RewardedAd};
 use crate::overlay::fatal_error::FatalError;
 use crate::overlay::reconnecting::Reconnecting;
+use crate::theme::Theme;
+use crate::translation::{load_translations, Translation};
 use crate::window::event_listener::WindowEventListener;
 use client_util::browser_storage::BrowserStorages;
 use client_util::context::WeakCoreState;
@@ -37,7 +40,9 @@ use client_util::setting::CommonSettings;
 use client_util::setting::Settings;
 use core_protocol::id::{InvitationId, ServerId};
 use core_protocol::name::Referrer;
-use core_protocol::rpc::{AdType, ChatRequest, PlayerRequest, Request, TeamRequest};
+use core_protocol::rpc::{
+    AdType, ChatRequest, LeaderboardRequest, PlayerRequest, Request, TeamRequest,
+};
 use engine_macros::SmolRoutable;
 use frontend::{Ctw, Gctw, PropertiesWrapper, Yew};
 use gloo_render::{request_animation_frame, AnimationFrame};
@@ -45,7 +50,7 @@ use js_hooks::console_log;
 use keyboard::KeyboardEventsListener;
 use std::marker::PhantomData;
 use std::num::NonZeroU8;
-use stylist::{global_style, GlobalStyle};
+use stylist::GlobalStyle;
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::future_to_promise;
 use web_sys::{FocusEvent, KeyboardEvent, MessageEvent, MouseEvent, TouchEvent, WheelEvent};
@@ -77,7 +82,9 @@ struct App<
     _message_listener: WindowEventListener<MessageEvent>,
     _context_menu_inhibitor: WindowEventListener<MouseEvent>,
     _error_tracer: ErrorTracer,
-    _global_style: GlobalStyle,
+    /// Mounted based on [`Self::theme`].
+    global_style: GlobalStyle,
+    theme: Theme,
     _spooky: PhantomData<(UI, R)>,
 }
 
@@ -96,6 +103,16 @@ enum PendingInfrastructure<G: GameClient> {
 }
 
 impl<G: GameClient> PendingInfrastructure<G> {
+    fn common_settings(&self) -> Option<&CommonSettings> {
+        match self {
+            Self::Done(infrastructure) => Some(&infrastructure.context.common_settings),
+            Self::Pending {
+                common_settings, ..
+            } => Some(common_settings),
+            Self::Swapping => None,
+        }
+    }
+
     fn is_pending(&self) -> bool {
         matches!(self, Self::Pending { .. })
     }
@@ -149,6 +166,8 @@ enum AppMsg<G: GameClient> {
     /// Signals just the renderer should be recreated.
     RecreateRenderer,
     SetServerId(Option<ServerId>),
+    /// Switch servers, keeping progress.
+    TransferServerId(ServerId),
     #[allow(unused)]
     FatalError(String),
     Frame {
@@ -160,6 +179,7 @@ enum AppMsg<G: GameClient> {
     Mouse(MouseEvent),
     RawZoom(f32),
     SendChatRequest(ChatRequest),
+    SendLeaderboardRequest(LeaderboardRequest),
     SendPlayerRequest(PlayerRequest),
     SendTeamRequest(TeamRequest),
     SendUiEvent(G::UiEvent),
@@ -168,6 +188,8 @@ enum AppMsg<G: GameClient> {
     Touch(TouchEvent),
     /// Error trace.
     Trace(String),
+    /// Translation files finished loading.
+    TranslationsLoaded,
     VisibilityChange(Event),
     RequestRewardedAd,
     ConsumeRewardedAd,
@@ -213,6 +235,7 @@ where
         let browser_storages = BrowserStorages::new();
         let common_settings = CommonSettings::load(&browser_storages, CommonSettings::default());
         let settings = G::GameSettings::load(&browser_storages, G::GameSettings::default());
+        let theme = Theme::new(&common_settings);
 
         Self {
             context_menu: None,
@@ -261,29 +284,8 @@ where
                 true,
             ),
             _error_tracer: ErrorTracer::new(trace_callback),
-            _global_style: global_style!(
-                r#"
-                html {
-                    font-family: sans-serif;
-                    font-size: 1.5vmin;
-                    font-size: calc(7px + 0.8vmin);
-                }
-
-                body {
-                    color: white;
-                    margin: 0;
-                    overflow: hidden;
-                    padding: 0;
-                    touch-action: none;
-                    user-select: none;
-                }
-
-                a {
-                    color: white;
-                }
-            "#
-            )
-            .expect("failed to mount global style"),
+            global_style: theme.global_style(),
+            theme,
             _spooky: PhantomData,
         }
     }
@@ -312,6 +314,14 @@ where
                         );
                     }
                 }
+                if let Some(common_settings) = self.infrastructure.common_settings() {
+                    let theme = Theme::new(common_settings);
+                    if theme != self.theme {
+                        self.global_style.unregister();
+                        self.global_style = theme.global_style();
+                        self.theme = theme;
+                    }
+                }
                 // Just in case.
                 return true;
             }
@@ -404,6 +414,11 @@ where
                     infrastructure.choose_server_id(server_id);
                 }
             }
+            AppMsg::TransferServerId(server_id) => {
+                if let Some(infrastructure) = self.infrastructure.as_mut() {
+                    infrastructure.transfer_to_server_id(server_id);
+                }
+            }
             AppMsg::FatalError(e) => {
                 self.fatal_error = Some(e);
                 return true;
@@ -446,6 +461,11 @@ where
                     infrastructure.send_request(Request::Chat(request));
                 }
             }
+            AppMsg::SendLeaderboardRequest(request) => {
+                if let Some(infrastructure) = self.infrastructure.as_mut() {
+                    infrastructure.send_request(Request::Leaderboard(request));
+                }
+            }
             AppMsg::SetContextMenuProps(props) => {
                 self.context_menu = props;
                 return true;
@@ -492,6 +512,10 @@ where
                     infrastructure.trace(message);
                 }
             }
+            AppMsg::TranslationsLoaded => {
+                // Re-render with the loaded translations.
+                return true;
+            }
             AppMsg::VisibilityChange(event) => {
                 if let Some(infrastructure) = self.infrastructure.as_mut() {
                     infrastructure.visibility_change(event);
@@ -511,13 +535,11 @@ where
                         self.outbound_enabled = false;
                         return true;
                     }
-                    #[cfg(feature = "audio")]
                     "mute" => {
                         if let Some(infrastructure) = self.infrastructure.as_mut() {
                             infrastructure.context.audio.set_muted_by_ad(true);
                         }
                     }
-                    #[cfg(feature = "audio")]
                     "unmute" => {
                         if let Some(infrastructure) = self.infrastructure.as_mut() {
                             infrastructure.context.audio.set_muted_by_ad(false);
@@ -574,6 +596,7 @@ where
         let change_common_settings_callback = ctx.link().callback(AppMsg::ChangeCommonSettings);
         let change_settings_callback = ctx.link().callback(AppMsg::ChangeSettings);
         let chat_request_callback = ctx.link().callback(AppMsg::SendChatRequest);
+        let leaderboard_request_callback = ctx.link().callback(AppMsg::SendLeaderboardRequest);
         let player_request_callback = ctx.link().callback(AppMsg::SendPlayerRequest);
         let raw_zoom_callback = ctx.link().callback(AppMsg::RawZoom);
         let recreate_renderer_callback = ctx.link().callback(|_| AppMsg::RecreateCanvas);
@@ -581,6 +604,7 @@ where
         let send_ui_event_callback = ctx.link().callback(AppMsg::SendUiEvent);
         let set_context_menu_callback = ctx.link().callback(AppMsg::SetContextMenuProps);
         let team_request_callback = ctx.link().callback(AppMsg::SendTeamRequest);
+        let transfer_server_id_callback = ctx.link().callback(AppMsg::TransferServerId);
 
         // Combine game and engine routes, except those with path parameters.
         let routes = R::routes()
@@ -597,6 +621,7 @@ where
             chat_request_callback,
             change_common_settings_callback,
             game_id: G::GAME_ID,
+            leaderboard_request_callback,
             outbound_enabled: self.outbound_enabled,
             rewarded_ad: self.rewarded_ad.clone(),
             player_request_callback,
@@ -624,8 +649,15 @@ where
                 .map(|i| WeakCoreState::new(&i.context.state.core))
                 .unwrap_or_default(),
             team_request_callback,
+            transfer_server_id_callback,
         };
 
+        let outdated_message = self
+            .infrastructure
+            .as_ref()
+            .filter(|i| i.context.outdated())
+            .map(|_| context.setting_cache.language.connection_outdated_message());
+
         let game_context = Gctw {
             send_ui_event_callback,
             settings_cache: match &self.infrastructure {
@@ -645,31 +677,35 @@ where
             <BrowserRouter>
                 <ContextProvider<Ctw> {context}>
                     <ContextProvider<Gctw<G>> context={game_context}>
-                        if self.recreating_canvas != RecreatingCanvas::Started {
-                            <Canvas
-                                resolution_divisor={NonZeroU8::new(1).unwrap()}
-                                mouse_callback={ctx.link().callback(AppMsg::Mouse)}
-                                touch_callback={ctx.link().callback(AppMsg::Touch)}
-                                focus_callback={ctx.link().callback(AppMsg::MouseFocus)}
-                                wheel_callback={ctx.link().callback(AppMsg::Wheel)}
-                            />
-                        }
-                        if self.infrastructure.as_ref().map(|i| i.context.connection_lost()).unwrap_or_default() {
-                            <FatalError/>
-                        } else if let Some(message) = self.fatal_error.as_ref() {
-                            <FatalError message={message.to_owned()}/>
-                        } else {
-                            <>
-                                <UI props={self.ui_props.clone()}/>
-                                <Switch<Route> render={switch}/>
-                                if let Some(context_menu) = self.context_menu.as_ref() {
-                                    {context_menu.clone()}
-                                }
-                                if self.infrastructure.as_ref().map(|i| i.context.socket.is_reconnecting()).unwrap_or_default() {
-                                    <Reconnecting/>
-                                }
-                            </>
-                        }
+                        <ContextProvider<Theme> context={self.theme}>
+                            if self.recreating_canvas != RecreatingCanvas::Started {
+                                <Canvas
+                                    resolution_divisor={NonZeroU8::new(1).unwrap()}
+                                    mouse_callback={ctx.link().callback(AppMsg::Mouse)}
+                                    touch_callback={ctx.link().callback(AppMsg::Touch)}
+                                    focus_callback={ctx.link().callback(AppMsg::MouseFocus)}
+                                    wheel_callback={ctx.link().callback(AppMsg::Wheel)}
+                                />
+                            }
+                            if let Some(message) = outdated_message {
+                                <FatalError {message}/>
+                            } else if self.infrastructure.as_ref().map(|i| i.context.connection_lost()).unwrap_or_default() {
+                                <FatalError/>
+                            } else if let Some(message) = self.fatal_error.as_ref() {
+                                <FatalError message={message.to_owned()}/>
+                            } else {
+                                <>
+                                    <UI props={self.ui_props.clone()}/>
+                                    <Switch<Route> render={switch}/>
+                                    if let Some(context_menu) = self.context_menu.as_ref() {
+                                        {context_menu.clone()}
+                                    }
+                                    if self.infrastructure.as_ref().map(|i| i.context.socket.is_reconnecting()).unwrap_or_default() {
+                                        <Reconnecting/>
+                                    }
+                                </>
+                            }
+                        </ContextProvider<Theme>>
                     </ContextProvider<Gctw<G>>>
                 </ContextProvider<Ctw>>
             </BrowserRouter>
@@ -685,6 +721,13 @@ where
                 Ok(JsValue::NULL)
             });
         }
+        if let Some(common_settings) = self.infrastructure.common_settings() {
+            // Does nothing if the language's translations were already requested.
+            load_translations(
+                common_settings.language,
+                ctx.link().callback(|_| AppMsg::TranslationsLoaded),
+            );
+        }
         match self.recreating_canvas {
             RecreatingCanvas::None => {}
             RecreatingCanvas::Started => ctx.link().send_message(AppMsg::RecreateCanvasPart2),
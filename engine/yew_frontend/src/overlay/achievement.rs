@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::frontend::use_core_state;
+use crate::translation::{use_translation, Translation};
+use core_protocol::dto::Achievement;
+use gloo::timers::callback::Timeout;
+use stylist::yew::styled_component;
+use yew::{html, html_nested, use_effect_with_deps, use_state, Html, Properties};
+
+/// Announces newly unlocked achievements with brief toasts. Meant to be placed in the flow of
+/// another overlay, such as above the game's status.
+#[styled_component(AchievementOverlay)]
+pub fn achievement_overlay() -> Html {
+    let achievements = use_core_state().achievements.clone();
+
+    html! {
+        <div id="achievements">
+            {achievements.into_iter().map(|achievement| html_nested!{
+                <AchievementToast key={achievement as u8} {achievement}/>
+            }).collect::<Html>()}
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct AchievementToastProps {
+    achievement: Achievement,
+}
+
+#[styled_component(AchievementToast)]
+fn achievement_toast(props: &AchievementToastProps) -> Html {
+    let toast_style = css!(
+        r#"
+        background-color: #00000040;
+        border-radius: 0.5rem;
+        color: white;
+        margin: 0 auto 0.5rem auto;
+        padding: 0.5rem 1rem;
+        pointer-events: none;
+        text-align: center;
+        user-select: none;
+        "#
+    );
+
+    let visible = use_state(|| true);
+
+    {
+        let visible = visible.clone();
+        use_effect_with_deps(
+            |_| {
+                let timeout = Timeout::new(5000, move || visible.set(false));
+                || drop(timeout)
+            },
+            (),
+        );
+    }
+
+    let t = use_translation();
+
+    html! {
+        if *visible {
+            <div class={toast_style}>
+                <small>{t.achievement_unlocked_label()}</small>
+                <h3 style="margin: 0;">{t.achievement_label(props.achievement)}</h3>
+                <p style="margin: 0;">{t.achievement_hint(props.achievement)}</p>
+            </div>
+        }
+    }
+}
@@ -9,16 +9,21 @@ use crate::frontend::{
     use_chat_request_callback, use_core_state, use_ctw, use_player_request_callback,
     use_set_context_menu_callback,
 };
+use crate::theme::use_theme;
 use crate::translation::{use_translation, Translation};
 use crate::window::event_listener::WindowEventListener;
 use client_util::browser_storage::BrowserStorages;
 use client_util::setting::CommonSettings;
-use core_protocol::id::LanguageId;
+use core_protocol::dto::{Commendation, PlayerDto};
+use core_protocol::id::{LanguageId, PlayerId};
 use core_protocol::rpc::{ChatRequest, PlayerRequest};
 use js_sys::JsString;
+use std::num::NonZeroU32;
 use std::str::pattern::Pattern;
 use stylist::yew::styled_component;
-use web_sys::{window, HtmlInputElement, InputEvent, KeyboardEvent, MouseEvent};
+use web_sys::{
+    window, Event, HtmlInputElement, HtmlSelectElement, InputEvent, KeyboardEvent, MouseEvent,
+};
 use yew::{
     classes, html, html_nested, use_effect_with_deps, use_node_ref, use_state_eq, AttrValue,
     Callback, Html, Properties,
@@ -36,8 +41,18 @@ pub struct ChatProps {
     pub hints: Vec<(&'static str, Vec<&'static str>)>,
 }
 
+/// Who messages typed into the chat input are sent to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Recipient {
+    Everyone,
+    Team,
+    Player(PlayerId),
+}
+
 #[styled_component(ChatOverlay)]
 pub fn chat_overlay(props: &ChatProps) -> Html {
+    let theme = use_theme();
+
     let message_css_class = css!(
         r#"
         color: white;
@@ -47,7 +62,7 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
 		text-overflow: ellipsis;
 		word-break: normal;
 		user-select: text;
-		text-align: left;
+		text-align: start;
         "#
     );
 
@@ -82,6 +97,22 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
         "#
     );
 
+    let commendation_style = css!(
+        r#"
+        color: #fffd2a;
+        font-size: 0.8em;
+        user-select: none;
+        "#
+    );
+
+    let verified_style = css!(
+        r#"
+        color: #5865f2;
+        font-size: 0.8em;
+        user-select: none;
+        "#
+    );
+
     let mention_style = css!(
         r#"
         color: #cae3ec;
@@ -104,11 +135,14 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
         pointer-events: all;
         white-space: nowrap;
         margin-top: 0.25em;
-        background-color: #00000025;
+        background-color: ${background};
         border: 0;
         color: white;
+        text-align: ${text_align};
         width: 100%;
-        "#
+        "#,
+        background = theme.panel_background(),
+        text_align = if theme.right_to_left { "right" } else { "left" }
     );
 
     let ctw = use_ctw();
@@ -132,6 +166,26 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
     let t = use_translation();
     let input_ref = use_node_ref();
     let help_hint = use_state_eq::<Option<&'static str>, _>(|| None);
+    let recipient = use_state_eq(|| Recipient::Everyone);
+    let core_state = use_core_state();
+
+    // Other real players, by alias, who can be whispered to.
+    let mut whisperable: Vec<PlayerDto> = core_state
+        .only_players()
+        .values()
+        .filter(|p| Some(p.player_id) != core_state.player_id)
+        .cloned()
+        .collect();
+    whisperable.sort_unstable_by_key(|p| p.alias);
+
+    // Fall back to everyone if the recipient is gone.
+    let current_recipient = match *recipient {
+        Recipient::Team if core_state.team_id().is_none() => Recipient::Everyone,
+        Recipient::Player(player_id) if !whisperable.iter().any(|p| p.player_id == player_id) => {
+            Recipient::Everyone
+        }
+        recipient => recipient,
+    };
 
     let oninput = {
         let help_hint = help_hint.clone();
@@ -151,6 +205,7 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
     let onkeydown = {
         let help_hint = help_hint.clone();
         let chat_request_callback = ctw.chat_request_callback;
+        let whisperable = whisperable.clone();
 
         move |event: KeyboardEvent| {
             if event.key_code() != ENTER {
@@ -164,10 +219,23 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
             if message.is_empty() {
                 return;
             }
-            chat_request_callback.emit(ChatRequest::Send {
-                message,
-                whisper: event.shift_key(),
-            });
+            let request = if let Some((player_id, message)) = parse_whisper(&message, &whisperable)
+            {
+                ChatRequest::Whisper { player_id, message }
+            } else {
+                match current_recipient {
+                    Recipient::Everyone => ChatRequest::Send {
+                        message,
+                        whisper: event.shift_key(),
+                    },
+                    Recipient::Team => ChatRequest::Send {
+                        message,
+                        whisper: true,
+                    },
+                    Recipient::Player(player_id) => ChatRequest::Whisper { player_id, message },
+                }
+            };
+            chat_request_callback.emit(request);
             on_save_chat_message.emit(String::new());
             help_hint.set(None);
         }
@@ -212,7 +280,20 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
         );
     }
 
-    let core_state = use_core_state();
+    let onchange_recipient = {
+        let recipient = recipient.clone();
+        move |event: Event| {
+            let select: HtmlSelectElement = event_target(&event);
+            let value = select.value();
+            recipient.set(match value.as_str() {
+                "team" => Recipient::Team,
+                _ => value
+                    .parse::<NonZeroU32>()
+                    .map_or(Recipient::Everyone, |n| Recipient::Player(PlayerId(n))),
+            });
+        }
+    };
+
     let chat_request_callback = use_chat_request_callback();
     let player_request_callback = use_player_request_callback();
     let set_context_menu_callback = use_set_context_menu_callback();
@@ -237,17 +318,28 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
         };
 
         let is_me = dto.player_id == core_state.player_id;
+        let commendations = dto
+            .player_id
+            .and_then(|player_id| core_state.player_or_bot(player_id))
+            .map(|p| p.commendations)
+            .filter(|&n| n > 0);
         let oncontextmenu = if let Some(player_id) = dto.player_id.filter(|_| moderator || !is_me) {
             let team_id = core_state.player_or_bot(player_id).and_then(|p| p.team_id);
             let chat_request_callback = chat_request_callback.clone();
             let player_request_callback = player_request_callback.clone();
             let set_context_menu_callback = set_context_menu_callback.clone();
+            let recipient = recipient.clone();
 
             Some(move |e: MouseEvent| {
                 e.prevent_default();
                 e.stop_propagation();
                 let chat_request_callback = chat_request_callback.clone();
-                let player_request_callback = player_request_callback.clone();
+                let onclick_whisper = {
+                    let recipient = recipient.clone();
+                    Callback::from(move |_: MouseEvent| {
+                        recipient.set(Recipient::Player(player_id));
+                    })
+                };
                 let onclick_mute = {
                     let chat_request_callback = chat_request_callback.clone();
                     Callback::from(move |_: MouseEvent| {
@@ -255,9 +347,15 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
                     })
                 };
                 let onclick_report = {
+                    let chat_request_callback = chat_request_callback.clone();
+                    Callback::from(move |_: MouseEvent| {
+                        chat_request_callback.emit(ChatRequest::Report(player_id));
+                    })
+                };
+                let onclick_commend = |commendation: Commendation| {
                     let player_request_callback = player_request_callback.clone();
                     Callback::from(move |_: MouseEvent| {
-                        player_request_callback.emit(PlayerRequest::Report(player_id));
+                        player_request_callback.emit(PlayerRequest::Commend{player_id, commendation});
                     })
                 };
                 let onclick_restrict_5m = {
@@ -288,8 +386,11 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
                                  <ContextMenuButton onclick={onclick_copy_team_id}>{"Copy Team ID"}</ContextMenuButton>
                             }
                         } else {
+                            <ContextMenuButton onclick={onclick_whisper}>{t.chat_whisper_label()}</ContextMenuButton>
                             <ContextMenuButton onclick={onclick_mute.clone()}>{t.chat_mute_label()}</ContextMenuButton>
                             <ContextMenuButton onclick={onclick_report}>{t.chat_report_label()}</ContextMenuButton>
+                            <ContextMenuButton onclick={onclick_commend(Commendation::GoodTeammate)}>{t.chat_commend_teammate_label()}</ContextMenuButton>
+                            <ContextMenuButton onclick={onclick_commend(Commendation::HonorableFight)}>{t.chat_commend_honorable_label()}</ContextMenuButton>
                         }
                     </ContextMenu>
                 };
@@ -300,13 +401,23 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
         };
 
         html_nested!{
-            <p class={classes!(message_css_class.clone(), dto.whisper.then(|| whisper_style.clone()))} oncontextmenu={oncontextmenu}>
+            <p class={classes!(message_css_class.clone(), (dto.whisper || dto.recipient.is_some()).then(|| whisper_style.clone()))} oncontextmenu={oncontextmenu}>
                 <span
                     onclick={move |_| onclick_reply()}
                     class={if dto.player_id.is_some() { name_css_class.clone() } else { official_name_css_class.clone() }}
                 >
                     {dto.team_name.map(|team_name| format!("[{}] {}", team_name, dto.alias)).unwrap_or(dto.alias.to_string())}
                 </span>
+                if dto.verified {
+                    <span class={verified_style.clone()} title={t.chat_verified_label()}>{" ✔"}</span>
+                }
+                if let Some(commendations) = commendations {
+                    <span class={commendation_style.clone()} title={t.chat_commendations_label()}>{format!(" ★{}", commendations)}</span>
+                }
+                if let Some(recipient) = dto.recipient {
+                    <span class={no_select_style.clone()}>{" → "}</span>
+                    <span class={name_css_class.clone()}>{recipient}</span>
+                }
                 <span class={no_select_style.clone()}>{" "}</span>
                 {segments(&dto.text, &mention_string).map(|Segment{contents, mention}| html_nested!{
                     <span class={classes!(mention.then(|| mention_style.clone()))}>{contents.to_owned()}</span>
@@ -330,13 +441,31 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
             open={ctw.setting_cache.chat_dialog_shown}
             {on_open_changed}
         >
-            {items}
+            <div role="log" aria-live="polite">
+                {items}
+            </div>
             if let Some(help_hint) = *help_hint {
                 <p><b>{"Automated help: "}{help_hint}</b></p>
             }
+            if core_state.team_id().is_some() || !whisperable.is_empty() {
+                <select onchange={onchange_recipient} class={input_css_class.clone()} aria-label={t.chat_label()}>
+                    <option value="everyone" selected={current_recipient == Recipient::Everyone}>{t.chat_everyone_label()}</option>
+                    if core_state.team_id().is_some() {
+                        <option value="team" selected={current_recipient == Recipient::Team}>{t.chat_team_label()}</option>
+                    }
+                    {whisperable.iter().map(|p| html_nested!{
+                        <option
+                            value={p.player_id.0.to_string()}
+                            selected={current_recipient == Recipient::Player(p.player_id)}
+                        >{format!("{} {}", t.chat_whisper_label(), p.alias)}</option>
+                    }).collect::<Html>()}
+                </select>
+            }
+            // Messages may be typed in either direction, regardless of the UI's.
             <input
                 type="text"
                 name="message"
+                dir="auto"
                 {title}
                 {oninput}
                 {onkeydown}
@@ -344,6 +473,7 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
                 minLength="1"
                 maxLength="128"
                 placeholder={t.chat_send_message_placeholder()}
+                aria-label={t.chat_send_message_placeholder()}
                 class={input_css_class.clone()}
                 ref={input_ref}
             />
@@ -351,6 +481,24 @@ pub fn chat_overlay(props: &ChatProps) -> Html {
     }
 }
 
+/// Parses `/w <alias> <message>` (or `/whisper`), into the recipient and the message.
+fn parse_whisper(text: &str, players: &[PlayerDto]) -> Option<(PlayerId, String)> {
+    let rest = text
+        .strip_prefix("/w ")
+        .or_else(|| text.strip_prefix("/whisper "))?
+        .trim_start();
+
+    // Aliases may contain spaces, so prefer the longest matching alias.
+    players
+        .iter()
+        .filter_map(|p| {
+            let message = rest.strip_prefix(p.alias.as_str())?.strip_prefix(' ')?;
+            Some((p.alias.as_str().len(), p.player_id, message))
+        })
+        .max_by_key(|&(len, _, _)| len)
+        .map(|(_, player_id, message)| (player_id, message.to_owned()))
+}
+
 fn help_hint_of(hints: &[(&'static str, Vec<&'static str>)], text: &str) -> Option<&'static str> {
     let text = text.to_ascii_lowercase();
     if text.find("/invite").is_some() {
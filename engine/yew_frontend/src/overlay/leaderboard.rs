@@ -203,10 +203,10 @@ pub fn leaderboard_overlay(props: &LeaderboardProps) -> Html {
             open={ctw.setting_cache.leaderboard_dialog_shown}
             {on_open_changed}
         >
-            <table class={table_css_class}>
+            <table class={table_css_class} aria-label={name.clone()}>
                 {items}
             </table>
-            <p class={p_css_class}>
+            <p class={p_css_class} aria-live="polite">
                 if let Some(children) = props.children.as_ref() {
                     {children.clone()}
                 } else {
@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+pub mod achievement;
 pub mod chat;
 pub mod fatal_error;
 pub mod leaderboard;
@@ -148,6 +148,7 @@ pub fn spawn_overlay(props: &DialogProps) -> Html {
                 minlength="1"
                 maxlength="12"
                 placeholder={t.splash_screen_alias_placeholder()}
+                aria-label={t.splash_screen_alias_placeholder()}
                 autocomplete="off"
             />
             <button
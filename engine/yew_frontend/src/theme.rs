@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use client_util::setting::CommonSettings;
+use stylist::GlobalStyle;
+use yew::{hook, use_context};
+
+/// Colors and sizes of the UI, derived from [`CommonSettings`] and provided as context.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub variant: ThemeVariant,
+    /// Multiplies the size of all `rem`/`em` based UI.
+    pub scale: f32,
+    /// Mirrors the UI, for right-to-left languages.
+    pub right_to_left: bool,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ThemeVariant {
+    #[default]
+    Default,
+    /// Opaque backgrounds and outlined text, for readability over any part of the game.
+    HighContrast,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            variant: ThemeVariant::Default,
+            scale: 1.0,
+            right_to_left: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn new(common_settings: &CommonSettings) -> Self {
+        Self {
+            variant: if common_settings.high_contrast {
+                ThemeVariant::HighContrast
+            } else {
+                ThemeVariant::Default
+            },
+            scale: common_settings.ui_scale,
+            right_to_left: common_settings.language.is_right_to_left(),
+        }
+    }
+
+    pub fn is_high_contrast(&self) -> bool {
+        self.variant == ThemeVariant::HighContrast
+    }
+
+    /// CSS `direction` of text and inline layout.
+    pub fn direction(&self) -> &'static str {
+        if self.right_to_left {
+            "rtl"
+        } else {
+            "ltr"
+        }
+    }
+
+    /// Background of dialogs.
+    pub fn dialog_background(&self) -> &'static str {
+        match self.variant {
+            ThemeVariant::Default => "#174479",
+            ThemeVariant::HighContrast => "#000000",
+        }
+    }
+
+    /// Border of dialogs.
+    pub fn dialog_border(&self) -> &'static str {
+        match self.variant {
+            ThemeVariant::Default => "none",
+            ThemeVariant::HighContrast => "2px solid white",
+        }
+    }
+
+    /// Background of sections and inputs drawn over the game.
+    pub fn panel_background(&self) -> &'static str {
+        match self.variant {
+            ThemeVariant::Default => "#00000025",
+            ThemeVariant::HighContrast => "#000000cc",
+        }
+    }
+
+    /// Shadow around text, if any.
+    pub fn text_shadow(&self) -> &'static str {
+        match self.variant {
+            ThemeVariant::Default => "none",
+            ThemeVariant::HighContrast => {
+                "-1px -1px 0 #000, 1px -1px 0 #000, -1px 1px 0 #000, 1px 1px 0 #000"
+            }
+        }
+    }
+
+    /// Mounts a global style, which sets the root font size, to which everything else is relative.
+    pub(crate) fn global_style(&self) -> GlobalStyle {
+        GlobalStyle::new(format!(
+            r#"
+            html {{
+                direction: {direction};
+                font-family: sans-serif;
+                font-size: {legacy_font_size}vmin;
+                font-size: calc({font_px}px + {font_vmin}vmin);
+            }}
+
+            body {{
+                color: white;
+                margin: 0;
+                overflow: hidden;
+                padding: 0;
+                text-shadow: {text_shadow};
+                touch-action: none;
+                user-select: none;
+            }}
+
+            a {{
+                color: {link_color};
+            }}
+            "#,
+            direction = self.direction(),
+            legacy_font_size = 1.5 * self.scale,
+            font_px = 7.0 * self.scale,
+            font_vmin = 0.8 * self.scale,
+            text_shadow = self.text_shadow(),
+            link_color = match self.variant {
+                ThemeVariant::Default => "white",
+                ThemeVariant::HighContrast => "#ffff00",
+            }
+        ))
+        .expect("failed to mount global style")
+    }
+}
+
+#[hook]
+pub fn use_theme() -> Theme {
+    use_context::<Theme>().unwrap()
+}
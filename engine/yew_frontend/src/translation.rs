@@ -2,9 +2,18 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::frontend::use_ctw;
+use core_protocol::dto::Achievement;
 use core_protocol::id::LanguageId::*;
 use core_protocol::id::{GameId, LanguageId, PeriodId};
-use yew::hook;
+use core_protocol::translation::parse_translations;
+use engine_macros::translations;
+use js_hooks::console_log;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{window, Request, RequestInit, RequestMode, Response};
+use yew::{hook, Callback};
 
 /// Only works in function component.
 #[hook]
@@ -12,6 +21,83 @@ pub fn use_translation() -> LanguageId {
     use_ctw().setting_cache.language
 }
 
+thread_local! {
+    /// Translations loaded from translation files, by language. [`None`] while loading.
+    static LOADED_TRANSLATIONS: RefCell<HashMap<LanguageId, Option<HashMap<String, &'static str>>>> =
+        RefCell::default();
+}
+
+/// Looks up a translation loaded by [`load_translations`]. Impls annotated with
+/// [`macro@translations`] fall back to their built-in translations if this returns [`None`].
+pub fn translated(language: LanguageId, key: &str) -> Option<&'static str> {
+    LOADED_TRANSLATIONS.with(|loaded| {
+        loaded
+            .borrow()
+            .get(&language)
+            .and_then(Option::as_ref)
+            .and_then(|translations| translations.get(key).copied())
+    })
+}
+
+/// Loads `/translations/{language}.ftl` from the CDN, at most once per language, calling
+/// `on_loaded` when it is ready to be [`translated`].
+pub(crate) fn load_translations(language: LanguageId, on_loaded: Callback<()>) {
+    let requested = LOADED_TRANSLATIONS.with(|loaded| {
+        let mut loaded = loaded.borrow_mut();
+        let requested = loaded.contains_key(&language);
+        loaded.entry(language).or_default();
+        requested
+    });
+    if requested {
+        return;
+    }
+
+    spawn_local(async move {
+        // Missing or invalid translation files aren't retried; built-in translations suffice.
+        let translations = match fetch_translations(language).await {
+            Ok(translations) => translations,
+            Err(e) => {
+                console_log!("could not load {} translations: {}", language, e);
+                HashMap::new()
+            }
+        };
+        LOADED_TRANSLATIONS.with(|loaded| {
+            loaded.borrow_mut().insert(language, Some(translations));
+        });
+        on_loaded.emit(());
+    });
+}
+
+async fn fetch_translations(language: LanguageId) -> Result<HashMap<String, &'static str>, String> {
+    let url = format!("/translations/{}.ftl", language);
+
+    let mut opts = RequestInit::new();
+    opts.method("GET");
+    opts.mode(RequestMode::SameOrigin);
+
+    let request = Request::new_with_str_and_init(&url, &opts).map_err(|e| format!("{:?}", e))?;
+
+    let resp_value = JsFuture::from(window().unwrap().fetch_with_request(&request))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let resp: Response = resp_value.dyn_into().map_err(|e| format!("{:?}", e))?;
+    if !resp.ok() {
+        return Err(format!("status {}", resp.status()));
+    }
+    let text_promise = resp.text().map_err(|e| format!("{:?}", e))?;
+    let src: String = JsFuture::from(text_promise)
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .as_string()
+        .ok_or(String::from("text not string"))?;
+
+    // Leaked, like built-in translations are static, but only once per language.
+    Ok(parse_translations(&src)?
+        .into_iter()
+        .map(|m| (m.key.to_owned(), &*Box::leak(m.value.into_boxed_str())))
+        .collect())
+}
+
 /// Declare static translations.
 #[macro_export]
 macro_rules! s {
@@ -55,7 +141,41 @@ pub trait Translation: Sized {
     s!(chat_send_team_message_hint);
     s!(chat_send_message_placeholder);
     s!(chat_report_label);
+    s!(chat_commend_teammate_label);
+    s!(chat_commend_honorable_label);
+    s!(chat_commendations_label);
+    s!(chat_verified_label);
     s!(chat_mute_label);
+    s!(chat_whisper_label);
+    s!(chat_everyone_label);
+    s!(chat_team_label);
+
+    // Achievements.
+    s!(achievement_unlocked_label);
+    fn achievement_label(self, achievement: Achievement) -> &'static str {
+        match achievement {
+            Achievement::FirstBlood => self.achievement_first_blood_label(),
+            Achievement::GiantSlayer => self.achievement_giant_slayer_label(),
+            Achievement::Survivor => self.achievement_survivor_label(),
+            Achievement::Voyager => self.achievement_voyager_label(),
+        }
+    }
+    fn achievement_hint(self, achievement: Achievement) -> &'static str {
+        match achievement {
+            Achievement::FirstBlood => self.achievement_first_blood_hint(),
+            Achievement::GiantSlayer => self.achievement_giant_slayer_hint(),
+            Achievement::Survivor => self.achievement_survivor_hint(),
+            Achievement::Voyager => self.achievement_voyager_hint(),
+        }
+    }
+    s!(achievement_first_blood_label);
+    s!(achievement_first_blood_hint);
+    s!(achievement_giant_slayer_label);
+    s!(achievement_giant_slayer_hint);
+    s!(achievement_survivor_label);
+    s!(achievement_survivor_hint);
+    s!(achievement_voyager_label);
+    s!(achievement_voyager_hint);
 
     // Live-board/leaderboard.
     sd!(liveboard_label, "Header for live leaderboard.");
@@ -110,6 +230,7 @@ pub trait Translation: Sized {
     // Connection lost.
     s!(connection_losing_message);
     s!(connection_lost_message);
+    s!(connection_outdated_message);
 
     // Alert
     s!(alert_dismiss);
@@ -154,6 +275,7 @@ pub trait Translation: Sized {
     fn terms_title(self, game_id: GameId) -> String;
 }
 
+#[translations("../translations")]
 impl Translation for LanguageId {
     fn label(self) -> &'static str {
         match self {
@@ -289,6 +411,262 @@ impl Translation for LanguageId {
         }
     }
 
+    fn chat_commend_teammate_label(self) -> &'static str {
+        match self {
+            German => "Guter Teamkamerad",
+            English | Bork => "Good teammate",
+            Spanish => "Buen compañero",
+            French => "Bon coéquipier",
+            Italian => "Buon compagno",
+            Arabic => "زميل جيد",
+            Japanese => "良い仲間",
+            Russian => "Хороший союзник",
+            Vietnamese => "Đồng đội tốt",
+            SimplifiedChinese => "好队友",
+            Hindi => "अच्छा साथी",
+        }
+    }
+
+    fn chat_commend_honorable_label(self) -> &'static str {
+        match self {
+            German => "Ehrenhafter Kampf",
+            English | Bork => "Honorable fight",
+            Spanish => "Combate honorable",
+            French => "Combat honorable",
+            Italian => "Combattimento onorevole",
+            Arabic => "قتال شريف",
+            Japanese => "立派な戦い",
+            Russian => "Честный бой",
+            Vietnamese => "Trận đấu danh dự",
+            SimplifiedChinese => "光荣之战",
+            Hindi => "सम्मानजनक लड़ाई",
+        }
+    }
+
+    fn chat_commendations_label(self) -> &'static str {
+        match self {
+            German => "Belobigungen",
+            English | Bork => "Commendations",
+            Spanish => "Elogios",
+            French => "Félicitations",
+            Italian => "Encomi",
+            Arabic => "إشادات",
+            Japanese => "称賛",
+            Russian => "Похвалы",
+            Vietnamese => "Lời khen",
+            SimplifiedChinese => "表彰",
+            Hindi => "प्रशंसा",
+        }
+    }
+
+    fn chat_verified_label(self) -> &'static str {
+        match self {
+            German => "Discord verifiziert",
+            English | Bork => "Discord verified",
+            Spanish => "Verificado en Discord",
+            French => "Vérifié sur Discord",
+            Italian => "Verificato su Discord",
+            Arabic => "موثق على ديسكورد",
+            Japanese => "Discord認証済み",
+            Russian => "Подтверждён в Discord",
+            Vietnamese => "Đã xác minh Discord",
+            SimplifiedChinese => "Discord已验证",
+            Hindi => "डिस्कॉर्ड सत्यापित",
+        }
+    }
+
+    fn chat_whisper_label(self) -> &'static str {
+        match self {
+            German => "Flüstern",
+            English | Bork => "Whisper",
+            Spanish => "Susurrar",
+            French => "Chuchoter",
+            Italian => "Sussurra",
+            Arabic => "همس",
+            Japanese => "ささやく",
+            Russian => "Шепнуть",
+            Vietnamese => "Thì thầm",
+            SimplifiedChinese => "私聊",
+            Hindi => "फुसफुसाएं",
+        }
+    }
+
+    fn chat_everyone_label(self) -> &'static str {
+        match self {
+            German => "Alle",
+            English | Bork => "Everyone",
+            Spanish => "Todos",
+            French => "Tout le monde",
+            Italian => "Tutti",
+            Arabic => "الجميع",
+            Japanese => "全員",
+            Russian => "Всем",
+            Vietnamese => "Mọi người",
+            SimplifiedChinese => "所有人",
+            Hindi => "सभी",
+        }
+    }
+
+    fn chat_team_label(self) -> &'static str {
+        match self {
+            German => "Team",
+            English | Bork => "Team",
+            Spanish => "Equipo",
+            French => "Équipe",
+            Italian => "Squadra",
+            Arabic => "الفريق",
+            Japanese => "チーム",
+            Russian => "Команда",
+            Vietnamese => "Đội",
+            SimplifiedChinese => "团队",
+            Hindi => "टीम",
+        }
+    }
+
+    fn achievement_unlocked_label(self) -> &'static str {
+        match self {
+            German => "Erfolg freigeschaltet",
+            English | Bork => "Achievement unlocked",
+            Spanish => "Logro desbloqueado",
+            French => "Succès débloqué",
+            Italian => "Obiettivo sbloccato",
+            Arabic => "تم فتح إنجاز",
+            Japanese => "実績解除",
+            Russian => "Достижение получено",
+            Vietnamese => "Đã mở khóa thành tựu",
+            SimplifiedChinese => "成就解锁",
+            Hindi => "उपलब्धि अनलॉक हुई",
+        }
+    }
+
+    fn achievement_first_blood_label(self) -> &'static str {
+        match self {
+            German => "Erstes Blut",
+            English | Bork => "First blood",
+            Spanish => "Primera sangre",
+            French => "Premier sang",
+            Italian => "Primo sangue",
+            Arabic => "الدم الأول",
+            Japanese => "先制攻撃",
+            Russian => "Первая кровь",
+            Vietnamese => "Chiến công đầu",
+            SimplifiedChinese => "第一滴血",
+            Hindi => "पहला शिकार",
+        }
+    }
+
+    fn achievement_first_blood_hint(self) -> &'static str {
+        match self {
+            German => "Versenke einen anderen Spieler",
+            English | Bork => "Sink another player",
+            Spanish => "Hunde a otro jugador",
+            French => "Coulez un autre joueur",
+            Italian => "Affonda un altro giocatore",
+            Arabic => "أغرق لاعبًا آخر",
+            Japanese => "他のプレイヤーを沈める",
+            Russian => "Потопите другого игрока",
+            Vietnamese => "Đánh chìm một người chơi khác",
+            SimplifiedChinese => "击沉另一名玩家",
+            Hindi => "किसी दूसरे खिलाड़ी को डुबोएं",
+        }
+    }
+
+    fn achievement_giant_slayer_label(self) -> &'static str {
+        match self {
+            German => "Riesentöter",
+            English | Bork => "Giant slayer",
+            Spanish => "Matagigantes",
+            French => "Tueur de géants",
+            Italian => "Ammazzagiganti",
+            Arabic => "قاتل العمالقة",
+            Japanese => "ジャイアントキリング",
+            Russian => "Убийца великанов",
+            Vietnamese => "Kẻ diệt khổng lồ",
+            SimplifiedChinese => "屠巨者",
+            Hindi => "दानव संहारक",
+        }
+    }
+
+    fn achievement_giant_slayer_hint(self) -> &'static str {
+        match self {
+            German => "Versenke einen Spieler, der mindestens zwei Stufen höher ist",
+            English | Bork => "Sink a player at least two levels higher",
+            Spanish => "Hunde a un jugador al menos dos niveles superior",
+            French => "Coulez un joueur d'au moins deux niveaux de plus",
+            Italian => "Affonda un giocatore di almeno due livelli superiore",
+            Arabic => "أغرق لاعبًا أعلى بمستويين على الأقل",
+            Japanese => "2レベル以上高いプレイヤーを沈める",
+            Russian => "Потопите игрока, который выше вас хотя бы на два уровня",
+            Vietnamese => "Đánh chìm người chơi cao hơn ít nhất hai cấp",
+            SimplifiedChinese => "击沉一名至少高两级的玩家",
+            Hindi => "कम से कम दो स्तर ऊंचे खिलाड़ी को डुबोएं",
+        }
+    }
+
+    fn achievement_survivor_label(self) -> &'static str {
+        match self {
+            German => "Überlebender",
+            English | Bork => "Survivor",
+            Spanish => "Superviviente",
+            French => "Survivant",
+            Italian => "Sopravvissuto",
+            Arabic => "الناجي",
+            Japanese => "サバイバー",
+            Russian => "Выживший",
+            Vietnamese => "Người sống sót",
+            SimplifiedChinese => "幸存者",
+            Hindi => "उत्तरजीवी",
+        }
+    }
+
+    fn achievement_survivor_hint(self) -> &'static str {
+        match self {
+            German => "Bleib 30 Minuten am Leben",
+            English | Bork => "Stay alive for 30 minutes",
+            Spanish => "Sobrevive durante 30 minutos",
+            French => "Restez en vie pendant 30 minutes",
+            Italian => "Resta in vita per 30 minuti",
+            Arabic => "ابقَ على قيد الحياة لمدة 30 دقيقة",
+            Japanese => "30分間生き残る",
+            Russian => "Продержитесь 30 минут",
+            Vietnamese => "Sống sót trong 30 phút",
+            SimplifiedChinese => "存活30分钟",
+            Hindi => "30 मिनट तक जीवित रहें",
+        }
+    }
+
+    fn achievement_voyager_label(self) -> &'static str {
+        match self {
+            German => "Weltenbummler",
+            English | Bork => "Voyager",
+            Spanish => "Viajero",
+            French => "Voyageur",
+            Italian => "Viaggiatore",
+            Arabic => "الرحّالة",
+            Japanese => "航海者",
+            Russian => "Путешественник",
+            Vietnamese => "Nhà hàng hải",
+            SimplifiedChinese => "远航者",
+            Hindi => "यात्री",
+        }
+    }
+
+    fn achievement_voyager_hint(self) -> &'static str {
+        match self {
+            German => "Lege insgesamt 50 km zurück",
+            English | Bork => "Travel 50 km in total",
+            Spanish => "Recorre 50 km en total",
+            French => "Parcourez 50 km au total",
+            Italian => "Percorri 50 km in totale",
+            Arabic => "اقطع مسافة 50 كم إجمالًا",
+            Japanese => "合計50km航行する",
+            Russian => "Пройдите в сумме 50 км",
+            Vietnamese => "Di chuyển tổng cộng 50 km",
+            SimplifiedChinese => "累计航行50公里",
+            Hindi => "कुल 50 किमी की यात्रा करें",
+        }
+    }
+
     fn liveboard_label(self) -> &'static str {
         match self {
             Bork => "Leaderbork",
@@ -750,6 +1128,23 @@ impl Translation for LanguageId {
         }
     }
 
+    fn connection_outdated_message(self) -> &'static str {
+        match self {
+            Bork => "The game got a new bork. Refresh to keep borking!",
+            German => "Das Spiel wurde aktualisiert. Laden Sie die Seite neu, um weiterzuspielen!",
+            English => "The game was updated. Refresh to keep playing!",
+            Spanish => "El juego se ha actualizado. ¡Actualiza la página para seguir jugando!",
+            French => "Le jeu a été mis à jour. Actualisez la page pour continuer à jouer!",
+            Italian => "Il gioco è stato aggiornato. Aggiorna la pagina per continuare a giocare!",
+            Arabic => "تم تحديث اللعبة. قم بتحديث الصفحة لمواصلة اللعب!",
+            Japanese => "ゲームが更新されました。ページを更新してプレイを続けてください！",
+            Russian => "Игра обновилась. Обновите страницу, чтобы продолжить игру!",
+            Vietnamese => "Trò chơi đã được cập nhật. Tải lại trang để tiếp tục chơi!",
+            SimplifiedChinese => "游戏已更新。刷新页面以继续游戏！",
+            Hindi => "गेम अपडेट हो गया है। खेलना जारी रखने के लिए पेज रीफ्रेश करें!",
+        }
+    }
+
     fn connection_losing_message(self) -> &'static str {
         match self {
             Bork => "Your connection was borked. Reborking now...",
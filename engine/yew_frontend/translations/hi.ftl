@@ -0,0 +1 @@
+chat_send_team_message_hint = भेजने के लिए एंटर दबाएं, या केवल टीम को भेजने के लिए Shift+Enter दबाएं
@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use client_util::apply::Apply;
+use client_util::audio::Audio;
+use client_util::context::Context;
+use client_util::game_client::GameClient;
+use common::angle::Angle;
+use common::contact::{Contact, ContactTrait};
+use common::entity::EntityType;
+use common::guidance::Guidance;
+use common::protocol::{Command, Control, Doctrine, Fire, Hint, Spawn, Update};
+use common::velocity::Velocity;
+use core_protocol::id::GameId;
+use glam::Vec2;
+use rand::seq::IteratorRandom;
+use rand::{thread_rng, Rng};
+use sprite_sheet::AudioSprite;
+
+/// A simulated player that spawns, wanders, and shoots at random.
+struct Mk48Client {
+    /// Time (in seconds) until trying to spawn again.
+    spawn_cooldown: f32,
+    guidance: Guidance,
+    /// Time (in seconds) until changing `guidance`.
+    guidance_cooldown: f32,
+}
+
+/// Just enough of the game state to play.
+#[derive(Default)]
+struct Mk48State {
+    contacts: Vec<Contact>,
+    score: u32,
+}
+
+impl Apply<Update> for Mk48State {
+    fn apply(&mut self, update: Update) {
+        self.contacts = update.contacts;
+        self.score = update.score;
+    }
+}
+
+/// There is nothing to hear.
+#[derive(Copy, Clone, Debug)]
+enum Silence {}
+
+impl Audio for Silence {
+    fn index(self) -> usize {
+        match self {}
+    }
+
+    fn path() -> &'static str {
+        ""
+    }
+
+    fn sprites() -> &'static [AudioSprite] {
+        &[]
+    }
+}
+
+impl GameClient for Mk48Client {
+    const GAME_ID: GameId = GameId::Mk48;
+
+    type Audio = Silence;
+    type GameRequest = Command;
+    type GameState = Mk48State;
+    type UiEvent = ();
+    type UiProps = ();
+    type GameUpdate = Update;
+    type GameSettings = ();
+
+    fn new(_context: &Context<Self>) -> Result<Self, String> {
+        Ok(Self {
+            spawn_cooldown: 0.0,
+            guidance: Guidance::new(),
+            guidance_cooldown: 0.0,
+        })
+    }
+
+    fn update(&mut self, elapsed_seconds: f32, context: &mut Context<Self>) {
+        let mut rng = thread_rng();
+
+        let player_id = context.state.core.player_id;
+        let game = &context.state.game;
+        let score = game.score;
+        let boat = game
+            .contacts
+            .iter()
+            .find(|contact| contact.is_boat() && contact.player_id() == player_id)
+            .and_then(|contact| Some((contact.entity_type()?, *contact.transform())));
+
+        if let Some((entity_type, transform)) = boat {
+            let data = entity_type.data();
+
+            self.guidance_cooldown -= elapsed_seconds;
+            if self.guidance_cooldown <= 0.0 {
+                self.guidance_cooldown = rng.gen_range(2.0..10.0);
+                self.guidance = Guidance {
+                    direction_target: Angle::from_radians(
+                        rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI),
+                    ),
+                    velocity_target: Velocity::from_mps(
+                        data.speed.to_mps() * rng.gen_range(0.2..1.0),
+                    ),
+                };
+            }
+
+            let aim_target = transform.position
+                + Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * 250.0;
+
+            let fire = (!data.armaments.is_empty() && rng.gen_bool(0.05)).then(|| Fire {
+                armament_index: rng.gen_range(0..data.armaments.len()) as u8,
+            });
+
+            context.send_to_game(Command::Control(Control {
+                guidance: Some(self.guidance),
+                submerge: false,
+                aim_target: Some(aim_target),
+                active: rng.gen_bool(0.5),
+                fire,
+                pay: None,
+                hint: Some(Hint::default()),
+                depth: None,
+                doctrine: Doctrine::default(),
+            }));
+        } else {
+            self.spawn_cooldown -= elapsed_seconds;
+            if self.spawn_cooldown > 0.0 {
+                return;
+            }
+            self.spawn_cooldown = 1.0;
+
+            if let Some(entity_type) = EntityType::spawn_options(score, false).choose(&mut rng) {
+                context.send_to_game(Command::Spawn(Spawn {
+                    entity_type,
+                    loadout: 0,
+                }));
+            }
+        }
+    }
+}
+
+fn main() {
+    headless_client::entry_point::<Mk48Client>();
+}
@@ -3,10 +3,13 @@ use common_util::range::map_ranges;
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Mul;
 use std::str::FromStr;
-use syn::{parse_macro_input, Data, DataEnum, DeriveInput, Lit, Meta, MetaNameValue, NestedMeta};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DataEnum, DeriveInput, Error, Lit, Meta, MetaNameValue, NestedMeta,
+};
 
 pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
     let DeriveInput { ident, data, .. } = parse_macro_input!(input);
@@ -25,11 +28,15 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
     let mut entities = variants
         .into_iter()
         .map(|variant| {
-            let mut entity = Entity::default();
+            let mut entity = Entity {
+                span: Some(variant.ident.span()),
+                ..Entity::default()
+            };
 
             for attr in variant.attrs
             /* TODO filter */
             {
+                let span = attr.span();
                 let meta = attr.parse_meta().expect("couldn't parse as meta");
                 let list = match meta {
                     Meta::List(list) => list,
@@ -238,6 +245,21 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                                 "torpedo_resistance" => {
                                     set_f32(&mut entity.torpedo_resistance, nested);
                                 }
+                                "armor_bow" => {
+                                    set_f32(&mut entity.armor_bow, nested);
+                                }
+                                "armor_belt" => {
+                                    set_f32(&mut entity.armor_belt, nested);
+                                }
+                                "armor_stern" => {
+                                    set_f32(&mut entity.armor_stern, nested);
+                                }
+                                "decoy_size" => {
+                                    set_f32(&mut entity.decoy_size, nested);
+                                }
+                                "terrain_damage" => {
+                                    set_f32(&mut entity.terrain_damage, nested);
+                                }
                                 _ => panic!("unexpected props path: {path}"),
                             }
                         }
@@ -268,8 +290,46 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                             );
                         }
                     }
+                    "seeker" => {
+                        if entity.seeker.is_some() {
+                            panic!("duplicate seeker");
+                        }
+                        let mut seeker = Seeker::default();
+
+                        for nested in list.nested {
+                            let NestedMeta::Meta(nested) = nested else {
+                            panic!("expected nested meta");
+                        };
+
+                            let path = nested.path().get_ident().unwrap().to_string();
+
+                            match path.as_str() {
+                                "cone" => {
+                                    set_angle(&mut seeker.cone, nested);
+                                }
+                                "deviation" => {
+                                    set_angle(&mut seeker.deviation, nested);
+                                }
+                                "delay" => {
+                                    set_f32(&mut seeker.delay, nested);
+                                }
+                                "reacquisition" => {
+                                    set_f32(&mut seeker.reacquisition, nested);
+                                }
+                                "decoy_susceptibility" => {
+                                    set_f32(&mut seeker.decoy_susceptibility, nested);
+                                }
+                                _ => panic!("unexpected seeker path: {path}"),
+                            }
+                        }
+
+                        entity.seeker = Some(seeker);
+                    }
                     "armament" => {
-                        let mut armament = Armament::default();
+                        let mut armament = Armament {
+                            span: Some(span),
+                            ..Armament::default()
+                        };
 
                         for (i, nested) in list.nested.into_iter().enumerate() {
                             let NestedMeta::Meta(nested) = nested else {
@@ -310,6 +370,9 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                                     "vertical" => {
                                         set_bool(&mut armament.vertical, nested);
                                     }
+                                    "loadout" => {
+                                        set_string(&mut armament.loadout, nested);
+                                    }
                                     _ => panic!("unexpected armament path: {path}"),
                                 },
                             }
@@ -318,7 +381,10 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                         entity.armaments.push(armament);
                     }
                     "turret" => {
-                        let mut turret = Turret::default();
+                        let mut turret = Turret {
+                            span: Some(span),
+                            ..Turret::default()
+                        };
                         let mut speed = None;
 
                         for (i, nested) in list.nested.into_iter().enumerate() {
@@ -413,6 +479,44 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
 
                         entity.exhausts.push(exhaust);
                     }
+                    "upgrades_to" => {
+                        if entity.upgrades_to.is_some() {
+                            panic!("duplicate upgrades_to");
+                        }
+                        entity.upgrades_to = Some(
+                            list.nested
+                                .into_iter()
+                                .map(|nested| {
+                                    let NestedMeta::Meta(Meta::Path(path)) = nested else {
+                                        panic!("expected entity type");
+                                    };
+                                    (path.get_ident().unwrap().to_string(), path.span())
+                                })
+                                .collect(),
+                        );
+                    }
+                    "loadout" => {
+                        let mut label = None;
+
+                        for nested in list.nested {
+                            let NestedMeta::Meta(nested) = nested else {
+                                panic!("expected nested meta");
+                            };
+
+                            let path = nested.path().get_ident().unwrap().to_string();
+
+                            match path.as_str() {
+                                "label" => {
+                                    set_string(&mut label, nested);
+                                }
+                                _ => panic!("unexpected loadout path: {path}"),
+                            }
+                        }
+
+                        entity
+                            .loadouts
+                            .push((label.expect("expected loadout label"), span));
+                    }
                     _ => panic!("unexpected path {path}"),
                 }
             }
@@ -423,6 +527,15 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
 
     //panic!("{entities:?}");
 
+    let errors = lint(&ordered_entity_names, &entities);
+    if !errors.is_empty() {
+        return errors
+            .iter()
+            .map(Error::to_compile_error)
+            .collect::<proc_macro2::TokenStream>()
+            .into();
+    }
+
     let original_entities = entities.clone();
     let mut max_radius = 0f32;
     let mut max_boat_level = 0;
@@ -509,7 +622,7 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
             }
             "Boat" => {
                 match entity.sub_kind() {
-                    "Dredger" | "Submarine" | "Tanker" => {}
+                    "Dredger" | "Kraken" | "Submarine" | "Tanker" => {}
                     _ => {
                         entity.anti_aircraft =
                             map_ranges(entity.length(), 30.0..300.0, 0.1..0.5, true);
@@ -532,7 +645,18 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                     }
                 }
 
-                if entity.sub_kind() == "Pirate" {
+                // Hulls are narrower and more sloped at the ends, so hits there tend to glance.
+                if entity.armor_bow.is_none() {
+                    entity.armor_bow = Some(0.75);
+                }
+                if entity.armor_belt.is_none() {
+                    entity.armor_belt = Some(1.0);
+                }
+                if entity.armor_stern.is_none() {
+                    entity.armor_stern = Some(0.9);
+                }
+
+                if matches!(entity.sub_kind(), "Kraken" | "Pirate") {
                     entity.npc = true;
                 }
             }
@@ -604,6 +728,7 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                         "Rocket" => 2.5,
                         "RocketTorpedo" => 20.0,
                         "Mine" => 30.0,
+                        "Net" => 40.0,
                         "Sam" => 16.0,
                         "Missile" => map_ranges(entity.length(), 1.0..6.0, 4.0..12.0, true),
                         "Shell" => map_ranges(entity.length(), 0.25..2.0, 8.0..15.0, true),
@@ -678,6 +803,13 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
         }
 
         for mut armament in armaments {
+            if let Some(loadout) = armament.loadout.as_deref() {
+                armament.loadout_index = entity
+                    .loadouts
+                    .iter()
+                    .position(|(label, _)| label == loadout)
+                    .map(|i| i as u8);
+            }
             let count = std::mem::take(&mut armament.count).unwrap_or(1);
             assert!(count > 0, "zero armament count");
             let symmetrical = std::mem::take(&mut armament.symmetrical);
@@ -735,6 +867,48 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
         }
         entity.sensors = sensors;
 
+        // Homing weapons (and aircraft) home towards targets/decoys.
+        if matches!(entity.kind(), "Weapon" | "Aircraft")
+            && !entity.sensors.is_empty()
+            && entity.sub_kind() != "Rocket"
+        {
+            let (cone, deviation) = if entity.sub_kind() == "Missile" {
+                (40.0, 30.0)
+            } else {
+                (80.0, 60.0)
+            };
+            let seeker = entity.seeker.get_or_insert_with(Default::default);
+            seeker.cone.get_or_insert(Angle::from_degrees(cone));
+            seeker.deviation.get_or_insert(Angle::from_degrees(deviation));
+            seeker.delay.get_or_insert(1.0);
+            seeker.reacquisition.get_or_insert(0.0);
+            seeker.decoy_susceptibility.get_or_insert(1.0);
+        } else {
+            assert!(entity.seeker.is_none(), "unexpected seeker for {variant}");
+        }
+
+        // Explosive weapons carve terrain in proportion to their damage, unless overridden.
+        if entity.kind() == "Weapon"
+            && matches!(
+                entity.sub_kind(),
+                "DepthCharge" | "Missile" | "Rocket" | "Shell" | "Torpedo"
+            )
+        {
+            entity
+                .terrain_damage
+                .get_or_insert(entity.damage.unwrap_or_default());
+        }
+
+        if entity.kind() == "Decoy" {
+            // Decoys appear very large to weapons.
+            entity.decoy_size.get_or_insert(200.0);
+        } else {
+            assert_eq!(
+                entity.decoy_size, None,
+                "unexpected decoy size for {variant}"
+            );
+        }
+
         let mut armaments = std::mem::take(&mut entity.armaments);
         armaments.sort_by_key(|armament| {
             let armament_data = original_entities.get(armament._type()).unwrap();
@@ -749,7 +923,7 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
                         5
                     }
                 }
-                ("Weapon", "DepthCharge") | ("Weapon", "Mine") => 1,
+                ("Weapon", "DepthCharge") | ("Weapon", "Mine") | ("Weapon", "Net") => 1,
                 ("Weapon", "Sam") => -5,
                 ("Decoy", _) => -8,
                 ("Aircraft", _) => {
@@ -859,6 +1033,7 @@ pub(crate) fn derive_entity_type(input: TokenStream) -> TokenStream {
 
 #[derive(Clone, Debug, Default)]
 struct Entity {
+    span: Option<Span>,
     name: Option<String>,
     label: Option<String>,
     link: Option<String>,
@@ -880,10 +1055,22 @@ struct Entity {
     damage: Option<f32>,
     ram_damage: Option<f32>,
     torpedo_resistance: Option<f32>,
+    armor_bow: Option<f32>,
+    armor_belt: Option<f32>,
+    armor_stern: Option<f32>,
+    decoy_size: Option<f32>,
+    terrain_damage: Option<f32>,
     sensors: HashMap<String, Sensor>,
+    seeker: Option<Seeker>,
     armaments: Vec<Armament>,
     turrets: Vec<Turret>,
     exhausts: Vec<Exhaust>,
+    /// Explicit upgrade graph edges, and where they were declared. If `None`, the entity may
+    /// upgrade to any higher level of the same kind.
+    upgrades_to: Option<Vec<(String, Span)>>,
+    /// Labels of selectable armament loadouts, and where they were declared. The first is the
+    /// default.
+    loadouts: Vec<(String, Span)>,
     limited: bool,
     npc: bool,
     anti_aircraft: f32,
@@ -907,6 +1094,193 @@ impl Entity {
     fn width(&self) -> f32 {
         self.width.unwrap()
     }
+
+    /// Whether an offset (relative to the center of the entity) lies on top of it.
+    fn contains(&self, forward: Option<f32>, side: Option<f32>) -> bool {
+        forward.unwrap_or_default().abs() <= self.length() * 0.5
+            && side.unwrap_or_default().abs() <= self.width() * 0.5
+    }
+}
+
+/// Checks entity data for mistakes that would otherwise surface as panics in the sprite sheet
+/// packer, or as subtle bugs in game. Errors point at the offending variant or attribute.
+fn lint(ordered_entity_names: &[String], entities: &HashMap<String, Entity>) -> Vec<Error> {
+    let mut errors = Vec::new();
+    let mut used = HashSet::new();
+
+    for variant in ordered_entity_names {
+        let entity = &entities[variant];
+        let span = entity.span.unwrap();
+        let kind = entity.kind();
+
+        // Rocket torpedoes carry a payload, but don't render it, so it doesn't count as used.
+        let payload = entity.sub_kind() == "RocketTorpedo";
+        if !entity.armaments.is_empty()
+            && !matches!(kind, "Boat" | "Aircraft" | "Turret")
+            && !payload
+        {
+            errors.push(Error::new(
+                span,
+                format!("{variant} is a {kind}, which cannot have armaments"),
+            ));
+        }
+        if !entity.turrets.is_empty() && kind != "Boat" {
+            errors.push(Error::new(
+                span,
+                format!("{variant} is a {kind}, which cannot have turrets"),
+            ));
+        }
+
+        for turret in &entity.turrets {
+            let span = turret.span.unwrap();
+            if let Some(_type) = turret._type.as_deref() {
+                match entities.get(_type).map(Entity::kind) {
+                    Some("Turret") => {
+                        used.insert(_type);
+                    }
+                    Some(other) => errors.push(Error::new(
+                        span,
+                        format!("turret {_type} is a {other}, not a Turret"),
+                    )),
+                    None => errors.push(Error::new(span, format!("unknown turret {_type}"))),
+                }
+            }
+            if !entity.contains(turret.position_forward, turret.position_side) {
+                errors.push(Error::new(
+                    span,
+                    format!("turret is outside the hull of {variant}"),
+                ));
+            }
+        }
+
+        // Symmetrical turrets are mirrored into two, and armaments index the mirrored turrets.
+        let turret_count = entity
+            .turrets
+            .iter()
+            .map(|turret| 1 + turret.symmetrical as usize)
+            .sum::<usize>();
+
+        for armament in &entity.armaments {
+            let span = armament.span.unwrap();
+            let _type = armament._type();
+            match entities.get(_type).map(Entity::kind) {
+                Some("Weapon" | "Aircraft" | "Decoy") => {
+                    if !payload {
+                        used.insert(_type);
+                    }
+                }
+                Some(other) => errors.push(Error::new(
+                    span,
+                    format!("armament {_type} is a {other}, not a Weapon, Aircraft, or Decoy"),
+                )),
+                None => errors.push(Error::new(span, format!("unknown armament {_type}"))),
+            }
+
+            if let Some(loadout) = armament.loadout.as_deref() {
+                if kind != "Boat" {
+                    errors.push(Error::new(
+                        span,
+                        format!("armament {_type} is part of a loadout, but {variant} is a {kind}"),
+                    ));
+                } else if !entity.loadouts.iter().any(|(label, _)| label == loadout) {
+                    errors.push(Error::new(
+                        span,
+                        format!("{variant} has no loadout labeled {loadout}"),
+                    ));
+                }
+            }
+
+            if let Some(turret) = armament.turret {
+                if turret >= turret_count {
+                    errors.push(Error::new(
+                        span,
+                        format!(
+                            "armament {_type} is on turret {turret}, but {variant} has {turret_count} turret(s)"
+                        ),
+                    ));
+                }
+            } else if !entity.contains(armament.position_forward, armament.position_side) {
+                // Armaments on turrets are relative to the turret, and are checked as part of it.
+                errors.push(Error::new(
+                    span,
+                    format!("armament {_type} is outside the hull of {variant}"),
+                ));
+            }
+        }
+
+        if !entity.loadouts.is_empty() {
+            let span = entity.loadouts[0].1;
+            if kind != "Boat" {
+                errors.push(Error::new(
+                    span,
+                    format!("{variant} is a {kind}, which cannot have loadouts"),
+                ));
+            } else if entity.loadouts.len() < 2 {
+                errors.push(Error::new(
+                    span,
+                    format!("{variant} has only one loadout, so there is nothing to choose"),
+                ));
+            }
+            for (i, (label, span)) in entity.loadouts.iter().enumerate() {
+                if entity.loadouts[..i].iter().any(|(other, _)| other == label) {
+                    errors.push(Error::new(*span, format!("duplicate loadout {label}")));
+                }
+            }
+        }
+
+        if let Some(upgrades_to) = &entity.upgrades_to {
+            if kind != "Boat" {
+                errors.push(Error::new(
+                    span,
+                    format!("{variant} is a {kind}, which cannot upgrade"),
+                ));
+            }
+            for (upgrade, span) in upgrades_to {
+                match entities.get(upgrade.as_str()) {
+                    Some(upgrade_entity) if upgrade_entity.kind() != kind => {
+                        errors.push(Error::new(
+                            *span,
+                            format!("{upgrade} is a {}, not a {kind}", upgrade_entity.kind()),
+                        ))
+                    }
+                    Some(upgrade_entity) if upgrade_entity.level <= entity.level => {
+                        errors.push(Error::new(
+                            *span,
+                            format!(
+                                "{variant} cannot upgrade to {upgrade}, which isn't a higher level"
+                            ),
+                        ))
+                    }
+                    Some(_) => {}
+                    None => errors.push(Error::new(*span, format!("unknown upgrade {upgrade}"))),
+                }
+            }
+        }
+
+        if kind == "Weapon" {
+            let range = entity.range.or(entity.lifespan).unwrap_or_default();
+            if range <= 0.0 {
+                errors.push(Error::new(
+                    span,
+                    format!("{variant} is a Weapon with zero range"),
+                ));
+            }
+        }
+    }
+
+    for variant in ordered_entity_names {
+        let entity = &entities[variant];
+        if matches!(entity.kind(), "Weapon" | "Aircraft" | "Decoy" | "Turret")
+            && !used.contains(variant.as_str())
+        {
+            errors.push(Error::new(
+                entity.span.unwrap(),
+                format!("{variant} is not used by any boat or aircraft"),
+            ));
+        }
+    }
+
+    errors
 }
 
 #[derive(Clone, Debug, Default)]
@@ -914,8 +1288,18 @@ struct Sensor {
     range: Option<f32>,
 }
 
+#[derive(Clone, Debug, Default)]
+struct Seeker {
+    cone: Option<Angle>,
+    deviation: Option<Angle>,
+    delay: Option<f32>,
+    reacquisition: Option<f32>,
+    decoy_susceptibility: Option<f32>,
+}
+
 #[derive(Clone, Debug, Default)]
 struct Armament {
+    span: Option<Span>,
     _type: Option<String>,
     position_forward: Option<f32>,
     position_side: Option<f32>,
@@ -926,6 +1310,10 @@ struct Armament {
     hidden: bool,
     external: bool,
     vertical: bool,
+    /// Label of the only loadout that includes this armament, if any.
+    loadout: Option<String>,
+    /// Resolved from `loadout`.
+    loadout_index: Option<u8>,
 }
 
 impl Armament {
@@ -936,6 +1324,7 @@ impl Armament {
 
 #[derive(Clone, Debug, Default)]
 struct Turret {
+    span: Option<Span>,
     _type: Option<String>,
     position_forward: Option<f32>,
     position_side: Option<f32>,
@@ -1055,7 +1444,13 @@ impl quote::ToTokens for Entity {
         let anti_aircraft = self.anti_aircraft;
         let ram_damage = self.ram_damage.unwrap_or_default();
         let torpedo_resistance = self.torpedo_resistance.unwrap_or_default();
+        let armor_bow = self.armor_bow.unwrap_or(1.0);
+        let armor_belt = self.armor_belt.unwrap_or(1.0);
+        let armor_stern = self.armor_stern.unwrap_or(1.0);
         let stealth = self.stealth.unwrap_or_default();
+        let decoy_size = self.decoy_size.unwrap_or_default();
+        let terrain_damage = self.terrain_damage.unwrap_or_default();
+        let seeker = quote_option(self.seeker.as_ref());
 
         let visual_range = self
             .sensors
@@ -1077,6 +1472,17 @@ impl quote::ToTokens for Entity {
         let turrets = &self.turrets;
         let exhausts = &self.exhausts;
 
+        let upgrades_to = quote_option(self.upgrades_to.as_ref().map(|upgrades_to| {
+            let upgrades_to = upgrades_to
+                .iter()
+                .map(|(upgrade, _)| string_to_ident(upgrade));
+            quote! {
+                &[#(EntityType::#upgrades_to),*]
+            }
+        }));
+
+        let loadouts = self.loadouts.iter().map(|(label, _)| label);
+
         let label = self.label.as_deref().unwrap();
         let link = quote_option(self.link.as_deref());
         let range = self.range.unwrap_or_default();
@@ -1105,6 +1511,9 @@ impl quote::ToTokens for Entity {
                     anti_aircraft: #anti_aircraft,
                     ram_damage: #ram_damage,
                     torpedo_resistance: #torpedo_resistance,
+                    armor_bow: #armor_bow,
+                    armor_belt: #armor_belt,
+                    armor_stern: #armor_stern,
                     stealth: #stealth,
                     sensors: Sensors{
                         visual: Sensor{
@@ -1117,9 +1526,14 @@ impl quote::ToTokens for Entity {
                             range: #sonar_range,
                         }
                     },
+                    seeker: #seeker,
+                    decoy_size: #decoy_size,
+                    terrain_damage: #terrain_damage,
                     armaments: &[#(#armaments),*],
                     turrets: &[#(#turrets),*],
                     exhausts: &[#(#exhausts),*],
+                    upgrades_to: #upgrades_to,
+                    loadouts: &[#(#loadouts),*],
                     label: #label,
                     link: #link,
                     range: #range,
@@ -1134,6 +1548,31 @@ impl quote::ToTokens for Entity {
     }
 }
 
+impl quote::ToTokens for Seeker {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let cone = self.cone.unwrap_or_default().0;
+        let deviation = self.deviation.unwrap_or_default().0;
+        let delay = (self.delay.unwrap_or_default() * 1000.0) as u32;
+        let reacquisition = (self.reacquisition.unwrap_or_default() * 1000.0) as u32;
+        let decoy_susceptibility = self.decoy_susceptibility.unwrap_or_default();
+
+        let ts: proc_macro2::TokenStream = {
+            quote! {
+                Seeker{
+                    cone: Angle(#cone),
+                    deviation: Angle(#deviation),
+                    delay: Ticks::from_whole_millis(#delay),
+                    reacquisition: Ticks::from_whole_millis(#reacquisition),
+                    decoy_susceptibility: #decoy_susceptibility,
+                }
+            }
+        }
+        .into();
+
+        tokens.extend(ts);
+    }
+}
+
 impl quote::ToTokens for Armament {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let entity_type = string_to_ident(self._type());
@@ -1144,6 +1583,7 @@ impl quote::ToTokens for Armament {
         let position_side = self.position_side.unwrap_or_default();
         let angle = self.angle.unwrap_or_default().0;
         let turret = quote_option(self.turret);
+        let loadout = quote_option(self.loadout_index);
 
         let ts: proc_macro2::TokenStream = {
             quote! {
@@ -1156,6 +1596,7 @@ impl quote::ToTokens for Armament {
                     position_side: #position_side,
                     angle: Angle(#angle),
                     turret: #turret,
+                    loadout: #loadout,
                 }
             }
         }
@@ -4,8 +4,12 @@
 use crate::entity::Entity;
 use common::entity::EntityId;
 use common::entity::*;
+use common::protocol::Round;
+use core_protocol::dto::LeaderboardDto;
 use idalloc::Slab;
 use ringbuffer::{ConstGenericRingBuffer, RingBufferExt, RingBufferRead, RingBufferWrite};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Arena manages entity ids and counts of each entity type. it takes care of delaying the reuse of
 /// EntityIds that are remembered by clients.
@@ -112,3 +116,103 @@ impl Arena {
         self.delay_recycle.push(front);
     }
 }
+
+/// Blitz mode plays the arena in timed rounds, with accelerated reloads and score gain. When a
+/// round ends, every boat is sunk, and the final standings are shown until the next round starts.
+pub struct Blitz {
+    /// Number of the current (or just ended) round, starting at one.
+    round: u32,
+    state: RoundState,
+}
+
+enum RoundState {
+    Playing {
+        ends: Instant,
+    },
+    Ended {
+        next: Instant,
+        results: Arc<[LeaderboardDto]>,
+    },
+}
+
+/// Returned by [`Blitz::update`] when the round state changes.
+pub enum RoundTransition {
+    /// Contains the round that ended, and its final standings, best first.
+    Ended(u32, Arc<[LeaderboardDto]>),
+    /// Contains the round that started.
+    Started(u32),
+}
+
+impl Blitz {
+    /// How long each round lasts.
+    pub const ROUND: Duration = Duration::from_secs(10 * 60);
+    /// How long final standings are shown before the next round starts.
+    pub const INTERMISSION: Duration = Duration::from_secs(20);
+    /// How much faster armaments reload.
+    pub const RELOAD_MULTIPLIER: f32 = 2.0;
+    /// How much faster score is gained.
+    pub const SCORE_MULTIPLIER: u32 = 3;
+    /// How many players are included in final standings.
+    pub const RESULTS_MAX: usize = 10;
+
+    /// Starts the first round.
+    pub fn new() -> Self {
+        Self {
+            round: 1,
+            state: RoundState::Playing {
+                ends: Instant::now() + Self::ROUND,
+            },
+        }
+    }
+
+    /// Whether a round is in progress, as opposed to between rounds.
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, RoundState::Playing { .. })
+    }
+
+    /// Advances the state machine, calling `standings` for the final standings (in any order) if
+    /// the round ends.
+    pub fn update(
+        &mut self,
+        standings: impl FnOnce() -> Vec<LeaderboardDto>,
+    ) -> Option<RoundTransition> {
+        let now = Instant::now();
+        match self.state {
+            RoundState::Playing { ends } if now >= ends => {
+                let mut results = standings();
+                results.sort_unstable_by(|a, b| b.cmp(a));
+                results.truncate(Self::RESULTS_MAX);
+                let results: Arc<[LeaderboardDto]> = results.into();
+                self.state = RoundState::Ended {
+                    next: now + Self::INTERMISSION,
+                    results: Arc::clone(&results),
+                };
+                Some(RoundTransition::Ended(self.round, results))
+            }
+            RoundState::Ended { next, .. } if now >= next => {
+                self.round = self.round.wrapping_add(1);
+                self.state = RoundState::Playing {
+                    ends: now + Self::ROUND,
+                };
+                Some(RoundTransition::Started(self.round))
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the round, as sent to clients.
+    pub fn round(&self) -> Round {
+        let (until, results) = match &self.state {
+            RoundState::Playing { ends } => (*ends, None),
+            RoundState::Ended { next, results } => (*next, Some(Arc::clone(results))),
+        };
+        Round {
+            number: self.round,
+            remaining: until
+                .saturating_duration_since(Instant::now())
+                .as_secs_f32()
+                .ceil() as u32,
+            results,
+        }
+    }
+}
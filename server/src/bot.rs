@@ -13,6 +13,7 @@ use common::guidance::Guidance;
 use common::protocol::*;
 use common::terrain;
 use common::terrain::Terrain;
+use common::ticks::Ticks;
 use common_util::range::gen_radius;
 use core_protocol::id::PlayerId;
 use game_server::game_service::{BotAction, GameArenaService};
@@ -334,6 +335,8 @@ impl Bot {
                     }),
                 pay: None,
                 hint: None,
+                depth: None,
+                doctrine: Doctrine::default(),
             });
 
             if rng.gen_bool(self.aggression as f64) && data.level < self.level_ambition {
@@ -342,7 +345,10 @@ impl Bot {
                     .upgrade_options(update.score(), true)
                     .choose(&mut rng)
                 {
-                    ret = Command::Upgrade(Upgrade { entity_type });
+                    ret = Command::Upgrade(Upgrade {
+                        entity_type,
+                        loadout: random_loadout(entity_type, &mut rng),
+                    });
                 }
             }
 
@@ -351,24 +357,27 @@ impl Bot {
             // Rage quit.
             BotAction::Quit
         } else {
+            let entity_type = EntityType::spawn_options(0, true)
+                .choose(&mut rng)
+                .expect("there must be at least one entity type to spawn as");
             BotAction::Some(Command::Spawn(Spawn {
-                entity_type: EntityType::spawn_options(0, true)
-                    .choose(&mut rng)
-                    .expect("there must be at least one entity type to spawn as"),
+                entity_type,
+                loadout: random_loadout(entity_type, &mut rng),
             }))
         }
     }
 }
 
 impl game_server::game_service::Bot<Server> for Bot {
-    type Input<'a> = CompleteRef<'a, impl Iterator<Item = ContactRef<'a>>>;
+    /// Like real players, bots only get an update once per tick.
+    type Input<'a> = Option<CompleteRef<'a, impl Iterator<Item = ContactRef<'a>>>>;
 
     fn get_input<'a>(
         server: &'a Server,
         player: &'a Arc<PlayerTuple<Server>>,
         _players: &'a PlayerRepo<Server>,
     ) -> Self::Input<'a> {
-        server.world.get_player_complete(player)
+        (server.delta != Ticks::ZERO).then(|| server.world.get_player_complete(player))
     }
 
     fn update(
@@ -377,6 +386,14 @@ impl game_server::game_service::Bot<Server> for Bot {
         player_id: PlayerId,
         _players: &PlayerRepo<Server>,
     ) -> BotAction<<Server as GameArenaService>::GameRequest> {
-        self.update(update, player_id)
+        match update {
+            Some(update) => self.update(update, player_id),
+            None => BotAction::None,
+        }
     }
 }
+
+/// Bots pick a loadout at random, so players meet all of them.
+fn random_loadout(entity_type: EntityType, rng: &mut ThreadRng) -> u8 {
+    rng.gen_range(0..entity_type.data().loadouts.len().max(1)) as u8
+}
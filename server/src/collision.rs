@@ -1,9 +1,65 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use common::angle::Angle;
 use common::transform::Transform;
 use glam::Vec2;
 
+/// Coefficient of restitution of boat vs. boat collisions: 0 means boats stick together, and 1
+/// means they bounce apart without losing any energy.
+const RAM_RESTITUTION: f32 = 0.3;
+/// Change in velocity (in meters per second) that results in the base amount of ram damage.
+const RAM_REFERENCE_SPEED: f32 = 10.0;
+/// Ram damage multiplier of a boat that is touching, but not being jolted by, another boat.
+const RAM_MIN_DAMAGE: f32 = 0.25;
+const RAM_MAX_DAMAGE: f32 = 2.0;
+/// Maximum change in direction of a boat due to a single collision.
+const RAM_MAX_DEFLECTION_DEGREES: f32 = 25.0;
+
+/// hit_angle_cos returns the cosine of the angle between the direction a weapon is traveling and
+/// the direction its target is facing: -1 for head-on into the bow, 0 for broadside, and 1 for
+/// into the stern.
+pub fn hit_angle_cos(target: Transform, weapon: Transform) -> f32 {
+    target.direction.to_vec().dot(weapon.direction.to_vec())
+}
+
+/// ram_impulse returns the change in velocity (in meters per second) of a boat that collides with
+/// another boat, given both of their (approximate) masses and velocities, and the collision normal
+/// (pointing away from the other boat). Only velocity along the normal is exchanged, so glancing
+/// collisions transfer little momentum.
+pub fn ram_impulse(
+    mass: f32,
+    velocity: Vec2,
+    other_mass: f32,
+    other_velocity: Vec2,
+    normal: Vec2,
+) -> Vec2 {
+    let closing_speed = (other_velocity - velocity).dot(normal);
+    if closing_speed <= 0.0 {
+        // Already separating.
+        return Vec2::ZERO;
+    }
+    let reduced_mass = mass * other_mass / (mass + other_mass);
+    let momentum = (1.0 + RAM_RESTITUTION) * closing_speed * reduced_mass;
+    normal * (momentum / mass)
+}
+
+/// ram_damage_multiplier scales ram damage by how violently a boat was jolted, as returned by
+/// [`ram_impulse`].
+pub fn ram_damage_multiplier(impulse: Vec2) -> f32 {
+    (impulse.length() / RAM_REFERENCE_SPEED).clamp(RAM_MIN_DAMAGE, RAM_MAX_DAMAGE)
+}
+
+/// ram_deflection returns how much a boat, traveling in the direction it is facing at the given
+/// speed (in meters per second), turns due to the sideways component of an impulse.
+pub fn ram_deflection(direction: Angle, speed: f32, impulse: Vec2) -> Angle {
+    let sideways = impulse.dot(direction.to_vec().perp());
+    // Slow boats would otherwise spin around, even from slight bumps.
+    let speed = speed.abs().max(RAM_REFERENCE_SPEED).copysign(speed);
+    let deflection = Angle::from_radians((sideways / speed).atan());
+    deflection.clamp_magnitude(Angle::from_degrees(RAM_MAX_DEFLECTION_DEGREES))
+}
+
 /// radius_collision performs a simple radius check. This is faster but less accurate than SAT.
 pub fn radius_collision(
     transform: Transform,
@@ -130,3 +186,41 @@ fn sat_collision_half(
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::collision::{ram_damage_multiplier, ram_deflection, ram_impulse};
+    use common::angle::Angle;
+    use glam::Vec2;
+
+    #[test]
+    fn ram_head_on() {
+        // A heavy boat rams a stationary light boat.
+        let light = ram_impulse(1.0, Vec2::ZERO, 9.0, Vec2::X * 10.0, Vec2::X);
+        let heavy = ram_impulse(9.0, Vec2::X * 10.0, 1.0, Vec2::ZERO, -Vec2::X);
+
+        // Momentum is conserved.
+        assert!((light * 1.0 + heavy * 9.0).length() < 0.001);
+        // The light boat is jolted more, so takes more damage.
+        assert!(light.x > 0.0 && heavy.x < 0.0);
+        assert!(ram_damage_multiplier(light) > ram_damage_multiplier(heavy));
+    }
+
+    #[test]
+    fn ram_glancing() {
+        let head_on = ram_impulse(1.0, Vec2::ZERO, 1.0, Vec2::X * 10.0, Vec2::X);
+        let glancing = ram_impulse(1.0, Vec2::ZERO, 1.0, Vec2::new(10.0, 1.0), Vec2::Y);
+        assert!(ram_damage_multiplier(glancing) < ram_damage_multiplier(head_on));
+
+        // Separating boats don't exchange momentum.
+        assert_eq!(
+            ram_impulse(1.0, Vec2::ZERO, 1.0, -Vec2::X * 10.0, Vec2::X),
+            Vec2::ZERO
+        );
+
+        // Being pushed to the left (while facing right) turns the boat to the left.
+        let deflection = ram_deflection(Angle::ZERO, 10.0, Vec2::Y * 5.0);
+        assert!(deflection > Angle::ZERO);
+        assert!(deflection <= Angle::from_degrees(25.0));
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::contact_acks::ContactAcks;
 use crate::contact_ref::ContactRef;
 use crate::player::Status;
 use crate::server::Server;
@@ -9,14 +10,16 @@ use atomic_refcell::AtomicRef;
 use common::complete::CompleteTrait;
 use common::contact::ContactTrait;
 use common::death_reason::DeathReason;
-use common::protocol::Update;
+use common::entity::EntitySubKind;
+use common::guidance::Guidance;
+use common::interest::Interest;
+use common::protocol::{Shipwreck, Update};
 use common::terrain;
 use common::terrain::{ChunkSet, Terrain};
 use common::ticks::{Ticks, TicksRepr};
 use common::velocity::Velocity;
 use game_server::player::PlayerData;
 use glam::Vec2;
-use std::ops::RangeInclusive;
 
 /// A "Complete" server to client update that references world data to avoid additional allocation.
 pub struct CompleteRef<'a, I: Iterator<Item = ContactRef<'a>>> {
@@ -26,15 +29,24 @@ pub struct CompleteRef<'a, I: Iterator<Item = ContactRef<'a>>> {
     world: &'a World,
     camera_pos: Vec2,
     camera_dims: Vec2,
+    /// View range, determining [`Interest`] in contacts.
+    camera_view: f32,
 }
 
 impl<'a, I: Iterator<Item = ContactRef<'a>>> CompleteRef<'a, I> {
+    /// In low bandwidth mode, contacts further than this fraction of the camera's smaller dimension
+    /// are treated like ones that aren't [`Interest::Near`]: they are only sent as often as needed
+    /// to keep them alive, with quantized positions and without guidance, so the client
+    /// extrapolates their positions in between.
+    const LOW_BANDWIDTH_NEAR: f32 = 0.25;
+
     pub fn new(
         contacts: I,
         player: AtomicRef<'a, PlayerData<Server>>,
         world: &'a World,
         camera_pos: Vec2,
         camera_dims: Vec2,
+        camera_view: f32,
     ) -> Self {
         Self {
             contacts: Some(contacts),
@@ -42,10 +54,59 @@ impl<'a, I: Iterator<Item = ContactRef<'a>>> CompleteRef<'a, I> {
             world,
             camera_pos,
             camera_dims,
+            camera_view,
+        }
+    }
+
+    /// Shipwrecks the player's submarine can detect, if any.
+    fn shipwrecks(&self) -> Box<[Shipwreck]> {
+        let entity = match self.player.data.status {
+            Status::Alive { entity_index, .. } => &self.world.entities[entity_index],
+            _ => return Box::new([]),
+        };
+        let data = entity.data();
+        if data.sub_kind != EntitySubKind::Submarine {
+            return Box::new([]);
         }
+
+        self.world
+            .shipwrecks_near(
+                entity.transform.position,
+                data.sensors.sonar.range,
+                Some(entity.id),
+            )
+            .map(|(position, salvage)| Shipwreck { position, salvage })
+            .collect()
+    }
+
+    /// Positions of active sonar pings the player can hear, other than their own.
+    fn pings(&self) -> Box<[Vec2]> {
+        let own_id = match self.player.data.status {
+            Status::Alive { entity_index, .. } => Some(self.world.entities[entity_index].id),
+            _ => None,
+        };
+
+        self.world
+            .pings
+            .iter()
+            .filter(|ping| {
+                Some(ping.entity_id) != own_id
+                    && ping.position.distance_squared(self.camera_pos) < ping.range.powi(2)
+            })
+            .map(|ping| ping.position)
+            .collect()
     }
 
-    pub fn into_update(self, counter: Ticks, loaded_chunks: &mut ChunkSet) -> Update {
+    /// Contacts are sent periodically, depending on [`Interest`] in them, or if they were sent in
+    /// an update that `acks` deems lost. Each update may cover multiple ticks, `delta`, in which
+    /// case contacts due on any of them are sent.
+    pub fn into_update(
+        mut self,
+        counter: Ticks,
+        delta: Ticks,
+        loaded_chunks: &mut ChunkSet,
+        acks: &mut ContactAcks,
+    ) -> Update {
         let death_reason = if let Status::Dead { reason, .. } = &self.player.data.status {
             Some(reason.clone())
         } else {
@@ -85,35 +146,115 @@ impl<'a, I: Iterator<Item = ContactRef<'a>>> CompleteRef<'a, I> {
 
         *loaded_chunks = new_loaded_chunks;
 
-        Update {
-            contacts: self
-                .contacts
-                .unwrap()
-                .filter_map(|contact| {
-                    let modulus = if let Some(entity_type) = contact.entity_type() {
-                        let range: RangeInclusive<Ticks> = entity_type.data().kind.keep_alive();
-
-                        if contact.transform().velocity.abs() > Velocity::from_mps(1.0) {
-                            // Send more often if moving.
-                            *range.start()
-                        } else {
-                            *range.end()
-                        }
+        let low_bandwidth = self.player.data.hint.low_bandwidth;
+        let near_squared = (self.camera_dims.min_element() * Self::LOW_BANDWIDTH_NEAR).powi(2);
+        let camera_pos = self.camera_pos;
+        let camera_view = self.camera_view;
+        let player_id = self.player.player_id;
+
+        let sequence = acks.next_sequence();
+        let mut sent = Vec::new();
+        let contacts = self
+            .contacts
+            .take()
+            .unwrap()
+            .filter_map(|contact| {
+                let distance_squared = contact.transform().position.distance_squared(camera_pos);
+                let interest = if contact.player_id() == Some(player_id) {
+                    // E.g. the player's boat, while they control an aircraft far away.
+                    Interest::Near
+                } else {
+                    Interest::of(distance_squared, camera_view)
+                };
+
+                let far = interest != Interest::Near
+                    || (low_bandwidth && distance_squared > near_squared);
+
+                let modulus = if let Some(entity_type) = contact.entity_type() {
+                    let kind = entity_type.data().kind;
+                    if contact.transform().velocity.abs() > Velocity::from_mps(1.0) && !far {
+                        // Send more often if moving.
+                        *kind.keep_alive().start()
                     } else {
-                        Ticks::from_repr(5)
-                    };
-
-                    let send = counter
-                        .wrapping_add(Ticks::from_repr(contact.id().get() as TicksRepr))
-                        % (modulus + Ticks::ONE)
-                        == Ticks::ZERO;
-                    send.then(|| contact.into_contact())
+                        interest.keep_alive(Some(kind))
+                    }
+                } else {
+                    Ticks::from_repr(5)
+                };
+
+                let send = counter.wrapping_add(Ticks::from_repr(contact.id().get() as TicksRepr))
+                    % (modulus + Ticks::ONE)
+                    < delta;
+                (send || acks.is_lost(contact.id())).then(|| {
+                    sent.push(contact.id());
+                    let mut contact = contact.into_contact();
+                    if far {
+                        let transform = contact.transform_mut();
+                        // Even if near, but outside the low bandwidth radius.
+                        transform.position =
+                            interest.max(Interest::Far).quantize(transform.position);
+                        // Without guidance, the client extrapolates in a straight line.
+                        let guidance = Guidance {
+                            direction_target: transform.direction,
+                            velocity_target: transform.velocity,
+                        };
+                        contact.predict_guidance(&guidance);
+                    }
+                    contact
                 })
-                .collect(),
+            })
+            .collect();
+        acks.record_sent(sequence, sent);
+
+        Update {
+            contacts,
             death_reason,
             score: self.player.score,
+            can_respec: matches!(
+                self.player.data.status,
+                Status::Alive {
+                    respecced: false,
+                    ..
+                }
+            ),
+            loadout: match self.player.data.status {
+                Status::Alive { entity_index, .. } => {
+                    self.world.entities[entity_index].extension().loadout()
+                }
+                _ => 0,
+            },
             world_radius: self.world.radius,
+            season: self.world.season,
+            biome: self.world.biome,
             terrain,
+            currents: None,
+            sinkings: self
+                .world
+                .sinkings
+                .iter()
+                .filter(|sinking| sinking.reason.is_due_to_player())
+                .cloned()
+                .collect(),
+            smuggling: self.world.smuggling(),
+            entity_overrides: None,
+            shipwrecks: self.shipwrecks(),
+            zones: self.world.zones(),
+            pings: if low_bandwidth {
+                Box::new([])
+            } else {
+                self.pings()
+            },
+            sequence,
+            controlled_aircraft: match self.player.data.status {
+                Status::Alive {
+                    controlled_aircraft,
+                    ..
+                } => controlled_aircraft,
+                _ => None,
+            },
+            round: self.world.blitz.as_ref().map(|blitz| blitz.round()),
+            missions: None,
+            stats: None,
         }
     }
 }
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::entity::EntityId;
+use common::protocol::Ack;
+use std::collections::{HashSet, VecDeque};
+
+/// Tracks which contacts were sent in which updates, and which of those updates the client
+/// acknowledged, so that contacts from lost updates can be resent right away instead of whenever
+/// they would next be sent.
+#[derive(Debug, Default)]
+pub struct ContactAcks {
+    /// Sequence of the most recent update.
+    sequence: u32,
+    /// Contacts sent in each update that hasn't been acknowledged (or deemed lost), oldest first.
+    unacknowledged: VecDeque<(u32, Vec<EntityId>)>,
+    /// Contacts that were sent in lost updates, and must be resent.
+    lost: HashSet<EntityId>,
+}
+
+impl ContactAcks {
+    /// Updates this far behind the most recently acknowledged one are lost, not just reordered.
+    const LOSS_THRESHOLD: u32 = 3;
+    /// Stop tracking updates after this many (the client may not be sending acks).
+    const MAX_UNACKNOWLEDGED: usize = 32;
+
+    /// Gets the sequence for the next update.
+    pub fn next_sequence(&mut self) -> u32 {
+        self.sequence = self.sequence.wrapping_add(1);
+        self.sequence
+    }
+
+    /// Returns true if the contact was sent in a lost update.
+    pub fn is_lost(&self, id: EntityId) -> bool {
+        self.lost.contains(&id)
+    }
+
+    /// Records which contacts were sent in the update with `sequence`, which supersedes any need
+    /// to resend them.
+    pub fn record_sent(&mut self, sequence: u32, ids: Vec<EntityId>) {
+        for id in &ids {
+            self.lost.remove(id);
+        }
+        if self.unacknowledged.len() >= Self::MAX_UNACKNOWLEDGED {
+            self.unacknowledged.pop_front();
+        }
+        self.unacknowledged.push_back((sequence, ids));
+    }
+
+    /// Processes the client's most recent acknowledgement. Idempotent.
+    pub fn acknowledge(&mut self, ack: Ack) {
+        let lost = &mut self.lost;
+        self.unacknowledged.retain(|(sequence, ids)| {
+            if ack.contains(*sequence) {
+                false
+            } else if sequence.saturating_add(Self::LOSS_THRESHOLD) <= ack.sequence {
+                lost.extend(ids.iter().copied());
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact_acks::ContactAcks;
+    use common::entity::EntityId;
+    use common::protocol::Ack;
+
+    #[test]
+    fn contact_acks() {
+        let mut acks = ContactAcks::default();
+        let id = |n| EntityId::new(n).unwrap();
+        let mut ack = Ack::default();
+
+        for n in 1..=5 {
+            let sequence = acks.next_sequence();
+            acks.record_sent(sequence, vec![id(n)]);
+            // Update 2 is lost.
+            if sequence != 2 {
+                ack.receive(sequence);
+            }
+        }
+
+        acks.acknowledge(ack);
+        assert!(acks.is_lost(id(2)));
+        assert!(!acks.is_lost(id(1)) && !acks.is_lost(id(5)));
+
+        // Resending clears it.
+        let sequence = acks.next_sequence();
+        acks.record_sent(sequence, vec![id(2)]);
+        assert!(!acks.is_lost(id(2)));
+    }
+}
@@ -8,8 +8,7 @@ use common::angle::Angle;
 use common::contact::{
     Contact, ContactTrait, ReloadsStorage, ANGLE_ARRAY_ZERO, RELOADS_ARRAY_ZERO,
 };
-use common::entity::EntityId;
-use common::entity::EntityType;
+use common::entity::{DisabledModules, EntityId, EntityType, StatusEffects};
 use common::guidance::Guidance;
 use common::ticks::Ticks;
 use common::transform::Transform;
@@ -20,12 +19,21 @@ use std::sync::Arc;
 pub struct ContactRef<'a> {
     entity: &'a Entity,
     has_type: bool,
+    relayed: bool,
     reloads: Option<BitArray<ReloadsStorage>>,
 }
 
 impl<'a> ContactRef<'a> {
     /// Creates a new `ContactRef`, referencing an entity, and having certain visibility parameters.
-    pub fn new(entity: &'a Entity, visible: bool, known: bool, has_type: bool) -> Self {
+    /// Relayed contacts never have a type.
+    pub fn new(
+        entity: &'a Entity,
+        visible: bool,
+        known: bool,
+        has_type: bool,
+        relayed: bool,
+    ) -> Self {
+        let has_type = has_type && !relayed;
         let reloads = (has_type && entity.is_boat() && (visible || known)).then(|| {
             let reloads = &*entity.extension().reloads;
             let mut arr = BitArray::ZERO;
@@ -42,6 +50,7 @@ impl<'a> ContactRef<'a> {
         Self {
             entity,
             has_type,
+            relayed,
             reloads,
         }
     }
@@ -51,10 +60,13 @@ impl<'a> ContactRef<'a> {
         Contact::new(
             self.altitude(),
             self.damage(),
+            self.disabled(),
+            self.effects(),
             self.entity_type(),
             *self.guidance(),
             self.id(),
             self.player_id(),
+            self.relayed,
             self.reloads,
             *self.transform(),
             self.turrets_arc().cloned(),
@@ -86,6 +98,25 @@ impl<'a> ContactTrait for ContactRef<'a> {
         }
     }
 
+    #[inline]
+    fn disabled(&self) -> DisabledModules {
+        if self.turrets_known() {
+            self.entity.extension().disabled()
+        } else {
+            DisabledModules::default()
+        }
+    }
+
+    #[inline]
+    fn effects(&self) -> StatusEffects {
+        // Smoke and flooding are visible to anyone who can tell it's a boat.
+        if self.is_boat() {
+            self.entity.extension().effects()
+        } else {
+            StatusEffects::default()
+        }
+    }
+
     #[inline]
     fn entity_type(&self) -> Option<EntityType> {
         if self.has_type {
@@ -113,6 +144,11 @@ impl<'a> ContactTrait for ContactRef<'a> {
             .map(|p| p.borrow_player().player_id)
     }
 
+    #[inline]
+    fn relayed(&self) -> bool {
+        self.relayed
+    }
+
     #[inline]
     fn reloads(&self) -> &BitSlice<ReloadsStorage> {
         self.reloads
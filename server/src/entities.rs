@@ -10,8 +10,13 @@ use maybe_parallel_iterator::{
 use std::convert::{TryFrom, TryInto};
 use std::ops::{Index, IndexMut, RangeInclusive};
 
-const SIZE: usize = 32 * common::world::SIZE;
-const SCALE: f32 = 800.0;
+/// Sectors along each side. The sectors form a uniform grid, which is also the collision broad
+/// phase (see [`Entities::iter_radius`]).
+const SIZE: usize = 64 * common::world::SIZE;
+/// Side length of a sector, in meters. Small enough that scanning for collisions, which is done by
+/// every entity every tick, considers few others, yet large enough that entities rarely move
+/// between sectors and sensor range scans don't visit too many sectors.
+const SCALE: f32 = 400.0;
 
 /// An efficient collection of entities.
 pub struct Entities {
@@ -58,49 +63,42 @@ impl SectorId {
         result
     }
 
-    /// Gets center of sector with id.
-    fn center(&self) -> Vec2 {
-        let mut pos = Vec2::new(self.0 as f32, self.1 as f32);
-        pos *= SCALE;
-        pos += SIZE as f32 * SCALE / -2.0 + SCALE / 2.0;
-        debug_assert_eq!(*self, Self::try_from(pos).unwrap());
-        pos
-    }
-
-    /// Returns true if any part of self is within radius of position.
-    fn in_radius(&self, position: Vec2, radius: f32) -> bool {
-        const HALF: f32 = SCALE / 2.0;
-        let abs_diff = (self.center() - position).abs();
-        if abs_diff.x > HALF + radius || abs_diff.y > HALF + radius {
-            false
-        } else if abs_diff.x <= HALF || abs_diff.y <= HALF {
-            true
-        } else {
-            (abs_diff - HALF).max(Vec2::ZERO).length_squared() < radius.powi(2)
-        }
+    /// Iterates all `SectorId`s in a circle, one column at a time, visiting only those within
+    /// the circle's extent along that column.
+    fn iter_radius(center: Vec2, radius: f32) -> impl Iterator<Item = Self> {
+        let start = Self::saturating_from(center - radius);
+        let end = Self::saturating_from(center + radius);
+        // Range inclusive is slow so add 1.
+        (start.0..end.0 + 1).flat_map(move |x| {
+            // Horizontal distance from the center to the nearest point of the column.
+            let min_x = Self::min_coordinate(x);
+            let dx = (min_x - center.x).max(center.x - (min_x + SCALE)).max(0.0);
+            let half_height = (radius.powi(2) - dx.powi(2)).max(0.0).sqrt();
+            let start_y = Self::saturating_coordinate(center.y - half_height);
+            let end_y = Self::saturating_coordinate(center.y + half_height);
+            (start_y..end_y + 1).map(move |y| Self(x, y))
+        })
     }
 
-    /// Iterates all `SectorId`s in a rectangle defined by corners start and end.
-    fn iter(start: Self, end: Self) -> impl Iterator<Item = Self> {
-        // Range inclusive is slow so add 1.
-        (start.0..end.0 + 1).flat_map(move |x| (start.1..end.1 + 1).map(move |y| Self(x, y)))
+    /// Returns the lowest x or y coordinate of sectors with an x or y of `i`.
+    fn min_coordinate(i: u8) -> f32 {
+        i as f32 * SCALE - SIZE as f32 * SCALE / 2.0
     }
 
-    /// Iterates all `SectorId`s in a circle.
-    fn iter_radius(center: Vec2, radius: f32) -> impl Iterator<Item = Self> {
-        let start = Self::saturating_from(center - radius);
-        let end = Self::saturating_from(center + radius);
-        Self::iter(start, end).filter(move |id| id.in_radius(center, radius))
+    /// Returns the x or y of the sectors containing an x or y coordinate, clamped to the dimensions
+    /// of the data structure.
+    fn saturating_coordinate(coordinate: f32) -> u8 {
+        let i = coordinate * (1.0 / SCALE) + SIZE as f32 / 2.0;
+        (i as i32).clamp(0, (SIZE - 1) as i32) as u8
     }
 
     /// Returns the `SectorId` containing pos, with pos being clamped to the dimensions of the data
     /// structure.
-    fn saturating_from(mut pos: Vec2) -> Self {
-        pos *= 1.0 / (SCALE);
-        pos += SIZE as f32 / 2.0;
-        let x = (pos.x as i32).clamp(0, (SIZE - 1) as i32) as u8;
-        let y = (pos.y as i32).clamp(0, (SIZE - 1) as i32) as u8;
-        Self(x, y)
+    fn saturating_from(pos: Vec2) -> Self {
+        Self(
+            Self::saturating_coordinate(pos.x),
+            Self::saturating_coordinate(pos.y),
+        )
     }
 }
 
@@ -278,3 +276,49 @@ impl IndexMut<EntityIndex> for Entities {
         &mut self.mut_sector(i.0).entities[i.1 as usize]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::{SectorId, SCALE, SIZE};
+    use glam::Vec2;
+    use rand::{thread_rng, Rng};
+
+    /// Tests that iterating the sectors in a circle visits each sector that overlaps it once, and
+    /// no others.
+    #[test]
+    fn sector_iter_radius() {
+        let mut rng = thread_rng();
+        let half_size = SIZE as f32 * SCALE / 2.0;
+
+        for _ in 0..100 {
+            let center = Vec2::new(
+                rng.gen_range(-half_size..half_size),
+                rng.gen_range(-half_size..half_size),
+            );
+            let radius = rng.gen_range(0.0..SCALE * 8.0);
+
+            let mut visited: Vec<SectorId> = SectorId::iter_radius(center, radius).collect();
+            let count = visited.len();
+            visited.sort_unstable();
+            visited.dedup();
+            assert_eq!(visited.len(), count, "visited a sector twice");
+
+            for index in 0..SIZE * SIZE {
+                let id = SectorId::from_sector_index(index);
+                let min = Vec2::new(
+                    SectorId::min_coordinate(id.0),
+                    SectorId::min_coordinate(id.1),
+                );
+                let distance = center.clamp(min, min + SCALE).distance(center);
+                let was_visited = visited.binary_search(&id).is_ok();
+
+                // Allow for rounding at the edge of the circle.
+                if distance < radius - 0.01 {
+                    assert!(was_visited, "{:?} {} missed {:?}", center, radius, id);
+                } else if distance > radius + 0.01 {
+                    assert!(!was_visited, "{:?} {} visited {:?}", center, radius, id);
+                }
+            }
+        }
+    }
+}
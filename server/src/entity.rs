@@ -13,10 +13,12 @@ use common::angle::Angle;
 use common::death_reason::DeathReason;
 use common::entity::*;
 use common::guidance::Guidance;
+use common::stats::LifeStats;
 use common::terrain::*;
 use common::ticks::{Ticks, TicksRepr};
 use common::transform::{DimensionTransform, Transform};
 use common::util::hash_u32_to_f32;
+use common_util::range::map_ranges;
 use game_server::player::{PlayerData, PlayerTuple};
 use glam::Vec2;
 use std::ptr;
@@ -39,6 +41,11 @@ pub struct Entity {
     /// When it represents damage, it is less than or equal to self.data().max_health(). Otherwise,
     /// it represents lifetime (for entities with finite lifespan).
     pub ticks: Ticks,
+    /// Ticks that have elapsed without being simulated, due to being far from any real player.
+    /// See [`SimulationLod`][`crate::simulation_lod::SimulationLod`].
+    pub unsimulated: Ticks,
+    /// Like [`Self::unsimulated`], but in seconds, which may include a fraction of a tick.
+    pub unsimulated_seconds: f32,
 }
 
 /// unset_entity_id returns a nonexistent id that will be overwritten by world.add
@@ -57,6 +64,8 @@ impl Entity {
             altitude: Altitude::ZERO,
             player,
             ticks: Ticks::ZERO,
+            unsimulated: Ticks::ZERO,
+            unsimulated_seconds: 0.0,
         }
     }
 
@@ -118,10 +127,11 @@ impl Entity {
         let extension = self.extension_mut();
 
         // Keep time until armaments reload. Use u32 to avoid overflow.
-        // Start by counting the total ticks left to reload (for non-limited armaments).
+        // Start by counting the total ticks left to reload (for non-limited armaments in the
+        // loadout).
         let mut total_reload = 0;
         for (i, reload) in extension.reloads.iter().enumerate() {
-            if !old_data.armaments[i].entity_type.data().limited {
+            if !old_data.armaments[i].entity_type.data().limited && *reload != Ticks::MAX {
                 total_reload += reload.0 as u32;
             }
         }
@@ -132,10 +142,10 @@ impl Entity {
         // Change the extension to correspond with the new type.
         extension.change_entity_type(entity_type);
 
-        // Finish by (un)reloading.
+        // Finish by (un)reloading, skipping armaments outside the loadout.
         for (i, reload) in extension.reloads_mut().iter_mut().enumerate() {
             let armament = &new_data.armaments[i];
-            if !armament.entity_type.data().limited {
+            if !armament.entity_type.data().limited && *reload != Ticks::MAX {
                 let to_consume = (armament.reload().0 as u32).min(total_reload);
                 *reload = Ticks::from_repr(to_consume as TicksRepr);
                 total_reload -= to_consume;
@@ -160,13 +170,16 @@ impl Entity {
         assert!(!player.data.status.is_alive());
         player.data.status = Status::new_alive(i);
 
-        // Clear flags when player's boat is spawned.
+        // Clear flags and statistics when player's boat is spawned.
         player.data.flags = Flags::default();
+        player.data.stats = LifeStats::default();
         drop(player);
 
         // Change entity type (allocate turrets/reloads).
         let entity_type = self.entity_type;
-        self.extension_mut().change_entity_type(entity_type);
+        let extension = self.extension_mut();
+        extension.change_entity_type(entity_type);
+        extension.start_spawn_shield();
     }
 
     /// Adjusts player's pointer to self, if applicable.
@@ -239,6 +252,13 @@ impl Entity {
         self.entity_type.data().kind == EntityKind::Boat
     }
 
+    /// Returns true if aircraft can spot (and therefore attack) this entity. Submerged boats are
+    /// only visible at periscope depth or shortly after cavitating.
+    pub fn is_visible_to_aircraft(&self) -> bool {
+        self.altitude.is_periscope_depth()
+            || (self.is_boat() && self.extension().recently_cavitated())
+    }
+
     /// Returns if this entity is owned by a real player (not a bot, not ownerless).
     /// For printing debug info without being too verbose (including bots).
     #[cfg(debug_assertions)]
@@ -369,11 +389,15 @@ impl Entity {
     /// Reloads arbitrary armaments/groups by a certain amount.
     pub fn reload(&mut self, amount: Ticks) {
         let armaments = &self.data().armaments;
+        let disabled = self.extension().disabled();
         let reloads = self.extension_mut().reloads_mut();
         if reloads.is_empty() {
             return;
         }
 
+        // Knocked out turrets don't reload.
+        let frozen = |armament: &Armament| armament.turret.map_or(false, |t| disabled.turret(t));
+
         // Split reloads into ranges of similar armaments to reload in parallel.
         let mut current = &armaments[0];
         let mut start = 0;
@@ -382,13 +406,17 @@ impl Entity {
             if next.is_similar_to(current) {
                 continue;
             }
-            Self::reload_range(&mut reloads[start..end], amount);
+            if !frozen(current) {
+                Self::reload_range(&mut reloads[start..end], amount);
+            }
             current = next;
             start = end;
         }
 
         // Final iteration
-        Self::reload_range(&mut reloads[start..], amount);
+        if !frozen(current) {
+            Self::reload_range(&mut reloads[start..], amount);
+        }
     }
 
     fn reload_range(reloads: &mut [Ticks], mut amount: Ticks) {
@@ -427,12 +455,23 @@ impl Entity {
         self.damage(delta * (self.data().max_health() / kill_time).max(Ticks::ONE))
     }
 
+    /// Returns false if the entity is a torpedo that hasn't run long enough to arm, which takes
+    /// longer at depth (giving deep submarines some protection from torpedoes fired up close).
+    pub fn is_armed(&self) -> bool {
+        if self.data().sub_kind != EntitySubKind::Torpedo {
+            return true;
+        }
+        let arming_time = map_ranges(self.altitude.to_meters(), 0.0..-180.0, 0.5..2.0, true);
+        self.ticks >= Ticks::from_secs(arming_time)
+    }
+
     /// Returns true if the entity obeys special altitude mechanics (overlaps a wider altitude range),
     /// which is useful for unguided weapons that, were they not able to hit certain targets, would be
-    /// underpowered.
+    /// underpowered. Nets also hang deep enough to catch torpedoes at a wider range of altitudes.
     fn special_altitude_overlap(&self) -> bool {
         let data = self.data();
-        data.sub_kind == EntitySubKind::Torpedo && !data.sensors.any()
+        (data.sub_kind == EntitySubKind::Torpedo && !data.sensors.any())
+            || data.sub_kind == EntitySubKind::Net
     }
 
     /// Returns true if two entities are overlapping, only taking into account their altitudes.
@@ -516,7 +555,7 @@ impl Entity {
             EntityKind::Weapon => match data.sub_kind {
                 EntitySubKind::Torpedo => target.unwrap_or(-unguided_weapon_altitude),
                 EntitySubKind::DepthCharge => Altitude::MIN, // Sink to bottom.
-                EntitySubKind::Mine => -unguided_weapon_altitude,
+                EntitySubKind::Mine | EntitySubKind::Net => -unguided_weapon_altitude,
                 EntitySubKind::Shell
                 | EntitySubKind::Rocket
                 | EntitySubKind::RocketTorpedo
@@ -591,7 +630,10 @@ impl Entity {
         let boat_data = boat.data();
         let boat_extension = boat.extension();
         for (i, armament) in boat_data.armaments.iter().enumerate() {
-            if armament.entity_type != self.entity_type || !data.limited {
+            if armament.entity_type != self.entity_type
+                || !data.limited
+                || !armament.in_loadout(boat_extension.loadout())
+            {
                 // Irrelevant armament.
                 continue;
             }
@@ -4,9 +4,11 @@
 use common::altitude::Altitude;
 use common::angle::Angle;
 use common::entity::*;
+use common::protocol::Doctrine;
 use common::ticks::Ticks;
 use common::util::make_mut_slice;
 use common_util::alloc::{arc_default_n, box_default_n};
+use rand::Rng;
 use std::iter::FromIterator;
 use std::sync::Arc;
 
@@ -18,6 +20,8 @@ pub struct EntityExtension {
     // Can't submerge right away to prevent dodging missiles.
     submerge: bool,
     submerge_delay: Ticks,
+    /// Target altitude while submerged, or [`None`] for the test depth.
+    depth_target: Option<Altitude>,
 
     /// Whether the player *wants* active sensors. To tell if the player *has* active sensors, use
     /// Used by Self::is_active().
@@ -25,8 +29,35 @@ pub struct EntityExtension {
     active: bool,
     deactivate_delay: Ticks,
 
+    /// Ticks remaining that the boat can be spotted by aircraft due to cavitating while submerged.
+    cavitation_remaining: Ticks,
+
+    /// What anti-aircraft guns engage.
+    doctrine: Doctrine,
+    /// Ticks remaining that the boat is easier to see due to firing anti-aircraft guns.
+    anti_aircraft_remaining: Ticks,
+
     /// Ticks of protection ticks remaining, zeroed if showing signs of aggression.
     spawn_protection_remaining: Ticks,
+    /// Ticks of complete invulnerability remaining after spawning, zeroed upon first input.
+    spawn_shield_remaining: Ticks,
+
+    /// Ticks remaining until propulsion is repaired after a critical hit.
+    propulsion_disabled: Ticks,
+    /// Ticks remaining until steering is repaired after a critical hit.
+    steering_disabled: Ticks,
+    /// Ticks remaining until each turret is repaired after a critical hit.
+    turrets_disabled: Box<[Ticks]>,
+
+    /// Ticks remaining until a fire started by a shell burns out.
+    fire_remaining: Ticks,
+    /// Ticks of pumping remaining until flooding caused by a torpedo is under control. Only
+    /// counts down while moving slowly (see [`Self::pump_flooding`]).
+    flooding_remaining: Ticks,
+
+    /// Index into [`EntityData::loadouts`]. Armaments outside of it are permanently unavailable
+    /// (reload of [`Ticks::MAX`]).
+    loadout: u8,
 
     // 1 reload per armament, 0 = reloaded.
     // Not an arc because converted to a bitset with max len of 32.
@@ -40,11 +71,29 @@ pub struct EntityExtension {
 impl EntityExtension {
     /// How long spawn protection lasts (it linearly fades over this time).
     const SPAWN_PROTECTION_INITIAL: Ticks = Ticks::from_whole_secs(20);
+    /// How long boats are invulnerable after spawning, unless they move or fire first.
+    const SPAWN_SHIELD: Ticks = Ticks::from_whole_secs(3);
 
     /// How long deactivating sensors is delayed.
     const DEACTIVATE_DELAY: Ticks = Ticks::from_repr(5);
     /// How long submerging is delayed.
     const SUBMERGE_DELAY: Ticks = Ticks::from_repr(8);
+    /// How long cavitation bubbles remain visible to aircraft.
+    const CAVITATION_DURATION: Ticks = Ticks::from_whole_secs(5);
+    /// How long anti-aircraft fire remains visible.
+    const ANTI_AIRCRAFT_DURATION: Ticks = Ticks::from_whole_secs(3);
+    /// How long a module stays knocked out after a critical hit.
+    const CRITICAL_DURATION: Ticks = Ticks::from_whole_secs(8);
+    /// How long a fire burns.
+    const FIRE_DURATION: Ticks = Ticks::from_whole_secs(10);
+    /// How long flooding takes to pump out.
+    const FLOODING_DURATION: Ticks = Ticks::from_whole_secs(6);
+    /// How long a fire would take to sink a boat from full health.
+    pub const FIRE_KILL_TIME: Ticks = Ticks::from_whole_secs(60);
+    /// How long flooding would take to sink a boat from full health.
+    pub const FLOODING_KILL_TIME: Ticks = Ticks::from_whole_secs(45);
+    /// Flooding is only pumped out below this fraction of top speed.
+    pub const FLOODING_PUMP_SPEED: f32 = 0.3;
 
     /// Allocates reloads and turrets, sized to a particular entity type.
     /// It can also give spawn protection.
@@ -57,14 +106,44 @@ impl EntityExtension {
         } else {
             Ticks::ZERO
         };
-        self.reloads = box_default_n(data.armaments.len());
+        self.reloads = data
+            .armaments
+            .iter()
+            .map(|armament| {
+                if armament.in_loadout(self.loadout) {
+                    Ticks::ZERO
+                } else {
+                    Ticks::MAX
+                }
+            })
+            .collect();
         self.turrets = Arc::from_iter(data.turrets.iter().map(|t| t.angle));
+        self.propulsion_disabled = Ticks::ZERO;
+        self.steering_disabled = Ticks::ZERO;
+        self.turrets_disabled = box_default_n(data.turrets.len());
+        self.fire_remaining = Ticks::ZERO;
+        self.flooding_remaining = Ticks::ZERO;
+    }
+
+    /// Armament loadout, as an index into [`EntityData::loadouts`].
+    pub fn loadout(&self) -> u8 {
+        self.loadout
     }
 
-    /// Returns the target altitude of the boat from submerge.
-    pub fn altitude_target(&self) -> Altitude {
+    /// Selects the armament loadout, which takes effect upon the next call to
+    /// [`Self::change_entity_type`]. Out of range loadouts fall back to the default.
+    pub fn set_loadout(&mut self, loadout: u8, entity_type: EntityType) {
+        self.loadout = if (loadout as usize) < entity_type.data().loadouts.len() {
+            loadout
+        } else {
+            0
+        };
+    }
+
+    /// Returns the target altitude of the boat from submerge and depth target.
+    pub fn altitude_target(&self, data: &EntityData) -> Altitude {
         if self.submerge && self.submerge_delay == Ticks::ZERO {
-            Altitude::MIN
+            self.depth_target.unwrap_or(-data.test_depth())
         } else {
             Altitude::ZERO
         }
@@ -78,6 +157,11 @@ impl EntityExtension {
         self.submerge = submerge;
     }
 
+    /// Sets the target altitude while submerged, which may be below the test depth.
+    pub fn set_depth_target(&mut self, depth_target: Option<Altitude>) {
+        self.depth_target = depth_target.map(|d| d.min(Altitude::ZERO));
+    }
+
     /// Returns whether active sensors, or within deactivate sensor delay.
     pub fn is_active(&self) -> bool {
         self.active || self.deactivate_delay > Ticks::ZERO
@@ -91,8 +175,41 @@ impl EntityExtension {
         self.active = active;
     }
 
+    /// Call while cavitating, to leave a trail of bubbles that aircraft can spot.
+    pub fn set_cavitating(&mut self) {
+        self.cavitation_remaining = Self::CAVITATION_DURATION;
+    }
+
+    /// Returns whether cavitated recently enough to still be spotted by aircraft.
+    pub fn recently_cavitated(&self) -> bool {
+        self.cavitation_remaining > Ticks::ZERO
+    }
+
+    /// Returns what anti-aircraft guns engage.
+    pub fn doctrine(&self) -> Doctrine {
+        self.doctrine
+    }
+
+    /// Sets what anti-aircraft guns engage.
+    pub fn set_doctrine(&mut self, doctrine: Doctrine) {
+        self.doctrine = doctrine;
+    }
+
+    /// Call while firing anti-aircraft guns, which makes the boat easier to see.
+    pub fn set_firing_anti_aircraft(&mut self) {
+        self.anti_aircraft_remaining = Self::ANTI_AIRCRAFT_DURATION;
+    }
+
+    /// Returns whether fired anti-aircraft guns recently enough to still be easier to see.
+    pub fn recently_fired_anti_aircraft(&self) -> bool {
+        self.anti_aircraft_remaining > Ticks::ZERO
+    }
+
     /// Returns a multiplier for damage taken, taking into account spawn protection.
     pub fn spawn_protection(&self) -> f32 {
+        if self.spawn_shield_remaining > Ticks::ZERO {
+            return 0.0;
+        }
         (Self::SPAWN_PROTECTION_INITIAL - self.spawn_protection_remaining).to_secs()
             / Self::SPAWN_PROTECTION_INITIAL.to_secs()
     }
@@ -101,16 +218,103 @@ impl EntityExtension {
     /// no longer deserving of spawn protection).
     pub fn clear_spawn_protection(&mut self) {
         self.spawn_protection_remaining = Ticks::ZERO;
+        self.clear_spawn_shield();
+    }
+
+    /// Makes a newly spawned boat invulnerable for a few seconds.
+    pub fn start_spawn_shield(&mut self) {
+        self.spawn_shield_remaining = Self::SPAWN_SHIELD;
+    }
+
+    /// Clears the post-spawn invulnerability (useful once the player starts playing).
+    pub fn clear_spawn_shield(&mut self) {
+        self.spawn_shield_remaining = Ticks::ZERO;
+    }
+
+    /// Returns which modules are knocked out.
+    pub fn disabled(&self) -> DisabledModules {
+        let mut disabled = DisabledModules::default();
+        disabled.set_propulsion(self.propulsion_disabled > Ticks::ZERO);
+        disabled.set_steering(self.steering_disabled > Ticks::ZERO);
+        for (i, &remaining) in self.turrets_disabled.iter().enumerate() {
+            disabled.set_turret(i, remaining > Ticks::ZERO);
+        }
+        disabled
+    }
+
+    /// Returns the chance that a hit dealing the given fraction of max health is critical, knocking
+    /// out a module.
+    pub fn critical_chance(damage_fraction: f32) -> f32 {
+        (damage_fraction * 1.5).clamp(0.0, 0.5)
+    }
+
+    /// Knocks out a random module (propulsion, steering, or one of the turrets) for a while.
+    pub fn critical_hit(&mut self, rng: &mut impl Rng) {
+        let turrets = self
+            .turrets_disabled
+            .len()
+            .min(DisabledModules::MAX_TURRETS);
+        let remaining = match rng.gen_range(0..2 + turrets) {
+            0 => &mut self.propulsion_disabled,
+            1 => &mut self.steering_disabled,
+            i => &mut self.turrets_disabled[i - 2],
+        };
+        *remaining = Self::CRITICAL_DURATION;
+    }
+
+    /// Returns which damage over time effects are active.
+    pub fn effects(&self) -> StatusEffects {
+        let mut effects = StatusEffects::default();
+        effects.set_fire(self.fire_remaining > Ticks::ZERO);
+        effects.set_flooding(self.flooding_remaining > Ticks::ZERO);
+        effects
+    }
+
+    /// Returns the chance that a hit dealing the given fraction of max health starts a fire (if
+    /// from a shell) or flooding (if from a torpedo).
+    pub fn effect_chance(damage_fraction: f32) -> f32 {
+        (damage_fraction * 2.0).clamp(0.0, 0.6)
+    }
+
+    /// Starts a fire, or restarts one that is already burning.
+    pub fn ignite(&mut self) {
+        self.fire_remaining = Self::FIRE_DURATION;
+    }
+
+    /// Starts flooding, or restarts flooding that is being pumped out.
+    pub fn flood(&mut self) {
+        self.flooding_remaining = Self::FLOODING_DURATION;
+    }
+
+    /// Call while moving slowly enough to pump out flooding.
+    pub fn pump_flooding(&mut self, delta: Ticks) {
+        self.flooding_remaining = self.flooding_remaining.saturating_sub(delta);
     }
 
     /// Subtracts from the player's tickers:
     /// submerge
     /// deactivate_delay
+    /// cavitation_remaining
+    /// anti_aircraft_remaining
     /// spawn_protection_remaining
+    /// spawn_shield_remaining
+    /// propulsion_disabled
+    /// steering_disabled
+    /// turrets_disabled
+    /// fire_remaining
     pub fn update_tickers(&mut self, delta: Ticks) {
         self.submerge_delay = self.submerge_delay.saturating_sub(delta);
         self.deactivate_delay = self.deactivate_delay.saturating_sub(delta);
+        self.cavitation_remaining = self.cavitation_remaining.saturating_sub(delta);
+        self.anti_aircraft_remaining = self.anti_aircraft_remaining.saturating_sub(delta);
         self.spawn_protection_remaining = self.spawn_protection_remaining.saturating_sub(delta);
+        self.spawn_shield_remaining = self.spawn_shield_remaining.saturating_sub(delta);
+        self.propulsion_disabled = self.propulsion_disabled.saturating_sub(delta);
+        self.steering_disabled = self.steering_disabled.saturating_sub(delta);
+        for remaining in self.turrets_disabled.iter_mut() {
+            *remaining = remaining.saturating_sub(delta);
+        }
+        self.fire_remaining = self.fire_remaining.saturating_sub(delta);
     }
 
     /// reloads_mut returns a mutable reference to the reloads component of the extension.
@@ -131,9 +335,20 @@ impl Default for EntityExtension {
         Self {
             submerge: false,
             submerge_delay: Ticks::ZERO,
+            depth_target: None,
             active: true,
             deactivate_delay: Ticks::ZERO,
+            cavitation_remaining: Ticks::ZERO,
+            doctrine: Doctrine::default(),
+            anti_aircraft_remaining: Ticks::ZERO,
             spawn_protection_remaining: Self::SPAWN_PROTECTION_INITIAL,
+            spawn_shield_remaining: Ticks::ZERO,
+            propulsion_disabled: Ticks::ZERO,
+            steering_disabled: Ticks::ZERO,
+            turrets_disabled: box_default_n(0),
+            fire_remaining: Ticks::ZERO,
+            flooding_remaining: Ticks::ZERO,
+            loadout: 0,
             reloads: box_default_n(0),
             turrets: arc_default_n(0),
         }
@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use common::entity::{EntityId, EntityType};
+use common::ticks::Ticks;
+use glam::Vec2;
+use std::collections::VecDeque;
+
+/// Recent boat positions, so weapon fire can be lag compensated (aimed relative to what the
+/// firing player saw, as opposed to where things are by the time the command arrives).
+#[derive(Default)]
+pub struct History {
+    /// Oldest first. Each snapshot is sorted by id.
+    snapshots: VecDeque<Box<[(EntityId, EntityType, Vec2)]>>,
+}
+
+impl History {
+    /// Never rewind further than this, so high latency doesn't allow shooting too far into
+    /// the past.
+    pub const MAX_REWIND: Ticks = Ticks::from_whole_millis(800);
+
+    /// Records the positions of boats as of the current tick.
+    pub fn record(&mut self, boats: impl Iterator<Item = (EntityId, EntityType, Vec2)>) {
+        let mut snapshot: Box<[_]> = boats.collect();
+        snapshot.sort_unstable_by_key(|(id, _, _)| *id);
+
+        if self.snapshots.len() > Self::MAX_REWIND.0 as usize {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Translates an aim target to account for how far the boat the player was aiming at has
+    /// moved in the last `rewind` ticks. Returns the original aim if it wasn't near a boat.
+    pub fn compensate_aim(&self, aim: Vec2, rewind: Ticks, shooter: EntityId) -> Vec2 {
+        let rewind = rewind.min(Self::MAX_REWIND).0 as usize;
+        if rewind == 0 || self.snapshots.is_empty() {
+            return aim;
+        }
+        let current = self.snapshots.back().unwrap();
+        let past = &self.snapshots[self.snapshots.len().saturating_sub(rewind + 1)];
+
+        // The boat that was under (or closest to) the aim, from the shooter's perspective.
+        let target = past
+            .iter()
+            .filter(|(id, entity_type, position)| {
+                *id != shooter && position.distance_squared(aim) < entity_type.data().radius.powi(2)
+            })
+            .min_by(|(_, _, a), (_, _, b)| {
+                a.distance_squared(aim)
+                    .partial_cmp(&b.distance_squared(aim))
+                    .unwrap()
+            });
+
+        if let Some(&(id, _, past_position)) = target {
+            if let Ok(i) = current.binary_search_by_key(&id, |(id, _, _)| *id) {
+                return aim + (current[i].2 - past_position);
+            }
+        }
+        aim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::History;
+    use common::entity::{EntityId, EntityType};
+    use common::ticks::Ticks;
+    use glam::Vec2;
+
+    #[test]
+    fn compensate_aim() {
+        let shooter = EntityId::new(1).unwrap();
+        let target = EntityId::new(2).unwrap();
+
+        let mut history = History::default();
+        for i in 0..20 {
+            history.record(
+                [
+                    (shooter, EntityType::G5, Vec2::ZERO),
+                    (target, EntityType::G5, Vec2::new(100.0, i as f32)),
+                ]
+                .into_iter(),
+            );
+        }
+
+        let aim = Vec2::new(100.0, 17.0);
+        assert_eq!(history.compensate_aim(aim, Ticks::ZERO, shooter), aim);
+        assert_eq!(
+            history.compensate_aim(aim, Ticks::from_repr(2), shooter),
+            Vec2::new(100.0, 19.0)
+        );
+        // Limited by MAX_REWIND.
+        let oldest = Vec2::new(100.0, 19.0 - History::MAX_REWIND.0 as f32);
+        assert_eq!(
+            history.compensate_aim(oldest, Ticks::from_repr(100), shooter),
+            Vec2::new(100.0, 19.0)
+        );
+        // Not aiming at anything.
+        let miss = Vec2::new(-500.0, 0.0);
+        assert_eq!(
+            history.compensate_aim(miss, Ticks::from_repr(2), shooter),
+            miss
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entities::EntityIndex;
+use crate::entity::Entity;
+use crate::player::Status;
+use crate::server::Server;
+use crate::world::World;
+use common::death_reason::DeathReason;
+use common::entity::EntityType;
+use common::ticks::Ticks;
+use common::util::{kill_score, level_to_score};
+use common::velocity::Velocity;
+use game_server::player::{PlayerRepo, PlayerTuple};
+use glam::Vec2;
+use log::info;
+use rand::seq::IteratorRandom;
+use rand::{thread_rng, Rng};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A rare boss event, in which a player (or a bot, if no player is available) becomes a kraken
+/// that attacks nearby boats with its tentacles. Every player that damages the kraken gets a share
+/// of the bounty when it sinks.
+#[derive(Default)]
+pub struct Kraken {
+    /// Waiting for a player to become the kraken.
+    pending: bool,
+    /// The player controlling the kraken, while it is alive.
+    player: Option<Arc<PlayerTuple<Server>>>,
+    /// Damage dealt to the kraken by each player, for splitting the bounty.
+    damage: Vec<(Arc<PlayerTuple<Server>>, Ticks)>,
+    /// Counts down to the next tentacle attack.
+    tentacle_cooldown: Ticks,
+}
+
+impl Kraken {
+    /// Average time between krakens.
+    const MEAN_INTERVAL: Ticks = Ticks::from_whole_secs(30 * 60);
+    /// Fewer real players than this couldn't hope to sink a kraken.
+    const MIN_REAL_PLAYERS: usize = 5;
+    /// Real players must have been dead this long to be chosen, so they aren't yanked out of the
+    /// respawn screen the moment they sink.
+    const MIN_DEAD: Duration = Duration::from_secs(5);
+    /// Split between every player that damaged the kraken.
+    const BOUNTY: u32 = level_to_score(6);
+    /// How far tentacles reach beyond the kraken's radius.
+    const TENTACLE_REACH: f32 = 60.0;
+    /// How many boats can be attacked at once.
+    const TENTACLE_COUNT: usize = 3;
+    const TENTACLE_PERIOD: Ticks = Ticks::from_whole_secs(2);
+    const TENTACLE_DAMAGE: Ticks = Ticks::from_whole_secs(30);
+    /// Boats in the grip of a tentacle can't go faster than this (in meters per second).
+    const TENTACLE_GRIP: f32 = 2.0;
+
+    /// Whether a player is currently the kraken.
+    pub fn is_alive(&self) -> bool {
+        self.player.is_some()
+    }
+
+    /// Starts, runs, and attacks with the kraken. Call once per tick, after the world updates.
+    pub fn update(world: &mut World, players: &PlayerRepo<Server>, delta: Ticks) {
+        if let Some(player) = world.kraken.player.clone() {
+            let kraken_index = match player.borrow_player().data.status {
+                Status::Alive { entity_index, .. } => entity_index,
+                // Sinking is handled by on_death.
+                _ => return,
+            };
+
+            world.kraken.tentacle_cooldown = world.kraken.tentacle_cooldown.saturating_sub(delta);
+            if world.kraken.tentacle_cooldown == Ticks::ZERO {
+                world.kraken.tentacle_cooldown = Self::TENTACLE_PERIOD;
+                Self::attack(world, kraken_index, &player);
+            }
+            return;
+        }
+
+        let real_players = players.iter_borrow().filter(|p| !p.is_bot()).count();
+        if real_players < Self::MIN_REAL_PLAYERS {
+            world.kraken.pending = false;
+            return;
+        }
+
+        let mut rng = thread_rng();
+        if !world.kraken.pending {
+            world.kraken.pending =
+                rng.gen_bool((delta.to_secs() / Self::MEAN_INTERVAL.to_secs()) as f64);
+            if !world.kraken.pending {
+                return;
+            }
+        }
+
+        // Prefer letting a real player be the kraken.
+        let available = |player: &Arc<PlayerTuple<Server>>| {
+            let player = player.borrow_player();
+            !player.data.flags.left_game
+                && match &player.data.status {
+                    Status::Alive { .. } => false,
+                    Status::Dead { time, .. } => player.is_bot() || time.elapsed() > Self::MIN_DEAD,
+                    Status::Spawning => player.is_bot(),
+                }
+        };
+        let chosen = players
+            .iter()
+            .filter(|p| !p.borrow_player().is_bot() && available(p))
+            .choose(&mut rng)
+            .or_else(|| {
+                players
+                    .iter()
+                    .filter(|p| p.borrow_player().is_bot() && available(p))
+                    .choose(&mut rng)
+            });
+
+        let player = if let Some(player) = chosen {
+            Arc::clone(player)
+        } else {
+            return;
+        };
+
+        // Appear near a random real player, where there are boats to fight.
+        let center = players
+            .iter_borrow()
+            .filter(|p| !p.is_bot())
+            .filter_map(|p| match p.data.status {
+                Status::Alive { entity_index, .. } => {
+                    Some(world.entities[entity_index].transform.position)
+                }
+                _ => None,
+            })
+            .choose(&mut rng)
+            .unwrap_or(Vec2::ZERO);
+
+        let mut kraken = Entity::new(EntityType::Kraken, Some(Arc::clone(&player)));
+        kraken.transform.position = center;
+        if world.spawn_here_or_nearby(kraken, 1000.0, Some(center)) {
+            info!("kraken spawned for {:?}", player.borrow_player().player_id);
+            world.kraken.pending = false;
+            world.kraken.player = Some(player);
+            world.kraken.tentacle_cooldown = Self::TENTACLE_PERIOD;
+        }
+    }
+
+    /// Damages and grips the closest boats within reach of the kraken's tentacles.
+    fn attack(world: &mut World, kraken_index: EntityIndex, player: &Arc<PlayerTuple<Server>>) {
+        let kraken = &world.entities[kraken_index];
+        let position = kraken.transform.position;
+        let reach = kraken.data().radius + Self::TENTACLE_REACH;
+        let team_id = player.borrow_player().team_id();
+
+        let mut targets: Vec<(EntityIndex, f32)> = world
+            .entities
+            .iter_radius(position, reach)
+            .filter(|(i, e)| {
+                *i != kraken_index
+                    && e.is_boat()
+                    && (team_id.is_none() || e.borrow_player().team_id() != team_id)
+            })
+            .map(|(i, e)| (i, e.transform.position.distance_squared(position)))
+            .collect();
+        targets.sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        targets.truncate(Self::TENTACLE_COUNT);
+
+        // Removing a boat swaps the last entity of its sector into its index, so go from the
+        // highest index to the lowest.
+        targets.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+
+        for (index, _) in targets {
+            let entity = &mut world.entities[index];
+            let damage = Self::TENTACLE_DAMAGE * entity.extension().spawn_protection();
+            if entity.damage(damage) {
+                let killer_alias = {
+                    let e_score = entity.borrow_player().score;
+                    let mut player = player.borrow_player_mut();
+                    player.score += kill_score(e_score, player.score);
+                    player.alias()
+                };
+                world.remove(index, DeathReason::Weapon(killer_alias, EntityType::Kraken));
+            } else {
+                entity.transform.velocity = entity
+                    .transform
+                    .velocity
+                    .clamp_magnitude(Velocity::from_mps(Self::TENTACLE_GRIP));
+            }
+        }
+    }
+
+    /// Records damage dealt to the kraken by `player`.
+    pub fn record_damage(&mut self, player: &Arc<PlayerTuple<Server>>, damage: Ticks) {
+        if let Some((_, total)) = self.damage.iter_mut().find(|(p, _)| Arc::ptr_eq(p, player)) {
+            *total = total.saturating_add(damage);
+        } else {
+            self.damage.push((Arc::clone(player), damage));
+        }
+    }
+
+    /// Called by on_world_remove after the kraken sinks. `score` is its player's score before it
+    /// sank, which is restored since being the kraken shouldn't cost anything.
+    pub fn on_death(world: &mut World, score: u32, reason: &DeathReason) {
+        if let Some(player) = world.kraken.player.take() {
+            player.borrow_player_mut().score = score;
+        }
+
+        let damage = std::mem::take(&mut world.kraken.damage);
+        if !reason.is_due_to_player() {
+            return;
+        }
+
+        let total: f32 = damage.iter().map(|(_, d)| d.to_secs()).sum();
+        if total <= 0.0 {
+            return;
+        }
+        for (player, d) in damage {
+            let share = (Self::BOUNTY as f32 * d.to_secs() / total) as u32;
+            player.borrow_player_mut().score += share;
+        }
+    }
+}
@@ -8,10 +8,14 @@
 #![feature(hash_drain_filter)]
 #![feature(type_alias_impl_trait)]
 #![feature(generic_associated_types)]
+#![feature(test)]
 
 //! The game server has authority over all game logic. Clients are served the client, which connects
 //! via websocket.
 
+#[cfg(test)]
+extern crate test;
+
 use crate::server::Server;
 use common::entity::EntityType;
 
@@ -19,21 +23,38 @@ mod arena;
 mod bot;
 mod collision;
 mod complete_ref;
+mod contact_acks;
 mod contact_ref;
 mod entities;
 mod entity;
 mod entity_extension;
+mod history;
+mod kraken;
 mod noise;
 mod player;
 mod protocol;
 mod server;
+mod simulation_lod;
 mod world;
+mod world_aircraft;
+mod world_audit;
+mod world_blitz;
+mod world_dying_shot;
 mod world_inbound;
+mod world_inspect;
 mod world_mutation;
 mod world_outbound;
 mod world_physics;
 mod world_physics_radius;
+mod world_region;
+mod world_season;
+mod world_sensors;
+mod world_shipwreck;
+mod world_smuggling;
 mod world_spawn;
+mod world_thumbnail;
+mod world_wreckage;
+mod world_zone;
 #[cfg(test)]
 mod world_test;
 
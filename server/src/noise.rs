@@ -1,16 +1,80 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use common::terrain;
+use common::region::Region;
+use common::terrain::{self, Coord};
+use common::world::Biome;
 use common_util::range::map_ranges;
+use glam::Vec2;
 use noise::{NoiseFn, SuperSimplex};
+use std::collections::HashMap;
 use std::mem::MaybeUninit;
 
 static mut NOISE: MaybeUninit<SuperSimplex> = MaybeUninit::uninit();
 
-/// Mutable so that many seeds can be tested (see tests).
+/// Mutable so that many seeds can be tested (see tests), and so each season can have a new world
+/// (see [`set_seed`]).
 pub static mut SEED: f64 = 42700.0;
 
+/// Only set before terrain is generated (see [`set_biome`]).
+static mut BIOME: Biome = Biome::Temperate;
+
+/// Handcrafted terrain that overrides noise (see [`set_region`]).
+static mut REGION: Option<HashMap<Coord, u8>> = None;
+
+/// Sets the biome of all subsequently generated terrain. Must be called before any terrain is
+/// generated, and never concurrently with terrain generation.
+pub fn set_biome(biome: Biome) {
+    unsafe { BIOME = biome }
+}
+
+/// Sets the seed of all subsequently generated terrain. Never call concurrently with terrain
+/// generation.
+pub fn set_seed(seed: f64) {
+    unsafe { SEED = seed }
+}
+
+/// Stamps a handcrafted region onto all subsequently generated terrain, including terrain that
+/// regenerates after being damaged. Must be called before any terrain is generated, and never
+/// concurrently with terrain generation.
+pub fn set_region(region: &Region) {
+    unsafe { REGION = Some(region.terrain_values()) }
+}
+
+pub fn biome() -> Biome {
+    unsafe { BIOME }
+}
+
+/// How a [`Biome`] affects temperate terrain.
+struct BiomeParams {
+    /// Added to height before the temperate land is scaled (positive = more land).
+    land: f64,
+    /// Multiplies the frequency of temperate noise (higher = smaller islands).
+    frequency: f64,
+    /// Whether to carve narrow channels through land.
+    channels: bool,
+    /// How far south of the arctic ice sheets may form (in terrain units).
+    ice_reach: isize,
+}
+
+impl BiomeParams {
+    fn new(biome: Biome) -> Self {
+        let (land, frequency, channels, ice_reach) = match biome {
+            Biome::Temperate => (0.0, 1.0, false, 0),
+            Biome::Archipelago => (0.04, 2.2, false, 0),
+            Biome::OpenOcean => (-0.12, 0.8, false, 0),
+            Biome::Fjord => (0.18, 0.7, true, 0),
+            Biome::IceShelf => (-0.04, 1.0, false, 60),
+        };
+        Self {
+            land,
+            frequency,
+            channels,
+            ice_reach,
+        }
+    }
+}
+
 pub fn init() {
     unsafe { NOISE = MaybeUninit::new(SuperSimplex::new()) }
 }
@@ -24,6 +88,13 @@ pub fn noise_generator(x: usize, y: usize) -> u8 {
     const ARCTIC_BLEND: f64 = 1.0 / 20.0;
     const TROPICS_BLEND: f64 = 1.0 / 10.0;
 
+    // Safety: Region is only set before terrain is generated (see [`set_region`]).
+    if let Some(&value) = unsafe { REGION.as_ref() }.and_then(|region| region.get(&Coord(x, y))) {
+        return value;
+    }
+
+    let params = BiomeParams::new(biome());
+
     // Distance from border of arctic (positive = arctic, negative = ocean).
     let arctic_distance = y as isize - terrain::ARCTIC as isize;
 
@@ -37,8 +108,8 @@ pub fn noise_generator(x: usize, y: usize) -> u8 {
     scale = scale.min((-tropics_distance as f64 * TROPICS_BLEND).clamp(0.0, 1.0));
 
     const S: f64 = terrain::SCALE as f64 * 0.0012;
-    // Safety: Seed is only ever modified for testing purposes, when there are no other threads
-    // accessing the terrain.
+    // Safety: Seed is only ever modified for testing purposes or between seasons, when there are
+    // no other threads accessing the terrain.
     let noise_x = x as f64 * S + unsafe { SEED };
     let noise_y = y as f64 * S;
 
@@ -47,11 +118,21 @@ pub fn noise_generator(x: usize, y: usize) -> u8 {
 
     // Don't waste time generating unused noise.
     if scale > 0.0001 {
-        height = fractal_noise(get_noise(), noise_x, noise_y, 4) * scale;
+        let f = params.frequency;
+        height = (fractal_noise(get_noise(), noise_x * f, noise_y * f, 4) + params.land) * scale;
+
+        if params.channels {
+            // Ridges of noise form long, narrow channels.
+            let c = fractal_noise(get_noise(), noise_x * 1.6 + 5000.0, noise_y * 1.6, 2).abs();
+            height = height.min(map_ranges(c as f32, 0.03..0.08, 0.2..1.0, true) as f64);
+        }
     }
 
-    if arctic_distance > 0 {
-        let ice_sheet = (arctic_distance as f64 * (1.0 / 40.0)).min(1.0);
+    // Distance from where ice sheets may form (positive = ice, negative = ocean).
+    let ice_distance = arctic_distance + params.ice_reach;
+
+    if ice_distance > 0 {
+        let ice_sheet = (ice_distance as f64 * (1.0 / 40.0)).min(1.0);
 
         let v = fractal_noise(get_noise(), noise_x * 0.35 + 1000.0, noise_y * 0.35, 4) * scale;
         let m = (v + 0.04).max(height + 0.25) - (1.0 - ice_sheet);
@@ -90,6 +171,32 @@ pub fn noise_generator(x: usize, y: usize) -> u8 {
     (height * 255.0) as u8
 }
 
+/// current generator returns the velocity of the ocean current (in meters per second) at a given
+/// world position. Currents are the curl of low frequency noise, so they swirl in large gyres
+/// instead of converging on (or diverging from) any one place.
+pub fn current_generator(position: Vec2) -> Vec2 {
+    // Much lower frequency than terrain, so gyres span several islands.
+    const S: f64 = 0.0012 * 0.15;
+    // Finite difference step, in noise units.
+    const H: f64 = 0.01;
+    // Meters per second per unit of noise gradient, such that typical currents are under a knot
+    // and the strongest approach the maximum (see `common::current::MAX_SPEED`).
+    const GAIN: f32 = 0.6;
+
+    // Safety: Seed is only ever modified for testing purposes or between seasons, when there are
+    // no other threads accessing the terrain.
+    let x = position.x as f64 * S + unsafe { SEED } + 7000.0;
+    let y = position.y as f64 * S;
+
+    let noise = get_noise();
+    let potential = |x: f64, y: f64| fractal_noise(noise, x, y, 2);
+    let dx = (potential(x + H, y) - potential(x - H, y)) * (0.5 / H);
+    let dy = (potential(x, y + H) - potential(x, y - H)) * (0.5 / H);
+
+    // Perpendicular to the gradient.
+    Vec2::new(dy as f32, -dx as f32) * GAIN
+}
+
 /// fractal noise returns multi-level noise for a given fractional coordinate.
 #[inline]
 fn fractal_noise(noise: &SuperSimplex, x: f64, y: f64, octaves: u32) -> f64 {
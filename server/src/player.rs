@@ -2,9 +2,17 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::entities::*;
+use crate::server::Server;
 use common::death_reason::DeathReason;
-use common::protocol::Hint;
+use common::entity::EntityId;
+use common::mission::{DailyMissions, Objective};
+use common::protocol::{Ack, Command, Control, Hint};
+use common::stats::LifeStats;
+use core_protocol::get_unix_time_now;
+use game_server::player::PlayerData;
+use game_server::streak::DailyStreak;
 use glam::Vec2;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::time::Instant;
 
@@ -36,6 +44,11 @@ pub enum Status {
         entity_index: EntityIndex,
         /// Where the player is aiming. Used by turrets and aircraft.
         aim_target: Option<Vec2>,
+        /// Aircraft steered by the player's controls, instead of their boat.
+        controlled_aircraft: Option<EntityId>,
+        /// Whether the player used their one free respec this life (see
+        /// [`EntityType::can_respec_to`][common::entity::EntityType::can_respec_to]).
+        respecced: bool,
     },
     /// Player had a boat.
     Dead {
@@ -57,6 +70,8 @@ impl Status {
         Self::Alive {
             entity_index,
             aim_target: None,
+            controlled_aircraft: None,
+            respecced: false,
         }
     }
 
@@ -97,8 +112,22 @@ pub struct Player {
     pub flags: Flags,
     /// Hints from client.
     pub hint: Hint,
+    /// Last control from client, which [`ControlDelta`][common::protocol::ControlDelta]s are
+    /// relative to.
+    pub control: Option<Control>,
+    /// Most recent acknowledgement of updates from client.
+    pub ack: Ack,
     /// Current status e.g. Alive, Dead, or Spawning.
     pub status: Status,
+    /// Daily missions, if any progress was made towards them. Real players only.
+    missions: Option<DailyMissions>,
+    /// Bonus score from completed missions, added to the player's score when their boat sinks
+    /// (i.e. their starting score for the next boat).
+    pub mission_bonus: u32,
+    /// Statistics about the current (or, if dead, last) boat.
+    pub stats: LifeStats,
+    /// Most recent commands, oldest first, for admins to inspect. Real players only.
+    pub recent_commands: VecDeque<String>,
 }
 
 impl Default for Player {
@@ -107,7 +136,56 @@ impl Default for Player {
         Self {
             flags: Flags::default(),
             hint: Hint::default(),
+            control: None,
+            ack: Ack::default(),
             status: Status::Spawning,
+            missions: None,
+            mission_bonus: 0,
+            stats: LifeStats::default(),
+            recent_commands: VecDeque::new(),
         }
     }
 }
+
+impl Player {
+    /// Oldest commands are discarded past this limit.
+    const MAX_RECENT_COMMANDS: usize = 32;
+
+    /// Records a command, for admins to inspect.
+    pub fn record_command(&mut self, command: &Command) {
+        if self.recent_commands.len() >= Self::MAX_RECENT_COMMANDS {
+            self.recent_commands.pop_front();
+        }
+        self.recent_commands
+            .push_back(format!("[{}] {:?}", get_unix_time_now(), command));
+    }
+
+    /// Today's missions and progress towards them, which reset at midnight (UTC).
+    pub fn daily_missions(&self, streak: &DailyStreak, today: u32) -> DailyMissions {
+        self.missions
+            .filter(|missions| missions.day == today)
+            .unwrap_or_else(|| DailyMissions::new(today, streak.length(today)))
+    }
+
+    /// Records progress towards today's missions. Completing all of them extends the streak,
+    /// which makes future missions more rewarding.
+    pub fn record_mission(&mut self, streak: &mut DailyStreak, objective: Objective, amount: u32) {
+        let today = DailyMissions::day(get_unix_time_now());
+        let missions = self.missions.insert(self.daily_missions(streak, today));
+        let reward = missions.record(objective, amount);
+        if reward > 0 && missions.is_complete() {
+            streak.extend(today);
+            missions.streak = streak.length(today);
+        }
+        self.mission_bonus += reward;
+    }
+}
+
+/// Records progress towards a real player's daily missions (see [`Player::record_mission`]).
+pub fn record_mission(player: &mut PlayerData<Server>, objective: Objective, amount: u32) {
+    if let Some(client) = player.client.as_deref_mut() {
+        player
+            .data
+            .record_mission(&mut client.streak, objective, amount);
+    }
+}
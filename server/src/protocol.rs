@@ -26,6 +26,10 @@ impl AsCommandTrait for Command {
             Command::Control(ref v) => v as &dyn CommandTrait,
             Command::Spawn(ref v) => v as &dyn CommandTrait,
             Command::Upgrade(ref v) => v as &dyn CommandTrait,
+            Command::ControlDelta(ref v) => v as &dyn CommandTrait,
+            Command::Ack(ref v) => v as &dyn CommandTrait,
+            Command::ControlAircraft(ref v) => v as &dyn CommandTrait,
+            Command::Purchase(ref v) => v as &dyn CommandTrait,
         }
     }
 }
@@ -1,22 +1,34 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::arena::{Blitz, RoundTransition};
 use crate::bot::*;
+use crate::contact_acks::ContactAcks;
 use crate::entity_extension::EntityExtension;
+use crate::kraken::Kraken;
 use crate::player::*;
 use crate::protocol::*;
 use crate::world::World;
-use common::entity::EntityType;
-use common::protocol::{Command, Update};
+use common::death_reason::DeathReason;
+use common::entity::{EntityData, EntityId, EntityKind, EntityOverrides, EntityType};
+use common::mission::DailyMissions;
+use common::protocol::{Ack, Command, Spawn, Update};
+use common::region::Region;
 use common::terrain::ChunkSet;
-use common::ticks::Ticks;
+use common::ticks::{Ticks, TicksRepr};
 use common::util::level_to_score;
+use common::world::{Biome, Season};
+use core_protocol::get_unix_time_now;
+use core_protocol::dto::{ArenaEvent, LeaderboardDto};
 use core_protocol::id::*;
 use game_server::context::Context;
-use game_server::game_service::GameArenaService;
+use game_server::game_service::{ArenaOptions, GameArenaService};
 use game_server::player::{PlayerRepo, PlayerTuple};
-use log::{error, warn};
+use glam::vec2;
+use image::RgbImage;
+use log::{error, info, warn};
 use std::cell::UnsafeCell;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -24,6 +36,25 @@ use std::time::Duration;
 pub struct Server {
     pub world: World,
     pub counter: Ticks,
+    /// Whole ticks that elapsed this update. More than one if running at a lower `--tick-rate`,
+    /// and sometimes zero if running at a higher one.
+    pub delta: Ticks,
+    /// Seconds simulated this update (see [`Context::tick_period`]).
+    pub delta_seconds: f32,
+    /// Fraction of a tick that was simulated, but has yet to count towards [`Self::delta`].
+    pub tick_fraction: f32,
+    /// Balance changes made by an admin.
+    pub entity_overrides: EntityOverrides,
+    /// Incremented whenever `entity_overrides` changes, so clients can be sent the changes.
+    pub entity_overrides_version: u32,
+    /// Incremented whenever the terrain is regenerated, so clients can be resent all of it.
+    pub terrain_version: u32,
+    /// Whether the kraken may spawn, as an arena option.
+    pub kraken: bool,
+    /// Whether smuggling runs occur, as an arena option.
+    pub smuggling: bool,
+    /// Whether capture zones exist, as an arena option.
+    pub zones: bool,
 }
 
 /// Stores a player, and metadata related to it. Data stored here may only be accessed when processing,
@@ -31,6 +62,25 @@ pub struct Server {
 #[derive(Default, Debug)]
 pub struct ClientData {
     pub loaded_chunks: ChunkSet,
+    /// The last [`Server::terrain_version`] sent.
+    pub terrain_version: u32,
+    /// The [`Server::terrain_version`] whose currents were last sent, if any.
+    pub currents_version: Option<u32>,
+    /// The last [`Server::entity_overrides_version`] sent.
+    pub entity_overrides_version: u32,
+    /// Which contacts were sent in which updates, to resend those from lost updates.
+    pub contact_acks: ContactAcks,
+    /// Found by [`World::audit_visibility`], oldest first.
+    pub visibility_discrepancies: VecDeque<String>,
+    /// The daily missions last sent.
+    pub missions: Option<DailyMissions>,
+    /// Whether statistics about the player's last boat were sent since it sank.
+    pub stats_sent: bool,
+}
+
+impl ClientData {
+    /// Oldest discrepancies are discarded past this limit.
+    const MAX_VISIBILITY_DISCREPANCIES: usize = 256;
 }
 
 #[derive(Default)]
@@ -40,12 +90,68 @@ pub struct PlayerExtension(pub UnsafeCell<EntityExtension>);
 unsafe impl Send for PlayerExtension {}
 unsafe impl Sync for PlayerExtension {}
 
+impl Server {
+    /// Ends and starts blitz rounds, if the arena is in blitz mode.
+    fn update_blitz(&mut self, context: &mut Context<Self>) {
+        let players = &context.players;
+        let transition = self.world.blitz.as_mut().and_then(|blitz| {
+            blitz.update(|| {
+                players
+                    .iter_borrow()
+                    .filter(|player| player.score > 0)
+                    .map(|player| LeaderboardDto {
+                        alias: player.alias(),
+                        score: player.score,
+                    })
+                    .collect()
+            })
+        });
+
+        match transition {
+            Some(RoundTransition::Ended(round, results)) => {
+                let final_scores: Vec<_> = players
+                    .iter_borrow()
+                    .map(|player| (player.player_id, player.score))
+                    .collect();
+                for (player_id, score) in final_scores {
+                    context.submit_final_score(player_id, score);
+                }
+
+                self.world.end_blitz_round();
+                // Everyone starts the next round from scratch.
+                for mut player in context.players.iter_borrow_mut() {
+                    player.score = 0;
+                }
+
+                if let Some(winner) = results.first() {
+                    context.announce(format!(
+                        "Round {} winner: {} ({})",
+                        round, winner.alias, winner.score
+                    ));
+                }
+                context.events.record(ArenaEvent::Game {
+                    player_id: None,
+                    text: format!("round {} ended: {:?}", round, results),
+                });
+            }
+            Some(RoundTransition::Started(round)) => {
+                context.announce(format!("Round {} has begun!", round));
+            }
+            None => {}
+        }
+    }
+}
+
 impl GameArenaService for Server {
     const GAME_ID: GameId = GameId::Mk48;
     const TICK_PERIOD_SECS: f32 = Ticks::PERIOD_SECS;
 
-    /// How long a player can remain in limbo after they lose connection.
-    const LIMBO: Duration = Duration::from_secs(6);
+    /// How long a player can remain in limbo after they lose connection. Long enough to ride out
+    /// a brief network outage, after which reconnecting (with the same session) reclaims the boat.
+    const LIMBO: Duration = Duration::from_secs(30);
+
+    /// World radius in meters, and highest boat level.
+    const PRIVATE_ARENA_OPTIONS: &'static [&'static str] = &["radius", "max_level"];
 
     //const TEAM_MEMBERS_MAX: usize = 2;
     //const TEAM_JOINERS_MAX: usize = 2;
@@ -58,12 +164,44 @@ impl GameArenaService for Server {
     type PlayerExtension = PlayerExtension;
 
     /// new returns a game server with the specified parameters.
-    fn new(min_players: usize) -> Self {
+    fn new(min_players: usize, arena_options: &ArenaOptions) -> Self {
+        // Must precede terrain generation.
+        crate::noise::set_biome(arena_options.get::<Biome>("biome").unwrap_or_default());
+
+        let mut world = World::new(World::target_radius(
+            min_players as f32 * EntityType::FairmileD.data().visual_area(),
+        ));
+        world.blitz = arena_options.get("blitz").unwrap_or(false).then(Blitz::new);
+        world.fixed_radius = arena_options
+            .get::<f32>("radius")
+            .filter(|radius| radius.is_finite());
+        if let Some(max_level) = arena_options.get::<u8>("max_level") {
+            world.max_level = max_level.clamp(1, EntityData::MAX_BOAT_LEVEL);
+        }
+
+        // A region overlay file, exported by the map editor.
+        if let Some(path) = arena_options.get::<String>("region") {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| Region::from_json(&json))
+            {
+                Ok(region) => world.stamp_region(region),
+                Err(e) => error!("could not load region {}: {}", path, e),
+            }
+        }
+
         Self {
-            world: World::new(World::target_radius(
-                min_players as f32 * EntityType::FairmileD.data().visual_area(),
-            )),
+            world,
             counter: Ticks::ZERO,
+            delta: Ticks::ONE,
+            delta_seconds: Ticks::ONE.to_secs(),
+            tick_fraction: 0.0,
+            entity_overrides: EntityOverrides::default(),
+            entity_overrides_version: 0,
+            terrain_version: 0,
+            kraken: arena_options.get("kraken").unwrap_or(true),
+            smuggling: arena_options.get("smuggling").unwrap_or(true),
+            zones: arena_options.get("zones").unwrap_or(true),
         }
     }
 
@@ -84,9 +222,10 @@ impl GameArenaService for Server {
     ) {
         let mut player = player_tuple.borrow_player_mut();
         player.data.flags.left_game = false;
+        // Client starts counting updates from scratch.
+        player.data.ack = Ack::default();
         #[cfg(debug_assertions)]
         {
-            use common::entity::EntityData;
             //use common::util::level_to_score;
             use rand::{thread_rng, Rng};
             let highest_level_score = level_to_score(EntityData::MAX_BOAT_LEVEL);
@@ -104,6 +243,12 @@ impl GameArenaService for Server {
         player: &Arc<PlayerTuple<Self>>,
         _players: &PlayerRepo<Server>,
     ) -> Option<Update> {
+        {
+            let mut player = player.borrow_player_mut();
+            if !player.is_bot() {
+                player.data.record_command(&update);
+            }
+        }
         if let Err(e) = update.as_command().apply(&mut self.world, player) {
             warn!("Command resulted in {}", e);
         }
@@ -147,17 +292,136 @@ impl GameArenaService for Server {
         player.data.flags.left_game = true;
     }
 
+    fn transfer_out(&mut self, player_tuple: &Arc<PlayerTuple<Self>>) -> Option<Vec<u8>> {
+        let player = player_tuple.borrow_player();
+        let entity_index = match player.data.status {
+            Status::Alive { entity_index, .. } if !player.data.flags.left_game => entity_index,
+            _ => return None,
+        };
+        drop(player);
+
+        // Respawn as the same boat on the other server.
+        let entity = &self.world.entities[entity_index];
+        let spawn = Spawn {
+            entity_type: entity.entity_type,
+            loadout: entity.extension().loadout(),
+        };
+
+        // Remove the boat as if the player left, so its score doesn't become coins.
+        player_tuple.borrow_player_mut().score = 0;
+        self.world.remove(entity_index, DeathReason::Unknown);
+
+        serde_json::to_vec(&spawn).ok()
+    }
+
+    fn transfer_in(
+        &mut self,
+        player_tuple: &Arc<PlayerTuple<Self>>,
+        progress: &[u8],
+        _players: &PlayerRepo<Server>,
+    ) {
+        let spawn: Spawn = match serde_json::from_slice(progress) {
+            Ok(spawn) => spawn,
+            Err(e) => {
+                warn!("invalid transfer progress: {}", e);
+                return;
+            }
+        };
+        // The player can still spawn manually (e.g. between rounds), having kept their score.
+        if let Err(e) = spawn.apply(&mut self.world, player_tuple) {
+            warn!("could not restore transferred boat: {}", e);
+        }
+    }
+
+    fn save_world(&self) -> Option<Vec<u8>> {
+        Some(self.world.save())
+    }
+
+    fn restore_world(&mut self, world: &[u8]) -> Result<(), &'static str> {
+        self.world.restore(world)
+    }
+
     fn get_game_update(
         &self,
         player: &Arc<PlayerTuple<Self>>,
         client_data: &mut Self::ClientData,
         _players: &PlayerRepo<Server>,
     ) -> Option<Self::GameUpdate> {
-        Some(
-            self.world
-                .get_player_complete(player)
-                .into_update(self.counter, &mut client_data.loaded_chunks),
-        )
+        // Clients expect one update per tick, so updates that didn't complete a tick are skipped.
+        if self.delta == Ticks::ZERO {
+            return None;
+        }
+
+        let ack = player.borrow_player().data.ack;
+        client_data.contact_acks.acknowledge(ack);
+
+        if client_data.terrain_version != self.terrain_version {
+            client_data.terrain_version = self.terrain_version;
+            client_data.loaded_chunks = ChunkSet::new();
+        }
+
+        let mut update = self.world.get_player_complete(player).into_update(
+            self.counter,
+            self.delta,
+            &mut client_data.loaded_chunks,
+            &mut client_data.contact_acks,
+        );
+
+        if client_data.currents_version != Some(self.terrain_version) {
+            client_data.currents_version = Some(self.terrain_version);
+            update.currents = Some(Arc::clone(&self.world.currents));
+        }
+
+        if client_data.entity_overrides_version != self.entity_overrides_version {
+            client_data.entity_overrides_version = self.entity_overrides_version;
+            update.entity_overrides = Some(self.entity_overrides.clone());
+        }
+
+        // Checked every update, since missions reset at midnight even without any progress.
+        let missions = {
+            let player = player.borrow_player();
+            let today = DailyMissions::day(get_unix_time_now());
+            player
+                .client()
+                .map(|client| player.data.daily_missions(&client.streak, today))
+        };
+        if client_data.missions != missions {
+            client_data.missions = missions;
+            update.missions = missions;
+        }
+
+        {
+            let player = player.borrow_player();
+            match player.data.status {
+                Status::Alive { .. } => client_data.stats_sent = false,
+                Status::Dead { .. } if !client_data.stats_sent => {
+                    client_data.stats_sent = true;
+                    update.stats = Some(player.data.stats.clone());
+                }
+                _ => {}
+            }
+        }
+
+        Some(update)
+    }
+
+    fn audit_visibility(
+        &self,
+        player_tuple: &Arc<PlayerTuple<Self>>,
+        update: &Self::GameUpdate,
+        client_data: &mut Self::ClientData,
+    ) {
+        let discrepancies = &mut client_data.visibility_discrepancies;
+        for discrepancy in self.world.audit_visibility(player_tuple, update) {
+            if discrepancies.len() >= ClientData::MAX_VISIBILITY_DISCREPANCIES {
+                discrepancies.pop_front();
+            }
+            discrepancies.push_back(format!("[{}] {}", get_unix_time_now(), discrepancy));
+        }
+    }
+
+    fn take_visibility_discrepancies(client_data: &mut Self::ClientData) -> Vec<String> {
+        client_data.visibility_discrepancies.drain(..).collect()
     }
 
     fn is_alive(&self, player_tuple: &Arc<PlayerTuple<Self>>) -> bool {
@@ -165,20 +429,202 @@ impl GameArenaService for Server {
         !player.data.flags.left_game && player.data.status.is_alive()
     }
 
+    fn snapshot(&self) -> Option<String> {
+        Some(self.world.snapshot())
+    }
+
+    fn inspect_entities(&self, x: f32, y: f32, radius: f32) -> Vec<String> {
+        self.world.inspect_entities(vec2(x, y), radius)
+    }
+
+    fn inspect_player(&self, player_tuple: &Arc<PlayerTuple<Self>>) -> Option<String> {
+        Some(self.world.inspect_player(player_tuple))
+    }
+
+    fn teleport_entity(&mut self, entity_id: u32, x: f32, y: f32) -> Result<(), &'static str> {
+        let id = EntityId::new(entity_id).ok_or("invalid entity id")?;
+        self.world.teleport(id, vec2(x, y))
+    }
+
+    fn delete_entity(&mut self, entity_id: u32) -> Result<(), &'static str> {
+        let id = EntityId::new(entity_id).ok_or("invalid entity id")?;
+        self.world.delete(id)
+    }
+
+    fn thumbnail(&self) -> Option<RgbImage> {
+        Some(self.world.thumbnail())
+    }
+
+    fn set_entity_overrides(&mut self, patch: &str) -> Result<(), &'static str> {
+        let entity_overrides: EntityOverrides = if patch.trim().is_empty() {
+            EntityOverrides::default()
+        } else {
+            serde_json::from_str(patch).map_err(|_| "invalid entity overrides")?
+        };
+        entity_overrides.validate()?;
+        entity_overrides.apply();
+        info!("entity overrides set to {:?}", entity_overrides);
+        self.entity_overrides = entity_overrides;
+        self.entity_overrides_version = self.entity_overrides_version.wrapping_add(1);
+        Ok(())
+    }
+
+    fn arena_options(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("blitz", self.world.blitz.is_some().to_string()),
+            ("kraken", self.kraken.to_string()),
+            ("smuggling", self.smuggling.to_string()),
+            ("zones", self.zones.to_string()),
+        ]
+    }
+
+    fn set_arena_option(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        if key == "blitz" {
+            let blitz: bool = value.parse().map_err(|_| "expected true or false")?;
+            if blitz != self.world.blitz.is_some() {
+                self.world.blitz = blitz.then(Blitz::new);
+            }
+            return Ok(());
+        }
+
+        let option = match key {
+            "kraken" => &mut self.kraken,
+            "smuggling" => &mut self.smuggling,
+            "zones" => &mut self.zones,
+            _ => return Err("unknown arena option"),
+        };
+        *option = value.parse().map_err(|_| "expected true or false")?;
+
+        if !self.zones {
+            self.world.zones.clear();
+        }
+        Ok(())
+    }
+
+    fn season_ended(&mut self, season: u32, _players: &PlayerRepo<Server>) {
+        self.world.reset_for_season(season.wrapping_add(1));
+        self.terrain_version = self.terrain_version.wrapping_add(1);
+    }
+
+    fn entity_counts(&self) -> Vec<(&'static str, usize)> {
+        vec![
+            ("aircraft", EntityKind::Aircraft),
+            ("boat", EntityKind::Boat),
+            ("collectible", EntityKind::Collectible),
+            ("decoy", EntityKind::Decoy),
+            ("obstacle", EntityKind::Obstacle),
+            ("weapon", EntityKind::Weapon),
+        ]
+        .into_iter()
+        .map(|(name, kind)| (name, self.world.arena.count_kind(kind)))
+        .collect()
+    }
+
     /// update runs server ticks.
     fn tick(&mut self, context: &mut Context<Self>) {
-        self.counter = self.counter.next();
+        // Ticks are the unit of game time, so the tick period may not be a whole number of them.
+        self.delta_seconds = context.tick_period().as_secs_f32();
+        self.tick_fraction += self.delta_seconds / Ticks::PERIOD_SECS;
+        let whole_ticks = self.tick_fraction.floor();
+        self.tick_fraction -= whole_ticks;
+        self.delta = Ticks::from_repr(whole_ticks as TicksRepr);
+        self.counter = self.counter.wrapping_add(self.delta);
+
+        // Only real players need to see a fully simulated world.
+        let world = &self.world;
+        let observers: Vec<_> = context
+            .players
+            .iter_borrow()
+            .filter(|player| !player.is_bot())
+            .filter_map(|player| match player.status {
+                Status::Alive { entity_index, .. } => {
+                    Some(world.entities[entity_index].transform.position)
+                }
+                Status::Dead { position, .. } => Some(position),
+                Status::Spawning => None,
+            })
+            .collect();
+        self.world
+            .simulation_lod
+            .set_observers(observers.into_iter());
+
+        // Boats of disconnected players go dead in the water (but remain vulnerable) until their
+        // player reconnects or limbo expires.
+        for player in context.players.iter_borrow() {
+            if let Status::Alive { entity_index, .. } = player.status {
+                if player.is_in_limbo() {
+                    self.world.hold_in_limbo(entity_index);
+                }
+            }
+        }
 
-        self.world.update(Ticks::ONE);
+        self.world.update(self.delta, self.delta_seconds);
+        self.world.update_controlled_aircraft(&context.players);
+        // A kraken that already spawned continues to attack.
+        if self.kraken || self.world.kraken.is_alive() {
+            Kraken::update(&mut self.world, &context.players, self.delta);
+        }
+        if self.smuggling {
+            self.world.update_smuggling(self.delta);
+        }
+        if self.zones {
+            self.world.update_zones(self.delta);
+        }
+        self.update_blitz(context);
+
+        // Scores achieved in particular boats count towards the class leaderboards.
+        for (player_id, entity_type, score) in self.world.class_scores.drain(..) {
+            if let Some(class) = entity_type.class_name() {
+                context.submit_class_score(player_id, class, score);
+            }
+        }
+
+        // Distance travelled counts towards achievements and statistics.
+        for mut player in context.players.iter_borrow_mut() {
+            if let Status::Alive { entity_index, .. } = player.status {
+                let velocity = self.world.entities[entity_index].transform.velocity;
+                let distance = velocity.abs().to_mps() * self.delta_seconds;
+                player.data.stats.travelled += distance;
+                player.data.stats.survived += self.delta;
+                if let Some(client) = player.client_mut() {
+                    client.achievements.travelled(distance);
+                }
+            }
+        }
+
+        // The rest only happens once per tick, like sending updates.
+        if self.delta == Ticks::ZERO {
+            return;
+        }
 
         // Needs to be called before clients receive updates, but after World::update.
         self.world.terrain.pre_update();
 
-        if self.counter.every(Ticks::from_whole_secs(60)) {
+        for sinking in &self.world.sinkings {
+            // Bots sinking on their own are not noteworthy.
+            if sinking.player_id.is_bot() && !sinking.reason.is_due_to_player() {
+                continue;
+            }
+            context.events.record(ArenaEvent::Game {
+                player_id: Some(sinking.player_id),
+                text: format!(
+                    "{} ({}) sank{}: {:?}",
+                    sinking.alias,
+                    sinking.entity_type.data().label,
+                    if sinking.mutual { " (mutual)" } else { "" },
+                    sinking.reason
+                ),
+            });
+        }
+
+        // Every minute, even if this update spanned multiple ticks.
+        if self.counter % Ticks::from_whole_secs(60) < self.delta {
             use std::collections::{BTreeMap, HashMap};
             use std::fs::OpenOptions;
             use std::io::{Read, Seek, Write};
 
+            self.world.season = Season::from_unix_time(get_unix_time_now());
+
             let mut count_score = HashMap::<EntityType, (usize, f32)>::new();
 
             for player in context.players.iter_borrow() {
@@ -245,7 +691,16 @@ impl GameArenaService for Server {
     }
 
     fn post_update(&mut self, _context: &mut Context<Self>) {
+        self.world.sunk_scores.clear();
+
+        // Kept until clients receive them, which is once per tick.
+        if self.delta == Ticks::ZERO {
+            return;
+        }
+
         // Needs to be after clients receive updates.
         self.world.terrain.post_update();
+        self.world.sinkings.clear();
+        self.world.pings.clear();
     }
 }
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entity::Entity;
+use common::ticks::Ticks;
+use glam::Vec2;
+
+/// Simulation level of detail. Entities far from every real player (e.g. bot skirmishes and
+/// drifting loot) are only simulated every few ticks, with a larger delta to catch up. Catching up
+/// happens immediately upon coming within range of a real player, so they never see the difference.
+#[derive(Default)]
+pub struct SimulationLod {
+    /// Positions of real players' boats, or where they died.
+    observers: Vec<Vec2>,
+    /// Incremented every tick, to stagger updates of unobserved entities.
+    counter: u32,
+}
+
+impl SimulationLod {
+    /// Entities within this distance of an observer are simulated every tick. Exceeds the maximum
+    /// sensor range, with some margin for the camera.
+    const OBSERVED_RADIUS: f32 = 2500.0;
+    /// Unobserved entities are simulated once per this many ticks.
+    const STRIDE: u32 = 4;
+
+    /// Call once per tick, before [`World::physics`][`crate::world::World::physics`].
+    pub fn set_observers(&mut self, observers: impl Iterator<Item = Vec2>) {
+        self.observers.clear();
+        self.observers.extend(observers);
+    }
+
+    /// Call once per tick, at the start of [`World::physics`][`crate::world::World::physics`].
+    pub fn advance(&mut self) {
+        self.counter = self.counter.wrapping_add(1);
+    }
+
+    fn is_observed(&self, position: Vec2) -> bool {
+        let r2 = Self::OBSERVED_RADIUS.powi(2);
+        self.observers
+            .iter()
+            .any(|observer| observer.distance_squared(position) <= r2)
+    }
+
+    /// Returns the delta (in ticks and seconds) an entity should be simulated with this tick,
+    /// including any catch-up, or None if it should not be simulated this tick.
+    pub fn delta(
+        &self,
+        entity: &mut Entity,
+        delta: Ticks,
+        delta_seconds: f32,
+    ) -> Option<(Ticks, f32)> {
+        let delta = entity.unsimulated.saturating_add(delta);
+        let delta_seconds = entity.unsimulated_seconds + delta_seconds;
+        if self.is_observed(entity.transform.position)
+            || (self.counter.wrapping_add(entity.id.get())) % Self::STRIDE == 0
+        {
+            entity.unsimulated = Ticks::ZERO;
+            entity.unsimulated_seconds = 0.0;
+            Some((delta, delta_seconds))
+        } else {
+            entity.unsimulated = delta;
+            entity.unsimulated_seconds = delta_seconds;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::Entity;
+    use crate::simulation_lod::SimulationLod;
+    use common::entity::{EntityId, EntityType};
+    use common::ticks::Ticks;
+    use glam::Vec2;
+
+    #[test]
+    fn simulation_lod() {
+        let mut lod = SimulationLod::default();
+        let mut entity = Entity::new(EntityType::Barrel, None);
+        entity.id = EntityId::new(1).unwrap();
+
+        // Unobserved entities are simulated every so often, but no time is lost.
+        let mut total = Ticks::ZERO;
+        let mut simulations = 0;
+        for _ in 0..SimulationLod::STRIDE * 3 {
+            lod.advance();
+            if let Some((delta, _)) = lod.delta(&mut entity, Ticks::ONE, Ticks::ONE.to_secs()) {
+                total += delta;
+                simulations += 1;
+            }
+        }
+        assert_eq!(simulations, 3);
+        assert_eq!(
+            (total + entity.unsimulated).0 as u32,
+            SimulationLod::STRIDE * 3
+        );
+
+        // Observed entities catch up immediately.
+        let owed = entity.unsimulated + Ticks::ONE;
+        lod.set_observers(std::iter::once(Vec2::ZERO));
+        lod.advance();
+        let (delta, delta_seconds) = lod.delta(&mut entity, Ticks::ONE, 0.1).unwrap();
+        assert_eq!(delta, owed);
+        assert!((delta_seconds - owed.to_secs()).abs() < 0.001);
+
+        // Including time that didn't complete a tick.
+        lod.advance();
+        assert_eq!(
+            lod.delta(&mut entity, Ticks::ZERO, 0.05),
+            Some((Ticks::ZERO, 0.05))
+        );
+    }
+}
@@ -1,22 +1,108 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::arena::Arena;
+use crate::arena::{Arena, Blitz};
 use crate::entities::{Entities, EntityIndex};
 use crate::entity::Entity;
-use crate::noise::noise_generator;
+use crate::history::History;
+use crate::kraken::Kraken;
+use crate::noise::{biome, current_generator, noise_generator};
+use crate::simulation_lod::SimulationLod;
+use crate::world_dying_shot::DyingBoat;
 use crate::world_mutation::Mutation;
+use crate::world_sensors::SonarPing;
+use crate::world_shipwreck::Shipwreck;
+use crate::world_smuggling::SmugglingObjective;
+use crate::world_zone::CaptureZone;
+use common::current::Currents;
 use common::death_reason::DeathReason;
-use common::entity::{EntityKind, EntityType};
-use common::terrain::Terrain;
-use common::ticks::Ticks;
+use common::entity::{EntityData, EntityKind, EntityType};
+use common::protocol::Sinking;
+use common::region::RegionStructure;
+use common::terrain::{ChunkId, Terrain};
+use common::ticks::{Ticks, TicksRepr};
+use common::transform::Transform;
+use common::velocity::Velocity;
+use common::world::{Biome, Season};
+use core_protocol::get_unix_time_now;
+use core_protocol::id::PlayerId;
+use glam::Vec2;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
 
 /// A game world of variable radius, consisting of entities and a terrain.
 pub struct World {
     pub arena: Arena,
     pub entities: Entities,
     pub terrain: Terrain,
+    /// Ocean currents, which are regenerated along with the terrain. Shared with updates.
+    pub currents: Arc<Currents>,
     pub radius: f32,
+    /// Radius of a world that doesn't grow or shrink with the number of boats (e.g. in a private
+    /// arena).
+    pub fixed_radius: Option<f32>,
+    /// Highest level of boat that may be spawned as or upgraded to.
+    pub max_level: u8,
+    /// Current seasonal event, for clients.
+    pub season: Season,
+    /// Style of the terrain, for clients.
+    pub biome: Biome,
+    /// Boats that sank this tick. Cleared after clients receive updates.
+    pub sinkings: Vec<Sinking>,
+    /// Scores that players whose boats sank this tick had before sinking, so that a kill they
+    /// made in the same tick is still valued fairly. Cleared along with sinkings.
+    pub sunk_scores: Vec<(PlayerId, u32)>,
+    /// Scores that players achieved in a boat before it sank or they upgraded from it, for the
+    /// class leaderboards. Drained each tick.
+    pub class_scores: Vec<(PlayerId, EntityType, u32)>,
+    /// Active sonar pings made this tick. Cleared after clients receive updates.
+    pub pings: Vec<SonarPing>,
+    /// Recent boat positions, for lag compensation.
+    pub history: History,
+    /// Boats recently sunk by weapons, for latency-fair resolution of close duels.
+    pub dying_boats: Vec<DyingBoat>,
+    /// Where and when boats were recently sunk by other players, to avoid spawning into fights.
+    pub recent_deaths: Vec<(Vec2, Instant)>,
+    /// Reduces how often entities far from real players are simulated.
+    pub simulation_lod: SimulationLod,
+    /// Rare boss event.
+    pub kraken: Kraken,
+    /// Player carrying smuggled cargo, if any.
+    pub smuggling: Option<SmugglingObjective>,
+    /// Sites that submarines can salvage.
+    pub shipwrecks: Vec<Shipwreck>,
+    /// Structures that teams can capture and hold for points.
+    pub zones: Vec<CaptureZone>,
+    /// Timed rounds, if the arena is in blitz mode.
+    pub blitz: Option<Blitz>,
+    /// Structures of a handcrafted region (see [`Self::stamp_region`]).
+    pub region_structures: Vec<RegionStructure>,
+}
+
+/// The part of a [`World`] that outlives the server process (see [`World::save`]).
+#[derive(Serialize, Deserialize)]
+struct SavedWorld {
+    radius: f32,
+    chunks: Vec<SavedChunk>,
+    entities: Vec<SavedEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedChunk {
+    chunk_id: ChunkId,
+    /// See [`Chunk::to_bytes`][common::terrain::Chunk::to_bytes].
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+}
+
+/// An entity that doesn't belong to a player, such as a crate or an oil platform.
+#[derive(Serialize, Deserialize)]
+struct SavedEntity {
+    entity_type: EntityType,
+    transform: Transform,
+    ticks: TicksRepr,
 }
 
 impl World {
@@ -26,37 +112,97 @@ impl World {
             arena: Arena::new(),
             entities: Entities::new(),
             terrain: Terrain::with_generator(noise_generator),
+            currents: Arc::new(Currents::with_generator(current_generator)),
             radius: initial_radius,
+            fixed_radius: None,
+            max_level: EntityData::MAX_BOAT_LEVEL,
+            season: Season::from_unix_time(get_unix_time_now()),
+            biome: biome(),
+            sinkings: Vec::new(),
+            sunk_scores: Vec::new(),
+            class_scores: Vec::new(),
+            pings: Vec::new(),
+            history: History::default(),
+            dying_boats: Vec::new(),
+            recent_deaths: Vec::new(),
+            simulation_lod: SimulationLod::default(),
+            kraken: Kraken::default(),
+            smuggling: None,
+            shipwrecks: Vec::new(),
+            zones: Vec::new(),
+            blitz: None,
+            region_structures: Vec::new(),
         }
     }
 
-    /// Updates the internals of the world, spawning and updating existing entities.
-    pub fn update(&mut self, delta: Ticks) {
+    /// Updates the internals of the world, spawning and updating existing entities. Simulates
+    /// `delta_seconds`, of which `delta` is the number of whole ticks that elapsed (the unit of
+    /// timers, such as reloads and damage).
+    pub fn update(&mut self, delta: Ticks, delta_seconds: f32) {
         self.spawn_statics(delta);
-        self.physics(delta);
-        self.physics_radius(delta);
+        self.physics(delta, delta_seconds);
+        self.physics_radius(delta, delta_seconds);
+        self.update_shipwrecks(delta);
+        self.update_pings(delta);
         self.arena.recycle();
 
-        let total_visual_area = EntityType::iter()
-            .map(|t| {
-                let data = t.data();
-                if data.kind == EntityKind::Boat {
-                    self.arena.count(t) as f32 * data.visual_area()
-                } else {
-                    0.0
-                }
+        let boats: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(_, entity)| {
+                (entity.data().kind == EntityKind::Boat).then(|| {
+                    let bot = entity.borrow_player().player_id.is_bot();
+                    (
+                        entity.id,
+                        entity.entity_type,
+                        entity.transform.position,
+                        bot,
+                    )
+                })
+            })
+            .collect();
+
+        // Bots fill in for missing players, so they shouldn't make the world as big.
+        let total_visual_area = boats
+            .iter()
+            .map(|&(_, entity_type, _, bot)| {
+                let weight = if bot { Self::BOT_VISUAL_WEIGHT } else { 1.0 };
+                entity_type.data().visual_area() * weight
             })
             .sum::<f32>();
 
-        let target_radius = Self::target_radius(total_visual_area);
-        let s = delta.to_secs();
+        // One snapshot per tick.
+        if delta != Ticks::ZERO {
+            self.history.record(
+                boats
+                    .into_iter()
+                    .map(|(id, entity_type, position, _)| (id, entity_type, position)),
+            );
+        }
 
+        let target_radius = match self.fixed_radius {
+            Some(radius) => radius.clamp(Self::MIN_RADIUS, Self::max_radius()),
+            None => Self::target_radius(total_visual_area),
+        };
+        self.update_radius(target_radius, delta_seconds);
+    }
+
+    /// Nudges the radius towards `target_radius`, faster if it is further away. Clients receive
+    /// the new radius with their next update. Terrain is generated on demand, so there is always
+    /// terrain up to the border.
+    fn update_radius(&mut self, target_radius: f32, delta_seconds: f32) {
         // Takes effect during testing with large bot counts.
         if target_radius.powi(2) > self.radius.powi(2) + 1000f32.powi(2) {
             self.radius = target_radius;
-        } else {
-            self.radius += (target_radius - self.radius).clamp(-s, 2.0 * s);
+            return;
         }
+
+        let difference = target_radius - self.radius;
+        let speed = (difference.abs() * Self::RADIUS_CHANGE_RATE).max(1.0);
+        let s = speed * delta_seconds;
+        // Grow faster than shrink, so players aren't suddenly caught by the border.
+        self.radius += difference.clamp(-s, 2.0 * s);
     }
 
     /// Adds an entity to the world (assigning it an id).
@@ -73,6 +219,19 @@ impl World {
         self.arena.drop_entity(entity);
     }
 
+    /// Brings the boat of a player in limbo to a stop. It remains vulnerable, and is removed like
+    /// any other once its player leaves.
+    pub fn hold_in_limbo(&mut self, index: EntityIndex) {
+        self.entities[index].guidance.velocity_target = Velocity::ZERO;
+    }
+
+    /// Smallest radius of the world.
+    const MIN_RADIUS: f32 = 400.0;
+    /// How much a bot's boat counts towards the size of the world, relative to a player's.
+    const BOT_VISUAL_WEIGHT: f32 = 0.6;
+    /// Fraction of the difference between the current and target radius to close per second.
+    const RADIUS_CHANGE_RATE: f32 = 0.002;
+
     /// Returns the area of the world, based on it's radius.
     pub fn area(&self) -> f32 {
         self.radius.powi(2) * std::f32::consts::PI
@@ -88,10 +247,144 @@ impl World {
     pub fn target_radius(total_visual_area: f32) -> f32 {
         (total_visual_area * Self::BOAT_VISUAL_OVERLAP / std::f32::consts::PI)
             .sqrt()
-            .clamp(400.0, Self::max_radius())
+            .clamp(Self::MIN_RADIUS, Self::max_radius())
     }
 
     fn max_radius() -> f32 {
         Entities::max_world_radius().min(Terrain::max_world_radius())
     }
+
+    /// Serializes the terrain, and entities that don't belong to players, to be restored by
+    /// a replacement server process with [`Self::restore`]. Players' boats are handed off
+    /// separately, as if they transferred servers.
+    pub fn save(&self) -> Vec<u8> {
+        let saved = SavedWorld {
+            radius: self.radius,
+            chunks: self
+                .terrain
+                .iter_chunks()
+                .map(|(chunk_id, chunk)| SavedChunk {
+                    chunk_id,
+                    bytes: chunk.to_bytes(),
+                })
+                .collect(),
+            entities: self
+                .entities
+                .par_iter()
+                .into_maybe_parallel_iter()
+                .filter(|(_, entity)| {
+                    entity.player.is_none()
+                        && matches!(
+                            entity.data().kind,
+                            EntityKind::Collectible | EntityKind::Obstacle
+                        )
+                })
+                .map(|(_, entity)| SavedEntity {
+                    entity_type: entity.entity_type,
+                    transform: entity.transform,
+                    ticks: entity.ticks.0,
+                })
+                .collect(),
+        };
+        bincode::serialize(&saved).unwrap()
+    }
+
+    /// Restores a world saved with [`Self::save`]. Call before adding any entities.
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+        let saved: SavedWorld = bincode::deserialize(bytes).map_err(|_| "invalid saved world")?;
+        if !saved.radius.is_finite() {
+            return Err("invalid saved radius");
+        }
+        self.radius = saved.radius.clamp(0.0, Self::max_radius());
+        for chunk in saved.chunks {
+            self.terrain.restore_chunk(chunk.chunk_id, &chunk.bytes)?;
+        }
+        for saved in saved.entities {
+            let mut entity = Entity::new(saved.entity_type, None);
+            entity.transform = saved.transform;
+            entity.ticks = Ticks::from_repr(saved.ticks);
+            self.add(entity);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::Entity;
+    use crate::world::World;
+    use crate::Server;
+    use common::entity::EntityType;
+    use common::terrain::{Coord, Terrain, TerrainMutation};
+    use common::ticks::Ticks;
+    use common::velocity::Velocity;
+    use core_protocol::id::PlayerId;
+    use game_server::game_service::GameArenaService;
+    use game_server::player::{PlayerData, PlayerTuple};
+    use glam::{vec2, Vec2};
+    use std::num::NonZeroU32;
+    use std::sync::Arc;
+
+    #[test]
+    fn save_restore() {
+        let mut world = World::new(1000.0);
+        world.terrain = Terrain::new();
+        world
+            .terrain
+            .modify(TerrainMutation::simple(vec2(50.0, 50.0), 60.0));
+        let mut barrel = Entity::new(EntityType::Barrel, None);
+        barrel.transform.position = vec2(100.0, 0.0);
+        world.add(barrel);
+
+        let mut restored = World::new(500.0);
+        restored.terrain = Terrain::new();
+        restored.restore(&world.save()).unwrap();
+
+        assert_eq!(restored.radius, world.radius);
+        let coord = Coord::from_position(vec2(50.0, 50.0)).unwrap();
+        assert_ne!(restored.terrain.at(coord), 0);
+        assert_eq!(restored.terrain.at(coord), world.terrain.at(coord));
+        let (_, barrel) = restored
+            .entities
+            .iter_radius(Vec2::ZERO, 200.0)
+            .next()
+            .unwrap();
+        assert_eq!(barrel.entity_type, EntityType::Barrel);
+        assert_eq!(barrel.transform.position, vec2(100.0, 0.0));
+
+        assert!(restored.restore(&[1, 2, 3]).is_err());
+    }
+    #[test]
+    fn limbo() {
+        let mut world = World::new(1000.0);
+        world.terrain = Terrain::new();
+
+        let player: Arc<PlayerTuple<Server>> = Arc::new(PlayerTuple::new(PlayerData::new(
+            PlayerId(NonZeroU32::new(1).unwrap()),
+            None,
+        )));
+        let mut boat = Entity::new(EntityType::G5, Some(Arc::clone(&player)));
+        boat.transform.velocity = Velocity::from_mps(10.0);
+        boat.guidance.velocity_target = boat.transform.velocity;
+        world.add(boat);
+        let entity_index = || player.borrow_player().data.status.get_entity_index();
+
+        // The boat stops, but survives limbo.
+        world.hold_in_limbo(entity_index().unwrap());
+        for _ in 0..Ticks::from_secs(Server::LIMBO.as_secs_f32()).0 {
+            world.physics(Ticks::ONE, Ticks::ONE.to_secs());
+        }
+        let index = entity_index().expect("boat should survive limbo");
+        assert_eq!(world.entities[index].transform.velocity, Velocity::ZERO);
+
+        // Once limbo expires, the player leaves, and the boat is removed.
+        player.borrow_player_mut().data.flags.left_game = true;
+        world.physics(Ticks::ONE, Ticks::ONE.to_secs());
+        assert_eq!(entity_index(), None);
+        assert!(world
+            .entities
+            .iter_radius(Vec2::ZERO, 1000.0)
+            .next()
+            .is_none());
+    }
 }
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entities::EntityIndex;
+use crate::entity::Entity;
+use crate::player::Status;
+use crate::server::Server;
+use crate::world::World;
+use common::entity::{EntityId, EntityKind};
+use game_server::player::PlayerRepo;
+
+impl World {
+    /// Aircraft can't fly further than this from their boat (see aircraft range in entity macro).
+    const MAX_AIRCRAFT_RANGE: f32 = 5000.0;
+    /// Controlled aircraft must keep this many seconds of fuel in reserve, beyond what it takes to
+    /// fly back to their boat.
+    const AIRCRAFT_FUEL_RESERVE: f32 = 2.0;
+
+    /// Returns the index of the aircraft with `id`, if it belongs to the same player as `boat`.
+    pub(crate) fn find_aircraft(&self, boat: &Entity, id: EntityId) -> Option<EntityIndex> {
+        self.entities
+            .iter_radius(boat.transform.position, Self::MAX_AIRCRAFT_RANGE)
+            .find(|(_, e)| {
+                e.id == id && e.data().kind == EntityKind::Aircraft && e.has_same_player(boat)
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Returns true if `aircraft` has enough fuel to be controlled manually, and still make it back
+    /// to `boat`.
+    pub(crate) fn can_control_aircraft(boat: &Entity, aircraft: &Entity) -> bool {
+        let data = aircraft.data();
+        let fuel = data.lifespan.saturating_sub(aircraft.ticks).to_secs();
+        let distance = aircraft
+            .transform
+            .position
+            .distance(boat.transform.position);
+        distance < (fuel - Self::AIRCRAFT_FUEL_RESERVE) * data.speed.to_mps()
+    }
+
+    /// Returns control of aircraft that no longer exist, or are low on fuel, to their boats. Call
+    /// once per tick.
+    pub fn update_controlled_aircraft(&self, players: &PlayerRepo<Server>) {
+        for player_tuple in players.iter() {
+            let mut player = player_tuple.borrow_player_mut();
+            if let Status::Alive {
+                entity_index,
+                controlled_aircraft: controlled_aircraft @ Some(_),
+                ..
+            } = &mut player.data.status
+            {
+                let boat = &self.entities[*entity_index];
+                let controllable = self
+                    .find_aircraft(boat, controlled_aircraft.unwrap())
+                    .map_or(false, |index| {
+                        Self::can_control_aircraft(boat, &self.entities[index])
+                    });
+                if !controllable {
+                    *controlled_aircraft = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::contact_ref::ContactRef;
+use crate::entity::Entity;
+use crate::player::Status;
+use crate::server::Server;
+use crate::world::World;
+use common::contact::ContactTrait;
+use common::entity::EntityId;
+use common::interest::Interest;
+use common::protocol::Update;
+use common_util::range::map_ranges;
+use game_server::player::PlayerTuple;
+use glam::Vec2;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use std::collections::HashMap;
+
+impl World {
+    /// Friendly entities within this range are always known.
+    const AUDIT_KNOWN_RANGE: f32 = 800.0;
+
+    /// Compares an update sent to a player against a deliberately simple and conservative estimate
+    /// of what they could possibly see, independent of [`World::get_player_complete`]. Returns a
+    /// description of each contact that the player should not have been sent.
+    pub fn audit_visibility(&self, tuple: &PlayerTuple<Server>, update: &Update) -> Vec<String> {
+        let player = tuple.borrow_player();
+        let player_id = player.player_id;
+
+        // Upper bounds of sensor ranges.
+        let (position, player_radius, visual, radar, sonar) = match player.data.status {
+            Status::Alive {
+                entity_index,
+                controlled_aircraft,
+                ..
+            } => {
+                let entity = &self.entities[entity_index];
+                let aircraft = controlled_aircraft
+                    .and_then(|id| self.find_aircraft(entity, id))
+                    .map(|index| &self.entities[index]);
+                // The aircraft's view replaces the boat's.
+                let entity = aircraft.unwrap_or(entity);
+                let data = entity.data();
+                let efficacy = map_ranges(entity.altitude.to_norm(), -0.35..0.0, 0.0..1.0, true);
+                let sonar = if entity.altitude.is_airborne() {
+                    0.0
+                } else {
+                    data.sensors.sonar.range
+                };
+                (
+                    entity.transform.position,
+                    data.radius,
+                    data.sensors.visual.range * efficacy,
+                    data.sensors.radar.range * efficacy,
+                    sonar,
+                )
+            }
+            Status::Dead {
+                position,
+                visual_range,
+                ..
+            } => {
+                let range = visual_range.max(500.0);
+                (position, 0.0, range, range, range)
+            }
+            Status::Spawning => (Vec2::ZERO, 0.0, 500.0, 500.0, 500.0),
+        };
+        drop(player);
+
+        let max_range = visual.max(radar).max(sonar);
+        let nearby: HashMap<EntityId, &Entity> = self
+            .entities
+            .iter_radius(position, max_range.max(Self::AUDIT_KNOWN_RANGE))
+            .map(|(_, entity)| (entity.id, entity))
+            .collect();
+
+        let mut discrepancies = Vec::new();
+        for contact in update.contacts.iter() {
+            // Relayed contacts come from fleet members' sensors, not the player's.
+            if contact.player_id() == Some(player_id) || contact.relayed() {
+                continue;
+            }
+
+            let distance = contact.transform().position.distance(position);
+            let label = contact
+                .entity_type()
+                .map(|entity_type| entity_type.data().label)
+                .unwrap_or("unknown");
+            let describe = |problem: &str| {
+                format!(
+                    "{} ({:?}) {} at {:.0}m from {:?}",
+                    label,
+                    contact.id(),
+                    problem,
+                    distance,
+                    position
+                )
+            };
+
+            let Some(entity) = nearby.get(&contact.id()) else {
+                discrepancies.push(describe(&format!(
+                    "sent beyond maximum sensor range of {:.0}m",
+                    max_range
+                )));
+                continue;
+            };
+
+            if entity.is_friendly_to_player(Some(tuple)) && distance < Self::AUDIT_KNOWN_RANGE {
+                continue;
+            }
+
+            // Positions of far contacts are quantized.
+            if distance > max_range + Interest::FAR_PRECISION {
+                discrepancies.push(describe(&format!(
+                    "sent beyond maximum sensor range of {:.0}m",
+                    max_range
+                )));
+            } else if entity.altitude.is_submerged() {
+                // Radar doesn't work underwater, and visual is reduced by at least 15%.
+                let data = entity.data();
+                let visual_ratio = distance.powi(2) * data.inv_size / (visual.powi(2) * 0.85);
+                let close = distance <= player_radius + Entity::CLOSE_PROXIMITY + data.radius;
+                if distance > sonar && visual_ratio >= 1.0 && !close {
+                    discrepancies.push(describe("sent while submerged beyond sonar range"));
+                }
+            }
+        }
+        discrepancies
+    }
+
+    /// Describes every entity, one line each and ordered by id, for comparison against a client's
+    /// view of the world when investigating desyncs. See [`ContactTrait::snapshot_line`].
+    pub fn snapshot(&self) -> String {
+        let mut lines: Vec<(EntityId, String)> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .map(|(_, entity)| {
+                let contact = ContactRef::new(entity, true, true, true, false);
+                (entity.id, contact.snapshot_line())
+            })
+            .collect();
+        lines.sort_unstable_by_key(|&(id, _)| id);
+
+        let mut snapshot = String::with_capacity(lines.len() * 32);
+        for (_, line) in lines {
+            snapshot.push_str(&line);
+            snapshot.push('\n');
+        }
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact_acks::ContactAcks;
+    use crate::contact_ref::ContactRef;
+    use crate::entity::Entity;
+    use crate::server::Server;
+    use crate::world::World;
+    use common::entity::EntityType;
+    use common::terrain::{ChunkSet, Terrain};
+    use common::ticks::Ticks;
+    use core_protocol::id::PlayerId;
+    use game_server::player::{PlayerData, PlayerTuple};
+    use glam::vec2;
+    use std::num::NonZeroU32;
+    use std::sync::Arc;
+
+    #[test]
+    fn audit_visibility() {
+        let mut world = World::new(5000.0);
+        world.terrain = Terrain::new();
+
+        let player: Arc<PlayerTuple<Server>> = Arc::new(PlayerTuple::new(PlayerData::new(
+            PlayerId(NonZeroU32::new(1).unwrap()),
+            None,
+        )));
+        world.add(Entity::new(EntityType::Zubr, Some(Arc::clone(&player))));
+
+        let mut barrel = Entity::new(EntityType::Barrel, None);
+        barrel.transform.position = vec2(3000.0, 0.0);
+        world.add(barrel);
+
+        let mut update = world.get_player_complete(&player).into_update(
+            Ticks::ZERO,
+            Ticks::ONE,
+            &mut ChunkSet::new(),
+            &mut ContactAcks::default(),
+        );
+        assert!(world.audit_visibility(&player, &update).is_empty());
+
+        // Simulate a leak.
+        let (_, barrel) = world
+            .entities
+            .iter_radius(vec2(3000.0, 0.0), 1.0)
+            .next()
+            .unwrap();
+        update
+            .contacts
+            .push(ContactRef::new(barrel, true, false, true, false).into_contact());
+        assert_eq!(world.audit_visibility(&player, &update).len(), 1);
+    }
+}
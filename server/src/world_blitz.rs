@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::arena::Blitz;
+use crate::world::World;
+use common::death_reason::DeathReason;
+use common::entity::EntityKind;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+
+impl World {
+    /// Scales score gained, which is accelerated in blitz mode.
+    pub fn blitz_score(&self, score: u32) -> u32 {
+        if self.blitz.is_some() {
+            score.saturating_mul(Blitz::SCORE_MULTIPLIER)
+        } else {
+            score
+        }
+    }
+
+    /// How fast armaments reload, which is accelerated in blitz mode.
+    pub fn blitz_reload_multiplier(&self) -> f32 {
+        if self.blitz.is_some() {
+            Blitz::RELOAD_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+
+    /// Ends a blitz round: sinks every boat, and clears everything but obstacles, so that the next
+    /// round starts on even terms.
+    pub fn end_blitz_round(&mut self) {
+        let mut boats: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(index, entity)| entity.is_boat().then_some(index))
+            .collect();
+        // Sorted in reverse to remove correctly.
+        boats.sort_unstable_by(|a, b| b.cmp(a));
+        for index in boats {
+            self.remove(index, DeathReason::RoundEnded);
+        }
+
+        // Includes anything sinking boats left behind, such as coins.
+        let mut others: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(index, entity)| {
+                (entity.data().kind != EntityKind::Obstacle).then_some(index)
+            })
+            .collect();
+        others.sort_unstable_by(|a, b| b.cmp(a));
+        for index in others {
+            self.remove(index, DeathReason::Unknown);
+        }
+
+        self.dying_boats.clear();
+        self.recent_deaths.clear();
+        self.smuggling = None;
+    }
+}
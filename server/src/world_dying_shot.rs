@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entities::EntityIndex;
+use crate::entity::Entity;
+use crate::history::History;
+use crate::server::Server;
+use crate::world::World;
+use common::altitude::Altitude;
+use common::angle::Angle;
+use common::death_reason::DeathReason;
+use common::entity::{EntityId, EntitySubKind, EntityType};
+use common::protocol::Fire;
+use common::ticks::Ticks;
+use common::transform::Transform;
+use core_protocol::name::PlayerAlias;
+use game_server::player::{PlayerData, PlayerTuple};
+use glam::Vec2;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A boat that was recently sunk by another player's weapon. Its player may have fired before
+/// finding out, in which case the shot is still honored (see [`World::fire_dying_shot`]).
+pub struct DyingBoat {
+    player: Arc<PlayerTuple<Server>>,
+    alias: PlayerAlias,
+    id: EntityId,
+    entity_type: EntityType,
+    transform: Transform,
+    altitude: Altitude,
+    reloads: Box<[Ticks]>,
+    turrets: Arc<[Angle]>,
+    sunk: Instant,
+    /// Who sunk the boat, for detecting mutual kills.
+    killer: PlayerAlias,
+}
+
+impl World {
+    /// Shots are honored if sent at most this long before the sinking was processed, the same
+    /// limit as lag compensation.
+    fn dying_shot_window() -> Duration {
+        Duration::from_secs_f32(History::MAX_REWIND.to_secs())
+    }
+
+    /// Called when a boat is about to be removed, to remember it in case its player fired before
+    /// finding out it was sunk.
+    pub(crate) fn record_dying_boat(&mut self, index: EntityIndex, reason: &DeathReason) {
+        let killer = if let DeathReason::Weapon(alias, _) = reason {
+            *alias
+        } else {
+            // Collisions are already resolved simultaneously for both parties.
+            return;
+        };
+
+        let now = Instant::now();
+        let window = Self::dying_shot_window();
+        self.dying_boats
+            .retain(|boat| now.duration_since(boat.sunk) < window);
+
+        let entity = &self.entities[index];
+        let player = Arc::clone(entity.player.as_ref().unwrap());
+        let alias = player.borrow_player().alias();
+        let extension = entity.extension();
+        self.dying_boats.push(DyingBoat {
+            player,
+            alias,
+            id: entity.id,
+            entity_type: entity.entity_type,
+            transform: entity.transform,
+            altitude: entity.altitude,
+            reloads: extension.reloads.clone(),
+            turrets: Arc::clone(&extension.turrets),
+            sunk: now,
+            killer,
+        });
+    }
+
+    /// Fires from a player's recently sunk boat, provided that the player sent the command before
+    /// their boat sank. This resolves close duels by when players fired, as opposed to when their
+    /// commands happened to arrive.
+    pub(crate) fn fire_dying_shot(
+        &mut self,
+        player_tuple: &Arc<PlayerTuple<Server>>,
+        fire: &Fire,
+        aim_target: Option<Vec2>,
+    ) -> Result<(), &'static str> {
+        let rtt = player_tuple
+            .borrow_player()
+            .client()
+            .and_then(|client| client.rtt())
+            .ok_or("cannot fire while not alive")?;
+        let i = self
+            .dying_boats
+            .iter()
+            .position(|boat| Arc::ptr_eq(&boat.player, player_tuple))
+            .ok_or("cannot fire while not alive")?;
+        let boat = &self.dying_boats[i];
+
+        // When the command was sent, assuming latency is symmetric.
+        let sent = Instant::now().checked_sub((rtt / 2).min(Self::dying_shot_window()));
+        if sent.map_or(true, |sent| sent > boat.sunk) {
+            return Err("cannot fire while not alive");
+        }
+
+        let data = boat.entity_type.data();
+        let armament = fire.armament(data, boat.altitude, &boat.reloads, &boat.turrets)?;
+        let armament_entity_data = armament.entity_type.data();
+        if armament_entity_data.limited || armament_entity_data.sub_kind == EntitySubKind::Depositor
+        {
+            return Err("cannot use armament after sinking");
+        }
+
+        let index = fire.armament_index as usize;
+        let mut armament_entity = Entity::new(armament.entity_type, Some(Arc::clone(player_tuple)));
+        armament_entity.transform = boat.transform + data.armament_transform(&boat.turrets, index);
+        armament_entity.altitude = boat.altitude;
+        let shooter = (boat.id, boat.transform.direction);
+
+        self.dying_boats[i].reloads[index] = armament.reload();
+
+        Fire::launch(
+            self,
+            armament_entity,
+            armament.vertical,
+            shooter,
+            aim_target,
+            Ticks::from_secs(rtt.as_secs_f32()),
+        )
+    }
+
+    /// Whether the player sinking for `reason` had recently sunk whoever sunk them, i.e. they
+    /// sunk each other. If their killer sank earlier in the same tick, that sinking is marked as
+    /// mutual too.
+    pub(crate) fn detect_mutual_kill(&mut self, alias: PlayerAlias, reason: &DeathReason) -> bool {
+        let killer = if let Some(killer) = reason.killer() {
+            killer
+        } else {
+            return false;
+        };
+
+        let mut mutual = false;
+        for sinking in &mut self.sinkings {
+            if sinking.alias == killer && sinking.reason.killer() == Some(alias) {
+                sinking.mutual = true;
+                mutual = true;
+            }
+        }
+
+        let window = Self::dying_shot_window();
+        mutual
+            || self.dying_boats.iter().any(|boat| {
+                boat.alias == killer && boat.killer == alias && boat.sunk.elapsed() < window
+            })
+    }
+
+    /// Returns the score of a player, as it was before their boat sank if that happened this tick.
+    /// Prevents the order in which simultaneous sinkings are applied from affecting kill scores.
+    pub(crate) fn score_before_sinking(&self, player: &PlayerData<Server>) -> u32 {
+        self.sunk_scores
+            .iter()
+            .find(|(player_id, _)| *player_id == player.player_id)
+            .map_or(player.score, |&(_, score)| score)
+    }
+}
@@ -6,17 +6,19 @@ use crate::player::Status;
 use crate::protocol::*;
 use crate::server::Server;
 use crate::world::World;
+use common::altitude::Altitude;
 use common::angle::Angle;
 use common::entity::*;
+use common::guidance::Guidance;
 use common::protocol::*;
 use common::terrain::TerrainMutation;
 use common::ticks::Ticks;
 use common::util::{level_to_score, score_to_level};
+use common::velocity::Velocity;
 use common::world::{clamp_y_to_strict_area_border, outside_strict_area, ARCTIC};
 use common_util::range::map_ranges;
 use game_server::player::PlayerTuple;
 use glam::Vec2;
-use maybe_parallel_iterator::IntoMaybeParallelIterator;
 use rand::{thread_rng, Rng};
 use std::ops::Range;
 use std::sync::Arc;
@@ -42,10 +44,22 @@ impl CommandTrait for Spawn {
             return Err("cannot spawn while already alive");
         }
 
+        if world
+            .blitz
+            .as_ref()
+            .map_or(false, |blitz| !blitz.is_playing())
+        {
+            return Err("cannot spawn between rounds");
+        }
+
         if !self.entity_type.can_spawn_as(player.score, player.is_bot()) {
             return Err("cannot spawn as given entity type");
         }
 
+        if self.entity_type.data().level > world.max_level {
+            return Err("level not allowed in this arena");
+        }
+
         // These initial positions may be overwritten later.
         let mut spawn_position = Vec2::ZERO;
         let mut spawn_radius = 0.8 * world.radius;
@@ -124,46 +138,17 @@ impl CommandTrait for Spawn {
             _ => None,
         };
 
-        if player.team_id().is_some() || player.invitation_accepted().is_some() {
-            // TODO: Inefficient to scan all entities; only need to scan all players. Unfortunately,
-            // that data is not available here, currently.
-            if let Some((_, team_boat)) = world
-                .entities
-                .par_iter()
-                .into_maybe_parallel_iter()
-                .find_any(|(_, entity)| {
-                    let data = entity.data();
-                    if data.kind != EntityKind::Boat {
-                        return false;
-                    }
-
-                    if let Some(exclusion_zone) = exclusion_zone {
-                        if entity.transform.position.distance_squared(exclusion_zone)
-                            < 1100f32.powi(2)
-                        {
-                            return false;
-                        }
-                    }
-
-                    let is_team_member = player.team_id().is_some()
-                        && entity.borrow_player().team_id() == player.team_id();
-
-                    let was_invited_by = player.invitation_accepted().is_some()
-                        && entity.borrow_player().player_id
-                            == player.invitation_accepted().as_ref().unwrap().player_id;
-
-                    is_team_member || was_invited_by
-                })
-            {
-                spawn_position = team_boat.transform.position;
-                spawn_radius = team_boat.data().radius + 25.0;
-            }
+        if let Some((position, radius)) = world.spawn_near_friend(&player, exclusion_zone) {
+            spawn_position = position;
+            spawn_radius = radius;
         }
 
         drop(player);
 
         let mut boat = Entity::new(self.entity_type, Some(Arc::clone(player_tuple)));
         boat.transform.position = spawn_position;
+        boat.extension_mut()
+            .set_loadout(self.loadout, self.entity_type);
         //#[cfg(debug_assertions)]
         //let begin = std::time::Instant::now();
         if world.spawn_here_or_nearby(boat, spawn_radius, exclusion_zone) {
@@ -189,6 +174,7 @@ impl CommandTrait for Control {
         player_tuple: &Arc<PlayerTuple<Server>>,
     ) -> Result<(), &'static str> {
         let mut player = player_tuple.borrow_player_mut();
+        player.data.control = Some(self.clone());
 
         // Pre-borrow.
         let world_radius = world.radius;
@@ -196,13 +182,29 @@ impl CommandTrait for Control {
         return if let Status::Alive {
             entity_index,
             aim_target,
+            controlled_aircraft,
             ..
         } = &mut player.data.status
         {
+            // While controlling an aircraft, the boat keeps its previous guidance.
+            let aircraft_index = controlled_aircraft
+                .and_then(|id| world.find_aircraft(&world.entities[*entity_index], id));
+            if let Some((aircraft_index, guidance)) = aircraft_index.zip(self.guidance) {
+                world.entities[aircraft_index].guidance = Guidance {
+                    // Aircraft can't fly backwards.
+                    velocity_target: guidance.velocity_target.max(Velocity::ZERO),
+                    ..guidance
+                };
+            }
+
             let entity = &mut world.entities[*entity_index];
 
             // Movement
-            if let Some(guidance) = self.guidance {
+            if let Some(guidance) = self.guidance.filter(|_| aircraft_index.is_none()) {
+                if guidance != entity.guidance {
+                    // Player is no longer idle after spawning.
+                    entity.extension_mut().clear_spawn_shield();
+                }
                 entity.guidance = guidance;
             }
             *aim_target = if let Some(mut aim_target) = self.aim_target {
@@ -217,6 +219,8 @@ impl CommandTrait for Control {
             };
             let extension = entity.extension_mut();
             extension.set_submerge(self.submerge);
+            extension.set_depth_target(self.depth);
+            extension.set_doctrine(self.doctrine);
             extension.set_active(self.active);
 
             drop(player);
@@ -234,12 +238,87 @@ impl CommandTrait for Control {
             }
 
             Ok(())
+        } else if let Some(fire) = &self.fire {
+            drop(player);
+
+            let aim_target = if let Some(mut aim_target) = self.aim_target {
+                sanitize_floats(aim_target.as_mut(), -world_radius * 2.0..world_radius * 2.0)?;
+                Some(aim_target)
+            } else {
+                None
+            };
+
+            // The player may have fired before finding out their boat sank.
+            world.fire_dying_shot(player_tuple, fire, aim_target)
         } else {
             Err("cannot control while not alive")
         };
     }
 }
 
+impl CommandTrait for ControlDelta {
+    fn apply(
+        &self,
+        world: &mut World,
+        player_tuple: &Arc<PlayerTuple<Server>>,
+    ) -> Result<(), &'static str> {
+        let control = player_tuple
+            .borrow_player()
+            .data
+            .control
+            .as_ref()
+            .map(|previous| self.apply(previous))
+            .ok_or("control delta without previous control")?;
+        control.apply(world, player_tuple)
+    }
+}
+
+impl CommandTrait for Ack {
+    fn apply(
+        &self,
+        _: &mut World,
+        player_tuple: &Arc<PlayerTuple<Server>>,
+    ) -> Result<(), &'static str> {
+        let mut player = player_tuple.borrow_player_mut();
+        // Acks may arrive out of order.
+        if self.sequence >= player.data.ack.sequence {
+            player.data.ack = *self;
+        }
+        Ok(())
+    }
+}
+
+impl CommandTrait for ControlAircraft {
+    fn apply(
+        &self,
+        world: &mut World,
+        player_tuple: &Arc<PlayerTuple<Server>>,
+    ) -> Result<(), &'static str> {
+        let mut player = player_tuple.borrow_player_mut();
+
+        if let Status::Alive {
+            entity_index,
+            controlled_aircraft,
+            ..
+        } = &mut player.data.status
+        {
+            if let Some(id) = self.entity_id {
+                let boat = &world.entities[*entity_index];
+                let aircraft_index = world
+                    .find_aircraft(boat, id)
+                    .ok_or("cannot control nonexistent aircraft")?;
+                if !World::can_control_aircraft(boat, &world.entities[aircraft_index]) {
+                    return Err("aircraft is low on fuel");
+                }
+            }
+            *controlled_aircraft = self.entity_id;
+            Ok(())
+        } else {
+            Err("cannot control aircraft while not alive")
+        }
+    }
+}
+
 impl CommandTrait for Fire {
     fn apply(
         &self,
@@ -262,42 +341,16 @@ impl CommandTrait for Fire {
             let entity = &mut world.entities[entity_index];
 
             let data = entity.data();
-
             let index = self.armament_index as usize;
-            if index >= data.armaments.len() {
-                return Err("armament index out of bounds");
-            }
-
-            if entity.extension().reloads[index] != Ticks::ZERO {
-                return Err("armament not yet reloaded");
-            }
-
-            let armament = &data.armaments[index];
+            let extension = entity.extension();
+            let armament = self.armament(
+                data,
+                entity.altitude,
+                &extension.reloads,
+                &extension.turrets,
+            )?;
             let armament_entity_data = armament.entity_type.data();
 
-            // Can't fire if boat is a submerged former submarine.
-            if entity.altitude.is_submerged()
-                && (data.sub_kind != EntitySubKind::Submarine
-                    || matches!(armament_entity_data.kind, EntityKind::Aircraft)
-                    || matches!(
-                        armament_entity_data.sub_kind,
-                        EntitySubKind::Shell | EntitySubKind::Sam
-                    ))
-            {
-                return Err("cannot fire while surfacing as a boat");
-            }
-
-            if let Some(turret_index) = armament.turret {
-                let turret_angle = entity.extension().turrets[turret_index];
-                let turret = &data.turrets[turret_index];
-
-                // The aim may be outside the range but the turret must not be fired if the turret's
-                // current angle is outside the range.
-                if !turret.within_azimuth(turret_angle) {
-                    return Err("invalid turret azimuth");
-                }
-            }
-
             let armament_transform =
                 entity.transform + data.armament_transform(&entity.extension().turrets, index);
 
@@ -318,36 +371,32 @@ impl CommandTrait for Fire {
                 }
             } else {
                 // Fire weapon.
-                let player_arc = Arc::clone(player_tuple);
-
-                drop(player);
-                let mut armament_entity = Entity::new(armament.entity_type, Some(player_arc));
-
+                let mut armament_entity =
+                    Entity::new(armament.entity_type, Some(Arc::clone(player_tuple)));
                 armament_entity.transform = armament_transform;
                 armament_entity.altitude = entity.altitude;
 
-                let aim_angle = aim_target
-                    .map(|aim| Angle::from(aim - armament_entity.transform.position))
-                    .unwrap_or(entity.transform.direction);
-
-                armament_entity.guidance.velocity_target = armament_entity_data.speed;
-                armament_entity.guidance.direction_target = aim_angle;
-
-                if armament.vertical {
-                    // Vertically-launched armaments can be launched in any horizontal direction.
-                    armament_entity.transform.direction = armament_entity.guidance.direction_target;
-                }
-
-                // Some weapons experience random deviation on launch
-                let deviation = match armament_entity_data.sub_kind {
-                    EntitySubKind::Rocket | EntitySubKind::RocketTorpedo => 0.05,
-                    EntitySubKind::Shell => 0.01,
-                    _ => 0.03,
-                };
-                armament_entity.transform.direction += thread_rng().gen::<Angle>() * deviation;
+                let shooter = (entity.id, entity.transform.direction);
+                let rewind = player
+                    .client()
+                    .and_then(|client| client.rtt())
+                    .map(|rtt| Ticks::from_secs(rtt.as_secs_f32()))
+                    .unwrap_or(Ticks::ZERO);
+                drop(player);
 
-                if !world.spawn_here_or_nearby(armament_entity, 0.0, None) {
-                    return Err("failed to fire from current location");
+                Self::launch(
+                    world,
+                    armament_entity,
+                    armament.vertical,
+                    shooter,
+                    aim_target,
+                    rewind,
+                )?;
+
+                // Aircraft aren't weapons themselves, so don't count against accuracy.
+                if armament_entity_data.kind == EntityKind::Weapon {
+                    let mut player = player_tuple.borrow_player_mut();
+                    player.data.stats.fired(armament.entity_type);
                 }
             }
 
@@ -362,6 +411,97 @@ impl CommandTrait for Fire {
     }
 }
 
+impl Fire {
+    /// Returns the armament to fire, if it is valid to fire it given the state of the boat.
+    pub(crate) fn armament(
+        &self,
+        data: &'static EntityData,
+        altitude: Altitude,
+        reloads: &[Ticks],
+        turrets: &[Angle],
+    ) -> Result<&'static Armament, &'static str> {
+        let index = self.armament_index as usize;
+        if index >= data.armaments.len() {
+            return Err("armament index out of bounds");
+        }
+
+        if reloads[index] != Ticks::ZERO {
+            return Err("armament not yet reloaded");
+        }
+
+        let armament = &data.armaments[index];
+        let armament_entity_data = armament.entity_type.data();
+
+        // Can't fire if boat is a submerged former submarine.
+        if altitude.is_submerged()
+            && (data.sub_kind != EntitySubKind::Submarine
+                || matches!(armament_entity_data.kind, EntityKind::Aircraft)
+                || matches!(
+                    armament_entity_data.sub_kind,
+                    EntitySubKind::Shell | EntitySubKind::Sam
+                ))
+        {
+            return Err("cannot fire while surfacing as a boat");
+        }
+
+        if let Some(turret_index) = armament.turret {
+            let turret_angle = turrets[turret_index];
+            let turret = &data.turrets[turret_index];
+
+            // The aim may be outside the range but the turret must not be fired if the turret's
+            // current angle is outside the range.
+            if !turret.within_azimuth(turret_angle) {
+                return Err("invalid turret azimuth");
+            }
+        }
+
+        Ok(armament)
+    }
+
+    /// Launches a weapon (already positioned at its armament) from a shooter (id and direction).
+    /// The aim target is compensated for the shooter's view of the world being `rewind` old.
+    pub(crate) fn launch(
+        world: &mut World,
+        mut armament_entity: Entity,
+        vertical: bool,
+        (shooter_id, shooter_direction): (EntityId, Angle),
+        aim_target: Option<Vec2>,
+        rewind: Ticks,
+    ) -> Result<(), &'static str> {
+        let armament_entity_data = armament_entity.data();
+
+        // The player aimed based on a view of the world that was about one round trip old.
+        let aim_target =
+            aim_target.map(|aim| world.history.compensate_aim(aim, rewind, shooter_id));
+
+        let aim_angle = aim_target
+            .map(|aim| Angle::from(aim - armament_entity.transform.position))
+            .unwrap_or(shooter_direction);
+
+        armament_entity.guidance.velocity_target = armament_entity_data.speed;
+        armament_entity.guidance.direction_target = aim_angle;
+
+        if vertical {
+            // Vertically-launched armaments can be launched in any horizontal direction.
+            armament_entity.transform.direction = armament_entity.guidance.direction_target;
+        }
+
+        // Some weapons experience random deviation on launch
+        let deviation = match armament_entity_data.sub_kind {
+            EntitySubKind::Rocket | EntitySubKind::RocketTorpedo => 0.05,
+            EntitySubKind::Shell => 0.01,
+            _ => 0.03,
+        };
+        armament_entity.transform.direction += thread_rng().gen::<Angle>() * deviation;
+
+        if world.spawn_here_or_nearby(armament_entity, 0.0, None) {
+            Ok(())
+        } else {
+            Err("failed to fire from current location")
+        }
+    }
+}
+
 impl CommandTrait for Pay {
     fn apply(
         &self,
@@ -419,6 +559,7 @@ impl CommandTrait for Hint {
     ) -> Result<(), &'static str> {
         player_tuple.borrow_player_mut().data.hint = Hint {
             aspect: sanitize_float(self.aspect, 0.5..2.0)?,
+            low_bandwidth: self.low_bandwidth,
         };
         Ok(())
     }
@@ -431,14 +572,30 @@ impl CommandTrait for Upgrade {
         player_tuple: &Arc<PlayerTuple<Server>>,
     ) -> Result<(), &'static str> {
         let mut player = player_tuple.borrow_player_mut();
-        let status = &mut player.data.status;
+        let score = player.score;
+        let bot = player.is_bot();
 
-        if let Status::Alive { entity_index, .. } = status {
+        if self.entity_type.data().level > world.max_level {
+            return Err("level not allowed in this arena");
+        }
+
+        if let Status::Alive {
+            entity_index,
+            respecced,
+            ..
+        } = &mut player.data.status
+        {
             let entity = &mut world.entities[*entity_index];
-            if !entity
+            let upgrade = entity
                 .entity_type
-                .can_upgrade_to(self.entity_type, player.score, player.is_bot())
-            {
+                .can_upgrade_to(self.entity_type, score, bot);
+            // Falls back to the one free respec per life.
+            let respec = !upgrade
+                && !*respecced
+                && entity
+                    .entity_type
+                    .can_respec_to(self.entity_type, score, bot);
+            if !(upgrade || respec) {
                 return Err("cannot upgrade to provided entity type");
             }
 
@@ -446,12 +603,21 @@ impl CommandTrait for Upgrade {
                 return Err("cannot upgrade outside the correct area");
             }
 
+            *respecced |= respec;
             player.data.flags.upgraded = true;
 
-            let below_full_potential = self.entity_type.data().level < score_to_level(player.score);
+            let below_full_potential = self.entity_type.data().level < score_to_level(score);
 
+            let player_id = player.player_id;
             drop(player);
 
+            world
+                .class_scores
+                .push((player_id, entity.entity_type, score));
+
+            entity
+                .extension_mut()
+                .set_loadout(self.loadout, self.entity_type);
             entity.change_entity_type(self.entity_type, &mut world.arena, below_full_potential);
 
             Ok(())
@@ -461,6 +627,74 @@ impl CommandTrait for Upgrade {
     }
 }
 
+impl CommandTrait for Purchase {
+    fn apply(
+        &self,
+        world: &mut World,
+        player_tuple: &Arc<PlayerTuple<Server>>,
+    ) -> Result<(), &'static str> {
+        let mut player = player_tuple.borrow_player_mut();
+
+        if let Status::Alive { entity_index, .. } = player.data.status {
+            let entity = &world.entities[entity_index];
+            let data = entity.data();
+            let position = entity.transform.position;
+
+            if !world
+                .entities
+                .iter_radius(position, EntityData::MAX_RADIUS + Purchase::RANGE)
+                .any(|(_, store)| {
+                    Purchase::in_range(
+                        entity.entity_type,
+                        position,
+                        store.entity_type,
+                        store.transform.position,
+                    )
+                })
+            {
+                return Err("cannot purchase away from a store");
+            }
+
+            let damage = entity.ticks.to_secs() / data.max_health().to_secs();
+            let cost = self.cost(data.level, damage);
+            if cost == 0 {
+                return Err("nothing to repair");
+            }
+
+            if player.score < level_to_score(data.level) + cost {
+                return Err("insufficient funds");
+            }
+
+            let entity = &mut world.entities[entity_index];
+            match *self {
+                Self::Repair => entity.ticks = Ticks::ZERO,
+                Self::Reload(armament_type) => {
+                    let reloads = entity.extension_mut().reloads_mut();
+                    let mut reloaded = false;
+                    for (armament, reload) in data.armaments.iter().zip(reloads.iter_mut()) {
+                        // Limited armaments (Ticks::MAX) can't be bought back.
+                        if armament.entity_type == armament_type
+                            && !matches!(*reload, Ticks::ZERO | Ticks::MAX)
+                        {
+                            *reload = Ticks::ZERO;
+                            reloaded = true;
+                        }
+                    }
+                    if !reloaded {
+                        return Err("nothing to reload");
+                    }
+                }
+            }
+
+            player.score -= cost;
+
+            Ok(())
+        } else {
+            Err("cannot purchase while not alive")
+        }
+    }
+}
+
 /// Returns an error if the float isn't finite. Otherwise, clamps it to the provided range.
 fn sanitize_float(float: f32, valid: Range<f32>) -> Result<f32, &'static str> {
     if float.is_finite() {
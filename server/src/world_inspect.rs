@@ -0,0 +1,154 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::contact_ref::ContactRef;
+use crate::entities::EntityIndex;
+use crate::player::Status;
+use crate::server::Server;
+use crate::world::World;
+use common::contact::ContactTrait;
+use common::death_reason::DeathReason;
+use common::entity::EntityId;
+use game_server::player::PlayerTuple;
+use glam::Vec2;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use std::fmt::Write;
+
+/// Admin tools for inspecting and editing a live world.
+impl World {
+    /// Describes each entity within `radius` of `center`, nearest first.
+    pub fn inspect_entities(&self, center: Vec2, radius: f32) -> Vec<String> {
+        let mut entities: Vec<_> = self
+            .entities
+            .iter_radius(center, radius)
+            .map(|(_, entity)| (entity.transform.position.distance_squared(center), entity))
+            .collect();
+        entities.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        entities
+            .into_iter()
+            .map(|(_, entity)| {
+                let contact = ContactRef::new(entity, true, true, true, false);
+                format!(
+                    "{} {:.1}m/s ticks={}",
+                    contact.snapshot_line(),
+                    entity.transform.velocity.to_mps(),
+                    entity.ticks.0,
+                )
+            })
+            .collect()
+    }
+
+    /// Describes a player's full state, including their boat (if any).
+    pub fn inspect_player(&self, tuple: &PlayerTuple<Server>) -> String {
+        let player = tuple.borrow_player();
+        let mut state = format!(
+            "player {} {:?} score={}\n",
+            player.player_id,
+            player.alias(),
+            player.score
+        );
+
+        match &player.data.status {
+            Status::Alive {
+                entity_index,
+                aim_target,
+                controlled_aircraft,
+                ..
+            } => {
+                let entity = &self.entities[*entity_index];
+                let data = entity.data();
+                let contact = ContactRef::new(entity, true, true, true, false);
+                let _ = writeln!(state, "boat {}", contact.snapshot_line());
+                let _ = writeln!(state, "damage {}/{}", entity.ticks.0, data.max_health().0);
+                let _ = writeln!(
+                    state,
+                    "sensors visual={:.0} radar={:.0} sonar={:.0} camera={:.0}",
+                    data.sensors.visual.range,
+                    data.sensors.radar.range,
+                    data.sensors.sonar.range,
+                    data.camera_range()
+                );
+                let reloads: Vec<_> = entity.extension().reloads.iter().map(|r| r.0).collect();
+                let _ = writeln!(state, "reloads {:?}", reloads);
+                let _ = writeln!(state, "aim {:?}", aim_target);
+                let _ = writeln!(state, "controlled aircraft {:?}", controlled_aircraft);
+            }
+            status => {
+                let _ = writeln!(state, "status {:?}", status);
+            }
+        }
+
+        let _ = writeln!(state, "control {:?}", player.data.control);
+        state.push_str("recent commands:\n");
+        for command in &player.data.recent_commands {
+            let _ = writeln!(state, "{}", command);
+        }
+        state
+    }
+
+    /// Finds an entity by id. Costs a scan of the whole world.
+    fn find_entity(&self, id: EntityId) -> Option<EntityIndex> {
+        self.entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .find_any(|(_, entity)| entity.id == id)
+            .map(|(index, _)| index)
+    }
+
+    /// Moves an entity to a position within the world border.
+    pub fn teleport(&mut self, id: EntityId, position: Vec2) -> Result<(), &'static str> {
+        if !position.is_finite() || position.length_squared() > self.radius.powi(2) {
+            return Err("outside world border");
+        }
+        let index = self.find_entity(id).ok_or("nonexistent entity")?;
+        self.entities[index].transform.position = position;
+        if index.changed(&self.entities[index]) {
+            self.entities.move_sector(index);
+        }
+        Ok(())
+    }
+
+    /// Removes an entity, as if its player left the game (if it's a boat).
+    pub fn delete(&mut self, id: EntityId) -> Result<(), &'static str> {
+        let index = self.find_entity(id).ok_or("nonexistent entity")?;
+        self.remove(index, DeathReason::Unknown);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::Entity;
+    use crate::world::World;
+    use common::entity::EntityType;
+    use common::terrain::Terrain;
+    use glam::{vec2, Vec2};
+
+    #[test]
+    fn teleport_and_delete() {
+        let mut world = World::new(5000.0);
+        world.terrain = Terrain::new();
+
+        let mut barrel = Entity::new(EntityType::Barrel, None);
+        barrel.transform.position = vec2(100.0, 0.0);
+        world.add(barrel);
+        let (_, barrel) = world
+            .entities
+            .iter_radius(Vec2::ZERO, 200.0)
+            .next()
+            .unwrap();
+        let id = barrel.id;
+
+        assert!(world.teleport(id, vec2(0.0, 10000.0)).is_err());
+        world.teleport(id, vec2(-3000.0, 0.0)).unwrap();
+        assert!(world.inspect_entities(Vec2::ZERO, 200.0).is_empty());
+        let entities = world.inspect_entities(vec2(-3000.0, 0.0), 200.0);
+        assert_eq!(entities.len(), 1);
+        let prefix = format!("{} {}", id, EntityType::Barrel.as_str());
+        assert!(entities[0].starts_with(&prefix));
+
+        world.delete(id).unwrap();
+        assert!(world.inspect_entities(vec2(-3000.0, 0.0), 200.0).is_empty());
+        assert!(world.delete(id).is_err());
+    }
+}
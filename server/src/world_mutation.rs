@@ -3,7 +3,9 @@
 
 use crate::entities::EntityIndex;
 use crate::entity::Entity;
-use crate::player::Status;
+use crate::entity_extension::EntityExtension;
+use crate::kraken::Kraken;
+use crate::player::{record_mission, Status};
 use crate::server::Server;
 use crate::world::World;
 use crate::world_physics_radius::MINE_SPEED;
@@ -12,11 +14,14 @@ use common::angle::Angle;
 use common::death_reason::DeathReason;
 use common::entity::*;
 use common::guidance::Guidance;
+use common::mission::Objective;
+use common::protocol::Sinking;
 use common::terrain::TerrainMutation;
+use common::ticks;
 use common::ticks::Ticks;
 use common::util::*;
 use common::velocity::Velocity;
-use game_server::player::PlayerTuple;
+use game_server::player::{PlayerData, PlayerTuple};
 use glam::Vec2;
 use rand::{thread_rng, Rng};
 use std::sync::Arc;
@@ -28,6 +33,8 @@ pub(crate) enum Mutation {
         other_player: Arc<PlayerTuple<Server>>,
         damage: Ticks,
         impulse: Velocity,
+        /// Change in direction due to being struck off-center.
+        deflection: Angle,
         ram: bool,
     },
     CollidedWithObstacle {
@@ -35,12 +42,17 @@ pub(crate) enum Mutation {
         entity_type: EntityType,
     },
     ClearSpawnProtection,
+    FiredAntiAircraft,
     UpgradeHq,
     #[allow(dead_code)]
     Score(u32),
     Remove(DeathReason),
     Repair(Ticks),
     Reload(Ticks),
+    /// Uses up some of the entity's lifespan, e.g. a net stopping a torpedo.
+    Wear(Ticks),
+    /// Slows the entity by a fraction of its velocity, e.g. a boat passing through debris.
+    Drag(f32),
     // For things that may only be collected once.
     CollectedBy(Arc<PlayerTuple<Server>>, u32),
     HitBy(Arc<PlayerTuple<Server>>, EntityType, Ticks),
@@ -59,6 +71,8 @@ impl Mutation {
         match self {
             Self::FireAll(_) => 127, // so that ASROC can fire before expiring
             Self::Remove(_) => 126,
+            // Simultaneous sinkings may be applied in any order, so kills are scored based on
+            // World::score_before_sinking.
             Self::HitBy(_, _, _) => 125,
             Self::CollidedWithBoat { .. } => 124,
             Self::CollectedBy(_, _) => 123,
@@ -122,37 +136,90 @@ impl Mutation {
             }
             Self::HitBy(other_player, weapon_type, damage) => {
                 let e = &mut entities[index];
+                if e.entity_type == EntityType::Kraken {
+                    world.kraken.record_damage(&other_player, damage);
+                }
+                if e.is_boat() && !Arc::ptr_eq(e.player.as_ref().unwrap(), &other_player) {
+                    let damage = ticks::to_damage(damage);
+                    e.borrow_player_mut().data.stats.hit_by(weapon_type, damage);
+                    let mut other_player = other_player.borrow_player_mut();
+                    other_player.data.stats.hit(weapon_type, damage);
+                }
                 if e.damage(damage) {
+                    let entity_type = e.entity_type;
                     let killer_alias = {
                         let e_score = e.borrow_player().score;
                         let mut other_player = other_player.borrow_player_mut();
-                        other_player.score += kill_score(e_score, other_player.score);
+                        // The killer may have sunk earlier this tick (e.g. they sunk each other).
+                        let killer_score = world.score_before_sinking(&other_player);
+                        other_player.score += world.blitz_score(kill_score(e_score, killer_score));
+                        Self::record_sinking(
+                            world,
+                            entity_type,
+                            killer_score,
+                            false,
+                            &mut other_player,
+                        );
                         let alias = other_player.alias();
                         drop(other_player);
                         alias
                     };
 
+                    world.intercept_smuggling(index, &other_player);
                     world.remove(index, DeathReason::Weapon(killer_alias, weapon_type));
                     return true;
                 }
+
+                let e = &mut world.entities[index];
+                let damage_fraction = damage.to_secs() / e.data().max_health().to_secs();
+                let mut rng = thread_rng();
+                if rng.gen_bool(EntityExtension::critical_chance(damage_fraction) as f64) {
+                    e.extension_mut().critical_hit(&mut rng);
+                }
+
+                // Shells are incendiary, and torpedoes hole the hull below the waterline.
+                let effect_chance = EntityExtension::effect_chance(damage_fraction) as f64;
+                match weapon_type.data().sub_kind {
+                    EntitySubKind::Shell if rng.gen_bool(effect_chance) => {
+                        e.extension_mut().ignite()
+                    }
+                    EntitySubKind::Torpedo if rng.gen_bool(effect_chance) => {
+                        e.extension_mut().flood()
+                    }
+                    _ => {}
+                }
             }
             Self::CollidedWithBoat {
                 damage,
                 impulse,
+                deflection,
                 other_player,
                 ram,
             } => {
                 let entity = &mut entities[index];
+                if entity.entity_type == EntityType::Kraken {
+                    world.kraken.record_damage(&other_player, damage);
+                }
                 if entity.damage(damage) {
                     let e_score = entity.borrow_player().score;
+                    let entity_type = entity.entity_type;
                     let killer_alias = {
                         let mut other_player = other_player.borrow_player_mut();
-                        other_player.score += ram_score(entity.borrow_player().score, e_score);
+                        let killer_score = world.score_before_sinking(&other_player);
+                        other_player.score += world.blitz_score(ram_score(e_score, killer_score));
+                        Self::record_sinking(
+                            world,
+                            entity_type,
+                            killer_score,
+                            ram,
+                            &mut other_player,
+                        );
                         let alias = other_player.alias();
                         drop(other_player);
                         alias
                     };
 
+                    world.intercept_smuggling(index, &other_player);
                     world.remove(
                         index,
                         if ram {
@@ -165,6 +232,7 @@ impl Mutation {
                 }
                 entity.transform.velocity =
                     (entity.transform.velocity + impulse).clamp_magnitude(Velocity::from_mps(15.0));
+                entity.transform.direction += deflection;
             }
             Self::CollidedWithObstacle {
                 impulse,
@@ -179,6 +247,7 @@ impl Mutation {
                     (entity.transform.velocity + impulse).clamp_magnitude(Velocity::from_mps(20.0));
             }
             Self::ClearSpawnProtection => entities[index].extension_mut().clear_spawn_protection(),
+            Self::FiredAntiAircraft => entities[index].extension_mut().set_firing_anti_aircraft(),
             Self::UpgradeHq => {
                 let entity = &mut entities[index];
                 entity.change_entity_type(EntityType::Hq, &mut world.arena, false);
@@ -190,11 +259,31 @@ impl Mutation {
             Self::Reload(amount) => {
                 entities[index].reload(amount);
             }
+            Self::Wear(amount) => {
+                let entity = &mut entities[index];
+                entity.ticks = entity.ticks.saturating_add(amount);
+                if entity.ticks > entity.data().lifespan {
+                    world.remove(index, DeathReason::Unknown);
+                    return true;
+                }
+            }
+            Self::Drag(fraction) => {
+                let entity = &mut entities[index];
+                entity.transform.velocity = entity.transform.velocity * (1.0 - fraction).max(0.0);
+            }
             Self::Score(score) => {
-                entities[index].borrow_player_mut().score += score;
+                let score = world.blitz_score(score);
+                world.entities[index].borrow_player_mut().score += score;
             }
             Self::CollectedBy(player, score) => {
-                player.borrow_player_mut().score += score;
+                let mut player = player.borrow_player_mut();
+                player.score += world.blitz_score(score);
+                match world.entities[index].entity_type {
+                    EntityType::Crate => record_mission(&mut player, Objective::CollectCrates, 1),
+                    EntityType::Barrel => record_mission(&mut player, Objective::CollectBarrels, 1),
+                    _ => {}
+                }
+                drop(player);
                 world.remove(index, DeathReason::Unknown);
                 return true;
             }
@@ -285,12 +374,47 @@ impl Mutation {
             let score_to_coins = matches!(
                 reason,
                 DeathReason::Border
+                    | DeathReason::Crushed
+                    | DeathReason::Fire
+                    | DeathReason::Flooding
                     | DeathReason::Terrain
                     | DeathReason::Unknown
                     | DeathReason::Obstacle(_)
             );
 
-            Self::boat_died(world, index, score_to_coins);
+            // Leaving the game (or the world or round resetting) doesn't count as sinking.
+            if !matches!(
+                reason,
+                DeathReason::Unknown | DeathReason::RoundEnded | DeathReason::SeasonEnded
+            ) {
+                let sinking = {
+                    let player = world.entities[index].borrow_player();
+                    let alias = player.alias();
+                    let player_id = player.player_id;
+                    drop(player);
+                    Sinking {
+                        player_id,
+                        alias,
+                        entity_type,
+                        reason: reason.clone(),
+                        mutual: world.detect_mutual_kill(alias, reason),
+                    }
+                };
+                world.sinkings.push(sinking);
+                world.record_dying_boat(index, reason);
+                world.spawn_wreckage(index);
+                if reason.is_due_to_player() {
+                    world.record_recent_death(world.entities[index].transform.position);
+                }
+            }
+
+            if entity_type == EntityType::Kraken {
+                let score = world.entities[index].borrow_player().score;
+                Self::boat_died(world, index, score_to_coins);
+                Kraken::on_death(world, score, reason);
+            } else {
+                Self::boat_died(world, index, score_to_coins);
+            }
         } else {
             if matches!(reason, DeathReason::Terrain) || data.sub_kind == EntitySubKind::DepthCharge
             {
@@ -330,14 +454,20 @@ impl Mutation {
         let mut player = entity.borrow_player_mut();
         let mut rng = thread_rng();
         let score = player.score;
+        let player_id = player.player_id;
+        let entity_type = entity.entity_type;
         player.score = if player.is_bot() {
             // Make sure there are bots in the shallow area.
             respawn_score(player.score).min(level_to_score(rng.gen_range(1..=2)))
         } else {
-            respawn_score(player.score)
+            // Bonus from completed missions goes towards the next boat.
+            respawn_score(player.score) + std::mem::take(&mut player.data.mission_bonus)
         };
         drop(player);
+        world.sunk_scores.push((player_id, score));
+        world.class_scores.push((player_id, entity_type, score));
 
+        let entity = &world.entities[index];
         let data = entity.data();
         debug_assert_eq!(data.kind, EntityKind::Boat);
 
@@ -367,39 +497,48 @@ impl Mutation {
         }
     }
 
+    /// Credits the player who sank (or rammed, if `ram`) a boat with any achievements they earned,
+    /// progress towards their missions, and the sinking in their statistics.
+    fn record_sinking(
+        world: &World,
+        entity_type: EntityType,
+        killer_score: u32,
+        ram: bool,
+        killer: &mut PlayerData<Server>,
+    ) {
+        let data = entity_type.data();
+        killer.data.stats.sunk.push(entity_type);
+        let killer_level = match killer.data.status {
+            Status::Alive { entity_index, .. } => world.entities[entity_index].data().level,
+            // The killer may have sunk earlier this tick.
+            _ => score_to_level(killer_score),
+        };
+        if let Some(client) = killer.client_mut() {
+            client.achievements.sank(killer_level, data.level);
+        }
+
+        record_mission(killer, Objective::SinkBoats, 1);
+        if data.sub_kind == EntitySubKind::Submarine {
+            record_mission(killer, Objective::SinkSubmarines, 1);
+        }
+        if ram {
+            record_mission(killer, Objective::RamBoats, 1);
+        }
+    }
+
     /// Called by on_world_remove when a non-boat dies.
     fn maybe_damage_terrain(world: &mut World, entity_index: EntityIndex) {
         let entity = &world.entities[entity_index];
         let data = entity.data();
 
         // Dying weapons may leave a mark on the terrain.
-        if data.kind == EntityKind::Weapon {
-            match data.sub_kind {
-                EntitySubKind::DepthCharge
-                | EntitySubKind::Missile
-                | EntitySubKind::Rocket
-                | EntitySubKind::Shell
-                | EntitySubKind::Torpedo => {
-                    // Weapons less than 0.7 damage do 0.7 amount damage/0.7% of the time.
-                    // Otherwise terrain wouldn't change since the delta would be too small.
-                    const MIN_AMOUNT: f32 = 0.7;
-                    let damage = data.damage;
-                    let probability = (damage * (1.0 / MIN_AMOUNT)).clamp(0.0, 1.0);
-                    let amount = data.damage.max(MIN_AMOUNT);
-
-                    if thread_rng().gen_bool(probability as f64) {
-                        // Modify terrain slightly in front of death, to account for finite tick rate.
-                        // Should be more correct, on average.
-                        let pos = entity.transform.position
-                            + (entity.transform.velocity.to_mps() * (Ticks::ONE.to_secs() * 0.5));
-                        world.terrain.modify(TerrainMutation::conditional(
-                            pos,
-                            -20.0 * amount,
-                            Altitude(-10)..=Altitude::MAX,
-                        ));
-                    }
-                }
-                _ => (),
+        if data.terrain_damage > 0.0 {
+            // Modify terrain slightly in front of death, to account for finite tick rate.
+            // Should be more correct, on average.
+            let pos = entity.transform.position
+                + (entity.transform.velocity.to_mps() * (Ticks::ONE.to_secs() * 0.5));
+            if let Some(mutation) = TerrainMutation::carve(pos, data.terrain_damage) {
+                world.terrain.modify(mutation);
             }
         }
     }
@@ -440,6 +579,7 @@ impl Mutation {
 
         let boat = &mut world.entities[boat_index];
         let armaments = &*boat.data().armaments;
+        let loadout = boat.extension().loadout();
         let consumption = boat.extension_mut().reloads_mut();
 
         if let Some(i) = landing_pad {
@@ -451,7 +591,7 @@ impl Mutation {
         for (a, c) in armaments
             .iter()
             .zip(consumption.iter_mut())
-            .filter(|(a, _)| a.entity_type == entity_type)
+            .filter(|(a, _)| a.entity_type == entity_type && a.in_loadout(loadout))
         {
             if try_reload(a, c) {
                 return;
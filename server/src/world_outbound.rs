@@ -7,13 +7,23 @@ use crate::entity::Entity;
 use crate::player::Status;
 use crate::server::Server;
 use crate::world::World;
+use common::altitude::Altitude;
 use common::entity::{EntityKind, EntitySubKind};
-use common::ticks::Ticks;
-use common_util::range::{map_ranges, map_ranges_fast};
+use common::interest::Interest;
+use common_util::range::map_ranges;
 use game_server::player::PlayerTuple;
 use glam::{vec2, Vec2};
 
+/// A fleet member's boat, relaying its radar contacts to the player via data link.
+struct DataLink {
+    position: Vec2,
+    radar: f32,
+}
+
 impl World {
+    /// Fleet members within this range relay their radar contacts, while their sensors are active.
+    pub const DATA_LINK_RANGE: f32 = 1500.0;
+
     /// get_player_complete gets the complete update for a player, corresponding to everything they
     /// are able to see at the current moment.
     pub fn get_player_complete<'a>(
@@ -32,6 +42,7 @@ impl World {
 
         struct Camera {
             active: bool,
+            altitude: Altitude,
             inner: f32,
             position: Vec2,
             radar: f32,
@@ -62,9 +73,32 @@ impl World {
                 sensors.sonar.range
             };
 
-            if player.data.status.is_alive() {
+            let controlled_aircraft = match player.data.status {
+                Status::Alive {
+                    controlled_aircraft: Some(id),
+                    ..
+                } => self.find_aircraft(entity, id),
+                _ => None,
+            };
+
+            if let Some(aircraft) = controlled_aircraft.map(|index| &self.entities[index]) {
+                // Players see what the aircraft they control sees.
+                let aircraft_data = aircraft.data();
+                Camera {
+                    active: false,
+                    altitude: aircraft.altitude,
+                    inner: aircraft_data.radii().start,
+                    position: aircraft.transform.position,
+                    radar: 0.0,
+                    sonar: 0.0,
+                    speed: aircraft.transform.velocity.abs().to_mps(),
+                    view: data.camera_range(),
+                    visual: aircraft_data.sensors.visual.range,
+                }
+            } else if player.data.status.is_alive() {
                 Camera {
                     active: entity.extension().is_active(),
+                    altitude: entity.altitude,
                     inner: data.radii().start,
                     position: entity.transform.position,
                     radar,
@@ -88,6 +122,7 @@ impl World {
             let range = map_ranges(elapsed, 10.0..2.0, 0.0..visual_range, true).max(500.0);
             Camera {
                 active: true,
+                altitude: Altitude::ZERO,
                 inner: 0.0,
                 position,
                 radar: range,
@@ -96,10 +131,25 @@ impl World {
                 view: range,
                 visual: range,
             }
+        } else if player.is_observer() {
+            // Observers (e.g. tournament casters) see the whole arena.
+            let range = self.radius;
+            Camera {
+                active: true,
+                altitude: Altitude::ZERO,
+                inner: 0.0,
+                position: Vec2::ZERO,
+                radar: range,
+                sonar: range,
+                speed: 0.0,
+                view: range,
+                visual: range,
+            }
         } else {
             let range = 500.0;
             Camera {
                 active: true,
+                altitude: Altitude::ZERO,
                 inner: 0.0,
                 position: Vec2::ZERO,
                 radar: range,
@@ -110,10 +160,39 @@ impl World {
             }
         };
 
+        // Fleet members, not including the player, whose radar contacts are relayed.
+        let data_links: Vec<DataLink> = player_entity
+            .filter(|_| player.team_id().is_some())
+            .map(|player_entity| {
+                self.entities
+                    .iter_radius(player_entity.transform.position, Self::DATA_LINK_RANGE)
+                    .map(|(_, e)| e)
+                    .filter(|e| {
+                        e.is_boat()
+                            && e.id != player_entity.id
+                            && e.is_friendly_to_player(Some(tuple))
+                            && e.extension().is_active()
+                            && !e.altitude.is_submerged()
+                            && e.data().sensors.radar.range > 0.0
+                    })
+                    .map(|e| DataLink {
+                        position: e.transform.position,
+                        radar: e.data().sensors.radar.range
+                            * map_ranges(e.altitude.to_norm(), -0.35..0.0, 0.0..1.0, true),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let visual_range_inv = camera.visual.powi(-2);
         let radar_range_inv = camera.radar.powi(-2);
         let sonar_range_inv = camera.sonar.powi(-2);
         let max_range = camera.visual.max(camera.radar.max(camera.sonar));
+        let max_range_squared = max_range.powi(2);
+        let data_link_range = data_links
+            .iter()
+            .map(|link| link.position.distance(camera.position) + link.radar)
+            .fold(max_range, f32::max);
         let close_proximity_squared = player_entity.map_or(0.0, |e| {
             (e.entity_type.data().radius + Entity::CLOSE_PROXIMITY).powi(2)
         });
@@ -125,7 +204,7 @@ impl World {
             .into_iter()
             .chain(
                 self.entities
-                    .iter_radius(camera.position, max_range)
+                    .iter_radius(camera.position, data_link_range)
                     .map(|(_, e)| e)
                     .filter(move |e| Some(*e) != player_entity),
             )
@@ -143,54 +222,32 @@ impl World {
 
                 // Variables related to detecting the contact.
                 let mut visible = false;
+                let mut relayed = false;
                 let mut uncertainty = 0f32;
                 let altitude = entity.altitude;
 
-                if !known {
+                if !known && distance_squared > max_range_squared {
+                    // Only fleet members can see this far.
+                    uncertainty = 1.0;
+                } else if !known {
                     let inv_size = data.inv_size;
                     let default_ratio = distance_squared * inv_size;
                     uncertainty = 1.0;
-                    let entity_abs_vel = entity.transform.velocity.abs().to_mps();
 
                     if radar_range_inv.is_finite() && !altitude.is_submerged() {
-                        let radar_ratio = default_ratio * radar_range_inv;
-
-                        if camera.active {
-                            // Active radar can see moving targets easier.
-                            uncertainty =
-                                uncertainty.min(radar_ratio * 15.0 / (15.0 + entity_abs_vel));
-                        }
-
-                        // Always-on passive radar:
-                        // Inlined to allow constant propagation and replace div with mul.
-                        const BASE_FACTOR: f32 = 25.0;
-                        const BASE_EMISSION: f32 = 5.0f32;
-                        // let mut emission = BASE_EMISSION;
-                        let passive_radar_ratio = if data.kind == EntityKind::Boat {
-                            const BOAT_EMISSION: f32 = 5.0;
-                            // emission += BOAT_EMISSION;
-                            if entity.extension().is_active() && data.sensors.radar.range > 0.0 {
-                                // Active radar gives away entity's position.
-                                const ACTIVE_EMISSION: f32 = 20.0;
-                                // emission += ACTIVE_EMISSION;
-                                BASE_FACTOR / (BASE_EMISSION + BOAT_EMISSION + ACTIVE_EMISSION)
-                            } else {
-                                BASE_FACTOR / (BASE_EMISSION + BOAT_EMISSION)
-                            }
-                        } else if data.sub_kind == EntitySubKind::Missile {
-                            const MISSILE_EMISSION: f32 = 30.0;
-                            // emission += MISSILE_EMISSION;
-                            BASE_FACTOR / (BASE_EMISSION + MISSILE_EMISSION)
-                        } else {
-                            BASE_FACTOR / BASE_EMISSION
-                        };
-                        // let passive_radar_ratio = BASE_FACTOR / emission;
-
-                        uncertainty = uncertainty.min(passive_radar_ratio);
+                        uncertainty = uncertainty.min(Self::radar_uncertainty(
+                            entity,
+                            default_ratio * radar_range_inv,
+                            camera.active,
+                        ));
                     }
 
                     if sonar_range_inv.is_finite() && !altitude.is_airborne() {
                         let mut sonar_ratio = default_ratio * sonar_range_inv;
+                        if Self::is_across_thermocline(camera.altitude, altitude) {
+                            sonar_ratio *= Self::THERMOCLINE_PENALTY;
+                        }
+
                         if camera.active {
                             // Active sonar.
                             uncertainty = uncertainty.min(sonar_ratio);
@@ -199,26 +256,7 @@ impl World {
                         // Beyond this point, sonar_ratio means passive sonar ratio.
 
                         // Always-on passive sonar:
-                        let mut noise = 2f32
-                            .max(entity_abs_vel - data.cavitation_speed(entity.altitude).to_mps());
-
-                        if data.kind == EntityKind::Boat
-                            || data.kind == EntityKind::Weapon
-                            || data.kind == EntityKind::Decoy
-                        {
-                            noise *= 2.0;
-
-                            if data.kind != EntityKind::Boat {
-                                noise += 100.0;
-                            } else if entity.extension().is_active()
-                                && data.sensors.sonar.range > 0.0
-                            {
-                                // Active sonar gives away entity's position.
-                                noise += 20.0;
-                            }
-                        }
-
-                        sonar_ratio /= noise;
+                        sonar_ratio /= Self::sonar_signature(entity);
 
                         // Making noise of your own reduces the performance of
                         // passive sonar
@@ -227,25 +265,8 @@ impl World {
                     }
 
                     if visual_range_inv.is_finite() {
-                        let mut visual_ratio = default_ratio * visual_range_inv;
-                        if altitude.is_submerged() {
-                            let extra = if data.kind == EntityKind::Boat
-                                && entity.extension().reloads.iter().any(|&t| t > Ticks::ZERO)
-                            {
-                                // A submarine that has fired recently is visible, for practical reasons.
-                                0.05
-                            } else {
-                                0.0
-                            };
-                            // Don't clamp high because to_norm can't return above 1.0 (high).
-                            visual_ratio /= map_ranges_fast(
-                                altitude.to_norm(),
-                                -0.5..1.0,
-                                0.0..0.8,
-                                true,
-                                false,
-                            ) + extra;
-                        }
+                        let visual_ratio =
+                            default_ratio * visual_range_inv / Self::visual_signature(entity);
                         visible = visual_ratio < 1.0;
                         uncertainty = uncertainty.min(visual_ratio);
                     }
@@ -262,20 +283,36 @@ impl World {
                         // towards them.
                         uncertainty = 0.4;
                     }
+                }
 
-                    if uncertainty >= 1.0 {
-                        // This player has no knowledge of this entity,
-                        // so it is not a contact.
-                        return None;
-                    }
+                if uncertainty >= 1.0 && !altitude.is_submerged() {
+                    let default_ratio = data.inv_size;
+                    uncertainty = data_links
+                        .iter()
+                        .filter_map(|link| {
+                            let ratio = default_ratio
+                                * link.position.distance_squared(entity.transform.position)
+                                * link.radar.powi(-2);
+                            (ratio < 1.0).then(|| Self::radar_uncertainty(entity, ratio, true))
+                        })
+                        .fold(uncertainty, f32::min);
+                    relayed = uncertainty < 1.0;
+                }
+
+                if uncertainty >= 1.0 {
+                    // This player has no knowledge of this entity,
+                    // so it is not a contact.
+                    return None;
                 }
 
                 let has_type = data.kind == EntityKind::Collectible
                     || friendly
-                    || uncertainty < 0.5
-                    || distance_squared < inner_circle_squared;
+                    || ((uncertainty < 0.5 || distance_squared < inner_circle_squared)
+                        // Distant boats are only worth a position.
+                        && (data.kind != EntityKind::Boat
+                            || Interest::of(distance_squared, camera_view) != Interest::Distant));
 
-                Some(ContactRef::new(entity, visible, known, has_type))
+                Some(ContactRef::new(entity, visible, known, has_type, relayed))
             });
 
         // How much more terrain can be sent.
@@ -289,6 +326,47 @@ impl World {
             camera_width * (1.0 / aspect).clamp(1.0, MAX_ASPECT),
         );
 
-        CompleteRef::new(contacts, player, self, camera_pos, camera_dims)
+        CompleteRef::new(contacts, player, self, camera_pos, camera_dims, camera_view)
+    }
+
+    /// Returns how uncertain radar is about `entity`, given the squared ratio of its distance to
+    /// (size adjusted) radar range. Values of 1.0 or more mean not detected.
+    #[inline]
+    fn radar_uncertainty(entity: &Entity, radar_ratio: f32, active: bool) -> f32 {
+        let data = entity.data();
+        let entity_abs_vel = entity.transform.velocity.abs().to_mps();
+        let mut uncertainty = 1f32;
+
+        if active {
+            // Active radar can see moving targets easier.
+            uncertainty = uncertainty.min(radar_ratio * 15.0 / (15.0 + entity_abs_vel));
+        }
+
+        // Always-on passive radar:
+        // Inlined to allow constant propagation and replace div with mul.
+        const BASE_FACTOR: f32 = 25.0;
+        const BASE_EMISSION: f32 = 5.0f32;
+        // let mut emission = BASE_EMISSION;
+        let passive_radar_ratio = if data.kind == EntityKind::Boat {
+            const BOAT_EMISSION: f32 = 5.0;
+            // emission += BOAT_EMISSION;
+            if entity.extension().is_active() && data.sensors.radar.range > 0.0 {
+                // Active radar gives away entity's position.
+                const ACTIVE_EMISSION: f32 = 20.0;
+                // emission += ACTIVE_EMISSION;
+                BASE_FACTOR / (BASE_EMISSION + BOAT_EMISSION + ACTIVE_EMISSION)
+            } else {
+                BASE_FACTOR / (BASE_EMISSION + BOAT_EMISSION)
+            }
+        } else if data.sub_kind == EntitySubKind::Missile {
+            const MISSILE_EMISSION: f32 = 30.0;
+            // emission += MISSILE_EMISSION;
+            BASE_FACTOR / (BASE_EMISSION + MISSILE_EMISSION)
+        } else {
+            BASE_FACTOR / BASE_EMISSION
+        };
+        // let passive_radar_ratio = BASE_FACTOR / emission;
+
+        uncertainty.min(passive_radar_ratio)
     }
 }
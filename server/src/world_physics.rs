@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::entities::EntityIndex;
+use crate::entity_extension::EntityExtension;
 use crate::player::{Flags, Status};
 use crate::world::World;
 use common::altitude::Altitude;
 use common::angle::Angle;
 use common::death_reason::DeathReason;
 use common::entity::*;
+use common::guidance::Guidance;
 use common::terrain::TerrainMutation;
 use common::ticks::Ticks;
 use common::transform::Transform;
@@ -21,6 +23,10 @@ use maybe_parallel_iterator::{IntoMaybeParallelIterator, MaybeParallelSort};
 use rand::Rng;
 use std::sync::{Arc, Mutex};
 
+/// Fraction of the ocean current that idle boats drift with. Collectibles and mines drift with
+/// all of it.
+const IDLE_BOAT_DRIFT: f32 = 0.25;
+
 /// Fate terminates the physics for a particular entity with a single fate.
 enum Fate {
     Remove(DeathReason),
@@ -32,15 +38,26 @@ impl World {
     /// update_entities performs updates intrinsic to one entity (and updates the world radius based
     /// on the number of boats). This is currently the only safe location for entity positions to change, due
     /// to the implementation of `Entities`.
-    pub fn physics(&mut self, delta: Ticks) {
-        let delta_seconds = delta.to_secs();
+    ///
+    /// Sectors are updated in parallel if the `threads` feature is enabled. Entities that move to
+    /// another sector, and other side effects, are collected and applied afterwards in order of
+    /// [`EntityIndex`], so the outcome doesn't depend on how the work was scheduled.
+    ///
+    /// Movement is simulated over `delta_seconds`, and timers over `delta` (see [`World::update`]).
+    pub fn physics(&mut self, delta: Ticks, delta_seconds: f32) {
+        self.simulation_lod.advance();
+
         let border_radius = self.radius; // Avoids double borrow.
         let border_radius_squared = self.radius.powi(2);
         let terrain = &self.terrain;
+        let currents = &*self.currents;
+        let simulation_lod = &self.simulation_lod;
+        let reload_multiplier = self.blitz_reload_multiplier();
 
-        // Collected updates (order doesn't matter).
+        // Collected updates, applied in order of the index of the entity that caused them.
         let terrain_mutations = Mutex::new(Vec::new());
         let barrel_spawns = Mutex::new(Vec::new());
+        // Order doesn't matter.
         let reset_flags = Mutex::new(Vec::new());
 
         let mut fates: Vec<_> = self
@@ -51,6 +68,9 @@ impl World {
                 let index = index as EntityIndex;
                 let data = entity.data();
 
+                // Entities far from real players may be simulated less often, but for longer.
+                let (delta, delta_seconds) = simulation_lod.delta(entity, delta, delta_seconds)?;
+
                 if data.lifespan != Ticks::ZERO {
                     entity.ticks = entity.ticks.saturating_add(delta);
 
@@ -83,52 +103,63 @@ impl World {
                 }
 
                 let mut max_speed = data.speed.to_mps();
+                let mut turn_scale = 1.0;
                 let mut repair_eligible = true;
 
                 match data.kind {
                     EntityKind::Aircraft => {
-                        let position_diff = if let Status::Alive {
-                            aim_target: Some(aim_target),
-                            ..
-                        } = entity.borrow_player().data.status
-                        {
-                            aim_target - entity.transform.position
-                        } else {
+                        let (position_diff, controlled) = match entity.borrow_player().data.status {
+                            // Steered by the player's controls instead.
+                            Status::Alive {
+                                controlled_aircraft: Some(id),
+                                ..
+                            } if id == entity.id => (Vec2::ZERO, true),
+                            Status::Alive {
+                                aim_target: Some(aim_target),
+                                ..
+                            } => (aim_target - entity.transform.position, false),
                             // Hover when no target or player is dead.
-                            Vec2::ZERO
+                            _ => (Vec2::ZERO, false),
                         };
 
-                        entity.guidance.direction_target = Angle::from(position_diff)
-                            + Angle::from_radians(
-                                (entity.hash() - 0.5) * std::f32::consts::PI * 0.25,
-                            );
-                        let distance_squared = position_diff.length_squared();
-
-                        let angle_deviation =
-                            (entity.transform.direction - entity.guidance.direction_target).abs();
-
-                        match data.sub_kind {
-                            EntitySubKind::Heli => {
-                                if angle_deviation < Angle::from_degrees(80.0) {
-                                    max_speed *= map_ranges(
-                                        distance_squared,
-                                        5.0..80f32.powi(2),
-                                        0.0..1.0,
-                                        true,
-                                    );
-                                } else {
-                                    // Turn in place.
-                                    max_speed = 0.0;
+                        if !controlled {
+                            entity.guidance = Guidance {
+                                direction_target: Angle::from(position_diff)
+                                    + Angle::from_radians(
+                                        (entity.hash() - 0.5) * std::f32::consts::PI * 0.25,
+                                    ),
+                                // In case the player was controlling it.
+                                velocity_target: data.speed,
+                            };
+                            let distance_squared = position_diff.length_squared();
+
+                            let angle_deviation = (entity.transform.direction
+                                - entity.guidance.direction_target)
+                                .abs();
+
+                            match data.sub_kind {
+                                EntitySubKind::Heli => {
+                                    if angle_deviation < Angle::from_degrees(80.0) {
+                                        max_speed *= map_ranges(
+                                            distance_squared,
+                                            5.0..80f32.powi(2),
+                                            0.0..1.0,
+                                            true,
+                                        );
+                                    } else {
+                                        // Turn in place.
+                                        max_speed = 0.0;
+                                    }
                                 }
-                            }
-                            EntitySubKind::Plane => {
-                                if distance_squared < 50.0f32.powi(2)
-                                    && angle_deviation > Angle::from_degrees(30.0)
-                                {
-                                    max_speed = max_speed.min(30.0);
+                                EntitySubKind::Plane => {
+                                    if distance_squared < 50.0f32.powi(2)
+                                        && angle_deviation > Angle::from_degrees(30.0)
+                                    {
+                                        max_speed = max_speed.min(30.0);
+                                    }
                                 }
+                                _ => unreachable!(),
                             }
-                            _ => unreachable!(),
                         }
 
                         entity.apply_altitude_target(terrain, None, 4.0, delta);
@@ -136,15 +167,14 @@ impl World {
                     EntityKind::Collectible | EntityKind::Weapon | EntityKind::Decoy => {
                         let altitude_change =
                             entity.apply_altitude_target(terrain, None, 3.0, delta);
+                        max_speed = data.max_speed(entity.altitude, DisabledModules::default());
                         if entity.altitude.is_submerged() {
                             match data.sub_kind {
-                                // Wait until risen to surface.
+                                // Rising to surface (see EntityData::max_speed).
                                 EntitySubKind::Missile
                                 | EntitySubKind::Rocket
                                 | EntitySubKind::RocketTorpedo
                                 | EntitySubKind::Sam => {
-                                    max_speed = EntityData::SURFACING_PROJECTILE_SPEED_LIMIT;
-
                                     // TODO: As long as ticks govern max range of weapons, prevent
                                     // weapon from timing out while rising to surface by reversing
                                     // delta.
@@ -154,8 +184,8 @@ impl World {
                                         entity.ticks = entity.ticks.saturating_sub(delta);
                                     }
                                 }
-                                EntitySubKind::Mine => {
-                                    // Delete mines when leaving populated team.
+                                EntitySubKind::Mine | EntitySubKind::Net => {
+                                    // Delete mines (and nets) when leaving populated team.
                                     if entity.borrow_player().data.flags.left_populated_team {
                                         return Some((index, Fate::Remove(DeathReason::Unknown)));
                                     }
@@ -165,9 +195,13 @@ impl World {
                         }
                     }
                     EntityKind::Boat => {
+                        let disabled = entity.extension().disabled();
+                        max_speed = data.max_speed(entity.altitude, disabled);
+                        turn_scale = disabled.turn_scale();
+
                         entity.apply_altitude_target(
                             terrain,
-                            Some(entity.extension().altitude_target()),
+                            Some(entity.extension().altitude_target(data)),
                             2.0,
                             delta,
                         );
@@ -180,6 +214,10 @@ impl World {
                         }
                     }
                     EntityKind::Obstacle => {
+                        if matches!(data.sub_kind, EntitySubKind::Debris | EntitySubKind::Wreck) {
+                            entity.altitude = World::wreckage_altitude(entity.ticks, data.lifespan);
+                        }
+
                         let rate: f32 = match entity.entity_type {
                             EntityType::OilPlatform => 1.0 / 4.0,
                             EntityType::Hq => 2.0 / 4.0,
@@ -192,16 +230,35 @@ impl World {
                             barrel_spawns
                                 .lock()
                                 .unwrap()
-                                .push(entity.transform.position)
+                                .push((index, entity.transform.position))
                         }
                     }
                     _ => {}
                 }
 
-                entity
-                    .transform
-                    .apply_guidance(data, entity.guidance, max_speed, delta_seconds);
-                entity.transform.do_kinematics(delta_seconds);
+                entity.transform.simulate(
+                    data,
+                    entity.guidance,
+                    max_speed,
+                    turn_scale,
+                    delta_seconds,
+                );
+
+                // Boats under way easily overcome the current.
+                let drift = match data.kind {
+                    EntityKind::Collectible => 1.0,
+                    EntityKind::Weapon if data.sub_kind == EntitySubKind::Mine => 1.0,
+                    EntityKind::Boat
+                        if entity.transform.velocity.abs() < Velocity::from_mps(1.0) =>
+                    {
+                        IDLE_BOAT_DRIFT
+                    }
+                    _ => 0.0,
+                };
+                if drift != 0.0 && !entity.altitude.is_airborne() {
+                    entity.transform.position +=
+                        currents.at(entity.transform.position) * (drift * delta_seconds);
+                }
 
                 let arctic = entity.transform.position.y >= ARCTIC;
 
@@ -259,10 +316,11 @@ impl World {
                                 breakable,
                             );
 
-                            terrain_mutations
-                                .lock()
-                                .unwrap()
-                                .push((terrain_mutation, is_icebreaker.then_some(index)));
+                            terrain_mutations.lock().unwrap().push((
+                                index,
+                                terrain_mutation,
+                                is_icebreaker.then_some(index),
+                            ));
                         }
                     }
 
@@ -333,9 +391,50 @@ impl World {
 
                 if data.kind == EntityKind::Boat {
                     entity.update_turret_aim(delta_seconds);
-                    entity.reload(delta);
+                    entity.reload(delta * reload_multiplier);
                     entity.extension_mut().update_tickers(delta);
 
+                    let test_depth = -data.test_depth();
+                    if data.sub_kind == EntitySubKind::Submarine && entity.altitude < test_depth {
+                        repair_eligible = false;
+                        // The hull gives way quicker the closer to crush depth.
+                        let kill_time = map_ranges(
+                            entity.altitude.to_meters(),
+                            test_depth.to_meters()..(-data.depth).to_meters(),
+                            60.0..10.0,
+                            true,
+                        );
+                        if entity.kill_in(delta, Ticks::from_secs(kill_time)) {
+                            return Some((index, Fate::Remove(DeathReason::Crushed)));
+                        }
+                    }
+
+                    let effects = entity.extension().effects();
+                    if effects.fire() {
+                        repair_eligible = false;
+                        if entity.kill_in(delta, EntityExtension::FIRE_KILL_TIME) {
+                            return Some((index, Fate::Remove(DeathReason::Fire)));
+                        }
+                    }
+                    if effects.flooding() {
+                        repair_eligible = false;
+                        // Pumps can only keep up if the boat slows down.
+                        if entity.transform.velocity.abs().to_mps()
+                            <= data.speed.to_mps() * EntityExtension::FLOODING_PUMP_SPEED
+                        {
+                            entity.extension_mut().pump_flooding(delta);
+                        }
+                        if entity.kill_in(delta, EntityExtension::FLOODING_KILL_TIME) {
+                            return Some((index, Fate::Remove(DeathReason::Flooding)));
+                        }
+                    }
+
+                    if entity.altitude.is_submerged()
+                        && entity.transform.velocity.abs() > data.cavitation_speed(entity.altitude)
+                    {
+                        entity.extension_mut().set_cavitating();
+                    }
+
                     if repair_eligible {
                         let repair_amount = if data.length > 200.0 {
                             3.0
@@ -350,6 +449,7 @@ impl World {
                     if data.sub_kind == EntitySubKind::Dredger {
                         // Dredgers excavate land they come into contact with.
                         terrain_mutations.lock().unwrap().push((
+                            index,
                             TerrainMutation::simple(entity.transform.position, -17.5),
                             None,
                         ))
@@ -364,18 +464,23 @@ impl World {
             })
             .collect();
 
-        for (mutation, award_entity_index) in terrain_mutations.into_inner().unwrap() {
+        let mut terrain_mutations = terrain_mutations.into_inner().unwrap();
+        terrain_mutations.sort_by_key(|&(index, _, _)| index);
+        for (_, mutation, award_entity_index) in terrain_mutations {
             if self.terrain.modify(mutation).unwrap_or(false) {
                 if let Some(index) = award_entity_index {
                     // Terrain actually changed, award some points.
-                    self.entities[index].borrow_player_mut().score += 1;
+                    let score = self.blitz_score(1);
+                    self.entities[index].borrow_player_mut().score += score;
                 }
             }
         }
 
         // Spawn barrels around oil platforms.
         let mut rng = rand::thread_rng();
-        for mut position in barrel_spawns.into_inner().unwrap() {
+        let mut barrel_spawns = barrel_spawns.into_inner().unwrap();
+        barrel_spawns.sort_by_key(|&(index, _)| index);
+        for (_, mut position) in barrel_spawns {
             const BARREL_RADIUS: f32 = 120.0;
             position +=
                 rng.gen::<Angle>().to_vec() * rng.gen_range((BARREL_RADIUS / 2.0)..BARREL_RADIUS);
@@ -426,14 +531,19 @@ impl World {
 
 #[cfg(test)]
 mod tests {
+    use crate::entities::SectorId;
     use crate::entity::Entity;
     use crate::world::World;
     use crate::Server;
-    use common::entity::{EntityKind, EntityType};
+    use common::angle::Angle;
+    use common::entity::{EntityId, EntityKind, EntityType};
     use common::terrain::Terrain;
     use common::ticks::Ticks;
     use core_protocol::id::PlayerId;
     use game_server::player::{PlayerData, PlayerTuple};
+    use glam::Vec2;
+    use maybe_parallel_iterator::IntoMaybeParallelIterator;
+    use std::convert::TryFrom;
     use std::num::NonZeroU32;
     use std::sync::Arc;
 
@@ -502,8 +612,8 @@ mod tests {
                 }
             }
 
-            world.physics(Ticks::ONE);
-            world.physics_radius(Ticks::ONE);
+            world.physics(Ticks::ONE, Ticks::ONE.to_secs());
+            world.physics_radius(Ticks::ONE, Ticks::ONE.to_secs());
             world.spawn_statics(Ticks::ONE);
             counter += Ticks::ONE;
         }
@@ -514,4 +624,142 @@ mod tests {
             println!("{:?} {:?}", case, timing.unwrap());
         }
     }
+
+    /// Tests that running at a higher tick rate, with some updates not completing a tick, doesn't
+    /// change the outcome.
+    #[test]
+    fn sub_ticks() {
+        let simulate = |steps: &[(Ticks, f32)]| {
+            let mut world = World::new(1000.0);
+            world.terrain = Terrain::new();
+            world
+                .simulation_lod
+                .set_observers(std::iter::once(Vec2::ZERO));
+
+            let player: Arc<PlayerTuple<Server>> = Arc::new(PlayerTuple::new(PlayerData::new(
+                PlayerId(NonZeroU32::new(1).unwrap()),
+                None,
+            )));
+            let mut boat = Entity::new(EntityType::G5, Some(Arc::clone(&player)));
+            boat.transform.velocity = boat.data().speed;
+            boat.guidance.velocity_target = boat.data().speed;
+            boat.damage(Ticks::from_secs(5.0));
+            world.add(boat);
+
+            for &(delta, delta_seconds) in steps.iter().cycle().take(steps.len() * 20) {
+                world.physics(delta, delta_seconds);
+            }
+
+            let index = player
+                .borrow_player()
+                .data
+                .status
+                .get_entity_index()
+                .unwrap();
+            let boat = &world.entities[index];
+            (boat.transform.position, boat.ticks)
+        };
+
+        let (position, damage) = simulate(&[(Ticks::ONE, 0.1)]);
+        let (position_20hz, damage_20hz) = simulate(&[(Ticks::ZERO, 0.05), (Ticks::ONE, 0.05)]);
+        assert!(
+            position.distance(position_20hz) < 0.1,
+            "{} vs {}",
+            position,
+            position_20hz
+        );
+        assert_eq!(damage, damage_20hz);
+        assert!(damage < Ticks::from_secs(5.0), "should have repaired");
+    }
+
+    /// Tests that simulating sectors in parallel gives the same outcome as simulating them one
+    /// at a time. Only exercises the parallel stages of [`World::update`], and avoids sinking,
+    /// since spawning, critical hits, and loot are random.
+    #[test]
+    fn parallel_matches_serial() {
+        let simulate = |threads: usize| {
+            let mut world = World::new(1500.0);
+            world.terrain = Terrain::new();
+            world
+                .simulation_lod
+                .set_observers(std::iter::once(Vec2::ZERO));
+
+            let mut player_ids = 0;
+            let mut new_player = || -> Arc<PlayerTuple<Server>> {
+                player_ids += 1;
+                Arc::new(PlayerTuple::new(PlayerData::new(
+                    PlayerId(NonZeroU32::new(player_ids).unwrap()),
+                    None,
+                )))
+            };
+
+            // Turning boats spanning several sectors, some near enough to sector boundaries to
+            // cross them, each overlapping another boat so their collisions depend on order.
+            for i in 0..64u32 {
+                let position = Vec2::new(
+                    (i % 8) as f32 * 200.0 - 750.0,
+                    (i / 8) as f32 * 200.0 - 750.0,
+                );
+                let direction = Angle::from_degrees((i * 37 % 360) as f32);
+
+                for offset in [0.0, 5.0] {
+                    let mut boat = Entity::new(EntityType::G5, Some(new_player()));
+                    boat.transform.position = position + direction.to_vec().perp() * offset;
+                    boat.transform.direction = direction;
+                    boat.transform.velocity = boat.data().speed * 0.5;
+                    boat.guidance.direction_target = direction + Angle::from_degrees(45.0);
+                    boat.guidance.velocity_target = boat.data().speed * 0.5;
+                    world.add(boat);
+                }
+            }
+
+            let sectors = |world: &World| {
+                let mut sectors: Vec<(EntityId, SectorId)> = world
+                    .entities
+                    .par_iter()
+                    .into_maybe_parallel_iter()
+                    .map(|(_, entity)| {
+                        (
+                            entity.id,
+                            SectorId::try_from(entity.transform.position).unwrap(),
+                        )
+                    })
+                    .collect();
+                sectors.sort_unstable_by_key(|&(id, _)| id);
+                sectors
+            };
+            let initial_sectors = sectors(&world);
+
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                for _ in 0..50 {
+                    world.physics(Ticks::ONE, Ticks::ONE.to_secs());
+                    world.physics_radius(Ticks::ONE, Ticks::ONE.to_secs());
+                }
+            });
+            assert!(world.sinkings.is_empty(), "sinking is random");
+
+            assert_ne!(
+                sectors(&world),
+                initial_sectors,
+                "expected some boats to change sectors"
+            );
+
+            let mut entities: Vec<_> = world
+                .entities
+                .par_iter()
+                .into_maybe_parallel_iter()
+                .map(|(_, entity)| (entity.id, entity.transform, entity.guidance, entity.ticks))
+                .collect();
+            entities.sort_unstable_by_key(|&(id, ..)| id);
+            entities
+        };
+
+        let serial = simulate(1);
+        assert_eq!(serial.len(), 128);
+        assert_eq!(simulate(4), serial);
+    }
 }
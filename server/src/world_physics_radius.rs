@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Softbear, Inc.
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::collision::{hit_angle_cos, ram_damage_multiplier, ram_deflection, ram_impulse};
 use crate::entities::EntityIndex;
 use crate::entity::Entity;
 use crate::world::World;
@@ -60,9 +61,9 @@ impl World {
     }
 
     /// update_entities_and_others performs updates on each pair of entities, with some exceptions.
-    pub fn physics_radius(&mut self, delta: Ticks) {
-        let delta_seconds = delta.to_secs();
-
+    /// Movement is considered over `delta_seconds`, and damage over `delta` (see
+    /// [`World::update`]).
+    pub fn physics_radius(&mut self, delta: Ticks, delta_seconds: f32) {
         // TODO: look into lock free data structures.
         let mutations = Mutex::new(Vec::new());
 
@@ -130,8 +131,11 @@ impl World {
                         }
                     };
 
-                    let mutate =
-                        |e: &Entity, m: Mutation| mutations.lock().unwrap().push((get_index(e), m));
+                    // Also records which entity's iteration caused the mutation, to apply
+                    // mutations in the same order regardless of how the work was scheduled.
+                    let mutate = |e: &Entity, m: Mutation| {
+                        mutations.lock().unwrap().push((get_index(e), m, index))
+                    };
 
                     macro_rules! debug_remove {
                         ($entity:expr, $($arg:tt)*) => {
@@ -157,7 +161,8 @@ impl World {
                         }
 
                         // Repair obstacles near non bots to prevent them from decaying in front of players.
-                        if boats.len() == 1 && obstacles.len() == 1 && !boats[0].borrow_player().player_id.is_bot() && obstacles[0].data().lifespan != Ticks::ZERO {
+                        // Wreckage is meant to be temporary, though.
+                        if boats.len() == 1 && obstacles.len() == 1 && !boats[0].borrow_player().player_id.is_bot() && obstacles[0].data().lifespan != Ticks::ZERO && !matches!(obstacles[0].data().sub_kind, EntitySubKind::Debris | EntitySubKind::Wreck) {
                             // Repair them ten times as fast as they decay.
                             mutate(obstacles[0], Mutation::Repair(delta * 10.0));
                         }
@@ -181,14 +186,12 @@ impl World {
                                 let target_data = target.data();
 
 
-                                let sub_kind = weapon_data.sub_kind;
-                                let is_rocket_torpedo = data.sub_kind == EntitySubKind::RocketTorpedo;
+                                let is_rocket_torpedo = weapon_data.sub_kind == EntitySubKind::RocketTorpedo;
                                 let mut rocket_torpedo_sensed = false;
 
-                                if weapon_data.sensors.any() && sub_kind != EntitySubKind::Rocket {
+                                if let Some(seeker) = weapon_data.seeker.as_ref() {
                                     // Home towards target/decoy
-                                    // Sensor activates after 1 second.
-                                    if weapons[0].ticks > Ticks::from_secs(1.0) {
+                                    if seeker.is_scanning(weapon.ticks) {
                                         // Different targets are relevant to each weapon.
                                         let relevant = match weapon_data.sub_kind {
                                             EntitySubKind::Sam => {
@@ -217,27 +220,18 @@ impl World {
 
                                             // Should not exceed range.
                                             let remaining_range = weapon.transform.velocity.to_mps() * weapon.data().lifespan.saturating_sub(weapon.ticks).to_secs() + 30.0;
-                                            // Should not go off target.
-                                            let angle_target_diff = (angle - weapon.guidance.direction_target).abs();
-                                            // Cannot sense beyond this angle.
                                             let angle_diff = (angle - weapon.transform.direction).abs();
 
-                                            let (max_angle_target_diff, max_angle_diff) = if weapon.data().sub_kind == EntitySubKind::Missile {
-                                                (Angle::from_degrees(30.0), Angle::from_degrees(40.0))
-                                            } else {
-                                                (Angle::from_degrees(60.0), Angle::from_degrees(80.0))
-                                            };
-                                            if (is_rocket_torpedo || distance_squared <= remaining_range.powi(2)) && angle_target_diff <= max_angle_target_diff && angle_diff <= max_angle_diff {
+                                            if (is_rocket_torpedo || distance_squared <= remaining_range.powi(2)) && seeker.can_acquire(angle, weapon.transform.direction, weapon.guidance.direction_target) {
                                                 if is_rocket_torpedo {
                                                     rocket_torpedo_sensed = true;
                                                 } else {
                                                     let mut size = target_data.radius;
                                                     if target_data.kind == EntityKind::Decoy {
-                                                        // Decoys appear very large to weapons.
-                                                        size += 200.0;
+                                                        size += target_data.decoy_size * seeker.decoy_susceptibility;
                                                     } else if target_data.kind == EntityKind::Boat && target_data.sensors.any() && target.extension().is_active() {
-                                                        // So do boats with active sensors.
-                                                        size += 75.0;
+                                                        // Boats with active sensors appear larger too.
+                                                        size += Seeker::ACTIVE_SENSOR_SIZE;
                                                     }
 
                                                     // Switch target from keel to center of boat if it's rotating away.
@@ -274,7 +268,8 @@ impl World {
                                     Some(weapon_data.armaments[0].entity_type.data().sub_kind)
                                 } else if weapon_data.kind == EntityKind::Aircraft {
                                     match target_data.kind {
-                                        EntityKind::Boat => {
+                                        // Submerged subs can't be spotted unless shallow or cavitating.
+                                        EntityKind::Boat if target.is_visible_to_aircraft() => {
                                             weapon_data.armaments.iter().map(|a| a.entity_type.data().sub_kind).find(|&s| {
                                                 if s == EntitySubKind::Sam {
                                                     return false;
@@ -320,14 +315,22 @@ impl World {
                                     }
                                 }
 
-                                // Automatic anti-aircraft has a chance of killing aircraft.
-                                if target_data.anti_aircraft > 0.0 && weapon_data.kind == EntityKind::Aircraft && target_data.kind == EntityKind::Boat {
+                                // Automatic anti-aircraft has a chance of killing aircraft and missiles, depending on doctrine.
+                                let efficacy = if target_data.kind == EntityKind::Boat && !weapon.altitude.is_submerged() {
+                                    target.extension().doctrine().efficacy(weapon_data)
+                                } else {
+                                    0.0
+                                };
+                                if target_data.anti_aircraft > 0.0 && efficacy > 0.0 {
                                     let d2 = weapon.transform.position.distance_squared(target.transform.position);
                                     let r2 = target_data.anti_aircraft_range().powi(2);
 
                                     // In range of aa.
                                     if d2 <= r2 {
-                                        let chance = (1.0 - d2/r2) * target_data.anti_aircraft * delta.to_secs();
+                                        // Tracers give away the boat's position.
+                                        mutate(target, Mutation::FiredAntiAircraft);
+
+                                        let chance = (1.0 - d2/r2) * target_data.anti_aircraft * efficacy * delta_seconds;
                                         if thread_rng().gen_bool((chance as f64).clamp(0.0, 1.0)) {
                                             debug_remove!(weapon, "shot down");
                                         }
@@ -422,10 +425,28 @@ impl World {
                             let data = boat.data();
                             let other_data = other_boat.data();
 
-                            // Approximate mass.
-                            let mass = data.width * data.length;
-                            let other_mass = other_data.width * other_data.length;
-                            let mut relative_mass = other_mass / mass;
+                            // Approximate mass. Boats that do more ram damage, especially rams,
+                            // are built to take less recoil.
+                            fn ram_mass(data: &EntityData) -> f32 {
+                                let mass = data.width * data.length * data.ram_damage;
+                                if data.sub_kind == EntitySubKind::Ram {
+                                    mass * 10.0
+                                } else {
+                                    mass
+                                }
+                            }
+                            let mass = ram_mass(data);
+                            let other_mass = ram_mass(other_data);
+
+                            let closest_point_on_other_keel = other_boat.closest_point_on_keel_to(boat.transform.position, 1.0);
+
+                            // Direction of repulsion.
+                            let normal = (boat.transform.position - closest_point_on_other_keel).normalize_or_zero();
+
+                            // Momentum transferred by the collision, which both slows and deflects.
+                            let speed = boat.transform.velocity.to_mps();
+                            let other_velocity = other_boat.transform.direction.to_vec() * other_boat.transform.velocity.to_mps();
+                            let jolt = ram_impulse(mass, boat.transform.direction.to_vec() * speed, other_mass, other_velocity, normal);
 
                             let mut damage = base_damage;
 
@@ -435,17 +456,12 @@ impl World {
                                 let front_d2 = front_pos.distance_squared(boat.transform.position);
                                 damage *= collision_multiplier(front_d2, data.radius.powi(2), data.sub_kind == EntitySubKind::Submarine);
                                 damage *= boat.extension().spawn_protection();
-
-                                // Boats that do more ram damage take less recoil.
-                                if data.ram_damage != 1.0 {
-                                    relative_mass /= data.ram_damage;
-                                }
+                                // Being jolted is more deadly, so glancing scrapes are survivable.
+                                damage *= ram_damage_multiplier(jolt);
 
                                 match data.sub_kind {
                                     EntitySubKind::Ram => {
                                         mutate(boat, Mutation::ClearSpawnProtection);
-                                        // Rams take less recoil.
-                                        relative_mass *= 0.1;
                                         // Rams take less damage from ramming.
                                         damage *= 1.0 / data.ram_damage;
                                     }
@@ -463,21 +479,33 @@ impl World {
                                 }
 
                                 damage *= other_data.ram_damage;
-                            } else {
-                                // Friendly targets are repelled quicker.
-                                relative_mass *= 3.0;
                             }
 
-                            let closest_point_on_other_keel = other_boat.closest_point_on_keel_to(boat.transform.position, 1.0);
-
-                            // Direction of repulsion.
-                            let pos_diff_closest_point_on_other_keel = (boat.transform.position - closest_point_on_other_keel).normalize_or_zero();
+                            // Boats that are overlapping, but no longer closing, are still pushed
+                            // apart. Friendly boats are repelled quicker.
+                            let separation = if friendly { 6.0 } else { 2.0 } * other_mass / mass;
 
-                            // Velocity change to cause repulsion.
-                            let impulse = Velocity::from_mps(2.0 * pos_diff_closest_point_on_other_keel.dot(boat.transform.direction.to_vec()) * relative_mass);
+                            // Boats only move forward/backward, so the sideways component of the
+                            // collision turns them instead.
+                            let impulse = Velocity::from_mps((jolt + normal * separation).dot(boat.transform.direction.to_vec()));
+                            let deflection = ram_deflection(boat.transform.direction, speed, jolt);
 
-                            mutate(boat, Mutation::CollidedWithBoat{other_player: Arc::clone(other_boat.player.as_ref().unwrap()), damage, ram: other_data.ram_damage > 1.0, impulse});
+                            mutate(boat, Mutation::CollidedWithBoat{other_player: Arc::clone(other_boat.player.as_ref().unwrap()), damage, ram: other_data.ram_damage > 1.0, impulse, deflection});
                         }
+                    } else if weapons.iter().any(|w| w.data().sub_kind == EntitySubKind::Net) {
+                        // Nets stop enemy torpedoes, wearing out in the process. Everything else
+                        // passes over or under them.
+                        if weapons.len() == 2 && !friendly {
+                            for (net, torpedo) in [(weapons[0], weapons[1]), (weapons[1], weapons[0])] {
+                                let torpedo_data = torpedo.data();
+                                if net.data().sub_kind == EntitySubKind::Net && torpedo_data.sub_kind == EntitySubKind::Torpedo {
+                                    mutate(net, Mutation::Wear(ticks::from_damage(torpedo_data.damage)));
+                                    debug_remove!(torpedo, "netted");
+                                }
+                            }
+                        }
+                    } else if boats.len() == 1 && weapons.len() == 1 && !friendly && !weapons[0].is_armed() {
+                        // No-op; torpedoes pass harmlessly until armed.
                     } else if boats.len() == 1 && weapons.len() == 1 && !friendly {
                         let boat_data = boats[0].data();
                         let weapon_data = weapons[0].data();
@@ -488,7 +516,12 @@ impl World {
                             .distance_squared(weapons[0].transform.position);
                         let r2 = boat_data.radius.powi(2);
 
-                        let damage_resistance = boat_data.resistance_to_subkind(weapon_data.sub_kind) * boats[0].extension().spawn_protection();
+                        let mut damage_resistance = boat_data.resistance_to_subkind(weapon_data.sub_kind) * boats[0].extension().spawn_protection();
+
+                        // Mines and depth charges explode around the hull, not into a side of it.
+                        if !matches!(weapon_data.sub_kind, EntitySubKind::Mine | EntitySubKind::DepthCharge) {
+                            damage_resistance *= boat_data.armor_multiplier(hit_angle_cos(boats[0].transform, weapons[0].transform));
+                        }
 
                         let damage = ticks::from_damage(
                             weapon_data.damage * collision_multiplier(d2, r2, boat_data.sub_kind == EntitySubKind::Submarine) * damage_resistance,
@@ -503,6 +536,11 @@ impl World {
                             ),
                         );
                         debug_remove!(weapons[0], "hit");
+                    } else if boats.len() == 1 && obstacles.len() == 1 && obstacles[0].data().sub_kind == EntitySubKind::Debris {
+                        // Boats plow through debris, but are slowed down.
+                        mutate(boats[0], Mutation::Drag(World::DEBRIS_DRAG * delta_seconds));
+                    } else if weapons.len() == 1 && obstacles.len() == 1 && obstacles[0].data().sub_kind == EntitySubKind::Debris && weapons[0].data().sub_kind != EntitySubKind::Shell {
+                        // No-op; debris only stops shells.
                     } else if boats.len() == 1 && obstacles.len() == 1 {
                         let pos_diff = (boats[0].transform.position - obstacles[0].transform.position).normalize_or_zero();
 
@@ -552,24 +590,29 @@ impl World {
 
         let mut mutations = mutations.into_inner().unwrap();
 
-        // Sort by reverse EntityIndex while prioritizing Mutation ordering.
-        mutations.maybe_par_sort_unstable_by(|a, b| {
+        // Sort by reverse EntityIndex while prioritizing Mutation ordering. Ties are broken by
+        // the entity that caused the mutation, and then by the order in which that entity caused
+        // them (the sort is stable), so the outcome doesn't depend on thread scheduling.
+        mutations.maybe_par_sort_by(|a, b| {
             b.0.cmp(&a.0).then_with(|| {
-                b.1.absolute_priority().cmp(&a.1.absolute_priority()).then(
-                    b.1.relative_priority()
-                        .partial_cmp(&a.1.relative_priority())
-                        .unwrap(),
-                )
+                b.1.absolute_priority()
+                    .cmp(&a.1.absolute_priority())
+                    .then(
+                        b.1.relative_priority()
+                            .partial_cmp(&a.1.relative_priority())
+                            .unwrap(),
+                    )
+                    .then(b.2.cmp(&a.2))
             })
         });
 
         // Apply mutations (already reversed).
         let mut skip = None;
         let mut iter = mutations.into_iter().peekable();
-        while let Some((index, mutation)) = iter.next() {
+        while let Some((index, mutation, _)) = iter.next() {
             let last_of_mutation_type = iter
                 .peek()
-                .map(|(next_index, next_mutation)| {
+                .map(|(next_index, next_mutation, _)| {
                     *next_index != index
                         || std::mem::discriminant(&mutation)
                             != std::mem::discriminant(next_mutation)
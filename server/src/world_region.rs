@@ -0,0 +1,46 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::noise::{noise_generator, set_region};
+use crate::world::World;
+use common::region::Region;
+use common::terrain::Terrain;
+use common::ticks::Ticks;
+use common::velocity::Velocity;
+
+impl World {
+    /// Region structures are considered present if an entity of the same type is this close.
+    const REGION_STRUCTURE_TOLERANCE: f32 = 1.0;
+
+    /// Stamps a handcrafted region, made with the map editor, onto the procedural world. Its
+    /// terrain replaces generated terrain, and its structures are spawned (see
+    /// [`Self::spawn_region_structures`]).
+    pub fn stamp_region(&mut self, region: Region) {
+        // Must precede terrain generation.
+        set_region(&region);
+        self.terrain = Terrain::with_generator(noise_generator);
+        self.region_structures = region.structures;
+    }
+
+    /// Spawns any region structures that are missing, such as those that were destroyed, or were
+    /// previously outside the world border.
+    pub fn spawn_region_structures(&mut self) {
+        for i in 0..self.region_structures.len() {
+            let structure = self.region_structures[i];
+            let present = self
+                .entities
+                .iter_radius(structure.position, Self::REGION_STRUCTURE_TOLERANCE)
+                .any(|(_, entity)| entity.entity_type == structure.entity_type);
+
+            if !present {
+                self.spawn_static(
+                    structure.entity_type,
+                    structure.position,
+                    structure.direction,
+                    Velocity::ZERO,
+                    Ticks::ZERO,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::noise::{current_generator, noise_generator, set_seed};
+use crate::world::World;
+use common::current::Currents;
+use common::death_reason::DeathReason;
+use common::terrain::Terrain;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use std::sync::Arc;
+
+impl World {
+    /// Starts a new season: sinks every boat, clears all other entities, and regenerates the
+    /// terrain (and currents) from a seed specific to the season. Clients must be resent all
+    /// terrain.
+    pub fn reset_for_season(&mut self, season: u32) {
+        let mut boats: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(index, entity)| entity.is_boat().then_some(index))
+            .collect();
+        // Sorted in reverse to remove correctly.
+        boats.sort_unstable_by(|a, b| b.cmp(a));
+        for index in boats {
+            self.remove(index, DeathReason::SeasonEnded);
+        }
+
+        // Includes anything sinking boats left behind, such as coins.
+        let mut others: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .map(|(index, _)| index)
+            .collect();
+        others.sort_unstable_by(|a, b| b.cmp(a));
+        for index in others {
+            self.remove(index, DeathReason::Unknown);
+        }
+
+        // Must precede terrain generation.
+        set_seed(season as f64 * 1000.0);
+        self.terrain = Terrain::with_generator(noise_generator);
+        self.currents = Arc::new(Currents::with_generator(current_generator));
+
+        self.pings.clear();
+        self.dying_boats.clear();
+        self.recent_deaths.clear();
+        self.smuggling = None;
+        self.shipwrecks.clear();
+        self.zones.clear();
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entity::Entity;
+use crate::world::World;
+use common::altitude::Altitude;
+use common::entity::{EntityId, EntityKind};
+use common::ticks::Ticks;
+use common_util::range::map_ranges_fast;
+use glam::Vec2;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use rand::{thread_rng, Rng};
+
+/// An active sonar ping, which is heard much further away than the pinging boat's sonar can
+/// detect anything, giving away its position.
+pub struct SonarPing {
+    /// The pinging boat, which doesn't need to be told about its own ping.
+    pub entity_id: EntityId,
+    pub position: Vec2,
+    /// Anyone within this range hears the ping.
+    pub range: f32,
+}
+
+impl World {
+    /// Colder water below this depth refracts sound, so sonar returns are reduced between entities
+    /// on opposite sides of it.
+    pub const THERMOCLINE: Altitude = Altitude::from_whole_meters(-60);
+    /// Multiplies the sonar ratio (of distance squared to range squared) across the thermocline.
+    pub(crate) const THERMOCLINE_PENALTY: f32 = 2.5;
+    /// Average time between pings, while a boat's active sonar is on.
+    const PING_PERIOD: Ticks = Ticks::from_whole_secs(3);
+    /// Pings are heard this many times further than the pinging boat's sonar range.
+    const PING_RANGE_FACTOR: f32 = 2.0;
+    /// Boats moving faster than this (in meters per second) leave a wake that is easier to see.
+    const WAKE_SPEED: f32 = 5.0;
+    /// Added to the visual signature of boats that recently fired anti-aircraft guns.
+    const ANTI_AIRCRAFT_SIGNATURE: f32 = 1.0;
+
+    /// Emits pings from boats using active sonar. Call once per tick, after which pings are
+    /// cleared once clients receive updates.
+    pub fn update_pings(&mut self, delta: Ticks) {
+        let probability = (delta.to_secs() / Self::PING_PERIOD.to_secs()).min(1.0) as f64;
+
+        let pings: Vec<SonarPing> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(_, entity)| {
+                let sonar = entity.data().sensors.sonar.range;
+                (entity.is_boat()
+                    && sonar > 0.0
+                    && !entity.altitude.is_airborne()
+                    && entity.extension().is_active()
+                    && thread_rng().gen_bool(probability))
+                .then(|| SonarPing {
+                    entity_id: entity.id,
+                    position: entity.transform.position,
+                    range: sonar * Self::PING_RANGE_FACTOR,
+                })
+            })
+            .collect();
+
+        self.pings.extend(pings);
+    }
+
+    /// Returns true if the thermocline is between two altitudes.
+    #[inline]
+    pub(crate) fn is_across_thermocline(a: Altitude, b: Altitude) -> bool {
+        (a < Self::THERMOCLINE) != (b < Self::THERMOCLINE)
+    }
+
+    /// How visible an entity is, as a divisor of the visual ratio. Depth reduces it, and so does
+    /// moving slowly enough not to leave a wake.
+    #[inline]
+    pub(crate) fn visual_signature(entity: &Entity) -> f32 {
+        let data = entity.data();
+        let altitude = entity.altitude;
+
+        if altitude.is_submerged() {
+            let extra = if data.kind == EntityKind::Boat
+                && entity.extension().reloads.iter().any(|&t| t > Ticks::ZERO)
+            {
+                // A submarine that has fired recently is visible, for practical reasons.
+                0.05
+            } else {
+                0.0
+            };
+            // Don't clamp high because to_norm can't return above 1.0 (high).
+            map_ranges_fast(altitude.to_norm(), -0.5..1.0, 0.0..0.8, true, false) + extra
+        } else if data.kind == EntityKind::Boat {
+            let speed = entity.transform.velocity.abs().to_mps();
+            let anti_aircraft = if entity.extension().recently_fired_anti_aircraft() {
+                // Tracers are visible from afar.
+                Self::ANTI_AIRCRAFT_SIGNATURE
+            } else {
+                0.0
+            };
+            1.0 + (speed - Self::WAKE_SPEED).max(0.0) * 0.02 + anti_aircraft
+        } else {
+            1.0
+        }
+    }
+
+    /// How noisy an entity is, as a divisor of the passive sonar ratio. Speed beyond cavitation
+    /// increases it, as does using active sonar.
+    #[inline]
+    pub(crate) fn sonar_signature(entity: &Entity) -> f32 {
+        let data = entity.data();
+        let speed = entity.transform.velocity.abs().to_mps();
+        let mut noise = 2f32.max(speed - data.cavitation_speed(entity.altitude).to_mps());
+
+        if data.kind == EntityKind::Boat
+            || data.kind == EntityKind::Weapon
+            || data.kind == EntityKind::Decoy
+        {
+            noise *= 2.0;
+
+            if data.kind != EntityKind::Boat {
+                noise += 100.0;
+            } else if entity.extension().is_active() && data.sensors.sonar.range > 0.0 {
+                // Active sonar gives away entity's position.
+                noise += 20.0;
+            }
+        }
+
+        noise
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::world::World;
+use common::entity::{EntityId, EntitySubKind};
+use common::ticks::Ticks;
+use common::velocity::Velocity;
+use glam::Vec2;
+
+/// A wreck on the seafloor, which submarines can salvage loot from.
+pub struct Shipwreck {
+    pub position: Vec2,
+    /// How many more times loot can be salvaged.
+    pub remaining: u8,
+    /// The submarine currently salvaging, and their progress towards the next loot.
+    pub salvage: Option<(EntityId, Ticks)>,
+}
+
+impl World {
+    /// Submarines must be at least this close to salvage.
+    pub const SALVAGE_RADIUS: f32 = 40.0;
+    /// Time spent salvaging each loot.
+    pub const SALVAGE_PERIOD: Ticks = Ticks::from_whole_secs(10);
+    /// Submarines faster than this aren't loitering, so can't salvage (in meters per second).
+    const SALVAGE_MAX_SPEED: f32 = 2.0;
+    /// Score for each loot.
+    const SALVAGE_SCORE: u32 = 20;
+
+    /// Makes progress salvaging shipwrecks, and awards loot.
+    pub fn update_shipwrecks(&mut self, delta: Ticks) {
+        let entities = &self.entities;
+        let max_speed = Velocity::from_mps(Self::SALVAGE_MAX_SPEED);
+
+        // Only submerged, loitering submarines can salvage (and are vulnerable while doing so).
+        let salvagers: Vec<_> = self
+            .shipwrecks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, shipwreck)| {
+                entities
+                    .iter_radius(shipwreck.position, Self::SALVAGE_RADIUS)
+                    .filter(|(_, e)| {
+                        e.is_boat()
+                            && e.data().sub_kind == EntitySubKind::Submarine
+                            && e.altitude.is_submerged()
+                            && e.transform.velocity.abs() <= max_speed
+                    })
+                    .min_by_key(|(_, e)| e.id)
+                    .map(|(index, e)| (i, index, e.id))
+            })
+            .collect();
+
+        for shipwreck in &mut self.shipwrecks {
+            if shipwreck
+                .salvage
+                .map_or(false, |(id, _)| !salvagers.iter().any(|s| s.2 == id))
+            {
+                // Salvager left, or was sunk.
+                shipwreck.salvage = None;
+            }
+        }
+
+        for (i, index, id) in salvagers {
+            let shipwreck = &mut self.shipwrecks[i];
+            let progress = match shipwreck.salvage {
+                Some((salvager, progress)) if salvager == id => progress + delta,
+                _ => delta,
+            };
+
+            if progress >= Self::SALVAGE_PERIOD {
+                self.entities[index].borrow_player_mut().score += Self::SALVAGE_SCORE;
+                shipwreck.remaining = shipwreck.remaining.saturating_sub(1);
+                shipwreck.salvage = None;
+            } else {
+                shipwreck.salvage = Some((id, progress));
+            }
+        }
+
+        // Exhausted shipwrecks, or those beyond a shrinking world, are gone for good.
+        let radius = self.radius;
+        self.shipwrecks.retain(|shipwreck| {
+            shipwreck.remaining > 0 && shipwreck.position.length_squared() < radius.powi(2)
+        });
+    }
+
+    /// Shipwrecks within `range` of `position`, along with salvage progress (0 to 1) if `salvager`
+    /// is salvaging them.
+    pub fn shipwrecks_near(
+        &self,
+        position: Vec2,
+        range: f32,
+        salvager: Option<EntityId>,
+    ) -> impl Iterator<Item = (Vec2, Option<f32>)> + '_ {
+        self.shipwrecks
+            .iter()
+            .filter(move |shipwreck| shipwreck.position.distance_squared(position) < range.powi(2))
+            .map(move |shipwreck| {
+                let progress = shipwreck
+                    .salvage
+                    .filter(|&(id, _)| Some(id) == salvager)
+                    .map(|(_, progress)| progress.to_secs() / Self::SALVAGE_PERIOD.to_secs());
+                (shipwreck.position, progress)
+            })
+    }
+}
@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entities::EntityIndex;
+use crate::player::Status;
+use crate::server::Server;
+use crate::world::World;
+use common::altitude::Altitude;
+use common::angle::Angle;
+use common::protocol::Smuggling;
+use common::terrain;
+use common::ticks::Ticks;
+use common::util::level_to_score;
+use game_server::player::PlayerTuple;
+use glam::Vec2;
+use log::info;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use rand::seq::IteratorRandom;
+use rand::{thread_rng, Rng};
+use std::sync::Arc;
+
+/// A player carrying valuable cargo to a far away port. Everyone else is told where the cargo is,
+/// and may intercept it by sinking the carrier.
+pub struct SmugglingObjective {
+    carrier: Arc<PlayerTuple<Server>>,
+    port: Vec2,
+}
+
+impl World {
+    /// Average time between smuggling objectives.
+    const SMUGGLING_MEAN_INTERVAL: Ticks = Ticks::from_whole_secs(10 * 60);
+    /// Fewer real players than this wouldn't make for much of a chase.
+    const SMUGGLING_MIN_REAL_PLAYERS: usize = 3;
+    /// How close the cargo must get to the port to be delivered.
+    const SMUGGLING_PORT_RADIUS: f32 = 150.0;
+    /// Awarded for delivering the cargo.
+    const SMUGGLING_DELIVERY_SCORE: u32 = level_to_score(6);
+    /// Awarded for sinking the carrier, who then carries the cargo.
+    const SMUGGLING_INTERCEPT_SCORE: u32 = level_to_score(4);
+
+    /// Starts, delivers, and ends smuggling objectives.
+    pub fn update_smuggling(&mut self, delta: Ticks) {
+        if let Some(objective) = &self.smuggling {
+            let position = match self.smuggling_carrier_position() {
+                Some(position) => position,
+                None => {
+                    // Carrier left the game, or sank without being intercepted.
+                    self.smuggling = None;
+                    return;
+                }
+            };
+
+            if position.distance_squared(objective.port) < Self::SMUGGLING_PORT_RADIUS.powi(2) {
+                let mut carrier = objective.carrier.borrow_player_mut();
+                carrier.score += Self::SMUGGLING_DELIVERY_SCORE;
+                info!("{:?} delivered smuggled cargo", carrier.player_id);
+                drop(carrier);
+                self.smuggling = None;
+            }
+            return;
+        }
+
+        let mut rng = thread_rng();
+        if !rng.gen_bool((delta.to_secs() / Self::SMUGGLING_MEAN_INTERVAL.to_secs()) as f64) {
+            return;
+        }
+
+        let real_boats: Vec<_> = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(_, entity)| {
+                (entity.is_boat() && !entity.borrow_player().is_bot()).then(|| {
+                    (
+                        Arc::clone(entity.player.as_ref().unwrap()),
+                        entity.transform.position,
+                    )
+                })
+            })
+            .collect();
+
+        if real_boats.len() < Self::SMUGGLING_MIN_REAL_PLAYERS {
+            return;
+        }
+
+        let (carrier, position) = real_boats.into_iter().choose(&mut rng).unwrap();
+        if let Some(port) = self.smuggling_port(position) {
+            info!("{:?} is smuggling cargo", carrier.borrow_player().player_id);
+            self.smuggling = Some(SmugglingObjective { carrier, port });
+        }
+    }
+
+    /// Call before a boat is sunk by another player. If the boat was carrying smuggled cargo, the
+    /// cargo now belongs to the `interceptor`.
+    pub fn intercept_smuggling(
+        &mut self,
+        index: EntityIndex,
+        interceptor: &Arc<PlayerTuple<Server>>,
+    ) {
+        if !self.is_smuggling_carrier(index) {
+            return;
+        }
+        let position = self.entities[index].transform.position;
+
+        interceptor.borrow_player_mut().score += Self::SMUGGLING_INTERCEPT_SCORE;
+        self.smuggling = self
+            .smuggling_port(position)
+            .map(|port| SmugglingObjective {
+                carrier: Arc::clone(interceptor),
+                port,
+            });
+    }
+
+    /// Returns true iff the boat at `index` is carrying smuggled cargo.
+    pub fn is_smuggling_carrier(&self, index: EntityIndex) -> bool {
+        self.smuggling.as_ref().map_or(false, |objective| {
+            self.entities[index]
+                .player
+                .as_ref()
+                .map_or(false, |player| Arc::ptr_eq(player, &objective.carrier))
+        })
+    }
+
+    /// Gets the current smuggling objective, for clients.
+    pub fn smuggling(&self) -> Option<Smuggling> {
+        let objective = self.smuggling.as_ref()?;
+        Some(Smuggling {
+            carrier: objective.carrier.borrow_player().player_id,
+            cargo: self.smuggling_carrier_position()?,
+            port: objective.port,
+        })
+    }
+
+    fn smuggling_carrier_position(&self) -> Option<Vec2> {
+        let objective = self.smuggling.as_ref()?;
+        match objective.carrier.borrow_player().data.status {
+            Status::Alive { entity_index, .. } => {
+                Some(self.entities[entity_index].transform.position)
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks a port, on the water, roughly across the world from `from`.
+    fn smuggling_port(&self, from: Vec2) -> Option<Vec2> {
+        let mut rng = thread_rng();
+        let away = Angle::from(-from);
+        (0..16).find_map(|_| {
+            let angle = away + Angle::from_radians(rng.gen_range(-0.6..0.6));
+            let port = angle.to_vec() * self.radius * rng.gen_range(0.5..0.8);
+            let far = port.distance_squared(from) > (self.radius * 0.8).powi(2);
+            let water = self.terrain.sample(port).unwrap_or(Altitude::MAX) < terrain::SAND_LEVEL;
+            (far && water).then(|| port)
+        })
+    }
+}
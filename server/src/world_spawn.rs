@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::entity::{unset_entity_id, Entity};
+use crate::server::Server;
 use crate::world::World;
+use crate::world_shipwreck::Shipwreck;
 use common::altitude::Altitude;
 use common::angle::Angle;
 use common::entity::*;
@@ -12,10 +14,12 @@ use common::transform::Transform;
 use common::velocity::Velocity;
 use common::world::distance_to_soft_area_border;
 use common_util::range::gen_radius;
+use game_server::player::PlayerData;
 use glam::Vec2;
 use log::{info, warn};
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
 use rand::{thread_rng, Rng};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 impl World {
     /// Target square meters of world per square meter of player vision.
@@ -24,6 +28,128 @@ impl World {
     const CRATE_DENSITY: f32 = 1.0 / 30000.0;
     /// Target density of obstacles (per square meter).
     const OBSTACLE_DENSITY: f32 = 1.0 / 1000000.0;
+    /// Target density of shipwrecks (per square meter).
+    const SHIPWRECK_DENSITY: f32 = 1.0 / 5000000.0;
+    /// How many times each shipwreck can be salvaged.
+    const SHIPWRECK_LOOT: u8 = 5;
+    /// Players don't spawn right next to a friend that has enemy boats within this radius, as
+    /// they would be spawning into the middle of a fight.
+    const FRIEND_SAFE_RADIUS: f32 = 500.0;
+    /// How many valid spawn points to compare when spawning a player's boat.
+    const SPAWN_CANDIDATES: u32 = 8;
+    /// Hostile entities and recent deaths within this radius make a spawn point less safe.
+    const SPAWN_DANGER_RADIUS: f32 = 1000.0;
+    /// How long a boat sinking makes the surrounding area less safe to spawn in.
+    const RECENT_DEATH_DURATION: Duration = Duration::from_secs(60);
+
+    /// Remembers that a boat was sunk by another player at `position`.
+    pub fn record_recent_death(&mut self, position: Vec2) {
+        self.recent_deaths
+            .retain(|(_, time)| time.elapsed() < Self::RECENT_DEATH_DURATION);
+        self.recent_deaths.push((position, Instant::now()));
+    }
+
+    /// Returns how dangerous it would be for `entity` (a boat) to spawn where it is, based on
+    /// nearby hostile weapons and boats, recent deaths, and terrain. Lower is safer.
+    fn spawn_danger(&self, entity: &Entity) -> f32 {
+        let position = entity.transform.position;
+        let closeness = |other: Vec2| {
+            (1.0 - position.distance(other) * (1.0 / Self::SPAWN_DANGER_RADIUS)).max(0.0)
+        };
+
+        let mut danger = 0.0;
+        for (_, other) in self
+            .entities
+            .iter_radius(position, Self::SPAWN_DANGER_RADIUS)
+        {
+            if entity.is_friendly(other) {
+                continue;
+            }
+            let other_data = other.data();
+            let weight = match other_data.kind {
+                EntityKind::Aircraft | EntityKind::Weapon => 1.0,
+                // Higher level boats are more dangerous.
+                EntityKind::Boat => 2.0 + other_data.level as f32,
+                _ => continue,
+            };
+            danger += weight * closeness(other.transform.position);
+        }
+
+        for &(death, time) in &self.recent_deaths {
+            if time.elapsed() < Self::RECENT_DEATH_DURATION {
+                danger += 3.0 * closeness(death);
+            }
+        }
+
+        // Land nearby makes it harder to maneuver away from danger.
+        if self
+            .terrain
+            .land_in_square(position, entity.data().radius * 4.0 + 200.0)
+        {
+            danger += 1.0;
+        }
+
+        danger
+    }
+
+    /// Finds a friend (preferably the player's inviter, otherwise any teammate) to spawn near,
+    /// returning the position and radius to spawn within. Friends near the exclusion zone are
+    /// ignored.
+    pub fn spawn_near_friend(
+        &self,
+        player: &PlayerData<Server>,
+        exclusion_zone: Option<Vec2>,
+    ) -> Option<(Vec2, f32)> {
+        let team_id = player.team_id();
+        let inviter_id = player.invitation_accepted().map(|i| i.player_id);
+        if team_id.is_none() && inviter_id.is_none() {
+            return None;
+        }
+
+        // TODO: Inefficient to scan all entities; only need to scan all players. Unfortunately,
+        // that data is not available here, currently.
+        let (_, friend) = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter(|(_, entity)| {
+                if !entity.is_boat() {
+                    return false;
+                }
+
+                if let Some(exclusion_zone) = exclusion_zone {
+                    if entity.transform.position.distance_squared(exclusion_zone) < 1100f32.powi(2)
+                    {
+                        return false;
+                    }
+                }
+
+                let friend = entity.borrow_player();
+                (team_id.is_some() && friend.team_id() == team_id)
+                    || Some(friend.player_id) == inviter_id
+            })
+            .min_by_key(|(_, entity)| Some(entity.borrow_player().player_id) != inviter_id)?;
+
+        let position = friend.transform.position;
+        let friend_team_id = friend.borrow_player().team_id();
+        let in_combat = self
+            .entities
+            .iter_radius(position, Self::FRIEND_SAFE_RADIUS)
+            .any(|(_, entity)| {
+                if !entity.is_boat() || entity.id == friend.id {
+                    return false;
+                }
+                let other = entity.borrow_player();
+                other.team_id().is_none() || other.team_id() != friend_team_id
+            });
+
+        let radius = if in_combat {
+            Self::FRIEND_SAFE_RADIUS
+        } else {
+            friend.data().radius + 25.0
+        };
+        Some((position, radius))
+    }
 
     /// spawn_here_or_nearby spawns an entity, adjusting it's position and/or rotation until
     /// it can spawn without colliding with world objects.
@@ -48,37 +174,60 @@ impl World {
             let mut rng = rand::thread_rng();
             let mut radius = initial_radius.max(1.0);
             let center = entity.transform.position;
-            let (max_attempts, mut threshold): (u32, f32) = if entity.is_boat() {
+            let (max_attempts, mut threshold, candidates): (u32, f32, u32) = if entity.is_boat() {
                 if entity.borrow_player().player_id.is_bot() {
-                    (128, 4.0)
+                    (128, 4.0, 1)
                 } else {
-                    (1024, 6.0)
+                    (1024, 6.0, Self::SPAWN_CANDIDATES)
                 }
             } else {
-                (8, 4.0)
+                (8, 4.0, 1)
             };
 
             let mut governor = max_attempts;
+            let mut remaining_candidates = candidates;
+            let mut best: Option<(f32, Transform)> = None;
             let max_distance_from_center =
                 (self.radius * 0.9 - entity.data().radius * 1.5).max(self.radius * 0.5);
 
-            // Always randomize on first iteration
-            while entity.transform.position == center
-                || exclusion_zone
-                    .map(|ez| {
-                        entity.transform.position.distance_squared(ez)
-                            < (threshold.min(3.0) * 500.0).powi(2)
-                    })
-                    .unwrap_or(false)
-                || !self.can_spawn(&entity, threshold, max_distance_from_center)
-            {
+            loop {
+                // Always randomize on first iteration
+                let valid = entity.transform.position != center
+                    && !exclusion_zone
+                        .map(|ez| {
+                            entity.transform.position.distance_squared(ez)
+                                < (threshold.min(3.0) * 500.0).powi(2)
+                        })
+                        .unwrap_or(false)
+                    && self.can_spawn(&entity, threshold, max_distance_from_center);
+
+                if valid {
+                    // Only compare candidates if there is more than one.
+                    let danger = if candidates > 1 {
+                        self.spawn_danger(&entity)
+                    } else {
+                        0.0
+                    };
+                    if best.map_or(true, |(best_danger, _)| danger < best_danger) {
+                        best = Some((danger, entity.transform));
+                    }
+
+                    remaining_candidates -= 1;
+                    if remaining_candidates == 0 {
+                        break;
+                    }
+                }
+
                 // Pick a new position
                 let position = gen_radius(&mut rng, radius);
                 entity.transform.position = center + position;
                 entity.transform.direction = rng.gen();
 
-                radius = (radius * 1.05).min(max_distance_from_center);
-                threshold = 0.005 + threshold * 0.995; // Approaches 1.0
+                // Keep comparing candidates that are held to the same standard.
+                if best.is_none() {
+                    radius = (radius * 1.05).min(max_distance_from_center);
+                    threshold = 0.005 + threshold * 0.995; // Approaches 1.0
+                }
 
                 debug_assert!(threshold >= 1.0, "so try_spawn works");
 
@@ -90,9 +239,13 @@ impl World {
                 }
             }
 
+            if let Some((_, transform)) = best {
+                entity.transform = transform;
+            }
+
             // Ensure determinism and allowing spawn with threshold 1.0.
             #[cfg(debug_assertions)]
-            if governor > 0 {
+            if best.is_some() {
                 for i in 0..3 {
                     debug_assert!(
                         self.can_spawn(&entity, threshold, max_distance_from_center),
@@ -282,6 +435,33 @@ impl World {
             self.target_count(Self::OBSTACLE_DENSITY),
             ticks.0 as usize * 2,
         );
+
+        // Shipwrecks aren't entities, since they persist until salvaged.
+        let shipwrecks = self
+            .target_count(Self::SHIPWRECK_DENSITY)
+            .saturating_sub(self.shipwrecks.len())
+            .min(ticks.0 as usize);
+        for _ in 0..shipwrecks {
+            self.spawn_shipwreck();
+        }
+
+        self.spawn_region_structures();
+    }
+
+    /// Tries to spawn a shipwreck on deep seafloor.
+    fn spawn_shipwreck(&mut self) {
+        let position = gen_radius(&mut thread_rng(), self.radius * 0.9);
+        let deep = self.terrain.sample(position).map_or(false, |altitude| {
+            altitude < Altitude::from_whole_meters(-40)
+        });
+
+        if deep && position.y < common::world::ARCTIC {
+            self.shipwrecks.push(Shipwreck {
+                position,
+                remaining: Self::SHIPWRECK_LOOT,
+                salvage: None,
+            });
+        }
     }
 
     /// Spawns a certain amount of basic entities, all throughout the world.
@@ -337,6 +517,8 @@ impl World {
             },
             entity_type,
             ticks,
+            unsimulated: Ticks::ZERO,
+            unsimulated_seconds: 0.0,
             id: unset_entity_id(),
             altitude: Altitude::ZERO,
         });
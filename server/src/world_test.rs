@@ -104,6 +104,7 @@ mod tests {
     use rand::{thread_rng, Rng};
     use server_util::generate_id::generate_id;
     use std::sync::Arc;
+    use test::Bencher;
 
     #[test]
     fn test_render() {
@@ -116,13 +117,48 @@ mod tests {
     }
 
     fn test_render_with(player_count: usize, resolution: u32) {
+        let (world, world_radius) = populated_world(player_count);
+
+        let image = world.test_render(Vec2::ZERO, world_radius, resolution);
+        image
+            .save(format!("test_render_{}.png", player_count))
+            .unwrap();
+    }
+
+    /// Times [`World::update`], including the collision broad phase, with `player_count` players.
+    fn bench_update(b: &mut Bencher, player_count: usize) {
+        let (mut world, _) = populated_world(player_count);
+        b.iter(|| world.update(Ticks::ONE, Ticks::ONE.to_secs()));
+    }
+
+    #[bench]
+    fn bench_update_100(b: &mut Bencher) {
+        bench_update(b, 100);
+    }
+
+    #[bench]
+    fn bench_update_250(b: &mut Bencher) {
+        bench_update(b, 250);
+    }
+
+    #[bench]
+    fn bench_update_500(b: &mut Bencher) {
+        bench_update(b, 500);
+    }
+
+    #[bench]
+    fn bench_update_1000(b: &mut Bencher) {
+        bench_update(b, 1000);
+    }
+
+    /// Creates a world with statics and `player_count` randomly leveled boats, returning it along
+    /// with its radius.
+    fn populated_world(player_count: usize) -> (World, f32) {
         crate::noise::init();
 
         let world_radius =
             World::target_radius(player_count as f32 * 1500f32.powi(2) * std::f32::consts::PI);
 
-        println!("rad: {}", world_radius);
-
         let mut world = World::new(world_radius);
         let mut rng = thread_rng();
 
@@ -153,7 +189,10 @@ mod tests {
                 .filter(|t| t.can_spawn_as(score, bot) && t.data().level == level)
                 .choose(&mut rng)
                 .unwrap();
-            let spawn = Command::Spawn(Spawn { entity_type });
+            let spawn = Command::Spawn(Spawn {
+                entity_type,
+                loadout: 0,
+            });
             const SPAWN_ATTEMPTS: usize = 25;
             for i in 0..=SPAWN_ATTEMPTS {
                 match spawn.as_command().apply(&mut world, player) {
@@ -170,9 +209,6 @@ mod tests {
             }
         }
 
-        let image = world.test_render(Vec2::ZERO, world_radius, resolution);
-        image
-            .save(format!("test_render_{}.png", player_count))
-            .unwrap();
+        (world, world_radius)
     }
 }
@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::world::World;
+use common::altitude::Altitude;
+use glam::Vec2;
+use image::{Rgb, RgbImage};
+
+impl World {
+    /// Width and height of thumbnails, in pixels.
+    const THUMBNAIL_SIZE: u32 = 64;
+    const THUMBNAIL_BORDER: Rgb<u8> = Rgb([0, 25, 60]);
+    const THUMBNAIL_WATER: Rgb<u8> = Rgb([0, 75, 130]);
+    const THUMBNAIL_LAND: Rgb<u8> = Rgb([194, 178, 128]);
+
+    /// Renders a tiny map of land and water within the world border, for server browsers.
+    pub fn thumbnail(&self) -> RgbImage {
+        let size = Self::THUMBNAIL_SIZE;
+        let meters_per_pixel = self.radius * 2.0 / size as f32;
+
+        RgbImage::from_fn(size, size, |x, y| {
+            // Flip y, since images are top down but the world is bottom up.
+            let position =
+                (Vec2::new(x as f32, (size - 1 - y) as f32) + 0.5) * meters_per_pixel - self.radius;
+
+            if position.length_squared() > self.radius.powi(2) {
+                Self::THUMBNAIL_BORDER
+            } else if self
+                .terrain
+                .sample(position)
+                .map_or(false, |altitude| altitude >= Altitude::ZERO)
+            {
+                Self::THUMBNAIL_LAND
+            } else {
+                Self::THUMBNAIL_WATER
+            }
+        })
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::entities::EntityIndex;
+use crate::entity::Entity;
+use crate::world::World;
+use common::altitude::Altitude;
+use common::entity::{EntitySubKind, EntityType};
+use common::ticks::Ticks;
+use common_util::range::map_ranges;
+use rand::{thread_rng, Rng};
+
+impl World {
+    /// Boats at least this long leave wreckage when they sink (in meters).
+    const WRECKAGE_MIN_LENGTH: f32 = 100.0;
+    /// Pieces of debris per meter of sunken boat.
+    const DEBRIS_PER_METER: f32 = 1.0 / 50.0;
+    /// Fraction of speed that boats lose per second passing through debris.
+    pub const DEBRIS_DRAG: f32 = 0.6;
+    /// Fraction of its lifespan after which wreckage starts to sink out of sight.
+    const WRECKAGE_SINK_START: f32 = 0.7;
+
+    /// Spawns a temporary wreck, surrounded by floating debris, where a large boat sank. Call
+    /// before the boat is removed.
+    pub fn spawn_wreckage(&mut self, index: EntityIndex) {
+        let boat = &self.entities[index];
+        let data = boat.data();
+        if data.length < Self::WRECKAGE_MIN_LENGTH
+            || data.sub_kind == EntitySubKind::Kraken
+            || boat.altitude.is_submerged()
+        {
+            return;
+        }
+
+        let transform = boat.transform;
+        let normal = transform.direction.to_vec();
+        let tangent = normal.perp();
+        let mut rng = thread_rng();
+
+        let mut wreck = Entity::new(EntityType::Wreck, None);
+        wreck.transform.position = transform.position;
+        wreck.transform.direction = transform.direction;
+        self.add(wreck);
+
+        let debris_lifespan = EntityType::Debris.data().lifespan;
+        for _ in 0..(data.length * Self::DEBRIS_PER_METER) as usize {
+            let mut debris = Entity::new(EntityType::Debris, None);
+            debris.transform.position = transform.position
+                + normal * ((rng.gen::<f32>() - 0.5) * data.length)
+                + tangent * ((rng.gen::<f32>() - 0.5) * data.width * 3.0);
+            debris.transform.direction = rng.gen();
+            // Randomize lifespan a bit so debris doesn't all disappear at the same time.
+            debris.ticks += debris_lifespan * (rng.gen::<f32>() * 0.25);
+            self.add(debris);
+        }
+    }
+
+    /// Wreckage floats at first, then sinks out of sight (which fades it out on the client) over
+    /// the end of its lifespan. Once submerged, it no longer gets in the way of surface boats.
+    pub fn wreckage_altitude(ticks: Ticks, lifespan: Ticks) -> Altitude {
+        let sunk = map_ranges(
+            ticks.to_secs() / lifespan.to_secs(),
+            Self::WRECKAGE_SINK_START..1.0,
+            0.0..1.0,
+            true,
+        );
+        Altitude::from_norm(-sunk)
+    }
+}
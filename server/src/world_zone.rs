@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: 2021 Softbear, Inc.
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::world::World;
+use common::entity::{EntityId, EntityType};
+use common::protocol::{Zone, ZoneHolder};
+use common::ticks::Ticks;
+use glam::Vec2;
+use log::info;
+use maybe_parallel_iterator::IntoMaybeParallelIterator;
+use rand::seq::IteratorRandom;
+use rand::thread_rng;
+
+/// The waters around a structure, which award points over time to whoever holds them.
+pub struct CaptureZone {
+    /// The structure at the center of the zone.
+    structure: EntityId,
+    position: Vec2,
+    holder: Option<ZoneHolder>,
+    /// Who is capturing the zone, and their progress towards [`World::ZONE_CAPTURE_TIME`].
+    capture: Option<(ZoneHolder, Ticks)>,
+    contested: bool,
+    /// Time until the holder is next awarded points.
+    award_timer: Ticks,
+}
+
+impl World {
+    /// Maximum number of capturable zones at once.
+    const ZONE_COUNT: usize = 3;
+    /// Boats must be at least this close to the structure to capture or hold the zone.
+    pub const ZONE_RADIUS: f32 = 250.0;
+    /// Zones are at least this far apart, so holding one doesn't mean holding all.
+    const ZONE_MIN_SPACING: f32 = 1500.0;
+    /// Time an uncontested holder must spend to capture a zone.
+    const ZONE_CAPTURE_TIME: Ticks = Ticks::from_whole_secs(20);
+    /// Time between awards to boats holding a zone.
+    const ZONE_AWARD_PERIOD: Ticks = Ticks::from_whole_secs(5);
+    /// Score for each boat holding a zone, per award.
+    const ZONE_AWARD_SCORE: u32 = 2;
+    /// Structures eligible to become zones.
+    const ZONE_STRUCTURES: [EntityType; 2] = [EntityType::Hq, EntityType::OilPlatform];
+
+    /// Picks new zones, makes progress capturing them, and awards points to holders.
+    pub fn update_zones(&mut self, delta: Ticks) {
+        let entities = &self.entities;
+
+        // Structures may be destroyed, or recycled when the world shrinks.
+        self.zones.retain(|zone| {
+            entities
+                .iter_radius(zone.position, 1.0)
+                .any(|(_, e)| e.id == zone.structure)
+        });
+
+        if self.zones.len() < Self::ZONE_COUNT {
+            self.spawn_zone();
+        }
+
+        for zone in &mut self.zones {
+            let mut occupants = Vec::new();
+            let mut holder_boats = Vec::new();
+            for (index, entity) in self
+                .entities
+                .iter_radius(zone.position, Self::ZONE_RADIUS)
+                .filter(|(_, e)| e.is_boat())
+            {
+                let player = entity.borrow_player();
+                let holder = ZoneHolder::new(player.player_id, player.team_id());
+                if !occupants.contains(&holder) {
+                    occupants.push(holder);
+                }
+                if Some(holder) == zone.holder {
+                    holder_boats.push(index);
+                }
+            }
+
+            zone.contested = occupants.len() > 1;
+            match occupants.as_slice() {
+                [] => {
+                    // Progress decays when nobody is capturing.
+                    zone.capture = zone.capture.and_then(|(capturer, progress)| {
+                        let progress = progress.saturating_sub(delta);
+                        (progress != Ticks::ZERO).then(|| (capturer, progress))
+                    });
+                }
+                &[occupant] if Some(occupant) != zone.holder => {
+                    let progress = match zone.capture {
+                        Some((capturer, progress)) if capturer == occupant => progress + delta,
+                        _ => delta,
+                    };
+                    if progress >= Self::ZONE_CAPTURE_TIME {
+                        info!("{:?} captured zone at {:?}", occupant, zone.position);
+                        zone.holder = Some(occupant);
+                        zone.capture = None;
+                        zone.award_timer = Self::ZONE_AWARD_PERIOD;
+                    } else {
+                        zone.capture = Some((occupant, progress));
+                    }
+                }
+                _ => {}
+            }
+
+            // Holders must remain in the zone (but may be contested) to earn points.
+            if zone.holder.is_none() || holder_boats.is_empty() {
+                continue;
+            }
+            zone.award_timer = zone.award_timer.saturating_sub(delta);
+            if zone.award_timer == Ticks::ZERO {
+                zone.award_timer = Self::ZONE_AWARD_PERIOD;
+                for index in holder_boats {
+                    self.entities[index].borrow_player_mut().score += Self::ZONE_AWARD_SCORE;
+                }
+            }
+        }
+    }
+
+    /// Turns a random eligible structure into a zone, if possible.
+    fn spawn_zone(&mut self) {
+        let max_distance = self.radius * 0.8;
+        let zones = &self.zones;
+        let candidate = self
+            .entities
+            .par_iter()
+            .into_maybe_parallel_iter()
+            .filter_map(|(_, entity)| {
+                let position = entity.transform.position;
+                (Self::ZONE_STRUCTURES.contains(&entity.entity_type)
+                    && position.length_squared() < max_distance.powi(2)
+                    && zones.iter().all(|zone| {
+                        zone.position.distance_squared(position) > Self::ZONE_MIN_SPACING.powi(2)
+                    }))
+                .then(|| (entity.id, position))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .choose(&mut thread_rng());
+
+        if let Some((structure, position)) = candidate {
+            self.zones.push(CaptureZone {
+                structure,
+                position,
+                holder: None,
+                capture: None,
+                contested: false,
+                award_timer: Self::ZONE_AWARD_PERIOD,
+            });
+        }
+    }
+
+    /// Gets the current zones, for clients.
+    pub fn zones(&self) -> Box<[Zone]> {
+        self.zones
+            .iter()
+            .map(|zone| Zone {
+                position: zone.position,
+                radius: Self::ZONE_RADIUS,
+                holder: zone.holder,
+                capture: zone.capture.map(|(capturer, progress)| {
+                    (
+                        capturer,
+                        progress.to_secs() / Self::ZONE_CAPTURE_TIME.to_secs(),
+                    )
+                }),
+                contested: zone.contested,
+            })
+            .collect()
+    }
+}
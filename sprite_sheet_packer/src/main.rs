@@ -88,41 +88,40 @@ fn main() {
     let optimize = true;
     pack_sprite_sheet(
         EntityType::iter()
+            // Wreckage is drawn with graphics instead of sprites.
+            .filter(|entity_type| {
+                !matches!(
+                    entity_type.data().sub_kind,
+                    EntitySubKind::Debris | EntitySubKind::Wreck
+                )
+            })
             .map(|entity_type| {
                 let data: &EntityData = entity_type.data();
                 let width = match data.kind {
+                    // The EntityTypeData derive guarantees that these are used by at least one
+                    // boat or aircraft, so the scale is never zero.
                     EntityKind::Weapon | EntityKind::Aircraft | EntityKind::Decoy => {
                         let mut scale: f32 = 0.0;
                         for owner_type in EntityType::iter() {
                             let owner: &EntityData = owner_type.data();
-                            if !matches!(owner.kind, EntityKind::Boat | EntityKind::Aircraft) {
-                                // Turrets/rocket torpedoes don't render their armaments so they
-                                // don't influence the resolution of the armament.
-                                if owner.kind == EntityKind::Turret
-                                    || owner.sub_kind == EntitySubKind::RocketTorpedo
-                                {
-                                    continue;
-                                }
-                                assert!(owner.armaments.is_empty(), "{owner_type:?} has armaments");
+                            // Turrets/rocket torpedoes don't render their armaments so they
+                            // don't influence the resolution of the armament.
+                            if owner.kind == EntityKind::Turret
+                                || owner.sub_kind == EntitySubKind::RocketTorpedo
+                            {
+                                continue;
                             }
 
                             if owner.armaments.iter().any(|a| a.entity_type == entity_type) {
                                 scale = scale.max(armament_pixels(data, owner));
                             }
                         }
-                        if scale == 0.0 {
-                            panic!("{:?} is not used", entity_type);
-                        }
                         scale
                     }
                     EntityKind::Turret => {
                         let mut scale: f32 = 0.0;
                         for owner_type in EntityType::iter() {
                             let owner: &EntityData = owner_type.data();
-                            if owner.kind != EntityKind::Boat {
-                                assert!(owner.turrets.is_empty(), "{owner_type:?} has turrets");
-                            }
-
                             if owner
                                 .turrets
                                 .iter()
@@ -131,9 +130,6 @@ fn main() {
                                 scale = scale.max(armament_pixels(data, owner));
                             }
                         }
-                        if scale == 0.0 {
-                            panic!("{:?} is not used", entity_type);
-                        }
                         scale
                     }
                     EntityKind::Obstacle => boat_pixels(data) * 0.85,